@@ -9,7 +9,7 @@ use database_utils::{DatabaseConnection, DatabaseStatement, DatabaseURL, Queryab
 use rand::distributions::{Distribution, Uniform};
 use rand::prelude::*;
 use readyset_client::consensus::AuthorityType;
-use readyset_client::{KeyComparison, ReadySetHandle, View, ViewQuery};
+use readyset_client::{shard_by, KeyComparison, ReadySetHandle, View, ViewQuery};
 use readyset_data::DfValue;
 use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
 use vec1::Vec1;
@@ -60,6 +60,12 @@ struct NoriaClientOpts {
     /// for some database types.
     #[arg(long, default_value = "10")]
     batch_size: u64,
+
+    /// The number of connections to open to the view for each thread. Lookups are assigned to a
+    /// connection by the shard of their key, so that all lookups for a given shard of a sharded
+    /// view use the same connection.
+    #[arg(long, default_value = "1")]
+    read_connections: usize,
 }
 
 #[derive(Parser, Clone)]
@@ -136,6 +142,10 @@ struct Reader {
     /// database client.
     #[command(flatten)]
     upstream_opts: UpstreamOpts,
+
+    /// Print the number of requests made over each connection once the experiment finishes.
+    #[arg(long, short)]
+    verbose: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -225,6 +235,15 @@ impl QueryExecutor {
             QueryExecutor::Upstream(e) => e.on_query(q).await,
         }
     }
+
+    /// The number of requests made over each of the executor's connections, if it has more than
+    /// one
+    fn connection_request_counts(&self) -> Option<&[u64]> {
+        match self {
+            QueryExecutor::Noria(e) => Some(&e.requests),
+            QueryExecutor::Upstream(_) => None,
+        }
+    }
 }
 
 struct QueryBatcher {
@@ -267,10 +286,14 @@ impl QueryBatcher {
     }
 }
 
-/// Executes queries directly to Noria through the `View` API.
+/// Executes queries directly to Noria through the `View` API, over a pool of connections to the
+/// view.
 struct NoriaExecutor {
-    view: View,
-    query_batcher: QueryBatcher,
+    views: Vec<View>,
+    /// A batcher for each connection in [`Self::views`]
+    query_batchers: Vec<QueryBatcher>,
+    /// The number of lookup requests made over each connection in [`Self::views`]
+    requests: Vec<u64>,
 }
 
 impl NoriaExecutor {
@@ -281,14 +304,73 @@ impl NoriaExecutor {
         let mut handle: ReadySetHandle = ReadySetHandle::new(authority).await;
         handle.ready().await.unwrap();
 
-        let view = handle.view("w").await.unwrap();
-        Self {
-            view,
-            query_batcher: QueryBatcher::new(
-                Duration::from_millis(opts.batch_duration_ms),
-                opts.batch_size,
-            ),
-        }
+        Self::with_handle(
+            handle,
+            "w",
+            opts.read_connections,
+            Duration::from_millis(opts.batch_duration_ms),
+            opts.batch_size,
+        )
+        .await
+        .unwrap()
+    }
+
+    /// Open `connections` independent connections to the view with the given name, resolving once
+    /// all of them are established
+    async fn with_handle(
+        handle: ReadySetHandle,
+        view_name: &str,
+        connections: usize,
+        batch_duration: Duration,
+        batch_size: u64,
+    ) -> Result<Self> {
+        let connections = connections.max(1);
+        let views = futures::future::try_join_all((0..connections).map(|_| {
+            let mut handle = handle.clone();
+            async move { handle.view(view_name).await }
+        }))
+        .await?;
+
+        Ok(Self {
+            views,
+            query_batchers: (0..connections)
+                .map(|_| QueryBatcher::new(batch_duration, batch_size))
+                .collect(),
+            requests: vec![0; connections],
+        })
+    }
+
+    /// Returns the index of the connection to use for lookups of the given key, which is
+    /// consistent for all keys in the same shard
+    fn connection_for(&self, key: u32) -> usize {
+        shard_by(&DfValue::from(key), self.views.len())
+    }
+
+    /// Look up the keys for the given batch of queries over the given connection, returning the
+    /// resulting rows
+    async fn lookup(
+        &mut self,
+        connection: usize,
+        batch: &[BatchedQuery],
+    ) -> Result<Vec<Vec<DfValue>>> {
+        let keys: Vec<_> = batch
+            .iter()
+            .map(|k| KeyComparison::Equal(Vec1::new(DfValue::Int(k.key[0] as _))))
+            .collect();
+
+        let vq = ViewQuery::from((keys, true));
+
+        self.requests[connection] += 1;
+        let r = self.views[connection]
+            .as_mut_reader_handle()
+            .unwrap()
+            .raw_lookup(vq)
+            .await?
+            .into_vec();
+        assert_eq!(r.len(), batch.len());
+        assert!(r.iter().all(|rset| !rset.is_empty()));
+
+        Ok(r)
     }
 
     async fn on_query(&mut self, q: BatchedQuery) -> Result<Vec<BatchedQuery>> {
@@ -297,27 +379,15 @@ impl NoriaExecutor {
         // together. This is done based on time: package all queries every
         // `batch_interval`, or it is done based on number of queries: only
         // batch queries into batches up to size `self.batch_size`.
-        self.query_batcher.add_query(q);
-        if let Some(batch) = self.query_batcher.get_batch_if_ready() {
+        //
+        // Queries are batched separately for each connection, so that each batch only contains
+        // keys for the shards assigned to its connection.
+        let connection = self.connection_for(q.key[0]);
+        self.query_batchers[connection].add_query(q);
+        if let Some(batch) = self.query_batchers[connection].get_batch_if_ready() {
             // It is batch time, execute the batched query and calculate the time
             // for each query from the query start times.
-            let keys: Vec<_> = batch
-                .iter()
-                .map(|k| KeyComparison::Equal(Vec1::new(DfValue::Int(k.key[0] as _))))
-                .collect();
-
-            let vq = ViewQuery::from((keys, true));
-
-            let r = self
-                .view
-                .as_mut_reader_handle()
-                .unwrap()
-                .raw_lookup(vq)
-                .await?
-                .into_vec();
-            assert_eq!(r.len(), batch.len());
-            assert!(r.iter().all(|rset| !rset.is_empty()));
-
+            self.lookup(connection, &batch).await?;
             return Ok(batch);
         }
 
@@ -395,6 +465,13 @@ impl Reader {
                     // We drop the senders so that the receiver
                     // eventually stops.
                     drop(sender);
+                    if self.verbose {
+                        if let Some(counts) = executor.connection_request_counts() {
+                            for (connection, count) in counts.iter().enumerate() {
+                                println!("connection {connection}: {count} requests");
+                            }
+                        }
+                    }
                     break;
                 }
             }
@@ -551,3 +628,67 @@ async fn main() -> anyhow::Result<()> {
     let reader: &'static mut _ = Box::leak(Box::new(Reader::parse()));
     reader.run().await
 }
+
+#[cfg(test)]
+mod tests {
+    use readyset_client::recipe::changelist::ChangeList;
+    use readyset_data::Dialect;
+
+    use super::*;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn pooled_reads_use_every_connection() {
+        let mut builder = readyset_server::Builder::for_tests();
+        builder.set_sharding(Some(2));
+        let (mut noria, shutdown_tx) = builder.start_local().await.unwrap();
+        noria.backend_ready().await;
+        noria
+            .extend_recipe(
+                ChangeList::from_str(
+                    "CREATE TABLE t (id INT, v INT, PRIMARY KEY (id));
+                     CREATE CACHE w FROM SELECT id, v FROM t WHERE id = ?;",
+                    Dialect::DEFAULT_MYSQL,
+                )
+                .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        let keys = 0..10u32;
+        let mut table = noria.table("t").await.unwrap();
+        table
+            .insert_many(
+                keys.clone()
+                    .map(|k| vec![DfValue::from(i64::from(k)), DfValue::from(i64::from(k * 2))]),
+            )
+            .await
+            .unwrap();
+        // Let writes propagate
+        tokio::time::sleep(Duration::from_secs(1)).await;
+
+        let mut executor =
+            NoriaExecutor::with_handle(noria.c.clone().unwrap(), "w", 2, Duration::ZERO, 1)
+                .await
+                .unwrap();
+        for key in keys {
+            let query = BatchedQuery {
+                key: vec![key],
+                issued: Instant::now(),
+            };
+            let connection = executor.connection_for(key);
+            assert_eq!(
+                executor.lookup(connection, &[query.clone()]).await.unwrap(),
+                vec![vec![
+                    DfValue::from(i64::from(key)),
+                    DfValue::from(i64::from(key * 2)),
+                ]]
+            );
+            assert_eq!(executor.on_query(query).await.unwrap().len(), 1);
+        }
+
+        assert_eq!(executor.requests.len(), 2);
+        assert!(executor.requests.iter().all(|count| *count > 0));
+
+        shutdown_tx.shutdown().await;
+    }
+}
@@ -77,6 +77,16 @@ struct Writer {
     /// If `None` is provided, the experiment will run until it is interrupted.
     #[arg(long)]
     run_for: Option<u32>,
+
+    /// Trace a sample of the writes as they propagate through the dataflow graph, and report the
+    /// percentiles of the time taken for them to reach each reader alongside the end-to-end
+    /// latencies.
+    #[arg(long)]
+    trace_propagation: bool,
+
+    /// The fraction of writes to trace, between 0 and 1, if `--trace-propagation` is set.
+    #[arg(long, default_value = "0.1", requires = "trace_propagation")]
+    trace_sample_rate: f64,
 }
 
 #[derive(Clone)]
@@ -228,6 +238,7 @@ impl Writer {
     async fn process_updates(
         &'static self,
         receiver: Receiver<WriterThreadUpdate>,
+        mut tracing_handle: Option<ReadySetHandle>,
     ) -> anyhow::Result<()> {
         // Process updates from readers, calculate statistics to report. We
         // store each channel's message in a hashmap for each interval. If
@@ -241,6 +252,9 @@ impl Writer {
             let now = Instant::now();
             if now > next_check {
                 self.process_thread_updates(&updates).await.unwrap();
+                if let Some(ch) = &mut tracing_handle {
+                    self.report_propagation_latencies(ch).await.unwrap();
+                }
                 updates.clear();
                 next_check = now + REPORTING_INTERVAL;
             }
@@ -270,6 +284,25 @@ impl Writer {
         Ok(())
     }
 
+    async fn report_propagation_latencies(&self, ch: &mut ReadySetHandle) -> anyhow::Result<()> {
+        let stats = ch.statistics().await?;
+        for (replica, node, desc, latency) in stats.propagation_latencies() {
+            println!(
+                "reader {} ({}) in {}: traced_writes: {}\t\
+                 p50: {:?}\tp90: {:?}\tp99: {:?}\tmax: {:?}",
+                node.index(),
+                desc,
+                replica,
+                latency.samples,
+                latency.p50,
+                latency.p90,
+                latency.p99,
+                latency.max
+            );
+        }
+        Ok(())
+    }
+
     pub async fn run(&'static self) -> anyhow::Result<()> {
         let path = self.schema.clone().unwrap_or_else(|| {
             PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap() + "/news_app_db.sql")
@@ -281,6 +314,18 @@ impl Writer {
         let current_articles = Arc::new(AtomicUsize::new(self.article_table_rows));
         let (tx, rx): (Sender<WriterThreadUpdate>, Receiver<WriterThreadUpdate>) = mpsc::channel();
 
+        let tracing_handle = if self.trace_propagation {
+            let auth = Arc::new(
+                self.authority
+                    .to_authority(&self.authority_address, &self.deployment),
+            );
+            let mut ch = ReadySetHandle::new(auth).await;
+            ch.set_write_tracing(self.trace_sample_rate).await?;
+            Some(ch)
+        } else {
+            None
+        };
+
         // Spawn a thread for articles
         let mut threads: Vec<_> = Vec::with_capacity(self.threads as usize);
         for _ in 0..self.threads {
@@ -298,7 +343,9 @@ impl Writer {
                     .await
             }))
         }
-        threads.push(tokio::spawn(async move { self.process_updates(rx).await }));
+        threads.push(tokio::spawn(async move {
+            self.process_updates(rx, tracing_handle).await
+        }));
 
         let res = futures::future::join_all(threads).await;
         for err_res in res.iter().filter(|e| e.is_err()) {
@@ -7,18 +7,19 @@ use std::convert::TryFrom;
 use std::str::FromStr;
 use std::time::{Duration, Instant};
 
-use anyhow::Result;
+use anyhow::{bail, Result};
 use async_trait::async_trait;
 use clap::Parser;
 use database_utils::{DatabaseURL, QueryableConnection};
 use metrics::Unit;
 use serde::{Deserialize, Serialize};
 use tokio::sync::mpsc::UnboundedSender;
-use tracing::{debug, error};
+use tracing::{debug, error, info};
 
 use crate::benchmark::{BenchmarkControl, BenchmarkResults, DeploymentParameters, MetricGoal};
 use crate::utils::generate::DataGenerator;
 use crate::utils::multi_thread::{self, MultithreadBenchmark};
+use crate::utils::open_loop::{self, ArrivalSchedule, Arrivals};
 use crate::utils::prometheus::ForwardPrometheusMetrics;
 use crate::utils::query::ArbitraryQueryParameters;
 use crate::utils::us_to_ms;
@@ -37,10 +38,36 @@ pub struct QueryBenchmark {
     #[arg(long)]
     target_qps: Option<u64>,
 
-    /// The number of threads to execute the read benchmark across.
+    /// The number of threads to execute the read benchmark across. In open-loop mode, this is
+    /// the number of connections queries are issued on, and so the maximum number of outstanding
+    /// queries.
     #[arg(long, default_value = "1")]
     threads: u64,
 
+    /// Issue queries in an open loop: start queries at `target_qps` (or at each rate in
+    /// `qps_sweep`) regardless of when earlier queries complete, and measure latency from each
+    /// query's scheduled start time. Queries scheduled while every connection is busy are
+    /// dropped. Requires `run_for`.
+    #[arg(long, requires = "run_for")]
+    #[serde(default)]
+    open_loop: bool,
+
+    /// A comma-separated list of offered loads, in queries per second, to run the open loop at
+    /// one after another, each for `run_for`.
+    #[arg(
+        long,
+        value_delimiter = ',',
+        requires = "open_loop",
+        conflicts_with = "target_qps"
+    )]
+    #[serde(default)]
+    qps_sweep: Vec<u64>,
+
+    /// How to schedule query start times in open-loop mode.
+    #[arg(long, value_enum, default_value_t = Arrivals::Poisson)]
+    #[serde(default)]
+    arrivals: Arrivals,
+
     /// Install and generate from an arbitrary schema.
     #[command(flatten)]
     data_generator: DataGenerator,
@@ -101,6 +128,10 @@ impl BenchmarkControl for QueryBenchmark {
         // non-select queries.
         let _ = self.query.migrate(&mut conn).await;
 
+        if self.open_loop {
+            return self.open_loop_benchmark(deployment).await;
+        }
+
         let thread_data = QueryBenchmarkThreadParams {
             target_qps: self.target_qps,
             threads: self.threads,
@@ -140,6 +171,93 @@ impl BenchmarkControl for QueryBenchmark {
     }
 }
 
+impl QueryBenchmark {
+    /// Run the query in an open loop at each offered load in `qps_sweep` (or just `target_qps`),
+    /// recording the achieved throughput, drop rate, and latency at each load
+    async fn open_loop_benchmark(
+        &self,
+        deployment: &DeploymentParameters,
+    ) -> Result<BenchmarkResults> {
+        let Some(run_for) = self.run_for else {
+            bail!("--open-loop requires --run-for");
+        };
+        let offered_loads = if self.qps_sweep.is_empty() {
+            self.target_qps.into_iter().collect::<Vec<_>>()
+        } else {
+            self.qps_sweep.clone()
+        };
+        if offered_loads.is_empty() || offered_loads.contains(&0) {
+            bail!("--open-loop requires a nonzero --target-qps or --qps-sweep");
+        }
+
+        let mut workers = Vec::with_capacity(self.threads as usize);
+        for _ in 0..self.threads {
+            let mut conn = DatabaseURL::from_str(&deployment.target_conn_str)?
+                .connect(None)
+                .await?;
+            let statement = self.query.prepared_statement(&mut conn).await?;
+            workers.push((conn, statement));
+        }
+
+        let mut benchmark_results = BenchmarkResults::new();
+        for offered_qps in offered_loads {
+            let mut schedule = ArrivalSchedule::new(self.arrivals, offered_qps);
+            let results = open_loop::run_open_loop(
+                &mut schedule,
+                &mut workers,
+                run_for,
+                |(mut conn, mut statement)| async move {
+                    let (query, params) = statement.generate_query();
+                    let res = conn.execute(query, params).await.map(|_| ());
+                    ((conn, statement), res.map_err(Into::into))
+                },
+            )
+            .await;
+
+            let hist = &results.latencies;
+            info!(
+                "offered qps: {}\tachieved qps: {:.0}\tdropped: {:.2}%\terrors: {}\t\
+                 p50: {:.1} ms\tp90: {:.1} ms\tp99: {:.1} ms\tp99.99: {:.1} ms",
+                offered_qps,
+                results.achieved_qps(),
+                results.drop_rate() * 100.0,
+                results.errors,
+                us_to_ms(hist.value_at_quantile(0.5)),
+                us_to_ms(hist.value_at_quantile(0.9)),
+                us_to_ms(hist.value_at_quantile(0.99)),
+                us_to_ms(hist.value_at_quantile(0.9999))
+            );
+
+            benchmark_results
+                .entry(
+                    &format!("achieved_qps_at_{offered_qps}_qps"),
+                    Unit::Count,
+                    MetricGoal::Increasing,
+                )
+                .push(results.achieved_qps());
+            benchmark_results
+                .entry(
+                    &format!("drop_rate_at_{offered_qps}_qps"),
+                    Unit::Percent,
+                    MetricGoal::Decreasing,
+                )
+                .push(results.drop_rate() * 100.0);
+            benchmark_results
+                .entry(
+                    &format!("query_duration_at_{offered_qps}_qps"),
+                    Unit::Microseconds,
+                    MetricGoal::Decreasing,
+                )
+                .extend(hist.iter_recorded().flat_map(|v| {
+                    std::iter::repeat(v.value_iterated_to() as f64)
+                        .take(v.count_at_value() as usize)
+                }));
+        }
+
+        Ok(benchmark_results)
+    }
+}
+
 #[derive(Debug, Clone)]
 /// A batched set of results sent on an interval by the read benchmark thread.
 pub(crate) struct QueryBenchmarkResultBatch {
@@ -12,6 +12,7 @@ use tracing::info;
 pub mod backend;
 pub mod generate;
 pub mod multi_thread;
+pub mod open_loop;
 pub mod path;
 pub mod prometheus;
 pub mod query;
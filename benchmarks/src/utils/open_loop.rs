@@ -0,0 +1,315 @@
+//! Open-loop load generation.
+//!
+//! A closed-loop benchmark issues each request once the previous one completes, so when the system
+//! under test slows down the benchmark slows down with it, and the time requests would have spent
+//! queued never shows up in the measured latencies (coordinated omission). In an open loop, request
+//! start times are scheduled at the offered rate up front, regardless of when earlier requests
+//! complete, and latency is measured from each request's *scheduled* start time.
+//!
+//! Requests are issued by a fixed pool of workers (eg database connections), which bounds the
+//! number of outstanding requests. A request scheduled when every worker is busy is counted as
+//! dropped, rather than being delayed until a worker becomes free.
+
+use std::future::Future;
+use std::time::Duration;
+
+use anyhow::Result;
+use clap::ValueEnum;
+use futures::stream::FuturesUnordered;
+use futures::StreamExt;
+use hdrhistogram::Histogram;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use rand_distr::{Distribution, Exp};
+use serde::{Deserialize, Serialize};
+use tokio::time::Instant;
+use tracing::error;
+
+/// The process used to schedule request start times in an open loop
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+pub enum Arrivals {
+    /// Exponentially distributed intervals between requests, as if each request came from an
+    /// independent user.
+    #[value(name = "poisson")]
+    #[default]
+    Poisson,
+
+    /// A fixed interval between requests.
+    #[value(name = "fixed")]
+    Fixed,
+}
+
+/// Generates the intervals between the scheduled start times of requests at an offered rate
+pub(crate) struct ArrivalSchedule {
+    arrivals: Arrivals,
+    offered_qps: u64,
+    rng: StdRng,
+}
+
+impl ArrivalSchedule {
+    pub(crate) fn new(arrivals: Arrivals, offered_qps: u64) -> Self {
+        Self::with_rng(arrivals, offered_qps, StdRng::from_entropy())
+    }
+
+    fn with_rng(arrivals: Arrivals, offered_qps: u64, rng: StdRng) -> Self {
+        assert!(offered_qps > 0, "Offered load must be nonzero");
+        Self {
+            arrivals,
+            offered_qps,
+            rng,
+        }
+    }
+
+    /// Returns the interval between the start time of the last request and the next
+    pub(crate) fn next_interval(&mut self) -> Duration {
+        let rate = self.offered_qps as f64;
+        match self.arrivals {
+            Arrivals::Fixed => Duration::from_secs_f64(1.0 / rate),
+            Arrivals::Poisson => {
+                Duration::from_secs_f64(Exp::new(rate).unwrap().sample(&mut self.rng))
+            }
+        }
+    }
+}
+
+/// The results of running an open loop at a single offered load
+pub(crate) struct OpenLoopResults {
+    pub(crate) offered_qps: u64,
+    /// The number of requests whose start time was scheduled, including those which were dropped
+    pub(crate) scheduled: u64,
+    /// The number of requests which completed successfully
+    pub(crate) completed: u64,
+    /// The number of requests which weren't issued because every worker was busy
+    pub(crate) dropped: u64,
+    /// The number of requests which were issued but returned an error
+    pub(crate) errors: u64,
+    /// The time from the first scheduled request until the last request completed
+    pub(crate) elapsed: Duration,
+    /// The latencies of successfully completed requests in microseconds, measured from their
+    /// scheduled start time
+    pub(crate) latencies: Histogram<u64>,
+}
+
+impl OpenLoopResults {
+    /// The rate at which requests were actually completed
+    pub(crate) fn achieved_qps(&self) -> f64 {
+        self.completed as f64 / self.elapsed.as_secs_f64()
+    }
+
+    /// The fraction of scheduled requests which were dropped
+    pub(crate) fn drop_rate(&self) -> f64 {
+        if self.scheduled == 0 {
+            0.0
+        } else {
+            self.dropped as f64 / self.scheduled as f64
+        }
+    }
+}
+
+/// Schedule requests at `offered_qps` for `duration`, issuing each one by calling `request` with
+/// an idle worker from `workers`. `request` must return the worker back once the request
+/// completes, so that it can be reused; once every outstanding request has completed, all the
+/// workers are back in `workers`.
+pub(crate) async fn run_open_loop<W, F, Fut>(
+    schedule: &mut ArrivalSchedule,
+    workers: &mut Vec<W>,
+    duration: Duration,
+    mut request: F,
+) -> OpenLoopResults
+where
+    F: FnMut(W) -> Fut,
+    Fut: Future<Output = (W, Result<()>)>,
+{
+    let mut latencies = Histogram::<u64>::new(3).unwrap();
+    let mut scheduled = 0;
+    let mut completed = 0;
+    let mut dropped = 0;
+    let mut errors = 0;
+
+    let start = Instant::now();
+    let end = start + duration;
+    let mut next_start = start;
+    let mut in_flight = FuturesUnordered::new();
+    loop {
+        tokio::select! {
+            // Prefer recording completions (and so freeing up workers) over issuing new requests
+            biased;
+
+            Some((worker, scheduled_start, res)) = in_flight.next(), if !in_flight.is_empty() => {
+                workers.push(worker);
+                match res {
+                    Ok(()) => {
+                        completed += 1;
+                        let latency = Instant::now().duration_since(scheduled_start);
+                        latencies.saturating_record(latency.as_micros() as u64);
+                    }
+                    Err(e) => {
+                        errors += 1;
+                        error!(err = %e, "Error issuing request");
+                    }
+                }
+            }
+            // If we've fallen behind schedule, this returns immediately, and the request's latency
+            // includes the time it spent waiting to be issued
+            _ = tokio::time::sleep_until(next_start), if next_start < end => {
+                scheduled += 1;
+                match workers.pop() {
+                    Some(worker) => {
+                        let fut = request(worker);
+                        let scheduled_start = next_start;
+                        in_flight.push(async move {
+                            let (worker, res) = fut.await;
+                            (worker, scheduled_start, res)
+                        });
+                    }
+                    None => dropped += 1,
+                }
+                next_start += schedule.next_interval();
+            }
+            else => break,
+        }
+    }
+
+    OpenLoopResults {
+        offered_qps: schedule.offered_qps,
+        scheduled,
+        completed,
+        dropped,
+        errors,
+        elapsed: start.elapsed(),
+        latencies,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+
+    use super::*;
+
+    /// A worker which takes the given amount of time to service each request
+    async fn mock_request(
+        mut service_times: VecDeque<Duration>,
+    ) -> (VecDeque<Duration>, Result<()>) {
+        let service_time = service_times
+            .pop_front()
+            .unwrap_or(Duration::from_millis(5));
+        tokio::time::sleep(service_time).await;
+        (service_times, Ok(()))
+    }
+
+    fn fixed_service_times(worker_count: usize, service_time: Duration) -> Vec<VecDeque<Duration>> {
+        (0..worker_count)
+            .map(|_| std::iter::repeat(service_time).take(1000).collect())
+            .collect()
+    }
+
+    #[test]
+    fn poisson_arrivals_average_offered_rate() {
+        let mut schedule =
+            ArrivalSchedule::with_rng(Arrivals::Poisson, 1000, StdRng::seed_from_u64(0));
+        let total = (0..10_000)
+            .map(|_| schedule.next_interval())
+            .sum::<Duration>();
+        let mean = total / 10_000;
+        assert!(
+            mean > Duration::from_micros(950) && mean < Duration::from_micros(1050),
+            "{mean:?}"
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn schedules_at_offered_rate_below_capacity() {
+        let mut schedule = ArrivalSchedule::new(Arrivals::Fixed, 100);
+        let mut workers = fixed_service_times(1, Duration::from_millis(5));
+        let results = run_open_loop(
+            &mut schedule,
+            &mut workers,
+            Duration::from_secs(1),
+            mock_request,
+        )
+        .await;
+
+        assert_eq!(results.scheduled, 100);
+        assert_eq!(results.completed, 100);
+        assert_eq!(results.dropped, 0);
+        assert_eq!(workers.len(), 1);
+        assert!(
+            results.latencies.min() >= 5_000,
+            "{}",
+            results.latencies.min()
+        );
+        assert!(
+            results.latencies.max() < 6_000,
+            "{}",
+            results.latencies.max()
+        );
+        assert!(results.achieved_qps() > 95.0, "{}", results.achieved_qps());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn drops_requests_above_capacity() {
+        // Requests arrive every 10ms and take 25ms, so two workers can service two out of every
+        // three requests, and the third finds both workers busy
+        let mut schedule = ArrivalSchedule::new(Arrivals::Fixed, 100);
+        let mut workers = fixed_service_times(2, Duration::from_millis(25));
+        let results = run_open_loop(
+            &mut schedule,
+            &mut workers,
+            Duration::from_secs(1),
+            mock_request,
+        )
+        .await;
+
+        assert_eq!(results.scheduled, 100);
+        assert_eq!(results.dropped, 33);
+        assert_eq!(results.completed, 67);
+        assert_eq!(workers.len(), 2);
+        assert!((results.drop_rate() - 0.33).abs() < f64::EPSILON);
+        // Requests which were issued started on schedule, so their latency is just their service
+        // time
+        assert!(
+            results.latencies.min() >= 25_000,
+            "{}",
+            results.latencies.min()
+        );
+        assert!(
+            results.latencies.max() < 26_000,
+            "{}",
+            results.latencies.max()
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn slow_request_does_not_delay_later_requests() {
+        // The first request takes 35ms, during which the requests scheduled at 10, 20, and 30ms
+        // find the only worker busy. In a closed loop, those would instead be issued back to back
+        // once the slow request completed, and their queueing time would go unmeasured.
+        let mut schedule = ArrivalSchedule::new(Arrivals::Fixed, 100);
+        let mut service_times = VecDeque::from([Duration::from_millis(35)]);
+        service_times.extend(std::iter::repeat(Duration::from_millis(5)).take(1000));
+        let mut workers = vec![service_times];
+        let results = run_open_loop(
+            &mut schedule,
+            &mut workers,
+            Duration::from_secs(1),
+            mock_request,
+        )
+        .await;
+
+        assert_eq!(results.scheduled, 100);
+        assert_eq!(results.dropped, 3);
+        assert_eq!(results.completed, 97);
+        assert!(
+            results.latencies.max() >= 35_000,
+            "{}",
+            results.latencies.max()
+        );
+        assert!(
+            results.latencies.max() < 36_000,
+            "{}",
+            results.latencies.max()
+        );
+        assert_eq!(results.latencies.count_between(0, 6_000), 96);
+    }
+}
@@ -121,6 +121,7 @@ fn query_indexed_by_columns(
             inner: TableExprInner::Table(table.name.clone().into()),
             alias: None,
             index_hint: None,
+            sample: None,
         }],
         fields: vec![FieldDefinitionExpr::All],
         where_clause: cols
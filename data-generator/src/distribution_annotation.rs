@@ -1,9 +1,9 @@
 use std::str::FromStr;
 
-use anyhow::bail;
+use anyhow::{anyhow, bail};
 use readyset_data::DfValue;
 
-use crate::ColumnGenerationSpec;
+use crate::{ColumnGenerationSpec, MAX_MATERIALIZED_CARDINALITY};
 
 /// An annotation for how to generate a parameter's value for a query. A
 /// parameter annotation takes the following form:
@@ -23,42 +23,131 @@ impl FromStr for DistributionAnnotation {
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let mut chunks = s.split_ascii_whitespace();
+        let spec = parse_spec(&mut chunks)?;
+        let unique = chunks.next().map(str::to_ascii_lowercase).as_deref() == Some("unique");
+        if unique && matches!(spec, ColumnGenerationSpec::WithCardinality { .. }) {
+            bail!("Unique columns cannot have a fixed cardinality");
+        }
+
+        Ok(Self { spec, unique })
+    }
+}
 
-        let spec = match chunks.next().unwrap().to_ascii_lowercase().as_str() {
-            "uniform" => {
-                let from: i64 = chunks.next().unwrap().parse().unwrap();
-                let to: i64 = chunks.next().unwrap().parse().unwrap();
-                ColumnGenerationSpec::Uniform(DfValue::Int(from), DfValue::Int(to))
+/// Parses a [`ColumnGenerationSpec`] from a colon-separated string, for use on the command line -
+/// for example `uniform:4:100`, or `cardinality:200:random` to generate 200 distinct random
+/// values.
+impl FromStr for ColumnGenerationSpec {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chunks = s.split(':');
+        let spec = parse_spec(&mut chunks)?;
+        if let Some(extra) = chunks.next() {
+            bail!("Unexpected trailing parameter in column generation spec: {extra}");
+        }
+        Ok(spec)
+    }
+}
+
+fn parse_spec<'a, I>(chunks: &mut I) -> anyhow::Result<ColumnGenerationSpec>
+where
+    I: Iterator<Item = &'a str>,
+{
+    let mut param = || {
+        chunks
+            .next()
+            .ok_or_else(|| anyhow!("Missing parameter in column generation spec"))
+    };
+
+    let spec = match param()?.to_ascii_lowercase().as_str() {
+        "uniform" => {
+            let from: i64 = param()?.parse()?;
+            let to: i64 = param()?.parse()?;
+            ColumnGenerationSpec::Uniform(DfValue::Int(from), DfValue::Int(to))
+        }
+        "zipf" => {
+            let from: i64 = param()?.parse()?;
+            let to: i64 = param()?.parse()?;
+            let alpha: f64 = param()?.parse()?;
+            ColumnGenerationSpec::Zipfian {
+                min: DfValue::Int(from),
+                max: DfValue::Int(to),
+                alpha,
             }
-            "zipf" => {
-                let from: i64 = chunks.next().unwrap().parse().unwrap();
-                let to: i64 = chunks.next().unwrap().parse().unwrap();
-                let alpha: f64 = chunks.next().unwrap().parse().unwrap();
-                ColumnGenerationSpec::Zipfian {
-                    min: DfValue::Int(from),
-                    max: DfValue::Int(to),
-                    alpha,
-                }
+        }
+        "regex" => {
+            let regex = param()?.trim_matches('"');
+            ColumnGenerationSpec::RandomString(regex.to_owned())
+        }
+        // Creates unique groups of size `num`.
+        "group" => {
+            let num: u32 = param()?.parse()?;
+            ColumnGenerationSpec::UniqueRepeated(num)
+        }
+        "constant" => {
+            let val: DfValue = param()?.into();
+            ColumnGenerationSpec::Constant(val)
+        }
+        "random" => ColumnGenerationSpec::Random,
+        // Generates random values, the given fraction of which are JSON objects with a "value" key.
+        "json" => {
+            let json_probability: f64 = param()?.parse()?;
+            if !(0.0..=1.0).contains(&json_probability) {
+                bail!("JSON probability must be between 0 and 1");
             }
-            "regex" => {
-                let regex = chunks.next().unwrap().trim_matches('"');
-                ColumnGenerationSpec::RandomString(regex.to_owned())
+            ColumnGenerationSpec::RandomJson { json_probability }
+        }
+        // Generates exactly `distinct` distinct values using the spec that follows.
+        "cardinality" => {
+            let distinct: u64 = param()?.parse()?;
+            if distinct == 0 {
+                bail!("Cardinality must be greater than zero");
             }
-            // Creates unique groups of size `num`.
-            "group" => {
-                let num: u32 = chunks.next().unwrap().parse().unwrap();
-                ColumnGenerationSpec::UniqueRepeated(num)
+            if distinct > u64::from(u32::MAX) {
+                bail!("Cardinality must be at most {}", u32::MAX);
             }
-            "constant" => {
-                let val: DfValue = chunks.next().unwrap().into();
-                ColumnGenerationSpec::Constant(val)
+            let inner = parse_spec(chunks)?;
+            // Above this many values, they're derived from their index rather than generated by
+            // the inner spec
+            if distinct > MAX_MATERIALIZED_CARDINALITY
+                && !matches!(inner, ColumnGenerationSpec::Random)
+            {
+                bail!(
+                    "Cardinalities above {MAX_MATERIALIZED_CARDINALITY} can only be used with \
+                     random values"
+                );
             }
-            _ => bail!("Unrecognized annotation"),
-        };
+            if let Some(max) = max_distinct_values(&inner) {
+                if max < distinct {
+                    bail!(
+                        "Cannot generate {distinct} distinct values from a spec which generates \
+                         at most {max}"
+                    );
+                }
+            }
+            ColumnGenerationSpec::WithCardinality {
+                inner: Box::new(inner),
+                distinct,
+            }
+        }
+        _ => bail!("Unrecognized annotation"),
+    };
 
-        let unique = chunks.next().map(str::to_ascii_lowercase).as_deref() == Some("unique");
+    Ok(spec)
+}
 
-        Ok(Self { spec, unique })
+/// Returns the number of distinct values `spec` can generate, if that's known to be bounded
+fn max_distinct_values(spec: &ColumnGenerationSpec) -> Option<u64> {
+    match spec {
+        ColumnGenerationSpec::Constant(_) | ColumnGenerationSpec::Null => Some(1),
+        ColumnGenerationSpec::Uniform(DfValue::Int(min), DfValue::Int(max))
+        | ColumnGenerationSpec::Zipfian {
+            min: DfValue::Int(min),
+            max: DfValue::Int(max),
+            ..
+        } => Some(u64::try_from(i128::from(*max) - i128::from(*min)).unwrap_or(0)),
+        ColumnGenerationSpec::WithCardinality { distinct, .. } => Some(*distinct),
+        _ => None,
     }
 }
 
@@ -89,6 +178,87 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn parse_cardinality_annotation_spec() {
+        let s = "cardinality 200 uniform 4 100"
+            .parse::<DistributionAnnotation>()
+            .unwrap();
+        assert_eq!(
+            s.spec,
+            ColumnGenerationSpec::WithCardinality {
+                inner: Box::new(ColumnGenerationSpec::Uniform(
+                    DfValue::Int(4),
+                    DfValue::Int(100)
+                )),
+                distinct: 200
+            }
+        );
+        assert!(!s.unique);
+    }
+
+    #[test]
+    fn unique_cardinality_annotation_rejected() {
+        "cardinality 200 random UNIQUE"
+            .parse::<DistributionAnnotation>()
+            .unwrap_err();
+    }
+
+    #[test]
+    fn parse_cli_spec() {
+        assert_eq!(
+            "cardinality:200:random"
+                .parse::<ColumnGenerationSpec>()
+                .unwrap(),
+            ColumnGenerationSpec::WithCardinality {
+                inner: Box::new(ColumnGenerationSpec::Random),
+                distinct: 200
+            }
+        );
+        assert_eq!(
+            "zipf:1:10:1.1".parse::<ColumnGenerationSpec>().unwrap(),
+            ColumnGenerationSpec::Zipfian {
+                min: DfValue::Int(1),
+                max: DfValue::Int(10),
+                alpha: 1.1
+            }
+        );
+        "cardinality:200"
+            .parse::<ColumnGenerationSpec>()
+            .unwrap_err();
+        "cardinality:0:random"
+            .parse::<ColumnGenerationSpec>()
+            .unwrap_err();
+        "random:5".parse::<ColumnGenerationSpec>().unwrap_err();
+        "cardinality:4294967296:random"
+            .parse::<ColumnGenerationSpec>()
+            .unwrap_err();
+        "cardinality:200000:uniform:0:1000000"
+            .parse::<ColumnGenerationSpec>()
+            .unwrap_err();
+        "cardinality:200000:random"
+            .parse::<ColumnGenerationSpec>()
+            .unwrap();
+        "cardinality:5:uniform:0:4"
+            .parse::<ColumnGenerationSpec>()
+            .unwrap_err();
+        "cardinality:4:uniform:0:4"
+            .parse::<ColumnGenerationSpec>()
+            .unwrap();
+        "cardinality:2:constant:1"
+            .parse::<ColumnGenerationSpec>()
+            .unwrap_err();
+        "cardinality:10:cardinality:5:random"
+            .parse::<ColumnGenerationSpec>()
+            .unwrap_err();
+        assert_eq!(
+            "json:0.5".parse::<ColumnGenerationSpec>().unwrap(),
+            ColumnGenerationSpec::RandomJson {
+                json_probability: 0.5
+            }
+        );
+        "json:2".parse::<ColumnGenerationSpec>().unwrap_err();
+    }
+
     #[test]
     fn parse_constant_spec() {
         let q = "constant 5";
@@ -3,10 +3,11 @@ use std::collections::HashSet;
 use std::net::{IpAddr, Ipv4Addr};
 use std::sync::Arc;
 
+use anyhow::bail;
 use bit_vec::BitVec;
 use chrono::{Duration, FixedOffset, NaiveDate, NaiveTime, TimeZone};
 use eui48::{MacAddress, MacAddressFormat};
-use nom_sql::SqlType;
+use nom_sql::{DialectDisplay, Expr, FunctionExpr, Literal, SqlType};
 use rand::distributions::Standard;
 use rand::prelude::Distribution;
 use rand::seq::SliceRandom;
@@ -19,6 +20,18 @@ mod distribution_annotation;
 
 pub use crate::distribution_annotation::DistributionAnnotation;
 
+/// Names of SQL functions which return the current date and/or time, and so can't be generated as a
+/// constant when used as the default value for a column
+const CURRENT_TIME_FUNCTIONS: &[&str] = &[
+    "now",
+    "current_timestamp",
+    "current_date",
+    "current_time",
+    "localtime",
+    "localtimestamp",
+    "utc_timestamp",
+];
+
 /// Variants and their parameters used to construct
 /// their respective ColumnGenerator.
 #[derive(Debug, PartialEq, Clone)]
@@ -29,6 +42,13 @@ pub enum ColumnGenerationSpec {
     UniqueFrom(u32),
     /// Generates a new unique value every n rows.
     UniqueRepeated(u32),
+    /// Generates a unique value for every row, spread out such that only `percent`% of the values
+    /// generated for the first n rows are also generated by [`ColumnGenerationSpec::Unique`] for
+    /// the first n rows.
+    ///
+    /// This can be used to control how many rows in one table have a match in another table when
+    /// joining on a column generated by [`ColumnGenerationSpec::Unique`].
+    UniqueSparse { percent: u32 },
     /// Generates an integer in the specified range.
     Uniform(DfValue, DfValue),
     /// Non-repeating Uniform, an optional batch size can be specified to
@@ -44,6 +64,10 @@ pub enum ColumnGenerationSpec {
     },
     /// Generates a random value for the row.
     Random,
+    /// Generates a random value for the row, except that for text and blob columns
+    /// `json_probability` of the values generated are JSON objects with a random integer under the
+    /// `"value"` key, eg `{"value": 42}`.
+    RandomJson { json_probability: f64 },
     /// Generate a random string from a regex
     RandomString(String),
     /// Generates an integer in the specified range. Cannot be used for
@@ -55,9 +79,43 @@ pub enum ColumnGenerationSpec {
     },
     /// Always generate the same value
     Constant(DfValue),
+    /// Always generate `NULL`
+    Null,
+    /// Generate exactly `distinct` distinct values using the `inner` spec, and then sample
+    /// uniformly among those values for every row.
+    ///
+    /// Cannot be used for unique columns, or with an `inner` spec which generates unique values.
+    WithCardinality {
+        inner: Box<ColumnGenerationSpec>,
+        distinct: u64,
+    },
 }
 
 impl ColumnGenerationSpec {
+    /// Convert the expression given as the `DEFAULT` value for a column in a `CREATE TABLE`
+    /// statement into a spec that generates values like that default.
+    ///
+    /// Literals are generated as constants (or [`ColumnGenerationSpec::Null`] for `NULL`), and
+    /// calls to functions returning the current time are generated randomly since their value
+    /// depends on when the row was inserted. Any other expression returns an error.
+    pub fn from_sql_default(default_expr: &Expr) -> anyhow::Result<ColumnGenerationSpec> {
+        match default_expr {
+            Expr::Literal(Literal::Null) => Ok(ColumnGenerationSpec::Null),
+            Expr::Literal(lit) => Ok(ColumnGenerationSpec::Constant(DfValue::try_from(lit)?)),
+            Expr::Call(FunctionExpr::Call { name, .. })
+                if CURRENT_TIME_FUNCTIONS
+                    .iter()
+                    .any(|f| name.as_str().eq_ignore_ascii_case(f)) =>
+            {
+                Ok(ColumnGenerationSpec::Random)
+            }
+            _ => bail!(
+                "Unsupported DEFAULT expression: {}",
+                default_expr.display(nom_sql::Dialect::MySQL)
+            ),
+        }
+    }
+
     pub fn generator_for_col(&self, col_type: SqlType) -> ColumnGenerator {
         match self {
             ColumnGenerationSpec::Unique => ColumnGenerator::Unique(col_type.into()),
@@ -67,6 +125,9 @@ impl ColumnGenerationSpec {
             ColumnGenerationSpec::UniqueRepeated(n) => {
                 ColumnGenerator::Unique(UniqueGenerator::new(col_type, 0, *n))
             }
+            ColumnGenerationSpec::UniqueSparse { percent } => {
+                ColumnGenerator::Unique(UniqueGenerator::sparse(col_type, *percent))
+            }
             ColumnGenerationSpec::Uniform(a, b) => ColumnGenerator::Uniform(UniformGenerator {
                 min: a.clone(),
                 max: b.clone(),
@@ -86,6 +147,9 @@ impl ColumnGenerationSpec {
                 pulled: HashSet::new(),
             }),
             ColumnGenerationSpec::Random => ColumnGenerator::Random(col_type.into()),
+            ColumnGenerationSpec::RandomJson { json_probability } => ColumnGenerator::Random(
+                RandomGenerator::with_json_probability(col_type, *json_probability),
+            ),
             ColumnGenerationSpec::RandomString(r) => ColumnGenerator::RandomString(r.into()),
             ColumnGenerationSpec::Zipfian { min, max, alpha } => {
                 ColumnGenerator::Zipfian(ZipfianGenerator::new(min.clone(), max.clone(), *alpha))
@@ -96,6 +160,22 @@ impl ColumnGenerationSpec {
                 let val = val.coerce_to(&col_type, &DfType::Unknown).unwrap();
                 ColumnGenerator::Constant(val.into())
             }
+            ColumnGenerationSpec::Null => ColumnGenerator::Constant(DfValue::None.into()),
+            ColumnGenerationSpec::WithCardinality { inner, distinct } => {
+                if matches!(
+                    **inner,
+                    ColumnGenerationSpec::Unique
+                        | ColumnGenerationSpec::UniqueFrom(_)
+                        | ColumnGenerationSpec::UniqueSparse { .. }
+                ) {
+                    panic!("Can't limit the cardinality of a Unique column");
+                }
+                ColumnGenerator::WithCardinality(CardinalityGenerator::new(
+                    inner.generator_for_col(col_type.clone()),
+                    col_type,
+                    *distinct,
+                ))
+            }
         }
     }
 }
@@ -119,6 +199,8 @@ pub enum ColumnGenerator {
     Zipfian(ZipfianGenerator),
     /// Generate a unique value for every row from a non unique generator
     NonRepeating(NonRepeatingGenerator),
+    /// Returns one of a fixed number of distinct values
+    WithCardinality(CardinalityGenerator),
 }
 
 impl ColumnGenerator {
@@ -131,6 +213,21 @@ impl ColumnGenerator {
             ColumnGenerator::RandomString(g) => g.gen(),
             ColumnGenerator::Zipfian(g) => g.gen(),
             ColumnGenerator::NonRepeating(g) => g.gen(),
+            ColumnGenerator::WithCardinality(g) => g.gen(),
+        }
+    }
+
+    /// Reset this generator to the state it was in when it was constructed, so that deterministic
+    /// generators will generate the same sequence of values again
+    pub fn reset(&mut self) {
+        match self {
+            ColumnGenerator::Constant(_) | ColumnGenerator::RandomString(_) => {}
+            ColumnGenerator::Unique(g) => g.reset(),
+            ColumnGenerator::Uniform(g) => g.reset(),
+            ColumnGenerator::Random(g) => g.reset(),
+            ColumnGenerator::Zipfian(g) => g.reset(),
+            ColumnGenerator::NonRepeating(g) => g.reset(),
+            ColumnGenerator::WithCardinality(g) => g.reset(),
         }
     }
 }
@@ -139,6 +236,9 @@ impl ColumnGenerator {
     pub fn into_unique(self) -> Self {
         match self {
             ColumnGenerator::Constant(_) => panic!("Can't make unique over Constant"),
+            ColumnGenerator::WithCardinality(_) => {
+                panic!("Can't make unique over WithCardinality")
+            }
             u @ ColumnGenerator::Unique(_) | u @ ColumnGenerator::NonRepeating(_) => u, /* nothing to do */
             u @ ColumnGenerator::Uniform(_)
             | u @ ColumnGenerator::Zipfian(_)
@@ -146,7 +246,7 @@ impl ColumnGenerator {
             | u @ ColumnGenerator::RandomString(_) => {
                 ColumnGenerator::NonRepeating(NonRepeatingGenerator {
                     generator: Box::new(u),
-                    generated: growable_bloom_filter::GrowableBloom::new(0.01, 1_000_000),
+                    generated: new_non_repeating_filter(),
                 })
             }
         }
@@ -215,9 +315,15 @@ pub struct UniqueGenerator {
     generated: u32,
     /// The current index to use to generate the random value. Incremented
     /// every batch_size.
-    index: u32,
+    index: u64,
+    /// The value of `index` when this generator was constructed, to return to on
+    /// [`reset`](Self::reset).
+    initial_index: u64,
     /// The number of values to generate before incrementing `index`.
     batch_size: u32,
+    /// The percentage of indices to generate values for. Values are generated for index `i * 100
+    /// / percent`, so if this is less than 100 some indices will be skipped.
+    percent: u32,
     sql_type: SqlType,
 }
 
@@ -225,11 +331,27 @@ impl UniqueGenerator {
     fn new(sql_type: SqlType, index: u32, batch_size: u32) -> Self {
         Self {
             generated: 0,
-            index,
+            index: index.into(),
+            initial_index: index.into(),
             batch_size,
+            percent: 100,
             sql_type,
         }
     }
+
+    /// # Panics
+    ///
+    /// Panics if `percent` is not between 1 and 100
+    fn sparse(sql_type: SqlType, percent: u32) -> Self {
+        assert!(
+            (1..=100).contains(&percent),
+            "UniqueSparse percent must be between 1 and 100"
+        );
+        Self {
+            percent,
+            ..Self::new(sql_type, 0, 1)
+        }
+    }
 }
 
 impl From<SqlType> for UniqueGenerator {
@@ -239,14 +361,37 @@ impl From<SqlType> for UniqueGenerator {
 }
 
 impl UniqueGenerator {
+    /// Generate the next value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if all the values this generator can produce have already been generated
     pub fn gen(&mut self) -> DfValue {
-        let val = unique_value_of_type(&self.sql_type, self.index);
+        let index = self
+            .index
+            .checked_mul(100)
+            .map(|index| index / u64::from(self.percent))
+            .and_then(|index| u32::try_from(index).ok())
+            .expect("Unique generator ran out of values");
+        let val = unique_value_of_type(&self.sql_type, index);
         self.generated += 1;
         if self.generated % self.batch_size == 0 {
             self.index += 1;
         }
         val
     }
+
+    /// Reset this generator to start generating values from its initial index again
+    pub fn reset(&mut self) {
+        self.generated = 0;
+        self.index = self.initial_index;
+    }
+
+    /// Returns the number of distinct values generated by the first `num_values` calls to
+    /// [`gen`](Self::gen) after this generator is constructed or [`reset`](Self::reset)
+    pub fn num_distinct(&self, num_values: u64) -> u64 {
+        num_values.div_ceil(u64::from(self.batch_size))
+    }
 }
 
 #[derive(Debug, Eq, PartialEq, Clone)]
@@ -293,6 +438,11 @@ impl UniformGenerator {
             val
         }
     }
+
+    /// Forget all the values pulled from the distribution so far
+    pub fn reset(&mut self) {
+        self.pulled.clear();
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -334,6 +484,9 @@ impl ZipfianGenerator {
         let offset = self.dist.sample(&mut rng);
         self.mapping.get(offset).unwrap().clone()
     }
+
+    /// Zipfian generators have no state beyond their (fixed) distribution, so this is a no-op
+    pub fn reset(&mut self) {}
 }
 
 impl PartialEq for ZipfianGenerator {
@@ -344,21 +497,71 @@ impl PartialEq for ZipfianGenerator {
 
 impl Eq for ZipfianGenerator {}
 
-#[derive(Debug, Eq, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone)]
 pub struct RandomGenerator {
     sql_type: SqlType,
+    /// The probability of generating a JSON object rather than a random value, if `sql_type` is a
+    /// text or blob type
+    json_probability: f64,
 }
 
+impl Eq for RandomGenerator {}
+
 impl From<SqlType> for RandomGenerator {
     fn from(sql_type: SqlType) -> Self {
-        Self { sql_type }
+        Self {
+            sql_type,
+            json_probability: 0.0,
+        }
     }
 }
 
 impl RandomGenerator {
+    /// Construct a new random generator which, if `sql_type` is a text or blob type, generates JSON
+    /// objects of the form `{"value": <int>}` with probability `json_probability`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `json_probability` is not between 0 and 1
+    pub fn with_json_probability(sql_type: SqlType, json_probability: f64) -> Self {
+        assert!(
+            (0.0..=1.0).contains(&json_probability),
+            "json_probability must be between 0 and 1"
+        );
+        Self {
+            sql_type,
+            json_probability,
+        }
+    }
+
     pub fn gen(&self) -> DfValue {
-        random_value_of_type(&self.sql_type, thread_rng())
+        let mut rng = thread_rng();
+        let is_text = matches!(
+            self.sql_type,
+            SqlType::Blob
+                | SqlType::LongBlob
+                | SqlType::MediumBlob
+                | SqlType::TinyBlob
+                | SqlType::TinyText
+                | SqlType::MediumText
+                | SqlType::LongText
+                | SqlType::Text
+                | SqlType::VarChar(None)
+        );
+        if is_text && rng.gen_bool(self.json_probability) {
+            format!(r#"{{"value": {}}}"#, rng.gen::<i32>()).into()
+        } else {
+            random_value_of_type(&self.sql_type, rng)
+        }
     }
+
+    /// Random generators have no state, so this is a no-op
+    pub fn reset(&mut self) {}
+}
+
+/// Construct the filter used by a [`NonRepeatingGenerator`] to track the values it has generated
+fn new_non_repeating_filter() -> growable_bloom_filter::GrowableBloom {
+    growable_bloom_filter::GrowableBloom::new(0.01, 1_000_000)
 }
 
 #[derive(Debug, Clone)]
@@ -387,6 +590,9 @@ impl NonRepeatingGenerator {
                 ColumnGenerator::Unique(_) => panic!("Non repeating over Unique"),
                 ColumnGenerator::Constant(_) => panic!("Non repeating over Constant"),
                 ColumnGenerator::NonRepeating(_) => panic!("Nested NonRepeating"),
+                ColumnGenerator::WithCardinality(_) => {
+                    panic!("Non repeating over WithCardinality")
+                }
             };
 
             if self.generated.insert(d.clone()) {
@@ -402,6 +608,95 @@ impl NonRepeatingGenerator {
             }
         }
     }
+
+    /// Reset the inner generator, and forget all the values generated so far
+    pub fn reset(&mut self) {
+        self.generator.reset();
+        self.generated = new_non_repeating_filter();
+    }
+}
+
+/// The maximum number of distinct values a [`CardinalityGenerator`] will generate up-front using
+/// its inner generator. Above this, values are instead derived deterministically from their index
+/// to bound memory usage, ignoring the inner generator - so parsed specs only allow this many
+/// distinct values with [`ColumnGenerationSpec::Random`].
+const MAX_MATERIALIZED_CARDINALITY: u64 = 100_000;
+
+/// The number of times in a row a [`CardinalityGenerator`] will fail to get a new distinct value
+/// out of its inner generator before giving up
+const MAX_CARDINALITY_MISSES: usize = 100_000;
+
+#[derive(Debug, Clone)]
+pub struct CardinalityGenerator {
+    generator: Box<ColumnGenerator>,
+    sql_type: SqlType,
+    distinct: u64,
+    /// The distinct values to sample from, generated the first time a value is requested. Always
+    /// `None` if `distinct` is greater than [`MAX_MATERIALIZED_CARDINALITY`].
+    values: Option<Vec<DfValue>>,
+}
+
+impl Eq for CardinalityGenerator {}
+
+impl PartialEq for CardinalityGenerator {
+    fn eq(&self, other: &Self) -> bool {
+        self.generator == other.generator && self.distinct == other.distinct
+    }
+}
+
+impl CardinalityGenerator {
+    fn new(generator: ColumnGenerator, sql_type: SqlType, distinct: u64) -> Self {
+        assert!(distinct > 0, "Cardinality must be greater than zero");
+        assert!(
+            distinct <= u32::MAX as u64,
+            "Cardinality must be at most {}",
+            u32::MAX
+        );
+        Self {
+            generator: Box::new(generator),
+            sql_type,
+            distinct,
+            values: None,
+        }
+    }
+
+    pub fn gen(&mut self) -> DfValue {
+        let idx = thread_rng().gen_range(0..self.distinct);
+        if self.distinct > MAX_MATERIALIZED_CARDINALITY {
+            return unique_value_of_type(&self.sql_type, idx as u32);
+        }
+
+        let generator = &mut self.generator;
+        let distinct = self.distinct as usize;
+        let values = self.values.get_or_insert_with(|| {
+            let mut seen = HashSet::with_capacity(distinct);
+            let mut values = Vec::with_capacity(distinct);
+            let mut misses = 0;
+            while values.len() < distinct {
+                let val = generator.gen();
+                if seen.insert(val.clone()) {
+                    values.push(val);
+                    misses = 0;
+                } else {
+                    misses += 1;
+                    assert!(
+                        misses <= MAX_CARDINALITY_MISSES,
+                        "Could not generate {distinct} distinct values from {generator:?}"
+                    );
+                }
+            }
+            values
+        });
+
+        #[allow(clippy::indexing_slicing)] // idx < distinct == values.len()
+        values[idx as usize].clone()
+    }
+
+    /// Reset the inner generator, and discard the distinct values sampled from it so far
+    pub fn reset(&mut self) {
+        self.generator.reset();
+        self.values = None;
+    }
 }
 
 /// Generate a constant value with the given [`SqlType`]
@@ -740,3 +1035,208 @@ pub fn unique_value_of_type(typ: &SqlType, idx: u32) -> DfValue {
         SqlType::Other(_) => unimplemented!(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use nom_sql::SqlIdentifier;
+
+    use super::*;
+
+    #[test]
+    fn from_sql_default_integer() {
+        assert_eq!(
+            ColumnGenerationSpec::from_sql_default(&Expr::Literal(Literal::Integer(5))).unwrap(),
+            ColumnGenerationSpec::Constant(DfValue::Int(5))
+        );
+    }
+
+    #[test]
+    fn from_sql_default_null() {
+        let spec = ColumnGenerationSpec::from_sql_default(&Expr::Literal(Literal::Null)).unwrap();
+        assert_eq!(spec, ColumnGenerationSpec::Null);
+        assert_eq!(
+            spec.generator_for_col(SqlType::Int(None)).gen(),
+            DfValue::None
+        );
+    }
+
+    #[test]
+    fn from_sql_default_now() {
+        for name in ["now", "CURRENT_TIMESTAMP"] {
+            assert_eq!(
+                ColumnGenerationSpec::from_sql_default(&Expr::Call(FunctionExpr::Call {
+                    name: SqlIdentifier::from(name),
+                    arguments: vec![],
+                }))
+                .unwrap(),
+                ColumnGenerationSpec::Random
+            );
+        }
+    }
+
+    fn distinct_values(spec: ColumnGenerationSpec, col_type: SqlType, rows: usize) -> usize {
+        let mut generator = spec.generator_for_col(col_type);
+        (0..rows)
+            .map(|_| generator.gen())
+            .collect::<HashSet<_>>()
+            .len()
+    }
+
+    #[test]
+    fn random_json_probability() {
+        let count_json = |json_probability| {
+            let mut generator = ColumnGenerationSpec::RandomJson { json_probability }
+                .generator_for_col(SqlType::Text);
+            (0..100)
+                .map(|_| generator.gen())
+                .filter(|val| {
+                    let s = String::try_from(val).unwrap();
+                    s.starts_with(r#"{"value": "#) && s.ends_with('}')
+                })
+                .count()
+        };
+        assert_eq!(count_json(0.0), 0);
+        assert_eq!(count_json(1.0), 100);
+        let some = count_json(0.5);
+        assert!(some > 0 && some < 100, "{some}");
+    }
+
+    #[test]
+    fn unique_sparse_overlap() {
+        let gen_values = |spec: ColumnGenerationSpec| {
+            let mut generator = spec.generator_for_col(SqlType::Int(None));
+            (0..1000).map(|_| generator.gen()).collect::<HashSet<_>>()
+        };
+        let dense = gen_values(ColumnGenerationSpec::Unique);
+        for percent in [1, 30, 50, 100] {
+            let sparse = gen_values(ColumnGenerationSpec::UniqueSparse { percent });
+            assert_eq!(sparse.len(), 1000);
+            assert_eq!(
+                sparse.intersection(&dense).count(),
+                1000 * percent as usize / 100
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "ran out of values")]
+    fn unique_sparse_out_of_values() {
+        let mut generator = UniqueGenerator {
+            index: u64::from(u32::MAX) * 30 / 100,
+            ..UniqueGenerator::sparse(SqlType::Int(None), 30)
+        };
+        generator.gen();
+        generator.gen();
+    }
+
+    #[test]
+    #[should_panic(expected = "percent must be between 1 and 100")]
+    fn unique_sparse_invalid_percent() {
+        UniqueGenerator::sparse(SqlType::Int(None), 0);
+    }
+
+    #[test]
+    fn with_cardinality_small() {
+        for (inner, col_type) in [
+            (ColumnGenerationSpec::Random, SqlType::Int(None)),
+            (ColumnGenerationSpec::Random, SqlType::Text),
+            (
+                ColumnGenerationSpec::Uniform(0i32.into(), 1000i32.into()),
+                SqlType::Int(None),
+            ),
+        ] {
+            let spec = ColumnGenerationSpec::WithCardinality {
+                inner: Box::new(inner),
+                distinct: 5,
+            };
+            assert_eq!(distinct_values(spec, col_type, 1000), 5);
+        }
+    }
+
+    #[test]
+    fn with_cardinality_near_row_count() {
+        let spec = ColumnGenerationSpec::WithCardinality {
+            inner: Box::new(ColumnGenerationSpec::Random),
+            distinct: 1000,
+        };
+        // Sampling 1000 times from 1000 values with replacement should see about 1000 * (1 - 1/e)
+        // ~= 632 of them
+        let distinct = distinct_values(spec, SqlType::BigInt(None), 1000);
+        assert!((550..=720).contains(&distinct), "{distinct}");
+    }
+
+    #[test]
+    fn with_cardinality_above_materialization_limit() {
+        let spec = ColumnGenerationSpec::WithCardinality {
+            inner: Box::new(ColumnGenerationSpec::Random),
+            distinct: MAX_MATERIALIZED_CARDINALITY * 10,
+        };
+        let mut generator = spec.generator_for_col(SqlType::BigInt(None));
+        for _ in 0..1000 {
+            match generator.gen() {
+                DfValue::Int(i) => assert!((i as u64) < MAX_MATERIALIZED_CARDINALITY * 10),
+                v => panic!("Unexpected value {v:?}"),
+            }
+        }
+        match generator {
+            ColumnGenerator::WithCardinality(g) => assert!(g.values.is_none()),
+            g => panic!("Unexpected generator {g:?}"),
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn with_cardinality_unsatisfiable() {
+        let spec = ColumnGenerationSpec::WithCardinality {
+            inner: Box::new(ColumnGenerationSpec::Uniform(0i32.into(), 3i32.into())),
+            distinct: 5,
+        };
+        spec.generator_for_col(SqlType::Int(None)).gen();
+    }
+
+    #[test]
+    #[should_panic]
+    fn with_cardinality_not_unique() {
+        let spec = ColumnGenerationSpec::WithCardinality {
+            inner: Box::new(ColumnGenerationSpec::Random),
+            distinct: 5,
+        };
+        spec.generator_for_col(SqlType::Int(None)).into_unique();
+    }
+
+    #[test]
+    fn unique_reset_regenerates_same_values() {
+        let mut generator = ColumnGenerator::Unique(UniqueGenerator::new(SqlType::Int(None), 5, 3));
+        let first = (0..100).map(|_| generator.gen()).collect::<Vec<_>>();
+        generator.reset();
+        let second = (0..100).map(|_| generator.gen()).collect::<Vec<_>>();
+        assert_eq!(first, second);
+        assert_eq!(first[0], unique_value_of_type(&SqlType::Int(None), 5));
+    }
+
+    #[test]
+    fn uniform_without_replacement_reset() {
+        let mut generator = ColumnGenerationSpec::UniformWithoutReplacement {
+            min: 0i32.into(),
+            max: 100i32.into(),
+            batch_size: None,
+        }
+        .generator_for_col(SqlType::Int(None));
+        let mut first = (0..100).map(|_| generator.gen()).collect::<Vec<_>>();
+        generator.reset();
+        // Without the reset, this would fail to find any more values to pull
+        let mut second = (0..100).map(|_| generator.gen()).collect::<Vec<_>>();
+        first.sort();
+        second.sort();
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn from_sql_default_unsupported() {
+        ColumnGenerationSpec::from_sql_default(&Expr::Call(FunctionExpr::Call {
+            name: "uuid".into(),
+            arguments: vec![],
+        }))
+        .unwrap_err();
+    }
+}
@@ -148,6 +148,46 @@ impl BuiltinFunction {
             DateTrunc { .. } => "date_trunc",
         }
     }
+
+    /// Returns all the expressions passed as arguments to this function
+    fn arguments(&self) -> Vec<&Expr> {
+        use BuiltinFunction::*;
+        match self {
+            ConvertTZ(args) => args.iter().collect(),
+            DayOfWeek(arg) | Month(arg) | JsonDepth(arg) | JsonValid(arg) | JsonQuote(arg)
+            | JsonTypeof(arg) | JsonArrayLength(arg) | JsonStripNulls(arg) | JsonbPretty(arg) => {
+                vec![arg]
+            }
+            IfNull(arg1, arg2)
+            | Timediff(arg1, arg2)
+            | Addtime(arg1, arg2)
+            | DateFormat(arg1, arg2)
+            | Round(arg1, arg2)
+            | JsonOverlaps(arg1, arg2)
+            | DateTrunc(arg1, arg2) => vec![arg1, arg2],
+            JsonObject { arg1, arg2, .. } => std::iter::once(arg1).chain(arg2).collect(),
+            JsonExtractPath { json, keys } => std::iter::once(json).chain(keys.iter()).collect(),
+            JsonbInsert(arg1, arg2, arg3, arg4) => {
+                [arg1, arg2, arg3].into_iter().chain(arg4).collect()
+            }
+            JsonbSet(arg1, arg2, arg3, arg4, null_value_treatment) => [arg1, arg2, arg3]
+                .into_iter()
+                .chain(arg4)
+                .chain(null_value_treatment.expr())
+                .collect(),
+            Coalesce(arg1, args) | Concat(arg1, args) => {
+                std::iter::once(arg1).chain(args).collect()
+            }
+            Substring(string, from, len) => {
+                std::iter::once(string).chain(from).chain(len).collect()
+            }
+            SplitPart(arg1, arg2, arg3) => vec![arg1, arg2, arg3],
+            Greatest { args, .. } | Least { args, .. } => args.iter().collect(),
+            ArrayToString(array, delimiter, null_string) => {
+                [array, delimiter].into_iter().chain(null_string).collect()
+            }
+        }
+    }
 }
 
 impl Display for BuiltinFunction {
@@ -429,4 +469,34 @@ impl Expr {
             | Expr::Array { ty, .. } => ty,
         }
     }
+
+    /// Returns the indices of all the columns referenced by this expression, in no particular
+    /// order and possibly with duplicates
+    pub fn referred_columns(&self) -> Vec<usize> {
+        let mut columns = vec![];
+        let mut exprs = vec![self];
+        while let Some(expr) = exprs.pop() {
+            match expr {
+                Expr::Column { index, .. } => columns.push(*index),
+                Expr::Literal { .. } => {}
+                Expr::Op { left, right, .. }
+                | Expr::OpAny { left, right, .. }
+                | Expr::OpAll { left, right, .. } => exprs.extend([left.as_ref(), right.as_ref()]),
+                Expr::Not { expr, .. } | Expr::Cast { expr, .. } => exprs.push(expr),
+                Expr::Call { func, .. } => exprs.extend(func.arguments()),
+                Expr::CaseWhen {
+                    branches,
+                    else_expr,
+                    ..
+                } => {
+                    for branch in branches {
+                        exprs.extend([&branch.condition, &branch.body]);
+                    }
+                    exprs.push(else_expr);
+                }
+                Expr::Array { elements, .. } => exprs.extend(elements),
+            }
+        }
+        columns
+    }
 }
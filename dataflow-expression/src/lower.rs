@@ -7,8 +7,8 @@ use nom_sql::{
 use readyset_data::dialect::SqlEngine;
 use readyset_data::{DfType, DfValue};
 use readyset_errors::{
-    internal, internal_err, invalid_query, invalid_query_err, unsupported, ReadySetError,
-    ReadySetResult,
+    internal, internal_err, invalid_query, invalid_query_err, unsupported, unsupported_feature,
+    ReadySetError, ReadySetResult, UnsupportedFeature,
 };
 use readyset_util::redacted::Sensitive;
 use vec1::Vec1;
@@ -448,7 +448,12 @@ impl BuiltinFunction {
 
                 (Self::DateTrunc(precision, source), ret_type)
             }
-            _ => unsupported!("Function {name} does not exist"),
+            _ => unsupported_feature!(
+                UnsupportedFeature::Function {
+                    name: name.to_string()
+                },
+                "Function {name} does not exist"
+            ),
         };
 
         if args.next().is_some() {
@@ -780,6 +785,14 @@ impl Expr {
 
                 Ok(Self::Call { func, ty })
             }
+            AstExpr::Call(FunctionExpr::Window { name, .. }) => {
+                unsupported_feature!(
+                    UnsupportedFeature::Function {
+                        name: name.to_string()
+                    },
+                    "Window function {name} is not yet supported"
+                )
+            }
             AstExpr::Call(call) => internal!(
                 "Unexpected (aggregate?) call node in project expression: {:?}",
                 Sensitive(&call)
@@ -2,7 +2,8 @@
 //! RocksDB through [`PersistentState`]. These benchmarks may be useful to characterize cold read
 //! performance, as [`PersistentState`] is what stores base tables. It would make sense to run
 //! these benchmarks before and after making a change to [`PersistentState`] or the RocksDB
-//! configuration to make sure performance has not regressed.
+//! configuration to make sure performance has not regressed. There is also a benchmark comparing
+//! write throughput with each of the supported [`CompressionAlgorithm`]s.
 //!
 //! To run the benchmarks with the default settings (100k entries) with data stored in a temp
 //! directory and deleted after the benchmarks run:
@@ -38,9 +39,10 @@
 //! ```
 use clap::Parser;
 use common::{Index, IndexType, Record};
-use criterion::{black_box, Criterion};
+use criterion::{black_box, Criterion, Throughput};
 use dataflow_state::{
-    DurabilityMode, PersistenceParameters, PersistentState, PointKey, RangeKey, SnapshotMode, State,
+    CompressionAlgorithm, DurabilityMode, PersistenceParameters, PersistentState, PointKey,
+    RangeKey, SnapshotMode, State,
 };
 use itertools::Itertools;
 use readyset_data::{DfValue, IntoBoundedRange};
@@ -202,6 +204,45 @@ pub fn rocksdb_range_lookup_large_strings(c: &mut Criterion, state: &PersistentS
     group.finish();
 }
 
+pub fn rocksdb_write_compression(c: &mut Criterion, durability_mode: DurabilityMode) {
+    const BATCH_SIZE: usize = 100;
+
+    let mut group = c.benchmark_group("RocksDB write compression");
+    group.throughput(Throughput::Elements(BATCH_SIZE as _));
+    for (name, compression) in [
+        ("none", CompressionAlgorithm::None),
+        ("lz4", CompressionAlgorithm::Lz4),
+        ("zstd", CompressionAlgorithm::Zstd { level: 3 }),
+    ] {
+        let mut state = PersistentState::new(
+            format!("bench_write_compression_{name}"),
+            vec![&[0usize][..]],
+            &PersistenceParameters {
+                mode: durability_mode,
+                persistence_threads: 6,
+                compression,
+                ..PersistenceParameters::default()
+            },
+        )
+        .unwrap();
+        state.add_index(Index::new(IndexType::HashMap, vec![0]), None);
+
+        group.bench_function(name, |b| {
+            let mut iter = 0usize;
+            b.iter(|| {
+                let batch = (iter..iter + BATCH_SIZE)
+                    .map(|i| Record::from(vec![i.into(), LARGE_STRINGS[i % 3].clone().into()]))
+                    .collect::<Vec<_>>();
+                state
+                    .process_records(&mut batch.into(), None, None)
+                    .unwrap();
+                iter += BATCH_SIZE;
+            })
+        });
+    }
+    group.finish();
+}
+
 #[derive(Parser, Debug)]
 struct PersistentStateBenchArgs {
     /// If specified, only run benches containing this string in their names
@@ -398,6 +439,7 @@ fn main() -> anyhow::Result<()> {
         rocksdb_get_secondary_unique_key(&mut criterion, &state, args.unique_entries);
         rocksdb_range_lookup(&mut criterion, &state);
         rocksdb_range_lookup_large_strings(&mut criterion, &large_strings_state);
+        rocksdb_write_compression(&mut criterion, args.durability_mode);
 
         criterion.final_summary();
     }
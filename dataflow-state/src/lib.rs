@@ -31,7 +31,8 @@ use serde::{Deserialize, Serialize};
 pub use crate::key::{PointKey, RangeKey};
 pub use crate::memory_state::MemoryState;
 pub use crate::persistent_state::{
-    DurabilityMode, PersistenceParameters, PersistentState, PersistentStateHandle, SnapshotMode,
+    CompressionAlgorithm, DurabilityMode, PersistenceParameters, PersistentState,
+    PersistentStateHandle, SnapshotMode,
 };
 
 /// Information about state evicted via a call to [`State::evict_bytes`]
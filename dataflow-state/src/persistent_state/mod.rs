@@ -232,6 +232,37 @@ impl FromStr for DurabilityMode {
     }
 }
 
+/// The compression algorithm to use for data persisted to disk by [`PersistentState`].
+///
+/// RocksDB only supports compressing its write-ahead log with Zstd, so with [`Self::Lz4`] only the
+/// SST files are compressed, whereas with [`Self::Zstd`] both the SST files and the write-ahead log
+/// are compressed.
+#[derive(Copy, Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum CompressionAlgorithm {
+    /// Don't compress any data
+    None,
+    /// Compress SST files with LZ4, which is fast to compress and decompress but has a moderate
+    /// compression ratio
+    #[default]
+    Lz4,
+    /// Compress SST files and the write-ahead log with Zstd, which has a higher compression ratio
+    /// than LZ4 at the cost of more CPU usage
+    Zstd {
+        /// The Zstd compression level
+        level: i32,
+    },
+}
+
+impl CompressionAlgorithm {
+    fn rocksdb_compression_type(self) -> rocksdb::DBCompressionType {
+        match self {
+            CompressionAlgorithm::None => rocksdb::DBCompressionType::None,
+            CompressionAlgorithm::Lz4 => rocksdb::DBCompressionType::Lz4,
+            CompressionAlgorithm::Zstd { .. } => rocksdb::DBCompressionType::Zstd,
+        }
+    }
+}
+
 /// Parameters to control the operation of GroupCommitQueue.
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
 pub struct PersistenceParameters {
@@ -248,6 +279,9 @@ pub struct PersistenceParameters {
     /// set to 0, the WAL will be flushed and synced to disk with every write
     #[serde(default)]
     pub wal_flush_interval_seconds: u64,
+    /// The compression algorithm to use for the data written to disk
+    #[serde(default)]
+    pub compression: CompressionAlgorithm,
 }
 
 impl Default for PersistenceParameters {
@@ -258,6 +292,7 @@ impl Default for PersistenceParameters {
             persistence_threads: 1,
             storage_dir: None,
             wal_flush_interval_seconds: 0,
+            compression: CompressionAlgorithm::default(),
         }
     }
 }
@@ -292,6 +327,7 @@ impl PersistenceParameters {
             persistence_threads,
             storage_dir,
             wal_flush_interval_seconds,
+            compression: CompressionAlgorithm::default(),
         }
     }
 }
@@ -1100,7 +1136,14 @@ fn deserialize_row<T: AsRef<[u8]>>(bytes: T) -> Vec<DfValue> {
 /// index type.
 fn base_options(params: &PersistenceParameters) -> rocksdb::Options {
     let mut opts = rocksdb::Options::default();
-    opts.set_compression_type(rocksdb::DBCompressionType::Lz4);
+    opts.set_compression_type(params.compression.rocksdb_compression_type());
+    if let CompressionAlgorithm::Zstd { level } = params.compression {
+        // The remaining arguments are the defaults for `window_bits`, `strategy`, and
+        // `max_dict_bytes`
+        opts.set_compression_options(-14, level, 0, 0);
+        // Zstd is the only compression algorithm RocksDB supports for the WAL
+        opts.set_wal_compression_type(rocksdb::DBCompressionType::Zstd);
+    }
     opts.create_if_missing(true);
     opts.create_missing_column_families(true);
     opts.set_allow_concurrent_memtable_write(false);
@@ -2762,6 +2805,53 @@ mod tests {
         }
     }
 
+    fn recover_with_compression(compression: CompressionAlgorithm) {
+        let (_dir, name) = get_tmp_path();
+        let params = PersistenceParameters {
+            mode: DurabilityMode::Permanent,
+            compression,
+            ..Default::default()
+        };
+        let records = (0..1000)
+            .map(|i: i32| vec![i.into(), format!("row number {i}").repeat(10).into()])
+            .collect::<Vec<Vec<DfValue>>>();
+        {
+            let mut state =
+                PersistentState::new(name.clone(), Vec::<Box<[usize]>>::new(), &params).unwrap();
+            state.add_index(Index::new(IndexType::HashMap, vec![0]), None);
+            for record in &records {
+                state
+                    .process_records(&mut vec![record.clone()].into(), None, None)
+                    .unwrap();
+            }
+        }
+
+        let state = PersistentState::new(name, Vec::<Box<[usize]>>::new(), &params).unwrap();
+        for record in &records {
+            match state.lookup(&[0], &PointKey::Single(record[0].clone())) {
+                LookupResult::Some(RecordResult::Owned(rows)) => {
+                    assert_eq!(rows, vec![record.clone()]);
+                }
+                _ => unreachable!(),
+            }
+        }
+    }
+
+    #[test]
+    fn persistent_state_recover_lz4() {
+        recover_with_compression(CompressionAlgorithm::Lz4);
+    }
+
+    #[test]
+    fn persistent_state_recover_zstd() {
+        recover_with_compression(CompressionAlgorithm::Zstd { level: 3 });
+    }
+
+    #[test]
+    fn persistent_state_recover_uncompressed() {
+        recover_with_compression(CompressionAlgorithm::None);
+    }
+
     #[test]
     fn persistent_state_recover_unique_key() {
         let (_dir, name) = get_tmp_path();
@@ -105,6 +105,10 @@ impl<'a> ReferredColumnsIter<'a> {
                 }
                 self.visit_expr(first_arg)
             }),
+            Window { .. } => {
+                self.exprs_to_visit.extend(fexpr.arguments());
+                None
+            }
             Substring { string, pos, len } => {
                 self.exprs_to_visit.extend(pos.iter().map(|e| e.as_ref()));
                 self.exprs_to_visit.extend(len.iter().map(|e| e.as_ref()));
@@ -214,6 +218,25 @@ impl<'a> ReferredColumnsMut<'a> {
                 self.exprs_to_visit.extend(args);
                 self.visit_expr(first_arg)
             }),
+            Window {
+                arguments,
+                partition_by,
+                order_by,
+                ..
+            } => {
+                self.exprs_to_visit
+                    .extend(arguments.iter_mut().chain(partition_by));
+                self.exprs_to_visit.extend(
+                    order_by
+                        .iter_mut()
+                        .flat_map(|oc| &mut oc.order_by)
+                        .filter_map(|ob| match &mut ob.field {
+                            FieldReference::Expr(expr) => Some(expr),
+                            FieldReference::Numeric(_) => None,
+                        }),
+                );
+                None
+            }
             Substring { string, pos, len } => {
                 self.exprs_to_visit
                     .extend(pos.iter_mut().map(|e| e.as_mut()));
@@ -346,6 +369,9 @@ pub fn is_aggregate(function: &FunctionExpr) -> bool {
         | FunctionExpr::Min(_)
         | FunctionExpr::GroupConcat { .. } => true,
         FunctionExpr::Substring { .. }
+        // Window functions are computed over their partition rather than by grouping, so they
+        // aren't aggregates even if the underlying function is
+        | FunctionExpr::Window { .. }
         // For now, assume all "generic" function calls are not aggregates
         | FunctionExpr::Call { .. } => false,
     }
@@ -524,6 +524,20 @@ pub fn walk_function_expr<'ast, V: Visitor<'ast>>(
             }
             Ok(())
         }
+        FunctionExpr::Window {
+            arguments,
+            partition_by,
+            order_by,
+            ..
+        } => {
+            for arg in arguments.iter().chain(partition_by) {
+                visitor.visit_expr(arg)?;
+            }
+            if let Some(order_by) = order_by {
+                visitor.visit_order_clause(order_by)?;
+            }
+            Ok(())
+        }
     }
 }
 
@@ -539,6 +539,20 @@ pub fn walk_function_expr<'ast, V: VisitorMut<'ast>>(
             }
             Ok(())
         }
+        FunctionExpr::Window {
+            arguments,
+            partition_by,
+            order_by,
+            ..
+        } => {
+            for arg in arguments.iter_mut().chain(partition_by) {
+                visitor.visit_expr(arg)?;
+            }
+            if let Some(order_by) = order_by {
+                visitor.visit_order_clause(order_by)?;
+            }
+            Ok(())
+        }
     }
 }
 
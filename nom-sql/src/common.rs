@@ -21,9 +21,10 @@ use test_strategy::Arbitrary;
 use crate::column::Column;
 use crate::dialect::{Dialect, DialectDisplay};
 use crate::expression::expression;
+use crate::order::order_clause;
 use crate::table::Relation;
 use crate::whitespace::{whitespace0, whitespace1};
-use crate::{Expr, FunctionExpr, Literal, NomSqlResult, SqlIdentifier};
+use crate::{Expr, FunctionExpr, Literal, NomSqlResult, OrderClause, SqlIdentifier};
 
 #[cfg(feature = "debug")]
 pub fn debug_print(tag: &str, i: &[u8]) {
@@ -512,13 +513,50 @@ fn function_call(
         let (i, name) = dialect.function_identifier()(i)?;
         let (i, _) = whitespace0(i)?;
         let (i, arguments) = delim_fx_args(dialect)(i)?;
-        Ok((
-            i,
-            FunctionExpr::Call {
+        let (i, window) = opt(preceded(
+            tuple((whitespace1, tag_no_case("over"), whitespace0)),
+            window_spec(dialect),
+        ))(i)?;
+
+        let expr = match window {
+            Some((partition_by, order_by)) => FunctionExpr::Window {
                 name: name.into(),
                 arguments,
+                partition_by,
+                order_by,
             },
-        ))
+            None => FunctionExpr::Call {
+                name: name.into(),
+                arguments,
+            },
+        };
+
+        Ok((i, expr))
+    }
+}
+
+/// Parses the parenthesized window specification following `OVER` in a window function call,
+/// returning the `PARTITION BY` expressions and the `ORDER BY` clause (if any)
+fn window_spec(
+    dialect: Dialect,
+) -> impl Fn(LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], (Vec<Expr>, Option<OrderClause>)> {
+    move |i| {
+        let (i, _) = tag("(")(i)?;
+        let (i, _) = whitespace0(i)?;
+        let (i, partition_by) = opt(preceded(
+            tuple((
+                tag_no_case("partition"),
+                whitespace1,
+                tag_no_case("by"),
+                whitespace1,
+            )),
+            separated_list1(ws_sep_comma, expression(dialect)),
+        ))(i)?;
+        let (i, order_by) = opt(order_clause(dialect))(i)?;
+        let (i, _) = whitespace0(i)?;
+        let (i, _) = tag(")")(i)?;
+
+        Ok((i, (partition_by.unwrap_or_default(), order_by)))
     }
 }
 
@@ -849,7 +887,7 @@ pub fn field_reference_list(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{to_nom_result, SqlType};
+    use crate::{to_nom_result, OrderBy, OrderType, SqlType};
 
     fn test_opt_delimited_fn_call(i: &str) -> IResult<&[u8], &[u8]> {
         opt_delimited(tag("("), tag("abc"), tag(")"))(i.as_bytes())
@@ -1091,6 +1129,48 @@ mod tests {
         );
     }
 
+    #[test]
+    fn window_function() {
+        let res = test_parse!(
+            function_expr(Dialect::MySQL),
+            b"row_number() OVER (PARTITION BY a ORDER BY b DESC)"
+        );
+        assert_eq!(
+            res,
+            FunctionExpr::Window {
+                name: "row_number".into(),
+                arguments: vec![],
+                partition_by: vec![Expr::Column("a".into())],
+                order_by: Some(OrderClause {
+                    order_by: vec![OrderBy {
+                        field: FieldReference::Expr(Expr::Column("b".into())),
+                        order_type: Some(OrderType::OrderDescending),
+                        null_order: None,
+                    }]
+                }),
+            }
+        );
+        assert_eq!(
+            res.display(Dialect::MySQL).to_string(),
+            "row_number() OVER (PARTITION BY `a` ORDER BY `b` DESC)"
+        );
+    }
+
+    #[test]
+    fn window_function_empty_spec() {
+        let res = test_parse!(function_expr(Dialect::MySQL), b"ntile(4) over ( )");
+        assert_eq!(
+            res,
+            FunctionExpr::Window {
+                name: "ntile".into(),
+                arguments: vec![Expr::Literal(4.into())],
+                partition_by: vec![],
+                order_by: None,
+            }
+        );
+        assert_eq!(res.display(Dialect::MySQL).to_string(), "ntile(4) OVER ()");
+    }
+
     #[test]
     fn disallow_trailing_comma_in_column_list() {
         let expected = [
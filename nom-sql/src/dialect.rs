@@ -16,7 +16,9 @@ use readyset_util::fmt::fmt_with;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
-use crate::keywords::{sql_keyword, sql_keyword_or_builtin_function, POSTGRES_NOT_RESERVED};
+use crate::keywords::{
+    postgres_only_keyword, sql_keyword, sql_keyword_or_builtin_function, POSTGRES_NOT_RESERVED,
+};
 use crate::literal::{raw_string_literal, QuotingStyle};
 use crate::select::{LimitClause, LimitValue};
 use crate::whitespace::whitespace0;
@@ -175,7 +177,10 @@ impl Dialect {
                                 Ok(i)
                             }
                         })),
-                        take_while1(is_sql_identifier),
+                        preceded(
+                            not(peek(postgres_only_keyword)),
+                            take_while1(is_sql_identifier),
+                        ),
                     ),
                     |v| {
                         str::from_utf8(&v)
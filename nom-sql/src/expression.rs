@@ -26,7 +26,8 @@ use crate::set::{variable_scope_prefix, Variable};
 use crate::sql_type::{mysql_int_cast_targets, type_identifier};
 use crate::whitespace::{whitespace0, whitespace1};
 use crate::{
-    Column, Dialect, DialectDisplay, Literal, NomSqlResult, SelectStatement, SqlIdentifier, SqlType,
+    Column, Dialect, DialectDisplay, FieldReference, Literal, NomSqlResult, OrderClause,
+    SelectStatement, SqlIdentifier, SqlType,
 };
 
 /// Function call expressions
@@ -76,6 +77,15 @@ pub enum FunctionExpr {
         name: SqlIdentifier,
         arguments: Vec<Expr>,
     },
+
+    /// Window function call expression, eg `row_number() OVER (PARTITION BY x ORDER BY y)`
+    #[weight(0)]
+    Window {
+        name: SqlIdentifier,
+        arguments: Vec<Expr>,
+        partition_by: Vec<Expr>,
+        order_by: Option<OrderClause>,
+    },
 }
 
 impl FunctionExpr {
@@ -99,6 +109,20 @@ impl FunctionExpr {
                     .chain(pos.iter().map(|p| p.as_ref()))
                     .chain(len.iter().map(|p| p.as_ref())))
             }
+            FunctionExpr::Window {
+                arguments,
+                partition_by,
+                order_by,
+                ..
+            } => concrete_iter!(arguments.iter().chain(partition_by).chain(
+                order_by
+                    .iter()
+                    .flat_map(|oc| &oc.order_by)
+                    .filter_map(|ob| match &ob.field {
+                        FieldReference::Expr(expr) => Some(expr),
+                        FieldReference::Numeric(_) => None,
+                    })
+            )),
         }
     }
 }
@@ -154,6 +178,36 @@ impl DialectDisplay for FunctionExpr {
                     write!(f, " for {}", len.display(dialect))?;
                 }
 
+                write!(f, ")")
+            }
+            FunctionExpr::Window {
+                name,
+                arguments,
+                partition_by,
+                order_by,
+            } => {
+                write!(
+                    f,
+                    "{}({}) OVER (",
+                    name,
+                    arguments.iter().map(|arg| arg.display(dialect)).join(", ")
+                )?;
+
+                if !partition_by.is_empty() {
+                    write!(
+                        f,
+                        "PARTITION BY {}",
+                        partition_by.iter().map(|e| e.display(dialect)).join(", ")
+                    )?;
+                    if order_by.is_some() {
+                        write!(f, " ")?;
+                    }
+                }
+
+                if let Some(order_by) = order_by {
+                    write!(f, "{}", order_by.display(dialect))?;
+                }
+
                 write!(f, ")")
             }
         })
@@ -24,6 +24,12 @@ pub struct InsertStatement {
     pub fields: Option<Vec<Column>>,
     pub data: Vec<Vec<Expr>>,
     pub ignore: bool,
+    /// The assignments to make to the conflicting row if the inserted row conflicts with one on a
+    /// unique key, from `ON DUPLICATE KEY UPDATE` in MySQL.
+    ///
+    /// This is always displayed as `ON DUPLICATE KEY UPDATE`, which is also what we parse for
+    /// PostgreSQL. PostgreSQL's own `ON CONFLICT` requires a conflict target, which MySQL's syntax
+    /// doesn't have, so there's no equivalent to display instead.
     pub on_duplicate: Option<Vec<(Column, Expr)>>,
 }
 
@@ -50,7 +56,24 @@ impl DialectDisplay for InsertStatement {
                     .iter()
                     .map(|data| format!("({})", data.iter().map(|l| l.display(dialect)).join(", ")))
                     .join(", ")
-            )
+            )?;
+
+            if let Some(ref on_duplicate) = self.on_duplicate {
+                write!(
+                    f,
+                    " ON DUPLICATE KEY UPDATE {}",
+                    on_duplicate
+                        .iter()
+                        .map(|(col, expr)| format!(
+                            "{} = {}",
+                            col.display(dialect),
+                            expr.display(dialect)
+                        ))
+                        .join(", ")
+                )?;
+            }
+
+            Ok(())
         })
     }
 }
@@ -335,6 +358,17 @@ mod tests {
             let parsed_again = test_parse!(insertion(Dialect::MySQL), stringified.as_bytes());
             assert_eq!(parsed, parsed_again);
         }
+
+        #[test]
+        fn stringify_insert_with_on_dup_update() {
+            let orig = b"INSERT INTO keystores (`key`, `value`) VALUES (?, ?) \
+                         ON DUPLICATE KEY UPDATE `value` = `value` + 1, `key` = ?";
+            let parsed = test_parse!(insertion(Dialect::MySQL), orig);
+            assert!(parsed.on_duplicate.is_some());
+            let stringified = parsed.display(Dialect::MySQL).to_string();
+            let parsed_again = test_parse!(insertion(Dialect::MySQL), stringified.as_bytes());
+            assert_eq!(parsed, parsed_again);
+        }
     }
 
     mod postgres {
@@ -488,6 +522,27 @@ mod tests {
             );
         }
 
+        #[test]
+        fn display_insert_with_on_dup_update() {
+            let stmt = InsertStatement {
+                table: Relation::from("keystores"),
+                fields: Some(vec![Column::from("key"), Column::from("value")]),
+                data: vec![vec![Expr::Literal(1.into()), Expr::Literal(2.into())]],
+                on_duplicate: Some(vec![(Column::from("value"), Expr::Literal(3.into()))]),
+                ignore: false,
+            };
+            let displayed = stmt.display(Dialect::PostgreSQL).to_string();
+            assert_eq!(
+                displayed,
+                "INSERT INTO \"keystores\" (\"key\", \"value\") VALUES (1, 2) \
+                 ON DUPLICATE KEY UPDATE \"value\" = 3"
+            );
+            assert_eq!(
+                test_parse!(insertion(Dialect::PostgreSQL), displayed.as_bytes()),
+                stmt
+            );
+        }
+
         #[test]
         fn insert_with_leading_value_whitespace() {
             let qstring = "INSERT INTO users (id, name) VALUES ( 42, 'test');";
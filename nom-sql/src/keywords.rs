@@ -237,6 +237,15 @@ pub fn sql_builtin_function(i: LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], &[u8]>
     builtin_function_a_to_z(i)
 }
 
+// Matches any keyword which is reserved in PostgreSQL, but not in MySQL, and so is only excluded
+// from PostgreSQL identifiers
+pub fn postgres_only_keyword(i: LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], &[u8]> {
+    map(
+        terminated(tag_no_case("TABLESAMPLE"), keyword_follow_char),
+        |i| *i,
+    )(i)
+}
+
 // Matches any SQL reserved keyword _or_ built-in function
 pub fn sql_keyword_or_builtin_function(i: LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], &[u8]> {
     alt((sql_keyword, sql_builtin_function))(i)
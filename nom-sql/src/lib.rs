@@ -56,8 +56,8 @@ pub use self::show::ShowStatement;
 pub use self::sql_identifier::SqlIdentifier;
 pub use self::sql_type::{EnumVariants, SqlType, SqlTypeArbitraryOptions};
 pub use self::table::{
-    replicator_table_list, NonReplicatedRelation, NotReplicatedReason, Relation, TableExpr,
-    TableExprInner,
+    replicator_table_list, NonReplicatedRelation, NotReplicatedReason, Relation, SampleMethod,
+    TableExpr, TableExprInner, TableSample,
 };
 pub use self::transaction::StartTransactionStatement;
 pub use self::update::UpdateStatement;
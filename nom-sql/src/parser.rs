@@ -864,6 +864,7 @@ mod tests {
                         inner: TableExprInner::Table("t1".into()),
                         alias: None,
                         index_hint: None,
+                        sample: None,
                     }],
                     ..Default::default()
                 })
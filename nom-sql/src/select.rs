@@ -36,19 +36,25 @@ use crate::{
 )]
 pub struct GroupByClause {
     pub fields: Vec<FieldReference>,
+    /// Whether the clause ends with `WITH ROLLUP`, requesting super-aggregate rows at each
+    /// grouping level. PostgreSQL spells this `GROUP BY ROLLUP (...)`, which is only supported
+    /// when the rollup covers the whole clause
+    pub with_rollup: bool,
 }
 
 impl DialectDisplay for GroupByClause {
     fn display(&self, dialect: Dialect) -> impl fmt::Display + '_ {
         fmt_with(move |f| {
-            write!(
-                f,
-                "GROUP BY {}",
-                self.fields
-                    .iter()
-                    .map(|field| field.display(dialect))
-                    .join(", ")
-            )
+            let fields = self
+                .fields
+                .iter()
+                .map(|field| field.display(dialect))
+                .join(", ");
+            match (self.with_rollup, dialect) {
+                (false, _) => write!(f, "GROUP BY {fields}"),
+                (true, Dialect::MySQL) => write!(f, "GROUP BY {fields} WITH ROLLUP"),
+                (true, Dialect::PostgreSQL) => write!(f, "GROUP BY ROLLUP ({fields})"),
+            }
         })
     }
 }
@@ -258,6 +264,17 @@ impl DialectDisplay for LimitClause {
                         write!(f, "OFFSET {}", offset.display(dialect))?;
                     }
                 }
+                // PostgreSQL has no `LIMIT <offset>, <limit>` syntax
+                LimitClause::OffsetCommaLimit { offset, limit }
+                    if dialect == Dialect::PostgreSQL =>
+                {
+                    write!(
+                        f,
+                        "LIMIT {} OFFSET {}",
+                        limit.display(dialect),
+                        offset.display(dialect)
+                    )?;
+                }
                 LimitClause::OffsetCommaLimit { offset, limit } => {
                     write!(
                         f,
@@ -309,6 +326,14 @@ impl SelectStatement {
             _ => false,
         })
     }
+
+    /// Format this statement as SQL which can be sent to a PostgreSQL upstream, with
+    /// double-quoted identifiers and PostgreSQL syntax for clauses such as `LIMIT` and `OFFSET`.
+    ///
+    /// This is equivalent to `self.display(Dialect::PostgreSQL).to_string()`
+    pub fn format_pg(&self) -> String {
+        self.display(Dialect::PostgreSQL).to_string()
+    }
 }
 
 impl DialectDisplay for SelectStatement {
@@ -388,8 +413,40 @@ pub fn group_by_clause(
         let (i, _) = whitespace1(i)?;
         let (i, _) = tag_no_case("by")(i)?;
         let (i, _) = whitespace1(i)?;
-        let (i, fields) = field_reference_list(dialect)(i)?;
-        Ok((i, GroupByClause { fields }))
+        let (i, (fields, with_rollup)) = match dialect {
+            Dialect::MySQL => {
+                let (i, fields) = field_reference_list(dialect)(i)?;
+                let (i, with_rollup) = opt(tuple((
+                    whitespace1,
+                    tag_no_case("with"),
+                    whitespace1,
+                    tag_no_case("rollup"),
+                )))(i)?;
+                (i, (fields, with_rollup.is_some()))
+            }
+            // Try `ROLLUP (...)` first, since it would otherwise parse as a function call
+            Dialect::PostgreSQL => alt((
+                map(
+                    preceded(
+                        terminated(tag_no_case("rollup"), whitespace0),
+                        delimited(
+                            terminated(tag("("), whitespace0),
+                            field_reference_list(dialect),
+                            preceded(whitespace0, tag(")")),
+                        ),
+                    ),
+                    |fields| (fields, true),
+                ),
+                map(field_reference_list(dialect), |fields| (fields, false)),
+            ))(i)?,
+        };
+        Ok((
+            i,
+            GroupByClause {
+                fields,
+                with_rollup,
+            },
+        ))
     }
 }
 
@@ -972,6 +1029,7 @@ mod tests {
                     }),
                     alias: Some("t".into()),
                     index_hint: None,
+                    sample: None,
                 }],
                 fields: vec![FieldDefinitionExpr::All],
                 ..Default::default()
@@ -996,6 +1054,7 @@ mod tests {
                     }),
                     alias: Some("t".into()),
                     index_hint: None,
+                    sample: None,
                 },],
                 fields: vec![FieldDefinitionExpr::All],
                 ..Default::default()
@@ -1215,6 +1274,7 @@ mod tests {
             fields: vec![FieldDefinitionExpr::from(Expr::Call(agg_expr))],
             group_by: Some(GroupByClause {
                 fields: vec![FieldReference::Expr(Expr::Column(Column::from("aid")))],
+                with_rollup: false,
             }),
             ..Default::default()
         };
@@ -1235,6 +1295,7 @@ mod tests {
             fields: vec![FieldDefinitionExpr::from(Expr::Call(agg_expr))],
             group_by: Some(GroupByClause {
                 fields: vec![FieldReference::Expr(Expr::Column(Column::from("aid")))],
+                with_rollup: false,
             }),
             ..Default::default()
         };
@@ -1267,6 +1328,7 @@ mod tests {
             fields: vec![FieldDefinitionExpr::from(Expr::Call(agg_expr))],
             group_by: Some(GroupByClause {
                 fields: vec![FieldReference::Expr(Expr::Column(Column::from("aid")))],
+                with_rollup: false,
             }),
             ..Default::default()
         };
@@ -1299,6 +1361,7 @@ mod tests {
             fields: vec![FieldDefinitionExpr::from(Expr::Call(agg_expr))],
             group_by: Some(GroupByClause {
                 fields: vec![FieldReference::Expr(Expr::Column(Column::from("aid")))],
+                with_rollup: false,
             }),
             ..Default::default()
         };
@@ -1332,6 +1395,7 @@ mod tests {
             fields: vec![FieldDefinitionExpr::from(Expr::Call(agg_expr))],
             group_by: Some(GroupByClause {
                 fields: vec![FieldReference::Expr(Expr::Column(Column::from("aid")))],
+                with_rollup: false,
             }),
             ..Default::default()
         };
@@ -1380,6 +1444,7 @@ mod tests {
                 fields: vec![FieldReference::Expr(Expr::Column(Column::from(
                     "votes.comment_id",
                 )))],
+                with_rollup: false,
             }),
             ..Default::default()
         };
@@ -1698,6 +1763,7 @@ mod tests {
                     })),
                     alias: Some("sq".into()),
                     index_hint: None,
+                    sample: None,
                 }],
                 fields: columns(&["x"]),
                 where_clause: Some(Expr::BinaryOp {
@@ -1741,6 +1807,7 @@ mod tests {
                     inner: TableExprInner::Subquery(Box::new(inner_select)),
                     alias: Some("ids".into()),
                     index_hint: None,
+                    sample: None,
                 }),
                 constraint: JoinConstraint::On(Expr::BinaryOp {
                     lhs: Box::new(Expr::Column(Column::from("orders.o_id"))),
@@ -1997,11 +2064,26 @@ mod tests {
             assert_eq!(
                 res.group_by,
                 Some(GroupByClause {
-                    fields: vec![FieldReference::Numeric(1)]
+                    fields: vec![FieldReference::Numeric(1)],
+                    with_rollup: false,
                 })
             )
         }
 
+        #[test]
+        fn group_by_with_rollup() {
+            let res = test_parse!(
+                selection(Dialect::MySQL),
+                b"SELECT a, b, count(*) FROM t GROUP BY a, b WITH ROLLUP"
+            );
+            let group_by = res.group_by.unwrap();
+            assert!(group_by.with_rollup);
+            assert_eq!(
+                group_by.display(Dialect::MySQL).to_string(),
+                "GROUP BY `a`, `b` WITH ROLLUP"
+            );
+        }
+
         #[test]
         fn order_by_column_number() {
             let res = test_parse!(selection(Dialect::MySQL), b"SELECT id FROM t ORDER BY 1");
@@ -2099,6 +2181,7 @@ mod tests {
                             inner: TableExprInner::Table("t2".into()),
                             alias: None,
                             index_hint: None,
+                            sample: None,
                         }),
                         constraint: JoinConstraint::On(Expr::Column("x".into()))
                     },
@@ -2108,19 +2191,30 @@ mod tests {
                             inner: TableExprInner::Table("t3".into()),
                             alias: None,
                             index_hint: None,
+                            sample: None,
                         }),
                         constraint: JoinConstraint::On(Expr::Column("z".into()))
                     },
                 ]
             )
         }
+
+        #[test]
+        fn tablesample_is_not_a_keyword() {
+            let stmt = test_parse!(
+                selection(Dialect::MySQL),
+                b"SELECT * FROM t tablesample WHERE tablesample.a = 1"
+            );
+            assert_eq!(stmt.tables[0].alias, Some("tablesample".into()));
+            assert_eq!(stmt.tables[0].sample, None);
+        }
     }
 
     mod postgres {
         use super::*;
         use crate::column::Column;
         use crate::common::FieldDefinitionExpr;
-        use crate::table::Relation;
+        use crate::table::{Relation, SampleMethod, TableSample};
         use crate::{BinaryOperator, Double, Expr, FunctionExpr, InValue};
 
         test_format_parse_round_trip!(
@@ -2128,6 +2222,33 @@ mod tests {
             rt_join_rhs(join_rhs, JoinRightSide, Dialect::PostgreSQL);
         );
 
+        #[test]
+        fn group_by_with_rollup() {
+            test_parse_expect_err!(
+                selection(Dialect::PostgreSQL),
+                b"SELECT a, count(*) FROM t GROUP BY a WITH ROLLUP"
+            );
+
+            let group_by = GroupByClause {
+                fields: vec![FieldReference::Expr(Expr::Column("a".into()))],
+                with_rollup: true,
+            };
+            assert_eq!(
+                group_by.display(Dialect::PostgreSQL).to_string(),
+                "GROUP BY ROLLUP (\"a\")"
+            );
+
+            let res = test_parse!(
+                selection(Dialect::PostgreSQL),
+                b"SELECT a, count(*) FROM t GROUP BY ROLLUP(a)"
+            );
+            assert_eq!(res.group_by, Some(group_by));
+
+            let displayed = res.display(Dialect::PostgreSQL).to_string();
+            let reparsed = test_parse!(selection(Dialect::PostgreSQL), displayed.as_bytes());
+            assert_eq!(reparsed, res);
+        }
+
         #[test]
         fn alias_generic_function() {
             let qstr = "SELECT id, coalesce(a, 'b',c) AS created_day FROM users;";
@@ -2301,6 +2422,7 @@ mod tests {
                     }),
                     alias: None,
                     index_hint: None,
+                    sample: None,
                 }]
             );
         }
@@ -2501,5 +2623,85 @@ mod tests {
 
             test_parse_expect_err!(selection(Dialect::PostgreSQL), qstr.as_bytes());
         }
+
+        /// Format `stmt` with [`SelectStatement::format_pg`], check that the result is accepted
+        /// by `sqlparser`'s PostgreSQL parser, and that formatting `sqlparser`'s AST back to SQL
+        /// and re-parsing it yields the same statement
+        fn check_format_pg_round_trip(stmt: SelectStatement) {
+            let formatted = stmt.format_pg();
+            let parsed = sqlparser::parser::Parser::parse_sql(
+                &sqlparser::dialect::PostgreSqlDialect {},
+                &formatted,
+            )
+            .unwrap_or_else(|e| panic!("Failed to parse {formatted}: {e}"));
+            assert_eq!(parsed.len(), 1);
+
+            let reformatted = parsed[0].to_string();
+            let round_trip = test_parse!(selection(Dialect::PostgreSQL), reformatted.as_bytes());
+            assert_eq!(round_trip, stmt, "{formatted} != {reformatted}");
+        }
+
+        #[test]
+        fn format_pg_round_trip() {
+            for qstr in [
+                "SELECT id, name FROM users WHERE name ILIKE $1 ORDER BY id DESC LIMIT 10 OFFSET 5",
+                "SELECT DISTINCT \"Mixed Case\" FROM \"Table\" WHERE x NOT ILIKE 'a%'",
+                "SELECT u.id, count(*) FROM users AS u JOIN posts AS p ON u.id = p.author_id \
+                 GROUP BY u.id HAVING count(*) > 1",
+                "SELECT x FROM t WHERE y IN (SELECT y FROM s WHERE z = $1) LIMIT 1",
+            ] {
+                check_format_pg_round_trip(test_parse!(
+                    selection(Dialect::PostgreSQL),
+                    qstr.as_bytes()
+                ));
+            }
+        }
+
+        #[test]
+        fn format_pg_from_mysql() {
+            let stmt = test_parse!(
+                selection(Dialect::MySQL),
+                b"SELECT `a` FROM `t` WHERE `b` = ? LIMIT 5, 10"
+            );
+            assert_eq!(
+                stmt.format_pg(),
+                "SELECT \"a\" FROM \"t\" WHERE (\"b\" = ?) LIMIT 10 OFFSET 5"
+            );
+        }
+
+        #[test]
+        fn table_sample() {
+            let stmt = test_parse!(
+                selection(Dialect::PostgreSQL),
+                b"SELECT * FROM t AS x TABLESAMPLE BERNOULLI (10) REPEATABLE (42) WHERE x.a = 1"
+            );
+            let table = &stmt.tables[0];
+            assert_eq!(table.alias, Some("x".into()));
+            assert_eq!(
+                table.sample,
+                Some(TableSample {
+                    method: SampleMethod::Bernoulli,
+                    percent: Literal::Integer(10),
+                    repeatable: Some(Literal::Integer(42)),
+                })
+            );
+            assert_eq!(
+                stmt.display(Dialect::PostgreSQL).to_string(),
+                "SELECT * FROM \"t\" AS \"x\" TABLESAMPLE BERNOULLI (10) REPEATABLE (42) \
+                 WHERE (\"x\".\"a\" = 1)"
+            );
+        }
+
+        #[test]
+        fn table_sample_system_no_alias() {
+            let stmt = test_parse!(
+                selection(Dialect::PostgreSQL),
+                b"SELECT * FROM t tablesample system(5.5)"
+            );
+            let sample = stmt.tables[0].sample.as_ref().unwrap();
+            assert_eq!(stmt.tables[0].alias, None);
+            assert_eq!(sample.method, SampleMethod::System);
+            assert_eq!(sample.repeatable, None);
+        }
     }
 }
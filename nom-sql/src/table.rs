@@ -3,10 +3,10 @@ use std::hash::{Hash, Hasher};
 use std::{fmt, str};
 
 use nom::branch::alt;
-use nom::bytes::complete::tag;
-use nom::combinator::{map, opt};
+use nom::bytes::complete::{tag, tag_no_case};
+use nom::combinator::{map, opt, value};
 use nom::multi::separated_list1;
-use nom::sequence::terminated;
+use nom::sequence::{delimited, preceded, terminated, tuple};
 use nom_locate::LocatedSpan;
 use readyset_util::fmt::fmt_with;
 use serde::{Deserialize, Serialize};
@@ -14,9 +14,10 @@ use test_strategy::Arbitrary;
 
 use crate::common::{as_alias, ws_sep_comma};
 use crate::index_hint::{index_hint_list, IndexHint};
+use crate::literal::literal;
 use crate::select::nested_selection;
-use crate::whitespace::whitespace0;
-use crate::{Dialect, DialectDisplay, NomSqlResult, SelectStatement, SqlIdentifier};
+use crate::whitespace::{whitespace0, whitespace1};
+use crate::{Dialect, DialectDisplay, Literal, NomSqlResult, SelectStatement, SqlIdentifier};
 
 /// A (potentially schema-qualified) name for a relation
 ///
@@ -134,12 +135,66 @@ impl DialectDisplay for TableExprInner {
     }
 }
 
-/// An expression for a table in the `FROM` clause of a query, with optional alias and index hint
+/// The sampling method named in a `TABLESAMPLE` clause
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, Arbitrary,
+)]
+pub enum SampleMethod {
+    /// Each row is independently included with the given probability
+    Bernoulli,
+    /// Each storage block is independently included with the given probability
+    System,
+}
+
+impl Display for SampleMethod {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            SampleMethod::Bernoulli => write!(f, "BERNOULLI"),
+            SampleMethod::System => write!(f, "SYSTEM"),
+        }
+    }
+}
+
+/// A `TABLESAMPLE <method> (<percent>) [REPEATABLE (<seed>)]` clause attached to a table in the
+/// `FROM` clause of a query
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, Arbitrary)]
+pub struct TableSample {
+    pub method: SampleMethod,
+    /// The percentage of the table to sample
+    pub percent: Literal,
+    /// The seed passed to `REPEATABLE`, if any
+    pub repeatable: Option<Literal>,
+}
+
+impl DialectDisplay for TableSample {
+    fn display(&self, dialect: Dialect) -> impl Display + '_ {
+        fmt_with(move |f| {
+            write!(
+                f,
+                "TABLESAMPLE {} ({})",
+                self.method,
+                self.percent.display(dialect)
+            )?;
+
+            if let Some(seed) = &self.repeatable {
+                write!(f, " REPEATABLE ({})", seed.display(dialect))?;
+            }
+
+            Ok(())
+        })
+    }
+}
+
+/// An expression for a table in the `FROM` clause of a query, with optional alias, index hint and
+/// sample clause
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, Arbitrary)]
 pub struct TableExpr {
     pub inner: TableExprInner,
     pub alias: Option<SqlIdentifier>,
     pub index_hint: Option<IndexHint>,
+    // TODO: generate once TABLESAMPLE is supported beyond parsing
+    #[strategy(proptest::strategy::Just(None))]
+    pub sample: Option<TableSample>,
 }
 
 /// Constructs a [`TableExpr`] with no alias
@@ -149,6 +204,7 @@ impl From<Relation> for TableExpr {
             inner: TableExprInner::Table(table),
             alias: None,
             index_hint: None,
+            sample: None,
         }
     }
 }
@@ -162,6 +218,10 @@ impl DialectDisplay for TableExpr {
                 write!(f, " AS {}", dialect.quote_identifier(alias))?;
             }
 
+            if let Some(sample) = &self.sample {
+                write!(f, " {}", sample.display(dialect))?;
+            }
+
             Ok(())
         })
     }
@@ -210,6 +270,47 @@ fn table_expr_inner(
     }
 }
 
+fn parenthesized_literal(
+    dialect: Dialect,
+) -> impl Fn(LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], Literal> {
+    move |i| {
+        delimited(
+            terminated(tag("("), whitespace0),
+            literal(dialect),
+            preceded(whitespace0, tag(")")),
+        )(i)
+    }
+}
+
+fn table_sample(
+    dialect: Dialect,
+) -> impl Fn(LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], TableSample> {
+    move |i| {
+        let (i, _) = whitespace1(i)?;
+        let (i, _) = tag_no_case("tablesample")(i)?;
+        let (i, _) = whitespace1(i)?;
+        let (i, method) = alt((
+            value(SampleMethod::Bernoulli, tag_no_case("bernoulli")),
+            value(SampleMethod::System, tag_no_case("system")),
+        ))(i)?;
+        let (i, _) = whitespace0(i)?;
+        let (i, percent) = parenthesized_literal(dialect)(i)?;
+        let (i, repeatable) = opt(preceded(
+            tuple((whitespace1, tag_no_case("repeatable"), whitespace0)),
+            parenthesized_literal(dialect),
+        ))(i)?;
+
+        Ok((
+            i,
+            TableSample {
+                method,
+                percent,
+                repeatable,
+            },
+        ))
+    }
+}
+
 pub fn table_expr(
     dialect: Dialect,
 ) -> impl Fn(LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], TableExpr> {
@@ -217,6 +318,11 @@ pub fn table_expr(
         let (i, inner) = table_expr_inner(dialect)(i)?;
         let (i, alias) = opt(as_alias(dialect))(i)?;
         let (i, _) = opt(index_hint_list(dialect))(i)?;
+        // TABLESAMPLE is only a (reserved) keyword in PostgreSQL - in MySQL, it's a valid alias
+        let (i, sample) = match dialect {
+            Dialect::PostgreSQL => opt(table_sample(dialect))(i)?,
+            Dialect::MySQL => (i, None),
+        };
         Ok((
             i,
             TableExpr {
@@ -225,6 +331,7 @@ pub fn table_expr(
                 // We don't actually care about the index list.
                 // Just parse it and always return an empty object.
                 index_hint: None,
+                sample,
             },
         ))
     }
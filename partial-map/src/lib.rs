@@ -111,6 +111,23 @@ impl<K, V> PartialMap<K, V> {
         self.map.keys()
     }
 
+    /// Returns an iterator, in ascending order, over the key-value pairs in this map whose keys are
+    /// greater than `after`, or over all of them if `after` is `None`
+    ///
+    /// Like [`Self::iter`], this does *not* consider ranges, so unlike [`Self::range`] it never
+    /// misses - only the keys which are actually present in the map are returned.
+    pub fn iter_after<Q>(&self, after: Option<&Q>) -> Range<K, V>
+    where
+        K: Borrow<Q> + Ord,
+        Q: Ord + ?Sized,
+    {
+        let start = match after {
+            Some(after) => std::ops::Bound::Excluded(after),
+            None => std::ops::Bound::Unbounded,
+        };
+        self.map.range::<Q, _>((start, std::ops::Bound::Unbounded))
+    }
+
     /// Returns an iterator over the key-value pairs in the map
     ///
     /// Note that this does *not* consider ranges, since iteration is not well-defined for certain
@@ -0,0 +1,497 @@
+//! Persisting generated queries, along with the exact data and lookup keys generated for them, to
+//! disk.
+//!
+//! Reproducing a failure found with a generated query requires the exact data that was generated
+//! for it, which can't necessarily be recreated from just the [`QuerySeed`] if the generation code
+//! has changed since the failing run. An [`Artifact`] records everything needed to replay a query -
+//! the DDL for the tables, the query itself, the rows in each table, and the keys that were looked
+//! up - in a compact binary format.
+//!
+//! # Format
+//!
+//! Artifacts are written using [`bincode`], prefixed with a header containing a magic number and
+//! the [`ARTIFACT_VERSION`]. Rather than storing each row as a map from column name to value, each
+//! table stores a manifest of its column names, in order, and rows are stored as positional lists
+//! of values in that order.
+//!
+//! [`QuerySeed`]: crate::QuerySeed
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use anyhow::{anyhow, bail, Context};
+use database_utils::{DatabaseConnection, QueryableConnection};
+use nom_sql::{
+    Column, CreateTableStatement, Dialect, DialectDisplay, Expr, InsertStatement, ItemPlaceholder,
+    Literal, SelectStatement,
+};
+use readyset_data::DfValue;
+use serde::{Deserialize, Serialize};
+
+use crate::{ColumnName, Query, TableName};
+
+/// Magic number written at the start of every artifact file
+const MAGIC: [u8; 4] = *b"QGAF";
+
+/// The version of the artifact format written by this version of the query generator.
+///
+/// This must be incremented whenever the format of artifacts changes in a backwards-incompatible
+/// way, including changes to the serialized representation of the query AST.
+pub const ARTIFACT_VERSION: u32 = 2;
+
+#[derive(Serialize, Deserialize)]
+struct Header {
+    magic: [u8; 4],
+    version: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct EncodedTable {
+    create_table: CreateTableStatement,
+    /// The names of the columns in the table, in the order values are stored in [`Self::rows`]
+    columns: Vec<ColumnName>,
+    rows: Vec<Vec<DfValue>>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct EncodedArtifact {
+    tables: Vec<EncodedTable>,
+    query: SelectStatement,
+    keys: Vec<Vec<DfValue>>,
+    expects_empty_results: bool,
+}
+
+/// A generated query, along with the tables it was run against, the data in those tables, and the
+/// keys that were used to look up the query.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Artifact {
+    /// `CREATE TABLE` statements for all the tables in the schema
+    pub create_tables: Vec<CreateTableStatement>,
+    /// The query itself
+    pub query: SelectStatement,
+    /// The rows in each table, as a map from column name to value
+    pub data: HashMap<TableName, Vec<HashMap<ColumnName, DfValue>>>,
+    /// The keys to look up the query with, one set of values per execution of the query
+    pub keys: Vec<Vec<DfValue>>,
+    /// Whether the query must return no rows when looked up with [`Self::keys`]. See
+    /// [`QueryState::expects_empty_results`](crate::QueryState::expects_empty_results).
+    pub expects_empty_results: bool,
+}
+
+impl Artifact {
+    /// Construct a new [`Artifact`] for the given query, generated data, and keys
+    pub fn new(
+        query: &Query,
+        data: &HashMap<TableName, Vec<HashMap<ColumnName, DfValue>>>,
+        keys: &[Vec<DfValue>],
+    ) -> Self {
+        Self {
+            create_tables: query.state.gen.ddl().collect(),
+            query: query.statement.clone(),
+            data: data.clone(),
+            keys: keys.to_vec(),
+            expects_empty_results: query.state.expects_empty_results(),
+        }
+    }
+
+    /// Write this artifact to the given writer
+    pub fn write<W: Write>(&self, mut writer: W) -> anyhow::Result<()> {
+        let mut data = self.data.iter().collect::<HashMap<_, _>>();
+        let tables = self
+            .create_tables
+            .iter()
+            .map(|create_table| -> anyhow::Result<EncodedTable> {
+                let columns = create_table
+                    .body
+                    .as_ref()
+                    .map_err(|unparsed| {
+                        anyhow!("Cannot write unparsed CREATE TABLE statement: {unparsed}")
+                    })?
+                    .fields
+                    .iter()
+                    .map(|field| ColumnName::from(&field.column.name))
+                    .collect::<Vec<_>>();
+                let table_name = TableName::from(&create_table.table.name);
+                let rows = data
+                    .remove(&table_name)
+                    .into_iter()
+                    .flatten()
+                    .map(|row| -> anyhow::Result<Vec<DfValue>> {
+                        if row.len() != columns.len() {
+                            bail!(
+                                "Row for table {table_name} has {} columns, but the table has {}",
+                                row.len(),
+                                columns.len()
+                            );
+                        }
+                        columns
+                            .iter()
+                            .map(|col| {
+                                row.get(col).cloned().ok_or_else(|| {
+                                    anyhow!("Row for table {table_name} is missing column {col}")
+                                })
+                            })
+                            .collect()
+                    })
+                    .collect::<anyhow::Result<Vec<_>>>()?;
+                Ok(EncodedTable {
+                    create_table: create_table.clone(),
+                    columns,
+                    rows,
+                })
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        if let Some(table_name) = data.keys().next() {
+            bail!("Data was given for table {table_name}, which does not exist in the schema");
+        }
+
+        bincode::serialize_into(
+            &mut writer,
+            &Header {
+                magic: MAGIC,
+                version: ARTIFACT_VERSION,
+            },
+        )?;
+        bincode::serialize_into(
+            &mut writer,
+            &EncodedArtifact {
+                tables,
+                query: self.query.clone(),
+                keys: self.keys.clone(),
+                expects_empty_results: self.expects_empty_results,
+            },
+        )?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Read an artifact previously written with [`Artifact::write`] from the given reader
+    pub fn read<R: Read>(mut reader: R) -> anyhow::Result<Self> {
+        let header: Header =
+            bincode::deserialize_from(&mut reader).context("Reading artifact header")?;
+        if header.magic != MAGIC {
+            bail!("Not a query generator artifact");
+        }
+        if header.version != ARTIFACT_VERSION {
+            bail!(
+                "Unsupported artifact version {} (expected {ARTIFACT_VERSION})",
+                header.version
+            );
+        }
+
+        let encoded: EncodedArtifact = bincode::deserialize_from(reader)?;
+        let mut create_tables = Vec::with_capacity(encoded.tables.len());
+        let mut data = HashMap::new();
+        for EncodedTable {
+            create_table,
+            columns,
+            rows,
+        } in encoded.tables
+        {
+            let table_name = TableName::from(&create_table.table.name);
+            let rows = rows
+                .into_iter()
+                .map(|row| -> anyhow::Result<HashMap<ColumnName, DfValue>> {
+                    if row.len() != columns.len() {
+                        bail!(
+                            "Row for table {table_name} has {} values, but the table has {} \
+                             columns",
+                            row.len(),
+                            columns.len()
+                        );
+                    }
+                    Ok(columns.iter().cloned().zip(row).collect())
+                })
+                .collect::<anyhow::Result<Vec<_>>>()?;
+            if !rows.is_empty() {
+                data.insert(table_name, rows);
+            }
+            create_tables.push(create_table);
+        }
+
+        Ok(Self {
+            create_tables,
+            query: encoded.query,
+            data,
+            keys: encoded.keys,
+            expects_empty_results: encoded.expects_empty_results,
+        })
+    }
+
+    /// Replay this artifact against the given database connection, by creating all the tables,
+    /// inserting all the data, and then executing the query once for each key.
+    ///
+    /// Returns the results of executing the query for each key, in the same order as
+    /// [`Self::keys`]. If the artifact has no keys, the query is executed once with no
+    /// parameters.
+    pub async fn replay(
+        &self,
+        conn: &mut DatabaseConnection,
+        dialect: Dialect,
+    ) -> anyhow::Result<Vec<Vec<Vec<DfValue>>>> {
+        self.load(conn, dialect).await?;
+        self.run_query(conn, dialect).await
+    }
+
+    /// Create all the tables in this artifact in the database at the given connection, and insert
+    /// all of the artifact's data into them.
+    pub async fn load(
+        &self,
+        conn: &mut DatabaseConnection,
+        dialect: Dialect,
+    ) -> anyhow::Result<()> {
+        for create_table in &self.create_tables {
+            let create_table = create_table.display(dialect).to_string();
+            conn.query_drop(create_table.as_str())
+                .await
+                .with_context(|| format!("Creating table: {create_table}"))?;
+        }
+
+        for create_table in &self.create_tables {
+            let table_name = TableName::from(&create_table.table.name);
+            let Some(rows) = self.data.get(&table_name) else {
+                continue;
+            };
+            for row in rows {
+                let (fields, values): (Vec<_>, Vec<_>) = row
+                    .iter()
+                    .map(|(col, val)| (Column::from(col.clone()), val.clone()))
+                    .unzip();
+                let insert = InsertStatement {
+                    table: create_table.table.clone(),
+                    data: vec![(0..values.len()).map(|i| placeholder(dialect, i)).collect()],
+                    fields: Some(fields),
+                    ignore: false,
+                    on_duplicate: None,
+                }
+                .display(dialect)
+                .to_string();
+                conn.execute(&insert, values)
+                    .await
+                    .with_context(|| format!("Inserting into {table_name}"))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Execute this artifact's query against the given database connection once for each key,
+    /// returning the results for each key in the same order as [`Self::keys`]. If the artifact has
+    /// no keys, the query is executed once with no parameters.
+    pub async fn run_query(
+        &self,
+        conn: &mut DatabaseConnection,
+        dialect: Dialect,
+    ) -> anyhow::Result<Vec<Vec<Vec<DfValue>>>> {
+        let query = self.query.display(dialect).to_string();
+        let mut results: Vec<Vec<Vec<DfValue>>> = vec![];
+        if self.keys.is_empty() {
+            results.push(conn.query(query.as_str()).await?.try_into()?);
+        } else {
+            for key in &self.keys {
+                results.push(
+                    conn.execute(&query, key.clone())
+                        .await
+                        .with_context(|| format!("Running query {query} with key {key:?}"))?
+                        .try_into()?,
+                );
+            }
+        }
+
+        Ok(results)
+    }
+}
+
+fn placeholder(dialect: Dialect, idx: usize) -> Expr {
+    Expr::Literal(Literal::Placeholder(match dialect {
+        Dialect::MySQL => ItemPlaceholder::QuestionMark,
+        Dialect::PostgreSQL => ItemPlaceholder::DollarNumber(idx as u32 + 1),
+    }))
+}
+
+/// Write an [`Artifact`] for the given query, generated data, and lookup keys to a file at the
+/// given path
+pub fn write_artifact<P: AsRef<Path>>(
+    path: P,
+    query: &Query,
+    data: &HashMap<TableName, Vec<HashMap<ColumnName, DfValue>>>,
+    keys: &[Vec<DfValue>],
+) -> anyhow::Result<()> {
+    let path = path.as_ref();
+    let file =
+        File::create(path).with_context(|| format!("Creating artifact file {}", path.display()))?;
+    Artifact::new(query, data, keys).write(BufWriter::new(file))
+}
+
+/// Read an [`Artifact`] previously written with [`write_artifact`] from the file at the given path
+pub fn read_artifact<P: AsRef<Path>>(path: P) -> anyhow::Result<Artifact> {
+    let path = path.as_ref();
+    let file =
+        File::open(path).with_context(|| format!("Opening artifact file {}", path.display()))?;
+    Artifact::read(BufReader::new(file))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use bit_vec::BitVec;
+    use chrono::{FixedOffset, NaiveDate, TimeZone};
+    use nom_sql::SqlType;
+    use rust_decimal::Decimal;
+
+    use super::*;
+    use crate::{GeneratorState, QueryOperation, QuerySeed};
+
+    /// Values of (almost) every variant of [`DfValue`]
+    fn mixed_values() -> Vec<DfValue> {
+        vec![
+            DfValue::None,
+            DfValue::Int(-12),
+            DfValue::UnsignedInt(34),
+            DfValue::Float(1.5),
+            DfValue::Double(-2.25),
+            DfValue::from("a longer piece of text which doesn't fit inline"),
+            DfValue::from("tiny"),
+            DfValue::from(
+                NaiveDate::from_ymd_opt(2022, 3, 4)
+                    .unwrap()
+                    .and_hms_micro_opt(5, 6, 7, 891011)
+                    .unwrap(),
+            ),
+            DfValue::from(
+                FixedOffset::east_opt(3600)
+                    .unwrap()
+                    .with_ymd_and_hms(2020, 1, 2, 3, 4, 5)
+                    .unwrap(),
+            ),
+            DfValue::from(NaiveDate::from_ymd_opt(1999, 12, 31).unwrap()),
+            DfValue::from(chrono::NaiveTime::from_hms_micro_opt(12, 34, 56, 789).unwrap()),
+            DfValue::ByteArray(Arc::new(vec![0, 1, 2, 255])),
+            DfValue::from(Decimal::new(12345, 2)),
+            DfValue::from(BitVec::from_bytes(&[0b1010_0101])),
+            DfValue::from(vec![DfValue::from(1), DfValue::from(2)]),
+        ]
+    }
+
+    fn generate_artifact(gen: &mut GeneratorState, rows_per_table: usize) -> Artifact {
+        let table_name = {
+            let table = gen.fresh_table_mut();
+            for sql_type in [SqlType::Int(None), SqlType::Text, SqlType::Timestamp] {
+                table.fresh_column_with_type(sql_type);
+            }
+            table.name.clone()
+        };
+        let mut query = gen.generate_query(QuerySeed::new(
+            vec![QueryOperation::SingleParameter],
+            vec![],
+        ));
+        let key = query.state.key();
+
+        let values = mixed_values();
+        let data = query
+            .state
+            .generate_data(rows_per_table, false, false)
+            .into_iter()
+            .chain([(table_name.clone(), vec![])])
+            .map(|(name, mut rows)| {
+                if name == table_name {
+                    let columns = query.state.gen.table(&name).unwrap().column_order.clone();
+                    rows = (0..rows_per_table)
+                        .map(|i| {
+                            columns
+                                .iter()
+                                .enumerate()
+                                .map(|(j, col)| {
+                                    (col.clone(), values[(i + j) % values.len()].clone())
+                                })
+                                .collect()
+                        })
+                        .collect();
+                }
+                (name, rows)
+            })
+            .collect();
+
+        Artifact::new(&query, &data, &[key])
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut gen = GeneratorState::default();
+        let artifact = generate_artifact(&mut gen, 20);
+
+        let mut buf = vec![];
+        artifact.write(&mut buf).unwrap();
+        let read = Artifact::read(buf.as_slice()).unwrap();
+        assert_eq!(read, artifact);
+    }
+
+    #[test]
+    fn round_trip_file() {
+        let mut gen = GeneratorState::default();
+        let mut query = gen.generate_query(QuerySeed::new(
+            vec![QueryOperation::SingleParameter],
+            vec![],
+        ));
+        let keys = vec![query.state.key()];
+        let data = query.state.generate_data(5, false, false);
+
+        let path = std::env::temp_dir().join(format!(
+            "query_generator_artifact_{}.bin",
+            std::process::id()
+        ));
+        write_artifact(&path, &query, &data, &keys).unwrap();
+        let read = read_artifact(&path);
+        std::fs::remove_file(&path).unwrap();
+        let read = read.unwrap();
+
+        assert_eq!(read.query, query.statement);
+        assert_eq!(read.data, data);
+        assert_eq!(read.keys, keys);
+    }
+
+    #[test]
+    fn smaller_than_map_encoding() {
+        let mut gen = GeneratorState::default();
+        let artifact = generate_artifact(&mut gen, 200);
+
+        let mut buf = vec![];
+        artifact.write(&mut buf).unwrap();
+
+        let naive = bincode::serialize(
+            &artifact
+                .data
+                .iter()
+                .map(|(table_name, rows)| (table_name.to_string(), rows))
+                .collect::<HashMap<_, _>>(),
+        )
+        .unwrap();
+
+        assert!(
+            buf.len() * 4 < naive.len() * 3,
+            "Artifact was {} bytes, naive map-based encoding of just the data was {} bytes",
+            buf.len(),
+            naive.len()
+        );
+    }
+
+    #[test]
+    fn rejects_wrong_version() {
+        let mut gen = GeneratorState::default();
+        let artifact = generate_artifact(&mut gen, 1);
+
+        let mut buf = vec![];
+        artifact.write(&mut buf).unwrap();
+        // The version immediately follows the 4-byte magic number
+        buf[4] = buf[4].wrapping_add(1);
+        let err = Artifact::read(buf.as_slice()).unwrap_err();
+        assert!(
+            err.to_string().contains("Unsupported artifact version"),
+            "{err}"
+        );
+    }
+}
@@ -0,0 +1,460 @@
+//! Canonicalizing the names in generated queries, so that queries can be compared structurally.
+//!
+//! Tables, table aliases, CTEs, field aliases, and columns in a generated query are given names
+//! from counters in the [`GeneratorState`](crate::GeneratorState),
+//! [`QueryState`](crate::QueryState), and [`TableSpec`](crate::TableSpec)s they were generated
+//! with, so the same [`QuerySeed`](crate::QuerySeed) generates queries which differ only in those
+//! names depending on what was generated before it. [`canonicalize`] renames each of them to a
+//! placeholder determined only by the position at which it first appears in the query, leaving the
+//! rest of the query (including literals and placeholders) untouched.
+
+use std::collections::HashMap;
+
+use nom_sql::analysis::visit_mut::{walk_common_table_expr, VisitorMut};
+use nom_sql::{
+    Column, CommonTableExpr, FieldDefinitionExpr, Relation, SelectStatement, SqlIdentifier,
+    TableExpr, TableExprInner,
+};
+use readyset_util::hash::stable_hash;
+
+/// A [`SelectStatement`] with all of its generated names replaced by positional placeholders
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CanonicalQuery {
+    /// The statement, with tables, table aliases, and CTEs renamed to `t1`, `t2`, ..., field
+    /// aliases renamed to `a1`, `a2`, ..., and the columns of each table renamed to `c1`, `c2`,
+    /// ..., in the order they first appear
+    pub statement: SelectStatement,
+    /// A hash of [`Self::statement`], which is stable across runs of the query generator (see
+    /// [`stable_hash`])
+    pub hash: u64,
+}
+
+/// Assigns placeholder names to identifiers in the order they're first seen
+struct Names {
+    prefix: &'static str,
+    names: HashMap<SqlIdentifier, SqlIdentifier>,
+}
+
+impl Names {
+    fn new(prefix: &'static str) -> Self {
+        Self {
+            prefix,
+            names: HashMap::new(),
+        }
+    }
+
+    fn rename(&mut self, ident: &mut SqlIdentifier) {
+        let next = self.names.len() + 1;
+        let prefix = self.prefix;
+        *ident = self
+            .names
+            .entry(ident.clone())
+            .or_insert_with(|| format!("{prefix}{next}").into())
+            .clone();
+    }
+}
+
+struct Canonicalize {
+    /// Tables, table aliases, and CTEs - everything which can be referred to as a [`Relation`]
+    relations: Names,
+    field_aliases: Names,
+    /// The (renamed) table each (renamed) table alias refers to, so that columns referenced via
+    /// an alias are renamed the same way as columns referenced via the table itself
+    table_aliases: HashMap<SqlIdentifier, SqlIdentifier>,
+    /// The columns referenced via each (renamed) table, or via no table at all
+    columns: HashMap<Option<SqlIdentifier>, Names>,
+}
+
+impl<'ast> VisitorMut<'ast> for Canonicalize {
+    type Error = !;
+
+    fn visit_table(&mut self, table: &'ast mut Relation) -> Result<(), Self::Error> {
+        self.relations.rename(&mut table.name);
+        Ok(())
+    }
+
+    fn visit_column(&mut self, column: &'ast mut Column) -> Result<(), Self::Error> {
+        if let Some(table) = &mut column.table {
+            self.visit_table(table)?;
+        }
+        // Columns can refer to the aliases of the fields projected by subqueries or CTEs, but
+        // otherwise are the columns of base tables, which are numbered per table
+        if let Some(alias) = self.field_aliases.names.get(&column.name) {
+            column.name = alias.clone();
+        } else {
+            let table = column.table.as_ref().map(|table| {
+                self.table_aliases
+                    .get(&table.name)
+                    .unwrap_or(&table.name)
+                    .clone()
+            });
+            self.columns
+                .entry(table)
+                .or_insert_with(|| Names::new("c"))
+                .rename(&mut column.name);
+        }
+        Ok(())
+    }
+
+    fn visit_table_expr(&mut self, table_expr: &'ast mut TableExpr) -> Result<(), Self::Error> {
+        match &mut table_expr.inner {
+            TableExprInner::Table(table) => self.visit_table(table)?,
+            TableExprInner::Subquery(sq) => self.visit_select_statement(sq)?,
+        }
+        if let Some(alias) = &mut table_expr.alias {
+            self.relations.rename(alias);
+            if let TableExprInner::Table(table) = &table_expr.inner {
+                self.table_aliases.insert(alias.clone(), table.name.clone());
+            }
+        }
+        Ok(())
+    }
+
+    fn visit_common_table_expr(
+        &mut self,
+        cte: &'ast mut CommonTableExpr,
+    ) -> Result<(), Self::Error> {
+        self.relations.rename(&mut cte.name);
+        walk_common_table_expr(self, cte)
+    }
+
+    fn visit_field_definition_expr(
+        &mut self,
+        fde: &'ast mut FieldDefinitionExpr,
+    ) -> Result<(), Self::Error> {
+        match fde {
+            FieldDefinitionExpr::All => {}
+            FieldDefinitionExpr::AllInTable(table) => self.visit_table(table)?,
+            FieldDefinitionExpr::Expr { expr, alias } => {
+                self.visit_expr(expr)?;
+                if let Some(alias) = alias {
+                    self.field_aliases.rename(alias);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Visit the clauses of the statement in the order names are defined in: CTEs, then the `FROM`
+    /// clause, then joins, then the projected fields, before everything which can only refer to
+    /// those names
+    fn visit_select_statement(
+        &mut self,
+        select_statement: &'ast mut SelectStatement,
+    ) -> Result<(), Self::Error> {
+        for cte in &mut select_statement.ctes {
+            self.visit_common_table_expr(cte)?;
+        }
+        for table_expr in &mut select_statement.tables {
+            self.visit_table_expr(table_expr)?;
+        }
+        for join in &mut select_statement.join {
+            self.visit_join_clause(join)?;
+        }
+        for field in &mut select_statement.fields {
+            self.visit_field_definition_expr(field)?;
+        }
+        if let Some(where_clause) = &mut select_statement.where_clause {
+            self.visit_where_clause(where_clause)?;
+        }
+        if let Some(group_by_clause) = &mut select_statement.group_by {
+            self.visit_group_by_clause(group_by_clause)?;
+        }
+        if let Some(having_clause) = &mut select_statement.having {
+            self.visit_having_clause(having_clause)?;
+        }
+        if let Some(order_clause) = &mut select_statement.order {
+            self.visit_order_clause(order_clause)?;
+        }
+        self.visit_limit_clause(&mut select_statement.limit_clause)
+    }
+}
+
+/// Rename the tables, table aliases, CTEs, field aliases, and columns in `stmt` to placeholders
+/// determined by the position at which each first appears, visiting CTEs, the `FROM` clause,
+/// joins, and projected fields (recursively, for subqueries) in that order.
+///
+/// Queries generated from the same [`QuerySeed`](crate::QuerySeed) canonicalize identically
+/// regardless of the [`GeneratorState`](crate::GeneratorState) they were generated with, and
+/// canonicalizing is idempotent. Multiple references to the same table (eg in a self-join) keep
+/// referring to the same placeholder, while each alias for it gets its own. Columns are numbered
+/// separately for each table, and a column referenced via an alias for a table gets the same
+/// placeholder as when it's referenced via the table itself.
+pub fn canonicalize(stmt: &SelectStatement) -> CanonicalQuery {
+    let mut statement = stmt.clone();
+    let Ok(()) = Canonicalize {
+        relations: Names::new("t"),
+        field_aliases: Names::new("a"),
+        table_aliases: HashMap::new(),
+        columns: HashMap::new(),
+    }
+    .visit_select_statement(&mut statement);
+
+    CanonicalQuery {
+        hash: stable_hash(&statement),
+        statement,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nom_sql::analysis::visit::{self, Visitor};
+    use nom_sql::{parse_select_statement, Dialect, DialectDisplay, JoinOperator, OrderType};
+    use test_strategy::proptest;
+
+    use super::*;
+    use crate::{GeneratorState, JoinConstraintType, QueryOperation, QuerySeed};
+
+    fn parse(q: &str) -> SelectStatement {
+        parse_select_statement(Dialect::MySQL, q).unwrap()
+    }
+
+    fn canonical_sql(q: &str) -> String {
+        canonicalize(&parse(q))
+            .statement
+            .display(Dialect::MySQL)
+            .to_string()
+    }
+
+    fn join_seed(operator: JoinOperator) -> QuerySeed {
+        QuerySeed::new(
+            vec![
+                QueryOperation::Join {
+                    operator,
+                    constraint: JoinConstraintType::On,
+                    key_type: nom_sql::SqlType::Int(None),
+                    key_columns: 1,
+                },
+                QueryOperation::SingleParameter,
+            ],
+            vec![],
+        )
+    }
+
+    /// Returns all the field aliases defined in `stmt`, including in subqueries
+    fn field_aliases(stmt: &SelectStatement) -> Vec<SqlIdentifier> {
+        #[derive(Default)]
+        struct FieldAliases(Vec<SqlIdentifier>);
+        impl<'ast> Visitor<'ast> for FieldAliases {
+            type Error = !;
+
+            fn visit_field_definition_expr(
+                &mut self,
+                fde: &'ast FieldDefinitionExpr,
+            ) -> Result<(), Self::Error> {
+                if let FieldDefinitionExpr::Expr {
+                    alias: Some(alias), ..
+                } = fde
+                {
+                    self.0.push(alias.clone());
+                }
+                visit::walk_field_definition_expr(self, fde)
+            }
+        }
+
+        let mut visitor = FieldAliases::default();
+        let Ok(()) = visitor.visit_select_statement(stmt);
+        visitor.0
+    }
+
+    /// Consistently renames every table, table alias, CTE, and field alias in a statement with an
+    /// injective function of the original name
+    struct Rename {
+        salt: u64,
+        field_aliases: Vec<SqlIdentifier>,
+    }
+
+    impl Rename {
+        fn rename(&self, ident: &mut SqlIdentifier) {
+            let hash = stable_hash(&(self.salt, ident.as_str()));
+            *ident = format!("{hash:016x}_{ident}").into();
+        }
+    }
+
+    impl<'ast> VisitorMut<'ast> for Rename {
+        type Error = !;
+
+        fn visit_table(&mut self, table: &'ast mut Relation) -> Result<(), Self::Error> {
+            self.rename(&mut table.name);
+            Ok(())
+        }
+
+        fn visit_column(&mut self, column: &'ast mut Column) -> Result<(), Self::Error> {
+            if let Some(table) = &mut column.table {
+                self.visit_table(table)?;
+            }
+            if self.field_aliases.contains(&column.name) {
+                self.rename(&mut column.name);
+            }
+            Ok(())
+        }
+
+        fn visit_table_expr(&mut self, table_expr: &'ast mut TableExpr) -> Result<(), Self::Error> {
+            nom_sql::analysis::visit_mut::walk_table_expr(self, table_expr)?;
+            if let Some(alias) = &mut table_expr.alias {
+                self.rename(alias);
+            }
+            Ok(())
+        }
+
+        fn visit_common_table_expr(
+            &mut self,
+            cte: &'ast mut CommonTableExpr,
+        ) -> Result<(), Self::Error> {
+            self.rename(&mut cte.name);
+            walk_common_table_expr(self, cte)
+        }
+
+        fn visit_field_definition_expr(
+            &mut self,
+            fde: &'ast mut FieldDefinitionExpr,
+        ) -> Result<(), Self::Error> {
+            match fde {
+                FieldDefinitionExpr::All => {}
+                FieldDefinitionExpr::AllInTable(table) => self.visit_table(table)?,
+                FieldDefinitionExpr::Expr { expr, alias } => {
+                    self.visit_expr(expr)?;
+                    if let Some(alias) = alias {
+                        self.rename(alias);
+                    }
+                }
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn renames_tables_and_aliases_positionally() {
+        assert_eq!(
+            canonical_sql(
+                "SELECT `table_4`.`column_2` AS `alias_2`, `alias_1`.`column_1` AS `alias_3` \
+                 FROM `table_4` JOIN `table_4` AS `alias_1` \
+                 ON (`table_4`.`column_1` = `alias_1`.`column_2`) \
+                 WHERE (`table_4`.`column_1` = ?) ORDER BY `alias_2` LIMIT 10"
+            ),
+            "SELECT `t1`.`c2` AS `a1`, `t2`.`c1` AS `a2` \
+             FROM `t1` JOIN `t1` AS `t2` ON (`t1`.`c1` = `t2`.`c2`) \
+             WHERE (`t1`.`c1` = ?) ORDER BY `a1` LIMIT 10"
+        );
+    }
+
+    #[test]
+    fn renames_ctes_and_subquery_aliases() {
+        assert_eq!(
+            canonical_sql(
+                "WITH `alias_3` AS (SELECT `table_2`.`column_1` AS `alias_1` FROM `table_2`) \
+                 SELECT `alias_3`.`alias_1` AS `alias_4` FROM `table_7` \
+                 JOIN `alias_3` ON (`table_7`.`column_1` = `alias_3`.`alias_1`)"
+            ),
+            "WITH `t1` AS (SELECT `t2`.`c1` AS `a1` FROM `t2`) \
+             SELECT `t1`.`a1` AS `a2` FROM `t3` \
+             JOIN `t1` ON (`t3`.`c1` = `t1`.`a1`)"
+        );
+    }
+
+    #[test]
+    fn renames_columns_positionally_per_table() {
+        assert_eq!(
+            canonical_sql(
+                "SELECT `table_1`.`column_3` FROM `table_1` \
+                 JOIN `table_2` ON (`table_1`.`column_5` = `table_2`.`column_3`) \
+                 WHERE (`table_2`.`column_4` = ?)"
+            ),
+            canonical_sql(
+                "SELECT `table_5`.`column_1` FROM `table_5` \
+                 JOIN `table_6` ON (`table_5`.`column_2` = `table_6`.`column_1`) \
+                 WHERE (`table_6`.`column_7` = ?)"
+            )
+        );
+        assert_ne!(
+            canonical_sql(
+                "SELECT `table_1`.`column_1` FROM `table_1` WHERE (`table_1`.`column_1` = ?)"
+            ),
+            canonical_sql(
+                "SELECT `table_1`.`column_1` FROM `table_1` WHERE (`table_1`.`column_2` = ?)"
+            )
+        );
+    }
+
+    #[test]
+    fn self_join_differs_from_join_to_other_table() {
+        let self_join = canonicalize(&parse(
+            "SELECT `table_1`.`column_1` FROM `table_1` \
+             JOIN `table_1` AS `alias_1` ON (`table_1`.`column_1` = `alias_1`.`column_1`)",
+        ));
+        let other_join = canonicalize(&parse(
+            "SELECT `table_1`.`column_1` FROM `table_1` \
+             JOIN `table_2` AS `alias_1` ON (`table_1`.`column_1` = `alias_1`.`column_1`)",
+        ));
+        assert_ne!(self_join, other_join);
+        assert_ne!(self_join.hash, other_join.hash);
+    }
+
+    #[test]
+    fn same_seed_in_different_generator_states() {
+        let mut gen1 = GeneratorState::default();
+        let query1 = gen1.generate_query(join_seed(JoinOperator::InnerJoin));
+
+        // The second query reuses the tables (and columns) created for the first, but joins them
+        // to a new table
+        let mut gen2 = GeneratorState::default();
+        gen2.generate_query(join_seed(JoinOperator::InnerJoin));
+        let query2 = gen2.generate_query(join_seed(JoinOperator::InnerJoin));
+
+        assert_ne!(query1.statement, query2.statement);
+        assert_eq!(
+            canonicalize(&query1.statement),
+            canonicalize(&query2.statement)
+        );
+    }
+
+    #[test]
+    fn different_operations_differ() {
+        let seeds = [
+            join_seed(JoinOperator::InnerJoin),
+            join_seed(JoinOperator::LeftJoin),
+            QuerySeed::new(
+                vec![QueryOperation::TopK {
+                    order_type: OrderType::OrderAscending,
+                    limit: 3,
+                }],
+                vec![],
+            ),
+            QuerySeed::new(
+                vec![QueryOperation::TopK {
+                    order_type: OrderType::OrderAscending,
+                    limit: 4,
+                }],
+                vec![],
+            ),
+        ];
+        let canonical = seeds
+            .into_iter()
+            .map(|seed| canonicalize(&GeneratorState::default().generate_query(seed).statement))
+            .collect::<Vec<_>>();
+        for (i, a) in canonical.iter().enumerate() {
+            for b in &canonical[i + 1..] {
+                assert_ne!(a.hash, b.hash);
+            }
+        }
+    }
+
+    #[proptest]
+    fn canonicalize_is_idempotent(seed: QuerySeed) {
+        let query = GeneratorState::default().generate_query(seed);
+        let canonical = canonicalize(&query.statement);
+        assert_eq!(canonicalize(&canonical.statement), canonical);
+    }
+
+    #[proptest]
+    fn canonicalize_is_invariant_under_renaming(seed: QuerySeed, salt: u64) {
+        let query = GeneratorState::default().generate_query(seed);
+        let mut renamed = query.statement.clone();
+        let Ok(()) = Rename {
+            salt,
+            field_aliases: field_aliases(&query.statement),
+        }
+        .visit_select_statement(&mut renamed);
+        assert_eq!(canonicalize(&renamed), canonicalize(&query.statement));
+    }
+}
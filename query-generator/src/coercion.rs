@@ -0,0 +1,133 @@
+//! Pairs of types which can be compared with each other via implicit type coercion, for use in
+//! generating filters which compare a column against a literal of a different type.
+//!
+//! | Column type      | Literal type | Literal                 | Dialects          |
+//! |------------------|--------------|-------------------------|-------------------|
+//! | `INT`            | `TEXT`       | `'1'`                   | MySQL, PostgreSQL |
+//! | `BIGINT`         | `TEXT`       | `'1'`                   | MySQL, PostgreSQL |
+//! | `DECIMAL(10, 2)` | `INT`        | `1`                     | MySQL, PostgreSQL |
+//! | `DOUBLE`         | `INT`        | `1`                     | MySQL, PostgreSQL |
+//! | `DATE`           | `TEXT`       | `'2020-01-01'`          | MySQL, PostgreSQL |
+//! | `DATETIME`       | `TEXT`       | `'2020-01-01 00:00:00'` | MySQL, PostgreSQL |
+//! | `TEXT`           | `INT`        | `1`                     | MySQL             |
+//!
+//! PostgreSQL treats a quoted literal as having unknown type, and resolves it to the type of the
+//! column it's compared to, which is why the text literals are valid there. It has no implicit
+//! coercion between text and integers though, so comparing a `TEXT` column with an integer
+//! literal is only valid in MySQL.
+
+use nom_sql::{Dialect, Literal, SqlType};
+use readyset_data::{DfType, DfValue, Dialect as DataDialect};
+
+/// All pairs of (column type, literal type) which can be compared via implicit type coercion. See
+/// the [module documentation](self) for the literals used for each pair.
+pub const COERCIBLE_PAIRS: &[(SqlType, SqlType)] = &[
+    (SqlType::Int(None), SqlType::Text),
+    (SqlType::BigInt(None), SqlType::Text),
+    (SqlType::Decimal(10, 2), SqlType::Int(None)),
+    (SqlType::Double, SqlType::Int(None)),
+    (SqlType::Date, SqlType::Text),
+    (SqlType::DateTime(None), SqlType::Text),
+    (SqlType::Text, SqlType::Int(None)),
+];
+
+/// Returns an iterator over all the pairs in [`COERCIBLE_PAIRS`] which are valid in the given
+/// dialect
+pub fn coercible_pairs(dialect: Dialect) -> impl Iterator<Item = &'static (SqlType, SqlType)> {
+    COERCIBLE_PAIRS
+        .iter()
+        .filter(move |(column_type, rhs_type)| {
+            dialect == Dialect::MySQL || !(column_type.is_any_text() && !rhs_type.is_any_text())
+        })
+}
+
+/// Returns the literal of type `rhs_type` to compare a column of type `column_type` against, or
+/// `None` if the two types cannot be compared via implicit type coercion
+pub fn coerced_literal(column_type: &SqlType, rhs_type: &SqlType) -> Option<Literal> {
+    match (column_type, rhs_type) {
+        (SqlType::Int(_) | SqlType::BigInt(_), SqlType::Text) => Some(Literal::String("1".into())),
+        (SqlType::Decimal(..) | SqlType::Double, SqlType::Int(_)) => Some(Literal::Integer(1)),
+        (SqlType::Date, SqlType::Text) => Some(Literal::String("2020-01-01".into())),
+        (SqlType::DateTime(_), SqlType::Text) => {
+            Some(Literal::String("2020-01-01 00:00:00".into()))
+        }
+        (SqlType::Text, SqlType::Int(_)) => Some(Literal::Integer(1)),
+        _ => None,
+    }
+}
+
+/// Returns the literal of type `rhs_type` to compare a column of type `column_type` against,
+/// along with a value of type `column_type` which compares equal to that literal, or `None` if the
+/// two types cannot be compared via implicit type coercion
+pub fn coerced_comparison(column_type: &SqlType, rhs_type: &SqlType) -> Option<(Literal, DfValue)> {
+    let literal = coerced_literal(column_type, rhs_type)?;
+    let to_ty = DfType::from_sql_type(column_type, DataDialect::DEFAULT_MYSQL, |_| None).ok()?;
+    let from_ty = DfType::from_sql_type(rhs_type, DataDialect::DEFAULT_MYSQL, |_| None).ok()?;
+    let value = DfValue::try_from(literal.clone())
+        .ok()?
+        .coerce_to(&to_ty, &from_ty)
+        .ok()?;
+    Some((literal, value))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use rust_decimal::Decimal;
+
+    use super::*;
+
+    #[test]
+    fn all_pairs_are_coercible() {
+        for (column_type, rhs_type) in COERCIBLE_PAIRS {
+            assert!(
+                coerced_comparison(column_type, rhs_type).is_some(),
+                "{column_type:?} should be comparable with {rhs_type:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn incompatible_pairs_are_refused() {
+        assert_eq!(
+            coerced_literal(&SqlType::Int(None), &SqlType::Int(None)),
+            None
+        );
+        assert_eq!(coerced_literal(&SqlType::Date, &SqlType::Int(None)), None);
+        assert_eq!(coerced_literal(&SqlType::Bool, &SqlType::Text), None);
+        assert!(coerced_comparison(&SqlType::Blob, &SqlType::Double).is_none());
+    }
+
+    #[test]
+    fn int_column_text_literal() {
+        assert_eq!(
+            coerced_comparison(&SqlType::Int(None), &SqlType::Text),
+            Some((Literal::String("1".into()), DfValue::from(1)))
+        );
+    }
+
+    #[test]
+    fn decimal_column_int_literal() {
+        assert_eq!(
+            coerced_comparison(&SqlType::Decimal(10, 2), &SqlType::Int(None)),
+            Some((Literal::Integer(1), DfValue::from(Decimal::from(1))))
+        );
+    }
+
+    #[test]
+    fn date_column_text_literal() {
+        let (literal, value) = coerced_comparison(&SqlType::Date, &SqlType::Text).unwrap();
+        assert_eq!(literal, Literal::String("2020-01-01".into()));
+        assert_eq!(
+            value,
+            DfValue::from(NaiveDate::from_ymd_opt(2020, 1, 1).unwrap())
+        );
+    }
+
+    #[test]
+    fn text_column_int_literal_is_mysql_only() {
+        let pair = (SqlType::Text, SqlType::Int(None));
+        assert!(coercible_pairs(Dialect::MySQL).any(|p| *p == pair));
+        assert!(!coercible_pairs(Dialect::PostgreSQL).any(|p| *p == pair));
+    }
+}
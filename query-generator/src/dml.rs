@@ -0,0 +1,251 @@
+//! Generating DML statements which write to the tables of a [`GeneratorState`].
+//!
+//! Unlike a [`QueryOperation`](crate::QueryOperation), which adds to a `SELECT` statement, each
+//! [`DMLOperation`] generates an entire [`DMLStatement`] writing to a single table, adding any
+//! columns and keys it needs to that table.
+
+use std::fmt;
+
+use nom_sql::{
+    Column, Dialect, DialectDisplay, Expr, FunctionExpr, InsertStatement, ItemPlaceholder, Literal,
+    Relation, SqlType,
+};
+use readyset_util::fmt::fmt_with;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    ColumnName, GeneratorState, IndexKind, IndexSpec, ParameterMode, TableName, TableSpec,
+};
+
+/// An operation which generates a [`DMLStatement`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum DMLOperation {
+    /// Insert a row with a parameter for every column of a table, updating some of the columns of
+    /// the existing row instead if it conflicts with one on a unique key (`INSERT ... ON DUPLICATE
+    /// KEY UPDATE`). This syntax is only supported by MySQL.
+    Upsert {
+        /// The number of columns in the unique key rows conflict on. A single column uses the
+        /// table's primary key, and multiple columns use a unique secondary index. Always at least
+        /// 1.
+        conflict_columns: usize,
+        /// The number of columns, outside of any unique key, which are updated to their new
+        /// values on conflict. Always at least 1.
+        update_columns: usize,
+    },
+}
+
+/// A DML statement generated by a [`DMLOperation`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum DMLStatement {
+    Insert(InsertStatement),
+}
+
+impl DialectDisplay for DMLStatement {
+    fn display(&self, dialect: Dialect) -> impl fmt::Display + '_ {
+        fmt_with(move |f| match self {
+            DMLStatement::Insert(stmt) => write!(f, "{}", stmt.display(dialect)),
+        })
+    }
+}
+
+/// Returns true if `column` is part of the primary key or a unique index of `table`, and so
+/// shouldn't be updated on conflict
+fn is_unique(table: &TableSpec, column: &ColumnName) -> bool {
+    table.primary_key.as_ref() == Some(column)
+        || table
+            .indexes
+            .iter()
+            .any(|index| index.kind == IndexKind::Unique && index.columns.contains(column))
+}
+
+/// Ensure `table` has a unique key with `num_columns` columns - its primary key if `num_columns` is
+/// 1, or otherwise a unique secondary index
+fn ensure_unique_key(table: &mut TableSpec, num_columns: usize) {
+    if num_columns == 1 {
+        table.primary_key();
+        return;
+    }
+
+    if table
+        .indexes
+        .iter()
+        .any(|index| index.kind == IndexKind::Unique && index.columns.len() == num_columns)
+    {
+        return;
+    }
+
+    let columns = (0..num_columns)
+        .map(|_| table.fresh_column_with_type(SqlType::Int(None)))
+        .collect::<Vec<_>>();
+    table.add_index(IndexSpec {
+        name: None,
+        columns,
+        kind: IndexKind::Unique,
+    });
+}
+
+impl DMLOperation {
+    /// Generate a statement for this operation which writes to the table named `table_name` in
+    /// `gen`, adding columns and keys to the table as necessary.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the table doesn't exist
+    pub fn generate_statement(
+        &self,
+        gen: &mut GeneratorState,
+        table_name: &TableName,
+    ) -> DMLStatement {
+        let parameter_mode = gen.parameter_mode;
+        let table = gen
+            .table_mut(table_name)
+            .unwrap_or_else(|| panic!("Table {table_name} does not exist"));
+
+        match *self {
+            DMLOperation::Upsert {
+                conflict_columns,
+                update_columns,
+            } => {
+                ensure_unique_key(table, conflict_columns.max(1));
+                let mut updated = table
+                    .column_order
+                    .iter()
+                    .filter(|col| !is_unique(table, col))
+                    .take(update_columns.max(1))
+                    .cloned()
+                    .collect::<Vec<_>>();
+                while updated.len() < update_columns.max(1) {
+                    updated.push(table.fresh_column_with_type(SqlType::Int(None)));
+                }
+
+                DMLStatement::Insert(InsertStatement {
+                    table: Relation::from(table.name.clone()),
+                    fields: Some(
+                        table
+                            .column_order
+                            .iter()
+                            .cloned()
+                            .map(Column::from)
+                            .collect(),
+                    ),
+                    data: vec![(0..table.column_order.len())
+                        .map(|i| {
+                            Expr::Literal(Literal::Placeholder(match parameter_mode {
+                                ParameterMode::Positional => ItemPlaceholder::QuestionMark,
+                                ParameterMode::Numbered => {
+                                    ItemPlaceholder::DollarNumber((i + 1).try_into().unwrap())
+                                }
+                            }))
+                        })
+                        .collect()],
+                    ignore: false,
+                    on_duplicate: Some(
+                        updated
+                            .into_iter()
+                            .map(|col| {
+                                let col = Column::from(col);
+                                let value = Expr::Call(FunctionExpr::Call {
+                                    name: "VALUES".into(),
+                                    arguments: vec![Expr::Column(col.clone())],
+                                });
+                                (col, value)
+                            })
+                            .collect(),
+                    ),
+                })
+            }
+        }
+    }
+}
+
+impl GeneratorState {
+    /// Generate a parameterized upsert (`INSERT ... ON DUPLICATE KEY UPDATE`) into the table
+    /// named `table_name`, which inserts a value for every column of the table and updates every
+    /// column outside of the table's primary key and unique indexes if the row conflicts with an
+    /// existing row on its primary key. A primary key or a column to update is added to the table
+    /// if it doesn't have one.
+    ///
+    /// The statement has one parameter for each column of the table (after generating the
+    /// statement), in the order given by [`TableSpec::column_order`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the table doesn't exist
+    pub fn generate_upsert(&mut self, table_name: &TableName) -> String {
+        let table = self
+            .table_mut(table_name)
+            .unwrap_or_else(|| panic!("Table {table_name} does not exist"));
+        table.primary_key();
+        let update_columns = table
+            .column_order
+            .iter()
+            .filter(|col| !is_unique(table, col))
+            .count();
+
+        DMLOperation::Upsert {
+            conflict_columns: 1,
+            update_columns,
+        }
+        .generate_statement(self, table_name)
+        .display(Dialect::MySQL)
+        .to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn upsert_on_primary_key() {
+        let mut gen = GeneratorState::default();
+        let table_name = gen.fresh_table_mut().name.clone();
+        assert_eq!(
+            gen.generate_upsert(&table_name),
+            "INSERT INTO `table_1` (`column_1`, `column_2`) VALUES (?, ?) \
+             ON DUPLICATE KEY UPDATE `column_2` = VALUES(`column_2`)"
+        );
+
+        let table = gen.table(&table_name).unwrap();
+        assert_eq!(table.primary_key, Some("column_1".into()));
+    }
+
+    #[test]
+    fn upsert_on_composite_unique_key() {
+        let mut gen = GeneratorState::with_parameter_mode(ParameterMode::Numbered);
+        let table_name = gen.fresh_table_mut().name.clone();
+        let DMLStatement::Insert(insert) = DMLOperation::Upsert {
+            conflict_columns: 2,
+            update_columns: 2,
+        }
+        .generate_statement(&mut gen, &table_name);
+
+        let table = gen.table(&table_name).unwrap();
+        let unique_index = table
+            .indexes
+            .iter()
+            .find(|index| index.kind == IndexKind::Unique)
+            .unwrap();
+        assert_eq!(unique_index.columns.len(), 2);
+
+        let fields = insert.fields.unwrap();
+        assert_eq!(fields.len(), 4);
+        assert_eq!(
+            insert.data,
+            vec![(1..=4)
+                .map(|n| Expr::Literal(Literal::Placeholder(ItemPlaceholder::DollarNumber(n))))
+                .collect::<Vec<_>>()]
+        );
+
+        let updated = insert
+            .on_duplicate
+            .unwrap()
+            .into_iter()
+            .map(|(col, _)| ColumnName::from(col))
+            .collect::<Vec<_>>();
+        assert_eq!(updated.len(), 2);
+        for col in &updated {
+            assert!(!unique_index.columns.contains(col), "{col} is in the key");
+        }
+    }
+}
@@ -0,0 +1,324 @@
+//! Generating pairs of statements which apply the same filter to an aggregated query either before
+//! or after aggregation, for testing that filters are only moved across aggregates when doing so
+//! can't change the results of the query.
+//!
+//! A filter on a column the query groups by selects whole groups, so applying it to the rows of the
+//! query's tables in its `WHERE` clause returns the same results as applying it to the rows of the
+//! query's results in a wrapping outer query. A filter on the input to an aggregate doesn't: moving
+//! it after aggregation applies the predicate to the aggregated value rather than to the rows which
+//! contribute to it. Each [`EquivalencePair`] records which of the two cases it is, so that a
+//! harness can check that the former pairs return identical results (and, optionally, that a
+//! rewrite pass never treats the latter pairs as interchangeable).
+
+use nom_sql::analysis::visit::Visitor;
+use nom_sql::analysis::ReferredColumns;
+use nom_sql::{
+    BinaryOperator, Column, Expr, FieldDefinitionExpr, FieldReference, LimitClause, Literal,
+    Relation, SelectStatement, SqlIdentifier, TableExpr, TableExprInner,
+};
+
+use crate::{contains_generated_aggregate, query_has_aggregate, Query};
+
+/// The alias given to the subquery wrapped by [`wrap_in_outer_query`]
+pub const OUTER_QUERY_ALIAS: &str = "x";
+
+/// The kind of column of an aggregated query that the filter in an [`EquivalencePair`] applies to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilteredColumn {
+    /// A column in the query's `GROUP BY` clause, which isn't the input to any aggregate
+    GroupBy,
+    /// The input to one of the query's aggregates
+    AggregateInput,
+}
+
+/// A query with a filter in its `WHERE` clause, along with the same query with that filter applied
+/// after aggregation instead
+#[derive(Debug, Clone, PartialEq)]
+pub struct EquivalencePair {
+    pub filtered_column: FilteredColumn,
+    /// The column filtered on by the `WHERE` clause of [`Self::pre_aggregation`]
+    pub column: Column,
+    /// The query as generated, with the filter in its `WHERE` clause
+    pub pre_aggregation: SelectStatement,
+    /// The query with the filter removed from its `WHERE` clause, wrapped in an outer query which
+    /// applies the same predicate to the projected column the filtered column ends up in - either
+    /// the column itself, or the aggregate it's the input to
+    pub post_aggregation: SelectStatement,
+    /// Whether both statements are expected to return the same results for any data. Only true
+    /// for filters on [`FilteredColumn::GroupBy`] columns.
+    pub equivalent: bool,
+}
+
+/// Wrap `inner` in an outer query which projects all of its columns and filters them with
+/// `where_clause`. Columns of `inner` can be referenced in `where_clause` using the table name
+/// [`OUTER_QUERY_ALIAS`].
+pub fn wrap_in_outer_query(inner: SelectStatement, where_clause: Option<Expr>) -> SelectStatement {
+    SelectStatement {
+        fields: vec![FieldDefinitionExpr::All],
+        tables: vec![TableExpr {
+            inner: TableExprInner::Subquery(Box::new(inner)),
+            alias: Some(OUTER_QUERY_ALIAS.into()),
+            index_hint: None,
+            sample: None,
+        }],
+        where_clause,
+        ..Default::default()
+    }
+}
+
+/// Split `expr` into the operands of its top-level `AND`s
+pub(crate) fn conjuncts(expr: Expr) -> Vec<Expr> {
+    match expr {
+        Expr::BinaryOp {
+            lhs,
+            op: BinaryOperator::And,
+            rhs,
+        } => {
+            let mut res = conjuncts(*lhs);
+            res.extend(conjuncts(*rhs));
+            res
+        }
+        expr => vec![expr],
+    }
+}
+
+/// The inverse of [`conjuncts`]
+pub(crate) fn conjunction(exprs: impl IntoIterator<Item = Expr>) -> Option<Expr> {
+    exprs.into_iter().reduce(|lhs, rhs| Expr::BinaryOp {
+        lhs: Box::new(lhs),
+        op: BinaryOperator::And,
+        rhs: Box::new(rhs),
+    })
+}
+
+/// Returns true if `expr` contains a placeholder
+pub(crate) fn has_placeholder(expr: &Expr) -> bool {
+    struct HasPlaceholder(bool);
+    impl<'ast> Visitor<'ast> for HasPlaceholder {
+        type Error = !;
+
+        fn visit_literal(&mut self, literal: &'ast Literal) -> Result<(), Self::Error> {
+            self.0 |= matches!(literal, Literal::Placeholder(_));
+            Ok(())
+        }
+    }
+
+    let mut visitor = HasPlaceholder(false);
+    let Ok(()) = visitor.visit_expr(expr);
+    visitor.0
+}
+
+/// Determine which kind of column `column` is in `statement`, along with the alias of the field
+/// that a filter on it should be applied to after aggregation
+fn classify(
+    statement: &SelectStatement,
+    column: &Column,
+) -> Option<(FilteredColumn, SqlIdentifier)> {
+    // Aggregate inputs take priority, since the generator groups by every projected column that
+    // isn't aggregated - including the input to an aggregate, if it's also projected by a filter
+    let aggregated = statement.fields.iter().find_map(|field| match field {
+        FieldDefinitionExpr::Expr {
+            expr,
+            alias: Some(alias),
+        } if contains_generated_aggregate(expr) && expr.referred_columns().any(|c| c == column) => {
+            Some(alias.clone())
+        }
+        _ => None,
+    });
+    if let Some(alias) = aggregated {
+        return Some((FilteredColumn::AggregateInput, alias));
+    }
+
+    let grouped = statement
+        .group_by
+        .iter()
+        .flat_map(|gb| &gb.fields)
+        .any(|field| matches!(field, FieldReference::Expr(Expr::Column(c)) if c == column));
+    if !grouped {
+        return None;
+    }
+    statement.fields.iter().find_map(|field| match field {
+        FieldDefinitionExpr::Expr {
+            expr: Expr::Column(c),
+            alias: Some(alias),
+        } if c == column => Some((FilteredColumn::GroupBy, alias.clone())),
+        _ => None,
+    })
+}
+
+/// Build an [`EquivalencePair`] for `statement` from the first conjunct of its `WHERE` clause which
+/// filters on a single column that's either grouped by or aggregated. Returns `None` if the
+/// statement has no aggregates, has a `LIMIT` or `HAVING` clause (which would be applied before
+/// the outer query's filter), or has no such conjunct.
+pub fn equivalence_pair(statement: &SelectStatement) -> Option<EquivalencePair> {
+    if !query_has_aggregate(statement)
+        || statement.having.is_some()
+        || !matches!(
+            statement.limit_clause,
+            LimitClause::LimitOffset {
+                limit: None,
+                offset: None
+            }
+        )
+    {
+        return None;
+    }
+
+    let conjuncts = conjuncts(statement.where_clause.clone()?);
+    conjuncts.iter().enumerate().find_map(|(idx, conjunct)| {
+        // Parameters can't be moved into an outer query without also changing the lookup key of
+        // the query, so filters containing them are skipped
+        if has_placeholder(conjunct) {
+            return None;
+        }
+        let mut columns = conjunct.referred_columns();
+        let column = columns.next()?.clone();
+        if columns.any(|c| *c != column) {
+            return None;
+        }
+        let (filtered_column, alias) = classify(statement, &column)?;
+
+        let mut predicate = conjunct.clone();
+        for col in predicate.referred_columns_mut() {
+            *col = Column {
+                name: alias.clone(),
+                table: Some(Relation::from(OUTER_QUERY_ALIAS)),
+            };
+        }
+
+        let mut inner = statement.clone();
+        inner.where_clause = conjunction(
+            conjuncts
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| *i != idx)
+                .map(|(_, c)| c.clone()),
+        );
+
+        Some(EquivalencePair {
+            filtered_column,
+            column,
+            pre_aggregation: statement.clone(),
+            post_aggregation: wrap_in_outer_query(inner, Some(predicate)),
+            equivalent: filtered_column == FilteredColumn::GroupBy,
+        })
+    })
+}
+
+impl<'gen> Query<'gen> {
+    /// Build an [`EquivalencePair`] for this query, if it has an aggregate and a filter which can
+    /// be moved after aggregation. See [`equivalence_pair`].
+    pub fn equivalence_pair(&self) -> Option<EquivalencePair> {
+        equivalence_pair(&self.statement)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nom_sql::{parse_select_statement, Dialect, DialectDisplay, SqlType};
+
+    use super::*;
+    use crate::{
+        AggregateType, Filter, FilterOp, FilterRHS, FilterTarget, GeneratorState, LogicalOp,
+        QueryOperation, QuerySeed,
+    };
+
+    fn parse(q: &str) -> SelectStatement {
+        parse_select_statement(Dialect::MySQL, q).unwrap()
+    }
+
+    #[test]
+    fn group_by_filter_is_equivalent() {
+        let statement = parse(
+            "SELECT t.a AS alias_1, count(t.b) AS alias_2 FROM t \
+             WHERE (t.a = 'x') AND (t.c = 1) GROUP BY t.a",
+        );
+        let pair = equivalence_pair(&statement).unwrap();
+        assert_eq!(pair.filtered_column, FilteredColumn::GroupBy);
+        assert!(pair.equivalent);
+        assert_eq!(pair.column, Column::from("t.a"));
+        assert_eq!(pair.pre_aggregation, statement);
+        assert_eq!(
+            pair.post_aggregation,
+            parse(
+                "SELECT * FROM (SELECT t.a AS alias_1, count(t.b) AS alias_2 FROM t \
+                 WHERE t.c = 1 GROUP BY t.a) AS x WHERE x.alias_1 = 'x'"
+            )
+        );
+    }
+
+    #[test]
+    fn aggregate_input_filter_is_not_equivalent() {
+        let statement =
+            parse("SELECT t.a AS alias_1, sum(t.b) AS alias_2 FROM t WHERE t.b > 5 GROUP BY t.a");
+        let pair = equivalence_pair(&statement).unwrap();
+        assert_eq!(pair.filtered_column, FilteredColumn::AggregateInput);
+        assert!(!pair.equivalent);
+        assert_eq!(pair.column, Column::from("t.b"));
+        assert_eq!(
+            pair.post_aggregation,
+            parse(
+                "SELECT * FROM (SELECT t.a AS alias_1, sum(t.b) AS alias_2 FROM t GROUP BY t.a) \
+                 AS x WHERE x.alias_2 > 5"
+            )
+        );
+    }
+
+    #[test]
+    fn no_pair_without_movable_filter() {
+        for q in [
+            // No aggregate
+            "SELECT t.a AS alias_1 FROM t WHERE t.a = 1",
+            // Parameterized filter
+            "SELECT t.a AS alias_1, count(t.b) AS alias_2 FROM t WHERE t.a = ? GROUP BY t.a",
+            // Filter on a column that's neither grouped nor aggregated
+            "SELECT count(t.b) AS alias_1 FROM t WHERE t.c = 1",
+            // Filter comparing two columns
+            "SELECT t.a AS alias_1, count(t.b) AS alias_2 FROM t WHERE t.a = t.b GROUP BY t.a",
+            // LIMIT is applied before the outer query's filter
+            "SELECT t.a AS alias_1, count(t.b) AS alias_2 FROM t WHERE t.a = 1 GROUP BY t.a \
+             LIMIT 3",
+        ] {
+            assert_eq!(equivalence_pair(&parse(q)), None, "{q}");
+        }
+    }
+
+    #[test]
+    fn generated_group_by_filter() {
+        let mut gen = GeneratorState::default();
+        let query = gen.generate_query(QuerySeed::new(
+            vec![
+                QueryOperation::ColumnAggregate(AggregateType::Count {
+                    column_type: SqlType::Int(None),
+                    distinct: false,
+                }),
+                QueryOperation::Filter(Filter {
+                    extend_where_with: LogicalOp::And,
+                    operation: FilterOp::Comparison {
+                        op: BinaryOperator::Equal,
+                        rhs: FilterRHS::Constant(Literal::String("a".into())),
+                    },
+                    column_type: SqlType::Text,
+                    filter_target: FilterTarget::default(),
+                }),
+            ],
+            vec![],
+        ));
+        let pair = query.equivalence_pair().unwrap();
+        assert_eq!(pair.filtered_column, FilteredColumn::GroupBy);
+        assert!(pair.equivalent);
+        assert_eq!(pair.pre_aggregation, query.statement);
+
+        let sql = pair.post_aggregation.display(Dialect::MySQL).to_string();
+        assert_eq!(
+            parse_select_statement(Dialect::MySQL, &sql).unwrap(),
+            pair.post_aggregation,
+            "{sql} should re-parse"
+        );
+        let TableExprInner::Subquery(inner) = &pair.post_aggregation.tables[0].inner else {
+            panic!("{sql} should select from a subquery");
+        };
+        assert_eq!(inner.where_clause, None);
+        assert_eq!(inner.group_by, query.statement.group_by);
+    }
+}
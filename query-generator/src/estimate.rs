@@ -0,0 +1,399 @@
+//! Rough estimation of the number of rows returned by generated queries, so that queries whose
+//! results are likely to be empty or pathologically large can be skipped (eg when picking queries
+//! for a time-boxed benchmark) without having to run them first.
+//!
+//! Estimates are built by composing a simple per-operation model over the operations the query was
+//! generated from, starting from the number of rows generated for each table:
+//!
+//! * Filters and parameters multiply the size by [`ResultSizeModel::filter_selectivity`]
+//! * Joins multiply the size by [`ResultSizeModel::join_fanout`] (`LEFT JOIN`s never reduce the
+//!   lower bound, and cross joins multiply by the number of rows in the joined table)
+//! * Aggregates collapse the size to the estimated number of groups, derived from the [cardinality
+//!   hints](crate::TableSpec::cardinality_hint_for) of the grouped columns
+//! * `DISTINCT` caps the size at the estimated number of distinct projected rows, derived the same
+//!   way
+//! * `LIMIT` caps the size at the limit
+//!
+//! Accuracy isn't the goal - the estimate is only meant to be monotonic in the operations of the
+//! query (adding a filter never raises it, adding a join with a fanout above 1 never lowers it).
+//! Subqueries aren't taken into account.
+
+use std::fmt::{self, Display};
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+
+use anyhow::anyhow;
+use nom_sql::{Column, Expr, FieldDefinitionExpr, FieldReference, JoinOperator};
+use serde::{Deserialize, Serialize};
+
+use crate::{query_has_aggregate, ColumnName, Query, QueryOperation, TableName};
+
+/// The default value for [`ResultSizeModel::filter_selectivity`]
+const DEFAULT_FILTER_SELECTIVITY: f64 = 0.5;
+
+/// The default value for [`ResultSizeModel::join_fanout`]
+const DEFAULT_JOIN_FANOUT: f64 = 1.0;
+
+/// Parameters of the model used to estimate the result size of queries
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ResultSizeModel {
+    /// The fraction of rows which satisfy each filter or parameter in a query
+    pub filter_selectivity: f64,
+    /// The number of rows on the right-hand side of each join matching each row on the left-hand
+    /// side
+    pub join_fanout: f64,
+}
+
+impl Default for ResultSizeModel {
+    fn default() -> Self {
+        Self {
+            filter_selectivity: DEFAULT_FILTER_SELECTIVITY,
+            join_fanout: DEFAULT_JOIN_FANOUT,
+        }
+    }
+}
+
+/// An approximate range for the number of rows returned by a query, returned by
+/// [`Query::estimate_result_size`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResultSizeEstimate {
+    /// The estimated minimum number of rows returned by the query
+    pub min: u64,
+    /// The estimated maximum number of rows returned by the query
+    pub max: u64,
+}
+
+impl ResultSizeEstimate {
+    /// Returns true if this estimate overlaps with `range`
+    pub fn overlaps(&self, range: &RangeInclusive<u64>) -> bool {
+        self.min <= *range.end() && self.max >= *range.start()
+    }
+}
+
+impl Display for ResultSizeEstimate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}..{}", self.min, self.max)
+    }
+}
+
+/// A range of result sizes, parsed from a string of the form `MIN..MAX`, for filtering generated
+/// queries by their [`ResultSizeEstimate`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResultSizeRange(pub RangeInclusive<u64>);
+
+impl FromStr for ResultSizeRange {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (min, max) = s
+            .split_once("..")
+            .ok_or_else(|| anyhow!("Expected a range of the form MIN..MAX, got {s:?}"))?;
+        let min = min.trim().parse()?;
+        let max = max.trim().parse()?;
+        if min > max {
+            return Err(anyhow!("Empty range {s:?}: {min} is greater than {max}"));
+        }
+        Ok(Self(min..=max))
+    }
+}
+
+/// A range of result sizes, tracked with floating point bounds while operations are applied
+#[derive(Debug, Clone, Copy)]
+struct Bounds {
+    min: f64,
+    max: f64,
+}
+
+impl Bounds {
+    fn scale(&mut self, factor: f64) {
+        self.min *= factor;
+        self.max *= factor;
+    }
+
+    fn cap(&mut self, limit: f64) {
+        self.min = self.min.min(limit);
+        self.max = self.max.min(limit);
+    }
+}
+
+impl<'gen> Query<'gen> {
+    /// Returns the estimated number of distinct values of the column `col`, if known
+    fn column_cardinality(&self, col: &Column) -> Option<f64> {
+        let table = TableName::from(&col.table.as_ref()?.name);
+        self.state
+            .gen
+            .table(&table)?
+            .cardinality_hint_for(&ColumnName::from(&col.name))
+            .map(|c| c as f64)
+    }
+
+    /// Returns the estimated number of distinct combinations of the values of `exprs`, if all of
+    /// them are columns whose cardinality is known
+    fn combined_cardinality<'a, I>(&self, exprs: I) -> Option<f64>
+    where
+        I: IntoIterator<Item = Option<&'a Expr>>,
+    {
+        exprs.into_iter().try_fold(1.0, |acc, expr| match expr? {
+            Expr::Column(col) => Some(acc * self.column_cardinality(col)?),
+            _ => None,
+        })
+    }
+
+    /// Estimate the number of rows returned by this query, if `rows_per_table` rows are generated
+    /// for each table, using the default [`ResultSizeModel`].
+    ///
+    /// See the [module documentation](crate::estimate) for how the estimate is computed.
+    pub fn estimate_result_size(&self, rows_per_table: usize) -> ResultSizeEstimate {
+        self.estimate_result_size_with(rows_per_table, &ResultSizeModel::default())
+    }
+
+    /// Estimate the number of rows returned by this query, if `rows_per_table` rows are generated
+    /// for each table, using the given [`ResultSizeModel`]
+    pub fn estimate_result_size_with(
+        &self,
+        rows_per_table: usize,
+        model: &ResultSizeModel,
+    ) -> ResultSizeEstimate {
+        let rows_per_table = rows_per_table as f64;
+        let mut bounds = Bounds {
+            min: rows_per_table,
+            max: rows_per_table,
+        };
+
+        // Filtering and joining happen before grouping, regardless of the order the operations
+        // were added to the query in
+        for op in &self.seed.operations {
+            match op {
+                QueryOperation::Filter(_)
+                | QueryOperation::JsonPath { .. }
+                | QueryOperation::SingleParameter
+                | QueryOperation::MultipleParameters
+                | QueryOperation::RangeParameter
+                | QueryOperation::MultipleRangeParameters
+                | QueryOperation::ParameterWithLiteralConstraint { .. } => {
+                    bounds.scale(model.filter_selectivity)
+                }
+                QueryOperation::InParameter { num_values } => {
+                    bounds.scale((f64::from(*num_values) * model.filter_selectivity).min(1.0))
+                }
+                QueryOperation::Join { operator, .. } => match operator {
+                    JoinOperator::CrossJoin => bounds.scale(rows_per_table),
+                    JoinOperator::LeftJoin | JoinOperator::LeftOuterJoin => {
+                        bounds.max *= model.join_fanout.max(1.0)
+                    }
+                    JoinOperator::Join
+                    | JoinOperator::InnerJoin
+                    | JoinOperator::RightJoin
+                    | JoinOperator::StraightJoin => bounds.scale(model.join_fanout),
+                },
+                QueryOperation::AntiJoin { overlap_percent } => {
+                    bounds.scale(f64::from(100 - overlap_percent) / 100.0)
+                }
+                _ => {}
+            }
+        }
+
+        let group_by = self.statement.group_by.as_ref();
+        if let Some(group_by) = group_by.filter(|gb| !gb.fields.is_empty()) {
+            let groups = self.combined_cardinality(group_by.fields.iter().map(|f| match f {
+                FieldReference::Expr(expr) => Some(expr),
+                FieldReference::Numeric(_) => None,
+            }));
+            match groups {
+                Some(groups) => bounds.cap(groups),
+                None => bounds.min = bounds.min.min(1.0),
+            }
+        } else if query_has_aggregate(&self.statement) {
+            // An aggregate with no GROUP BY always returns exactly one row
+            bounds = Bounds { min: 1.0, max: 1.0 };
+        }
+
+        if self.statement.distinct {
+            let distinct =
+                self.combined_cardinality(self.statement.fields.iter().map(|f| match f {
+                    FieldDefinitionExpr::Expr { expr, .. } => Some(expr),
+                    _ => None,
+                }));
+            match distinct {
+                Some(distinct) => bounds.cap(distinct),
+                None => bounds.min = bounds.min.min(1.0),
+            }
+        }
+
+        for op in &self.seed.operations {
+            match op {
+                QueryOperation::TopK { limit, .. } | QueryOperation::ParamTopK { limit, .. } => {
+                    bounds.cap(*limit as f64)
+                }
+                QueryOperation::Paginate {
+                    limit, page_number, ..
+                } => {
+                    let offset = (*limit * *page_number) as f64;
+                    bounds.min = (bounds.min - offset).max(0.0);
+                    bounds.max = (bounds.max - offset).max(0.0);
+                    bounds.cap(*limit as f64);
+                }
+                _ => {}
+            }
+        }
+
+        ResultSizeEstimate {
+            min: bounds.min.floor() as u64,
+            max: bounds.max.ceil() as u64,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+    use nom_sql::{BinaryOperator, Literal, OrderType, SqlType};
+
+    use super::*;
+    use crate::{
+        AggregateType, Filter, FilterOp, FilterRHS, FilterTarget, GenerateOpts, GeneratorState,
+        JoinConstraintType, LogicalOp, QuerySeed,
+    };
+
+    fn estimate(operations: Vec<QueryOperation>, model: &ResultSizeModel) -> ResultSizeEstimate {
+        let mut gen = GeneratorState::default();
+        let query = gen.generate_query(QuerySeed::new(operations, vec![]));
+        query.estimate_result_size_with(1000, model)
+    }
+
+    fn filter() -> QueryOperation {
+        QueryOperation::Filter(Filter {
+            extend_where_with: LogicalOp::And,
+            operation: FilterOp::Comparison {
+                op: BinaryOperator::Equal,
+                rhs: FilterRHS::Constant(Literal::Integer(1)),
+            },
+            column_type: SqlType::Int(None),
+            filter_target: FilterTarget::Base,
+        })
+    }
+
+    fn join() -> QueryOperation {
+        QueryOperation::Join {
+            operator: JoinOperator::InnerJoin,
+            constraint: JoinConstraintType::On,
+            key_type: SqlType::Int(None),
+            key_columns: 1,
+        }
+    }
+
+    #[test]
+    fn unfiltered() {
+        assert_eq!(
+            estimate(vec![], &Default::default()),
+            ResultSizeEstimate {
+                min: 1000,
+                max: 1000
+            }
+        );
+    }
+
+    #[test]
+    fn adding_filter_never_raises_estimate() {
+        let model = ResultSizeModel::default();
+        for base in [vec![], vec![join()], vec![join(), filter()]] {
+            let without = estimate(base.clone(), &model);
+            let mut ops = base;
+            ops.push(filter());
+            let with = estimate(ops, &model);
+            assert!(with.min <= without.min, "{with} > {without}");
+            assert!(with.max <= without.max, "{with} > {without}");
+        }
+    }
+
+    #[test]
+    fn adding_join_with_fanout_raises_estimate() {
+        let model = ResultSizeModel {
+            join_fanout: 3.0,
+            ..Default::default()
+        };
+        let without = estimate(vec![filter()], &model);
+        let with = estimate(vec![filter(), join()], &model);
+        assert!(with.min > without.min, "{with} <= {without}");
+        assert!(with.max > without.max, "{with} <= {without}");
+    }
+
+    #[test]
+    fn topk_caps_estimate() {
+        let est = estimate(
+            vec![QueryOperation::TopK {
+                order_type: OrderType::OrderAscending,
+                limit: 3,
+            }],
+            &Default::default(),
+        );
+        assert_eq!(est, ResultSizeEstimate { min: 3, max: 3 });
+    }
+
+    #[test]
+    fn ungrouped_aggregate_returns_one_row() {
+        let est = estimate(
+            vec![QueryOperation::ColumnAggregate(AggregateType::Count {
+                column_type: SqlType::Int(None),
+                distinct: false,
+            })],
+            &Default::default(),
+        );
+        assert_eq!(est, ResultSizeEstimate { min: 1, max: 1 });
+    }
+
+    #[test]
+    fn parse_result_size_range() {
+        assert_eq!(
+            "1..100".parse::<ResultSizeRange>().unwrap(),
+            ResultSizeRange(1..=100)
+        );
+        "100..1".parse::<ResultSizeRange>().unwrap_err();
+        "100".parse::<ResultSizeRange>().unwrap_err();
+        assert!(ResultSizeEstimate { min: 0, max: 5 }.overlaps(&(5..=10)));
+        assert!(!ResultSizeEstimate { min: 0, max: 4 }.overlaps(&(5..=10)));
+    }
+
+    #[test]
+    fn filter_by_result_size_range() {
+        let opts = GenerateOpts::try_parse_from([
+            "test",
+            "--operations",
+            "topk,equal_filters",
+            "--num-operations",
+            "1",
+            "--result-size-range",
+            "1..10",
+        ])
+        .unwrap();
+        let mut gen = GeneratorState::default();
+        let kept = opts
+            .clone()
+            .into_query_seeds()
+            .filter(|seed| {
+                let query = gen.generate_query(seed.clone());
+                opts.admits(&query.estimate_result_size(1000))
+            })
+            .collect::<Vec<_>>();
+
+        assert!(!kept.is_empty());
+        for seed in &kept {
+            assert!(
+                seed.operations
+                    .iter()
+                    .all(|op| matches!(op, QueryOperation::TopK { .. })),
+                "{seed:?} should have been filtered out"
+            );
+        }
+    }
+
+    #[test]
+    fn no_result_size_range_admits_everything() {
+        let opts = GenerateOpts::try_parse_from(["test"]).unwrap();
+        assert!(opts.admits(&ResultSizeEstimate { min: 0, max: 0 }));
+        assert!(opts.admits(&ResultSizeEstimate {
+            min: u64::MAX,
+            max: u64::MAX
+        }));
+    }
+}
@@ -0,0 +1,369 @@
+//! Checking that generated data satisfies the integrity constraints declared on the tables it was
+//! generated for, so that data which would be rejected by the database it's loaded into (for
+//! example because of a schema change made after the data was generated) can be caught up front.
+//!
+//! See [`GeneratorState::check_referential_integrity`]. Data which satisfies the foreign keys of
+//! the schema can be generated with [`GeneratorState::tables_in_foreign_key_order`] and
+//! [`GeneratorState::fill_foreign_keys`].
+
+use std::collections::{HashMap, HashSet};
+use std::fmt::{self, Display};
+
+use itertools::Itertools;
+use rand::seq::SliceRandom;
+use rand::thread_rng;
+use readyset_data::DfValue;
+
+use crate::{ColumnName, ForeignKeySpec, GeneratorState, TableName};
+
+/// The maximum number of offending rows or values recorded for each violated constraint in an
+/// [`IntegrityReport`]
+pub const MAX_SAMPLES: usize = 5;
+
+/// Violations of a single foreign key in a set of generated data
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ForeignKeyViolations {
+    /// The table the foreign key is on
+    pub table: TableName,
+    /// The foreign key which was violated
+    pub foreign_key: ForeignKeySpec,
+    /// The number of rows of [`Self::table`] whose values for the foreign key columns don't match
+    /// any row of the target table
+    pub count: usize,
+    /// Up to [`MAX_SAMPLES`] of the offending rows
+    pub samples: Vec<HashMap<ColumnName, DfValue>>,
+}
+
+/// Duplicate values for the primary key of a single table in a set of generated data
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicatePrimaryKeys {
+    /// The table with duplicate primary keys
+    pub table: TableName,
+    /// The primary key column of the table
+    pub column: ColumnName,
+    /// The number of rows whose primary key was already used by an earlier row
+    pub count: usize,
+    /// Up to [`MAX_SAMPLES`] of the duplicated primary key values
+    pub samples: Vec<DfValue>,
+}
+
+/// The result of checking a set of generated data with
+/// [`GeneratorState::check_referential_integrity`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IntegrityReport {
+    /// Each foreign key with at least one violation, ordered by table name
+    pub foreign_key_violations: Vec<ForeignKeyViolations>,
+    /// Each table with at least one duplicate primary key, ordered by table name
+    pub duplicate_primary_keys: Vec<DuplicatePrimaryKeys>,
+}
+
+impl IntegrityReport {
+    /// Returns true if no violations were found
+    pub fn is_ok(&self) -> bool {
+        self.foreign_key_violations.is_empty() && self.duplicate_primary_keys.is_empty()
+    }
+}
+
+impl Display for IntegrityReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for violations in &self.foreign_key_violations {
+            let fk = &violations.foreign_key;
+            writeln!(
+                f,
+                "{} rows of {} violate the foreign key ({}) referencing {} ({})",
+                violations.count,
+                violations.table,
+                fk.columns.iter().join(", "),
+                fk.target_table,
+                fk.target_columns.iter().join(", "),
+            )?;
+            for row in &violations.samples {
+                let values = fk
+                    .columns
+                    .iter()
+                    .map(|col| format!("{col} = {}", row.get(col).unwrap_or(&DfValue::None)));
+                writeln!(f, "  {}", values.format(", "))?;
+            }
+        }
+        for duplicates in &self.duplicate_primary_keys {
+            writeln!(
+                f,
+                "{} rows of {} duplicate an earlier value of the primary key {}",
+                duplicates.count, duplicates.table, duplicates.column,
+            )?;
+            for value in &duplicates.samples {
+                writeln!(f, "  {} = {value}", duplicates.column)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns the values of the given columns in `row`, or `None` if any of them are missing or NULL
+fn key_values(row: &HashMap<ColumnName, DfValue>, columns: &[ColumnName]) -> Option<Vec<DfValue>> {
+    columns
+        .iter()
+        .map(|col| row.get(col).filter(|v| !v.is_none()).cloned())
+        .collect()
+}
+
+impl GeneratorState {
+    /// Check that `data`, which maps table names to the rows generated for each table, satisfies
+    /// all the foreign keys and primary keys declared on the tables in this generator state.
+    ///
+    /// A row violates a foreign key if its values for all of the foreign key columns are non-NULL,
+    /// and don't match the values of the target columns in any row of the target table. Rows with a
+    /// NULL value for any of the foreign key columns never violate the foreign key, per the
+    /// default `MATCH SIMPLE` semantics of SQL foreign keys. Tables which have no rows in `data`
+    /// are treated as empty.
+    ///
+    /// This runs in time linear in the number of rows in `data`.
+    pub fn check_referential_integrity(
+        &self,
+        data: &HashMap<TableName, Vec<HashMap<ColumnName, DfValue>>>,
+    ) -> IntegrityReport {
+        let no_rows = vec![];
+        let rows_for = |table: &TableName| data.get(table).unwrap_or(&no_rows);
+        // Sets of the keys of each target table, built at most once per set of target columns
+        let mut target_keys: HashMap<(&TableName, &[ColumnName]), HashSet<Vec<DfValue>>> =
+            HashMap::new();
+        let mut report = IntegrityReport::default();
+
+        for table in self.tables.values().sorted_by(|a, b| a.name.cmp(&b.name)) {
+            for fk in &table.foreign_keys {
+                let keys = target_keys
+                    .entry((&fk.target_table, fk.target_columns.as_slice()))
+                    .or_insert_with(|| {
+                        rows_for(&fk.target_table)
+                            .iter()
+                            .filter_map(|row| key_values(row, &fk.target_columns))
+                            .collect()
+                    });
+
+                let mut violations = ForeignKeyViolations {
+                    table: table.name.clone(),
+                    foreign_key: fk.clone(),
+                    count: 0,
+                    samples: vec![],
+                };
+                for row in rows_for(&table.name) {
+                    match key_values(row, &fk.columns) {
+                        Some(key) if !keys.contains(&key) => {
+                            violations.count += 1;
+                            if violations.samples.len() < MAX_SAMPLES {
+                                violations.samples.push(row.clone());
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+                if violations.count > 0 {
+                    report.foreign_key_violations.push(violations);
+                }
+            }
+
+            if let Some(pk) = &table.primary_key {
+                let mut seen = HashSet::new();
+                let mut duplicates = DuplicatePrimaryKeys {
+                    table: table.name.clone(),
+                    column: pk.clone(),
+                    count: 0,
+                    samples: vec![],
+                };
+                for value in rows_for(&table.name).iter().filter_map(|row| row.get(pk)) {
+                    if !seen.insert(value) {
+                        duplicates.count += 1;
+                        if duplicates.samples.len() < MAX_SAMPLES {
+                            duplicates.samples.push(value.clone());
+                        }
+                    }
+                }
+                if duplicates.count > 0 {
+                    report.duplicate_primary_keys.push(duplicates);
+                }
+            }
+        }
+
+        report
+    }
+
+    /// Returns the names of all the tables in this generator state, ordered so that each table
+    /// comes after all the tables its foreign keys reference, as long as the foreign keys don't
+    /// form a cycle. Otherwise, tables are ordered by name.
+    ///
+    /// Generating data for tables in this order allows the foreign keys of each table to be
+    /// filled in with [`Self::fill_foreign_keys`].
+    pub fn tables_in_foreign_key_order(&self) -> Vec<TableName> {
+        let mut remaining = self.tables.keys().sorted().collect::<Vec<_>>();
+        let mut order: Vec<TableName> = Vec::with_capacity(remaining.len());
+        while !remaining.is_empty() {
+            let ready = remaining
+                .iter()
+                .position(|table| {
+                    self.tables[*table].foreign_keys.iter().all(|fk| {
+                        fk.target_table == **table || !remaining.contains(&&fk.target_table)
+                    })
+                })
+                // The foreign keys of the remaining tables form a cycle, which we break at the
+                // first table
+                .unwrap_or(0);
+            order.push(remaining.remove(ready).clone());
+        }
+        order
+    }
+
+    /// Replace the values of the foreign key columns in each of `rows`, which were generated for
+    /// the table named `table_name`, with the values of the target columns of a random row of the
+    /// target table in `data`, so that the rows satisfy all the foreign keys of the table.
+    ///
+    /// Foreign keys whose target table has no rows in `data` (for example because the foreign keys
+    /// of the schema form a cycle) are left unchanged.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `table_name` is not a known table
+    pub fn fill_foreign_keys(
+        &self,
+        table_name: &TableName,
+        rows: &mut [HashMap<ColumnName, DfValue>],
+        data: &HashMap<TableName, Vec<HashMap<ColumnName, DfValue>>>,
+    ) {
+        let mut rng = thread_rng();
+        for fk in &self.tables[table_name].foreign_keys {
+            let Some(targets) = data.get(&fk.target_table).filter(|rows| !rows.is_empty()) else {
+                continue;
+            };
+            for row in rows.iter_mut() {
+                let target = targets.choose(&mut rng).unwrap();
+                for (col, target_col) in fk.columns.iter().zip(&fk.target_columns) {
+                    let value = target.get(target_col).cloned().unwrap_or(DfValue::None);
+                    row.insert(col.clone(), value);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nom_sql::Dialect;
+
+    use super::*;
+
+    fn gen() -> GeneratorState {
+        GeneratorState::from(
+            [
+                "CREATE TABLE customers (id INT, name TEXT, PRIMARY KEY (id))",
+                "CREATE TABLE orders (
+                    id INT,
+                    customer_id INT,
+                    PRIMARY KEY (id),
+                    FOREIGN KEY (customer_id) REFERENCES customers (id)
+                )",
+            ]
+            .into_iter()
+            .map(|ddl| nom_sql::parse_create_table(Dialect::MySQL, ddl).unwrap())
+            .collect::<Vec<_>>(),
+        )
+    }
+
+    fn row(values: &[(&str, DfValue)]) -> HashMap<ColumnName, DfValue> {
+        values
+            .iter()
+            .map(|(col, val)| (ColumnName::from(*col), val.clone()))
+            .collect()
+    }
+
+    fn order(id: i64, customer_id: DfValue) -> HashMap<ColumnName, DfValue> {
+        row(&[("id", id.into()), ("customer_id", customer_id)])
+    }
+
+    fn customers() -> Vec<HashMap<ColumnName, DfValue>> {
+        (1..=2)
+            .map(|id: i64| row(&[("id", id.into()), ("name", "customer".into())]))
+            .collect()
+    }
+
+    #[test]
+    fn generated_data_satisfies_foreign_keys() {
+        let mut gen = gen();
+        let tables = gen.tables_in_foreign_key_order();
+        assert_eq!(
+            tables,
+            vec![TableName::from("customers"), TableName::from("orders")]
+        );
+
+        let mut data = HashMap::new();
+        for table in tables {
+            let mut rows = gen.generate_data_for_table(&table, 50, false);
+            gen.fill_foreign_keys(&table, &mut rows, &data);
+            data.insert(table, rows);
+        }
+        let report = gen.check_referential_integrity(&data);
+        assert!(report.is_ok(), "{report}");
+    }
+
+    #[test]
+    fn valid_data() {
+        let data = HashMap::from([
+            (TableName::from("customers"), customers()),
+            (
+                TableName::from("orders"),
+                vec![
+                    order(1, 1.into()),
+                    order(2, 2.into()),
+                    order(3, DfValue::None),
+                ],
+            ),
+        ]);
+        let report = gen().check_referential_integrity(&data);
+        assert!(report.is_ok(), "{report}");
+    }
+
+    #[test]
+    fn orphan_row_and_duplicate_primary_key() {
+        let orphan = order(3, 7.into());
+        let data = HashMap::from([
+            (TableName::from("customers"), customers()),
+            (
+                TableName::from("orders"),
+                vec![
+                    order(1, 1.into()),
+                    order(2, DfValue::None),
+                    orphan.clone(),
+                    order(3, 2.into()),
+                ],
+            ),
+        ]);
+        let gen = gen();
+        let report = gen.check_referential_integrity(&data);
+        assert!(!report.is_ok());
+
+        assert_eq!(
+            report.foreign_key_violations,
+            vec![ForeignKeyViolations {
+                table: "orders".into(),
+                foreign_key: gen.table("orders").unwrap().foreign_keys[0].clone(),
+                count: 1,
+                samples: vec![orphan],
+            }]
+        );
+        assert_eq!(
+            report.duplicate_primary_keys,
+            vec![DuplicatePrimaryKeys {
+                table: "orders".into(),
+                column: "id".into(),
+                count: 1,
+                samples: vec![3.into()],
+            }]
+        );
+    }
+
+    #[test]
+    fn missing_target_table_data() {
+        let data = HashMap::from([(TableName::from("orders"), vec![order(1, 1.into())])]);
+        let report = gen().check_referential_integrity(&data);
+        assert_eq!(report.foreign_key_violations.len(), 1);
+        assert_eq!(report.foreign_key_violations[0].count, 1);
+    }
+}
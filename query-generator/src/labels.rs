@@ -0,0 +1,243 @@
+//! Deriving Prometheus-style metric labels for generated queries, so that metrics scraped from
+//! ReadySet while running generated queries can be joined against what each query contains.
+//!
+//! Labels are derived from the [`QueryOperation`]s the query was generated from (rather than from
+//! the SQL of the query), and only ever include low-cardinality values - the classes of operation
+//! present in the query, and counts of parameters, tables, and subqueries - along with a stable
+//! identifier for the query itself. Unbounded values such as literal constants are never included.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use nom_sql::JoinOperator;
+
+use crate::{canonicalize, AggregateType, Query, QueryOperation, QuerySeed};
+
+/// The maximum length of a label name or value returned by [`Query::metric_labels`]
+pub const MAX_LABEL_LENGTH: usize = 64;
+
+/// Sanitize `name` so that it's a valid Prometheus label name: only ASCII alphanumeric characters
+/// and underscores, not starting with a digit, not starting with `__` (which is reserved for
+/// internal use by Prometheus), and at most [`MAX_LABEL_LENGTH`] characters long.
+pub fn sanitize_label_name(name: &str) -> String {
+    let mut res = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect::<String>();
+    while res.starts_with("__") {
+        res.remove(0);
+    }
+    if res.is_empty() || res.starts_with(|c: char| c.is_ascii_digit()) {
+        res.insert(0, '_');
+    }
+    res.truncate(MAX_LABEL_LENGTH);
+    res
+}
+
+/// Sanitize `value` so that it can be used as a label value without needing to be escaped: only
+/// ASCII alphanumeric characters, underscores, commas, periods, and hyphens, and at most
+/// [`MAX_LABEL_LENGTH`] characters long.
+pub fn sanitize_label_value(value: &str) -> String {
+    let mut res = value
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '_' | ',' | '.' | '-') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect::<String>();
+    res.truncate(MAX_LABEL_LENGTH);
+    res
+}
+
+fn join_operator_class(operator: &JoinOperator) -> &'static str {
+    match operator {
+        JoinOperator::Join | JoinOperator::InnerJoin => "inner",
+        JoinOperator::LeftJoin | JoinOperator::LeftOuterJoin => "left",
+        JoinOperator::RightJoin => "right",
+        JoinOperator::CrossJoin => "cross",
+        JoinOperator::StraightJoin => "straight",
+    }
+}
+
+fn aggregate_class(aggregate: &AggregateType) -> &'static str {
+    match aggregate {
+        AggregateType::Count { .. } => "count",
+        AggregateType::Sum { .. } => "sum",
+        AggregateType::Avg { .. } => "avg",
+        AggregateType::GroupConcat => "group_concat",
+        AggregateType::Max { .. } => "max",
+        AggregateType::Min { .. } => "min",
+        AggregateType::BitAnd { .. } => "bit_and",
+        AggregateType::BitOr { .. } => "bit_or",
+        AggregateType::BitXor { .. } => "bit_xor",
+    }
+}
+
+impl QueryOperation {
+    /// Returns the label name and value for the class of this operation, if it has one
+    fn label_class(&self) -> Option<(&'static str, &'static str)> {
+        match self {
+            QueryOperation::ColumnAggregate(agg) => Some(("aggregates", aggregate_class(agg))),
+            QueryOperation::Filter(_) => Some(("filters", "true")),
+            QueryOperation::Distinct => Some(("distinct", "true")),
+            QueryOperation::Join { operator, .. } => Some(("joins", join_operator_class(operator))),
+            QueryOperation::AntiJoin { .. } => Some(("joins", "anti")),
+            QueryOperation::TopK { .. } | QueryOperation::ParamTopK { .. } => {
+                Some(("topk", "true"))
+            }
+            QueryOperation::Paginate { .. } => Some(("paginate", "true")),
+            QueryOperation::GroupByExpression { .. } => Some(("group_by_expression", "true")),
+            QueryOperation::Rollup { .. } => Some(("rollup", "true")),
+            QueryOperation::WindowRankFunction { .. } => Some(("window", "true")),
+            QueryOperation::JsonPath { .. } => Some(("json_path", "true")),
+            QueryOperation::ProjectLiteral(_)
+            | QueryOperation::ProjectBuiltinFunction(_)
+            | QueryOperation::SingleParameter
+            | QueryOperation::MultipleParameters
+            | QueryOperation::InParameter { .. }
+            | QueryOperation::RangeParameter
+            | QueryOperation::MultipleRangeParameters
+            | QueryOperation::ParameterWithLiteralConstraint { .. }
+            | QueryOperation::Subquery(_) => None,
+        }
+    }
+}
+
+impl QuerySeed {
+    /// Returns the maximum depth of subqueries nested within this seed
+    fn subquery_depth(&self) -> usize {
+        self.subqueries
+            .iter()
+            .map(|sq| sq.seed.subquery_depth() + 1)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Record the label class of each operation in this seed and all of its subqueries
+    fn collect_label_classes(&self, classes: &mut BTreeMap<&'static str, BTreeSet<&'static str>>) {
+        for (name, value) in self.operations.iter().filter_map(|op| op.label_class()) {
+            classes.entry(name).or_default().insert(value);
+        }
+        for subquery in &self.subqueries {
+            subquery.seed.collect_label_classes(classes);
+        }
+    }
+}
+
+impl<'gen> Query<'gen> {
+    /// Returns an identifier for this query which is stable across runs of the query generator,
+    /// as long as the same query is generated. The identifier is derived from the
+    /// [canonicalized](canonicalize) query, so it doesn't depend on the names of the tables,
+    /// columns, and aliases in the query, and hashed with
+    /// [`stable_hash`](readyset_util::hash::stable_hash).
+    pub fn stable_id(&self) -> String {
+        format!("{:016x}", canonicalize(&self.statement).hash)
+    }
+
+    /// Returns a set of low-cardinality metric labels describing this query, derived from the
+    /// operations it was generated from.
+    ///
+    /// Each class of operation present in the query gets its own label (eg `joins=inner,left`,
+    /// `aggregates=count`, or `topk=true`), along with the number of `parameters`, the number of
+    /// `tables`, the `subquery_depth`, and the [`stable_id`](Self::stable_id) of the query. All
+    /// label names and values are sanitized with [`sanitize_label_name`] and
+    /// [`sanitize_label_value`].
+    pub fn metric_labels(&self) -> BTreeMap<String, String> {
+        let mut classes = BTreeMap::new();
+        self.seed.collect_label_classes(&mut classes);
+
+        classes
+            .into_iter()
+            .map(|(name, values)| (name, values.into_iter().collect::<Vec<_>>().join(",")))
+            .chain([
+                ("parameters", self.state.parameters.len().to_string()),
+                ("tables", self.state.tables().count().to_string()),
+                ("subquery_depth", self.seed.subquery_depth().to_string()),
+                ("stable_id", self.stable_id()),
+            ])
+            .map(|(name, value)| (sanitize_label_name(name), sanitize_label_value(&value)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nom_sql::{OrderType, SqlType};
+
+    use super::*;
+    use crate::{GeneratorState, JoinConstraintType};
+
+    fn seed() -> QuerySeed {
+        QuerySeed::new(
+            vec![
+                QueryOperation::Join {
+                    operator: JoinOperator::InnerJoin,
+                    constraint: JoinConstraintType::On,
+                    key_type: SqlType::Int(None),
+                    key_columns: 1,
+                },
+                QueryOperation::ColumnAggregate(AggregateType::Count {
+                    column_type: SqlType::Int(None),
+                    distinct: false,
+                }),
+                QueryOperation::SingleParameter,
+                QueryOperation::TopK {
+                    order_type: OrderType::OrderAscending,
+                    limit: 3,
+                },
+            ],
+            vec![],
+        )
+    }
+
+    #[test]
+    fn metric_labels() {
+        let mut gen = GeneratorState::default();
+        let labels = gen.generate_query(seed()).metric_labels();
+        assert_eq!(labels["joins"], "inner");
+        assert_eq!(labels["aggregates"], "count");
+        assert_eq!(labels["topk"], "true");
+        assert_eq!(labels["parameters"], "1");
+        assert!(labels["tables"].parse::<usize>().unwrap() >= 2);
+        assert_eq!(labels["subquery_depth"], "0");
+        assert!(labels.contains_key("stable_id"));
+    }
+
+    #[test]
+    fn metric_labels_stable_across_regeneration() {
+        let first = GeneratorState::default()
+            .generate_query(seed())
+            .metric_labels();
+        let second = GeneratorState::default()
+            .generate_query(seed())
+            .metric_labels();
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn sanitize_adversarial_label_names() {
+        assert_eq!(sanitize_label_name("joins"), "joins");
+        assert_eq!(sanitize_label_name("__name__"), "_name__");
+        assert_eq!(sanitize_label_name("9lives"), "_9lives");
+        assert_eq!(sanitize_label_name(""), "_");
+        assert_eq!(sanitize_label_name("op\"}{evil=\"1"), "op___evil__1");
+        assert_eq!(sanitize_label_name("jöins"), "j_ins");
+        assert_eq!(
+            sanitize_label_name(&"a".repeat(100)).len(),
+            MAX_LABEL_LENGTH
+        );
+    }
+
+    #[test]
+    fn sanitize_adversarial_label_values() {
+        assert_eq!(sanitize_label_value("inner,left"), "inner,left");
+        assert_eq!(sanitize_label_value("1\"} evil{"), "1___evil_");
+        assert_eq!(sanitize_label_value("line\nbreak"), "line_break");
+        assert_eq!(
+            sanitize_label_value(&"🦀".repeat(100)).len(),
+            MAX_LABEL_LENGTH
+        );
+    }
+}
@@ -1,4 +1,4 @@
-#![feature(never_type)]
+#![feature(never_type, exhaustive_patterns)]
 //! A deterministic, exhaustive, parametric generator for SQL queries, and associated DDL.
 //!
 //! The intent of this library is to provide a hook for generating SQL queries both
@@ -19,14 +19,19 @@
 //! Generating a simple query, with a single query parameter and a single inner join:
 //!
 //! ```rust
-//! use nom_sql::{Dialect, DialectDisplay, JoinOperator};
-//! use query_generator::{GeneratorState, QueryOperation, QuerySeed};
+//! use nom_sql::{Dialect, DialectDisplay, JoinOperator, SqlType};
+//! use query_generator::{GeneratorState, JoinConstraintType, QueryOperation, QuerySeed};
 //!
 //! let mut gen = GeneratorState::default();
 //! let query = gen.generate_query(QuerySeed::new(
 //!     vec![
 //!         QueryOperation::SingleParameter,
-//!         QueryOperation::Join(JoinOperator::InnerJoin),
+//!         QueryOperation::Join {
+//!             operator: JoinOperator::InnerJoin,
+//!             constraint: JoinConstraintType::On,
+//!             key_type: SqlType::Int(None),
+//!             key_columns: 1,
+//!         },
 //!     ],
 //!     vec![],
 //! ));
@@ -59,7 +64,24 @@
 //! [2]: TableSpec::fresh_column
 //! [3]: QueryOperation::permute
 
+pub mod artifact;
+pub mod canonical;
+pub mod coercion;
+pub mod dml;
+pub mod equivalence;
+pub mod estimate;
+pub mod integrity;
+pub mod labels;
+#[cfg(feature = "parallel")]
+pub mod parallel;
+pub mod parameterization;
+pub mod placement;
+pub mod pushdown;
+pub mod runtime;
+pub mod schema_diff;
+mod spec;
 mod types;
+pub mod validate;
 
 use std::borrow::Borrow;
 use std::collections::{HashMap, HashSet};
@@ -70,39 +92,52 @@ use std::iter::{self, FromIterator};
 use std::ops::{Bound, DerefMut};
 use std::str::FromStr;
 use std::sync::Arc;
+use std::{fmt, mem};
 
-use anyhow::anyhow;
+use anyhow::{anyhow, bail};
 use clap::Parser;
 use data_generator::{
     random_value_of_type, unique_value_of_type, ColumnGenerationSpec, ColumnGenerator,
     DistributionAnnotation,
 };
 use derive_more::{Deref, Display, From, Into};
+use futures_util::stream::{self, Stream, StreamExt};
 use itertools::{Either, Itertools};
 use lazy_static::lazy_static;
+use nom_sql::analysis::visit::Visitor;
+use nom_sql::analysis::visit_mut::{walk_select_statement, VisitorMut};
 use nom_sql::analysis::{contains_aggregate, ReferredColumns};
 use nom_sql::{
     BinaryOperator, Column, ColumnConstraint, ColumnSpecification, CommonTableExpr,
-    CreateTableBody, CreateTableStatement, Dialect as ParseDialect, Expr, FieldDefinitionExpr,
-    FieldReference, FunctionExpr, InValue, ItemPlaceholder, JoinClause, JoinConstraint,
-    JoinOperator, JoinRightSide, LimitClause, LimitValue, Literal, OrderBy, OrderClause, OrderType,
-    Relation, SelectStatement, SqlIdentifier, SqlType, SqlTypeArbitraryOptions, TableExpr,
-    TableExprInner, TableKey,
+    CreateTableBody, CreateTableStatement, Dialect as ParseDialect, DialectDisplay, Double, Expr,
+    FieldDefinitionExpr, FieldReference, FunctionExpr, InValue, ItemPlaceholder, JoinClause,
+    JoinConstraint, JoinOperator, JoinRightSide, LimitClause, LimitValue, Literal, OrderBy,
+    OrderClause, OrderType, Relation, SelectStatement, SqlIdentifier, SqlType,
+    SqlTypeArbitraryOptions, TableExpr, TableExprInner, TableKey,
 };
 use parking_lot::Mutex;
 use proptest::arbitrary::{any, any_with, Arbitrary};
+use proptest::prop_oneof;
 use proptest::sample::Select;
 use proptest::strategy::{BoxedStrategy, Strategy};
-use rand::thread_rng;
-use readyset_data::{DfType, DfValue, Dialect};
-use readyset_sql_passes::outermost_table_exprs;
+use rand::{thread_rng, Rng};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use readyset_data::{DfType, DfValue, Dialect as DataDialect};
+use readyset_sql_passes::{outermost_table_exprs, AliasRemoval, Rewrite, RewriteContext};
 use readyset_util::intervals::{BoundPair, IterBoundPair};
 use serde::{Deserialize, Serialize};
 use strum::IntoEnumIterator;
 use strum_macros::EnumIter;
 use test_strategy::Arbitrary;
 
-use crate::types::{arbitrary_numeric_type, arbitrary_postgres_min_max_arg_type};
+pub use crate::canonical::{canonicalize, CanonicalQuery};
+use crate::estimate::{ResultSizeEstimate, ResultSizeRange};
+use crate::placement::{Placement, PlacementError, PlacementPlan, PlacementTarget};
+use crate::spec::{split_operation_list, OperationSpec};
+use crate::types::{
+    arbitrary_integer_type, arbitrary_numeric_type, arbitrary_postgres_min_max_arg_type,
+};
 
 /// Query dialect to use when generating queries.
 ///
@@ -122,7 +157,55 @@ impl PartialEq<ParseDialect> for QueryDialect {
     }
 }
 
-#[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Hash, From, Into, Display, Clone)]
+/// Database dialect to generate `EXPLAIN` statements for, via
+/// [`Query::generate_explain_for_dialect`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Dialect {
+    /// `EXPLAIN <query>`
+    #[value(name = "mysql")]
+    MySQL,
+    /// `EXPLAIN ANALYZE <query>`
+    #[value(name = "postgresql", alias("postgres"))]
+    PostgreSQL,
+    /// `EXPLAIN QUERY PLAN <query>`
+    #[value(name = "sqlite")]
+    SQLite,
+}
+
+impl Dialect {
+    /// Returns the keyword(s) which prefix a query to explain it in this dialect
+    pub fn explain_prefix(self) -> &'static str {
+        match self {
+            Dialect::MySQL => "EXPLAIN",
+            Dialect::PostgreSQL => "EXPLAIN ANALYZE",
+            Dialect::SQLite => "EXPLAIN QUERY PLAN",
+        }
+    }
+
+    /// Returns the [`ParseDialect`] to use when displaying queries for this dialect.
+    ///
+    /// SQLite quotes identifiers the same way PostgreSQL does, so we display queries for it using
+    /// the PostgreSQL dialect.
+    fn display_dialect(self) -> ParseDialect {
+        match self {
+            Dialect::MySQL => ParseDialect::MySQL,
+            Dialect::PostgreSQL | Dialect::SQLite => ParseDialect::PostgreSQL,
+        }
+    }
+}
+
+impl From<ParseDialect> for Dialect {
+    fn from(dialect: ParseDialect) -> Self {
+        match dialect {
+            ParseDialect::MySQL => Dialect::MySQL,
+            ParseDialect::PostgreSQL => Dialect::PostgreSQL,
+        }
+    }
+}
+
+#[derive(
+    Debug, Eq, PartialEq, Ord, PartialOrd, Hash, From, Into, Display, Clone, Serialize, Deserialize,
+)]
 #[repr(transparent)]
 pub struct TableName(SqlIdentifier);
 
@@ -262,16 +345,171 @@ pub struct ColumnDataGeneration {
 pub struct ColumnSpec {
     pub sql_type: SqlType,
     pub gen_spec: Arc<Mutex<ColumnDataGeneration>>,
+    /// The expected number of distinct values in the column, if known.
+    ///
+    /// This isn't used when generating data for the column, but is kept up to date with the
+    /// column's generator where possible - see [`TableSpec::cardinality_hint_for`].
+    pub cardinality_hint: Option<u64>,
+}
+
+/// Returns the number of distinct values generated by `spec`, if that's known independently of the
+/// number of rows generated
+fn cardinality_hint_for_spec(spec: &ColumnGenerationSpec) -> Option<u64> {
+    match spec {
+        ColumnGenerationSpec::WithCardinality { distinct, .. } => Some(*distinct),
+        _ => None,
+    }
+}
+
+/// The kind of a secondary index on a table, as specified by an [`IndexSpec`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IndexKind {
+    /// A `UNIQUE KEY`
+    Unique,
+    /// A plain `KEY`
+    NonUnique,
+    /// A `FULLTEXT KEY`
+    FullText,
+}
+
+/// Specification for a secondary index on a table
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct IndexSpec {
+    /// The name of the index, if any
+    pub name: Option<String>,
+    /// The columns in the index, in order
+    pub columns: Vec<ColumnName>,
+    pub kind: IndexKind,
+}
+
+impl IndexSpec {
+    /// Construct an [`IndexSpec`] from the given [`TableKey`], or return `None` if the key isn't a
+    /// secondary index (eg if it's a primary key, foreign key, or check constraint)
+    pub fn from_table_key(key: &TableKey) -> Option<Self> {
+        let (index_name, columns, kind) = match key {
+            TableKey::UniqueKey {
+                index_name,
+                columns,
+                ..
+            } => (index_name, columns, IndexKind::Unique),
+            TableKey::Key {
+                index_name,
+                columns,
+                ..
+            } => (index_name, columns, IndexKind::NonUnique),
+            TableKey::FulltextKey {
+                index_name,
+                columns,
+            } => (index_name, columns, IndexKind::FullText),
+            _ => return None,
+        };
+
+        Some(Self {
+            name: index_name.as_ref().map(|n| n.to_string()),
+            columns: columns.iter().cloned().map(ColumnName::from).collect(),
+            kind,
+        })
+    }
+}
+
+/// Specification for a foreign key from some columns of a table to columns of another table
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ForeignKeySpec {
+    /// The name of the constraint, if any
+    pub name: Option<String>,
+    /// The columns of the table the foreign key is on, in order
+    pub columns: Vec<ColumnName>,
+    /// The table referenced by the foreign key
+    pub target_table: TableName,
+    /// The columns of [`Self::target_table`] referenced by the foreign key, in the same order as
+    /// [`Self::columns`]
+    pub target_columns: Vec<ColumnName>,
+}
+
+impl ForeignKeySpec {
+    /// Construct a [`ForeignKeySpec`] from the given [`TableKey`], or return `None` if the key
+    /// isn't a foreign key
+    pub fn from_table_key(key: &TableKey) -> Option<Self> {
+        match key {
+            TableKey::ForeignKey {
+                constraint_name,
+                columns,
+                target_table,
+                target_columns,
+                ..
+            } => Some(Self {
+                name: constraint_name.as_ref().map(|n| n.to_string()),
+                columns: columns.iter().cloned().map(ColumnName::from).collect(),
+                target_table: target_table.name.clone().into(),
+                target_columns: target_columns
+                    .iter()
+                    .cloned()
+                    .map(ColumnName::from)
+                    .collect(),
+            }),
+            _ => None,
+        }
+    }
+}
+
+impl From<ForeignKeySpec> for TableKey {
+    fn from(spec: ForeignKeySpec) -> Self {
+        TableKey::ForeignKey {
+            constraint_name: spec.name.map(SqlIdentifier::from),
+            index_name: None,
+            columns: spec.columns.into_iter().map(Column::from).collect(),
+            target_table: spec.target_table.into(),
+            target_columns: spec.target_columns.into_iter().map(Column::from).collect(),
+            on_delete: None,
+            on_update: None,
+        }
+    }
+}
+
+impl From<IndexSpec> for TableKey {
+    fn from(spec: IndexSpec) -> Self {
+        let index_name = spec.name.map(SqlIdentifier::from);
+        let columns = spec.columns.into_iter().map(Column::from).collect();
+        match spec.kind {
+            IndexKind::Unique => TableKey::UniqueKey {
+                constraint_name: None,
+                index_name,
+                columns,
+                index_type: None,
+            },
+            IndexKind::NonUnique => TableKey::Key {
+                constraint_name: None,
+                index_name,
+                columns,
+                index_type: None,
+            },
+            IndexKind::FullText => TableKey::FulltextKey {
+                index_name,
+                columns,
+            },
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct TableSpec {
     pub name: TableName,
     pub columns: HashMap<ColumnName, ColumnSpec>,
+    /// The names of all the columns in [`Self::columns`], in the order they should appear in DDL
+    /// for the table. Any columns missing from this (if [`Self::columns`] was modified directly)
+    /// appear in DDL after these, sorted by name.
+    pub column_order: Vec<ColumnName>,
     column_name_counter: u32,
 
     /// Name of the primary key column for the table, if any
     pub primary_key: Option<ColumnName>,
+
+    /// Secondary indexes on the table, in the order they should appear in DDL for the table
+    pub indexes: Vec<IndexSpec>,
+
+    /// Foreign keys from columns of this table to other tables, in the order they should appear in
+    /// DDL for the table
+    pub foreign_keys: Vec<ForeignKeySpec>,
 }
 
 impl From<CreateTableStatement> for TableSpec {
@@ -283,24 +521,36 @@ impl From<CreateTableStatement> for TableSpec {
 
         let mut spec = TableSpec {
             name: stmt.table.name.into(),
+            column_order: body
+                .fields
+                .iter()
+                .map(|field| field.column.name.clone().into())
+                .collect(),
             columns: body
                 .fields
                 .iter()
                 .map(|field| {
                     let sql_type = field.sql_type.clone();
                     let df_type =
-                        DfType::from_sql_type(&sql_type, Dialect::DEFAULT_MYSQL, |_| None).unwrap();
+                        DfType::from_sql_type(&sql_type, DataDialect::DEFAULT_MYSQL, |_| None)
+                            .unwrap();
 
-                    let generator = if let Some(d) =
-                        field.has_default().and_then(|l| DfValue::try_from(l).ok())
-                    {
-                        // Prefer the specified default value for a field
-                        ColumnGenerator::Constant(
+                    let default_spec = field.constraints.iter().find_map(|c| match c {
+                        ColumnConstraint::DefaultValue(expr) => {
+                            ColumnGenerationSpec::from_sql_default(expr).ok()
+                        }
+                        _ => None,
+                    });
+                    let cardinality_hint =
+                        default_spec.as_ref().and_then(cardinality_hint_for_spec);
+                    let generator = match default_spec {
+                        // Prefer generating values like the specified default value for a field
+                        Some(ColumnGenerationSpec::Constant(d)) => ColumnGenerator::Constant(
                             d.coerce_to(&df_type, &DfType::Unknown).unwrap().into(),
-                        )
-                    } else {
+                        ),
+                        Some(spec) => spec.generator_for_col(sql_type.clone()),
                         // Otherwise default to generating fields with a constant value.
-                        ColumnGenerator::Constant(sql_type.clone().into())
+                        None => ColumnGenerator::Constant(sql_type.clone().into()),
                     };
 
                     (
@@ -311,12 +561,25 @@ impl From<CreateTableStatement> for TableSpec {
                                 generator,
                                 expected_values: HashSet::new(),
                             })),
+                            cardinality_hint,
                         },
                     )
                 })
                 .collect(),
             column_name_counter: 0,
             primary_key: primary_key.clone(),
+            indexes: body
+                .keys
+                .iter()
+                .flatten()
+                .filter_map(IndexSpec::from_table_key)
+                .collect(),
+            foreign_keys: body
+                .keys
+                .iter()
+                .flatten()
+                .filter_map(ForeignKeySpec::from_table_key)
+                .collect(),
         };
 
         for col in body
@@ -370,42 +633,185 @@ impl From<CreateTableStatement> for TableSpec {
 }
 
 impl From<TableSpec> for CreateTableStatement {
-    fn from(spec: TableSpec) -> Self {
+    fn from(mut spec: TableSpec) -> Self {
+        let keys = spec
+            .primary_key
+            .map(|cn| TableKey::PrimaryKey {
+                index_name: None,
+                constraint_name: None,
+                columns: vec![cn.into()],
+            })
+            .into_iter()
+            .chain(spec.indexes.into_iter().map(TableKey::from))
+            .chain(spec.foreign_keys.into_iter().map(TableKey::from))
+            .collect::<Vec<_>>();
+
+        let mut unordered_columns = spec
+            .columns
+            .keys()
+            .filter(|col| !spec.column_order.contains(col))
+            .cloned()
+            .collect::<Vec<_>>();
+        unordered_columns.sort();
+
         CreateTableStatement {
             if_not_exists: false,
             table: spec.name.into(),
             body: Ok(CreateTableBody {
                 fields: spec
-                    .columns
+                    .column_order
                     .into_iter()
-                    .map(|(col_name, col_type)| ColumnSpecification {
-                        column: col_name.into(),
-                        sql_type: col_type.sql_type,
-                        constraints: vec![],
-                        comment: None,
+                    .chain(unordered_columns)
+                    .filter_map(|col_name| {
+                        let col_type = spec.columns.remove(&col_name)?;
+                        Some(ColumnSpecification {
+                            column: col_name.into(),
+                            sql_type: col_type.sql_type,
+                            constraints: vec![],
+                            comment: None,
+                        })
                     })
                     .collect(),
-                keys: spec.primary_key.map(|cn| {
-                    vec![TableKey::PrimaryKey {
-                        index_name: None,
-                        constraint_name: None,
-                        columns: vec![cn.into()],
-                    }]
-                }),
+                keys: if keys.is_empty() { None } else { Some(keys) },
             }),
             options: Ok(vec![]),
         }
     }
 }
 
+/// Options for deliberately generating rows which violate the unique keys of a table, with
+/// [`TableSpec::generate_data_with_conflicts`], to test that writes of such rows are rejected or
+/// reconciled correctly.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ConflictInjection {
+    /// The number out of every 1000 rows which should duplicate the values of the unique key
+    /// columns of an earlier row. Values over 1000 are treated as 1000.
+    pub unique_key_violations_per_1000: u32,
+    /// If true, each conflicting row is an exact duplicate of the earlier row it conflicts with.
+    /// Otherwise, it differs from that row in the value of its first non-key column (if it has
+    /// any).
+    pub exact_duplicates: bool,
+}
+
+impl ConflictInjection {
+    /// Returns true if the row at `index` should conflict with an earlier row.
+    ///
+    /// Conflicts are spread evenly, such that the first `n` rows contain
+    /// `n * unique_key_violations_per_1000 / 1000` conflicts (rounded down), except that the first
+    /// row never conflicts since there's no earlier row for it to conflict with.
+    fn is_conflict(&self, index: usize) -> bool {
+        let rate = self.unique_key_violations_per_1000.min(1000) as usize;
+        index > 0 && (index + 1) * rate / 1000 > index * rate / 1000
+    }
+}
+
+/// Limits on the approximate amount of memory taken up by the rows generated for a table, to avoid
+/// running out of memory when generating many rows of tables with large text or blob columns.
+///
+/// Sizes are estimated with [`approximate_value_size`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DataGenerationBudget {
+    /// If set, stop generating rows before the total size of the rows generated exceeds this many
+    /// bytes
+    pub max_bytes_total: Option<u64>,
+    /// If set, truncate variable-length values in each row (other than the values of columns with
+    /// unique generators) so that the row takes up at most this many bytes
+    pub max_bytes_per_row: Option<u64>,
+}
+
+/// Marker for data generation which stopped early because it reached the
+/// [`max_bytes_total`](DataGenerationBudget::max_bytes_total) of its [`DataGenerationBudget`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BudgetExceeded {
+    /// The number of rows that were requested
+    pub rows_requested: usize,
+}
+
+/// The rows generated for a table by [`TableSpec::generate_data_with_budget`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeneratedData {
+    pub rows: Vec<HashMap<ColumnName, DfValue>>,
+    /// The approximate total size of [`Self::rows`], in bytes
+    pub approximate_bytes: u64,
+    /// Set if fewer rows were generated than requested because the budget ran out
+    pub budget_exceeded: Option<BudgetExceeded>,
+}
+
+/// Returns the approximate number of bytes taken up by `value`: the length of its contents for
+/// text and byte array values, and the size of a [`DfValue`] for everything else
+pub fn approximate_value_size(value: &DfValue) -> u64 {
+    let len = match value {
+        DfValue::Text(_) | DfValue::TinyText(_) => value.as_str().map_or(0, str::len),
+        DfValue::ByteArray(bytes) => bytes.len(),
+        _ => mem::size_of::<DfValue>(),
+    };
+    len as u64
+}
+
+/// Truncate the text or bytes of `value` to at most `max_bytes` bytes. Other values are left
+/// unchanged.
+fn truncate_value(value: &mut DfValue, max_bytes: u64) {
+    let max_bytes = usize::try_from(max_bytes).unwrap_or(usize::MAX);
+    match value {
+        DfValue::Text(_) | DfValue::TinyText(_) => {
+            let Some(s) = value.as_str() else { return };
+            if s.len() <= max_bytes {
+                return;
+            }
+            let mut end = max_bytes;
+            while !s.is_char_boundary(end) {
+                end -= 1;
+            }
+            *value = DfValue::from(&s[..end]);
+        }
+        DfValue::ByteArray(bytes) if bytes.len() > max_bytes => {
+            *value = DfValue::ByteArray(Arc::new(bytes[..max_bytes].to_vec()));
+        }
+        _ => {}
+    }
+}
+
 impl TableSpec {
     pub fn new(name: TableName) -> Self {
         Self {
             name,
             columns: Default::default(),
+            column_order: Default::default(),
             column_name_counter: 0,
             primary_key: None,
+            indexes: vec![],
+            foreign_keys: vec![],
+        }
+    }
+
+    /// Add a secondary index to this table.
+    ///
+    /// If the index is unique, each of its columns will be generated with unique values.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any of the columns in the index don't exist in the table
+    pub fn add_index(&mut self, index: IndexSpec) {
+        for col in &index.columns {
+            let col_spec = self
+                .columns
+                .get(col)
+                .unwrap_or_else(|| panic!("Column {col} does not exist in table {}", self.name));
+            if index.kind == IndexKind::Unique {
+                col_spec.gen_spec.lock().generator =
+                    ColumnGenerator::Unique(col_spec.sql_type.clone().into());
+            }
         }
+        self.indexes.push(index);
+    }
+
+    /// Returns true if lookups on the given column can use an index on this table - either the
+    /// primary key or a (non-fulltext) secondary index whose first column is that column
+    pub fn has_index_on(&self, column: &ColumnName) -> bool {
+        self.primary_key.as_ref() == Some(column)
+            || self.indexes.iter().any(|index| {
+                index.kind != IndexKind::FullText && index.columns.first() == Some(column)
+            })
     }
 
     /// Generate a new, unique column in this table (of an unspecified type) and return its name
@@ -417,6 +823,13 @@ impl TableSpec {
     pub fn fresh_column_with_type(&mut self, col_type: SqlType) -> ColumnName {
         self.column_name_counter += 1;
         let column_name = ColumnName(format!("column_{}", self.column_name_counter).into());
+        self.add_column(column_name.clone(), col_type);
+        column_name
+    }
+
+    /// Add a new column with the given name and type to this table, which must not already have a
+    /// column with that name
+    fn add_column(&mut self, column_name: ColumnName, col_type: SqlType) {
         self.columns.insert(
             column_name.clone(),
             ColumnSpec {
@@ -425,9 +838,10 @@ impl TableSpec {
                     generator: ColumnGenerator::Constant(col_type.into()),
                     expected_values: HashSet::new(),
                 })),
+                cardinality_hint: None,
             },
         );
-        column_name
+        self.column_order.push(column_name);
     }
 
     /// Returns the name of *some* column in this table which passes filter, potentially generating
@@ -452,6 +866,47 @@ impl TableSpec {
         self.some_column_name_filtered(|| SqlType::Int(None), |_, _| true)
     }
 
+    /// Returns the names of the columns in this table to join on with `num_columns` key columns of
+    /// the given type, potentially generating new columns.
+    ///
+    /// Joins on a single key column use [*some* column](Self::some_column_with_type) of the type.
+    /// Composite joins always use fresh columns, generated with
+    /// [`Self::composite_join_key_spec`] for their position in the key.
+    pub fn join_key_columns(&mut self, key_type: &SqlType, num_columns: usize) -> Vec<ColumnName> {
+        if num_columns == 1 {
+            return vec![self.some_column_with_type(key_type.clone())];
+        }
+
+        (0..num_columns)
+            .map(|position| {
+                let column = self.fresh_column_with_type(key_type.clone());
+                self.set_column_generator_spec(
+                    column.clone(),
+                    Self::composite_join_key_spec(position, num_columns),
+                );
+                column
+            })
+            .collect()
+    }
+
+    /// Returns the generator spec for the column at `position` in a composite join key with
+    /// `num_columns` columns.
+    ///
+    /// Each column repeats each of its values a different number of times: the last column is
+    /// unique, and earlier columns repeat their values more often, so that row `i` has the key
+    /// `(i / n, ..., i / 2, i)`. No prefix of the key identifies a row, so a join which only
+    /// compared some of the key columns would match more rows than one comparing all of them.
+    ///
+    /// Since the values only depend on the index of the row, every table given keys this way has
+    /// the same sequence of key tuples, so the tuples of the table with fewer rows are a sample of
+    /// the tuples of the other, and every row on that side of the join has a match.
+    fn composite_join_key_spec(position: usize, num_columns: usize) -> ColumnGenerationSpec {
+        match num_columns - position {
+            1 => ColumnGenerationSpec::Unique,
+            repeat => ColumnGenerationSpec::UniqueRepeated(repeat.try_into().unwrap_or(u32::MAX)),
+        }
+    }
+
     /// Returns the name of *some* column in this table with the given type, potentially generating
     /// a new column if necessary
     pub fn some_column_with_type(&mut self, col_type: SqlType) -> ColumnName {
@@ -521,12 +976,8 @@ impl TableSpec {
     ) {
         assert!(self.columns.contains_key(&column_name));
         let col_spec = self.columns.get_mut(&column_name).unwrap();
-        self.columns
-            .get_mut(&column_name)
-            .unwrap()
-            .gen_spec
-            .lock()
-            .generator = spec.generator_for_col(col_spec.sql_type.clone());
+        col_spec.gen_spec.lock().generator = spec.generator_for_col(col_spec.sql_type.clone());
+        col_spec.cardinality_hint = cardinality_hint_for_spec(&spec);
     }
 
     /// Overrides the existing `gen_spec` for a set of columns..
@@ -536,6 +987,36 @@ impl TableSpec {
         }
     }
 
+    /// Set the expected number of distinct values in the column given by `column_name`, overriding
+    /// any hint derived from the column's generator.
+    ///
+    /// Note that changing the generator for the column (or generating data for a column with a
+    /// unique generator) will replace this hint.
+    pub fn set_cardinality_hint(&mut self, column_name: &ColumnName, hint: Option<u64>) {
+        assert!(self.columns.contains_key(column_name));
+        self.columns.get_mut(column_name).unwrap().cardinality_hint = hint;
+    }
+
+    /// Returns the expected number of distinct values in the column given by `column_name`, if
+    /// known.
+    ///
+    /// Columns generated with [`ColumnGenerationSpec::WithCardinality`] are hinted with their
+    /// number of distinct values, and columns with unique generators are hinted with the number of
+    /// distinct values among the rows most recently generated for the table.
+    pub fn cardinality_hint_for(&self, column_name: &ColumnName) -> Option<u64> {
+        self.columns.get(column_name)?.cardinality_hint
+    }
+
+    /// Update the cardinality hints of all columns with unique generators, for generating
+    /// `num_rows` rows of data for this table
+    fn hint_unique_cardinalities(&mut self, num_rows: usize) {
+        for col_spec in self.columns.values_mut() {
+            if let ColumnGenerator::Unique(g) = &col_spec.gen_spec.lock().generator {
+                col_spec.cardinality_hint = Some(g.num_distinct(num_rows as u64));
+            }
+        }
+    }
+
     fn generate_row(&mut self, index: usize, random: bool) -> HashMap<ColumnName, DfValue> {
         self.columns
             .iter_mut()
@@ -545,6 +1026,7 @@ impl TableSpec {
                     ColumnSpec {
                         sql_type: col_type,
                         gen_spec: col_spec,
+                        ..
                     },
                 )| {
                     let mut spec = col_spec.lock();
@@ -568,6 +1050,7 @@ impl TableSpec {
                         ColumnGenerator::RandomString(r) => r.gen(),
                         ColumnGenerator::Zipfian(z) => z.gen(),
                         ColumnGenerator::NonRepeating(r) => r.gen(),
+                        ColumnGenerator::WithCardinality(c) => c.gen(),
                     };
 
                     (col_name.clone(), value)
@@ -587,6 +1070,21 @@ impl TableSpec {
         self.generate_data_from_index(num_rows, 0, random)
     }
 
+    /// Returns a stream of `num_rows` rows of data for this table, the same as would be returned by
+    /// [`generate_data`](Self::generate_data), but generating each row lazily as the stream is
+    /// polled.
+    ///
+    /// This allows starting to insert (or write out) the rows of large tables before all of them
+    /// have been generated, without needing to hold all of them in memory at once.
+    pub fn generate_data_stream(
+        &mut self,
+        num_rows: usize,
+        random: bool,
+    ) -> impl Stream<Item = HashMap<ColumnName, DfValue>> + '_ {
+        self.hint_unique_cardinalities(num_rows);
+        stream::iter(0..num_rows).map(move |n| self.generate_row(n, random))
+    }
+
     /// Generate `num_rows` rows of data for this table starting with the index:
     /// `index`. If `random` is true, columns that are not unique and do not
     /// need to yield expected values, have their DataGenerationSpec overridden
@@ -597,11 +1095,252 @@ impl TableSpec {
         index: usize,
         random: bool,
     ) -> Vec<HashMap<ColumnName, DfValue>> {
+        self.hint_unique_cardinalities(index + num_rows);
         (index..index + num_rows)
             .map(|n| self.generate_row(n, random))
             .collect()
     }
 
+    /// Generate up to `num_rows` rows of data for this table, the same as
+    /// [`generate_data`](Self::generate_data), but within the given [`DataGenerationBudget`].
+    pub fn generate_data_with_budget(
+        &mut self,
+        num_rows: usize,
+        random: bool,
+        budget: DataGenerationBudget,
+    ) -> GeneratedData {
+        self.generate_data_from_index_with_budget(num_rows, 0, random, budget)
+    }
+
+    /// Generate up to `num_rows` rows of data for this table starting with the index `index`, the
+    /// same as [`generate_data_from_index`](Self::generate_data_from_index), but within the given
+    /// [`DataGenerationBudget`].
+    ///
+    /// If the budget has a per-row limit, variable-length values are truncated once the row's
+    /// earlier columns (in the order they were created) have used up that limit. If generating the
+    /// next row would exceed the total limit, generation stops early, and the returned data is
+    /// marked as [`BudgetExceeded`].
+    pub fn generate_data_from_index_with_budget(
+        &mut self,
+        num_rows: usize,
+        index: usize,
+        random: bool,
+        budget: DataGenerationBudget,
+    ) -> GeneratedData {
+        self.hint_unique_cardinalities(index + num_rows);
+        let mut rows = Vec::with_capacity(num_rows);
+        let mut approximate_bytes = 0;
+        for n in index..index + num_rows {
+            let mut row = self.generate_row(n, random);
+            let mut row_bytes = 0;
+            for col in &self.column_order {
+                // unwrap: every generated row has a value for every column
+                let value = row.get_mut(col).unwrap();
+                if let Some(max) = budget.max_bytes_per_row {
+                    if !matches!(
+                        self.columns[col].gen_spec.lock().generator,
+                        ColumnGenerator::Unique(_)
+                    ) {
+                        truncate_value(value, max.saturating_sub(row_bytes));
+                    }
+                }
+                row_bytes += approximate_value_size(value);
+            }
+
+            if budget
+                .max_bytes_total
+                .is_some_and(|max| approximate_bytes + row_bytes > max)
+            {
+                return GeneratedData {
+                    rows,
+                    approximate_bytes,
+                    budget_exceeded: Some(BudgetExceeded {
+                        rows_requested: num_rows,
+                    }),
+                };
+            }
+            approximate_bytes += row_bytes;
+            rows.push(row);
+        }
+
+        GeneratedData {
+            rows,
+            approximate_bytes,
+            budget_exceeded: None,
+        }
+    }
+
+    /// Generate `num_rows` rows of data for this table, the same as
+    /// [`generate_data`](Self::generate_data), but with rows which violate the unique keys of the
+    /// table injected at the rate given by `injection`.
+    ///
+    /// Each injected row copies the values of the columns with unique generators (the primary key
+    /// and the columns of any unique indexes) from the most recent non-injected row. Generating an
+    /// injected row doesn't advance any of the column generators, so the non-injected rows are
+    /// exactly the rows that [`generate_data`](Self::generate_data) would generate for
+    /// `num_rows` minus the number of injected rows.
+    ///
+    /// Returns the rows, along with the indices of the injected rows in ascending order. If no
+    /// column of the table has a unique generator, the table has no unique keys to violate, so no
+    /// rows are injected.
+    pub fn generate_data_with_conflicts(
+        &mut self,
+        num_rows: usize,
+        random: bool,
+        injection: ConflictInjection,
+    ) -> (Vec<HashMap<ColumnName, DfValue>>, Vec<usize>) {
+        let has_unique_column = self.columns.values().any(|col_spec| {
+            matches!(
+                col_spec.gen_spec.lock().generator,
+                ColumnGenerator::Unique(_)
+            )
+        });
+        if !has_unique_column {
+            return (self.generate_data(num_rows, random), vec![]);
+        }
+
+        let conflicts = (0..num_rows)
+            .filter(|i| injection.is_conflict(*i))
+            .collect::<Vec<_>>();
+        self.hint_unique_cardinalities(num_rows - conflicts.len());
+
+        // The first non-key column, which is changed in conflicting rows that aren't exact
+        // duplicates
+        let changed_column = self
+            .column_order
+            .iter()
+            .find(|col| {
+                !matches!(
+                    self.columns[*col].gen_spec.lock().generator,
+                    ColumnGenerator::Unique(_)
+                )
+            })
+            .cloned();
+
+        let mut rows: Vec<HashMap<ColumnName, DfValue>> = Vec::with_capacity(num_rows);
+        let mut next_conflict = conflicts.iter().peekable();
+        let mut num_generated = 0;
+        let mut last_generated = 0;
+        for i in 0..num_rows {
+            if next_conflict.next_if_eq(&&i).is_none() {
+                rows.push(self.generate_row(num_generated, random));
+                num_generated += 1;
+                last_generated = i;
+                continue;
+            }
+
+            let mut row = rows[last_generated].clone();
+            if let (false, Some(col)) = (injection.exact_duplicates, &changed_column) {
+                let sql_type = &self.columns[col].sql_type;
+                // unwrap: every generated row has a value for every column
+                let value = row.get_mut(col).unwrap();
+                let mut new_value = unique_value_of_type(sql_type, i as u32);
+                if new_value == *value {
+                    new_value = unique_value_of_type(sql_type, i as u32 + 1);
+                }
+                *value = new_value;
+            }
+            rows.push(row);
+        }
+
+        (rows, conflicts)
+    }
+
+    /// Reset the data generators for every column in this table to their initial state, so that
+    /// subsequent calls to [`generate_data`](Self::generate_data) generate the same values for
+    /// all columns with deterministic generators as they did before.
+    pub fn reset_generators(&mut self) {
+        for col_spec in self.columns.values() {
+            col_spec.gen_spec.lock().generator.reset();
+        }
+    }
+
+    /// Remove the column given by `column_name` from this table, along with all of its data
+    /// generation information.
+    ///
+    /// Returns an error if the column does not exist, or if it is the primary key of the table.
+    /// Queries previously generated against this column can be found with
+    /// [`GeneratorState::queries_referencing_column`].
+    pub fn drop_column(&mut self, column_name: &ColumnName) -> anyhow::Result<()> {
+        if self.primary_key.as_ref() == Some(column_name) {
+            bail!(
+                "Cannot drop primary key column {} of table {}",
+                column_name,
+                self.name
+            );
+        }
+        if self.columns.remove(column_name).is_none() {
+            bail!("Column {} not found in table {}", column_name, self.name);
+        }
+        self.column_order.retain(|col| col != column_name);
+        Ok(())
+    }
+
+    /// Rename the column given by `old_name` to `new_name`, preserving its type and data
+    /// generation information, and its membership of the table's keys.
+    ///
+    /// Returns an error if `old_name` does not exist, or if a column named `new_name` already
+    /// exists in the table.
+    pub fn rename_column(
+        &mut self,
+        old_name: &ColumnName,
+        new_name: ColumnName,
+    ) -> anyhow::Result<()> {
+        if self.columns.contains_key(&new_name) {
+            bail!("Column {} already exists in table {}", new_name, self.name);
+        }
+        let spec = self
+            .columns
+            .remove(old_name)
+            .ok_or_else(|| anyhow!("Column {} not found in table {}", old_name, self.name))?;
+        if self.primary_key.as_ref() == Some(old_name) {
+            self.primary_key = Some(new_name.clone());
+        }
+        for col in self
+            .column_order
+            .iter_mut()
+            .chain(self.indexes.iter_mut().flat_map(|idx| &mut idx.columns))
+            .chain(self.foreign_keys.iter_mut().flat_map(|fk| &mut fk.columns))
+        {
+            if col == old_name {
+                *col = new_name.clone();
+            }
+        }
+        self.columns.insert(new_name, spec);
+        Ok(())
+    }
+
+    /// Change the type of the column given by `column_name` to `sql_type`, replacing its data
+    /// generator with one for the new type. Columns with unique generators keep generating unique
+    /// values.
+    ///
+    /// Returns an error if the column does not exist.
+    pub fn change_column_type(
+        &mut self,
+        column_name: &ColumnName,
+        sql_type: SqlType,
+    ) -> anyhow::Result<()> {
+        let spec = self
+            .columns
+            .get_mut(column_name)
+            .ok_or_else(|| anyhow!("Column {} not found in table {}", column_name, self.name))?;
+        let generator = match spec.gen_spec.lock().generator {
+            ColumnGenerator::Unique(_) => ColumnGenerator::Unique(sql_type.clone().into()),
+            _ => ColumnGenerator::Constant(sql_type.clone().into()),
+        };
+        // Clones of this table share generators with it, so replace the generator rather than
+        // updating it in place
+        *spec = ColumnSpec {
+            sql_type,
+            gen_spec: Arc::new(Mutex::new(ColumnDataGeneration {
+                generator,
+                expected_values: HashSet::new(),
+            })),
+            cardinality_hint: None,
+        };
+        Ok(())
+    }
+
     /// Ensure this table has a primary key column, and return its name
     pub fn primary_key(&mut self) -> &ColumnName {
         if self.primary_key.is_none() {
@@ -616,7 +1355,7 @@ impl TableSpec {
 }
 
 /// How to add parameters to the query during generation
-#[derive(Debug, Clone, Copy, Default)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub enum ParameterMode {
     /// Add positional (`?`) parameters
     #[default]
@@ -625,42 +1364,262 @@ pub enum ParameterMode {
     Numbered,
 }
 
-#[derive(Debug, Default)]
-pub struct GeneratorState {
-    tables: HashMap<TableName, TableSpec>,
-    table_name_counter: u32,
-    parameter_mode: ParameterMode,
+impl From<ParseDialect> for ParameterMode {
+    /// Returns the style of parameters accepted by the given dialect
+    fn from(dialect: ParseDialect) -> Self {
+        match dialect {
+            ParseDialect::MySQL => ParameterMode::Positional,
+            ParseDialect::PostgreSQL => ParameterMode::Numbered,
+        }
+    }
 }
 
-impl GeneratorState {
-    /// Create a new [`GeneratorState`] with the given mode for adding new parameters
-    pub fn with_parameter_mode(parameter_mode: ParameterMode) -> Self {
-        Self {
-            parameter_mode,
-            ..Default::default()
+/// Identifier for a query generated by a [`GeneratorState`], unique within that
+/// [`GeneratorState`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct QueryId(usize);
+
+/// [`Visitor`] which collects the names of all the table-qualified columns referenced in a query
+#[derive(Default)]
+struct ReferencedColumns(HashSet<(TableName, ColumnName)>);
+
+impl<'ast> Visitor<'ast> for ReferencedColumns {
+    type Error = !;
+
+    fn visit_column(&mut self, column: &'ast Column) -> Result<(), Self::Error> {
+        if let Some(table) = &column.table {
+            self.0.insert(((&table.name).into(), (&column.name).into()));
         }
+        Ok(())
     }
+}
 
-    /// Create a new, unique, empty table, and return a mutable reference to that table
-    pub fn fresh_table_mut(&mut self) -> &mut TableSpec {
-        self.table_name_counter += 1;
-        let table_name: TableName = format!("table_{}", self.table_name_counter).as_str().into();
-        self.tables
-            .entry(table_name)
-            .or_insert_with_key(|tn| TableSpec::new(tn.clone()))
-    }
+/// [`Visitor`] which collects the names of all the tables referenced in a query
+#[derive(Default)]
+struct ReferencedTables(HashSet<TableName>);
 
-    /// Returns a reference to the table with the given name, if it exists
-    pub fn table<'a, TN>(&'a self, name: &TN) -> Option<&'a TableSpec>
-    where
-        TableName: Borrow<TN>,
-        TN: Eq + Hash + ?Sized,
-    {
-        self.tables.get(name)
+impl<'ast> Visitor<'ast> for ReferencedTables {
+    type Error = !;
+
+    fn visit_table(&mut self, table: &'ast Relation) -> Result<(), Self::Error> {
+        self.0.insert((&table.name).into());
+        Ok(())
     }
+}
 
-    /// Returns a mutable reference to the table with the given name, if it exists
-    pub fn table_mut<'a, TN>(&'a mut self, name: &TN) -> Option<&'a mut TableSpec>
+/// How aggressively [`Query::strip_qualifications`] should remove table qualifiers from columns
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, clap::ValueEnum)]
+pub enum StripLevel {
+    /// Only strip the qualifier from columns whose name is unambiguous across all the tables in
+    /// scope for that column, so that the meaning of the query does not change
+    Safe,
+    /// Strip the qualifier from all columns, which may produce intentionally ambiguous queries
+    Aggressive,
+}
+
+/// [`VisitorMut`] which removes table qualifiers from columns, according to a [`StripLevel`]
+struct StripQualifications<'a> {
+    level: StripLevel,
+    tables: &'a HashMap<TableName, TableSpec>,
+    /// The tables in scope at each level of subquery nesting, as a map from the name used to
+    /// refer to each table in the query (its alias, if any) to the table in the schema, or `None`
+    /// if the columns of the table aren't known (such as for subqueries and CTEs)
+    scopes: Vec<HashMap<SqlIdentifier, Option<&'a TableSpec>>>,
+}
+
+impl<'a> StripQualifications<'a> {
+    /// Returns true if exactly one table in scope has a column with the given name, and the
+    /// columns of all the tables in scope are known
+    fn is_unambiguous(&self, column_name: &ColumnName) -> bool {
+        let mut num_matching = 0;
+        for table in self.scopes.iter().flat_map(|scope| scope.values()) {
+            match table {
+                Some(table) if table.columns.contains_key(column_name) => num_matching += 1,
+                Some(_) => {}
+                None => return false,
+            }
+        }
+        num_matching == 1
+    }
+}
+
+impl<'ast, 'a> VisitorMut<'ast> for StripQualifications<'a> {
+    type Error = !;
+
+    fn visit_select_statement(
+        &mut self,
+        select_statement: &'ast mut SelectStatement,
+    ) -> Result<(), Self::Error> {
+        let tables = self.tables;
+        self.scopes.push(
+            outermost_table_exprs(select_statement)
+                .filter_map(|te| {
+                    let table = te.inner.as_table();
+                    let name = te.alias.clone().or_else(|| table.map(|t| t.name.clone()))?;
+                    let spec = table
+                        .filter(|t| t.schema.is_none())
+                        .and_then(|t| tables.get(&TableName::from(&t.name)));
+                    Some((name, spec))
+                })
+                .collect(),
+        );
+        walk_select_statement(self, select_statement)?;
+        self.scopes.pop();
+        Ok(())
+    }
+
+    fn visit_column(&mut self, column: &'ast mut Column) -> Result<(), Self::Error> {
+        let Some(table) = &column.table else {
+            return Ok(());
+        };
+
+        let strip = match self.level {
+            StripLevel::Aggressive => true,
+            StripLevel::Safe => {
+                table.schema.is_none()
+                    && self
+                        .scopes
+                        .iter()
+                        .any(|scope| scope.contains_key(&table.name))
+                    && self.is_unambiguous(&(&column.name).into())
+            }
+        };
+        if strip {
+            column.table = None;
+        }
+
+        Ok(())
+    }
+}
+
+/// [`VisitorMut`] which rewrites the table qualifier of columns referring to one table to refer to
+/// another name, stopping at any subqueries which themselves reference a table with the original
+/// name
+struct RenameTableQualifier {
+    from: SqlIdentifier,
+    to: SqlIdentifier,
+    depth: usize,
+}
+
+impl<'ast> VisitorMut<'ast> for RenameTableQualifier {
+    type Error = !;
+
+    fn visit_select_statement(
+        &mut self,
+        select_statement: &'ast mut SelectStatement,
+    ) -> Result<(), Self::Error> {
+        if self.depth > 0
+            && outermost_table_exprs(select_statement).any(|te| {
+                te.alias.as_ref() == Some(&self.from)
+                    || (te.alias.is_none()
+                        && te.inner.as_table().map_or(false, |t| t.name == self.from))
+            })
+        {
+            // The original name is shadowed within this subquery
+            return Ok(());
+        }
+
+        self.depth += 1;
+        walk_select_statement(self, select_statement)?;
+        self.depth -= 1;
+        Ok(())
+    }
+
+    fn visit_column(&mut self, column: &'ast mut Column) -> Result<(), Self::Error> {
+        if let Some(table) = &mut column.table {
+            if table.schema.is_none() && table.name == self.from {
+                table.name = self.to.clone();
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct GeneratorState {
+    tables: HashMap<TableName, TableSpec>,
+    table_name_counter: u32,
+    parameter_mode: ParameterMode,
+    /// The number of queries generated by this state, used to assign each a [`QueryId`]
+    num_generated_queries: usize,
+    /// Whether to record the columns referenced by new queries. See
+    /// [`Self::set_track_referenced_columns`].
+    track_referenced_columns: bool,
+    /// The set of table-qualified columns referenced by each query generated by this state while
+    /// [tracking them](Self::set_track_referenced_columns)
+    referenced_columns: HashMap<QueryId, HashSet<(TableName, ColumnName)>>,
+    /// The number of rows generated so far for each table by
+    /// [`generate_data_for_table`](Self::generate_data_for_table)
+    row_counts: HashMap<TableName, usize>,
+    /// Whether to trace the operations applied to new queries. See [`Self::set_trace_operations`].
+    trace_operations: bool,
+    /// The budget for data generated by [`generate_data_for_table`](Self::generate_data_for_table)
+    data_budget: DataGenerationBudget,
+    /// The approximate total size of the rows generated by
+    /// [`generate_data_for_table`](Self::generate_data_for_table) since the budget was last set,
+    /// in bytes. See [`Self::data_bytes_generated`].
+    data_bytes_generated: u64,
+}
+
+impl GeneratorState {
+    /// Create a new [`GeneratorState`] with the given mode for adding new parameters
+    pub fn with_parameter_mode(parameter_mode: ParameterMode) -> Self {
+        Self {
+            parameter_mode,
+            ..Default::default()
+        }
+    }
+
+    /// Create a new, unique, empty table, and return a mutable reference to that table
+    pub fn fresh_table_mut(&mut self) -> &mut TableSpec {
+        self.table_name_counter += 1;
+        let table_name: TableName = format!("table_{}", self.table_name_counter).as_str().into();
+        self.tables
+            .entry(table_name)
+            .or_insert_with_key(|tn| TableSpec::new(tn.clone()))
+    }
+
+    /// Ensure that both of the given tables have a column named `name` with type `sql_type`, adding
+    /// the column to either table if necessary, and return the name of the column.
+    ///
+    /// This is used to generate joins with a `USING` clause, which requires the join column to
+    /// have the same name on both sides.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either table does not exist, or if either table already has a column named `name`
+    /// with a type other than `sql_type`
+    pub fn ensure_shared_column_name(
+        &mut self,
+        t1: &TableName,
+        t2: &TableName,
+        name: ColumnName,
+        sql_type: SqlType,
+    ) -> ColumnName {
+        for table_name in [t1, t2] {
+            let table = self.tables.get_mut(table_name).unwrap();
+            match table.columns.get(&name) {
+                Some(col) => assert_eq!(
+                    col.sql_type, sql_type,
+                    "Column {name} in {table_name} has the wrong type"
+                ),
+                None => table.add_column(name.clone(), sql_type.clone()),
+            }
+        }
+        name
+    }
+
+    /// Returns a reference to the table with the given name, if it exists
+    pub fn table<'a, TN>(&'a self, name: &TN) -> Option<&'a TableSpec>
+    where
+        TableName: Borrow<TN>,
+        TN: Eq + Hash + ?Sized,
+    {
+        self.tables.get(name)
+    }
+
+    /// Returns a mutable reference to the table with the given name, if it exists
+    pub fn table_mut<'a, TN>(&'a mut self, name: &TN) -> Option<&'a mut TableSpec>
     where
         TableName: Borrow<TN>,
         TN: Eq + Hash + ?Sized,
@@ -684,15 +1643,70 @@ impl GeneratorState {
     }
 
     pub fn new_query(&mut self) -> QueryState<'_> {
-        QueryState::new(self)
+        let trace = self.trace_operations;
+        let mut state = QueryState::new(self);
+        state.set_trace(trace);
+        state
+    }
+
+    /// Enable or disable [tracing](QueryState::set_trace) of the operations applied to all queries
+    /// subsequently generated by this generator state
+    pub fn set_trace_operations(&mut self, trace: bool) {
+        self.trace_operations = trace;
+    }
+
+    /// Enable or disable recording the columns referenced by each query subsequently generated by
+    /// this generator state, for [`Self::queries_referencing_column`]. Disabled by default, since
+    /// the columns are kept for as long as this generator state is.
+    pub fn set_track_referenced_columns(&mut self, track: bool) {
+        self.track_referenced_columns = track;
+    }
+
+    /// Set the [`DataGenerationBudget`] for all data subsequently generated for tables by
+    /// [`generate_data_for_table`](Self::generate_data_for_table), including the data generated
+    /// for queries by [`QueryState::generate_data`].
+    ///
+    /// The [`max_bytes_total`](DataGenerationBudget::max_bytes_total) of the budget applies to
+    /// all of that data together, not to each table or call separately. Setting a budget resets
+    /// the running total of [`Self::data_bytes_generated`].
+    pub fn set_data_budget(&mut self, budget: DataGenerationBudget) {
+        self.data_budget = budget;
+        self.data_bytes_generated = 0;
+    }
+
+    /// Returns the approximate total size, in bytes, of all the rows generated by
+    /// [`generate_data_for_table`](Self::generate_data_for_table) since the
+    /// [budget](Self::set_data_budget) was last set, which counts against the budget's
+    /// [`max_bytes_total`](DataGenerationBudget::max_bytes_total).
+    ///
+    /// Only tracked while a budget is set.
+    pub fn data_bytes_generated(&self) -> u64 {
+        self.data_bytes_generated
     }
 
     /// Generate a new query using the given [`QuerySeed`]
     pub fn generate_query(&mut self, seed: QuerySeed) -> Query {
         let mut state = self.new_query();
-        let query = seed.generate(&mut state);
+        let query = seed.clone().generate(&mut state, None);
+
+        Query::new(state, query, seed)
+    }
+
+    /// Generate a new query using the given [`QuerySeed`], applying the operations pinned by the
+    /// given [`PlacementPlan`] to the tables and columns it specifies.
+    ///
+    /// Returns an error without generating a query if the plan is invalid for the seed or for the
+    /// tables in this generator state.
+    pub fn generate_query_with_plan(
+        &mut self,
+        seed: QuerySeed,
+        plan: &PlacementPlan,
+    ) -> Result<Query, PlacementError> {
+        plan.validate(&seed, self)?;
+        let mut state = self.new_query();
+        let query = seed.clone().generate(&mut state, Some(plan));
 
-        Query::new(state, query)
+        Ok(Query::new(state, query, seed))
     }
 
     /// Return an iterator over `CreateTableStatement`s for all the tables in the schema
@@ -705,10 +1719,58 @@ impl GeneratorState {
         self.tables.values().map(|tbl| tbl.clone().into())
     }
 
+    /// Run the given query through ReadySet's query rewriting passes as a dry run, against the
+    /// schema of the tables in this generator state, returning the rewritten query.
+    ///
+    /// This can be used to check that a generated query is accepted by ReadySet (for example, that
+    /// none of its column references are ambiguous) without needing a running server.
+    pub fn rewrite_query(&self, statement: SelectStatement) -> anyhow::Result<SelectStatement> {
+        let schema_name = SqlIdentifier::from("query_generator");
+        let ddl = self
+            .ddl()
+            .map(|mut stmt| {
+                stmt.table.schema = Some(schema_name.clone());
+                stmt
+            })
+            .collect::<Vec<_>>();
+        let view_schemas = ddl
+            .iter()
+            .filter_map(|stmt| {
+                let body = stmt.body.as_ref().ok()?;
+                Some((
+                    stmt.table.clone(),
+                    body.fields.iter().map(|f| f.column.name.clone()).collect(),
+                ))
+            })
+            .collect::<HashMap<_, _>>();
+        let search_path = [schema_name];
+
+        let mut statement = statement.rewrite(&mut RewriteContext {
+            view_schemas: &view_schemas,
+            base_schemas: ddl
+                .iter()
+                .filter_map(|stmt| Some((&stmt.table, stmt.body.as_ref().ok()?)))
+                .collect(),
+            uncompiled_views: &[],
+            non_replicated_relations: &HashSet::new(),
+            custom_types: &HashMap::new(),
+            search_path: &search_path,
+            dialect: DataDialect::DEFAULT_MYSQL,
+            invalidating_tables: None,
+            self_join_policy: Default::default(),
+        })?;
+        statement.rewrite_table_aliases("query");
+        Ok(statement)
+    }
+
     /// Generate `num_rows` rows of data for the table given by `table_name`.
     /// If `random` is passed on column data will be random in length for
     /// variable length data, and value for fixed-length data.
     ///
+    /// If a [budget](Self::set_data_budget) has been set, fewer than `num_rows` rows may be
+    /// returned, in which case a warning is logged. Once the total size of the rows generated
+    /// since the budget was set reaches its limit, no more rows are generated for any table.
+    ///
     /// # Panics
     ///
     /// Panics if `table_name` is not a known table
@@ -718,10 +1780,142 @@ impl GeneratorState {
         num_rows: usize,
         random: bool,
     ) -> Vec<HashMap<ColumnName, DfValue>> {
-        self.tables
+        let table = self.tables.get_mut(table_name).unwrap();
+        let rows = if self.data_budget == DataGenerationBudget::default() {
+            table.generate_data(num_rows, random)
+        } else {
+            let budget = DataGenerationBudget {
+                max_bytes_total: self
+                    .data_budget
+                    .max_bytes_total
+                    .map(|max| max.saturating_sub(self.data_bytes_generated)),
+                ..self.data_budget
+            };
+            let data = table.generate_data_with_budget(num_rows, random, budget);
+            self.data_bytes_generated += data.approximate_bytes;
+            if let Some(BudgetExceeded { rows_requested }) = data.budget_exceeded {
+                tracing::warn!(
+                    table = %table_name,
+                    rows_generated = data.rows.len(),
+                    rows_requested,
+                    bytes_generated = self.data_bytes_generated,
+                    "Data generation budget exceeded, generated fewer rows than requested"
+                );
+            }
+            data.rows
+        };
+        // Rows are always generated starting from the first index, so the rows generated by
+        // earlier calls are a prefix of the rows generated by later calls with more rows
+        let row_count = self.row_counts.entry(table_name.clone()).or_default();
+        *row_count = (*row_count).max(rows.len());
+        rows
+    }
+
+    /// Generate `num_rows` rows of data for the given table with injected unique key violations,
+    /// using [`TableSpec::generate_data_with_conflicts`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `table_name` is not a known table
+    pub fn generate_data_for_table_with_conflicts(
+        &mut self,
+        table_name: &TableName,
+        num_rows: usize,
+        random: bool,
+        injection: ConflictInjection,
+    ) -> (Vec<HashMap<ColumnName, DfValue>>, Vec<usize>) {
+        let (rows, conflicts) = self
+            .tables
             .get_mut(table_name)
             .unwrap()
-            .generate_data(num_rows, random)
+            .generate_data_with_conflicts(num_rows, random, injection);
+        // Only count the rows with distinct keys, since those are the rows that other tables can
+        // reference
+        let row_count = self.row_counts.entry(table_name.clone()).or_default();
+        *row_count = (*row_count).max(num_rows - conflicts.len());
+        (rows, conflicts)
+    }
+
+    /// Returns the number of rows generated so far for the given table, or 0 if no data has been
+    /// generated for the table yet
+    pub fn row_count_for(&self, table_name: &TableName) -> usize {
+        self.row_counts.get(table_name).copied().unwrap_or(0)
+    }
+
+    /// Generate `num_rows` rows of data for the given table, the same as
+    /// [`generate_data_for_table`](Self::generate_data_for_table), but with each value of
+    /// `fk_column` replaced by the index of a random row already generated for `parent_table`.
+    ///
+    /// Since unique columns generate values from the index of each row, this keeps the foreign key
+    /// referencing existing rows when the parent table's key is unique.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no rows have been generated for `parent_table` yet
+    pub fn generate_data_correlated(
+        &mut self,
+        table_name: &TableName,
+        num_rows: usize,
+        fk_column: &ColumnName,
+        parent_table: &TableName,
+        random: bool,
+    ) -> Vec<HashMap<ColumnName, DfValue>> {
+        let parent_rows = self.row_count_for(parent_table);
+        assert!(
+            parent_rows > 0,
+            "No rows have been generated for parent table {parent_table}"
+        );
+        let fk_type = DfType::from_sql_type(
+            &self.tables[table_name].columns[fk_column].sql_type,
+            DataDialect::DEFAULT_MYSQL,
+            |_| None,
+        )
+        .unwrap();
+
+        let mut rng = thread_rng();
+        let mut rows = self.generate_data_for_table(table_name, num_rows, random);
+        for row in &mut rows {
+            let fk = DfValue::from(rng.gen_range(0..parent_rows) as i64)
+                .coerce_to(&fk_type, &DfType::Unknown)
+                .unwrap();
+            row.insert(fk_column.clone(), fk);
+        }
+        rows
+    }
+
+    /// Return an identifier for a newly generated query, recording the columns it references if
+    /// [enabled](Self::set_track_referenced_columns)
+    fn record_query(&mut self, statement: &SelectStatement) -> QueryId {
+        let id = QueryId(self.num_generated_queries);
+        self.num_generated_queries += 1;
+        if self.track_referenced_columns {
+            let mut referenced = ReferencedColumns::default();
+            let Ok(()) = referenced.visit_select_statement(statement);
+            self.referenced_columns.insert(id, referenced.0);
+        }
+        id
+    }
+
+    /// Returns the identifiers of all the queries generated by this state which reference the
+    /// given column of the given table, in the order they were generated. Only queries generated
+    /// while [tracking referenced columns](Self::set_track_referenced_columns) are included.
+    ///
+    /// This can be used to find which queries are invalidated by
+    /// [dropping](TableSpec::drop_column) or [renaming](TableSpec::rename_column) a column.
+    pub fn queries_referencing_column(
+        &self,
+        table_name: &TableName,
+        column_name: &ColumnName,
+    ) -> Vec<QueryId> {
+        let key = (table_name.clone(), column_name.clone());
+        let mut queries = self
+            .referenced_columns
+            .iter()
+            .filter(|(_, columns)| columns.contains(&key))
+            .map(|(id, _)| *id)
+            .collect::<Vec<_>>();
+        queries.sort();
+        queries
     }
 
     /// Get a reference to the generator state's tables.
@@ -757,6 +1951,54 @@ pub struct QueryParameter {
     generator: Arc<Mutex<ColumnGenerator>>,
 }
 
+/// A record of a single [`QueryOperation`] being applied to a query, along with the shape of the
+/// query immediately afterwards. See [`QueryState::set_trace`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceEntry {
+    /// The operation which was applied
+    pub operation: QueryOperation,
+    /// The number of fields projected by the query after applying the operation
+    pub fields: usize,
+    /// The number of tables in the `FROM` clause and `JOIN`s of the query after applying the
+    /// operation
+    pub tables: usize,
+    /// The depth of the tree of `AND` and `OR` expressions in the `WHERE` clause of the query
+    /// after applying the operation, or 0 if it has no `WHERE` clause
+    pub where_clause_depth: usize,
+}
+
+impl TraceEntry {
+    fn new(operation: QueryOperation, query: &SelectStatement) -> Self {
+        fn depth(expr: &Expr) -> usize {
+            match expr {
+                Expr::BinaryOp {
+                    lhs,
+                    op: BinaryOperator::And | BinaryOperator::Or,
+                    rhs,
+                } => 1 + depth(lhs).max(depth(rhs)),
+                _ => 1,
+            }
+        }
+
+        Self {
+            operation,
+            fields: query.fields.len(),
+            tables: query.tables.len() + query.join.len(),
+            where_clause_depth: query.where_clause.as_ref().map_or(0, depth),
+        }
+    }
+}
+
+impl fmt::Display for TraceEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:?}: {} fields, {} tables, where clause depth {}",
+            self.operation, self.fields, self.tables, self.where_clause_depth
+        )
+    }
+}
+
 pub struct QueryState<'a> {
     gen: &'a mut GeneratorState,
     tables: HashSet<TableName>,
@@ -764,6 +2006,23 @@ pub struct QueryState<'a> {
     unique_parameters: HashMap<TableName, Vec<(ColumnName, DfValue)>>,
     alias_counter: u32,
     value_counter: u8,
+    /// Tables which are the right-hand side of an anti-join in the query, and so must not have
+    /// any further filters or parameters added on their columns
+    anti_joined_tables: HashSet<TableName>,
+    /// The placement pinned by a [`PlacementPlan`] for the operation currently being added to the
+    /// query, if any
+    placement: Option<Placement>,
+    /// Tables referenced only by subqueries of the query, which data needs to be generated for but
+    /// which must not be picked for the outer query. See [`Self::scoped_for_subquery`].
+    subquery_tables: HashSet<TableName>,
+    /// The operations applied to the query so far, if tracing is enabled. See [`Self::set_trace`].
+    trace: Option<Vec<TraceEntry>>,
+    /// True if the query has deliberately unsatisfiable conditions, so it must return no rows when
+    /// executed with the key generated for it. See [`Self::expects_empty_results`].
+    expects_empty_results: bool,
+    /// The minimum number of rows to generate for each table, regardless of the number of rows per
+    /// table requested. See [`Self::require_rows`].
+    min_rows: HashMap<TableName, usize>,
 }
 
 impl<'a> QueryState<'a> {
@@ -775,9 +2034,90 @@ impl<'a> QueryState<'a> {
             parameters: Vec::new(),
             alias_counter: 0,
             value_counter: 0,
+            anti_joined_tables: HashSet::new(),
+            placement: None,
+            subquery_tables: HashSet::new(),
+            trace: None,
+            expects_empty_results: false,
+            min_rows: HashMap::new(),
+        }
+    }
+
+    /// Require that at least `rows` rows are generated for `table` by [`Self::generate_data`],
+    /// even if fewer rows per table are requested
+    pub fn require_rows(&mut self, table: TableName, rows: usize) {
+        let min_rows = self.min_rows.entry(table).or_default();
+        *min_rows = (*min_rows).max(rows);
+    }
+
+    /// Returns the number of rows [`Self::generate_data`] generates for `table` when asked for
+    /// `rows_per_table` rows per table
+    pub fn rows_for_table(&self, table: &TableName, rows_per_table: usize) -> usize {
+        self.min_rows
+            .get(table)
+            .map_or(rows_per_table, |min_rows| rows_per_table.max(*min_rows))
+    }
+
+    /// Returns true if the query was generated with conditions that contradict each other (such as
+    /// by [`QueryOperation::ParameterWithLiteralConstraint`] with `contradictory` set), and so
+    /// should return no rows when executed with the key generated for it by [`Self::key`],
+    /// regardless of the data generated. Other keys may satisfy the conditions.
+    ///
+    /// Contradictions within subqueries, and queries with aggregates but no `GROUP BY` (which
+    /// always return a row), are never expected to return empty results.
+    pub fn expects_empty_results(&self) -> bool {
+        self.expects_empty_results
+    }
+
+    /// Enable or disable tracing of the operations applied to the query.
+    ///
+    /// While tracing is enabled, a [`TraceEntry`] is recorded in [`Self::trace_log`] for each
+    /// operation applied to the query (including the operations of its subqueries, which are
+    /// applied to the subquery rather than the outer query), in the order they're applied.
+    /// Disabling tracing discards the log.
+    pub fn set_trace(&mut self, trace: bool) {
+        if !trace {
+            self.trace = None;
+        } else if self.trace.is_none() {
+            self.trace = Some(vec![]);
         }
     }
 
+    /// Returns the log of operations applied to the query while tracing was enabled with
+    /// [`Self::set_trace`]
+    pub fn trace_log(&self) -> &[TraceEntry] {
+        self.trace.as_deref().unwrap_or_default()
+    }
+
+    /// Run `f` to generate a subquery of the query in its own scope, in which none of the tables
+    /// already referenced by the query can be picked by [`Self::some_table_mut`], returning the
+    /// result of `f`.
+    ///
+    /// Once `f` returns, the tables picked within the scope are only used to generate data for the
+    /// query, and will never be picked for the outer query. Anything the outer query needs from
+    /// the subquery (such as the column to join against) should be picked within `f`. Parameters
+    /// are shared with the outer query, since placeholders are numbered across the whole query.
+    pub fn scoped_for_subquery<T, F>(&mut self, f: F) -> T
+    where
+        F: FnOnce(&mut QueryState) -> T,
+    {
+        let outer_tables = mem::take(&mut self.tables);
+        // An empty subquery doesn't necessarily make the outer query empty (eg if it's left
+        // joined)
+        let expects_empty_results = self.expects_empty_results;
+        let res = f(self);
+        self.expects_empty_results = expects_empty_results;
+        let subquery_tables = mem::replace(&mut self.tables, outer_tables);
+        self.subquery_tables.extend(subquery_tables);
+        res
+    }
+
+    /// Returns an iterator over all the tables referenced by the query, including those referenced
+    /// only by its subqueries
+    pub fn tables(&self) -> impl Iterator<Item = &TableName> {
+        self.tables.union(&self.subquery_tables)
+    }
+
     /// Returns the next placeholder that will be used according to the configured parameter mode
     pub fn next_placeholder(&self) -> ItemPlaceholder {
         match self.gen.parameter_mode {
@@ -797,10 +2137,23 @@ impl<'a> QueryState<'a> {
     /// Return a mutable reference to *some* table in the schema - the implication being that the
     /// caller doesn't care which table
     pub fn some_table_mut(&mut self) -> &mut TableSpec {
-        if let Some(table) = self.tables.iter().last() {
+        if let Some(table) = self
+            .tables
+            .iter()
+            .filter(|tbl| !self.anti_joined_tables.contains(*tbl))
+            .last()
+        {
             self.gen.table_mut(table).unwrap()
         } else {
-            let table = self.gen.some_table_mut();
+            let unused_table = self
+                .gen
+                .table_names()
+                .find(|tbl| !self.subquery_tables.contains(*tbl))
+                .cloned();
+            let table = match unused_table {
+                Some(table) => self.gen.table_mut(&table).unwrap(),
+                None => self.gen.fresh_table_mut(),
+            };
             self.tables.insert(table.name.clone());
             table
         }
@@ -808,12 +2161,12 @@ impl<'a> QueryState<'a> {
 
     /// Returns a mutable reference to some table referenced in the given query.
     ///
-    /// Adds a table to the query if none exist
+    /// Adds a table to the query if none exist. Never returns the right-hand side of an anti-join.
     pub fn some_table_in_query_mut<'b>(
         &'b mut self,
         query: &mut SelectStatement,
     ) -> &'b mut TableSpec {
-        match query
+        let table_name = query
             .tables
             .iter()
             .chain(query.join.iter().filter_map(|jc| match &jc.right {
@@ -821,21 +2174,88 @@ impl<'a> QueryState<'a> {
                 _ => None,
             }))
             .filter_map(|te| te.inner.as_table())
-            .next()
-        {
-            Some(tbl) => self.gen.table_mut(tbl.name.as_str()).unwrap(),
+            .find(|tbl| !self.anti_joined_tables.contains(tbl.name.as_str()))
+            .map(|tbl| tbl.name.clone());
+        match table_name {
+            Some(table_name) => self.gen.table_mut(table_name.as_str()).unwrap(),
             None => {
                 let table = self.some_table_mut();
                 query.tables.push(TableExpr {
                     inner: TableExprInner::Table(table.name.clone().into()),
                     alias: None,
                     index_hint: None,
+                    sample: None,
                 });
                 table
             }
         }
     }
 
+    /// Returns true if the given table is referenced by the FROM clause or joins of the given
+    /// query
+    fn table_in_query(table: &TableName, query: &SelectStatement) -> bool {
+        query
+            .tables
+            .iter()
+            .chain(query.join.iter().filter_map(|jc| match &jc.right {
+                JoinRightSide::Table(tbl) => Some(tbl),
+                _ => None,
+            }))
+            .filter_map(|te| te.inner.as_table())
+            .any(|tbl| tbl.name == table.0)
+    }
+
+    /// Add the given table to the FROM clause of the given query, unless it's already referenced
+    /// by the query
+    fn add_table_to_query(&mut self, table: &TableName, query: &mut SelectStatement) {
+        self.tables.insert(table.clone());
+        if !Self::table_in_query(table, query) {
+            query
+                .tables
+                .push(TableExpr::from(Relation::from(table.clone())));
+        }
+    }
+
+    /// Take the column pinned by the [`PlacementPlan`] for the operation currently being added to
+    /// the query, adding its table to the query if necessary
+    fn placed_column(&mut self, query: &mut SelectStatement) -> Option<PlacementTarget> {
+        match self.placement.take()? {
+            Placement::Column(target) => {
+                self.add_table_to_query(&target.table, query);
+                Some(target)
+            }
+            placement => {
+                self.placement = Some(placement);
+                None
+            }
+        }
+    }
+
+    /// Take the pair of columns pinned by the [`PlacementPlan`] for the join currently being added
+    /// to the query, adding the table of the left column to the query if necessary.
+    ///
+    /// If the table of the right column is already referenced by the query (for example because
+    /// an earlier operation was pinned to it), also returns a fresh alias to join it under, so that
+    /// references to it stay unambiguous.
+    fn placed_join(
+        &mut self,
+        query: &mut SelectStatement,
+    ) -> Option<(PlacementTarget, PlacementTarget, Option<SqlIdentifier>)> {
+        match self.placement.take()? {
+            Placement::Join { left, right } => {
+                self.add_table_to_query(&left.table, query);
+                let right_alias =
+                    Self::table_in_query(&right.table, query).then(|| self.fresh_alias());
+                self.tables.insert(right.table.clone());
+                Some((left, right, right_alias))
+            }
+            placement => {
+                self.placement = Some(placement);
+                None
+            }
+        }
+    }
+
     /// Returns a mutable reference to some table *not* referenced in the given query
     pub fn some_table_not_in_query_mut<'b>(
         &'b mut self,
@@ -868,7 +2288,8 @@ impl<'a> QueryState<'a> {
     }
 
     /// Generate `rows_per_table` rows of data for all the tables referenced in the query for this
-    /// QueryState.
+    /// QueryState, or more for tables that the query needs more rows in (see
+    /// [`Self::require_rows`]).
     ///
     /// If `make_unique` is true and `make_unique_key` was previously called, the returned rows
     /// are modified to match the key returned by `make_unique_key`.
@@ -878,13 +2299,14 @@ impl<'a> QueryState<'a> {
         make_unique: bool,
         random: bool,
     ) -> HashMap<TableName, Vec<HashMap<ColumnName, DfValue>>> {
-        let table_names = self.tables.clone();
+        let table_names = self.tables().cloned().collect::<Vec<_>>();
         table_names
             .iter()
             .map(|table_name| {
+                let num_rows = self.rows_for_table(table_name, rows_per_table);
                 let mut rows = self
                     .gen
-                    .generate_data_for_table(table_name, rows_per_table, random);
+                    .generate_data_for_table(table_name, num_rows, random);
                 if make_unique {
                     if let Some(column_data) = self.unique_parameters.get(table_name) {
                         for row in &mut rows {
@@ -984,6 +2406,18 @@ impl<'a> QueryState<'a> {
         ret
     }
 
+    /// Returns the table and column of each parameter in the query which isn't covered by an index
+    /// on its table (see [`TableSpec::has_index_on`]), and which therefore might benefit from an
+    /// index being added
+    pub fn missing_indexes(&self) -> Vec<(TableName, ColumnName)> {
+        self.parameters
+            .iter()
+            .filter(|param| !self.gen.tables[&param.table_name].has_index_on(&param.column_name))
+            .map(|param| (param.table_name.clone(), param.column_name.clone()))
+            .unique()
+            .collect()
+    }
+
     /// Returns a lookup key for the parameters in the query that will return results
     pub fn key(&self) -> Vec<DfValue> {
         self.parameters
@@ -1007,42 +2441,162 @@ impl<'a> QueryState<'a> {
 }
 
 pub struct Query<'gen> {
+    pub id: QueryId,
     pub state: QueryState<'gen>,
     pub statement: SelectStatement,
+    /// The seed the query was generated from
+    pub seed: QuerySeed,
+    /// The level at which table qualifiers were stripped from the columns in [`Self::statement`],
+    /// if [`Self::strip_qualifications`] has been called
+    pub strip_level: Option<StripLevel>,
 }
 
 impl<'gen> Query<'gen> {
-    pub fn new(state: QueryState<'gen>, statement: SelectStatement) -> Self {
-        Self { state, statement }
+    pub fn new(mut state: QueryState<'gen>, statement: SelectStatement, seed: QuerySeed) -> Self {
+        let id = state.gen.record_query(&statement);
+        Self {
+            id,
+            state,
+            statement,
+            seed,
+            strip_level: None,
+        }
     }
-}
 
-fn min_max_arg_type(dialect: ParseDialect) -> impl Strategy<Value = SqlType> {
-    match dialect {
-        ParseDialect::MySQL => any_with::<SqlType>(SqlTypeArbitraryOptions {
-            generate_arrays: false,
-            dialect: Some(dialect),
-            ..Default::default()
-        })
-        .boxed(),
-        ParseDialect::PostgreSQL => arbitrary_postgres_min_max_arg_type().boxed(),
+    /// Returns the log of operations applied to generate this query, if tracing was enabled while
+    /// generating it. See [`QueryState::set_trace`].
+    pub fn trace_log(&self) -> &[TraceEntry] {
+        self.state.trace_log()
     }
-}
 
-#[derive(Debug, Eq, PartialEq, Clone, Serialize, Deserialize, Arbitrary)]
-#[arbitrary(args = QueryDialect)]
-pub enum AggregateType {
-    Count {
-        #[any(generate_arrays = false, dialect = Some(args.0))]
-        column_type: SqlType,
-        distinct: bool,
-    },
-    Sum {
-        #[strategy(arbitrary_numeric_type(Some(args.0)))]
-        column_type: SqlType,
-        distinct: bool,
-    },
-    Avg {
+    /// Remove table qualifiers from the columns in this query, to exercise the passes in ReadySet
+    /// which resolve unqualified column references.
+    ///
+    /// See [`StripLevel`] for which columns have their qualifiers removed.
+    pub fn strip_qualifications(&mut self, level: StripLevel) {
+        let tables = self.state.gen.tables();
+        let Ok(()) = StripQualifications {
+            level,
+            tables,
+            scopes: vec![],
+        }
+        .visit_select_statement(&mut self.statement);
+        self.strip_level = Some(level);
+    }
+
+    /// Alias each of the tables in the outermost `FROM` and `JOIN` clauses of this query to the
+    /// name of *another* table in the schema, which is not otherwise referenced by the query.
+    ///
+    /// Columns qualified with the original table names are rewritten to refer to the new aliases.
+    pub fn shadow_table_names(&mut self) {
+        let mut referenced = ReferencedTables::default();
+        let Ok(()) = referenced.visit_select_statement(&self.statement);
+        let mut candidates = self
+            .state
+            .gen
+            .table_names()
+            .filter(|name| !referenced.0.contains(*name))
+            .cloned()
+            .sorted()
+            .collect::<Vec<_>>();
+
+        let table_exprs = self
+            .statement
+            .tables
+            .iter_mut()
+            .chain(
+                self.statement
+                    .join
+                    .iter_mut()
+                    .filter_map(|jc| match &mut jc.right {
+                        JoinRightSide::Table(te) => Some(te),
+                        _ => None,
+                    }),
+            )
+            .filter(|te| te.alias.is_none())
+            .collect::<Vec<_>>();
+
+        let mut renames = vec![];
+        for table_expr in table_exprs {
+            let Some(table) = table_expr.inner.as_table() else {
+                continue;
+            };
+            if !self
+                .state
+                .gen
+                .tables
+                .contains_key(&TableName::from(&table.name))
+            {
+                // A reference to a CTE
+                continue;
+            }
+
+            let alias = match candidates.pop() {
+                Some(name) => name,
+                None => {
+                    let fresh = self.state.gen.fresh_table_mut();
+                    fresh.primary_key();
+                    fresh.name.clone()
+                }
+            };
+            renames.push((table.name.clone(), SqlIdentifier::from(alias.clone())));
+            table_expr.alias = Some(alias.into());
+        }
+
+        for (from, to) in renames {
+            let Ok(()) = RenameTableQualifier { from, to, depth: 0 }
+                .visit_select_statement(&mut self.statement);
+        }
+    }
+
+    /// Run this query through ReadySet's query rewriting passes as a dry run, returning the
+    /// rewritten query. See [`GeneratorState::rewrite_query`].
+    pub fn dry_run_rewrite(&self) -> anyhow::Result<SelectStatement> {
+        self.state.gen.rewrite_query(self.statement.clone())
+    }
+
+    /// Returns an `EXPLAIN` statement for this query, displayed using the MySQL dialect
+    pub fn generate_explain_query(&self) -> String {
+        format!("EXPLAIN {}", self.statement.display(ParseDialect::MySQL))
+    }
+
+    /// Returns a statement explaining this query using the `EXPLAIN` syntax of the given
+    /// [`Dialect`]
+    pub fn generate_explain_for_dialect(&self, dialect: Dialect) -> String {
+        format!(
+            "{} {}",
+            dialect.explain_prefix(),
+            self.statement.display(dialect.display_dialect())
+        )
+    }
+}
+
+fn min_max_arg_type(dialect: ParseDialect) -> impl Strategy<Value = SqlType> {
+    match dialect {
+        ParseDialect::MySQL => any_with::<SqlType>(SqlTypeArbitraryOptions {
+            generate_arrays: false,
+            dialect: Some(dialect),
+            ..Default::default()
+        })
+        .boxed(),
+        ParseDialect::PostgreSQL => arbitrary_postgres_min_max_arg_type().boxed(),
+    }
+}
+
+#[derive(Debug, Eq, PartialEq, Hash, Clone, Serialize, Deserialize, Arbitrary)]
+#[arbitrary(args = QueryDialect)]
+pub enum AggregateType {
+    Count {
+        #[any(generate_arrays = false, dialect = Some(args.0))]
+        column_type: SqlType,
+        distinct: bool,
+    },
+    Sum {
+        #[strategy(arbitrary_numeric_type(Some(args.0)))]
+        column_type: SqlType,
+        distinct: bool,
+    },
+    Avg {
         #[strategy(arbitrary_numeric_type(Some(args.0)))]
         column_type: SqlType,
         distinct: bool,
@@ -1057,6 +2611,30 @@ pub enum AggregateType {
         #[strategy(min_max_arg_type(args.0))]
         column_type: SqlType,
     },
+    /// `BIT_AND` of an integer column.
+    ///
+    /// Only generated randomly for MySQL, since the bitwise aggregates are MySQL-specific
+    #[weight(u32::from(*args == ParseDialect::MySQL))]
+    BitAnd {
+        #[strategy(arbitrary_integer_type(Some(args.0)))]
+        column_type: SqlType,
+    },
+    /// `BIT_OR` of an integer column.
+    ///
+    /// Only generated randomly for MySQL, since the bitwise aggregates are MySQL-specific
+    #[weight(u32::from(*args == ParseDialect::MySQL))]
+    BitOr {
+        #[strategy(arbitrary_integer_type(Some(args.0)))]
+        column_type: SqlType,
+    },
+    /// `BIT_XOR` of an integer column.
+    ///
+    /// Only generated randomly for MySQL, since the bitwise aggregates are MySQL-specific
+    #[weight(u32::from(*args == ParseDialect::MySQL))]
+    BitXor {
+        #[strategy(arbitrary_integer_type(Some(args.0)))]
+        column_type: SqlType,
+    },
 }
 
 impl AggregateType {
@@ -1068,32 +2646,57 @@ impl AggregateType {
             AggregateType::GroupConcat => SqlType::Text,
             AggregateType::Max { column_type } => column_type.clone(),
             AggregateType::Min { column_type } => column_type.clone(),
+            AggregateType::BitAnd { column_type }
+            | AggregateType::BitOr { column_type }
+            | AggregateType::BitXor { column_type } => column_type.clone(),
+        }
+    }
+
+    /// Returns the name of the function called for this aggregate, if it's one of the bitwise
+    /// aggregates (which are represented as generic function calls, rather than having their own
+    /// variant of [`FunctionExpr`])
+    fn bitwise_function_name(&self) -> Option<&'static str> {
+        match self {
+            AggregateType::BitAnd { .. } => Some("bit_and"),
+            AggregateType::BitOr { .. } => Some("bit_or"),
+            AggregateType::BitXor { .. } => Some("bit_xor"),
+            _ => None,
         }
     }
 }
 
+/// The names of the functions called for the bitwise aggregates, which aren't recognized as
+/// aggregates by [`contains_aggregate`]
+const BITWISE_AGGREGATE_FUNCTIONS: &[&str] = &["bit_and", "bit_or", "bit_xor"];
+
+/// Values generated for the columns aggregated by the bitwise aggregates are drawn from
+/// `0..BIT_FLAG_VALUES`, which fits in every integer type
+const BIT_FLAG_VALUES: i64 = 128;
+
 /// Parameters for generating an arbitrary FilterRhs
 #[derive(Clone)]
 pub struct FilterRhsArgs {
     column_type: SqlType,
+    dialect: QueryDialect,
 }
 
 impl Default for FilterRhsArgs {
     fn default() -> Self {
         Self {
             column_type: SqlType::Int(None),
+            dialect: QueryDialect::default(),
         }
     }
 }
 
-#[derive(Debug, Eq, PartialEq, Clone, Serialize, Deserialize, Arbitrary)]
+#[derive(Debug, Eq, PartialEq, Hash, Clone, Serialize, Deserialize, Arbitrary)]
 #[arbitrary(args = FilterRhsArgs)]
 pub enum FilterRHS {
     Constant(#[strategy(Literal::arbitrary_with_type(&args.column_type))] Literal),
     Column,
 }
 
-#[derive(Debug, Eq, PartialEq, Clone, Copy, EnumIter, Serialize, Deserialize, Arbitrary)]
+#[derive(Debug, Eq, PartialEq, Hash, Clone, Copy, EnumIter, Serialize, Deserialize, Arbitrary)]
 pub enum LogicalOp {
     And,
     Or,
@@ -1108,24 +2711,37 @@ impl From<LogicalOp> for BinaryOperator {
     }
 }
 
-fn filter_op(ty: &SqlType) -> impl Strategy<Value = BinaryOperator> {
+/// Returns a strategy for generating the operators which can be used to compare a column of the
+/// given type in a filter
+fn filter_op_for_type(
+    ty: &SqlType,
+    dialect: QueryDialect,
+) -> impl Strategy<Value = BinaryOperator> {
     use BinaryOperator::*;
-    let mut variants = vec![Equal, NotEqual, Greater, GreaterOrEqual, Less, LessOrEqual];
+    let mut variants = vec![Equal, NotEqual];
+
+    if *ty != SqlType::Bool {
+        variants.extend([Greater, GreaterOrEqual, Less, LessOrEqual]);
+    }
 
     if ty.is_any_text() {
         variants.extend([Like, NotLike]);
+        // MySQL doesn't support ILIKE
+        if dialect == ParseDialect::PostgreSQL {
+            variants.extend([ILike, NotILike]);
+        }
     }
 
     proptest::sample::select(variants)
 }
 
 /// An individual filter operation
-#[derive(Debug, Eq, PartialEq, Clone, Serialize, Deserialize, Arbitrary)]
+#[derive(Debug, Eq, PartialEq, Hash, Clone, Serialize, Deserialize, Arbitrary)]
 #[arbitrary(args = FilterRhsArgs)]
 pub enum FilterOp {
     /// Compare a column with either another column, or a value
     Comparison {
-        #[strategy(filter_op(&args.column_type))]
+        #[strategy(filter_op_for_type(&args.column_type, args.dialect))]
         op: BinaryOperator,
 
         #[strategy(any_with::<FilterRHS>((*args).clone()))]
@@ -1145,10 +2761,37 @@ pub enum FilterOp {
 
     /// An IS NULL comparison on a column
     IsNull { negated: bool },
+
+    /// An equality comparison of a column with a literal of type `rhs_type`, which differs from
+    /// the type of the column and so must be implicitly coerced to compare them. See the
+    /// [`coercion`] module for the supported pairs of column type and literal type.
+    ///
+    /// Never generated by the derived [`Arbitrary`] impl, since the column type is chosen by
+    /// [`Filter`]
+    #[weight(0)]
+    CoercedComparison { rhs_type: SqlType },
+}
+
+/// Which kind of column a [`Filter`] is applied to
+#[derive(Debug, Default, Eq, PartialEq, Hash, Clone, Copy, Serialize, Deserialize, Arbitrary)]
+pub enum FilterTarget {
+    /// A column of a base table in the query
+    #[default]
+    #[weight(9)]
+    Base,
+
+    /// A column projected from a CTE or a subquery in the FROM clause of the query, referenced by
+    /// the alias of that subquery.
+    ///
+    /// Expected values for the filter are planted in the base table column that the projected
+    /// column is traced back to. If the query doesn't contain any such subqueries, the filter is
+    /// applied to a column of a base table instead.
+    #[weight(1)]
+    Subquery,
 }
 
 /// A full representation of a filter to be added to a query
-#[derive(Debug, Eq, PartialEq, Clone, Serialize, Deserialize)]
+#[derive(Debug, Eq, PartialEq, Hash, Clone, Serialize, Deserialize)]
 pub struct Filter {
     /// How to add the filter to the WHERE clause of the query
     pub extend_where_with: LogicalOp,
@@ -1158,6 +2801,10 @@ pub struct Filter {
 
     /// The type of the column that's being filtered on
     pub column_type: SqlType,
+
+    /// Which kind of column the filter is applied to
+    #[serde(default)]
+    pub filter_target: FilterTarget,
 }
 
 impl Arbitrary for Filter {
@@ -1166,7 +2813,24 @@ impl Arbitrary for Filter {
     type Strategy = BoxedStrategy<Filter>;
 
     fn arbitrary_with(dialect: Self::Parameters) -> Self::Strategy {
-        (
+        let coerced_comparison = (
+            proptest::sample::select(
+                coercion::coercible_pairs(dialect.0)
+                    .cloned()
+                    .collect::<Vec<_>>(),
+            ),
+            any::<LogicalOp>(),
+            any::<FilterTarget>(),
+        )
+            .prop_map(
+                |((column_type, rhs_type), extend_where_with, filter_target)| Self {
+                    filter_target,
+                    ..Self::coerced_comparison(column_type, rhs_type, extend_where_with)
+                        .expect("Pairs returned by coercible_pairs are coercible")
+                },
+            );
+
+        let comparison = (
             any_with::<SqlType>(SqlTypeArbitraryOptions {
                 generate_arrays: false, // TODO: Set to true once we're targeting Postgres as well
                 generate_other: false,
@@ -1175,23 +2839,51 @@ impl Arbitrary for Filter {
                 dialect: Some(dialect.0),
             }),
             any::<LogicalOp>(),
+            any::<FilterTarget>(),
         )
-            .prop_flat_map(|(column_type, extend_where_with)| {
+            .prop_flat_map(move |(column_type, extend_where_with, filter_target)| {
                 any_with::<FilterOp>(FilterRhsArgs {
                     column_type: column_type.clone(),
+                    dialect,
                 })
                 .prop_map(move |operation| Self {
                     column_type: column_type.clone(),
                     operation,
                     extend_where_with,
+                    filter_target,
                 })
-            })
-            .boxed()
+            });
+
+        prop_oneof![9 => comparison, 1 => coerced_comparison].boxed()
     }
 }
 
 impl Filter {
+    /// Construct a new [`FilterOp::CoercedComparison`] filter comparing a column of type
+    /// `column_type` with a literal of type `rhs_type`, or return `None` if the two types cannot
+    /// be compared via implicit type coercion
+    pub fn coerced_comparison(
+        column_type: SqlType,
+        rhs_type: SqlType,
+        extend_where_with: LogicalOp,
+    ) -> Option<Self> {
+        coercion::coerced_literal(&column_type, &rhs_type)?;
+        Some(Self {
+            extend_where_with,
+            operation: FilterOp::CoercedComparison { rhs_type },
+            column_type,
+            filter_target: FilterTarget::Base,
+        })
+    }
+
     fn all_with_operator(operator: BinaryOperator) -> impl Iterator<Item = Self> {
+        Self::all_with_operator_and_target(operator, FilterTarget::Base)
+    }
+
+    fn all_with_operator_and_target(
+        operator: BinaryOperator,
+        filter_target: FilterTarget,
+    ) -> impl Iterator<Item = Self> {
         ALL_FILTER_RHS
             .iter()
             .cloned()
@@ -1200,12 +2892,325 @@ impl Filter {
                 operation: FilterOp::Comparison { op: operator, rhs },
                 extend_where_with,
                 column_type: SqlType::Int(None),
+                filter_target,
+            })
+    }
+
+    /// Build the condition for this filter, comparing `lhs` with the right-hand side of the filter
+    /// operation.
+    ///
+    /// `lhs` must be the column `column` of the base table `table`, or be projected from it by a
+    /// subquery, so that expected values can be planted there for the filter to match some rows.
+    /// `other_column` is called to build the right-hand side of comparisons with
+    /// [`FilterRHS::Column`].
+    fn condition(
+        &self,
+        state: &mut QueryState<'_>,
+        lhs: Expr,
+        table: &TableName,
+        column: &ColumnName,
+        mut other_column: impl FnMut(&mut QueryState<'_>) -> Expr,
+    ) -> Expr {
+        let expect_value = |state: &mut QueryState<'_>, value: DfValue| {
+            state
+                .gen
+                .table_mut(table)
+                .unwrap()
+                .expect_value(column.clone(), value)
+        };
+
+        let mut filter_rhs_to_expr = |state: &mut QueryState<'_>, rhs: &FilterRHS| match rhs {
+            FilterRHS::Constant(val) => {
+                expect_value(state, val.clone().try_into().unwrap());
+                Expr::Literal(val.clone())
+            }
+            FilterRHS::Column => other_column(state),
+        };
+
+        match &self.operation {
+            FilterOp::Comparison { op, rhs } => Expr::BinaryOp {
+                op: *op,
+                lhs: Box::new(lhs),
+                rhs: Box::new(filter_rhs_to_expr(state, rhs)),
+            },
+            FilterOp::Between { negated, min, max } => Expr::Between {
+                operand: Box::new(lhs),
+                min: Box::new(filter_rhs_to_expr(state, min)),
+                max: Box::new(filter_rhs_to_expr(state, max)),
+                negated: *negated,
+            },
+            FilterOp::CoercedComparison { rhs_type } => {
+                let (literal, value) = coercion::coerced_comparison(&self.column_type, rhs_type)
+                    .unwrap_or_else(|| {
+                        panic!(
+                            "Cannot compare a column of type {} with a literal of type {}",
+                            self.column_type.display(ParseDialect::MySQL),
+                            rhs_type.display(ParseDialect::MySQL)
+                        )
+                    });
+                expect_value(state, value);
+                Expr::BinaryOp {
+                    op: BinaryOperator::Equal,
+                    lhs: Box::new(lhs),
+                    rhs: Box::new(Expr::Literal(literal)),
+                }
+            }
+            FilterOp::IsNull { negated } => {
+                expect_value(state, DfValue::None);
+                Expr::BinaryOp {
+                    lhs: Box::new(lhs),
+                    op: if *negated {
+                        BinaryOperator::Is
+                    } else {
+                        BinaryOperator::IsNot
+                    },
+                    rhs: Box::new(Expr::Literal(Literal::Null)),
+                }
+            }
+        }
+    }
+
+    /// Add this filter to `query` on a column of some base table in the query
+    fn add_to_base_table(&self, state: &mut QueryState<'_>, query: &mut SelectStatement) {
+        let alias = state.fresh_alias();
+        let (table_name, col) = match state.placed_column(query) {
+            Some(PlacementTarget { table, column }) => (table, column),
+            None => {
+                let tbl = state.some_table_in_query_mut(query);
+                let table_name = tbl.name.clone();
+                let col = tbl.some_column_with_type(self.column_type.clone());
+
+                if query.tables.is_empty() {
+                    query
+                        .tables
+                        .push(TableExpr::from(Relation::from(table_name.0.as_str())));
+                }
+                (table_name, col)
+            }
+        };
+
+        let col_expr = Expr::Column(Column {
+            table: Some(Relation::from(table_name.0.as_str())),
+            ..col.clone().into()
+        });
+
+        query.fields.push(FieldDefinitionExpr::Expr {
+            expr: col_expr.clone(),
+            alias: Some(alias),
+        });
+
+        let cond = self.condition(state, col_expr, &table_name, &col, |state| {
+            let tbl = state.gen.table_mut(&table_name).unwrap();
+            let col = tbl.some_column_with_type_different_than(self.column_type.clone(), &col);
+            Expr::Column(Column {
+                table: Some(tbl.name.clone().into()),
+                ..col.into()
+            })
+        });
+
+        extend_where(query, self.extend_where_with, cond);
+    }
+
+    /// Add this filter to `query` on a column projected from one of the CTEs or subqueries in the
+    /// FROM clause of the query, projecting a new column from one of them if none of the right
+    /// type exist. Returns `false` without modifying the query if there aren't any such subqueries.
+    fn add_to_derived_column(
+        &self,
+        state: &mut QueryState<'_>,
+        query: &mut SelectStatement,
+    ) -> bool {
+        let Some(subquery) = derived_subqueries(query)
+            .next()
+            .map(|(name, _)| name.clone())
+        else {
+            return false;
+        };
+
+        let column = derived_columns(state, query)
+            .into_iter()
+            .find(|col| col.sql_type == self.column_type)
+            .unwrap_or_else(|| {
+                project_derived_column(state, query, subquery, &self.column_type, None)
+            });
+
+        let col_expr = column.outer_expr();
+        query.fields.push(FieldDefinitionExpr::Expr {
+            expr: col_expr.clone(),
+            alias: Some(state.fresh_alias()),
+        });
+
+        let cond = self.condition(state, col_expr, &column.table, &column.column, |state| {
+            project_derived_column(
+                state,
+                query,
+                column.subquery.clone(),
+                &self.column_type,
+                Some(&column.column),
+            )
+            .outer_expr()
+        });
+
+        extend_where(query, self.extend_where_with, cond);
+        true
+    }
+}
+
+/// A column projected from a CTE or a subquery in the FROM clause of a query, along with the
+/// column of a base table which it was projected from
+struct DerivedColumn {
+    /// The name of the CTE, or the alias of the subquery
+    subquery: SqlIdentifier,
+    /// The name of the column in the outer query
+    alias: SqlIdentifier,
+    /// The base table the column was projected from
+    table: TableName,
+    /// The column in the base table the column was projected from
+    column: ColumnName,
+    sql_type: SqlType,
+}
+
+impl DerivedColumn {
+    /// Returns an expression referencing this column in the outer query
+    fn outer_expr(&self) -> Expr {
+        Expr::Column(Column {
+            name: self.alias.clone(),
+            table: Some(Relation {
+                name: self.subquery.clone(),
+                schema: None,
+            }),
+        })
+    }
+}
+
+/// Returns the names and statements of all the CTEs and subqueries joined to in the FROM clause of
+/// `query`
+fn derived_subqueries(
+    query: &SelectStatement,
+) -> impl Iterator<Item = (&SqlIdentifier, &SelectStatement)> {
+    query
+        .ctes
+        .iter()
+        .map(|cte| (&cte.name, &cte.statement))
+        .chain(query.join.iter().filter_map(|jc| match &jc.right {
+            JoinRightSide::Table(TableExpr {
+                inner: TableExprInner::Subquery(statement),
+                alias: Some(name),
+                ..
+            }) => Some((name, statement.as_ref())),
+            _ => None,
+        }))
+}
+
+/// Returns all the columns projected directly from a base table by the CTEs and subqueries in the
+/// FROM clause of `query`
+fn derived_columns(state: &QueryState<'_>, query: &SelectStatement) -> Vec<DerivedColumn> {
+    derived_subqueries(query)
+        .flat_map(|(subquery, statement)| {
+            statement
+                .fields
+                .iter()
+                .filter_map(move |field| match field {
+                    FieldDefinitionExpr::Expr {
+                        expr:
+                            Expr::Column(Column {
+                                name,
+                                table: Some(table),
+                            }),
+                        alias,
+                    } => {
+                        // Columns of tables which aren't in the schema are projected from a nested
+                        // subquery, so can't be traced back to a base table
+                        let table = TableName::from(table.name.as_str());
+                        let column = ColumnName::from(name.as_str());
+                        let sql_type = state
+                            .gen
+                            .tables
+                            .get(&table)?
+                            .columns
+                            .get(&column)?
+                            .sql_type
+                            .clone();
+                        Some(DerivedColumn {
+                            subquery: subquery.clone(),
+                            alias: alias.clone().unwrap_or_else(|| name.clone()),
+                            table,
+                            column,
+                            sql_type,
+                        })
+                    }
+                    _ => None,
+                })
+        })
+        .collect()
+}
+
+/// Project a new column of type `sql_type` (other than `different_than`, if given) from some base
+/// table in the CTE or subquery named `subquery` in the FROM clause of `query`, and return it.
+///
+/// # Panics
+///
+/// Panics if `query` doesn't contain a CTE or a subquery named `subquery`
+fn project_derived_column(
+    state: &mut QueryState<'_>,
+    query: &mut SelectStatement,
+    subquery: SqlIdentifier,
+    sql_type: &SqlType,
+    different_than: Option<&ColumnName>,
+) -> DerivedColumn {
+    let alias = state.fresh_alias();
+    let statement = query
+        .ctes
+        .iter_mut()
+        .find(|cte| cte.name == subquery)
+        .map(|cte| &mut cte.statement)
+        .or_else(|| {
+            query.join.iter_mut().find_map(|jc| match &mut jc.right {
+                JoinRightSide::Table(TableExpr {
+                    inner: TableExprInner::Subquery(statement),
+                    alias: Some(name),
+                    ..
+                }) if *name == subquery => Some(statement.as_mut()),
+                _ => None,
             })
+        })
+        .unwrap_or_else(|| panic!("Query should contain a subquery named {subquery}"));
+
+    let table = state.some_table_in_query_mut(statement);
+    let table_name = table.name.clone();
+    let column = match different_than {
+        Some(different_than) => {
+            table.some_column_with_type_different_than(sql_type.clone(), different_than)
+        }
+        None => table.some_column_with_type(sql_type.clone()),
+    };
+
+    let column_expr = Expr::Column(Column {
+        name: column.clone().into(),
+        table: Some(table_name.clone().into()),
+    });
+    statement.fields.push(FieldDefinitionExpr::Expr {
+        expr: column_expr.clone(),
+        alias: Some(alias.clone()),
+    });
+    if statement.group_by.is_some() || query_has_aggregate(statement) {
+        statement
+            .group_by
+            .get_or_insert_with(Default::default)
+            .fields
+            .push(FieldReference::Expr(column_expr));
+    }
+
+    DerivedColumn {
+        subquery,
+        alias,
+        table: table_name,
+        column,
+        sql_type: sql_type.clone(),
     }
 }
 
 // The names of the built-in functions we can generate for use in a project expression
-#[derive(Debug, Eq, PartialEq, Clone, Copy, EnumIter, Serialize, Deserialize)]
+#[derive(Debug, Eq, PartialEq, Hash, Clone, Copy, EnumIter, Serialize, Deserialize)]
 pub enum BuiltinFunction {
     ConvertTZ,
     DayOfWeek,
@@ -1214,6 +3219,33 @@ pub enum BuiltinFunction {
     Timediff,
     Addtime,
     Round,
+    /// Only generated by the `char_functions` operation, since nom-sql can't parse calls to it
+    /// (`CHAR` is a keyword) and dataflow-expression doesn't implement it
+    Char,
+    /// Only generated by the `char_functions` operation, since dataflow-expression doesn't
+    /// implement it
+    Ascii,
+}
+
+impl BuiltinFunction {
+    /// Returns true if this function is only generated when explicitly requested via the
+    /// `char_functions` operation, rather than randomly or as part of `project_builtin`
+    pub fn is_char_function(self) -> bool {
+        matches!(self, BuiltinFunction::Char | BuiltinFunction::Ascii)
+    }
+
+    /// Returns the type of the value returned by a call to this function, as generated by
+    /// [`QueryOperation::ProjectBuiltinFunction`]
+    pub fn return_type(self) -> SqlType {
+        match self {
+            BuiltinFunction::ConvertTZ => SqlType::Timestamp,
+            BuiltinFunction::DayOfWeek | BuiltinFunction::Month => SqlType::Int(None),
+            BuiltinFunction::IfNull | BuiltinFunction::Char => SqlType::Text,
+            BuiltinFunction::Timediff | BuiltinFunction::Addtime => SqlType::Time,
+            BuiltinFunction::Round => SqlType::Real,
+            BuiltinFunction::Ascii => SqlType::Int(Some(32)),
+        }
+    }
 }
 
 impl Arbitrary for BuiltinFunction {
@@ -1234,14 +3266,151 @@ impl Arbitrary for BuiltinFunction {
     }
 }
 
+/// The types of literal values which can be projected by [`QueryOperation::ProjectLiteral`]
+#[derive(Debug, Eq, PartialEq, Hash, Clone, Copy, EnumIter, Serialize, Deserialize, Arbitrary)]
+pub enum LiteralType {
+    Integer,
+    Float,
+    Text,
+    Boolean,
+    Null,
+    /// The current timestamp. This isn't a literal as far as SQL is concerned, but is projected as
+    /// a call to `now()`
+    CurrentTimestamp,
+}
+
+impl LiteralType {
+    /// Returns an expression which evaluates to a value of this type
+    pub fn to_expr(self) -> Expr {
+        match self {
+            LiteralType::Integer => Expr::Literal(Literal::Integer(1)),
+            LiteralType::Float => Expr::Literal(Literal::Double(Double {
+                value: 1.5,
+                precision: 1,
+            })),
+            LiteralType::Text => Expr::Literal(Literal::String("a".into())),
+            LiteralType::Boolean => Expr::Literal(Literal::Boolean(true)),
+            LiteralType::Null => Expr::Literal(Literal::Null),
+            LiteralType::CurrentTimestamp => Expr::Call(FunctionExpr::Call {
+                name: "now".into(),
+                arguments: vec![],
+            }),
+        }
+    }
+}
+
+/// The granularity at which to bucket a timestamp column for [`QueryOperation::GroupByExpression`]
+#[derive(Debug, Eq, PartialEq, Hash, Clone, Copy, EnumIter, Serialize, Deserialize, Arbitrary)]
+pub enum TimeBucket {
+    /// `date_format(col, '%Y-%m-%d')`
+    Date,
+    /// `month(col)`
+    Month,
+    /// `date_format(col, '%Y')`
+    Year,
+}
+
+impl TimeBucket {
+    /// Returns an expression computing the bucket for the value of the given timestamp column
+    pub fn to_expr(self, column: Column) -> Expr {
+        let call = |name: &str, format: Option<&str>| {
+            Expr::Call(FunctionExpr::Call {
+                name: name.into(),
+                arguments: iter::once(Expr::Column(column.clone()))
+                    .chain(format.map(|f| Expr::Literal(Literal::String(f.into()))))
+                    .collect(),
+            })
+        };
+
+        match self {
+            TimeBucket::Date => call("date_format", Some("%Y-%m-%d")),
+            TimeBucket::Month => call("month", None),
+            TimeBucket::Year => call("date_format", Some("%Y")),
+        }
+    }
+}
+
+/// The number of distinct timestamps generated for columns grouped by
+/// [`QueryOperation::GroupByExpression`], so that each bucket has a non-trivial number of rows
+const TIME_BUCKET_DISTINCT_TIMESTAMPS: u64 = 8;
+
+/// A ranking window function, projected by [`QueryOperation::WindowRankFunction`]
+#[derive(Debug, Eq, PartialEq, Hash, Clone, Copy, Serialize, Deserialize, Arbitrary)]
+pub enum RankFunction {
+    /// `ROW_NUMBER()`
+    RowNumber,
+    /// `RANK()`
+    Rank,
+    /// `DENSE_RANK()`
+    DenseRank,
+    /// `NTILE(n)`
+    Ntile(#[strategy(1..=10u32)] u32),
+}
+
+impl RankFunction {
+    /// Returns the name of the function, as it should be called in SQL
+    pub fn name(self) -> &'static str {
+        match self {
+            RankFunction::RowNumber => "ROW_NUMBER",
+            RankFunction::Rank => "RANK",
+            RankFunction::DenseRank => "DENSE_RANK",
+            RankFunction::Ntile(_) => "NTILE",
+        }
+    }
+
+    /// Returns the arguments to pass to the function
+    pub fn arguments(self) -> Vec<Expr> {
+        match self {
+            RankFunction::Ntile(buckets) => {
+                vec![Expr::Literal(Literal::UnsignedInteger(buckets.into()))]
+            }
+            _ => vec![],
+        }
+    }
+}
+
+const ALL_RANK_FUNCTIONS: &[RankFunction] = &[
+    RankFunction::RowNumber,
+    RankFunction::Rank,
+    RankFunction::DenseRank,
+    RankFunction::Ntile(4),
+];
+
+/// The number of distinct values generated for the column partitioned by in
+/// [`QueryOperation::WindowRankFunction`], so that each partition has a non-trivial number of rows
+const WINDOW_PARTITION_DISTINCT_VALUES: u64 = 3;
+
+/// The number of distinct values generated for the column ordered by in
+/// [`QueryOperation::WindowRankFunction`], so that partitions contain ties
+const WINDOW_ORDER_DISTINCT_VALUES: u64 = 5;
+
+/// The kind of constraint to use to join two tables
+#[derive(Debug, Eq, PartialEq, Hash, Clone, Copy, EnumIter, Serialize, Deserialize, Arbitrary)]
+pub enum JoinConstraintType {
+    /// `JOIN t2 ON t1.x = t2.y`
+    On,
+    /// `JOIN t2 USING (x)`
+    ///
+    /// Not generated randomly, since ReadySet does not support `USING`
+    #[weight(0)]
+    Using,
+}
+
 /// A representation for where in a query a subquery is located
 ///
 /// When we support them, subqueries in `IN` clauses should go here as well
-#[derive(Debug, Eq, PartialEq, Clone, Serialize, Deserialize, Arbitrary)]
+#[derive(Debug, Eq, PartialEq, Hash, Clone, Serialize, Deserialize, Arbitrary)]
 #[arbitrary(args = QueryDialect)]
 pub enum SubqueryPosition {
     Cte(JoinOperator),
     Join(JoinOperator),
+    /// A CTE which is joined against twice, under two distinct aliases and on two different join
+    /// keys, to exercise reuse of the same subquery in the dataflow graph.
+    ///
+    /// Not generated randomly, and only included in [`GenerateOpts::into_query_seeds`] when all
+    /// operations are permuted if [`GenerateOpts::include_cte_reuse`] is set
+    #[weight(0)]
+    CteReusedTwice(JoinOperator),
     /// TODO, once we support them:
     ///
     /// - `extend_where_with: LogicalOp`
@@ -1287,14 +3456,38 @@ pub struct QueryOperationArgs {
 /// each of which should be relatively straightforward to add here.
 ///
 /// [0]: https://docs.google.com/document/d/1rb-AU_PsH2Z40XFLjmLP7DcyeJzlwKI4Aa-GQgEoWKA
-#[derive(Debug, Eq, PartialEq, Clone, Serialize, Deserialize, Arbitrary)]
+#[derive(Debug, Eq, PartialEq, Hash, Clone, Serialize, Deserialize, Arbitrary)]
 #[arbitrary(args = QueryOperationArgs)]
 pub enum QueryOperation {
     ColumnAggregate(#[any(args.dialect)] AggregateType),
     Filter(#[any(args.dialect)] Filter),
     Distinct,
-    Join(JoinOperator),
-    ProjectLiteral,
+    Join {
+        operator: JoinOperator,
+        constraint: JoinConstraintType,
+        /// The type of the key columns joined on, unless the join is pinned to existing columns
+        #[strategy(proptest::strategy::Just(SqlType::Int(None)))]
+        key_type: SqlType,
+        /// The number of key columns joined on, from 1 to [`MAX_JOIN_KEY_COLUMNS`].
+        ///
+        /// Joins on more than one key column join on a conjunction of equalities between fresh
+        /// columns of both tables, whose data is generated such that every tuple of key values in
+        /// the right table matches a tuple in the left table. Joins which are pinned to existing
+        /// columns by a placement plan always join on just the pinned columns.
+        #[strategy(1..=MAX_JOIN_KEY_COLUMNS)]
+        key_columns: usize,
+    },
+    /// `LEFT JOIN` a fresh table on a key column, and filter for rows with no match on the right
+    /// with `WHERE right.key IS NULL`.
+    ///
+    /// The data generated for the right table's key matches `overlap_percent`% of the keys in the
+    /// left table, so the query returns the remaining rows of the left table. Nothing else is ever
+    /// added to the query on the columns of the right table, as that would change which rows match
+    AntiJoin {
+        #[strategy(1..=99u8)]
+        overlap_percent: u8,
+    },
+    ProjectLiteral(LiteralType),
     SingleParameter,
     MultipleParameters,
     InParameter {
@@ -1306,18 +3499,115 @@ pub enum QueryOperation {
     ProjectBuiltinFunction(#[any(args.dialect)] BuiltinFunction),
     TopK {
         order_type: OrderType,
-        #[strategy(0..=100u64)]
+        #[strategy(0..=MAX_LIMIT)]
         limit: u64,
     },
-    Paginate {
+    /// Add a parameter on a fresh column, and `ORDER BY` a different fresh timestamp column of the
+    /// same table with a `LIMIT`, eg `WHERE author_id = ? ORDER BY created DESC LIMIT 10`.
+    ///
+    /// The data generated for the table gives each value of the parameter column
+    /// [`PARAM_TOPK_ROWS_PER_LIMIT`] times `limit` rows, each with a distinct timestamp, and the
+    /// key for the parameter is always a value that owns rows, so the limit always applies. At
+    /// least that many rows are generated for the table even if fewer rows per table are
+    /// requested (see [`QueryState::require_rows`]).
+    ParamTopK {
         order_type: OrderType,
-        #[strategy(0..=100u64)]
+        #[strategy(1..=MAX_LIMIT)]
         limit: u64,
-        #[strategy(0..=100u64)]
-        page_number: u64,
+    },
+    Paginate {
+        order_type: OrderType,
+        #[strategy(0..=MAX_LIMIT)]
+        limit: u64,
+        #[strategy(0..=MAX_PAGE_NUMBER)]
+        page_number: u64,
     },
     #[weight(0)]
     Subquery(SubqueryPosition),
+    /// Project an expression bucketing a fresh timestamp column by [`TimeBucket`], and `GROUP BY`
+    /// that expression, adding a `COUNT(*)` if the query doesn't already have an aggregate.
+    ///
+    /// Only generated randomly for MySQL, since the functions used to compute the buckets are
+    /// MySQL-specific
+    #[weight(u32::from(args.dialect == ParseDialect::MySQL))]
+    GroupByExpression {
+        bucket: TimeBucket,
+    },
+    /// `GROUP BY` some number of fresh columns `WITH ROLLUP`, projecting `COUNT(*)`.
+    ///
+    /// Not generated randomly, since ReadySet does not support `WITH ROLLUP`
+    #[weight(0)]
+    Rollup {
+        #[strategy(1..=3usize)]
+        num_group_cols: usize,
+    },
+    /// Project a ranking window function along with a fresh column it's partitioned by and a
+    /// fresh column it's ordered by, eg `ROW_NUMBER() OVER (PARTITION BY x ORDER BY y)`.
+    ///
+    /// Not generated randomly, since ReadySet does not support window functions
+    #[weight(0)]
+    WindowRankFunction {
+        func: RankFunction,
+        #[any(generate_arrays = false, dialect = Some(args.dialect.0))]
+        partition_by_type: SqlType,
+        #[any(generate_arrays = false, dialect = Some(args.dialect.0))]
+        order_by_type: SqlType,
+    },
+    /// Filter on a JSON path expression extracted from a fresh text column, eg
+    /// `JSON_EXTRACT(x, '$.value') = 42`, projecting the column.
+    ///
+    /// Every value generated for the column is a valid JSON object, since `JSON_EXTRACT` errors on
+    /// anything else. Most have a random integer under the `"value"` key, one always satisfies the
+    /// filter, and one (`{}`) doesn't have the key at all.
+    ///
+    /// Only generated randomly for MySQL, since `JSON_EXTRACT` is MySQL-specific
+    #[weight(u32::from(args.dialect == ParseDialect::MySQL))]
+    JsonPath {
+        #[strategy(proptest::sample::select(COMPARISON_OPS))]
+        operator: BinaryOperator,
+    },
+    /// Add a parameter on a fresh integer column, and also compare that same column against a
+    /// literal with `literal_op`, eg `WHERE x = ? AND x > 9`.
+    ///
+    /// The key for the parameter is always [`PARAM_WITH_LITERAL_KEY`], which the generated data
+    /// contains. If `contradictory` is false, the literal is chosen so that the key satisfies the
+    /// comparison, and the query returns rows; if it's true, the literal is chosen so that it
+    /// doesn't, and the query is recorded as expecting empty results when executed with that key
+    /// (see [`QueryState::expects_empty_results`]). Contradictory queries are never generated
+    /// randomly.
+    ///
+    /// If `literal_op` isn't one of the comparison operators, only the parameter is added.
+    ParameterWithLiteralConstraint {
+        #[strategy(proptest::sample::select(COMPARISON_OPS))]
+        literal_op: BinaryOperator,
+        #[strategy(proptest::strategy::Just(false))]
+        contradictory: bool,
+    },
+}
+
+/// The integer compared against by the filters added by [`QueryOperation::JsonPath`]
+const JSON_PATH_VALUE: i64 = 42;
+
+/// The key used for the parameter added by [`QueryOperation::ParameterWithLiteralConstraint`]
+const PARAM_WITH_LITERAL_KEY: i64 = 10;
+
+/// Returns the literal to compare against with `op` in a
+/// [`QueryOperation::ParameterWithLiteralConstraint`], such that [`PARAM_WITH_LITERAL_KEY`]
+/// satisfies the comparison unless `contradictory` is true. Returns `None` if `op` isn't a
+/// comparison operator.
+fn param_with_literal_value(op: BinaryOperator, contradictory: bool) -> Option<i64> {
+    let key = PARAM_WITH_LITERAL_KEY;
+    Some(match (op, contradictory) {
+        (
+            BinaryOperator::Equal | BinaryOperator::GreaterOrEqual | BinaryOperator::LessOrEqual,
+            false,
+        )
+        | (BinaryOperator::NotEqual | BinaryOperator::Greater | BinaryOperator::Less, true) => key,
+        (BinaryOperator::Greater, false) | (BinaryOperator::LessOrEqual, true) => key - 1,
+        (BinaryOperator::NotEqual | BinaryOperator::Less, false)
+        | (BinaryOperator::Equal | BinaryOperator::GreaterOrEqual, true) => key + 1,
+        _ => return None,
+    })
 }
 
 const ALL_FILTER_RHS: &[FilterRHS] = &[FilterRHS::Column, FilterRHS::Constant(Literal::Integer(1))];
@@ -1339,6 +3629,21 @@ const JOIN_OPERATORS: &[JoinOperator] = &[
 
 const DEFAULT_LIMIT: u64 = 3;
 
+/// The maximum number of key columns in a [`QueryOperation::Join`]
+pub const MAX_JOIN_KEY_COLUMNS: usize = 3;
+
+/// The maximum `limit` of a [`QueryOperation::TopK`], [`QueryOperation::ParamTopK`], or
+/// [`QueryOperation::Paginate`] that can be given in an operation specification
+pub const MAX_LIMIT: u64 = 100;
+
+/// The maximum `page_number` of a [`QueryOperation::Paginate`] that can be given in an operation
+/// specification
+pub const MAX_PAGE_NUMBER: u64 = 100;
+
+/// The number of rows generated for each value of the parameter column added by
+/// [`QueryOperation::ParamTopK`], as a multiple of its `limit`
+pub const PARAM_TOPK_ROWS_PER_LIMIT: u64 = 5;
+
 const ALL_TOPK: &[QueryOperation] = &[
     QueryOperation::TopK {
         order_type: OrderType::OrderAscending,
@@ -1405,6 +3710,15 @@ const ALL_AGGREGATE_TYPES: &[AggregateType] = &[
     AggregateType::Min {
         column_type: SqlType::Int(None),
     },
+    AggregateType::BitAnd {
+        column_type: SqlType::Int(None),
+    },
+    AggregateType::BitOr {
+        column_type: SqlType::Int(None),
+    },
+    AggregateType::BitXor {
+        column_type: SqlType::Int(None),
+    },
 ];
 
 const ALL_SUBQUERY_POSITIONS: &[SubqueryPosition] = &[
@@ -1412,6 +3726,11 @@ const ALL_SUBQUERY_POSITIONS: &[SubqueryPosition] = &[
     SubqueryPosition::Cte(JoinOperator::InnerJoin),
 ];
 
+/// Subquery positions which are only included in the set of all operations if
+/// [`GenerateOpts::include_cte_reuse`] is set
+const CTE_REUSE_SUBQUERY_POSITIONS: &[SubqueryPosition] =
+    &[SubqueryPosition::CteReusedTwice(JoinOperator::InnerJoin)];
+
 lazy_static! {
     static ref ALL_COMPARISON_FILTER_OPS: Vec<FilterOp> = {
         COMPARISON_OPS
@@ -1456,29 +3775,49 @@ lazy_static! {
             .map(|(operation, extend_where_with)| Filter {
                 extend_where_with,
                 operation,
-                column_type: SqlType::Int(None)
+                column_type: SqlType::Int(None),
+                filter_target: FilterTarget::Base,
             })
             .collect()
     };
 
     /// A list of all possible [`QueryOperation`]s
+    ///
+    /// Doesn't include joins with a `USING` clause, since ReadySet doesn't support them yet
     pub static ref ALL_OPERATIONS: Vec<QueryOperation> = {
         ALL_AGGREGATE_TYPES
             .iter()
             .cloned()
             .map(QueryOperation::ColumnAggregate)
             .chain(iter::once(QueryOperation::Distinct))
-            .chain(JOIN_OPERATORS.iter().cloned().map(QueryOperation::Join))
-            .chain(iter::once(QueryOperation::ProjectLiteral))
+            .chain(JOIN_OPERATORS.iter().map(|&operator| QueryOperation::Join {
+                operator,
+                constraint: JoinConstraintType::On,
+                key_type: SqlType::Int(None),
+                key_columns: 1,
+            }))
+            .chain(iter::once(QueryOperation::AntiJoin { overlap_percent: 50 }))
+            .chain(LiteralType::iter().map(QueryOperation::ProjectLiteral))
             .chain(iter::once(QueryOperation::SingleParameter))
             .chain(iter::once(QueryOperation::InParameter { num_values: 3 }))
-            .chain(BuiltinFunction::iter().map(QueryOperation::ProjectBuiltinFunction))
+            .chain(
+                BuiltinFunction::iter()
+                    .filter(|f| !f.is_char_function())
+                    .map(QueryOperation::ProjectBuiltinFunction)
+            )
+            .chain(TimeBucket::iter().map(|bucket| QueryOperation::GroupByExpression { bucket }))
             .chain(ALL_TOPK.iter().cloned())
             .chain(ALL_SUBQUERY_POSITIONS.iter().cloned().map(QueryOperation::Subquery))
             .collect()
     };
 }
 
+/// Returns an integer literal for the given value, saturating at [`i64::MAX`] rather than wrapping
+/// around to a negative number
+fn integer_literal(n: u64) -> Literal {
+    Literal::Integer(i64::try_from(n).unwrap_or(i64::MAX))
+}
+
 fn extend_where(query: &mut SelectStatement, op: LogicalOp, cond: Expr) {
     query.where_clause = Some(match query.where_clause.take() {
         Some(existing_cond) => Expr::BinaryOp {
@@ -1494,11 +3833,22 @@ fn and_where(query: &mut SelectStatement, cond: Expr) {
     extend_where(query, LogicalOp::And, cond)
 }
 
+/// Returns true if `expr` contains an aggregate, including the bitwise aggregates which aren't
+/// recognized by [`contains_aggregate`]
+fn contains_generated_aggregate(expr: &Expr) -> bool {
+    contains_aggregate(expr)
+        || matches!(
+            expr,
+            Expr::Call(FunctionExpr::Call { name, .. })
+                if BITWISE_AGGREGATE_FUNCTIONS.contains(&name.as_str())
+        )
+}
+
 fn query_has_aggregate(query: &SelectStatement) -> bool {
     query.fields.iter().any(|fde| {
         matches!(
             fde,
-            FieldDefinitionExpr::Expr { expr, .. } if contains_aggregate(expr),
+            FieldDefinitionExpr::Expr { expr, .. } if contains_generated_aggregate(expr),
         )
     })
 }
@@ -1521,7 +3871,7 @@ where
             _ => None,
         }))
         .filter_map(|te| te.inner.as_table())
-        .next()
+        .find(|tbl| !state.anti_joined_tables.contains(tbl.name.as_str()))
     {
         Some(tbl) => {
             let column = state
@@ -1595,7 +3945,8 @@ impl QueryOperation {
     /// returns false, `add_to_query` will not be called on this query operation when adding it to a
     /// subquery.
     fn supported_in_subqueries(&self) -> bool {
-        // We don't currently support query parameters in subqueries
+        // We don't currently support query parameters, or filters on the columns of nested
+        // subqueries, in subqueries
         !matches!(
             self,
             QueryOperation::MultipleParameters
@@ -1603,7 +3954,13 @@ impl QueryOperation {
                 | QueryOperation::InParameter { .. }
                 | QueryOperation::RangeParameter
                 | QueryOperation::MultipleRangeParameters
+                | QueryOperation::ParameterWithLiteralConstraint { .. }
+                | QueryOperation::ParamTopK { .. }
                 | QueryOperation::Paginate { .. }
+                | QueryOperation::Filter(Filter {
+                    filter_target: FilterTarget::Subquery,
+                    ..
+                })
         )
     }
 
@@ -1615,19 +3972,33 @@ impl QueryOperation {
                 use AggregateType::*;
 
                 let alias = state.fresh_alias();
-                let tbl = state.some_table_in_query_mut(query);
+                let (table_name, col) = match state.placed_column(query) {
+                    Some(PlacementTarget { table, column }) => (table, column),
+                    None => {
+                        let tbl = state.some_table_in_query_mut(query);
 
-                if query.tables.is_empty() {
-                    query
-                        .tables
-                        .push(TableExpr::from(Relation::from(tbl.name.clone())));
-                }
+                        if query.tables.is_empty() {
+                            query
+                                .tables
+                                .push(TableExpr::from(Relation::from(tbl.name.clone())));
+                        }
 
-                let col = tbl.fresh_column_with_type(agg.column_type());
+                        let col = tbl.fresh_column_with_type(agg.column_type());
+                        if agg.bitwise_function_name().is_some() {
+                            // Generate a spread of flag values, so that the bitwise aggregates
+                            // combine more than one distinct value
+                            tbl.set_column_generator_spec(
+                                col.clone(),
+                                ColumnGenerationSpec::Uniform(0i64.into(), BIT_FLAG_VALUES.into()),
+                            );
+                        }
+                        (tbl.name.clone(), col)
+                    }
+                };
 
                 let expr = Box::new(Expr::Column(Column {
                     name: col.into(),
-                    table: Some(tbl.name.clone().into()),
+                    table: Some(table_name.into()),
                 }));
 
                 let func = match *agg {
@@ -1640,6 +4011,10 @@ impl QueryOperation {
                     },
                     Max { .. } => FunctionExpr::Max(expr),
                     Min { .. } => FunctionExpr::Min(expr),
+                    BitAnd { .. } | BitOr { .. } | BitXor { .. } => FunctionExpr::Call {
+                        name: agg.bitwise_function_name().unwrap().into(),
+                        arguments: vec![*expr],
+                    },
                 };
 
                 query.fields.push(FieldDefinitionExpr::Expr {
@@ -1649,68 +4024,11 @@ impl QueryOperation {
             }
 
             QueryOperation::Filter(filter) => {
-                let alias = state.fresh_alias();
-                let tbl = state.some_table_in_query_mut(query);
-                let col = tbl.some_column_with_type(filter.column_type.clone());
-
-                if query.tables.is_empty() {
-                    query
-                        .tables
-                        .push(TableExpr::from(Relation::from(tbl.name.0.as_str())));
+                if filter.filter_target != FilterTarget::Subquery
+                    || !filter.add_to_derived_column(state, query)
+                {
+                    filter.add_to_base_table(state, query)
                 }
-
-                let col_expr = Expr::Column(Column {
-                    table: Some(Relation::from(tbl.name.0.as_str())),
-                    ..col.clone().into()
-                });
-
-                query.fields.push(FieldDefinitionExpr::Expr {
-                    expr: col_expr.clone(),
-                    alias: Some(alias),
-                });
-
-                let mut filter_rhs_to_expr = |rhs: &FilterRHS| match rhs {
-                    FilterRHS::Constant(val) => {
-                        tbl.expect_value(col.clone(), val.clone().try_into().unwrap());
-                        Expr::Literal(val.clone())
-                    }
-                    FilterRHS::Column => {
-                        let col = tbl
-                            .some_column_with_type_different_than(filter.column_type.clone(), &col);
-                        Expr::Column(Column {
-                            table: Some(tbl.name.clone().into()),
-                            ..col.into()
-                        })
-                    }
-                };
-
-                let cond = match &filter.operation {
-                    FilterOp::Comparison { op, rhs } => Expr::BinaryOp {
-                        op: *op,
-                        lhs: Box::new(col_expr),
-                        rhs: Box::new(filter_rhs_to_expr(rhs)),
-                    },
-                    FilterOp::Between { negated, min, max } => Expr::Between {
-                        operand: Box::new(col_expr),
-                        min: Box::new(filter_rhs_to_expr(min)),
-                        max: Box::new(filter_rhs_to_expr(max)),
-                        negated: *negated,
-                    },
-                    FilterOp::IsNull { negated } => {
-                        tbl.expect_value(col, DfValue::None);
-                        Expr::BinaryOp {
-                            lhs: Box::new(col_expr),
-                            op: if *negated {
-                                BinaryOperator::Is
-                            } else {
-                                BinaryOperator::IsNot
-                            },
-                            rhs: Box::new(Expr::Literal(Literal::Null)),
-                        }
-                    }
-                };
-
-                extend_where(query, filter.extend_where_with, cond);
             }
 
             QueryOperation::Distinct => {
@@ -1734,39 +4052,124 @@ impl QueryOperation {
                 }
             }
 
-            QueryOperation::Join(operator) => {
-                let left_table = state.some_table_in_query_mut(query);
-                let left_table_name = left_table.name.clone();
-                let left_join_key = left_table.some_column_with_type(SqlType::Int(None));
-                let left_projected = left_table.fresh_column();
+            QueryOperation::Join {
+                operator,
+                constraint,
+                key_type,
+                key_columns,
+            } => {
+                let placed = state.placed_join(query);
+                let pinned = placed.is_some();
+                let (left_table_name, left_join_keys, left_projected);
+                let (right_table_name, right_join_keys, right_projected);
+                let mut right_alias = None;
+                if let Some((left, right, alias)) = placed {
+                    // Project the pinned join keys themselves, rather than adding new columns to
+                    // what's likely an existing schema
+                    left_table_name = left.table;
+                    left_projected = left.column.clone();
+                    left_join_keys = vec![left.column];
+                    right_table_name = right.table;
+                    right_projected = right.column.clone();
+                    right_join_keys = vec![right.column];
+                    right_alias = alias;
+                } else {
+                    let left_table = state.some_table_in_query_mut(query);
+                    left_table_name = left_table.name.clone();
+                    left_join_keys = left_table.join_key_columns(key_type, *key_columns);
+                    left_projected = left_table.fresh_column();
 
-                if query.tables.is_empty() {
-                    query
-                        .tables
-                        .push(TableExpr::from(Relation::from(left_table_name.clone())));
+                    if query.tables.is_empty() {
+                        query
+                            .tables
+                            .push(TableExpr::from(Relation::from(left_table_name.clone())));
+                    }
+
+                    let right_table = state.fresh_table_mut();
+                    right_table_name = right_table.name.clone();
+                    right_join_keys = right_table.join_key_columns(key_type, *key_columns);
+                    right_projected = right_table.fresh_column();
                 }
+                // What columns of the right side of the join are qualified with
+                let right_relation = match &right_alias {
+                    Some(alias) => Relation::from(alias.clone()),
+                    None => Relation::from(right_table_name.clone()),
+                };
 
-                let right_table = state.fresh_table_mut();
-                let right_table_name = right_table.name.clone();
-                let right_join_key = right_table.some_column_with_type(SqlType::Int(None));
-                let right_projected = right_table.fresh_column();
+                let constraint = match constraint {
+                    JoinConstraintType::On => JoinConstraint::On(
+                        left_join_keys
+                            .into_iter()
+                            .zip(right_join_keys)
+                            .map(|(left_join_key, right_join_key)| Expr::BinaryOp {
+                                op: BinaryOperator::Equal,
+                                lhs: Box::new(Expr::Column(Column {
+                                    table: Some(left_table_name.clone().into()),
+                                    ..left_join_key.into()
+                                })),
+                                rhs: Box::new(Expr::Column(Column {
+                                    table: Some(right_relation.clone()),
+                                    ..right_join_key.into()
+                                })),
+                            })
+                            .reduce(|lhs, rhs| Expr::BinaryOp {
+                                lhs: Box::new(lhs),
+                                op: BinaryOperator::And,
+                                rhs: Box::new(rhs),
+                            })
+                            .expect("Joins have at least one key column"),
+                    ),
+                    // Pinned join keys have already been checked to have the same name
+                    JoinConstraintType::Using if pinned => JoinConstraint::Using(
+                        left_join_keys.into_iter().map(Column::from).collect(),
+                    ),
+                    JoinConstraintType::Using => {
+                        // The right table is fresh, so naming the columns after it guarantees no
+                        // other table already in the query has a column with the same name
+                        let shared_columns = (1..=*key_columns)
+                            .map(|i| {
+                                let name = if i == 1 {
+                                    format!("{right_table_name}_key")
+                                } else {
+                                    format!("{right_table_name}_key_{i}")
+                                };
+                                let shared_column = state.gen.ensure_shared_column_name(
+                                    &left_table_name,
+                                    &right_table_name,
+                                    ColumnName(name.into()),
+                                    key_type.clone(),
+                                );
+                                if *key_columns > 1 {
+                                    // See `TableSpec::join_key_columns`
+                                    let spec =
+                                        TableSpec::composite_join_key_spec(i - 1, *key_columns);
+                                    for table in [&left_table_name, &right_table_name] {
+                                        state
+                                            .gen
+                                            .table_mut(table)
+                                            .unwrap()
+                                            .set_column_generator_spec(
+                                                shared_column.clone(),
+                                                spec.clone(),
+                                            );
+                                    }
+                                }
+                                Column::from(shared_column)
+                            })
+                            .collect();
+                        JoinConstraint::Using(shared_columns)
+                    }
+                };
 
                 query.join.push(JoinClause {
                     operator: *operator,
-                    right: JoinRightSide::Table(TableExpr::from(Relation::from(
-                        right_table.name.clone(),
-                    ))),
-                    constraint: JoinConstraint::On(Expr::BinaryOp {
-                        op: BinaryOperator::Equal,
-                        lhs: Box::new(Expr::Column(Column {
-                            table: Some(left_table_name.clone().into()),
-                            ..left_join_key.into()
-                        })),
-                        rhs: Box::new(Expr::Column(Column {
-                            table: Some(right_table_name.clone().into()),
-                            ..right_join_key.into()
-                        })),
+                    right: JoinRightSide::Table(TableExpr {
+                        inner: TableExprInner::Table(Relation::from(right_table_name)),
+                        alias: right_alias,
+                        index_hint: None,
+                        sample: None,
                     }),
+                    constraint,
                 });
 
                 query.fields.push(FieldDefinitionExpr::Expr {
@@ -1778,23 +4181,87 @@ impl QueryOperation {
                 });
                 query.fields.push(FieldDefinitionExpr::Expr {
                     expr: Expr::Column(Column {
-                        table: Some(right_table_name.into()),
+                        table: Some(right_relation),
                         ..right_projected.into()
                     }),
                     alias: Some(state.fresh_alias()),
                 });
             }
 
-            QueryOperation::ProjectLiteral => {
+            QueryOperation::AntiJoin { overlap_percent } => {
+                let left_table = state.some_table_in_query_mut(query);
+                let left_table_name = left_table.name.clone();
+                let left_join_key = left_table.fresh_column_with_type(SqlType::Int(None));
+                left_table
+                    .set_column_generator_spec(left_join_key.clone(), ColumnGenerationSpec::Unique);
+
+                if query.tables.is_empty() {
+                    query
+                        .tables
+                        .push(TableExpr::from(Relation::from(left_table_name.clone())));
+                }
+
+                let right_table = state.fresh_table_mut();
+                let right_table_name = right_table.name.clone();
+                let right_join_key = right_table.fresh_column_with_type(SqlType::Int(None));
+                right_table.set_column_generator_spec(
+                    right_join_key.clone(),
+                    ColumnGenerationSpec::UniqueSparse {
+                        percent: (*overlap_percent).into(),
+                    },
+                );
+                state.anti_joined_tables.insert(right_table_name.clone());
+
+                let left_join_key = Column {
+                    table: Some(left_table_name.into()),
+                    ..left_join_key.into()
+                };
+                let right_join_key = Column {
+                    table: Some(right_table_name.clone().into()),
+                    ..right_join_key.into()
+                };
+
+                query.join.push(JoinClause {
+                    operator: JoinOperator::LeftJoin,
+                    right: JoinRightSide::Table(TableExpr::from(Relation::from(right_table_name))),
+                    constraint: JoinConstraint::On(Expr::BinaryOp {
+                        op: BinaryOperator::Equal,
+                        lhs: Box::new(Expr::Column(left_join_key.clone())),
+                        rhs: Box::new(Expr::Column(right_join_key.clone())),
+                    }),
+                });
+
+                query.fields.push(FieldDefinitionExpr::Expr {
+                    expr: Expr::Column(left_join_key),
+                    alias: Some(state.fresh_alias()),
+                });
+
+                and_where(
+                    query,
+                    Expr::BinaryOp {
+                        lhs: Box::new(Expr::Column(right_join_key)),
+                        op: BinaryOperator::Is,
+                        rhs: Box::new(Expr::Literal(Literal::Null)),
+                    },
+                );
+            }
+
+            QueryOperation::ProjectLiteral(literal_type) => {
                 let alias = state.fresh_alias();
                 query.fields.push(FieldDefinitionExpr::Expr {
-                    expr: Expr::Literal(Literal::Integer(1)),
+                    expr: literal_type.to_expr(),
                     alias: Some(alias),
                 });
             }
 
             QueryOperation::SingleParameter => {
-                let col = parameter_column_in_query(state, query);
+                let col = match state.placed_column(query) {
+                    Some(PlacementTarget { table, column }) => Column {
+                        table: Some(table.into()),
+                        ..column.into()
+                    },
+                    None => parameter_column_in_query(state, query),
+                };
                 and_where(
                     query,
                     Expr::BinaryOp {
@@ -1932,6 +4399,10 @@ impl QueryOperation {
                     }
                     BuiltinFunction::Addtime => add_builtin!(addtime(SqlType::Time, SqlType::Time)),
                     BuiltinFunction::Round => add_builtin!(round(SqlType::Real)),
+                    // Integer columns hold small values, so this will usually project a
+                    // non-printable character - which is still a valid result
+                    BuiltinFunction::Char => add_builtin!(char(SqlType::Int(None))),
+                    BuiltinFunction::Ascii => add_builtin!(ascii(SqlType::Text)),
                 }
             }
             QueryOperation::TopK { order_type, limit } => {
@@ -1957,7 +4428,7 @@ impl QueryOperation {
                 });
 
                 query.limit_clause = LimitClause::LimitOffset {
-                    limit: Some(LimitValue::Literal(Literal::Integer(*limit as _))),
+                    limit: Some(LimitValue::Literal(integer_literal(*limit))),
                     offset: None,
                 };
 
@@ -1968,6 +4439,88 @@ impl QueryOperation {
                     })
                 }
             }
+            QueryOperation::ParamTopK { order_type, limit } => {
+                let table = state.some_table_in_query_mut(query);
+
+                if query.tables.is_empty() {
+                    query
+                        .tables
+                        .push(TableExpr::from(Relation::from(table.name.clone())));
+                }
+
+                // Give each parameter value enough rows for the limit to be meaningful, and give
+                // every row a distinct timestamp so the rows returned for a key are well-defined
+                let param_column_name = table.fresh_column_with_type(SqlType::Int(None));
+                table.set_column_generator_spec(
+                    param_column_name.clone(),
+                    ColumnGenerationSpec::UniqueRepeated(
+                        limit
+                            .checked_mul(PARAM_TOPK_ROWS_PER_LIMIT)
+                            .and_then(|n| n.try_into().ok())
+                            .unwrap_or(u32::MAX),
+                    ),
+                );
+                let order_column_name = table.fresh_column_with_type(SqlType::Timestamp);
+                table.set_column_generator_spec(
+                    order_column_name.clone(),
+                    ColumnGenerationSpec::Unique,
+                );
+
+                let table_name = table.name.clone();
+                let param_column = Column {
+                    table: Some(table_name.clone().into()),
+                    ..param_column_name.clone().into()
+                };
+                let order_column = Column {
+                    table: Some(table_name.clone().into()),
+                    ..order_column_name.into()
+                };
+
+                and_where(
+                    query,
+                    Expr::BinaryOp {
+                        op: BinaryOperator::Equal,
+                        lhs: Box::new(Expr::Column(param_column)),
+                        rhs: Box::new(Expr::Literal(Literal::Placeholder(
+                            state.next_placeholder(),
+                        ))),
+                    },
+                );
+                // The first value generated for the parameter column owns the first
+                // `limit * PARAM_TOPK_ROWS_PER_LIMIT` rows of the table, so make sure they're all
+                // generated
+                state.require_rows(
+                    table_name.clone(),
+                    limit
+                        .checked_mul(PARAM_TOPK_ROWS_PER_LIMIT)
+                        .and_then(|n| n.try_into().ok())
+                        .unwrap_or(usize::MAX),
+                );
+                state.add_parameter_with_value(
+                    table_name,
+                    param_column_name,
+                    unique_value_of_type(&SqlType::Int(None), 0),
+                );
+
+                query.order = Some(OrderClause {
+                    order_by: vec![OrderBy {
+                        field: FieldReference::Expr(Expr::Column(order_column.clone())),
+                        order_type: Some(*order_type),
+                        null_order: None,
+                    }],
+                });
+                query.limit_clause = LimitClause::LimitOffset {
+                    limit: Some(LimitValue::Literal(integer_literal(*limit))),
+                    offset: None,
+                };
+
+                if query.distinct {
+                    query.fields.push(FieldDefinitionExpr::Expr {
+                        expr: Expr::Column(order_column),
+                        alias: Some(state.fresh_alias()),
+                    })
+                }
+            }
             QueryOperation::Paginate {
                 order_type,
                 limit,
@@ -1996,15 +4549,16 @@ impl QueryOperation {
 
                 // Since we are setting both fields, check first to see what kind of syntax
                 // we were using.
+                let offset = integer_literal(limit.saturating_mul(*page_number));
                 if matches!(query.limit_clause, LimitClause::OffsetCommaLimit { .. }) {
                     query.limit_clause = LimitClause::OffsetCommaLimit {
-                        limit: LimitValue::Literal(Literal::Integer(*limit as _)),
-                        offset: Literal::Integer((*limit * *page_number) as _),
+                        limit: LimitValue::Literal(integer_literal(*limit)),
+                        offset,
                     }
                 } else {
                     query.limit_clause = LimitClause::LimitOffset {
-                        limit: Some(LimitValue::Literal(Literal::Integer(*limit as _))),
-                        offset: Some(Literal::Integer((*limit * *page_number) as _)),
+                        limit: Some(LimitValue::Literal(integer_literal(*limit))),
+                        offset: Some(offset),
                     };
                 }
 
@@ -2015,72 +4569,331 @@ impl QueryOperation {
                     })
                 }
             }
-            // Subqueries are turned into QuerySeed::subqueries as part of
-            // GeneratorOps::into_query_seeds
-            QueryOperation::Subquery(_) => {}
-        }
-    }
-
-    /// Returns an iterator over all permutations of length 1..`max_depth` [`QueryOperation`]s.
-    pub fn permute(max_depth: usize) -> impl Iterator<Item = Vec<&'static QueryOperation>> {
-        (1..=max_depth).flat_map(|depth| ALL_OPERATIONS.iter().combinations(depth))
-    }
-}
+            QueryOperation::GroupByExpression { bucket } => {
+                let has_aggregate = query_has_aggregate(query);
+                let table = state.some_table_in_query_mut(query);
 
-/// Representation of a subset of query operations
-///
-/// Operations can be converted from a user-supplied string using [`FromStr::from_str`], which
-/// supports the following speccifications:
-///
-/// | Specification                           | Meaning                                 |
-/// |-----------------------------------------|-----------------------------------------|
-/// | aggregates                              | All [`AggregateType`]s                  |
-/// | count                                   | COUNT aggregates                        |
-/// | count_distinct                          | COUNT(DISTINCT) aggregates              |
-/// | sum                                     | SUM aggregates                          |
-/// | sum_distinct                            | SUM(DISTINCT) aggregates                |
-/// | avg                                     | AVG aggregates                          |
-/// | avg_distinct                            | AVG(DISTINCT) aggregates                |
-/// | group_concat                            | GROUP_CONCAT aggregates                 |
-/// | max                                     | MAX aggregates                          |
-/// | min                                     | MIN aggregates                          |
-/// | filters                                 | All constant-valued [`Filter`]s         |
-/// | equal_filters                           | Constant-valued `=` filters             |
-/// | not_equal_filters                       | Constant-valued `!=` filters            |
-/// | greater_filters                         | Constant-valued `>` filters             |
-/// | greater_or_equal_filters                | Constant-valued `>=` filters            |
-/// | less_filters                            | Constant-valued `<` filters             |
-/// | less_or_equal_filters                   | Constant-valued `<=` filters            |
-/// | between_filters                         | Constant-valued `BETWEEN` filters       |
-/// | is_null_filters                         | IS NULL and IS NOT NULL filters         |
-/// | distinct                                | `SELECT DISTINCT`                       |
-/// | joins                                   | Joins, with all [`JoinOperator`]s       |
-/// | inner_join                              | `INNER JOIN`s                           |
-/// | left_join                               | `LEFT JOIN`s                            |
-/// | single_parameter / single_param / param | A single query parameter                |
-/// | range_param                             | A range query parameter                 |
-/// | multiple_parameters / params            | Multiple query parameters               |
-/// | multiple_range_params                   | Multiple range query parameters         |
-/// | in_parameter                            | IN with multiple query parameters       |
-/// | project_literal                         | A projected literal value               |
-/// | project_builtin                         | Project a built-in function             |
-/// | subqueries                              | All subqueries                          |
-/// | cte                                     | CTEs (WITH statements)                  |
-/// | join_subquery                           | JOIN to a subquery directly             |
-/// | topk                                    | ORDER BY combined with LIMIT            |
-/// | paginate                                | ORDER BY combined with LIMIT and OFFSET |
-/// | exists                                  | EXISTS with a subquery                  |
-#[repr(transparent)]
-#[derive(Debug, PartialEq, Eq, Clone, From, Into)]
-pub struct Operations(pub Vec<QueryOperation>);
+                if query.tables.is_empty() {
+                    query
+                        .tables
+                        .push(TableExpr::from(Relation::from(table.name.clone())));
+                }
 
-impl FromStr for Operations {
+                let column_name = table.fresh_column_with_type(SqlType::Timestamp);
+                // Spread the timestamps over a small number of distinct values, so that groups
+                // contain more than one row
+                table.set_column_generator_spec(
+                    column_name.clone(),
+                    ColumnGenerationSpec::WithCardinality {
+                        inner: Box::new(ColumnGenerationSpec::Random),
+                        distinct: TIME_BUCKET_DISTINCT_TIMESTAMPS,
+                    },
+                );
+                let expr = bucket.to_expr(Column {
+                    table: Some(table.name.clone().into()),
+                    ..column_name.into()
+                });
+
+                query.fields.push(FieldDefinitionExpr::Expr {
+                    expr: expr.clone(),
+                    alias: Some(state.fresh_alias()),
+                });
+                if !has_aggregate {
+                    query.fields.push(FieldDefinitionExpr::Expr {
+                        expr: Expr::Call(FunctionExpr::CountStar),
+                        alias: Some(state.fresh_alias()),
+                    });
+                }
+
+                query
+                    .group_by
+                    .get_or_insert_with(Default::default)
+                    .fields
+                    .push(FieldReference::Expr(expr));
+            }
+            QueryOperation::Rollup { num_group_cols } => {
+                let table = state.some_table_in_query_mut(query);
+
+                if query.tables.is_empty() {
+                    query
+                        .tables
+                        .push(TableExpr::from(Relation::from(table.name.clone())));
+                }
+
+                let columns = (0..*num_group_cols)
+                    .map(|_| Column {
+                        table: Some(table.name.clone().into()),
+                        ..table.fresh_column().into()
+                    })
+                    .collect::<Vec<_>>();
+
+                for column in &columns {
+                    query.fields.push(FieldDefinitionExpr::Expr {
+                        expr: Expr::Column(column.clone()),
+                        alias: Some(state.fresh_alias()),
+                    });
+                }
+                query.fields.push(FieldDefinitionExpr::Expr {
+                    expr: Expr::Call(FunctionExpr::CountStar),
+                    alias: Some(state.fresh_alias()),
+                });
+
+                let group_by = query.group_by.get_or_insert_with(Default::default);
+                group_by.fields.extend(
+                    columns
+                        .into_iter()
+                        .map(|column| FieldReference::Expr(Expr::Column(column))),
+                );
+                group_by.with_rollup = true;
+            }
+            QueryOperation::JsonPath { operator } => {
+                let alias = state.fresh_alias();
+                let table = state.some_table_in_query_mut(query);
+
+                if query.tables.is_empty() {
+                    query
+                        .tables
+                        .push(TableExpr::from(Relation::from(table.name.clone())));
+                }
+
+                let column_name = table.fresh_column_with_type(SqlType::Text);
+                table.set_column_generator_spec(
+                    column_name.clone(),
+                    ColumnGenerationSpec::RandomJson {
+                        json_probability: 1.0,
+                    },
+                );
+                table.expect_value(column_name.clone(), "{}".into());
+                let planted_value = match operator {
+                    BinaryOperator::Less => JSON_PATH_VALUE - 1,
+                    BinaryOperator::Greater | BinaryOperator::NotEqual => JSON_PATH_VALUE + 1,
+                    _ => JSON_PATH_VALUE,
+                };
+                table.expect_value(
+                    column_name.clone(),
+                    format!(r#"{{"value": {planted_value}}}"#).into(),
+                );
+
+                let column = Column {
+                    table: Some(table.name.clone().into()),
+                    ..column_name.into()
+                };
+                query.fields.push(FieldDefinitionExpr::Expr {
+                    expr: Expr::Column(column.clone()),
+                    alias: Some(alias),
+                });
+                and_where(
+                    query,
+                    Expr::BinaryOp {
+                        op: *operator,
+                        lhs: Box::new(Expr::Call(FunctionExpr::Call {
+                            name: "JSON_EXTRACT".into(),
+                            arguments: vec![
+                                Expr::Column(column),
+                                Expr::Literal(Literal::String("$.value".into())),
+                            ],
+                        })),
+                        rhs: Box::new(Expr::Literal(Literal::Integer(JSON_PATH_VALUE))),
+                    },
+                );
+            }
+            QueryOperation::ParameterWithLiteralConstraint {
+                literal_op,
+                contradictory,
+            } => {
+                let table = state.some_table_in_query_mut(query);
+
+                if query.tables.is_empty() {
+                    query
+                        .tables
+                        .push(TableExpr::from(Relation::from(table.name.clone())));
+                }
+
+                let column_name = table.fresh_column_with_type(SqlType::Int(None));
+                table.expect_value(column_name.clone(), DfValue::from(PARAM_WITH_LITERAL_KEY));
+                let table_name = table.name.clone();
+                let column = Column {
+                    table: Some(table_name.clone().into()),
+                    ..column_name.clone().into()
+                };
+
+                and_where(
+                    query,
+                    Expr::BinaryOp {
+                        op: BinaryOperator::Equal,
+                        lhs: Box::new(Expr::Column(column.clone())),
+                        rhs: Box::new(Expr::Literal(Literal::Placeholder(
+                            state.next_placeholder(),
+                        ))),
+                    },
+                );
+                state.add_parameter_with_value(table_name, column_name, PARAM_WITH_LITERAL_KEY);
+                let Some(literal) = param_with_literal_value(*literal_op, *contradictory) else {
+                    tracing::warn!(
+                        %literal_op,
+                        "Not adding literal constraint with non-comparison operator"
+                    );
+                    return;
+                };
+                and_where(
+                    query,
+                    Expr::BinaryOp {
+                        op: *literal_op,
+                        lhs: Box::new(Expr::Column(column)),
+                        rhs: Box::new(Expr::Literal(Literal::Integer(literal))),
+                    },
+                );
+                if *contradictory {
+                    state.expects_empty_results = true;
+                }
+            }
+            QueryOperation::WindowRankFunction {
+                func,
+                partition_by_type,
+                order_by_type,
+            } => {
+                let table = state.some_table_in_query_mut(query);
+
+                if query.tables.is_empty() {
+                    query
+                        .tables
+                        .push(TableExpr::from(Relation::from(table.name.clone())));
+                }
+
+                let mut column_with_distinct_values = |col_type: &SqlType, distinct: u64| {
+                    let column_name = table.fresh_column_with_type(col_type.clone());
+                    table.set_column_generator_spec(
+                        column_name.clone(),
+                        ColumnGenerationSpec::WithCardinality {
+                            inner: Box::new(ColumnGenerationSpec::Random),
+                            distinct,
+                        },
+                    );
+                    Column {
+                        table: Some(table.name.clone().into()),
+                        ..column_name.into()
+                    }
+                };
+                let partition_col = column_with_distinct_values(
+                    partition_by_type,
+                    WINDOW_PARTITION_DISTINCT_VALUES,
+                );
+                let order_col =
+                    column_with_distinct_values(order_by_type, WINDOW_ORDER_DISTINCT_VALUES);
+
+                for column in [&partition_col, &order_col] {
+                    query.fields.push(FieldDefinitionExpr::Expr {
+                        expr: Expr::Column(column.clone()),
+                        alias: Some(state.fresh_alias()),
+                    });
+                }
+                query.fields.push(FieldDefinitionExpr::Expr {
+                    expr: Expr::Call(FunctionExpr::Window {
+                        name: func.name().into(),
+                        arguments: func.arguments(),
+                        partition_by: vec![Expr::Column(partition_col)],
+                        order_by: Some(OrderClause {
+                            order_by: vec![OrderBy {
+                                field: FieldReference::Expr(Expr::Column(order_col)),
+                                order_type: None,
+                                null_order: None,
+                            }],
+                        }),
+                    }),
+                    alias: Some(state.fresh_alias()),
+                });
+            }
+            // Subqueries are turned into QuerySeed::subqueries as part of
+            // GeneratorOps::into_query_seeds
+            QueryOperation::Subquery(_) => {}
+        }
+    }
+
+    /// Returns an iterator over all permutations of length 1..`max_depth` [`QueryOperation`]s.
+    pub fn permute(max_depth: usize) -> impl Iterator<Item = Vec<&'static QueryOperation>> {
+        (1..=max_depth).flat_map(|depth| ALL_OPERATIONS.iter().combinations(depth))
+    }
+}
+
+/// Representation of a subset of query operations
+///
+/// Operations can be converted from a user-supplied string using [`FromStr::from_str`], which
+/// supports the following speccifications:
+///
+/// | Specification                           | Meaning                                 |
+/// |-----------------------------------------|-----------------------------------------|
+/// | aggregates                              | All [`AggregateType`]s                  |
+/// | count                                   | COUNT aggregates                        |
+/// | count_distinct                          | COUNT(DISTINCT) aggregates              |
+/// | sum                                     | SUM aggregates                          |
+/// | sum_distinct                            | SUM(DISTINCT) aggregates                |
+/// | avg                                     | AVG aggregates                          |
+/// | avg_distinct                            | AVG(DISTINCT) aggregates                |
+/// | group_concat                            | GROUP_CONCAT aggregates                 |
+/// | max                                     | MAX aggregates                          |
+/// | min                                     | MIN aggregates                          |
+/// | filters                                 | All constant-valued [`Filter`]s         |
+/// | equal_filters                           | Constant-valued `=` filters             |
+/// | not_equal_filters                       | Constant-valued `!=` filters            |
+/// | greater_filters                         | Constant-valued `>` filters             |
+/// | greater_or_equal_filters                | Constant-valued `>=` filters            |
+/// | less_filters                            | Constant-valued `<` filters             |
+/// | less_or_equal_filters                   | Constant-valued `<=` filters            |
+/// | between_filters                         | Constant-valued `BETWEEN` filters       |
+/// | is_null_filters                         | IS NULL and IS NOT NULL filters         |
+/// | coercion_filters                        | Comparisons requiring type coercion     |
+/// | subquery_filters                        | `=` filters on a CTE/subquery's column  |
+/// | distinct                                | `SELECT DISTINCT`                       |
+/// | joins                                   | Joins, with all [`JoinOperator`]s       |
+/// | inner_join                              | `INNER JOIN`s                           |
+/// | inner_join_using                        | `INNER JOIN`s with a `USING` clause     |
+/// | left_join                               | `LEFT JOIN`s                            |
+/// | composite_join                          | `INNER JOIN`s on two key columns        |
+/// | anti_join                               | LEFT JOIN ... WHERE right.key IS NULL   |
+/// | single_parameter / single_param / param | A single query parameter                |
+/// | range_param                             | A range query parameter                 |
+/// | multiple_parameters / params            | Multiple query parameters               |
+/// | multiple_range_params                   | Multiple range query parameters         |
+/// | in_parameter                            | IN with multiple query parameters       |
+/// | project_literal                         | Projected literal values of every type  |
+/// | project_builtin                         | Project a built-in function             |
+/// | char_functions                          | Project CHAR() and ASCII() calls        |
+/// | subqueries                              | All subqueries                          |
+/// | cte                                     | CTEs (WITH statements)                  |
+/// | cte_reuse                               | A CTE joined against twice              |
+/// | join_subquery                           | JOIN to a subquery directly             |
+/// | topk                                    | ORDER BY combined with LIMIT            |
+/// | paginate                                | ORDER BY combined with LIMIT and OFFSET |
+/// | exists                                  | EXISTS with a subquery                  |
+/// | rollup                                  | GROUP BY ... WITH ROLLUP, with COUNT(*) |
+/// | group_by_time                           | GROUP BY date, month, or year buckets   |
+/// | json_path                               | Comparisons on JSON_EXTRACT(col, path)  |
+///
+/// Any specification may be followed by a parenthesized list of arguments overriding the fields of
+/// the operations it names, eg `topk(limit=100,desc)`, `count(distinct)`, `sum(type=bigint)`,
+/// `inner_join(key_type=text,key_columns=3)`, or `filters(op=greater)`.
+///
+/// Joins with a `USING` clause are only generated when asked for with `inner_join_using`, since
+/// ReadySet doesn't support them yet.
+#[repr(transparent)]
+#[derive(Debug, PartialEq, Eq, Clone, From, Into)]
+pub struct Operations(pub Vec<QueryOperation>);
+
+impl FromStr for Operations {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let spec = OperationSpec::parse(s)?;
+        spec.apply(Self::named(spec.name)?)
+    }
+}
+
+impl Operations {
+    /// Returns the operations named by `name`, with the default values for all their fields
+    fn named(name: &str) -> anyhow::Result<Self> {
         use QueryOperation::*;
 
-        match s {
+        match name {
             "aggregates" => Ok(ALL_AGGREGATE_TYPES
                 .iter()
                 .cloned()
@@ -2125,10 +4938,28 @@ impl FromStr for Operations {
                 column_type: SqlType::Int(None),
             })]
             .into()),
+            "bit_and" => Ok(vec![ColumnAggregate(AggregateType::BitAnd {
+                column_type: SqlType::Int(None),
+            })]
+            .into()),
+            "bit_or" => Ok(vec![ColumnAggregate(AggregateType::BitOr {
+                column_type: SqlType::Int(None),
+            })]
+            .into()),
+            "bit_xor" => Ok(vec![ColumnAggregate(AggregateType::BitXor {
+                column_type: SqlType::Int(None),
+            })]
+            .into()),
             "filters" => Ok(ALL_FILTERS.iter().cloned().map(Filter).collect()),
             "equal_filters" => Ok(crate::Filter::all_with_operator(BinaryOperator::Equal)
                 .map(Filter)
                 .collect()),
+            "subquery_filters" => Ok(crate::Filter::all_with_operator_and_target(
+                BinaryOperator::Equal,
+                FilterTarget::Subquery,
+            )
+            .map(Filter)
+            .collect()),
             "not_equal_filters" => Ok(crate::Filter::all_with_operator(BinaryOperator::NotEqual)
                 .map(Filter)
                 .collect()),
@@ -2153,8 +4984,8 @@ impl FromStr for Operations {
                 .map(|(extend_where_with, operation)| crate::Filter {
                     extend_where_with,
                     operation,
-
                     column_type: SqlType::Int(None),
+                    filter_target: FilterTarget::Base,
                 })
                 .map(Filter)
                 .collect()),
@@ -2167,28 +4998,90 @@ impl FromStr for Operations {
                     extend_where_with,
                     operation,
                     column_type: SqlType::Int(None),
+                    filter_target: FilterTarget::Base,
+                })
+                .map(Filter)
+                .collect()),
+            "coercion_filters" => Ok(coercion::COERCIBLE_PAIRS
+                .iter()
+                .cartesian_product(LogicalOp::iter())
+                .filter_map(|((column_type, rhs_type), extend_where_with)| {
+                    crate::Filter::coerced_comparison(
+                        column_type.clone(),
+                        rhs_type.clone(),
+                        extend_where_with,
+                    )
                 })
                 .map(Filter)
                 .collect()),
             "distinct" => Ok(vec![Distinct].into()),
-            "joins" => Ok(JOIN_OPERATORS.iter().cloned().map(Join).collect()),
-            "inner_join" => Ok(vec![Join(JoinOperator::InnerJoin)].into()),
-            "left_join" => Ok(vec![Join(JoinOperator::LeftJoin)].into()),
+            "joins" => Ok(JOIN_OPERATORS
+                .iter()
+                .map(|&operator| Join {
+                    operator,
+                    constraint: JoinConstraintType::On,
+                    key_type: SqlType::Int(None),
+                    key_columns: 1,
+                })
+                .collect()),
+            "inner_join" => Ok(vec![Join {
+                operator: JoinOperator::InnerJoin,
+                constraint: JoinConstraintType::On,
+                key_type: SqlType::Int(None),
+                key_columns: 1,
+            }]
+            .into()),
+            "inner_join_using" => Ok(vec![Join {
+                operator: JoinOperator::InnerJoin,
+                constraint: JoinConstraintType::Using,
+                key_type: SqlType::Int(None),
+                key_columns: 1,
+            }]
+            .into()),
+            "left_join" => Ok(vec![Join {
+                operator: JoinOperator::LeftJoin,
+                constraint: JoinConstraintType::On,
+                key_type: SqlType::Int(None),
+                key_columns: 1,
+            }]
+            .into()),
+            "composite_join" => Ok(vec![Join {
+                operator: JoinOperator::InnerJoin,
+                constraint: JoinConstraintType::On,
+                key_type: SqlType::Int(None),
+                key_columns: 2,
+            }]
+            .into()),
+            "anti_join" => Ok(vec![AntiJoin {
+                overlap_percent: 50,
+            }]
+            .into()),
             "single_parameter" | "single_param" | "param" => Ok(vec![SingleParameter].into()),
             "multiple_parameters" | "params" => Ok(vec![MultipleParameters].into()),
             "range_param" => Ok(vec![RangeParameter].into()),
             "multiple_range_params" => Ok(vec![MultipleRangeParameters].into()),
             "in_parameter" => Ok(vec![InParameter { num_values: 3 }].into()),
-            "project_literal" => Ok(vec![ProjectLiteral].into()),
+            "project_literal" => Ok(LiteralType::iter().map(ProjectLiteral).collect()),
             "project_builtin" => Ok(BuiltinFunction::iter()
+                .filter(|f| !f.is_char_function())
                 .map(ProjectBuiltinFunction)
                 .collect()),
+            "char_functions" => Ok(vec![
+                ProjectBuiltinFunction(BuiltinFunction::Char),
+                ProjectBuiltinFunction(BuiltinFunction::Ascii),
+            ]
+            .into()),
             "subqueries" => Ok(ALL_SUBQUERY_POSITIONS
                 .iter()
                 .cloned()
                 .map(Subquery)
                 .collect()),
             "cte" => Ok(vec![Subquery(SubqueryPosition::Cte(JoinOperator::InnerJoin))].into()),
+            "cte_reuse" => Ok(CTE_REUSE_SUBQUERY_POSITIONS
+                .iter()
+                .cloned()
+                .map(Subquery)
+                .collect()),
             "join_subquery" => {
                 Ok(vec![Subquery(SubqueryPosition::Join(JoinOperator::InnerJoin))].into())
             }
@@ -2201,6 +5094,41 @@ impl FromStr for Operations {
             .into()),
             "topk" => Ok(ALL_TOPK.to_vec().into()),
             "paginate" => Ok(ALL_PAGINATE.to_vec().into()),
+            "feed_query" | "param_topk" => Ok(vec![ParamTopK {
+                order_type: OrderType::OrderDescending,
+                limit: DEFAULT_LIMIT,
+            }]
+            .into()),
+            "rollup" => Ok(vec![Rollup { num_group_cols: 2 }].into()),
+            "window_rank" => Ok(ALL_RANK_FUNCTIONS
+                .iter()
+                .map(|&func| WindowRankFunction {
+                    func,
+                    partition_by_type: SqlType::Int(None),
+                    order_by_type: SqlType::Int(None),
+                })
+                .collect()),
+            "group_by_time" => Ok(TimeBucket::iter()
+                .map(|bucket| GroupByExpression { bucket })
+                .collect()),
+            "json_path" => Ok(COMPARISON_OPS
+                .iter()
+                .map(|&operator| JsonPath { operator })
+                .collect()),
+            "param_plus_literal" => Ok(COMPARISON_OPS
+                .iter()
+                .map(|&literal_op| ParameterWithLiteralConstraint {
+                    literal_op,
+                    contradictory: false,
+                })
+                .collect()),
+            "param_contradiction" => Ok(COMPARISON_OPS
+                .iter()
+                .map(|&literal_op| ParameterWithLiteralConstraint {
+                    literal_op,
+                    contradictory: true,
+                })
+                .collect()),
             s => Err(anyhow!("unknown query operation: {}", s)),
         }
     }
@@ -2274,7 +5202,9 @@ impl Arbitrary for Operations {
                     QueryOperation::SingleParameter
                     | QueryOperation::MultipleParameters
                     | QueryOperation::RangeParameter
-                    | QueryOperation::MultipleRangeParameters => {
+                    | QueryOperation::MultipleRangeParameters
+                    | QueryOperation::ParameterWithLiteralConstraint { .. }
+                    | QueryOperation::ParamTopK { .. } => {
                         if or_filter_found {
                             false
                         } else {
@@ -2305,7 +5235,8 @@ impl Arbitrary for Operations {
 /// line.
 ///
 /// `OperationList` can be converted from a (user-supplied) string using [`FromStr::from_str`],
-/// using a comma-separated list of [`Operations`]
+/// using a comma-separated list of [`Operations`]. Commas within the parenthesized arguments to an
+/// operation don't separate operations.
 #[repr(transparent)]
 #[derive(Clone)]
 pub struct OperationList(pub Vec<Operations>);
@@ -2314,7 +5245,8 @@ impl FromStr for OperationList {
     type Err = anyhow::Error;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         Ok(Self(
-            s.split(',')
+            split_operation_list(s)?
+                .into_iter()
                 .map(Operations::from_str)
                 .collect::<Result<Vec<_>, _>>()?,
         ))
@@ -2322,6 +5254,31 @@ impl FromStr for OperationList {
 }
 
 impl OperationList {
+    /// Remove any [`QueryOperation`] which also appears earlier within the same [`Operations`],
+    /// then remove any [`Operations`] left empty, logging a warning if any duplicates were found.
+    ///
+    /// Duplicates are only removed within each [`Operations`], since the same operation appearing
+    /// in two different [`Operations`] is meaningful to [`Self::permute`], and removing every
+    /// operation from one of them would leave [`Self::permute`] with nothing to pick from it.
+    pub fn dedup(&mut self) {
+        let mut duplicates = vec![];
+        for Operations(ops) in &mut self.0 {
+            let mut seen = HashSet::new();
+            ops.retain(|op| {
+                let first = seen.insert(op.clone());
+                if !first {
+                    duplicates.push(op.clone());
+                }
+                first
+            });
+        }
+        self.0.retain(|Operations(ops)| !ops.is_empty());
+
+        if !duplicates.is_empty() {
+            tracing::warn!(?duplicates, "Removed duplicate query operations");
+        }
+    }
+
     /// Generate a set of permutations of all the sets of [`QueryOperation`]s represented by the
     /// [`Operations`] in this `OperationList`.
     pub fn permute(&self) -> impl Iterator<Item = Vec<QueryOperation>> + '_ {
@@ -2339,7 +5296,7 @@ impl From<Vec<Vec<QueryOperation>>> for OperationList {
 }
 
 /// A specification for a subquery included in a query
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Subquery {
     /// Where does the subquery appear in the query?
     position: SubqueryPosition,
@@ -2348,54 +5305,102 @@ pub struct Subquery {
     seed: QuerySeed,
 }
 
+/// Returns the name under which `subquery` projects `column` of `table`, adding it to the fields
+/// of the subquery with a fresh alias if it isn't already projected
+fn project_subquery_column(
+    state: &mut QueryState<'_>,
+    subquery: &mut SelectStatement,
+    table: &TableName,
+    column: &ColumnName,
+) -> SqlIdentifier {
+    subquery
+        .fields
+        .iter()
+        // First, see if we're already projecting the column
+        .find_map(|f| match f {
+            FieldDefinitionExpr::Expr {
+                expr:
+                    Expr::Column(Column {
+                        name,
+                        table:
+                            Some(Relation {
+                                name: table_name, ..
+                            }),
+                    }),
+                alias,
+            } if *name == column.0 && *table_name == table.0 => {
+                Some(alias.clone().unwrap_or_else(|| name.clone()))
+            }
+            _ => None,
+        })
+        // If we don't find it, add it to the fields with a fresh alias, and use that alias
+        .unwrap_or_else(|| {
+            let alias = state.fresh_alias();
+            let col = Column {
+                name: column.clone().into(),
+                table: Some(table.clone().into()),
+            };
+            subquery.fields.push(FieldDefinitionExpr::Expr {
+                expr: Expr::Column(col.clone()),
+                alias: Some(alias.clone()),
+            });
+
+            if let Some(gb) = &mut subquery.group_by {
+                gb.fields.push(FieldReference::Expr(Expr::Column(col)))
+            }
+
+            alias
+        })
+}
+
+/// Returns an `ON` constraint joining `left_column` of `left_table` to `right_column` of
+/// `right_table`
+fn join_on(
+    left_table: TableName,
+    left_column: ColumnName,
+    right_table: SqlIdentifier,
+    right_column: SqlIdentifier,
+) -> JoinConstraint {
+    JoinConstraint::On(Expr::BinaryOp {
+        lhs: Box::new(Expr::Column(Column {
+            name: left_column.into(),
+            table: Some(left_table.into()),
+        })),
+        op: BinaryOperator::Equal,
+        rhs: Box::new(Expr::Column(Column {
+            name: right_column,
+            table: Some(right_table.into()),
+        })),
+    })
+}
+
 impl Subquery {
     fn add_to_query(self, state: &mut QueryState<'_>, query: &mut SelectStatement) {
-        // perturb the generator to make a new table, so that we don't get the same table in the
-        // subquery that we got in the outer query
-        state.fresh_table_mut();
-        let mut subquery = self.seed.generate(state);
-        let right_table = state.some_table_in_query_mut(&mut subquery);
-        let right_table_name = right_table.name.clone();
-        let right_join_col = right_table.some_column_with_type(SqlType::Int(None));
-        let right_join_key = subquery
-            .fields
+        let reused = matches!(self.position, SubqueryPosition::CteReusedTwice(_));
+        let (mut subquery, right_table_name, right_join_cols) =
+            state.scoped_for_subquery(|state| {
+                // perturb the generator to make a new table, so that we don't get the same table
+                // in the subquery that we got in the outer query
+                state.fresh_table_mut();
+                let mut subquery = self.seed.generate(state, None);
+                let right_table = state.some_table_in_query_mut(&mut subquery);
+                let right_table_name = right_table.name.clone();
+                let right_join_col = right_table.some_column_with_type(SqlType::Int(None));
+                let mut right_join_cols = vec![right_join_col];
+                if reused {
+                    // Each reference to the CTE joins on a different column
+                    right_join_cols.push(right_table.some_column_with_type_different_than(
+                        SqlType::Int(None),
+                        &right_join_cols[0],
+                    ));
+                }
+                (subquery, right_table_name, right_join_cols)
+            });
+        let mut right_join_keys = right_join_cols
             .iter()
-            // First, see if we're already projecting the join column we picked in the subquery
-            .find_map(|f| match f {
-                FieldDefinitionExpr::Expr {
-                    expr:
-                        Expr::Column(Column {
-                            name,
-                            table:
-                                Some(Relation {
-                                    name: table_name, ..
-                                }),
-                        }),
-                    alias,
-                } if *name == right_join_col.0 && *table_name == right_table_name.0 => {
-                    Some(alias.clone().unwrap_or_else(|| name.clone()))
-                }
-                _ => None,
-            })
-            // If we don't find it, add it to the fields with a fresh alias, and use that alias as
-            // our join column
-            .unwrap_or_else(|| {
-                let alias = state.fresh_alias();
-                let col = Column {
-                    name: right_join_col.clone().into(),
-                    table: Some(right_table_name.into()),
-                };
-                subquery.fields.push(FieldDefinitionExpr::Expr {
-                    expr: Expr::Column(col.clone()),
-                    alias: Some(alias.clone()),
-                });
-
-                if let Some(gb) = &mut subquery.group_by {
-                    gb.fields.push(FieldReference::Expr(Expr::Column(col)))
-                }
-
-                alias
-            });
+            .map(|col| project_subquery_column(state, &mut subquery, &right_table_name, col))
+            .collect::<Vec<_>>();
+        let right_join_key = right_join_keys.remove(0);
 
         let left_table = state.some_table_in_query_mut(query);
         let left_table_name = left_table.name.clone();
@@ -2421,9 +5426,58 @@ impl Subquery {
                     inner: TableExprInner::Subquery(Box::new(subquery)),
                     alias: Some(subquery_name.clone()),
                     index_hint: None,
+                    sample: None,
                 }),
                 operator,
             ),
+            SubqueryPosition::CteReusedTwice(operator) => {
+                let second_left_join_key = state
+                    .gen
+                    .table_mut(&left_table_name)
+                    .unwrap()
+                    .some_column_with_type_different_than(SqlType::Int(None), &left_join_key);
+                query.ctes.push(CommonTableExpr {
+                    name: subquery_name.clone(),
+                    statement: subquery,
+                });
+
+                for (left_join_key, right_join_key) in [
+                    (left_join_key, right_join_key),
+                    (second_left_join_key, right_join_keys.remove(0)),
+                ] {
+                    let alias = state.fresh_alias();
+                    query.join.push(JoinClause {
+                        operator,
+                        right: JoinRightSide::Table(TableExpr {
+                            inner: TableExprInner::Table(Relation {
+                                name: subquery_name.clone(),
+                                schema: None,
+                            }),
+                            alias: Some(alias.clone()),
+                            index_hint: None,
+                            sample: None,
+                        }),
+                        constraint: join_on(
+                            left_table_name.clone(),
+                            left_join_key,
+                            alias.clone(),
+                            right_join_key.clone(),
+                        ),
+                    });
+
+                    // Project the join key of each reference separately. If the query is
+                    // aggregated, these will be added to the GROUP BY along with every other
+                    // projected column.
+                    query.fields.push(FieldDefinitionExpr::Expr {
+                        expr: Expr::Column(Column {
+                            name: right_join_key,
+                            table: Some(alias.into()),
+                        }),
+                        alias: Some(state.fresh_alias()),
+                    });
+                }
+                return;
+            }
 
             SubqueryPosition::Exists { correlated } => {
                 if let Some(col_type) = correlated {
@@ -2479,23 +5533,18 @@ impl Subquery {
         query.join.push(JoinClause {
             operator,
             right: join_rhs,
-            constraint: JoinConstraint::On(Expr::BinaryOp {
-                lhs: Box::new(Expr::Column(Column {
-                    name: left_join_key.into(),
-                    table: Some(left_table_name.into()),
-                })),
-                op: BinaryOperator::Equal,
-                rhs: Box::new(Expr::Column(Column {
-                    name: right_join_key,
-                    table: Some(subquery_name.into()),
-                })),
-            }),
+            constraint: join_on(
+                left_table_name,
+                left_join_key,
+                subquery_name,
+                right_join_key,
+            ),
         })
     }
 }
 
 /// A specification for generating an individual query
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct QuerySeed {
     /// The set of operations to include in the query
     operations: Vec<QueryOperation>,
@@ -2547,17 +5596,52 @@ impl QuerySeed {
         }
     }
 
-    fn generate(self, state: &mut QueryState) -> SelectStatement {
+    /// Generate a query from this seed, pinning operations to the placements in `plan` if given.
+    ///
+    /// The plan must already have been validated against this seed with
+    /// [`PlacementPlan::validate`].
+    fn generate(self, state: &mut QueryState, plan: Option<&PlacementPlan>) -> SelectStatement {
         let mut query = SelectStatement::default();
 
-        for op in self.operations {
-            op.add_to_query(state, &mut query);
+        // Filters on the columns of subqueries can only be added once those subqueries have been
+        // added to the query
+        let (subquery_filters, operations): (Vec<_>, Vec<_>) = self
+            .operations
+            .into_iter()
+            .enumerate()
+            .partition(|(_, op)| {
+                matches!(
+                    op,
+                    QueryOperation::Filter(Filter {
+                        filter_target: FilterTarget::Subquery,
+                        ..
+                    })
+                )
+            });
+
+        let add_operation = |state: &mut QueryState,
+                             query: &mut SelectStatement,
+                             (i, op): (usize, QueryOperation)| {
+            state.placement = plan.and_then(|plan| plan.get(i)).cloned();
+            op.add_to_query(state, query);
+            state.placement = None;
+            if let Some(trace) = &mut state.trace {
+                trace.push(TraceEntry::new(op, query));
+            }
+        };
+
+        for op in operations {
+            add_operation(state, &mut query, op);
         }
 
         for subquery in self.subqueries {
             subquery.add_to_query(state, &mut query);
         }
 
+        for op in subquery_filters {
+            add_operation(state, &mut query, op);
+        }
+
         if query.tables.is_empty() {
             state.some_table_in_query_mut(&mut query);
         }
@@ -2589,7 +5673,11 @@ impl QuerySeed {
                 .collect();
             for field in &query.fields {
                 if let FieldDefinitionExpr::Expr { expr, .. } = field {
-                    if !contains_aggregate(expr) {
+                    // Expressions which are already grouped by don't need their columns grouped
+                    // by as well
+                    if !contains_generated_aggregate(expr)
+                        && !existing_group_by_exprs.contains(expr)
+                    {
                         for col in expr.referred_columns() {
                             if !existing_group_by_exprs
                                 .iter()
@@ -2622,34 +5710,62 @@ impl QuerySeed {
             if !group_by.fields.is_empty() {
                 query.group_by = Some(group_by);
             }
+
+            // Aggregating over no rows without a GROUP BY still returns a single row (eg `0` for
+            // `COUNT(*)`), so contradictory conditions don't make the query empty
+            if query.group_by.is_none() {
+                state.expects_empty_results = false;
+            }
         }
 
         query
     }
 }
 
+/// Parse a range of numbers of operations, either as a single number (`3`), an exclusive range
+/// (`1..3`), or an inclusive range (`1..=3`). Returns an error if the lower bound of a range is
+/// greater than its upper bound.
 fn parse_num_operations<T>(s: &str) -> anyhow::Result<BoundPair<T>>
 where
-    T: FromStr + Clone,
+    T: FromStr + Clone + PartialOrd,
     <T as FromStr>::Err: Send + Sync + Error + 'static,
 {
     use Bound::*;
 
+    let parse = |n: &str| {
+        T::from_str(n).map_err(|e| anyhow!("Invalid number of operations {n:?} in {s:?}: {e}"))
+    };
+
     let (lower_s, upper_s) = match s.split_once("..") {
         Some(lu) => lu,
         None => {
-            let n = T::from_str(s)?;
+            let n = parse(s)?;
             return Ok((Included(n.clone()), Included(n)));
         }
     };
 
-    let lower = T::from_str(lower_s)?;
+    let lower = parse(lower_s)?;
+    let upper = match upper_s.strip_prefix('=') {
+        Some(without_equals) => Included(parse(without_equals)?),
+        None => Excluded(parse(upper_s)?),
+    };
 
-    if let Some(without_equals) = upper_s.strip_prefix('=') {
-        Ok((Included(lower), Included(T::from_str(without_equals)?)))
-    } else {
-        Ok((Included(lower), Excluded(T::from_str(upper_s)?)))
+    if let Included(u) | Excluded(u) = &upper {
+        if lower > *u {
+            bail!("Invalid range {s:?}: lower bound exceeds upper bound");
+        }
     }
+
+    Ok((Included(lower), upper))
+}
+
+/// Parse a `;`-separated list of ranges, each in the format accepted by [`parse_num_operations`]
+fn parse_num_operations_multi<T>(s: &str) -> anyhow::Result<Vec<BoundPair<T>>>
+where
+    T: FromStr + Clone + PartialOrd,
+    <T as FromStr>::Err: Send + Sync + Error + 'static,
+{
+    s.split(';').map(parse_num_operations).collect()
 }
 
 #[derive(Parser, Clone)]
@@ -2665,23 +5781,77 @@ pub struct GenerateOpts {
     pub subquery_depth: usize,
 
     /// Range of operations to be used in a single query, represented as either a single number or
-    /// a Rust-compatible range
+    /// a Rust-compatible range: `N..M` to exclude `M`, or `N..=M` to include it, where `N` may not
+    /// be greater than `M`. Multiple ranges can be separated with `;`, eg `1..=1;3..=3`.
     ///
     /// If not specified, queries will all contain a number of operations equal to the length of
     /// `operations`.
-    #[arg(long, value_parser = parse_num_operations::<usize>)]
-    pub num_operations: Option<BoundPair<usize>>,
+    // NOTE: `Vec` is fully qualified so that clap parses the whole value with the value parser,
+    // rather than treating the argument as accepting multiple values
+    #[arg(long, value_parser = parse_num_operations_multi::<usize>)]
+    pub num_operations: Option<::std::vec::Vec<BoundPair<usize>>>,
+
+    /// Include CTEs which are joined against twice in the set of subquery positions permuted when
+    /// `operations` isn't specified
+    #[arg(long)]
+    pub include_cte_reuse: bool,
+
+    /// Only keep generated queries whose estimated number of result rows, given `--rows-per-table`
+    /// rows in each table, overlaps the given range of the form `MIN..MAX`
+    #[arg(long)]
+    pub result_size_range: Option<ResultSizeRange>,
 }
 
 impl GenerateOpts {
+    /// Returns true if a generated query whose result size was estimated as `estimate` should be
+    /// kept, according to [`Self::result_size_range`]
+    pub fn admits(&self, estimate: &ResultSizeEstimate) -> bool {
+        self.result_size_range
+            .as_ref()
+            .map_or(true, |ResultSizeRange(range)| estimate.overlaps(range))
+    }
+
     /// Construct an iterator of [`QuerySeed`]s from the options in self.
     ///
     /// This involves permuting [`Self::operations`] up to [`Self::num_operations`] times, and
     /// recursively generating subqueries up to a depth of [`Self::subquery_depth`]
     pub fn into_query_seeds(self) -> impl Iterator<Item = QuerySeed> {
         let operations: Vec<_> = match self.operations {
-            Some(OperationList(ops)) => ops.into_iter().flat_map(|ops| ops.into_iter()).collect(),
-            None => ALL_OPERATIONS.clone(),
+            Some(mut ops) => {
+                ops.dedup();
+                // All the groups are flattened into one list here, so overlapping groups (eg
+                // `aggregates,count`) would otherwise result in duplicate seeds
+                let mut seen = HashSet::new();
+                let mut duplicates = vec![];
+                let operations = ops
+                    .0
+                    .into_iter()
+                    .flat_map(|ops| ops.into_iter())
+                    .filter(|op| {
+                        let first = seen.insert(op.clone());
+                        if !first {
+                            duplicates.push(op.clone());
+                        }
+                        first
+                    })
+                    .collect();
+                if !duplicates.is_empty() {
+                    tracing::warn!(?duplicates, "Removed duplicate query operations");
+                }
+                operations
+            }
+            None => {
+                let mut operations = ALL_OPERATIONS.clone();
+                if self.include_cte_reuse {
+                    operations.extend(
+                        CTE_REUSE_SUBQUERY_POSITIONS
+                            .iter()
+                            .cloned()
+                            .map(QueryOperation::Subquery),
+                    );
+                }
+                operations
+            }
         };
 
         let (subqueries, operations): (Vec<SubqueryPosition>, Vec<QueryOperation>) =
@@ -2695,7 +5865,12 @@ impl GenerateOpts {
 
         let num_operations = match self.num_operations {
             None => Either::Left(1..=operations.len()),
-            Some(num_ops) => Either::Right(num_ops.into_iter().unwrap()),
+            Some(ranges) => Either::Right(
+                ranges
+                    .into_iter()
+                    .flat_map(|num_ops| num_ops.into_iter().unwrap())
+                    .unique(),
+            ),
         };
 
         let available_ops: Vec<_> = num_operations
@@ -2780,11 +5955,31 @@ impl GenerateOpts {
             }))
         }
     }
+
+    /// Construct a parallel iterator over the same [`QuerySeed`]s as [`Self::into_query_seeds`],
+    /// in the same order.
+    ///
+    /// The seeds are split into `parallelism` contiguous, disjoint chunks, so that when the
+    /// iterator is driven by a thread pool with `parallelism` threads each thread processes one
+    /// chunk.
+    ///
+    /// Only available with the `parallel` feature.
+    #[cfg(feature = "parallel")]
+    pub fn into_query_seeds_parallel(
+        self,
+        parallelism: usize,
+    ) -> impl IndexedParallelIterator<Item = QuerySeed> {
+        // Enumerating the seeds themselves is cheap compared to generating queries from them
+        let seeds = self.into_query_seeds().collect::<Vec<_>>();
+        let chunk_size = seeds.len().div_ceil(parallelism.max(1)).max(1);
+        seeds.into_par_iter().with_min_len(chunk_size)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use nom_sql::{BinaryOperator, DialectDisplay};
+    use test_strategy::proptest;
 
     use super::*;
 
@@ -2836,11 +6031,35 @@ mod tests {
                     QueryOperation::ColumnAggregate(AggregateType::Min {
                         column_type: SqlType::Int(None)
                     }),
+                    QueryOperation::ColumnAggregate(AggregateType::BitAnd {
+                        column_type: SqlType::Int(None)
+                    }),
+                    QueryOperation::ColumnAggregate(AggregateType::BitOr {
+                        column_type: SqlType::Int(None)
+                    }),
+                    QueryOperation::ColumnAggregate(AggregateType::BitXor {
+                        column_type: SqlType::Int(None)
+                    }),
                 ]),
                 Operations(vec![
-                    QueryOperation::Join(JoinOperator::LeftJoin),
-                    QueryOperation::Join(JoinOperator::LeftOuterJoin),
-                    QueryOperation::Join(JoinOperator::InnerJoin),
+                    QueryOperation::Join {
+                        operator: JoinOperator::LeftJoin,
+                        constraint: JoinConstraintType::On,
+                        key_type: SqlType::Int(None),
+                        key_columns: 1,
+                    },
+                    QueryOperation::Join {
+                        operator: JoinOperator::LeftOuterJoin,
+                        constraint: JoinConstraintType::On,
+                        key_type: SqlType::Int(None),
+                        key_columns: 1,
+                    },
+                    QueryOperation::Join {
+                        operator: JoinOperator::InnerJoin,
+                        constraint: JoinConstraintType::On,
+                        key_type: SqlType::Int(None),
+                        key_columns: 1,
+                    },
                 ])
             ]
         );
@@ -2848,7 +6067,12 @@ mod tests {
 
     #[test]
     fn single_join() {
-        let query = generate_query(vec![QueryOperation::Join(JoinOperator::LeftJoin)]);
+        let query = generate_query(vec![QueryOperation::Join {
+            operator: JoinOperator::LeftJoin,
+            constraint: JoinConstraintType::On,
+            key_type: SqlType::Int(None),
+            key_columns: 1,
+        }]);
         eprintln!("query: {}", query.display(ParseDialect::MySQL));
         assert_eq!(query.tables.len(), 1);
         assert_eq!(query.join.len(), 1);
@@ -2885,6 +6109,42 @@ mod tests {
         }
     }
 
+    #[test]
+    fn single_join_using() {
+        let mut gen = GeneratorState::default();
+        let query = gen.generate_query(QuerySeed {
+            operations: vec![QueryOperation::Join {
+                operator: JoinOperator::InnerJoin,
+                constraint: JoinConstraintType::Using,
+                key_type: SqlType::Int(None),
+                key_columns: 1,
+            }],
+            subqueries: vec![],
+        });
+        let statement = &query.statement;
+        eprintln!("query: {}", statement.display(ParseDialect::MySQL));
+        assert_eq!(statement.join.len(), 1);
+        let join = statement.join.first().unwrap();
+        let shared_column = match &join.constraint {
+            JoinConstraint::Using(cols) => {
+                assert_eq!(cols.len(), 1);
+                ColumnName::from(cols.first().unwrap().name.clone())
+            }
+            constraint => unreachable!("Unexpected constraint: {:?}", constraint),
+        };
+
+        let tables = gen.tables();
+        assert_eq!(tables.len(), 2);
+        for table in tables.values() {
+            assert_eq!(
+                table.columns[&shared_column].sql_type,
+                SqlType::Int(None),
+                "{} should have {shared_column}",
+                table.name
+            );
+        }
+    }
+
     mod parse_num_operations {
         use super::*;
 
@@ -2911,6 +6171,145 @@ mod tests {
                 (Bound::Included(0), Bound::Included(123))
             )
         }
+
+        #[test]
+        fn multiple() {
+            assert_eq!(
+                parse_num_operations_multi::<usize>("1;3..5").unwrap(),
+                vec![
+                    (Bound::Included(1), Bound::Included(1)),
+                    (Bound::Included(3), Bound::Excluded(5))
+                ]
+            )
+        }
+
+        #[test]
+        fn empty_range() {
+            assert_eq!(
+                parse_num_operations::<usize>("2..2").unwrap(),
+                (Bound::Included(2), Bound::Excluded(2))
+            )
+        }
+
+        #[test]
+        fn reversed_bounds() {
+            for s in ["5..2", "5..=4"] {
+                let err = parse_num_operations::<usize>(s).unwrap_err();
+                assert!(
+                    err.to_string().contains("lower bound exceeds upper bound"),
+                    "{s}: {err}"
+                );
+            }
+            assert!(parse_num_operations_multi::<usize>("1;5..2").is_err());
+        }
+
+        #[test]
+        fn invalid_numbers() {
+            for s in [
+                "",
+                "-1",
+                "-3..2",
+                "18446744073709551616",
+                "1..18446744073709551616",
+                "1..2..3",
+                "1..",
+                "..3",
+                "1;",
+            ] {
+                let err = parse_num_operations_multi::<usize>(s).unwrap_err();
+                assert!(
+                    err.to_string().starts_with("Invalid number of operations"),
+                    "{s}: {err}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn into_query_seeds_multiple_num_operations() {
+        let opts = GenerateOpts {
+            operations: Some(
+                vec![vec![
+                    QueryOperation::Distinct,
+                    QueryOperation::SingleParameter,
+                    QueryOperation::RangeParameter,
+                ]]
+                .into(),
+            ),
+            subquery_depth: 0,
+            num_operations: Some(vec![
+                (Bound::Included(1), Bound::Included(1)),
+                (Bound::Included(3), Bound::Included(3)),
+            ]),
+            include_cte_reuse: false,
+            result_size_range: None,
+        };
+
+        let sizes = opts
+            .into_query_seeds()
+            .map(|seed| seed.operations.len())
+            .counts();
+        assert_eq!(sizes, HashMap::from([(1, 3), (3, 1)]));
+    }
+
+    #[test]
+    fn dedup_operation_list() {
+        let mut list = OperationList::from(vec![
+            vec![QueryOperation::Distinct, QueryOperation::Distinct],
+            vec![QueryOperation::SingleParameter, QueryOperation::Distinct],
+            vec![],
+        ]);
+        list.dedup();
+        let OperationList(res) = list;
+        assert_eq!(
+            res,
+            vec![
+                Operations(vec![QueryOperation::Distinct]),
+                Operations(vec![
+                    QueryOperation::SingleParameter,
+                    QueryOperation::Distinct
+                ]),
+            ]
+        );
+    }
+
+    #[test]
+    fn dedup_operation_list_permutes() {
+        let mut list = OperationList::from_str("count,aggregates,distinct,count").unwrap();
+        list.dedup();
+        assert_eq!(list.0.len(), 4);
+        assert!(list.0.iter().all(|Operations(ops)| !ops.is_empty()));
+        assert_ne!(list.permute().count(), 0);
+    }
+
+    #[test]
+    fn into_query_seeds_dedups_operations() {
+        let num_seeds = |operations: &str| {
+            GenerateOpts {
+                operations: Some(OperationList::from_str(operations).unwrap()),
+                subquery_depth: 0,
+                num_operations: Some(vec![(Bound::Included(1), Bound::Included(2))]),
+                include_cte_reuse: false,
+                result_size_range: None,
+            }
+            .into_query_seeds()
+            .count()
+        };
+
+        assert_eq!(num_seeds("aggregates,count"), num_seeds("aggregates"));
+    }
+
+    #[test]
+    fn num_operations_cli() {
+        let opts =
+            GenerateOpts::try_parse_from(["test", "--num-operations", "1..=1;3..=3"]).unwrap();
+        assert_eq!(
+            opts.num_operations,
+            Some(vec![
+                (Bound::Included(1), Bound::Included(1)),
+                (Bound::Included(3), Bound::Included(3)),
+            ])
+        );
     }
 
     #[test]
@@ -2942,6 +6341,50 @@ mod tests {
         assert_eq!(key.len(), 3);
     }
 
+    #[cfg(feature = "parallel")]
+    fn topk_limits_opts() -> GenerateOpts {
+        GenerateOpts {
+            operations: Some(
+                vec![(1..=100)
+                    .map(|limit| QueryOperation::TopK {
+                        order_type: OrderType::OrderAscending,
+                        limit,
+                    })
+                    .collect()]
+                .into(),
+            ),
+            subquery_depth: 0,
+            num_operations: Some(vec![(Bound::Included(1), Bound::Included(1))]),
+            include_cte_reuse: false,
+            result_size_range: None,
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "parallel")]
+    fn into_query_seeds_parallel_matches_sequential() {
+        let sequential = topk_limits_opts().into_query_seeds().collect::<Vec<_>>();
+        assert_eq!(sequential.len(), 100);
+
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(4)
+            .build()
+            .unwrap();
+        let mut parallel = pool.install(|| {
+            topk_limits_opts()
+                .into_query_seeds_parallel(4)
+                .collect::<Vec<_>>()
+        });
+        assert_eq!(parallel.len(), sequential.len());
+        for seed in &sequential {
+            let pos = parallel
+                .iter()
+                .position(|s| s == seed)
+                .unwrap_or_else(|| panic!("{seed:?} not generated in parallel"));
+            parallel.swap_remove(pos);
+        }
+    }
+
     #[test]
     fn into_query_seeds_just_subquery() {
         let opts = GenerateOpts {
@@ -2953,6 +6396,8 @@ mod tests {
             ),
             subquery_depth: 1,
             num_operations: None,
+            include_cte_reuse: false,
+            result_size_range: None,
         };
 
         let seeds = opts.into_query_seeds().collect::<Vec<_>>();
@@ -2973,33 +6418,1752 @@ mod tests {
     }
 
     #[test]
-    fn double_param_uses_different_col() {
-        let query = generate_query(vec![
-            QueryOperation::SingleParameter,
-            QueryOperation::RangeParameter,
-        ]);
-        eprintln!("query: {}", query.display(ParseDialect::MySQL));
-        match &query.where_clause {
-            Some(
-                expr @ Expr::BinaryOp {
-                    lhs,
-                    op: BinaryOperator::And,
-                    rhs,
-                },
-            ) => match (lhs.as_ref(), rhs.as_ref()) {
-                (Expr::BinaryOp { lhs: lhs1, .. }, Expr::BinaryOp { lhs: lhs2, .. }) => {
-                    assert_ne!(lhs1, lhs2);
-                }
-                _ => panic!(
-                    "Unexpected where clause for query: {}",
-                    expr.display(ParseDialect::MySQL)
-                ),
-            },
-            Some(expr) => panic!(
-                "Unexpected where clause for query: {}",
-                expr.display(ParseDialect::MySQL)
-            ),
-            None => panic!("Expected query to have a where clause!"),
+    fn explain_query() {
+        let mut gen = GeneratorState::default();
+        let query = gen.generate_query(QuerySeed::new(
+            vec![QueryOperation::SingleParameter],
+            vec![],
+        ));
+        let query_sql = query.statement.display(ParseDialect::MySQL).to_string();
+        assert_eq!(
+            query.generate_explain_query(),
+            format!("EXPLAIN {query_sql}")
+        );
+    }
+
+    #[test]
+    fn explain_for_dialect() {
+        let mut gen = GeneratorState::default();
+        let query = gen.generate_query(QuerySeed::new(
+            vec![QueryOperation::SingleParameter],
+            vec![],
+        ));
+        let mysql_sql = query.statement.display(ParseDialect::MySQL).to_string();
+        let postgres_sql = query
+            .statement
+            .display(ParseDialect::PostgreSQL)
+            .to_string();
+
+        let mysql = query.generate_explain_for_dialect(Dialect::MySQL);
+        assert_eq!(mysql.strip_prefix("EXPLAIN "), Some(mysql_sql.as_str()));
+
+        let postgres = query.generate_explain_for_dialect(Dialect::PostgreSQL);
+        assert_eq!(
+            postgres.strip_prefix("EXPLAIN ANALYZE "),
+            Some(postgres_sql.as_str())
+        );
+
+        let sqlite = query.generate_explain_for_dialect(Dialect::SQLite);
+        assert_eq!(
+            sqlite.strip_prefix("EXPLAIN QUERY PLAN "),
+            Some(postgres_sql.as_str())
+        );
+    }
+
+    #[test]
+    fn drop_column_invalidates_referencing_queries() {
+        let mut gen = GeneratorState::default();
+        gen.set_track_referenced_columns(true);
+        let param_query = gen
+            .generate_query(QuerySeed::new(
+                vec![QueryOperation::SingleParameter],
+                vec![],
+            ))
+            .id;
+        let count_query = gen
+            .generate_query(QuerySeed::new(
+                vec![QueryOperation::ColumnAggregate(AggregateType::Count {
+                    column_type: SqlType::Int(None),
+                    distinct: false,
+                })],
+                vec![],
+            ))
+            .id;
+        assert_ne!(param_query, count_query);
+
+        let table_name = TableName::from("table_1");
+        let column_name = ColumnName::from("column_2");
+        gen.table_mut(&table_name)
+            .unwrap()
+            .drop_column(&column_name)
+            .unwrap();
+
+        assert!(!gen
+            .table(&table_name)
+            .unwrap()
+            .columns
+            .contains_key(&column_name));
+        assert_eq!(
+            gen.queries_referencing_column(&table_name, &column_name),
+            vec![count_query]
+        );
+
+        gen.set_track_referenced_columns(false);
+        let untracked_query = gen
+            .generate_query(QuerySeed::new(
+                vec![QueryOperation::ColumnAggregate(AggregateType::Count {
+                    column_type: SqlType::Int(None),
+                    distinct: false,
+                })],
+                vec![],
+            ))
+            .id;
+        assert!(untracked_query > count_query);
+        assert!(!gen.referenced_columns.contains_key(&untracked_query));
+    }
+
+    #[test]
+    fn generate_data_correlated_references_parent_rows() {
+        let mut gen = GeneratorState::default();
+        let parent = gen.fresh_table_mut().name.clone();
+        let child_table = gen.fresh_table_mut();
+        let child = child_table.name.clone();
+        let fk = child_table.fresh_column_with_type(SqlType::Int(None));
+
+        assert_eq!(gen.row_count_for(&parent), 0);
+        gen.generate_data_for_table(&parent, 100, false);
+        assert_eq!(gen.row_count_for(&parent), 100);
+
+        let rows = gen.generate_data_correlated(&child, 200, &fk, &parent, false);
+        assert_eq!(rows.len(), 200);
+        assert_eq!(gen.row_count_for(&child), 200);
+        for row in rows {
+            match row[&fk] {
+                DfValue::Int(v) => assert!((0..100).contains(&v), "{v} out of range"),
+                ref v => panic!("Expected an integer foreign key, got {v:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn drop_primary_key_column() {
+        let mut table = TableSpec::new("t".into());
+        let pk = table.primary_key().clone();
+        table.drop_column(&pk).unwrap_err();
+        assert!(table.columns.contains_key(&pk));
+    }
+
+    #[test]
+    fn rename_column() {
+        let mut table = TableSpec::new("t".into());
+        let pk = table.primary_key().clone();
+        let other = table.fresh_column();
+
+        table.rename_column(&pk, other.clone()).unwrap_err();
+        table.rename_column(&pk, "id".into()).unwrap();
+        assert_eq!(table.primary_key, Some("id".into()));
+        assert!(!table.columns.contains_key(&pk));
+        assert!(table.columns.contains_key(&ColumnName::from("id")));
+    }
+
+    #[test]
+    fn ddl_column_order_is_deterministic() {
+        let mut gen = GeneratorState::default();
+        let table = gen.fresh_table_mut();
+        table.primary_key();
+        let columns = (0..10)
+            .map(|_| table.fresh_column_with_type(SqlType::Text))
+            .collect::<Vec<_>>();
+
+        let ddl = || {
+            gen.ddl()
+                .map(|stmt| stmt.display(ParseDialect::MySQL).to_string())
+                .collect::<Vec<_>>()
+        };
+        let first = ddl();
+        assert_eq!(first, ddl());
+
+        let field_names = gen
+            .ddl()
+            .next()
+            .unwrap()
+            .body
+            .unwrap()
+            .fields
+            .into_iter()
+            .map(|field| ColumnName::from(field.column))
+            .collect::<Vec<_>>();
+        assert_eq!(field_names[0], ColumnName::from("column_1"));
+        assert_eq!(field_names[1..], columns);
+    }
+
+    #[test]
+    fn ddl_includes_columns_missing_from_column_order() {
+        let mut table = TableSpec::new("t".into());
+        let first = table.fresh_column();
+        let second = table.fresh_column();
+        let third = table.fresh_column();
+        table.column_order = vec![second.clone()];
+
+        let field_names = CreateTableStatement::from(table)
+            .body
+            .unwrap()
+            .fields
+            .into_iter()
+            .map(|field| ColumnName::from(field.column))
+            .collect::<Vec<_>>();
+        assert_eq!(field_names, vec![second, first, third]);
+    }
+
+    #[test]
+    fn project_literal() {
+        for literal_type in LiteralType::iter() {
+            let query = generate_query(vec![QueryOperation::ProjectLiteral(literal_type)]);
+            assert!(
+                query.fields.iter().any(|field| matches!(
+                    field,
+                    FieldDefinitionExpr::Expr { expr, .. } if *expr == literal_type.to_expr()
+                )),
+                "Expected {literal_type:?} to be projected in {}",
+                query.display(ParseDialect::MySQL)
+            );
+        }
+    }
+
+    #[test]
+    fn parse_project_literal() {
+        let Operations(operations) = "project_literal".parse().unwrap();
+        assert_eq!(
+            operations,
+            LiteralType::iter()
+                .map(QueryOperation::ProjectLiteral)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn char_functions() {
+        let Operations(operations) = "char_functions".parse().unwrap();
+        let query = generate_query(operations);
+        let sql = query.display(ParseDialect::MySQL).to_string();
+        assert!(sql.contains("char(`"), "{sql}");
+        assert!(sql.contains("ascii(`"), "{sql}");
+
+        assert_eq!(BuiltinFunction::Char.return_type(), SqlType::Text);
+        assert_eq!(BuiltinFunction::Ascii.return_type(), SqlType::Int(Some(32)));
+
+        // Only generated when explicitly requested
+        let Operations(project_builtin) = "project_builtin".parse().unwrap();
+        assert!(!project_builtin.iter().any(|op| matches!(
+            op,
+            QueryOperation::ProjectBuiltinFunction(f) if f.is_char_function()
+        )));
+    }
+
+    #[test]
+    fn coerced_comparison_sql() {
+        for (column_type, rhs_type, expected_rhs) in [
+            (SqlType::Int(None), SqlType::Text, "'1'"),
+            (SqlType::Decimal(10, 2), SqlType::Int(None), "1"),
+            (SqlType::Date, SqlType::Text, "'2020-01-01'"),
+        ] {
+            let mut gen = GeneratorState::default();
+            let mut query = gen.generate_query(QuerySeed {
+                operations: vec![QueryOperation::Filter(
+                    Filter::coerced_comparison(column_type.clone(), rhs_type, LogicalOp::And)
+                        .unwrap(),
+                )],
+                subqueries: vec![],
+            });
+            let (column, rhs) = match &query.statement.where_clause {
+                Some(Expr::BinaryOp {
+                    op: BinaryOperator::Equal,
+                    lhs,
+                    rhs,
+                }) => match lhs.as_ref() {
+                    Expr::Column(column) => (column.clone(), rhs.clone()),
+                    lhs => panic!("Expected column on lhs of filter, got {lhs:?}"),
+                },
+                where_clause => panic!("Expected equality filter, got {where_clause:?}"),
+            };
+            assert_eq!(rhs.display(ParseDialect::MySQL).to_string(), expected_rhs);
+
+            // The planted value must match the literal under MySQL's coercion rules
+            let (_, expected_value) = coercion::coerced_comparison(
+                &column_type,
+                &match rhs.as_ref() {
+                    Expr::Literal(Literal::String(_)) => SqlType::Text,
+                    _ => SqlType::Int(None),
+                },
+            )
+            .unwrap();
+            let table_name = TableName::from(column.table.unwrap().name.as_str());
+            let data = query.state.generate_data(10, false, false);
+            assert!(
+                data[&table_name]
+                    .iter()
+                    .any(|row| row[&ColumnName::from(column.name.as_str())] == expected_value),
+                "Expected some row to contain {expected_value:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn coerced_comparison_refuses_incompatible_types() {
+        assert!(
+            Filter::coerced_comparison(SqlType::Int(None), SqlType::Int(None), LogicalOp::And)
+                .is_none()
+        );
+        assert!(
+            Filter::coerced_comparison(SqlType::Date, SqlType::Int(None), LogicalOp::And).is_none()
+        );
+    }
+
+    #[test]
+    fn parse_coercion_filters() {
+        let Operations(operations) = "coercion_filters".parse().unwrap();
+        for (column_type, rhs_type) in [
+            (SqlType::Int(None), SqlType::Text),
+            (SqlType::Decimal(10, 2), SqlType::Int(None)),
+            (SqlType::Date, SqlType::Text),
+        ] {
+            assert!(operations.contains(&QueryOperation::Filter(
+                Filter::coerced_comparison(column_type, rhs_type, LogicalOp::And).unwrap()
+            )));
+        }
+    }
+
+    fn subquery_filter() -> Filter {
+        Filter {
+            extend_where_with: LogicalOp::And,
+            operation: FilterOp::Comparison {
+                op: BinaryOperator::Equal,
+                rhs: FilterRHS::Constant(Literal::Integer(1)),
+            },
+            column_type: SqlType::Int(None),
+            filter_target: FilterTarget::Subquery,
+        }
+    }
+
+    /// Returns the column compared with `1` in the WHERE clause of `query`
+    fn filtered_column(query: &SelectStatement) -> Column {
+        match &query.where_clause {
+            Some(Expr::BinaryOp {
+                op: BinaryOperator::Equal,
+                lhs,
+                rhs,
+            }) if **rhs == Expr::Literal(Literal::Integer(1)) => match lhs.as_ref() {
+                Expr::Column(column) => column.clone(),
+                lhs => panic!("Expected column on lhs of filter, got {lhs:?}"),
+            },
+            where_clause => panic!("Expected equality filter, got {where_clause:?}"),
+        }
+    }
+
+    #[test]
+    fn subquery_filter_sql() {
+        for position in [
+            SubqueryPosition::Cte(JoinOperator::InnerJoin),
+            SubqueryPosition::Join(JoinOperator::InnerJoin),
+        ] {
+            let is_cte = matches!(position, SubqueryPosition::Cte(_));
+            let mut gen = GeneratorState::default();
+            let mut query = gen.generate_query(QuerySeed {
+                operations: vec![QueryOperation::Filter(subquery_filter())],
+                subqueries: vec![Subquery {
+                    position,
+                    seed: QuerySeed::new(vec![], vec![]),
+                }],
+            });
+            let (subquery, statement) = derived_subqueries(&query.statement)
+                .map(|(name, statement)| (name.clone(), statement.clone()))
+                .next()
+                .expect("Query should contain a subquery");
+
+            let column = filtered_column(&query.statement);
+            assert_eq!(
+                column.table,
+                Some(Relation {
+                    name: subquery.clone(),
+                    schema: None
+                })
+            );
+
+            let sql = query.statement.display(ParseDialect::MySQL).to_string();
+            if is_cte {
+                assert!(
+                    sql.starts_with(&format!("WITH `{subquery}` AS (SELECT ")),
+                    "{sql}"
+                );
+            }
+            assert!(
+                sql.contains(&format!("WHERE (`{subquery}`.`{}` = 1)", column.name)),
+                "{sql}"
+            );
+
+            // The planted value must be in the base table column the filtered column was
+            // projected from
+            let base_column = statement
+                .fields
+                .iter()
+                .find_map(|field| match field {
+                    FieldDefinitionExpr::Expr {
+                        expr: Expr::Column(base_column),
+                        alias: Some(alias),
+                    } if *alias == column.name => Some(base_column.clone()),
+                    _ => None,
+                })
+                .expect("Filtered column should be projected from the subquery");
+            let table_name = TableName::from(base_column.table.unwrap().name.as_str());
+            let expected_value = DfValue::try_from(Literal::Integer(1)).unwrap();
+            let data = query.state.generate_data(10, false, false);
+            assert!(
+                data[&table_name]
+                    .iter()
+                    .any(|row| row[&ColumnName::from(base_column.name.as_str())] == expected_value),
+                "Expected some row to contain {expected_value:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn subqueries_use_distinct_tables() {
+        fn base_tables(statement: &SelectStatement) -> HashSet<SqlIdentifier> {
+            statement
+                .tables
+                .iter()
+                .chain(statement.join.iter().filter_map(|jc| match &jc.right {
+                    JoinRightSide::Table(tbl) => Some(tbl),
+                    _ => None,
+                }))
+                .filter_map(|te| te.inner.as_table())
+                .map(|tbl| tbl.name.clone())
+                .collect()
+        }
+
+        let subquery = || Subquery {
+            position: SubqueryPosition::Join(JoinOperator::InnerJoin),
+            seed: QuerySeed::new(
+                vec![QueryOperation::Join {
+                    operator: JoinOperator::InnerJoin,
+                    constraint: JoinConstraintType::On,
+                    key_type: SqlType::Int(None),
+                    key_columns: 1,
+                }],
+                vec![],
+            ),
+        };
+        let mut gen = GeneratorState::default();
+        let mut query = gen.generate_query(QuerySeed::new(vec![], vec![subquery(), subquery()]));
+
+        let outer_tables = base_tables(&query.statement);
+        let subquery_tables = query
+            .statement
+            .join
+            .iter()
+            .filter_map(|jc| match &jc.right {
+                JoinRightSide::Table(TableExpr {
+                    inner: TableExprInner::Subquery(subquery),
+                    ..
+                }) => Some(base_tables(subquery)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(subquery_tables.len(), 2);
+        assert!(!outer_tables.is_empty());
+
+        for (i, tables) in subquery_tables.iter().enumerate() {
+            assert_eq!(tables.len(), 2, "{tables:?}");
+            assert!(
+                outer_tables.is_disjoint(tables),
+                "{outer_tables:?} {tables:?}"
+            );
+            for other in &subquery_tables[i + 1..] {
+                assert!(tables.is_disjoint(other), "{tables:?} {other:?}");
+            }
+        }
+
+        // Data still needs to be generated for the tables of the subqueries
+        let data = query.state.generate_data(1, false, false);
+        for table in subquery_tables.iter().flatten() {
+            assert!(data.contains_key(table.as_str()), "No data for {table}");
+        }
+    }
+
+    #[test]
+    fn cte_reused_twice() {
+        let Operations(operations) = "cte_reuse".parse().unwrap();
+        let QueryOperation::Subquery(position) = operations.into_iter().exactly_one().unwrap()
+        else {
+            panic!("Expected cte_reuse to be a subquery")
+        };
+
+        let mut gen = GeneratorState::default();
+        let query = gen.generate_query(QuerySeed {
+            operations: vec![],
+            subqueries: vec![Subquery {
+                position,
+                seed: QuerySeed {
+                    operations: vec![],
+                    subqueries: vec![],
+                },
+            }],
+        });
+
+        assert_eq!(
+            query.statement.display(ParseDialect::MySQL).to_string(),
+            "WITH `alias_3` AS (SELECT `table_1`.`column_1` AS `alias_1`, \
+             `table_1`.`column_2` AS `alias_2` FROM `table_1`) \
+             SELECT `alias_4`.`alias_1` AS `alias_5`, `alias_6`.`alias_2` AS `alias_7` \
+             FROM `table_2` \
+             INNER JOIN `alias_3` AS `alias_4` ON (`table_2`.`column_1` = `alias_4`.`alias_1`) \
+             INNER JOIN `alias_3` AS `alias_6` ON (`table_2`.`column_2` = `alias_6`.`alias_2`)"
+        );
+        query.dry_run_rewrite().unwrap();
+    }
+
+    #[test]
+    fn subquery_filter_without_subquery_filters_base_table() {
+        let mut gen = GeneratorState::default();
+        let query = gen
+            .generate_query(QuerySeed::new(
+                vec![QueryOperation::Filter(subquery_filter())],
+                vec![],
+            ))
+            .statement;
+        let table = filtered_column(&query).table.unwrap();
+        assert!(gen
+            .tables()
+            .contains_key(&TableName::from(table.name.as_str())));
+    }
+
+    #[test]
+    fn parse_subquery_filters() {
+        let Operations(operations) = "subquery_filters".parse().unwrap();
+        assert!(!operations.is_empty());
+        assert!(operations.iter().all(|op| matches!(
+            op,
+            QueryOperation::Filter(Filter {
+                filter_target: FilterTarget::Subquery,
+                ..
+            })
+        )));
+        assert!(operations.contains(&QueryOperation::Filter(subquery_filter())));
+    }
+
+    #[test]
+    fn time_bucket_sql() {
+        let column = Column::from("t.c");
+        assert_eq!(
+            TimeBucket::Date
+                .to_expr(column.clone())
+                .display(ParseDialect::MySQL)
+                .to_string(),
+            "date_format(`t`.`c`, '%Y-%m-%d')"
+        );
+        assert_eq!(
+            TimeBucket::Month
+                .to_expr(column.clone())
+                .display(ParseDialect::MySQL)
+                .to_string(),
+            "month(`t`.`c`)"
+        );
+        assert_eq!(
+            TimeBucket::Year
+                .to_expr(column)
+                .display(ParseDialect::MySQL)
+                .to_string(),
+            "date_format(`t`.`c`, '%Y')"
+        );
+    }
+
+    #[test]
+    fn group_by_time_bucket() {
+        for bucket in TimeBucket::iter() {
+            let mut gen = GeneratorState::default();
+            let query = gen.generate_query(QuerySeed {
+                operations: vec![QueryOperation::GroupByExpression { bucket }],
+                subqueries: vec![],
+            });
+            let statement = &query.statement;
+            eprintln!("query: {}", statement.display(ParseDialect::MySQL));
+
+            let group_by = &statement.group_by.as_ref().unwrap().fields;
+            assert_eq!(group_by.len(), 1, "Raw column should not be grouped by");
+            let FieldReference::Expr(group_by_expr) = &group_by[0] else {
+                panic!("Expected expression in GROUP BY");
+            };
+            assert!(matches!(group_by_expr, Expr::Call(_)));
+            assert!(statement.fields.iter().any(|field| matches!(
+                field,
+                FieldDefinitionExpr::Expr { expr, .. } if expr == group_by_expr
+            )));
+            assert!(statement.fields.iter().any(|field| matches!(
+                field,
+                FieldDefinitionExpr::Expr {
+                    expr: Expr::Call(FunctionExpr::CountStar),
+                    ..
+                }
+            )));
+
+            query.dry_run_rewrite().unwrap();
+        }
+    }
+
+    #[test]
+    fn parse_group_by_time() {
+        let Operations(operations) = "group_by_time".parse().unwrap();
+        assert_eq!(
+            operations,
+            TimeBucket::iter()
+                .map(|bucket| QueryOperation::GroupByExpression { bucket })
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn table_spec_round_trips_indexes() {
+        let stmt = nom_sql::parse_create_table(
+            ParseDialect::MySQL,
+            "CREATE TABLE t (
+                id INT,
+                a INT,
+                b INT,
+                c TEXT,
+                PRIMARY KEY (id),
+                UNIQUE KEY a_unique (a),
+                KEY b_c (b, c),
+                KEY (c),
+                FULLTEXT KEY c_fulltext (c)
+            )",
+        )
+        .unwrap();
+        let spec = TableSpec::from(stmt.clone());
+        assert_eq!(
+            spec.indexes,
+            vec![
+                IndexSpec {
+                    name: Some("a_unique".into()),
+                    columns: vec!["a".into()],
+                    kind: IndexKind::Unique,
+                },
+                IndexSpec {
+                    name: Some("b_c".into()),
+                    columns: vec!["b".into(), "c".into()],
+                    kind: IndexKind::NonUnique,
+                },
+                IndexSpec {
+                    name: None,
+                    columns: vec!["c".into()],
+                    kind: IndexKind::NonUnique,
+                },
+                IndexSpec {
+                    name: Some("c_fulltext".into()),
+                    columns: vec!["c".into()],
+                    kind: IndexKind::FullText,
+                },
+            ]
+        );
+
+        let round_tripped = CreateTableStatement::from(spec);
+        assert_eq!(round_tripped.body.unwrap().keys, stmt.body.unwrap().keys,);
+    }
+
+    #[test]
+    fn reset_generators_regenerates_same_data() {
+        let mut spec = TableSpec::new("t".into());
+        let a = spec.fresh_column();
+        let b = spec.fresh_column_with_type(SqlType::Text);
+        spec.fresh_column();
+        spec.set_primary_key_column(&a);
+        spec.set_column_generator_spec(b, ColumnGenerationSpec::Unique);
+
+        let first = spec.generate_data(100, false);
+        spec.reset_generators();
+        let second = spec.generate_data(100, false);
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn cardinality_hints() {
+        let mut spec = TableSpec::new("t".into());
+        let id = spec.fresh_column();
+        let category = spec.fresh_column_with_type(SqlType::Text);
+        let other = spec.fresh_column();
+        spec.set_primary_key_column(&id);
+        spec.set_column_generator_spec(
+            category.clone(),
+            ColumnGenerationSpec::WithCardinality {
+                inner: Box::new(ColumnGenerationSpec::Random),
+                distinct: 5,
+            },
+        );
+        assert_eq!(spec.cardinality_hint_for(&category), Some(5));
+        assert_eq!(spec.cardinality_hint_for(&other), None);
+        assert_eq!(spec.cardinality_hint_for(&"nonexistent".into()), None);
+
+        spec.generate_data(100, false);
+        assert_eq!(spec.cardinality_hint_for(&id), Some(100));
+        assert_eq!(spec.cardinality_hint_for(&category), Some(5));
+
+        spec.set_cardinality_hint(&other, Some(3));
+        assert_eq!(spec.cardinality_hint_for(&other), Some(3));
+    }
+
+    #[tokio::test]
+    async fn generate_data_stream_matches_generate_data() {
+        let mut spec = TableSpec::new("t".into());
+        let a = spec.fresh_column();
+        let b = spec.fresh_column_with_type(SqlType::Text);
+        let c = spec.fresh_column();
+        spec.set_primary_key_column(&a);
+        spec.set_column_generator_spec(b, ColumnGenerationSpec::Unique);
+        spec.expect_value(c, DfValue::from(7));
+
+        let streamed = spec
+            .generate_data_stream(100, false)
+            .collect::<Vec<_>>()
+            .await;
+        spec.reset_generators();
+        let collected = spec.generate_data(100, false);
+        assert_eq!(streamed, collected);
+    }
+
+    #[test]
+    fn generate_data_with_budget_stops_early() {
+        let mut spec = TableSpec::new("t".into());
+        let id = spec.fresh_column();
+        spec.set_primary_key_column(&id);
+        for _ in 0..4 {
+            spec.fresh_column_with_type(SqlType::Text);
+        }
+
+        let budget = DataGenerationBudget {
+            max_bytes_total: Some(1 << 20),
+            max_bytes_per_row: None,
+        };
+        let data = spec.generate_data_with_budget(1000, true, budget);
+        assert_eq!(
+            data.budget_exceeded,
+            Some(BudgetExceeded {
+                rows_requested: 1000
+            })
+        );
+        assert!(data.rows.len() < 1000);
+        assert!(data.approximate_bytes <= 1 << 20);
+        assert_eq!(
+            data.approximate_bytes,
+            data.rows
+                .iter()
+                .flat_map(|row| row.values())
+                .map(approximate_value_size)
+                .sum::<u64>()
+        );
+    }
+
+    #[test]
+    fn generate_data_with_budget_clamps_rows() {
+        let mut spec = TableSpec::new("t".into());
+        let id = spec.fresh_column();
+        spec.set_primary_key_column(&id);
+        for _ in 0..4 {
+            spec.fresh_column_with_type(SqlType::Text);
+        }
+
+        let budget = DataGenerationBudget {
+            max_bytes_total: None,
+            max_bytes_per_row: Some(1000),
+        };
+        let data = spec.generate_data_with_budget(100, true, budget);
+        assert_eq!(data.rows.len(), 100);
+        assert_eq!(data.budget_exceeded, None);
+        for row in &data.rows {
+            assert!(row.values().map(approximate_value_size).sum::<u64>() <= 1000);
+        }
+        // Unique columns are never truncated
+        let ids = data
+            .rows
+            .iter()
+            .map(|row| &row[&id])
+            .collect::<HashSet<_>>();
+        assert_eq!(ids.len(), 100);
+    }
+
+    #[test]
+    fn generate_data_with_budget_fixed_width() {
+        let mut spec = TableSpec::new("t".into());
+        let a = spec.fresh_column();
+        spec.fresh_column();
+        spec.set_primary_key_column(&a);
+
+        let budget = DataGenerationBudget {
+            max_bytes_total: Some(1 << 20),
+            max_bytes_per_row: Some(64),
+        };
+        let data = spec.generate_data_with_budget(100, false, budget);
+        spec.reset_generators();
+        assert_eq!(data.budget_exceeded, None);
+        assert_eq!(data.rows, spec.generate_data(100, false));
+    }
+
+    #[test]
+    fn data_budget_applies_across_tables() {
+        let mut gen = GeneratorState::default();
+        let tables = (0..2)
+            .map(|_| {
+                let table = gen.fresh_table_mut();
+                table.fresh_column_with_type(SqlType::Int(None));
+                table.name.clone()
+            })
+            .collect::<Vec<_>>();
+        let row_bytes = mem::size_of::<DfValue>() as u64;
+        gen.set_data_budget(DataGenerationBudget {
+            max_bytes_total: Some(15 * row_bytes),
+            max_bytes_per_row: None,
+        });
+
+        assert_eq!(gen.generate_data_for_table(&tables[0], 10, false).len(), 10);
+        assert_eq!(gen.data_bytes_generated(), 10 * row_bytes);
+        // Only what's left of the budget is available to the second table
+        assert_eq!(gen.generate_data_for_table(&tables[1], 10, false).len(), 5);
+        assert_eq!(gen.data_bytes_generated(), 15 * row_bytes);
+        assert!(gen
+            .generate_data_for_table(&tables[0], 10, false)
+            .is_empty());
+    }
+
+    fn conflicts_table() -> (TableSpec, ColumnName, ColumnName, ColumnName) {
+        let mut spec = TableSpec::new("t".into());
+        let a = spec.fresh_column();
+        let b = spec.fresh_column_with_type(SqlType::Text);
+        let c = spec.fresh_column();
+        spec.set_primary_key_column(&a);
+        spec.set_column_generator_spec(b.clone(), ColumnGenerationSpec::Unique);
+        spec.expect_value(c.clone(), DfValue::from(7));
+        (spec, a, b, c)
+    }
+
+    #[test]
+    fn generate_data_with_conflicts_without_unique_columns() {
+        let mut spec = TableSpec::new("t".into());
+        spec.fresh_column();
+        spec.fresh_column_with_type(SqlType::Text);
+        let (rows, conflicts) = spec.generate_data_with_conflicts(
+            50,
+            false,
+            ConflictInjection {
+                unique_key_violations_per_1000: 100,
+                exact_duplicates: true,
+            },
+        );
+        assert_eq!(rows.len(), 50);
+        assert!(conflicts.is_empty());
+    }
+
+    #[test]
+    fn generate_data_with_conflicts() {
+        let (mut spec, a, b, c) = conflicts_table();
+        let (rows, conflicts) = spec.generate_data_with_conflicts(
+            50,
+            false,
+            ConflictInjection {
+                unique_key_violations_per_1000: 100,
+                exact_duplicates: false,
+            },
+        );
+        assert_eq!(rows.len(), 50);
+        assert_eq!(conflicts, vec![9, 19, 29, 39, 49]);
+
+        for &i in &conflicts {
+            // Each conflict shares its keys with the row before it, but not the rest of its data
+            let (row, earlier) = (&rows[i], &rows[i - 1]);
+            assert_eq!(row[&a], earlier[&a]);
+            assert_eq!(row[&b], earlier[&b]);
+            assert_ne!(row[&c], earlier[&c]);
+        }
+
+        // Removing the conflicts leaves the data that would've been generated without them
+        let clean = rows
+            .into_iter()
+            .enumerate()
+            .filter(|(i, _)| !conflicts.contains(i))
+            .map(|(_, row)| row)
+            .collect::<Vec<_>>();
+        spec.reset_generators();
+        assert_eq!(clean, spec.generate_data(45, false));
+        assert_eq!(
+            clean.iter().map(|row| &row[&a]).unique().count(),
+            clean.len()
+        );
+    }
+
+    #[test]
+    fn generate_data_with_exact_duplicate_conflicts() {
+        let (mut spec, ..) = conflicts_table();
+        let (rows, conflicts) = spec.generate_data_with_conflicts(
+            2000,
+            false,
+            ConflictInjection {
+                unique_key_violations_per_1000: 3,
+                exact_duplicates: true,
+            },
+        );
+        assert_eq!(conflicts.len(), 6);
+        assert_eq!(conflicts[0], 333);
+        for &i in &conflicts {
+            assert_eq!(rows[i], rows[i - 1]);
+        }
+    }
+
+    #[test]
+    fn add_index() {
+        let mut spec = TableSpec::new("t".into());
+        let a = spec.fresh_column();
+        let b = spec.fresh_column();
+        assert!(!spec.has_index_on(&a));
+
+        spec.add_index(IndexSpec {
+            name: None,
+            columns: vec![a.clone(), b.clone()],
+            kind: IndexKind::Unique,
+        });
+        assert!(spec.has_index_on(&a));
+        assert!(!spec.has_index_on(&b));
+        assert!(matches!(
+            spec.columns[&a].gen_spec.lock().generator,
+            ColumnGenerator::Unique(_)
+        ));
+
+        let stmt = CreateTableStatement::from(spec);
+        assert_eq!(
+            stmt.body.unwrap().keys,
+            Some(vec![TableKey::UniqueKey {
+                constraint_name: None,
+                index_name: None,
+                columns: vec![a.into(), b.into()],
+                index_type: None,
+            }])
+        );
+    }
+
+    #[test]
+    fn missing_indexes() {
+        let mut gen = GeneratorState::default();
+        let query = gen.generate_query(QuerySeed {
+            operations: vec![QueryOperation::SingleParameter],
+            subqueries: vec![],
+        });
+        let mut state = query.state;
+        let missing = state.missing_indexes();
+        assert_eq!(missing.len(), 1);
+
+        let (table, column) = missing.into_iter().next().unwrap();
+        state.gen.table_mut(&table).unwrap().add_index(IndexSpec {
+            name: None,
+            columns: vec![column],
+            kind: IndexKind::NonUnique,
+        });
+        assert!(state.missing_indexes().is_empty());
+    }
+
+    #[test]
+    fn rollup() {
+        let query = generate_query(vec![QueryOperation::Rollup { num_group_cols: 2 }]);
+        let group_by = query.group_by.as_ref().unwrap();
+        assert!(group_by.with_rollup);
+        assert_eq!(group_by.fields.len(), 2);
+        assert!(query
+            .display(ParseDialect::MySQL)
+            .to_string()
+            .ends_with("WITH ROLLUP"));
+    }
+
+    #[test]
+    fn anti_join() {
+        let query = generate_query(vec![QueryOperation::AntiJoin {
+            overlap_percent: 50,
+        }]);
+        assert_eq!(query.join.len(), 1);
+        assert_eq!(query.join[0].operator, JoinOperator::LeftJoin);
+        let right_key = match &query.join[0].constraint {
+            JoinConstraint::On(Expr::BinaryOp { rhs, .. }) => rhs.clone(),
+            constraint => panic!("Expected ON constraint, got {constraint:?}"),
+        };
+        assert_eq!(
+            query.where_clause,
+            Some(Expr::BinaryOp {
+                lhs: right_key,
+                op: BinaryOperator::Is,
+                rhs: Box::new(Expr::Literal(Literal::Null)),
+            })
+        );
+        assert_eq!(
+            query.display(ParseDialect::MySQL).to_string(),
+            "SELECT `table_1`.`column_1` AS `alias_1` FROM `table_1` \
+             LEFT JOIN `table_2` ON (`table_1`.`column_1` = `table_2`.`column_1`) \
+             WHERE (`table_2`.`column_1` IS NULL)"
+        );
+    }
+
+    #[test]
+    fn trace_operations() {
+        let operations = vec![
+            QueryOperation::Join {
+                operator: JoinOperator::InnerJoin,
+                constraint: JoinConstraintType::On,
+                key_type: SqlType::Int(None),
+                key_columns: 1,
+            },
+            QueryOperation::SingleParameter,
+            QueryOperation::Filter(Filter {
+                extend_where_with: LogicalOp::And,
+                operation: FilterOp::Comparison {
+                    op: BinaryOperator::Equal,
+                    rhs: FilterRHS::Constant(Literal::Integer(1)),
+                },
+                column_type: SqlType::Int(None),
+                filter_target: FilterTarget::Base,
+            }),
+            QueryOperation::ColumnAggregate(AggregateType::Count {
+                column_type: SqlType::Int(None),
+                distinct: false,
+            }),
+        ];
+
+        let mut gen = GeneratorState::default();
+        let query = gen.generate_query(QuerySeed::new(operations.clone(), vec![]));
+        assert!(query.trace_log().is_empty());
+
+        gen.set_trace_operations(true);
+        let query = gen.generate_query(QuerySeed::new(operations.clone(), vec![]));
+        let trace = query.trace_log();
+        assert_eq!(
+            trace
+                .iter()
+                .map(|entry| entry.operation.clone())
+                .collect::<Vec<_>>(),
+            operations
+        );
+        assert_eq!(
+            trace.iter().map(|entry| entry.tables).collect::<Vec<_>>(),
+            vec![2, 2, 2, 2]
+        );
+        assert_eq!(
+            trace
+                .iter()
+                .map(|entry| entry.where_clause_depth)
+                .collect::<Vec<_>>(),
+            vec![0, 1, 2, 2]
+        );
+        assert_eq!(trace[0].fields, 2);
+        assert_eq!(trace[3].fields, trace[2].fields + 1);
+    }
+
+    #[test]
+    fn composite_join() {
+        let mut gen = GeneratorState::default();
+        let query = gen.generate_query(QuerySeed {
+            operations: Operations::named("composite_join").unwrap().0,
+            subqueries: vec![],
+        });
+        assert_eq!(
+            query.statement.display(ParseDialect::MySQL).to_string(),
+            "SELECT `table_1`.`column_3` AS `alias_1`, `table_2`.`column_3` AS `alias_2` \
+             FROM `table_1` \
+             INNER JOIN `table_2` ON ((`table_1`.`column_1` = `table_2`.`column_1`) \
+             AND (`table_1`.`column_2` = `table_2`.`column_2`))"
+        );
+        query.dry_run_rewrite().unwrap();
+    }
+
+    #[test]
+    fn composite_join_using() {
+        let mut gen = GeneratorState::default();
+        let query = gen.generate_query(QuerySeed {
+            operations: vec![QueryOperation::Join {
+                operator: JoinOperator::InnerJoin,
+                constraint: JoinConstraintType::Using,
+                key_type: SqlType::Int(None),
+                key_columns: 3,
+            }],
+            subqueries: vec![],
+        });
+        match &query.statement.join[0].constraint {
+            JoinConstraint::Using(columns) => assert_eq!(
+                columns
+                    .iter()
+                    .map(|column| column.name.as_str())
+                    .collect::<Vec<_>>(),
+                vec!["table_2_key", "table_2_key_2", "table_2_key_3"]
+            ),
+            constraint => panic!("Expected USING constraint, got {constraint:?}"),
+        }
+    }
+
+    #[test]
+    fn composite_join_has_matching_rows() {
+        for key_columns in 2..=MAX_JOIN_KEY_COLUMNS {
+            let mut gen = GeneratorState::default();
+            let mut query = gen.generate_query(QuerySeed {
+                operations: vec![QueryOperation::Join {
+                    operator: JoinOperator::InnerJoin,
+                    constraint: JoinConstraintType::On,
+                    key_type: SqlType::Int(None),
+                    key_columns,
+                }],
+                subqueries: vec![],
+            });
+
+            let mut equalities = vec![];
+            let mut exprs = match &query.statement.join[0].constraint {
+                JoinConstraint::On(expr) => vec![expr.clone()],
+                constraint => panic!("Expected ON constraint, got {constraint:?}"),
+            };
+            while let Some(expr) = exprs.pop() {
+                match expr {
+                    Expr::BinaryOp {
+                        lhs,
+                        op: BinaryOperator::And,
+                        rhs,
+                    } => exprs.extend([*lhs, *rhs]),
+                    Expr::BinaryOp {
+                        lhs,
+                        op: BinaryOperator::Equal,
+                        rhs,
+                    } => match (*lhs, *rhs) {
+                        (Expr::Column(lhs), Expr::Column(rhs)) => equalities.push((lhs, rhs)),
+                        _ => panic!("Expected columns on both sides of the join constraint"),
+                    },
+                    expr => panic!("Unexpected expression in join constraint: {expr:?}"),
+                }
+            }
+            assert_eq!(equalities.len(), key_columns);
+
+            let data = query.state.generate_data(100, false, false);
+            let tuples = |columns: Vec<&Column>| {
+                let table = TableName::from(columns[0].table.as_ref().unwrap().name.as_str());
+                data[&table]
+                    .iter()
+                    .map(|row| {
+                        columns
+                            .iter()
+                            .map(|column| row[&ColumnName::from(column.name.as_str())].clone())
+                            .collect::<Vec<_>>()
+                    })
+                    .collect::<HashSet<_>>()
+            };
+            let left = tuples(equalities.iter().map(|(lhs, _)| lhs).collect());
+            let right = tuples(equalities.iter().map(|(_, rhs)| rhs).collect());
+            assert!(
+                left.intersection(&right).next().is_some(),
+                "Expected some rows to match on all {key_columns} key columns"
+            );
+        }
+    }
+
+    #[test]
+    fn composite_join_keys_depend_on_every_column() {
+        for key_columns in 2..=MAX_JOIN_KEY_COLUMNS {
+            let mut gen = GeneratorState::default();
+            let mut key_tuples = |rows: usize| {
+                let table = gen.fresh_table_mut();
+                let table_name = table.name.clone();
+                let keys = table.join_key_columns(&SqlType::Int(None), key_columns);
+                gen.generate_data_for_table(&table_name, rows, false)
+                    .into_iter()
+                    .map(|row| keys.iter().map(|key| row[key].clone()).collect::<Vec<_>>())
+                    .collect::<Vec<_>>()
+            };
+            let left = key_tuples(20);
+            let right = key_tuples(10);
+
+            // The right side's tuples are a sample of the left side's
+            assert!(right.iter().all(|tuple| left.contains(tuple)));
+
+            // Joining on only a prefix of the key matches more rows than joining on all of it
+            let matches = |prefix: usize| {
+                right
+                    .iter()
+                    .map(|r| left.iter().filter(|l| l[..prefix] == r[..prefix]).count())
+                    .sum::<usize>()
+            };
+            assert_eq!(matches(key_columns), right.len());
+            assert!(
+                matches(1) > matches(key_columns),
+                "Joining on the first of {key_columns} key columns should match more rows"
+            );
+        }
+    }
+
+    #[test]
+    fn anti_join_returns_unmatched_rows() {
+        for overlap_percent in [10, 50, 90] {
+            let mut gen = GeneratorState::default();
+            let mut query = gen.generate_query(QuerySeed {
+                operations: vec![QueryOperation::AntiJoin { overlap_percent }],
+                subqueries: vec![],
+            });
+            let (left_key, right_key) = match &query.statement.join[0].constraint {
+                JoinConstraint::On(Expr::BinaryOp { lhs, rhs, .. }) => {
+                    match (lhs.as_ref(), rhs.as_ref()) {
+                        (Expr::Column(lhs), Expr::Column(rhs)) => (lhs.clone(), rhs.clone()),
+                        _ => panic!("Expected columns on both sides of the join constraint"),
+                    }
+                }
+                constraint => panic!("Expected ON constraint, got {constraint:?}"),
+            };
+            let values = |data: &HashMap<TableName, Vec<HashMap<ColumnName, DfValue>>>,
+                          column: &Column| {
+                data[&TableName::from(column.table.as_ref().unwrap().name.as_str())]
+                    .iter()
+                    .map(|row| row[&ColumnName::from(column.name.as_str())].clone())
+                    .collect::<Vec<_>>()
+            };
+
+            let data = query.state.generate_data(100, false, false);
+            let right_keys = values(&data, &right_key)
+                .into_iter()
+                .collect::<HashSet<_>>();
+            let unmatched = values(&data, &left_key)
+                .into_iter()
+                .filter(|key| !right_keys.contains(key))
+                .count();
+            assert_eq!(unmatched, 100 - usize::from(overlap_percent));
+        }
+    }
+
+    #[test]
+    fn filter_after_anti_join_skips_right_table() {
+        let mut gen = GeneratorState::default();
+        let query = gen.generate_query(QuerySeed {
+            operations: vec![
+                QueryOperation::AntiJoin {
+                    overlap_percent: 50,
+                },
+                QueryOperation::Filter(Filter {
+                    extend_where_with: LogicalOp::And,
+                    operation: FilterOp::Comparison {
+                        op: BinaryOperator::Equal,
+                        rhs: FilterRHS::Constant(Literal::Integer(1)),
+                    },
+                    column_type: SqlType::Int(None),
+                    filter_target: FilterTarget::Base,
+                }),
+                QueryOperation::SingleParameter,
+            ],
+            subqueries: vec![],
+        });
+        let right_table = match &query.statement.join[0].right {
+            JoinRightSide::Table(tbl) => tbl.inner.as_table().unwrap().name.clone(),
+            right => panic!("Expected a table on the right of the join, got {right:?}"),
+        };
+        assert!(query
+            .state
+            .parameters
+            .iter()
+            .all(|param| param.table_name.0 != right_table));
+        let sql = query.statement.display(ParseDialect::MySQL).to_string();
+        assert_eq!(
+            sql.matches(&format!("`{right_table}`.")).count(),
+            2,
+            "{sql}"
+        );
+    }
+
+    #[test]
+    fn bitwise_aggregates() {
+        for (agg, name) in [
+            (
+                AggregateType::BitAnd {
+                    column_type: SqlType::Int(None),
+                },
+                "bit_and",
+            ),
+            (
+                AggregateType::BitOr {
+                    column_type: SqlType::Int(None),
+                },
+                "bit_or",
+            ),
+            (
+                AggregateType::BitXor {
+                    column_type: SqlType::Int(None),
+                },
+                "bit_xor",
+            ),
+        ] {
+            let query = generate_query(vec![
+                QueryOperation::ColumnAggregate(agg),
+                QueryOperation::ProjectLiteral(LiteralType::Integer),
+            ]);
+            assert!(query_has_aggregate(&query));
+            assert_eq!(
+                query.display(ParseDialect::MySQL).to_string(),
+                format!(
+                    "SELECT {name}(`table_1`.`column_1`) AS `alias_1`, 1 AS `alias_2` \
+                     FROM `table_1`"
+                )
+            );
+        }
+    }
+
+    #[test]
+    fn bitwise_aggregate_data_has_flag_values() {
+        let mut gen = GeneratorState::default();
+        let mut query = gen.generate_query(QuerySeed::new(
+            vec![QueryOperation::ColumnAggregate(AggregateType::BitOr {
+                column_type: SqlType::Int(None),
+            })],
+            vec![],
+        ));
+        let data = query.state.generate_data(100, false, false);
+        let values = data[&TableName::from("table_1")]
+            .iter()
+            .map(|row| i64::try_from(&row[&ColumnName::from("column_1")]).unwrap())
+            .collect::<Vec<_>>();
+        assert!(values.iter().all(|v| (0..BIT_FLAG_VALUES).contains(v)));
+        assert!(values.iter().unique().count() > 1);
+    }
+
+    #[test]
+    fn param_topk() {
+        let query = generate_query(vec![QueryOperation::ParamTopK {
+            order_type: OrderType::OrderDescending,
+            limit: 10,
+        }]);
+        let sql = query.display(ParseDialect::MySQL).to_string();
+        assert!(
+            sql.ends_with(
+                " FROM `table_1` WHERE (`table_1`.`column_1` = ?) \
+                 ORDER BY `table_1`.`column_2` DESC LIMIT 10"
+            ),
+            "{sql}"
+        );
+    }
+
+    #[test]
+    fn parse_feed_query() {
+        let Operations(operations) = "feed_query".parse().unwrap();
+        assert_eq!(
+            operations,
+            vec![QueryOperation::ParamTopK {
+                order_type: OrderType::OrderDescending,
+                limit: DEFAULT_LIMIT,
+            }]
+        );
+    }
+
+    /// Generate data for a [`QueryOperation::ParamTopK`] query, returning the values of the order
+    /// column of all the rows matching the query's key
+    fn param_topk_key_rows(limit: u64, rows_per_table: usize) -> Vec<DfValue> {
+        let mut gen = GeneratorState::default();
+        let mut query = gen.generate_query(QuerySeed {
+            operations: vec![QueryOperation::ParamTopK {
+                order_type: OrderType::OrderDescending,
+                limit,
+            }],
+            subqueries: vec![],
+        });
+        let key = query.state.key();
+        assert_eq!(key.len(), 1);
+        let data = query.state.generate_data(rows_per_table, false, false);
+        data[&TableName::from("table_1")]
+            .iter()
+            .filter(|row| row[&ColumnName::from("column_1")] == key[0])
+            .map(|row| row[&ColumnName::from("column_2")].clone())
+            .collect()
+    }
+
+    #[test]
+    fn param_topk_rows_per_key() {
+        for limit in [1, 3, 10] {
+            let timestamps = param_topk_key_rows(limit, 100);
+            assert_eq!(
+                timestamps.len() as u64,
+                limit * PARAM_TOPK_ROWS_PER_LIMIT,
+                "limit {limit}"
+            );
+            assert_eq!(
+                timestamps.iter().unique().count(),
+                timestamps.len(),
+                "Timestamps for a key should be distinct"
+            );
+        }
+    }
+
+    #[test]
+    fn param_topk_rows_per_key_with_few_rows_per_table() {
+        // Fewer rows per table than the key needs, as with the CLI's default `--rows-per-table`
+        let limit = 3;
+        let timestamps = param_topk_key_rows(limit, 10);
+        assert_eq!(timestamps.len() as u64, limit * PARAM_TOPK_ROWS_PER_LIMIT);
+    }
+
+    #[test]
+    fn param_topk_returns_newest_rows() {
+        let limit = 3;
+        let mut timestamps = param_topk_key_rows(limit, 10);
+        timestamps.sort_by(|a, b| b.cmp(a));
+        let (newest, rest) = timestamps.split_at(limit as usize);
+
+        // The key owns the first `limit * PARAM_TOPK_ROWS_PER_LIMIT` rows, whose timestamps
+        // increase with the row index, so the newest rows for the key are the last `limit` of them
+        let num_key_rows = (limit * PARAM_TOPK_ROWS_PER_LIMIT) as u32;
+        let expected = (num_key_rows - limit as u32..num_key_rows)
+            .rev()
+            .map(|idx| unique_value_of_type(&SqlType::Timestamp, idx))
+            .collect::<Vec<_>>();
+        assert_eq!(newest, expected);
+
+        // The rows a `LIMIT` picks out must be unambiguous, so no excluded row may tie with (or be
+        // newer than) any of the rows returned
+        let oldest_returned = newest.last().unwrap();
+        assert!(!rest.is_empty());
+        assert!(rest.iter().all(|ts| ts < oldest_returned));
+    }
+
+    #[test]
+    fn json_path() {
+        let query = generate_query(vec![QueryOperation::JsonPath {
+            operator: BinaryOperator::Equal,
+        }]);
+        assert_eq!(
+            query.display(ParseDialect::MySQL).to_string(),
+            "SELECT `table_1`.`column_1` AS `alias_1` FROM `table_1` \
+             WHERE (JSON_EXTRACT(`table_1`.`column_1`, '$.value') = 42)"
+        );
+    }
+
+    #[test]
+    fn json_path_data_matches_filter() {
+        for &operator in COMPARISON_OPS {
+            let mut gen = GeneratorState::default();
+            let mut query = gen.generate_query(QuerySeed {
+                operations: vec![QueryOperation::JsonPath { operator }],
+                subqueries: vec![],
+            });
+            let data = query.state.generate_data(100, false, false);
+            // `JSON_EXTRACT` errors on values which aren't valid JSON, so every row must be
+            let json_values = data[&TableName::from("table_1")]
+                .iter()
+                .map(|row| {
+                    let json = String::try_from(&row[&ColumnName::from("column_1")]).unwrap();
+                    serde_json::from_str::<serde_json::Value>(&json)
+                        .unwrap_or_else(|e| panic!("Invalid JSON {json:?}: {e}"))
+                })
+                .collect::<Vec<_>>();
+            assert!(
+                json_values.iter().any(|json| json.get("value").is_none()),
+                "No JSON value without the path in {json_values:?}"
+            );
+            let path_values = json_values
+                .iter()
+                .filter_map(|json| json.get("value"))
+                .map(|value| value.as_i64().unwrap())
+                .collect::<Vec<_>>();
+            assert!(
+                path_values.iter().any(|&value| match operator {
+                    BinaryOperator::Equal => value == JSON_PATH_VALUE,
+                    BinaryOperator::NotEqual => value != JSON_PATH_VALUE,
+                    BinaryOperator::Greater => value > JSON_PATH_VALUE,
+                    BinaryOperator::GreaterOrEqual => value >= JSON_PATH_VALUE,
+                    BinaryOperator::Less => value < JSON_PATH_VALUE,
+                    BinaryOperator::LessOrEqual => value <= JSON_PATH_VALUE,
+                    _ => unreachable!(),
+                }),
+                "No JSON value matching {operator} {JSON_PATH_VALUE} in {path_values:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn param_plus_literal() {
+        let query = generate_query(vec![QueryOperation::ParameterWithLiteralConstraint {
+            literal_op: BinaryOperator::Greater,
+            contradictory: false,
+        }]);
+        let sql = query.display(ParseDialect::MySQL).to_string();
+        assert!(
+            sql.ends_with(
+                " FROM `table_1` \
+                 WHERE ((`table_1`.`column_1` = ?) AND (`table_1`.`column_1` > 9))"
+            ),
+            "{sql}"
+        );
+    }
+
+    #[test]
+    fn param_contradiction() {
+        let query = generate_query(vec![QueryOperation::ParameterWithLiteralConstraint {
+            literal_op: BinaryOperator::Equal,
+            contradictory: true,
+        }]);
+        let sql = query.display(ParseDialect::MySQL).to_string();
+        assert!(
+            sql.ends_with(
+                " FROM `table_1` \
+                 WHERE ((`table_1`.`column_1` = ?) AND (`table_1`.`column_1` = 11))"
+            ),
+            "{sql}"
+        );
+    }
+
+    #[test]
+    fn param_with_literal_key_matches_constraint() {
+        for contradictory in [false, true] {
+            for &literal_op in COMPARISON_OPS {
+                let mut gen = GeneratorState::default();
+                let mut query = gen.generate_query(QuerySeed {
+                    operations: vec![QueryOperation::ParameterWithLiteralConstraint {
+                        literal_op,
+                        contradictory,
+                    }],
+                    subqueries: vec![],
+                });
+                assert_eq!(query.state.expects_empty_results(), contradictory);
+
+                let key = query.state.key();
+                assert_eq!(key, vec![DfValue::from(PARAM_WITH_LITERAL_KEY)]);
+                let key = i64::try_from(&key[0]).unwrap();
+                let literal = param_with_literal_value(literal_op, contradictory).unwrap();
+                let satisfied = match literal_op {
+                    BinaryOperator::Equal => key == literal,
+                    BinaryOperator::NotEqual => key != literal,
+                    BinaryOperator::Greater => key > literal,
+                    BinaryOperator::GreaterOrEqual => key >= literal,
+                    BinaryOperator::Less => key < literal,
+                    BinaryOperator::LessOrEqual => key <= literal,
+                    _ => unreachable!(),
+                };
+                assert_eq!(satisfied, !contradictory, "{key} {literal_op} {literal}");
+
+                // The key is only useful for the satisfiable form if some rows have it
+                let data = query.state.generate_data(100, false, false);
+                assert!(data[&TableName::from("table_1")]
+                    .iter()
+                    .any(|row| row[&ColumnName::from("column_1")] == DfValue::from(key)));
+            }
+        }
+    }
+
+    #[test]
+    fn param_contradiction_with_ungrouped_aggregate_is_not_empty() {
+        let contradiction = QueryOperation::ParameterWithLiteralConstraint {
+            literal_op: BinaryOperator::Equal,
+            contradictory: true,
+        };
+        let count = QueryOperation::ColumnAggregate(AggregateType::Count {
+            column_type: SqlType::Int(None),
+            distinct: false,
+        });
+        let mut gen = GeneratorState::default();
+        let query = gen.generate_query(QuerySeed::new(vec![contradiction, count], vec![]));
+        assert!(query.statement.group_by.is_none());
+        assert!(!query.state.expects_empty_results());
+    }
+
+    #[test]
+    fn param_contradiction_in_subquery_is_not_empty() {
+        let subquery = Subquery {
+            position: SubqueryPosition::Join(JoinOperator::LeftJoin),
+            seed: QuerySeed::new(
+                vec![QueryOperation::ParameterWithLiteralConstraint {
+                    literal_op: BinaryOperator::Equal,
+                    contradictory: true,
+                }],
+                vec![],
+            ),
+        };
+        let mut gen = GeneratorState::default();
+        let query = gen.generate_query(QuerySeed::new(vec![], vec![subquery]));
+        assert!(!query.state.expects_empty_results());
+    }
+
+    #[test]
+    fn param_plus_literal_non_comparison_operator() {
+        let query = generate_query(vec![QueryOperation::ParameterWithLiteralConstraint {
+            literal_op: BinaryOperator::And,
+            contradictory: true,
+        }]);
+        let sql = query.display(ParseDialect::MySQL).to_string();
+        assert!(
+            sql.ends_with(" FROM `table_1` WHERE (`table_1`.`column_1` = ?)"),
+            "{sql}"
+        );
+    }
+
+    #[test]
+    fn parse_param_plus_literal() {
+        for (src, contradictory) in [("param_plus_literal", false), ("param_contradiction", true)] {
+            let Operations(ops) = Operations::from_str(src).unwrap();
+            assert_eq!(ops.len(), COMPARISON_OPS.len());
+            assert!(ops.iter().all(|op| matches!(
+                op,
+                QueryOperation::ParameterWithLiteralConstraint { contradictory: c, .. }
+                    if *c == contradictory
+            )));
+        }
+    }
+
+    #[test]
+    fn window_rank_function() {
+        let query = generate_query(vec![QueryOperation::WindowRankFunction {
+            func: RankFunction::RowNumber,
+            partition_by_type: SqlType::Int(None),
+            order_by_type: SqlType::Text,
+        }]);
+        assert_eq!(query.fields.len(), 3);
+        let sql = query.display(ParseDialect::MySQL).to_string();
+        assert!(sql.contains("ROW_NUMBER() OVER (PARTITION BY"), "{sql}");
+        assert!(!query_has_aggregate(&query));
+    }
+
+    #[test]
+    fn parse_window_rank() {
+        let Operations(operations) = "window_rank".parse().unwrap();
+        assert_eq!(operations.len(), ALL_RANK_FUNCTIONS.len());
+        let query = generate_query(vec![operations.last().unwrap().clone()]);
+        assert!(query
+            .display(ParseDialect::MySQL)
+            .to_string()
+            .contains("NTILE(4) OVER"));
+    }
+
+    #[test]
+    fn safe_strip_qualifications_single_table() {
+        let mut gen = GeneratorState::default();
+        let mut query = gen.generate_query(QuerySeed {
+            operations: vec![QueryOperation::SingleParameter],
+            subqueries: vec![],
+        });
+        query.strip_qualifications(StripLevel::Safe);
+        assert_eq!(query.strip_level, Some(StripLevel::Safe));
+        let mut columns = query
+            .statement
+            .where_clause
+            .as_ref()
+            .unwrap()
+            .referred_columns();
+        assert!(columns.all(|col| col.table.is_none()));
+        query.dry_run_rewrite().unwrap();
+    }
+
+    #[test]
+    fn safe_strip_qualifications_join() {
+        let mut gen = GeneratorState::default();
+        let mut query = gen.generate_query(QuerySeed {
+            operations: vec![
+                QueryOperation::Join {
+                    operator: JoinOperator::InnerJoin,
+                    constraint: JoinConstraintType::On,
+                    key_type: SqlType::Int(None),
+                    key_columns: 1,
+                },
+                QueryOperation::SingleParameter,
+            ],
+            subqueries: vec![],
+        });
+        query.shadow_table_names();
+        query.strip_qualifications(StripLevel::Safe);
+        query.dry_run_rewrite().unwrap();
+    }
+
+    #[test]
+    fn aggressive_strip_qualifications_join_is_ambiguous() {
+        let mut gen = GeneratorState::default();
+        let mut query = gen.generate_query(QuerySeed {
+            operations: vec![QueryOperation::Join {
+                operator: JoinOperator::InnerJoin,
+                constraint: JoinConstraintType::On,
+                key_type: SqlType::Int(None),
+                key_columns: 1,
+            }],
+            subqueries: vec![],
+        });
+        query.strip_qualifications(StripLevel::Aggressive);
+        let err = query.dry_run_rewrite().unwrap_err();
+        assert!(err.to_string().contains("ambiguous"), "{err}");
+    }
+
+    #[test]
+    fn double_param_uses_different_col() {
+        let query = generate_query(vec![
+            QueryOperation::SingleParameter,
+            QueryOperation::RangeParameter,
+        ]);
+        eprintln!("query: {}", query.display(ParseDialect::MySQL));
+        match &query.where_clause {
+            Some(
+                expr @ Expr::BinaryOp {
+                    lhs,
+                    op: BinaryOperator::And,
+                    rhs,
+                },
+            ) => match (lhs.as_ref(), rhs.as_ref()) {
+                (Expr::BinaryOp { lhs: lhs1, .. }, Expr::BinaryOp { lhs: lhs2, .. }) => {
+                    assert_ne!(lhs1, lhs2);
+                }
+                _ => panic!(
+                    "Unexpected where clause for query: {}",
+                    expr.display(ParseDialect::MySQL)
+                ),
+            },
+            Some(expr) => panic!(
+                "Unexpected where clause for query: {}",
+                expr.display(ParseDialect::MySQL)
+            ),
+            None => panic!("Expected query to have a where clause!"),
+        }
+    }
+
+    fn comparison_op(operation: FilterOp) -> Option<BinaryOperator> {
+        match operation {
+            FilterOp::Comparison { op, .. } => Some(op),
+            _ => None,
+        }
+    }
+
+    #[proptest]
+    fn no_like_filters_on_int_columns(
+        #[strategy(any_with::<FilterOp>(FilterRhsArgs {
+            column_type: SqlType::Int(Some(32)),
+            dialect: QueryDialect(ParseDialect::PostgreSQL),
+        }))]
+        operation: FilterOp,
+    ) {
+        if let Some(op) = comparison_op(operation) {
+            assert!(
+                !matches!(
+                    op,
+                    BinaryOperator::Like
+                        | BinaryOperator::NotLike
+                        | BinaryOperator::ILike
+                        | BinaryOperator::NotILike
+                ),
+                "Generated {op:?} filter on an INT column"
+            );
+        }
+    }
+
+    #[proptest]
+    fn only_equality_filters_on_bool_columns(
+        #[strategy(any_with::<FilterOp>(FilterRhsArgs {
+            column_type: SqlType::Bool,
+            dialect: QueryDialect::default(),
+        }))]
+        operation: FilterOp,
+    ) {
+        if let Some(op) = comparison_op(operation) {
+            assert!(
+                matches!(op, BinaryOperator::Equal | BinaryOperator::NotEqual),
+                "Generated {op:?} filter on a BOOL column"
+            );
+        }
+    }
+
+    #[proptest]
+    fn no_ilike_filters_in_mysql(
+        #[strategy(any_with::<FilterOp>(FilterRhsArgs {
+            column_type: SqlType::Text,
+            dialect: QueryDialect(ParseDialect::MySQL),
+        }))]
+        operation: FilterOp,
+    ) {
+        if let Some(op) = comparison_op(operation) {
+            assert!(
+                !matches!(op, BinaryOperator::ILike | BinaryOperator::NotILike),
+                "Generated {op:?} filter in MySQL"
+            );
         }
     }
 }
@@ -1,12 +1,47 @@
 //! A command-line interface to query_generator
 
-use anyhow::bail;
-use clap::Parser;
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, File};
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+use std::time::Duration;
+use std::{mem, thread};
+
+use anyhow::{anyhow, bail, Context};
+use clap::{Parser, Subcommand, ValueEnum};
+use database_utils::DatabaseURL;
+use itertools::Itertools;
 use nom_sql::DialectDisplay;
-use query_generator::GenerateOpts;
+use query_generator::artifact::{read_artifact, write_artifact};
+#[cfg(feature = "parallel")]
+use query_generator::parallel::generate_all_parallel_by_worker;
+use query_generator::placement::PlacementPlan;
+use query_generator::runtime::{run_queries, DatabaseDriver, RuntimeOptions};
+use query_generator::{
+    ColumnName, ConflictInjection, DataGenerationBudget, GenerateOpts, GeneratorState, Query,
+    StripLevel, TableName,
+};
+use readyset_data::DfValue;
+use serde_json::json;
+
+/// How to output generated queries
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+enum OutputMode {
+    /// Output the generated queries themselves
+    #[default]
+    Query,
+    /// Output an `EXPLAIN` statement for each generated query, using the syntax of the configured
+    /// dialect
+    Explain,
+}
 
 #[derive(Parser)]
 struct Opts {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     #[command(flatten)]
     options: GenerateOpts,
 
@@ -17,41 +52,612 @@ struct Opts {
     #[arg(long)]
     ddl_only: bool,
 
+    /// Path to a file of `CREATE TABLE` statements, separated by semicolons, to generate queries
+    /// against instead of generating new tables
+    #[arg(long)]
+    schema: Option<PathBuf>,
+
+    /// A JSON placement plan pinning operations in each generated query to specific tables and
+    /// columns, keyed by the index of each operation. See the `placement` module of the query
+    /// generator for the format.
+    #[arg(long, value_parser = parse_placement_plan)]
+    placement_plan: Option<PlacementPlan>,
+
     #[arg(long)]
     queries_only: bool,
+
+    /// Whether to output the generated queries themselves, or `EXPLAIN` statements for them
+    #[arg(long, value_enum, ignore_case = true, default_value = "query")]
+    output_mode: OutputMode,
+
+    /// Remove table qualifiers from column references in generated queries, to exercise the
+    /// passes which resolve unqualified columns
+    #[arg(long, value_enum)]
+    strip_qualifications: Option<StripLevel>,
+
+    /// Alias tables in generated queries to the names of other tables in the schema
+    #[arg(long)]
+    shadow_table_names: bool,
+
+    /// Print a warning to stderr for each parameter in a generated query that looks up a column
+    /// which isn't covered by an index
+    #[arg(long)]
+    warn_missing_indexes: bool,
+
+    /// Print each operation applied to generate each query to stderr, along with the shape of the
+    /// query immediately after applying it
+    #[arg(long)]
+    verbose: bool,
+
+    /// Output each generated query as a JSON object, along with the variants of the query which
+    /// differ only in which of their literals are parameterized, and the canonical form all the
+    /// variants should normalize to
+    #[arg(long)]
+    emit_variants: bool,
+
+    /// Also output a variant of each generated query with its filters on single tables pushed
+    /// into derived tables below its joins, if it has any. Each query and its variant are followed
+    /// by a comment ending in the stable id of the query, so they can be matched up.
+    #[arg(long)]
+    emit_pushdown_variants: bool,
+
+    /// Write a JSON object to the given path mapping the name of each generated query to a set of
+    /// Prometheus-style metric labels describing it
+    #[arg(long)]
+    emit_labels: Option<PathBuf>,
+
+    /// Write an artifact for each generated query, containing the query along with the data and
+    /// lookup keys generated for it, to a file in the given directory
+    #[arg(long)]
+    dump_artifacts: Option<PathBuf>,
+
+    /// After generating queries, generate `--rows-per-table` rows of data for each table, with the
+    /// values of foreign key columns taken from the rows of the tables they reference, and check
+    /// that they satisfy the foreign keys and primary keys of the schema, failing if they don't
+    #[arg(long)]
+    verify_data: bool,
+
+    /// When verifying data, deliberately make this many out of every 1000 rows of each table
+    /// duplicate the primary and unique key values of an earlier row, and check that exactly those
+    /// rows violate the primary key of their table
+    #[arg(long, requires = "verify_data")]
+    inject_key_conflicts: Option<u32>,
+
+    /// Make the rows injected with `--inject-key-conflicts` exact duplicates of the rows they
+    /// conflict with, rather than differing from them in a non-key column
+    #[arg(long, requires = "inject_key_conflicts")]
+    exact_key_conflicts: bool,
+
+    /// Number of rows to generate for each table when writing artifacts or validating queries, and
+    /// to assume for each table when estimating the result size of queries. More rows are
+    /// generated for tables that a query needs more rows in, such as those of `feed_query`
+    /// queries.
+    #[arg(long, default_value = "10")]
+    rows_per_table: usize,
+
+    /// Approximate maximum total size of all the rows generated when writing artifacts or
+    /// verifying data, eg `2GB`. Once it's reached, fewer than `--rows-per-table` rows (or
+    /// none at all) are generated for the remaining tables, with a warning.
+    #[arg(long, value_parser = parse_byte_size)]
+    data_budget: Option<u64>,
+
+    /// Validate each generated query by running it against both the MySQL database at the given
+    /// URL and the ReadySet instance at `--readyset-dsn`, and comparing the results.
+    ///
+    /// The ReadySet instance must not be replicating from this database.
+    #[arg(long, requires = "readyset_dsn")]
+    validate_against_mysql: Option<DatabaseURL>,
+
+    /// URL of the ReadySet instance to validate generated queries against
+    #[arg(long, requires = "validate_against_mysql")]
+    readyset_dsn: Option<DatabaseURL>,
+
+    /// Directory to write an artifact to for each query that fails validation, which can be
+    /// replayed with the `replay` subcommand
+    #[arg(long, default_value = "tests/regressions")]
+    regressions_dir: PathBuf,
+
+    /// Maximum number of seconds to spend creating the tables for, loading the data for, and
+    /// caching each query when validating, before giving up on the query
+    #[arg(long, default_value = "60")]
+    install_timeout: u64,
+
+    /// Maximum number of seconds to spend running each query when validating, before giving up on
+    /// the query
+    #[arg(long, default_value = "30")]
+    query_timeout: u64,
+
+    /// Reconnect to both databases before validating the next query whenever a query times out or
+    /// panics, in case the previous query left either connection in a bad state
+    #[arg(long)]
+    restart_on_failure: bool,
+
+    /// Write a JSON summary of the outcome of validating each query to the given path, including
+    /// the seed of every query which didn't complete
+    #[arg(long)]
+    validation_summary: Option<PathBuf>,
+
+    /// Number of threads to generate queries and their data on when validating. Each thread
+    /// generates queries with its own generator state. Ignored unless built with the `parallel`
+    /// feature.
+    #[arg(long, default_value = "1")]
+    parallel: usize,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Replay an artifact written with `--dump-artifacts`, by creating its tables, loading its
+    /// data, and running its query once for each of its stored keys
+    Replay(Replay),
+}
+
+#[derive(Parser)]
+struct Replay {
+    /// Path to the artifact file to replay
+    artifact: PathBuf,
+
+    /// URL of the database to replay the artifact against
+    #[arg(long)]
+    database_url: DatabaseURL,
+}
+
+impl Replay {
+    pub fn run(self) -> anyhow::Result<()> {
+        let artifact = read_artifact(&self.artifact)?;
+        let dialect = self.database_url.dialect();
+        let results = tokio::runtime::Runtime::new()?.block_on(async {
+            let mut conn = self.database_url.connect(None).await?;
+            artifact.replay(&mut conn, dialect).await
+        })?;
+
+        println!("{}", artifact.query.display(dialect));
+        for (i, rows) in results.into_iter().enumerate() {
+            println!(
+                "Key: {:?}",
+                artifact.keys.get(i).cloned().unwrap_or_default()
+            );
+            for row in rows {
+                println!("{}", row.iter().join(" | "));
+            }
+        }
+
+        Ok(())
+    }
 }
 
 impl Opts {
     pub fn run(self) -> anyhow::Result<()> {
+        if let Some(Command::Replay(replay)) = self.command {
+            return replay.run();
+        }
+        if let (Some(reference_url), Some(readyset_url)) =
+            (&self.validate_against_mysql, &self.readyset_dsn)
+        {
+            return self.validate(reference_url.clone(), readyset_url.clone());
+        }
+
         if self.ddl_only && self.queries_only {
             bail!("Cannot specify both --ddl-only and --queries-only")
         }
-        let mut gen = query_generator::GeneratorState::default();
-        let queries = self
-            .options
-            .into_query_seeds()
-            .map(|seed| gen.generate_query(seed).statement);
+        let mut gen = match &self.schema {
+            Some(path) => read_schema(path, self.dialect)?,
+            None => GeneratorState::default(),
+        };
+        let placement_plan = self.placement_plan;
+        let dialect = self.dialect;
+        let output_mode = self.output_mode;
+        let strip_qualifications = self.strip_qualifications;
+        let shadow_table_names = self.shadow_table_names;
+        let warn_missing_indexes = self.warn_missing_indexes;
+        let verbose = self.verbose;
+        gen.set_trace_operations(verbose);
+        gen.set_data_budget(DataGenerationBudget {
+            max_bytes_total: self.data_budget,
+            max_bytes_per_row: None,
+        });
+        let emit_variants = self.emit_variants;
+        let emit_pushdown_variants = self.emit_pushdown_variants;
+        let rows_per_table = self.rows_per_table;
+        let dump_artifacts = self.dump_artifacts;
+        let emit_labels = self.emit_labels;
+        let options = self.options;
+        let mut labels = BTreeMap::new();
+        if let Some(dir) = &dump_artifacts {
+            fs::create_dir_all(dir)?;
+        }
+        let queries = options.clone().into_query_seeds().enumerate().map(
+            |(i, seed)| -> anyhow::Result<Option<String>> {
+                let mut query = match &placement_plan {
+                    Some(plan) => gen
+                        .generate_query_with_plan(seed, plan)
+                        .with_context(|| format!("Invalid placement plan for query {i}"))?,
+                    None => gen.generate_query(seed),
+                };
+                if shadow_table_names {
+                    query.shadow_table_names();
+                }
+                if let Some(level) = strip_qualifications {
+                    query.strip_qualifications(level);
+                }
+                let result_size = query.estimate_result_size(rows_per_table);
+                if !options.admits(&result_size) {
+                    return Ok(None);
+                }
+                if verbose {
+                    eprintln!("query {i}:");
+                    for entry in query.trace_log() {
+                        eprintln!("  {entry}");
+                    }
+                }
+                if warn_missing_indexes {
+                    for (table, column) in query.state.missing_indexes() {
+                        eprintln!(
+                            "warning: query {i} would benefit from an index on {table}.{column}"
+                        );
+                    }
+                }
+                if emit_labels.is_some() {
+                    labels.insert(format!("query_{i}"), query.metric_labels());
+                }
+                if let Some(dir) = &dump_artifacts {
+                    let keys = [query.state.key()];
+                    let data = query.state.generate_data(rows_per_table, false, false);
+                    write_artifact(dir.join(format!("query_{i}.bin")), &query, &data, &keys)?;
+                }
+                let render = |query: &Query| match output_mode {
+                    OutputMode::Query => query.statement.display(dialect).to_string(),
+                    OutputMode::Explain => query.generate_explain_for_dialect(dialect.into()),
+                };
+                let output = render(&query);
+                let pushdown_output = emit_pushdown_variants
+                    .then(|| query.pushdown_variant())
+                    .flatten()
+                    .map(|variant| {
+                        let statement = mem::replace(&mut query.statement, variant);
+                        let variant_output = render(&query);
+                        query.statement = statement;
+                        variant_output
+                    });
+                if !emit_variants {
+                    if !emit_pushdown_variants {
+                        return Ok(Some(output));
+                    }
+                    let stable_id = query.stable_id();
+                    let mut lines = vec![format!("{output} -- flat_{stable_id}")];
+                    lines.extend(
+                        pushdown_output.map(|variant| format!("{variant} -- pushdown_{stable_id}")),
+                    );
+                    return Ok(Some(lines.join("\n")));
+                }
+                let variants = query
+                    .parameterization_variants(dialect)?
+                    .into_iter()
+                    .map(|variant| {
+                        json!({
+                            "kind": format!("{:?}", variant.kind),
+                            "query": variant.statement.display(dialect).to_string(),
+                            "key": variant.key.iter().map(|v| v.to_string()).collect::<Vec<_>>(),
+                            "canonical": variant.canonical.display(dialect).to_string(),
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                Ok(Some(
+                    json!({
+                        "query": output,
+                        "variants": variants,
+                        "estimated_result_size": result_size,
+                        "pushdown_variant": pushdown_output,
+                        "stable_id": query.stable_id(),
+                    })
+                    .to_string(),
+                ))
+            },
+        );
 
         if self.queries_only {
             for query in queries {
-                println!("{}", query.display(self.dialect));
+                if let Some(query) = query? {
+                    println!("{}", query);
+                }
             }
         } else {
-            let queries = queries.collect::<Vec<_>>();
+            let queries = queries.collect::<anyhow::Result<Vec<_>>>()?;
             for create_table_statement in gen.ddl() {
                 println!("{}", create_table_statement.display(self.dialect))
             }
             if !self.ddl_only {
-                for query in queries {
-                    println!("{}", query.display(self.dialect));
+                for query in queries.into_iter().flatten() {
+                    println!("{}", query);
                 }
             }
         }
 
+        if let Some(path) = emit_labels {
+            serde_json::to_writer_pretty(BufWriter::new(File::create(path)?), &labels)?;
+        }
+
+        if self.verify_data {
+            // Generate data for the tables referenced by foreign keys first, so that the foreign
+            // keys of the tables referencing them can be filled in from the rows generated
+            let mut data = HashMap::new();
+            for table in gen.tables_in_foreign_key_order() {
+                let mut rows = match self.inject_key_conflicts {
+                    Some(unique_key_violations_per_1000) => {
+                        let (rows, conflicts) = gen.generate_data_for_table_with_conflicts(
+                            &table,
+                            rows_per_table,
+                            false,
+                            ConflictInjection {
+                                unique_key_violations_per_1000,
+                                exact_duplicates: self.exact_key_conflicts,
+                            },
+                        );
+                        verify_key_conflicts(&gen, &table, &rows, &conflicts)?;
+                        remove_rows(rows, &conflicts)
+                    }
+                    None => gen.generate_data_for_table(&table, rows_per_table, false),
+                };
+                gen.fill_foreign_keys(&table, &mut rows, &data);
+                data.insert(table, rows);
+            }
+            let report = gen.check_referential_integrity(&data);
+            if !report.is_ok() {
+                eprint!("{report}");
+                bail!("Generated data violates the integrity constraints of the schema");
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Validate each generated query against the given MySQL database and ReadySet instance,
+    /// writing an artifact for each query that fails validation to [`Self::regressions_dir`].
+    ///
+    /// Queries and their data are generated on a separate thread (or threads, with
+    /// [`Self::parallel`]) while earlier queries are being validated, and only a bounded number of
+    /// them are generated ahead of the query being validated, so that the data for every query
+    /// isn't held in memory at once.
+    ///
+    /// Each query is isolated from the rest of the run according to [`Self::install_timeout`],
+    /// [`Self::query_timeout`], and [`Self::restart_on_failure`], and a summary of the outcome of
+    /// each query is printed at the end of the run.
+    fn validate(self, reference_url: DatabaseURL, readyset_url: DatabaseURL) -> anyhow::Result<()> {
+        let rows_per_table = self.rows_per_table;
+        let regressions_dir = self.regressions_dir;
+        let validation_summary = self.validation_summary;
+        let options = RuntimeOptions {
+            install_timeout: Duration::from_secs(self.install_timeout),
+            query_timeout: Duration::from_secs(self.query_timeout),
+            restart_on_failure: self.restart_on_failure,
+        };
+        let verbose = self.verbose;
+        let shadow_table_names = self.shadow_table_names;
+        let strip_qualifications = self.strip_qualifications;
+        let parallel = self.parallel;
+        let mut gen = GeneratorState::default();
+        gen.set_trace_operations(verbose);
+        let filter = self.options.clone();
+        let opts = self.options;
+        let runtime = tokio::runtime::Runtime::new()?;
+
+        // Generation blocks once this many queries are waiting to be validated
+        let (tx, rx) = mpsc::sync_channel(parallel.max(1));
+        // Set once validation has finished, so that any remaining queries aren't generated
+        let stopped = AtomicBool::new(false);
+        let summary = thread::scope(|s| -> anyhow::Result<_> {
+            let generator = s.spawn(|| {
+                let res = generate_queries(opts, &gen, parallel, |mut query| {
+                    if stopped.load(Ordering::Relaxed) {
+                        return;
+                    }
+                    if shadow_table_names {
+                        query.shadow_table_names();
+                    }
+                    if let Some(level) = strip_qualifications {
+                        query.strip_qualifications(level);
+                    }
+                    if !filter.admits(&query.estimate_result_size(rows_per_table)) {
+                        return;
+                    }
+                    // Queries may be generated on multiple threads, so the trace is printed
+                    // when the query is validated to keep the traces of different queries from
+                    // interleaving
+                    let trace = query
+                        .trace_log()
+                        .iter()
+                        .map(|e| e.to_string())
+                        .collect::<Vec<_>>();
+                    // If validation stopped early there's nothing left to send the query to
+                    let _ = tx.send((query.seed.clone(), query.to_artifact(rows_per_table), trace));
+                });
+                // Dropping the sender once every query has been generated ends validation
+                drop(tx);
+                res
+            });
+
+            let queries = rx
+                .into_iter()
+                .enumerate()
+                .map(|(i, (seed, artifact, trace))| {
+                    if verbose {
+                        eprintln!("query {i}:");
+                        for entry in trace {
+                            eprintln!("  {entry}");
+                        }
+                    }
+                    (seed, artifact)
+                });
+            let summary = runtime.block_on(async {
+                let mut driver = DatabaseDriver::connect(reference_url, readyset_url).await?;
+                run_queries(&mut driver, queries, options).await
+            });
+            stopped.store(true, Ordering::Relaxed);
+            generator
+                .join()
+                .map_err(|_| anyhow!("Query generation panicked"))??;
+            summary
+        })?;
+        let num_queries = summary.results.len();
+
+        let mut num_failures = 0;
+        for failure in summary.validation_failures() {
+            num_failures += 1;
+            fs::create_dir_all(&regressions_dir)?;
+            let path = regressions_dir.join(format!("{}.bin", failure.query_hash()));
+            failure
+                .artifact
+                .write(BufWriter::new(File::create(&path)?))?;
+            println!("{failure}");
+            println!("Wrote artifact to {}", path.display());
+        }
+
+        print!("{summary}");
+        if let Some(path) = validation_summary {
+            serde_json::to_writer_pretty(BufWriter::new(File::create(path)?), &summary.to_json())?;
+        }
+
+        let num_incomplete = summary.incomplete().count();
+        if num_failures > 0 || num_incomplete > 0 {
+            bail!(
+                "{num_failures} of {num_queries} queries failed validation, and \
+                 {num_incomplete} did not complete"
+            );
+        }
+        println!("All {num_queries} queries passed validation");
         Ok(())
     }
 }
 
+fn parse_placement_plan(s: &str) -> Result<PlacementPlan, serde_json::Error> {
+    serde_json::from_str(s)
+}
+
+/// Parse a number of bytes, optionally followed by one of the (binary) units `KB`, `MB`, `GB` or
+/// `TB`, eg `2GB`
+fn parse_byte_size(s: &str) -> anyhow::Result<u64> {
+    let (n, unit) = s.split_at(s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len()));
+    let multiplier: u64 = match unit.trim().to_ascii_uppercase().as_str() {
+        "" | "B" => 1,
+        "KB" => 1 << 10,
+        "MB" => 1 << 20,
+        "GB" => 1 << 30,
+        "TB" => 1 << 40,
+        _ => bail!("Invalid unit {unit:?} in byte size {s:?}"),
+    };
+    n.parse::<u64>()
+        .with_context(|| format!("Invalid byte size {s:?}"))?
+        .checked_mul(multiplier)
+        .ok_or_else(|| anyhow!("Byte size {s:?} is too large"))
+}
+
+/// Read a generator state from a file of `CREATE TABLE` statements separated by semicolons
+fn read_schema(path: &Path, dialect: nom_sql::Dialect) -> anyhow::Result<GeneratorState> {
+    let ddl = fs::read_to_string(path)?;
+    let stmts = ddl
+        .split(';')
+        .filter(|stmt| !stmt.trim().is_empty())
+        .map(|stmt| nom_sql::parse_create_table(dialect, stmt).map_err(|e| anyhow!(e)))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    Ok(GeneratorState::from(stmts))
+}
+
+/// Check that the rows of `table` generated with `--inject-key-conflicts` violate its primary key
+/// exactly at the injected `conflicts`, and report the injected rows on stderr
+fn verify_key_conflicts(
+    gen: &GeneratorState,
+    table: &TableName,
+    rows: &[HashMap<ColumnName, DfValue>],
+    conflicts: &[usize],
+) -> anyhow::Result<()> {
+    eprintln!(
+        "Injected {} key conflicts into {table} at rows [{}]",
+        conflicts.len(),
+        conflicts.iter().join(", ")
+    );
+
+    let has_primary_key = gen
+        .table(table)
+        .map_or(false, |spec| spec.primary_key.is_some());
+    if !has_primary_key {
+        eprintln!(
+            "warning: {table} has no primary key, so the injected key conflicts can't be verified"
+        );
+        return Ok(());
+    }
+
+    let report = gen.check_referential_integrity(&HashMap::from([(table.clone(), rows.to_vec())]));
+    let num_duplicates = report
+        .duplicate_primary_keys
+        .iter()
+        .find(|duplicates| duplicates.table == *table)
+        .map_or(0, |duplicates| duplicates.count);
+    if num_duplicates != conflicts.len() {
+        bail!(
+            "Expected {} rows of {table} to duplicate an earlier primary key, but found {}",
+            conflicts.len(),
+            num_duplicates
+        );
+    }
+    Ok(())
+}
+
+/// Remove the rows at the given (ascending) indices from `rows`
+fn remove_rows(
+    rows: Vec<HashMap<ColumnName, DfValue>>,
+    indices: &[usize],
+) -> Vec<HashMap<ColumnName, DfValue>> {
+    let mut indices = indices.iter().peekable();
+    rows.into_iter()
+        .enumerate()
+        .filter(|(i, _)| indices.next_if_eq(&i).is_none())
+        .map(|(_, row)| row)
+        .collect()
+}
+
+/// Generate a query for each of the seeds from `opts` on a thread pool with `parallelism` threads,
+/// each with its own clone of `base_state`, returning the result of calling `f` with each query in
+/// the same order as the seeds
+#[cfg(feature = "parallel")]
+fn generate_queries<F, T>(
+    opts: GenerateOpts,
+    base_state: &GeneratorState,
+    parallelism: usize,
+    f: F,
+) -> anyhow::Result<Vec<T>>
+where
+    F: Fn(Query) -> T + Sync,
+    T: Send,
+{
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(parallelism)
+        .build()?;
+    let (_, results) =
+        pool.install(|| generate_all_parallel_by_worker(opts, base_state, |_, query| f(query)));
+    Ok(results)
+}
+
+/// Generate a query for each of the seeds from `opts` on the current thread, using a clone of
+/// `base_state`, returning the result of calling `f` with each query in the same order as the seeds
+#[cfg(not(feature = "parallel"))]
+fn generate_queries<F, T>(
+    opts: GenerateOpts,
+    base_state: &GeneratorState,
+    parallelism: usize,
+    f: F,
+) -> anyhow::Result<Vec<T>>
+where
+    F: Fn(Query) -> T,
+{
+    if parallelism > 1 {
+        eprintln!("warning: built without the `parallel` feature, ignoring --parallel");
+    }
+    let mut gen = base_state.clone();
+    Ok(opts
+        .into_query_seeds()
+        .map(|seed| f(gen.generate_query(seed)))
+        .collect())
+}
+
 fn main() -> anyhow::Result<()> {
     let opts = Opts::parse();
     opts.run()
@@ -0,0 +1,330 @@
+//! Exhaustively generating queries on multiple threads, with [rayon].
+//!
+//! The seeds from a [`GenerateOpts`] are independent of each other, so generating queries from them
+//! can be spread across the threads of a rayon thread pool with [`generate_all_parallel`] (or
+//! [`generate_all_parallel_by_worker`], which also collects a result for each query). Each
+//! worker generates queries from a contiguous chunk of the seeds, using its own clone of a base
+//! [`GeneratorState`], so no locking is needed on the table name counter. This means that the
+//! names of tables are only unique *per worker* - two queries generated by different workers may
+//! use different tables with the same name. [`Recipe::prefix_table_names`] can be used to restore
+//! global uniqueness when combining the schemas of queries generated by different workers.
+//!
+//! Given the same seeds, base state, and number of threads in the pool, each query generated is
+//! the same from run to run - only the order in which queries are passed to the callback differs.
+//!
+//! This module is only available with the `parallel` feature.
+
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+use nom_sql::analysis::visit_mut::{walk_table_expr, VisitorMut};
+use nom_sql::{CreateTableStatement, Relation, SelectStatement, SqlIdentifier, TableExpr};
+use rayon::prelude::*;
+
+use crate::{GenerateOpts, GeneratorState, Query};
+
+/// A summary of a run of [`generate_all_parallel`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GenerationSummary {
+    /// The number of queries generated by each worker, indexed by worker id
+    pub queries_per_worker: Vec<usize>,
+    /// The total wall-clock time taken to generate all the queries, including the time spent in
+    /// the callback
+    pub wall_time: Duration,
+}
+
+impl GenerationSummary {
+    /// Returns the total number of queries generated by all workers
+    pub fn total_queries(&self) -> usize {
+        self.queries_per_worker.iter().sum()
+    }
+}
+
+/// Generate a query for each of the seeds from `opts` on the current rayon thread pool, passing
+/// each one to `f`.
+///
+/// The seeds are split into one contiguous chunk per thread in the pool, and each chunk is
+/// processed by a worker with its own clone of `base_state`. See the [module
+/// documentation](self) for what this means for the names of tables.
+pub fn generate_all_parallel<F>(
+    opts: GenerateOpts,
+    base_state: &GeneratorState,
+    f: F,
+) -> GenerationSummary
+where
+    F: Fn(Query) + Sync,
+{
+    generate_all_parallel_by_worker(opts, base_state, |_, query| f(query)).0
+}
+
+/// Like [`generate_all_parallel`], but also passes `f` the id of the worker which generated each
+/// query (for example to construct a [`Recipe`] for the query), and returns the results of `f` in
+/// the same order as the seeds they were generated from.
+pub fn generate_all_parallel_by_worker<F, T>(
+    opts: GenerateOpts,
+    base_state: &GeneratorState,
+    f: F,
+) -> (GenerationSummary, Vec<T>)
+where
+    F: Fn(usize, Query) -> T + Sync,
+    T: Send,
+{
+    let start = Instant::now();
+    let seeds = opts.into_query_seeds().collect::<Vec<_>>();
+    let chunk_size = seeds
+        .len()
+        .div_ceil(rayon::current_num_threads().max(1))
+        .max(1);
+
+    let results_per_worker = seeds
+        .par_chunks(chunk_size)
+        .enumerate()
+        .map(|(worker, seeds)| {
+            let mut gen = base_state.clone();
+            seeds
+                .iter()
+                .map(|seed| f(worker, gen.generate_query(seed.clone())))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    let summary = GenerationSummary {
+        queries_per_worker: results_per_worker.iter().map(Vec::len).collect(),
+        wall_time: start.elapsed(),
+    };
+    (summary, results_per_worker.into_iter().flatten().collect())
+}
+
+/// A generated query, along with the `CREATE TABLE` statements for all the tables known to the
+/// worker that generated it at the time
+#[derive(Debug, Clone, PartialEq)]
+pub struct Recipe {
+    /// The id of the worker that generated the query
+    pub worker: usize,
+    /// `CREATE TABLE` statements for all the tables known to the worker
+    pub create_tables: Vec<CreateTableStatement>,
+    /// The query itself
+    pub query: SelectStatement,
+}
+
+impl Recipe {
+    /// Construct a new [`Recipe`] for a query generated by the given worker
+    pub fn new(worker: usize, query: &Query) -> Self {
+        Self {
+            worker,
+            create_tables: query.state.gen.ddl().collect(),
+            query: query.statement.clone(),
+        }
+    }
+
+    /// Prefix the name of each of the tables in this recipe with `w<worker>_`, both in the
+    /// `CREATE TABLE` statements and everywhere they're referenced in the query, so that tables
+    /// created by different workers never have the same name.
+    pub fn prefix_table_names(&mut self) {
+        let mut prefixer = PrefixTableNames {
+            prefix: format!("w{}_", self.worker),
+            tables: self
+                .create_tables
+                .iter()
+                .map(|create_table| create_table.table.name.clone())
+                .collect(),
+        };
+
+        for create_table in &mut self.create_tables {
+            let Ok(()) = prefixer.visit_create_table_statement(create_table);
+        }
+        let Ok(()) = prefixer.visit_select_statement(&mut self.query);
+    }
+}
+
+/// Visitor which prefixes every reference to one of a set of tables, including as a table alias
+struct PrefixTableNames {
+    prefix: String,
+    tables: HashSet<SqlIdentifier>,
+}
+
+impl PrefixTableNames {
+    fn prefixed(&self, name: &SqlIdentifier) -> Option<SqlIdentifier> {
+        self.tables
+            .contains(name)
+            .then(|| format!("{}{name}", self.prefix).into())
+    }
+}
+
+impl<'ast> VisitorMut<'ast> for PrefixTableNames {
+    type Error = !;
+
+    fn visit_table(&mut self, table: &'ast mut Relation) -> Result<(), Self::Error> {
+        if table.schema.is_none() {
+            if let Some(name) = self.prefixed(&table.name) {
+                table.name = name;
+            }
+        }
+        Ok(())
+    }
+
+    fn visit_table_expr(&mut self, table_expr: &'ast mut TableExpr) -> Result<(), Self::Error> {
+        // Tables may be aliased to the names of other tables, by `Query::shadow_table_names`
+        if let Some(alias) = &mut table_expr.alias {
+            if let Some(name) = self.prefixed(alias) {
+                *alias = name;
+            }
+        }
+        walk_table_expr(self, table_expr)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::ops::Bound;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use nom_sql::{Dialect, DialectDisplay, OrderType};
+
+    use super::*;
+    use crate::{QueryOperation, QuerySeed};
+
+    fn opts(num_limits: u64) -> GenerateOpts {
+        GenerateOpts {
+            operations: Some(
+                vec![(1..=num_limits)
+                    .map(|limit| QueryOperation::TopK {
+                        order_type: OrderType::OrderAscending,
+                        limit,
+                    })
+                    .collect()]
+                .into(),
+            ),
+            subquery_depth: 0,
+            num_operations: Some(vec![(Bound::Included(2), Bound::Included(2))]),
+            include_cte_reuse: false,
+            result_size_range: None,
+        }
+    }
+
+    fn pool(num_threads: usize) -> rayon::ThreadPool {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(num_threads)
+            .build()
+            .unwrap()
+    }
+
+    fn generate_recipes(num_threads: usize, num_limits: u64) -> Vec<(QuerySeed, Recipe)> {
+        let (summary, recipes) = pool(num_threads).install(|| {
+            generate_all_parallel_by_worker(opts(num_limits), &GeneratorState::default(), |w, q| {
+                (q.seed.clone(), Recipe::new(w, &q))
+            })
+        });
+        assert_eq!(summary.total_queries(), recipes.len());
+        recipes
+    }
+
+    #[test]
+    fn summary_counts_per_worker() {
+        let num_queries = AtomicUsize::new(0);
+        let summary = pool(4).install(|| {
+            generate_all_parallel(opts(20), &GeneratorState::default(), |_| {
+                num_queries.fetch_add(1, Ordering::Relaxed);
+            })
+        });
+        // 20 choose 2
+        assert_eq!(summary.total_queries(), 190);
+        assert_eq!(num_queries.into_inner(), 190);
+        assert!(summary.queries_per_worker.len() <= 4);
+        for count in &summary.queries_per_worker {
+            assert!(*count > 0 && *count <= 190_usize.div_ceil(4), "{summary:?}");
+        }
+    }
+
+    #[test]
+    fn results_are_in_seed_order() {
+        let seeds = opts(20).into_query_seeds().collect::<Vec<_>>();
+        let (_, results) = pool(4).install(|| {
+            generate_all_parallel_by_worker(opts(20), &GeneratorState::default(), |_, q| q.seed)
+        });
+        assert_eq!(results, seeds);
+    }
+
+    #[test]
+    fn queries_are_deterministic() {
+        let sql = |recipes: Vec<(QuerySeed, Recipe)>| {
+            recipes
+                .into_iter()
+                .map(|(seed, recipe)| {
+                    (
+                        format!("{seed:?}"),
+                        recipe.query.display(Dialect::MySQL).to_string(),
+                    )
+                })
+                .collect::<HashMap<_, _>>()
+        };
+        assert_eq!(sql(generate_recipes(4, 20)), sql(generate_recipes(4, 20)));
+    }
+
+    #[test]
+    fn prefixed_table_names_are_unique_across_workers() {
+        let mut recipes = generate_recipes(4, 20)
+            .into_iter()
+            .map(|(_, recipe)| recipe)
+            .collect::<Vec<_>>();
+
+        let owners = |recipes: &[Recipe]| {
+            let mut owners: HashMap<SqlIdentifier, HashSet<usize>> = HashMap::new();
+            for recipe in recipes {
+                for create_table in &recipe.create_tables {
+                    owners
+                        .entry(create_table.table.name.clone())
+                        .or_default()
+                        .insert(recipe.worker);
+                }
+            }
+            owners
+        };
+
+        // Every worker starts from the same base state, so they all create `table_1`
+        assert_eq!(owners(&recipes)[&SqlIdentifier::from("table_1")].len(), 4);
+
+        for recipe in &mut recipes {
+            recipe.prefix_table_names();
+        }
+        for (table, workers) in owners(&recipes) {
+            assert_eq!(workers.len(), 1, "{table} created by workers {workers:?}");
+        }
+        for recipe in &recipes {
+            let query = recipe.query.display(Dialect::MySQL).to_string();
+            let prefix = format!("`w{}_table_", recipe.worker);
+            assert!(query.contains(&prefix), "{query}");
+            assert!(!query.contains("`table_"), "{query}");
+        }
+    }
+
+    #[test]
+    #[ignore = "Benchmark; requires at least 4 cores"]
+    fn parallel_speedup() {
+        assert!(std::thread::available_parallelism().unwrap().get() >= 4);
+        // 142 choose 2 = 10011 seeds
+        let time = |num_threads| {
+            pool(num_threads).install(|| {
+                generate_all_parallel(opts(142), &GeneratorState::default(), |q| {
+                    q.statement.display(Dialect::MySQL).to_string();
+                })
+            })
+        };
+
+        let sequential = time(1);
+        let parallel = time(4);
+        assert_eq!(sequential.total_queries(), 10011);
+        assert_eq!(parallel.total_queries(), 10011);
+        eprintln!(
+            "sequential: {:?}, parallel: {:?}",
+            sequential.wall_time, parallel.wall_time
+        );
+        assert!(
+            parallel.wall_time * 2 < sequential.wall_time,
+            "expected >2x speedup, got {:?} vs {:?}",
+            parallel.wall_time,
+            sequential.wall_time
+        );
+    }
+}
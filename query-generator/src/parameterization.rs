@@ -0,0 +1,432 @@
+//! Generating variants of queries which differ only in which of their literals are parameterized,
+//! for testing the normalization of queries to cache keys.
+//!
+//! ReadySet's adapter auto-parameterizes literals in incoming queries so that they match the
+//! parameterized form of a cached query. To test that logic (or any other normalizer), each
+//! [`ParameterizationVariant`] of a query carries a *canonical form* - the query with every
+//! placeholder replaced by the literal value of its key - which the variant must be equivalent to
+//! once its own key is bound.
+
+use anyhow::anyhow;
+use nom_sql::analysis::visit_mut::VisitorMut;
+use nom_sql::{Dialect, Expr, ItemPlaceholder, Literal, SelectStatement};
+use readyset_data::DfValue;
+
+use crate::{ParameterMode, Query};
+
+/// The way in which a [`ParameterizationVariant`] differs from the query it was generated from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VariantKind {
+    /// Every placeholder in the query is replaced with the literal value of its key
+    Literal,
+    /// Only the literal at the given position is parameterized, out of several literals in the
+    /// query with the same value. Positions count the literals in `WHERE` clauses of the
+    /// canonical form of the query, in order.
+    SingleParameter { position: usize },
+    /// Every placeholder in the query is replaced with the literal value of its key, and every
+    /// integer literal in a `WHERE` clause is written as a quoted string (eg `'1'` rather than
+    /// `1`)
+    QuotedIntegers,
+    /// The placeholders in the query are written using the given parameter mode, which is the
+    /// one accepted by the dialect the variants were generated for. Only generated if the query
+    /// uses a different style.
+    PlaceholderStyle { mode: ParameterMode },
+}
+
+/// A statement which is semantically identical to a generated query, but differs from it in which
+/// of its literals are parameterized or how they're written.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParameterizationVariant {
+    pub kind: VariantKind,
+    /// The variant statement itself
+    pub statement: SelectStatement,
+    /// The values to bind to the placeholders in [`Self::statement`]
+    pub key: Vec<DfValue>,
+    /// The form every variant of the same query should normalize to: the query with every
+    /// placeholder replaced by the literal value of its key, and with integer literals unquoted
+    pub canonical: SelectStatement,
+}
+
+/// [`VisitorMut`] which replaces placeholders with the literal values of the corresponding
+/// elements of a key
+struct BindPlaceholders<'a> {
+    key: &'a [DfValue],
+    next_positional: usize,
+}
+
+impl<'ast, 'a> VisitorMut<'ast> for BindPlaceholders<'a> {
+    type Error = anyhow::Error;
+
+    fn visit_literal(&mut self, literal: &'ast mut Literal) -> Result<(), Self::Error> {
+        let Literal::Placeholder(placeholder) = literal else {
+            return Ok(());
+        };
+        let idx = key_index(placeholder, &mut self.next_positional);
+        let value = self
+            .key
+            .get(idx)
+            .ok_or_else(|| anyhow!("No value in key for placeholder {}", idx + 1))?;
+        *literal = Literal::try_from(value.clone())?;
+        Ok(())
+    }
+}
+
+/// [`VisitorMut`] which rewrites placeholders to use the placeholder style of a [`ParameterMode`],
+/// recording the index in the original key of the value for each rewritten placeholder, in order
+struct RestylePlaceholders {
+    mode: ParameterMode,
+    next_positional: usize,
+    key_indices: Vec<usize>,
+}
+
+impl<'ast> VisitorMut<'ast> for RestylePlaceholders {
+    type Error = !;
+
+    fn visit_literal(&mut self, literal: &'ast mut Literal) -> Result<(), Self::Error> {
+        let Literal::Placeholder(placeholder) = literal else {
+            return Ok(());
+        };
+        self.key_indices
+            .push(key_index(placeholder, &mut self.next_positional));
+        *placeholder = match self.mode {
+            ParameterMode::Positional => ItemPlaceholder::QuestionMark,
+            ParameterMode::Numbered => {
+                ItemPlaceholder::DollarNumber(self.key_indices.len().try_into().unwrap())
+            }
+        };
+        Ok(())
+    }
+}
+
+/// [`VisitorMut`] which calls a function with the position and value of every literal in a
+/// `WHERE` clause (including the `WHERE` clauses of subqueries)
+struct WhereLiterals<F> {
+    f: F,
+    position: usize,
+    where_depth: usize,
+}
+
+impl<F> WhereLiterals<F> {
+    fn new(f: F) -> Self {
+        Self {
+            f,
+            position: 0,
+            where_depth: 0,
+        }
+    }
+}
+
+impl<'ast, F> VisitorMut<'ast> for WhereLiterals<F>
+where
+    F: FnMut(usize, &mut Literal),
+{
+    type Error = !;
+
+    fn visit_where_clause(&mut self, expr: &'ast mut Expr) -> Result<(), Self::Error> {
+        self.where_depth += 1;
+        self.visit_expr(expr)?;
+        self.where_depth -= 1;
+        Ok(())
+    }
+
+    fn visit_literal(&mut self, literal: &'ast mut Literal) -> Result<(), Self::Error> {
+        if self.where_depth > 0 {
+            (self.f)(self.position, literal);
+            self.position += 1;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the index in the key of the value for the given placeholder, given the number of
+/// positional placeholders seen so far
+fn key_index(placeholder: &ItemPlaceholder, next_positional: &mut usize) -> usize {
+    match placeholder {
+        ItemPlaceholder::QuestionMark => {
+            *next_positional += 1;
+            *next_positional - 1
+        }
+        ItemPlaceholder::DollarNumber(n) | ItemPlaceholder::ColonNumber(n) => {
+            (*n as usize).saturating_sub(1)
+        }
+    }
+}
+
+/// Returns true if the given literal can be replaced with a placeholder without changing the
+/// syntax of the query it's in (eg `IS NULL` can't be written as `IS ?`)
+fn is_parameterizable(literal: &Literal) -> bool {
+    !matches!(
+        literal,
+        Literal::Null | Literal::Boolean(_) | Literal::Placeholder(_)
+    )
+}
+
+/// Replace the placeholders in the given statement with the literal values of the corresponding
+/// elements of `key`.
+///
+/// Positional (`?`) placeholders take their values from the key in order, and numbered (`$1`,
+/// `:1`) placeholders take the value at their (1-based) number. Returns an error if the key doesn't
+/// have a value for every placeholder, or if any of the values used can't be represented as a
+/// literal.
+pub fn bind_placeholders(
+    statement: &SelectStatement,
+    key: &[DfValue],
+) -> anyhow::Result<SelectStatement> {
+    let mut res = statement.clone();
+    BindPlaceholders {
+        key,
+        next_positional: 0,
+    }
+    .visit_select_statement(&mut res)?;
+    Ok(res)
+}
+
+/// Generate all the [`ParameterizationVariant`]s of the given statement, parameterized with the
+/// given key, which are valid in `dialect`. See [`VariantKind`] for the different variants
+/// generated.
+pub fn parameterization_variants(
+    statement: &SelectStatement,
+    key: &[DfValue],
+    dialect: Dialect,
+) -> anyhow::Result<Vec<ParameterizationVariant>> {
+    let canonical = bind_placeholders(statement, key)?;
+    let variant = |kind, statement, key| ParameterizationVariant {
+        kind,
+        statement,
+        key,
+        canonical: canonical.clone(),
+    };
+
+    let mut variants = vec![variant(VariantKind::Literal, canonical.clone(), vec![])];
+
+    let mut literals = vec![];
+    let Ok(()) = WhereLiterals::new(|_, lit: &mut Literal| literals.push(lit.clone()))
+        .visit_select_statement(&mut canonical.clone());
+    for (position, literal) in literals.iter().enumerate() {
+        let shared = literals
+            .iter()
+            .enumerate()
+            .any(|(other, lit)| other != position && lit == literal);
+        if !shared || !is_parameterizable(literal) {
+            continue;
+        }
+
+        let mut statement = canonical.clone();
+        let Ok(()) = WhereLiterals::new(|pos, lit: &mut Literal| {
+            if pos == position {
+                *lit = Literal::Placeholder(ItemPlaceholder::QuestionMark);
+            }
+        })
+        .visit_select_statement(&mut statement);
+        variants.push(variant(
+            VariantKind::SingleParameter { position },
+            statement,
+            vec![DfValue::try_from(literal.clone())?],
+        ));
+    }
+
+    let mut quoted = canonical.clone();
+    let mut quoted_any = false;
+    let Ok(()) = WhereLiterals::new(|_, lit: &mut Literal| {
+        if let Literal::Integer(i) = lit {
+            *lit = Literal::String(i.to_string());
+            quoted_any = true;
+        }
+    })
+    .visit_select_statement(&mut quoted);
+    if quoted_any {
+        variants.push(variant(VariantKind::QuotedIntegers, quoted, vec![]));
+    }
+
+    if !key.is_empty() {
+        let mode = ParameterMode::from(dialect);
+        let mut restyled = statement.clone();
+        let mut restyle = RestylePlaceholders {
+            mode,
+            next_positional: 0,
+            key_indices: vec![],
+        };
+        let Ok(()) = restyle.visit_select_statement(&mut restyled);
+        if restyled != *statement {
+            let key = restyle
+                .key_indices
+                .into_iter()
+                .map(|idx| {
+                    key.get(idx)
+                        .cloned()
+                        .ok_or_else(|| anyhow!("No value in key for placeholder {}", idx + 1))
+                })
+                .collect::<anyhow::Result<Vec<_>>>()?;
+            variants.push(variant(
+                VariantKind::PlaceholderStyle { mode },
+                restyled,
+                key,
+            ));
+        }
+    }
+
+    Ok(variants)
+}
+
+impl<'gen> Query<'gen> {
+    /// Returns this query with each of its placeholders replaced with the literal value of the
+    /// corresponding element of `key`. See [`bind_placeholders`].
+    pub fn deparameterize(&self, key: &[DfValue]) -> anyhow::Result<SelectStatement> {
+        bind_placeholders(&self.statement, key)
+    }
+
+    /// Generate all the [`ParameterizationVariant`]s of this query which are valid in `dialect`,
+    /// using a lookup key for its parameters which will return results. See
+    /// [`parameterization_variants`].
+    pub fn parameterization_variants(
+        &self,
+        dialect: Dialect,
+    ) -> anyhow::Result<Vec<ParameterizationVariant>> {
+        parameterization_variants(&self.statement, &self.state.key(), dialect)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nom_sql::{parse_select_statement, DialectDisplay};
+
+    use super::*;
+    use crate::{GeneratorState, QueryOperation, QuerySeed};
+
+    fn parse(q: &str) -> SelectStatement {
+        parse_select_statement(Dialect::MySQL, q).unwrap()
+    }
+
+    /// Replace every literal in the given statement with `NULL`, so that statements which differ
+    /// only in their literals and placeholders compare equal
+    fn erase_literals(statement: &SelectStatement) -> SelectStatement {
+        struct EraseLiterals;
+        impl<'ast> VisitorMut<'ast> for EraseLiterals {
+            type Error = !;
+
+            fn visit_literal(&mut self, literal: &'ast mut Literal) -> Result<(), Self::Error> {
+                *literal = Literal::Null;
+                Ok(())
+            }
+        }
+
+        let mut res = statement.clone();
+        let Ok(()) = EraseLiterals.visit_select_statement(&mut res);
+        res
+    }
+
+    #[test]
+    fn bind_positional_and_numbered() {
+        let key = [DfValue::from(1), DfValue::from("a")];
+        assert_eq!(
+            bind_placeholders(&parse("SELECT t.x FROM t WHERE t.x = ? AND t.y = ?"), &key).unwrap(),
+            parse("SELECT t.x FROM t WHERE t.x = 1 AND t.y = 'a'")
+        );
+        assert_eq!(
+            bind_placeholders(
+                &parse("SELECT t.x FROM t WHERE t.y = $2 AND t.x = $1"),
+                &key
+            )
+            .unwrap(),
+            parse("SELECT t.x FROM t WHERE t.y = 'a' AND t.x = 1")
+        );
+        bind_placeholders(&parse("SELECT t.x FROM t WHERE t.x = $3"), &key).unwrap_err();
+    }
+
+    #[test]
+    fn variants_of_same_valued_literals() {
+        let statement = parse("SELECT t.x FROM t WHERE t.x = ? AND t.y = 1");
+        let variants =
+            parameterization_variants(&statement, &[1.into()], Dialect::PostgreSQL).unwrap();
+        let kinds = variants.iter().map(|v| v.kind.clone()).collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            vec![
+                VariantKind::Literal,
+                VariantKind::SingleParameter { position: 0 },
+                VariantKind::SingleParameter { position: 1 },
+                VariantKind::QuotedIntegers,
+                VariantKind::PlaceholderStyle {
+                    mode: ParameterMode::Numbered
+                },
+            ]
+        );
+
+        let canonical = parse("SELECT t.x FROM t WHERE t.x = 1 AND t.y = 1");
+        for variant in &variants {
+            assert_eq!(variant.canonical, canonical);
+        }
+        assert_eq!(
+            variants[2].statement,
+            parse("SELECT t.x FROM t WHERE t.x = 1 AND t.y = ?")
+        );
+        assert_eq!(
+            variants[3].statement,
+            parse("SELECT t.x FROM t WHERE t.x = '1' AND t.y = '1'")
+        );
+        assert_eq!(
+            variants[4].statement,
+            parse("SELECT t.x FROM t WHERE t.x = $1 AND t.y = 1")
+        );
+    }
+
+    #[test]
+    fn placeholder_style_variants_match_dialect() {
+        let positional = parse("SELECT t.x FROM t WHERE t.x = ?");
+        let numbered = parse("SELECT t.x FROM t WHERE t.x = $1");
+        for (statement, dialect, expected_mode) in [
+            (&positional, Dialect::MySQL, None),
+            (
+                &positional,
+                Dialect::PostgreSQL,
+                Some(ParameterMode::Numbered),
+            ),
+            (&numbered, Dialect::MySQL, Some(ParameterMode::Positional)),
+            (&numbered, Dialect::PostgreSQL, None),
+        ] {
+            let modes = parameterization_variants(statement, &[1.into()], dialect)
+                .unwrap()
+                .into_iter()
+                .filter_map(|v| match v.kind {
+                    VariantKind::PlaceholderStyle { mode } => Some(mode),
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(modes, Vec::from_iter(expected_mode), "{dialect:?}");
+        }
+    }
+
+    #[test]
+    fn generated_query_variants() {
+        let mut gen = GeneratorState::default();
+        let query = gen.generate_query(QuerySeed::new(
+            vec![
+                QueryOperation::MultipleParameters,
+                QueryOperation::RangeParameter,
+            ],
+            vec![],
+        ));
+        let variants = query.parameterization_variants(Dialect::MySQL).unwrap();
+        assert!(variants.len() > 1);
+
+        for variant in variants {
+            let sql = variant.statement.display(Dialect::MySQL).to_string();
+            assert_eq!(
+                parse_select_statement(Dialect::MySQL, &sql).unwrap(),
+                variant.statement,
+                "{sql} should re-parse"
+            );
+
+            assert_eq!(
+                erase_literals(&variant.statement),
+                erase_literals(&variant.canonical)
+            );
+            if variant.kind != VariantKind::QuotedIntegers {
+                assert_eq!(
+                    bind_placeholders(&variant.statement, &variant.key).unwrap(),
+                    variant.canonical
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,464 @@
+//! Pinning the tables and columns that individual operations in a [`QuerySeed`] are applied to,
+//! rather than letting the generator pick them.
+//!
+//! A [`PlacementPlan`] maps the index of an operation in a seed's list of operations to the
+//! [`Placement`] for that operation. Plans are passed to
+//! [`GeneratorState::generate_query_with_plan`], which validates the plan against the seed and
+//! the tables in the generator state before generating the query, so plans are mostly useful
+//! with a generator state created from an existing schema. Operations without an entry in the plan
+//! pick their tables and columns as usual.
+//!
+//! Only a subset of operations can currently be pinned:
+//!
+//! | Operation                                | Placement                 |
+//! |------------------------------------------|---------------------------|
+//! | [`QueryOperation::ColumnAggregate`]      | [`Placement::Column`]     |
+//! | [`QueryOperation::Filter`] on base table | [`Placement::Column`]     |
+//! | [`QueryOperation::SingleParameter`]      | [`Placement::Column`]     |
+//! | [`QueryOperation::Join`]                 | [`Placement::Join`]       |
+//!
+//! Plans can be written as JSON, for example:
+//!
+//! ```json
+//! {
+//!     "0": {
+//!         "left": { "table": "orders", "column": "customer_id" },
+//!         "right": { "table": "customers", "column": "id" }
+//!     },
+//!     "1": { "table": "customers", "column": "region" }
+//! }
+//! ```
+
+use std::collections::BTreeMap;
+
+use nom_sql::{Dialect, DialectDisplay, SqlType};
+use readyset_data::{DfType, Dialect as DataDialect};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    AggregateType, ColumnName, Filter, FilterTarget, GeneratorState, JoinConstraintType,
+    QueryOperation, QuerySeed, TableName,
+};
+
+/// A single column of a table in the schema, which an operation is pinned to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlacementTarget {
+    pub table: TableName,
+    pub column: ColumnName,
+}
+
+/// Where to apply a single operation in a query
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Placement {
+    /// Apply the operation to a single column
+    Column(PlacementTarget),
+    /// Join the table of `left`, which is added to the query if it isn't already there, to the
+    /// table of `right` on the two columns
+    Join {
+        left: PlacementTarget,
+        right: PlacementTarget,
+    },
+}
+
+/// A mapping from the index of an operation in a [`QuerySeed`] to the [`Placement`] for that
+/// operation. See [the module documentation](self) for more information.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct PlacementPlan(pub BTreeMap<usize, Placement>);
+
+/// An error in a [`PlacementPlan`], naming the index of the offending entry in the plan
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum PlacementError {
+    /// The plan has an entry for an operation that isn't in the seed
+    #[error("Placement {index} is for an operation which doesn't exist (the seed has {num_operations} operations)")]
+    NoSuchOperation { index: usize, num_operations: usize },
+
+    /// The plan has an entry for an operation which can't be pinned
+    #[error("Placement {index} is for operation {operation}, which can't be pinned")]
+    UnsupportedOperation { index: usize, operation: String },
+
+    /// The plan has the wrong kind of [`Placement`] for the operation
+    #[error("Placement {index} must be {expected}")]
+    WrongPlacementKind {
+        index: usize,
+        expected: &'static str,
+    },
+
+    /// The plan references a table that isn't in the schema
+    #[error("Placement {index} references table {table}, which doesn't exist")]
+    NoSuchTable { index: usize, table: TableName },
+
+    /// The plan references a column that isn't in its table
+    #[error("Placement {index} references column {table}.{column}, which doesn't exist")]
+    NoSuchColumn {
+        index: usize,
+        table: TableName,
+        column: ColumnName,
+    },
+
+    /// The column referenced by the plan has a type which can't be used for the operation
+    #[error(
+        "Placement {index} references column {table}.{column} of type {}, but {reason}",
+        .column_type.display(Dialect::MySQL)
+    )]
+    IncompatibleType {
+        index: usize,
+        table: TableName,
+        column: ColumnName,
+        column_type: SqlType,
+        reason: &'static str,
+    },
+
+    /// The plan joins two columns which can't be joined
+    #[error("Placement {index} can't join {table} to itself")]
+    SelfJoin { index: usize, table: TableName },
+
+    /// The plan joins two columns with different names using `USING`
+    #[error("Placement {index} joins {left} to {right} with USING, so the columns must have the same name")]
+    MismatchedUsingColumns {
+        index: usize,
+        left: ColumnName,
+        right: ColumnName,
+    },
+}
+
+fn df_type(ty: &SqlType) -> Option<DfType> {
+    DfType::from_sql_type(ty, DataDialect::DEFAULT_MYSQL, |_| None).ok()
+}
+
+fn is_numeric(ty: &SqlType) -> bool {
+    df_type(ty).map_or(false, |ty| {
+        ty.is_any_int() || ty.is_any_float() || matches!(ty, DfType::Numeric { .. })
+    })
+}
+
+/// Returns true if a column of type `actual` can be used in place of a column of type `expected`
+fn is_compatible(expected: &SqlType, actual: &SqlType) -> bool {
+    expected == actual
+        || (is_numeric(expected) && is_numeric(actual))
+        || (expected.is_any_text() && actual.is_any_text())
+}
+
+impl PlacementTarget {
+    /// Returns the type of the column referenced by this target, or an error if it doesn't exist
+    fn column_type<'a>(
+        &self,
+        index: usize,
+        gen: &'a GeneratorState,
+    ) -> Result<&'a SqlType, PlacementError> {
+        let table = gen
+            .table(&self.table)
+            .ok_or_else(|| PlacementError::NoSuchTable {
+                index,
+                table: self.table.clone(),
+            })?;
+        table
+            .columns
+            .get(&self.column)
+            .map(|col| &col.sql_type)
+            .ok_or_else(|| PlacementError::NoSuchColumn {
+                index,
+                table: self.table.clone(),
+                column: self.column.clone(),
+            })
+    }
+
+    fn incompatible(
+        &self,
+        index: usize,
+        column_type: &SqlType,
+        reason: &'static str,
+    ) -> PlacementError {
+        PlacementError::IncompatibleType {
+            index,
+            table: self.table.clone(),
+            column: self.column.clone(),
+            column_type: column_type.clone(),
+            reason,
+        }
+    }
+}
+
+impl PlacementPlan {
+    /// Returns the placement for the operation at the given index, if any
+    pub fn get(&self, index: usize) -> Option<&Placement> {
+        self.0.get(&index)
+    }
+
+    /// Check that every entry in this plan is for an operation in `seed` which can be pinned, and
+    /// references columns that exist in `gen` with a type that can be used for that operation
+    pub fn validate(&self, seed: &QuerySeed, gen: &GeneratorState) -> Result<(), PlacementError> {
+        for (&index, placement) in &self.0 {
+            let operation = seed
+                .operations
+                .get(index)
+                .ok_or(PlacementError::NoSuchOperation {
+                    index,
+                    num_operations: seed.operations.len(),
+                })?;
+
+            match (operation, placement) {
+                (QueryOperation::ColumnAggregate(agg), Placement::Column(target)) => {
+                    let column_type = target.column_type(index, gen)?;
+                    if matches!(agg, AggregateType::Sum { .. } | AggregateType::Avg { .. })
+                        && !is_numeric(column_type)
+                    {
+                        return Err(target.incompatible(
+                            index,
+                            column_type,
+                            "SUM and AVG can only be applied to numeric columns",
+                        ));
+                    }
+                }
+                (
+                    QueryOperation::Filter(
+                        filter @ Filter {
+                            filter_target: FilterTarget::Base,
+                            ..
+                        },
+                    ),
+                    Placement::Column(target),
+                ) => {
+                    let column_type = target.column_type(index, gen)?;
+                    if !is_compatible(&filter.column_type, column_type) {
+                        return Err(target.incompatible(
+                            index,
+                            column_type,
+                            "the filter compares against a value of a different type",
+                        ));
+                    }
+                }
+                (QueryOperation::SingleParameter, Placement::Column(target)) => {
+                    target.column_type(index, gen)?;
+                }
+                (QueryOperation::Join { constraint, .. }, Placement::Join { left, right }) => {
+                    let left_type = left.column_type(index, gen)?;
+                    let right_type = right.column_type(index, gen)?;
+                    if left.table == right.table {
+                        return Err(PlacementError::SelfJoin {
+                            index,
+                            table: left.table.clone(),
+                        });
+                    }
+                    if !is_compatible(left_type, right_type) {
+                        return Err(right.incompatible(
+                            index,
+                            right_type,
+                            "it must have the same type as the left side of the join",
+                        ));
+                    }
+                    if *constraint == JoinConstraintType::Using && left.column != right.column {
+                        return Err(PlacementError::MismatchedUsingColumns {
+                            index,
+                            left: left.column.clone(),
+                            right: right.column.clone(),
+                        });
+                    }
+                }
+                (
+                    QueryOperation::ColumnAggregate(_)
+                    | QueryOperation::Filter(Filter {
+                        filter_target: FilterTarget::Base,
+                        ..
+                    })
+                    | QueryOperation::SingleParameter,
+                    Placement::Join { .. },
+                ) => {
+                    return Err(PlacementError::WrongPlacementKind {
+                        index,
+                        expected: "a single column",
+                    })
+                }
+                (QueryOperation::Join { .. }, Placement::Column(_)) => {
+                    return Err(PlacementError::WrongPlacementKind {
+                        index,
+                        expected: "a pair of left and right columns",
+                    })
+                }
+                (operation, _) => {
+                    return Err(PlacementError::UnsupportedOperation {
+                        index,
+                        operation: format!("{operation:?}"),
+                    })
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nom_sql::JoinOperator;
+
+    use super::*;
+
+    fn gen() -> GeneratorState {
+        GeneratorState::from(
+            [
+                "CREATE TABLE customers (id INT, name TEXT, region TEXT, PRIMARY KEY (id))",
+                "CREATE TABLE orders (id INT, customer_id INT, total INT, PRIMARY KEY (id))",
+            ]
+            .into_iter()
+            .map(|ddl| nom_sql::parse_create_table(Dialect::MySQL, ddl).unwrap())
+            .collect::<Vec<_>>(),
+        )
+    }
+
+    fn seed() -> QuerySeed {
+        QuerySeed::new(
+            vec![
+                QueryOperation::Join {
+                    operator: JoinOperator::InnerJoin,
+                    constraint: JoinConstraintType::On,
+                    key_type: SqlType::Int(None),
+                    key_columns: 1,
+                },
+                QueryOperation::ColumnAggregate(AggregateType::Sum {
+                    column_type: SqlType::Int(None),
+                    distinct: false,
+                }),
+                QueryOperation::SingleParameter,
+            ],
+            vec![],
+        )
+    }
+
+    fn target(table: &str, column: &str) -> PlacementTarget {
+        PlacementTarget {
+            table: TableName::from(table),
+            column: ColumnName::from(column),
+        }
+    }
+
+    #[test]
+    fn full_plan() {
+        let plan = PlacementPlan(BTreeMap::from([
+            (
+                0,
+                Placement::Join {
+                    left: target("orders", "customer_id"),
+                    right: target("customers", "id"),
+                },
+            ),
+            (1, Placement::Column(target("orders", "total"))),
+            (2, Placement::Column(target("customers", "region"))),
+        ]));
+
+        let mut gen = gen();
+        let query = gen.generate_query_with_plan(seed(), &plan).unwrap();
+        assert_eq!(
+            query.statement.display(Dialect::MySQL).to_string(),
+            "SELECT `orders`.`customer_id` AS `alias_1`, `customers`.`id` AS `alias_2`, \
+             sum(`orders`.`total`) AS `alias_3` \
+             FROM `orders` \
+             INNER JOIN `customers` ON (`orders`.`customer_id` = `customers`.`id`) \
+             WHERE (`customers`.`region` = ?) \
+             GROUP BY `orders`.`customer_id`, `customers`.`id`"
+        );
+
+        // The plan shouldn't have added any columns to the schema
+        assert_eq!(gen.tables()[&TableName::from("orders")].columns.len(), 3);
+        assert_eq!(gen.tables()[&TableName::from("customers")].columns.len(), 3);
+    }
+
+    #[test]
+    fn join_to_table_already_in_query_is_aliased() {
+        let seed = QuerySeed::new(
+            vec![
+                QueryOperation::SingleParameter,
+                QueryOperation::Join {
+                    operator: JoinOperator::InnerJoin,
+                    constraint: JoinConstraintType::On,
+                    key_type: SqlType::Int(None),
+                    key_columns: 1,
+                },
+            ],
+            vec![],
+        );
+        let plan = PlacementPlan(BTreeMap::from([
+            (0, Placement::Column(target("customers", "region"))),
+            (
+                1,
+                Placement::Join {
+                    left: target("orders", "customer_id"),
+                    right: target("customers", "id"),
+                },
+            ),
+        ]));
+
+        let mut gen = gen();
+        let query = gen.generate_query_with_plan(seed, &plan).unwrap();
+        assert_eq!(
+            query.statement.display(Dialect::MySQL).to_string(),
+            "SELECT `orders`.`customer_id` AS `alias_2`, `alias_1`.`id` AS `alias_3` \
+             FROM `customers`, `orders` \
+             INNER JOIN `customers` AS `alias_1` ON (`orders`.`customer_id` = `alias_1`.`id`) \
+             WHERE (`customers`.`region` = ?)"
+        );
+    }
+
+    #[test]
+    fn plan_parses_from_json() {
+        let plan: PlacementPlan = serde_json::from_str(
+            r#"{
+                "0": {
+                    "left": { "table": "orders", "column": "customer_id" },
+                    "right": { "table": "customers", "column": "id" }
+                },
+                "2": { "table": "customers", "column": "region" }
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            plan.get(0),
+            Some(&Placement::Join {
+                left: target("orders", "customer_id"),
+                right: target("customers", "id"),
+            })
+        );
+        assert_eq!(
+            plan.get(2),
+            Some(&Placement::Column(target("customers", "region")))
+        );
+    }
+
+    #[test]
+    fn sum_over_text_column() {
+        let plan = PlacementPlan(BTreeMap::from([(
+            1,
+            Placement::Column(target("customers", "name")),
+        )]));
+        let err = gen().generate_query_with_plan(seed(), &plan).unwrap_err();
+        assert_eq!(
+            err,
+            PlacementError::IncompatibleType {
+                index: 1,
+                table: "customers".into(),
+                column: "name".into(),
+                column_type: SqlType::Text,
+                reason: "SUM and AVG can only be applied to numeric columns",
+            }
+        );
+    }
+
+    #[test]
+    fn nonexistent_column() {
+        let plan = PlacementPlan(BTreeMap::from([(
+            2,
+            Placement::Column(target("customers", "country")),
+        )]));
+        let err = gen().generate_query_with_plan(seed(), &plan).unwrap_err();
+        assert_eq!(
+            err,
+            PlacementError::NoSuchColumn {
+                index: 2,
+                table: "customers".into(),
+                column: "country".into(),
+            }
+        );
+    }
+}
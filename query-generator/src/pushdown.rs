@@ -0,0 +1,450 @@
+//! Rewriting generated queries to push filters on a single table below the joins in the query.
+//!
+//! ReadySet's planner can take advantage of filters which appear syntactically before a join,
+//! inside a derived table for the filtered table (eg `FROM (SELECT * FROM t WHERE ...) AS t JOIN
+//! ...`). [`pushdown_variant`] produces that form of a query from the flat form the generator
+//! emits, so that the plans generated for both forms of the same logical query can be compared.
+//!
+//! The derived table for a table keeps the name (or alias) of the table it wraps and projects all
+//! of its columns, so the rest of the query - including its projections - is left unchanged.
+//! Filters on the null-extended side of an outer join are never pushed down, since filtering rows
+//! before the join would produce `NULL`-extended rows rather than removing them.
+
+use nom_sql::analysis::visit::Visitor;
+use nom_sql::analysis::visit_mut::VisitorMut;
+use nom_sql::analysis::ReferredColumns;
+use nom_sql::{
+    Expr, FieldDefinitionExpr, ItemPlaceholder, JoinOperator, JoinRightSide, Literal,
+    SelectStatement, SqlIdentifier, TableExpr, TableExprInner,
+};
+
+use crate::equivalence::{conjunction, conjuncts, has_placeholder};
+use crate::Query;
+
+/// The position of a table in the `FROM` clause or `JOIN` clauses of a query
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TableLocation {
+    From(usize),
+    Join(usize),
+}
+
+/// A table in a query which filters might be pushed into
+struct Candidate {
+    /// The name columns of the table are referenced by in the query
+    name: SqlIdentifier,
+    location: TableLocation,
+    /// Whether filters can be pushed into the table - false for subqueries, tables qualified with
+    /// a schema, and tables on the null-extended side of an outer join
+    pushable: bool,
+}
+
+/// Returns the name columns in `table_expr` are referenced by, and whether it's a plain table
+/// (rather than a subquery) that can be wrapped in a derived table with the same name
+fn table_name(table_expr: &TableExpr) -> Option<(SqlIdentifier, bool)> {
+    match (&table_expr.inner, &table_expr.alias) {
+        (TableExprInner::Table(_), Some(alias)) => Some((alias.clone(), true)),
+        (TableExprInner::Table(rel), None) => Some((rel.name.clone(), rel.schema.is_none())),
+        (TableExprInner::Subquery(_), Some(alias)) => Some((alias.clone(), false)),
+        (TableExprInner::Subquery(_), None) => None,
+    }
+}
+
+/// Collect all the tables in the `FROM` and `JOIN` clauses of `statement`, marking those on the
+/// null-extended side of a `LEFT` or `RIGHT` join as not pushable
+fn candidates(statement: &SelectStatement) -> Vec<Candidate> {
+    let mut res = statement
+        .tables
+        .iter()
+        .enumerate()
+        .filter_map(|(i, table_expr)| {
+            let (name, pushable) = table_name(table_expr)?;
+            Some(Candidate {
+                name,
+                location: TableLocation::From(i),
+                pushable,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    for (i, join) in statement.join.iter().enumerate() {
+        if join.operator == JoinOperator::RightJoin {
+            for candidate in &mut res {
+                candidate.pushable = false;
+            }
+        }
+        let left_join = matches!(
+            join.operator,
+            JoinOperator::LeftJoin | JoinOperator::LeftOuterJoin
+        );
+        // Parenthesized lists of tables on the right side of a join are never pushed into, but
+        // still need to be recorded so that filters on them aren't attributed to another table
+        let (table_exprs, single) = match &join.right {
+            JoinRightSide::Table(table_expr) => (vec![table_expr], true),
+            JoinRightSide::Tables(table_exprs) => (table_exprs.iter().collect(), false),
+        };
+        res.extend(table_exprs.into_iter().filter_map(|table_expr| {
+            let (name, pushable) = table_name(table_expr)?;
+            Some(Candidate {
+                name,
+                location: TableLocation::Join(i),
+                pushable: pushable && single && !left_join,
+            })
+        }));
+    }
+
+    res
+}
+
+/// Returns true if `expr` contains a subquery, which might be correlated with the tables of the
+/// outer query
+fn has_subquery(expr: &Expr) -> bool {
+    struct HasSubquery(bool);
+    impl<'ast> Visitor<'ast> for HasSubquery {
+        type Error = !;
+
+        fn visit_select_statement(&mut self, _: &'ast SelectStatement) -> Result<(), Self::Error> {
+            self.0 = true;
+            Ok(())
+        }
+    }
+
+    let mut visitor = HasSubquery(false);
+    let Ok(()) = visitor.visit_expr(expr);
+    visitor.0
+}
+
+/// Returns the index in `candidates` of the only table referenced by `conjunct`, if it can be
+/// pushed into that table
+fn pushdown_target(candidates: &[Candidate], conjunct: &Expr) -> Option<usize> {
+    if has_subquery(conjunct) {
+        return None;
+    }
+
+    let mut tables = conjunct.referred_columns().map(|col| col.table.as_ref());
+    let table = tables.next()??;
+    if table.schema.is_some() || tables.any(|t| t != Some(table)) {
+        return None;
+    }
+
+    let mut matching = candidates
+        .iter()
+        .enumerate()
+        .filter(|(_, candidate)| candidate.name == table.name);
+    match (matching.next(), matching.next()) {
+        (Some((idx, candidate)), None) if candidate.pushable => Some(idx),
+        _ => None,
+    }
+}
+
+/// Move each of the conjuncts of the `WHERE` clause of `statement` for which `include` returns
+/// true, and which can be pushed into a single table, into a derived table for that table.
+/// Returns `None` if no conjuncts were moved.
+fn push_down(
+    statement: &SelectStatement,
+    include: impl Fn(&Expr) -> bool,
+) -> Option<SelectStatement> {
+    let candidates = candidates(statement);
+    let mut pushed = candidates.iter().map(|_| vec![]).collect::<Vec<_>>();
+    let mut remaining = vec![];
+    for conjunct in conjuncts(statement.where_clause.clone()?) {
+        match pushdown_target(&candidates, &conjunct).filter(|_| include(&conjunct)) {
+            Some(idx) => pushed[idx].push(conjunct),
+            None => remaining.push(conjunct),
+        }
+    }
+    if pushed.iter().all(|conjuncts| conjuncts.is_empty()) {
+        return None;
+    }
+
+    let mut res = statement.clone();
+    res.where_clause = conjunction(remaining);
+    for (candidate, conjuncts) in candidates.iter().zip(pushed) {
+        let Some(where_clause) = conjunction(conjuncts) else {
+            continue;
+        };
+        let table_expr = match candidate.location {
+            TableLocation::From(i) => &mut res.tables[i],
+            TableLocation::Join(i) => match &mut res.join[i].right {
+                JoinRightSide::Table(table_expr) => table_expr,
+                JoinRightSide::Tables(_) => unreachable!("Pushed into a list of tables"),
+            },
+        };
+        let table = table_expr.clone();
+        *table_expr = TableExpr {
+            inner: TableExprInner::Subquery(Box::new(SelectStatement {
+                fields: vec![FieldDefinitionExpr::All],
+                tables: vec![table],
+                where_clause: Some(where_clause),
+                ..Default::default()
+            })),
+            alias: Some(candidate.name.clone()),
+            index_hint: None,
+            sample: None,
+        };
+    }
+
+    Some(res)
+}
+
+/// Visitor which replaces each placeholder with a numbered placeholder, remembering the original
+struct NumberPlaceholders(Vec<ItemPlaceholder>);
+
+impl<'ast> VisitorMut<'ast> for NumberPlaceholders {
+    type Error = !;
+
+    fn visit_literal(&mut self, literal: &'ast mut Literal) -> Result<(), Self::Error> {
+        if let Literal::Placeholder(placeholder) = literal {
+            self.0.push(placeholder.clone());
+            *placeholder = ItemPlaceholder::DollarNumber(self.0.len() as u32);
+        }
+        Ok(())
+    }
+}
+
+/// Visitor which restores the placeholders replaced by [`NumberPlaceholders`]
+struct RestorePlaceholders(Vec<ItemPlaceholder>);
+
+impl<'ast> VisitorMut<'ast> for RestorePlaceholders {
+    type Error = !;
+
+    fn visit_literal(&mut self, literal: &'ast mut Literal) -> Result<(), Self::Error> {
+        if let Literal::Placeholder(ItemPlaceholder::DollarNumber(n)) = literal {
+            *literal = Literal::Placeholder(self.0[*n as usize - 1].clone());
+        }
+        Ok(())
+    }
+}
+
+/// Returns the numbers of the placeholders in `statement`, in the order they appear when the
+/// statement is displayed - which is the order positional parameters are bound in
+fn placeholder_order(statement: &SelectStatement) -> Vec<u32> {
+    struct PlaceholderOrder(Vec<u32>);
+    impl<'ast> Visitor<'ast> for PlaceholderOrder {
+        type Error = !;
+
+        fn visit_literal(&mut self, literal: &'ast Literal) -> Result<(), Self::Error> {
+            if let Literal::Placeholder(ItemPlaceholder::DollarNumber(n)) = literal {
+                self.0.push(*n);
+            }
+            Ok(())
+        }
+
+        fn visit_select_statement(
+            &mut self,
+            statement: &'ast SelectStatement,
+        ) -> Result<(), Self::Error> {
+            // The default walk visits the tables of the statement before its fields, and its
+            // `HAVING` clause before its `GROUP BY` clause
+            for cte in &statement.ctes {
+                self.visit_common_table_expr(cte)?;
+            }
+            for field in &statement.fields {
+                self.visit_field_definition_expr(field)?;
+            }
+            for table_expr in &statement.tables {
+                self.visit_table_expr(table_expr)?;
+            }
+            for join in &statement.join {
+                self.visit_join_clause(join)?;
+            }
+            if let Some(where_clause) = &statement.where_clause {
+                self.visit_where_clause(where_clause)?;
+            }
+            if let Some(group_by) = &statement.group_by {
+                self.visit_group_by_clause(group_by)?;
+            }
+            if let Some(having) = &statement.having {
+                self.visit_having_clause(having)?;
+            }
+            if let Some(order) = &statement.order {
+                self.visit_order_clause(order)?;
+            }
+            self.visit_limit_clause(&statement.limit_clause)
+        }
+    }
+
+    let mut visitor = PlaceholderOrder(vec![]);
+    let Ok(()) = visitor.visit_select_statement(statement);
+    visitor.0
+}
+
+/// Rewrite `statement` to move each conjunct of its `WHERE` clause which references only one of
+/// the tables it joins into a derived table for that table. Returns `None` if the statement has no
+/// joins, or none of its filters can be pushed down.
+///
+/// Filters containing placeholders are only pushed down if doing so keeps all the placeholders in
+/// the statement in the same order, so that the variant can be executed with the same positional
+/// parameters as the original.
+pub fn pushdown_variant(statement: &SelectStatement) -> Option<SelectStatement> {
+    if statement.join.is_empty() && statement.tables.len() < 2 {
+        return None;
+    }
+
+    let mut numbered = statement.clone();
+    let mut numberer = NumberPlaceholders(vec![]);
+    let Ok(()) = numberer.visit_select_statement(&mut numbered);
+    let order = placeholder_order(&numbered);
+
+    let mut res = push_down(&numbered, |_| true)
+        .filter(|res| placeholder_order(res) == order)
+        .or_else(|| push_down(&numbered, |conjunct| !has_placeholder(conjunct)))?;
+    let Ok(()) = RestorePlaceholders(numberer.0).visit_select_statement(&mut res);
+    Some(res)
+}
+
+impl<'gen> Query<'gen> {
+    /// Rewrite this query to push its filters on single tables into derived tables below its
+    /// joins, if it has any. See [`pushdown_variant`].
+    pub fn pushdown_variant(&self) -> Option<SelectStatement> {
+        pushdown_variant(&self.statement)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nom_sql::{parse_select_statement, Dialect, DialectDisplay, SqlType};
+
+    use super::*;
+    use crate::{GeneratorState, JoinConstraintType, QueryOperation, QuerySeed};
+
+    fn parse(q: &str) -> SelectStatement {
+        parse_select_statement(Dialect::MySQL, q).unwrap()
+    }
+
+    #[test]
+    fn inner_join_filters() {
+        let statement = parse(
+            "SELECT t1.a AS alias_1, t2.b AS alias_2 FROM t1 JOIN t2 ON (t1.id = t2.id) \
+             WHERE (t1.a = 1) AND (t1.c = t2.c) AND (t2.b = ?) AND (t1.d > 3)",
+        );
+        let variant = pushdown_variant(&statement).unwrap();
+        assert_eq!(
+            variant,
+            parse(
+                "SELECT t1.a AS alias_1, t2.b AS alias_2 \
+                 FROM (SELECT * FROM t1 WHERE (t1.a = 1) AND (t1.d > 3)) AS t1 \
+                 JOIN (SELECT * FROM t2 WHERE t2.b = ?) AS t2 ON (t1.id = t2.id) \
+                 WHERE t1.c = t2.c"
+            )
+        );
+        assert_eq!(variant.fields, statement.fields);
+
+        let sql = variant.display(Dialect::MySQL).to_string();
+        assert_eq!(parse(&sql), variant, "{sql} should re-parse");
+    }
+
+    #[test]
+    fn generated_variant_rewrites() {
+        let mut gen = GeneratorState::default();
+        let query = gen.generate_query(QuerySeed::new(
+            vec![
+                QueryOperation::Join {
+                    operator: JoinOperator::InnerJoin,
+                    constraint: JoinConstraintType::On,
+                    key_type: SqlType::Int(None),
+                    key_columns: 1,
+                },
+                QueryOperation::SingleParameter,
+            ],
+            vec![],
+        ));
+        query.dry_run_rewrite().unwrap();
+
+        // The parameter is pushed into a derived table with the same name as the table it filters
+        let variant = query.pushdown_variant().unwrap();
+        assert!(variant.where_clause.is_none());
+        assert_eq!(variant.fields, query.statement.fields);
+        query.state.gen.rewrite_query(variant).unwrap();
+    }
+
+    #[test]
+    fn existing_derived_table_named_after_table() {
+        // Filters on a derived table aren't pushed into it, even if it has the name of the table it
+        // wraps
+        let statement = parse(
+            "SELECT t1.a AS alias_1 FROM (SELECT * FROM t1 WHERE t1.a = 1) AS t1 \
+             JOIN t2 ON (t1.id = t2.id) WHERE (t1.b = 2) AND (t2.c = 3)",
+        );
+        let variant = pushdown_variant(&statement).unwrap();
+        assert_eq!(
+            variant,
+            parse(
+                "SELECT t1.a AS alias_1 FROM (SELECT * FROM t1 WHERE t1.a = 1) AS t1 \
+                 JOIN (SELECT * FROM t2 WHERE t2.c = 3) AS t2 ON (t1.id = t2.id) \
+                 WHERE t1.b = 2"
+            )
+        );
+
+        // So pushing down an already pushed down variant doesn't nest derived tables
+        assert_eq!(pushdown_variant(&variant), None);
+    }
+
+    #[test]
+    fn aliased_tables() {
+        assert_eq!(
+            pushdown_variant(&parse(
+                "SELECT x.a AS alias_1 FROM t1 AS x JOIN t2 AS y ON (x.id = y.id) WHERE x.a = 1"
+            ))
+            .unwrap(),
+            parse(
+                "SELECT x.a AS alias_1 FROM (SELECT * FROM t1 AS x WHERE x.a = 1) AS x \
+                 JOIN t2 AS y ON (x.id = y.id)"
+            )
+        );
+    }
+
+    #[test]
+    fn left_join_preserved_side() {
+        assert_eq!(
+            pushdown_variant(&parse(
+                "SELECT t1.a AS alias_1 FROM t1 LEFT JOIN t2 ON (t1.id = t2.id) \
+                 WHERE (t1.a = 1) AND (t2.b = 2)"
+            ))
+            .unwrap(),
+            parse(
+                "SELECT t1.a AS alias_1 FROM (SELECT * FROM t1 WHERE t1.a = 1) AS t1 \
+                 LEFT JOIN t2 ON (t1.id = t2.id) WHERE t2.b = 2"
+            )
+        );
+    }
+
+    #[test]
+    fn keeps_placeholder_order() {
+        // Pushing `t1.a = ?` down would move it before `t2.b = ?`
+        assert_eq!(
+            pushdown_variant(&parse(
+                "SELECT t1.a AS alias_1 FROM t1 JOIN t2 ON (t1.id = t2.id) \
+                 WHERE (t2.b = ?) AND (t1.a = ?) AND (t1.c = 1)"
+            ))
+            .unwrap(),
+            parse(
+                "SELECT t1.a AS alias_1 FROM (SELECT * FROM t1 WHERE t1.c = 1) AS t1 \
+                 JOIN t2 ON (t1.id = t2.id) WHERE (t2.b = ?) AND (t1.a = ?)"
+            )
+        );
+    }
+
+    #[test]
+    fn no_variant() {
+        for q in [
+            // No joins
+            "SELECT t.a AS alias_1 FROM t WHERE t.a = 1",
+            // Filter on the null-extended side of a left join
+            "SELECT t1.a AS alias_1 FROM t1 LEFT JOIN t2 ON (t1.id = t2.id) WHERE t2.b = 1",
+            // Filter on the null-extended side of a right join
+            "SELECT t1.a AS alias_1 FROM t1 RIGHT JOIN t2 ON (t1.id = t2.id) WHERE t1.b = 1",
+            // Filter referencing multiple tables
+            "SELECT t1.a AS alias_1 FROM t1 JOIN t2 ON (t1.id = t2.id) WHERE t1.b = t2.b",
+            // Unqualified column
+            "SELECT t1.a AS alias_1 FROM t1 JOIN t2 ON (t1.id = t2.id) WHERE b = 1",
+            // Subquery, which may be correlated
+            "SELECT t1.a AS alias_1 FROM t1 JOIN t2 ON (t1.id = t2.id) \
+             WHERE t1.b IN (SELECT t3.b FROM t3 WHERE t3.c = t2.c)",
+            // Placeholders which would be reordered, and nothing else to push down
+            "SELECT t1.a AS alias_1 FROM t1 JOIN t2 ON (t1.id = t2.id) \
+             WHERE (t2.b = ?) AND (t1.a = ?)",
+        ] {
+            assert_eq!(pushdown_variant(&parse(q)), None, "{q}");
+        }
+    }
+}
@@ -0,0 +1,589 @@
+//! Running a batch of generated queries against a backend, isolating each query from the rest of
+//! the run.
+//!
+//! A single pathological query which hangs while being installed in ReadySet, or wedges a read,
+//! shouldn't stall an entire run of the query generator. Each query is run through a
+//! [`QueryDriver`] in two phases - installing the query (creating its tables, loading its data,
+//! and caching it) and executing it - each of which is bounded by its own timeout and has any
+//! panics caught. The [`QueryOutcome`] of every query is recorded in a [`RunSummary`], and if a
+//! query times out or panics the driver can optionally be [restarted](QueryDriver::restart)
+//! before moving on to the next query, since the backend may have been left in a bad state.
+
+use std::any::Any;
+use std::fmt::{self, Display};
+use std::panic::AssertUnwindSafe;
+use std::time::Duration;
+
+use database_utils::{DatabaseConnection, DatabaseURL};
+use futures_util::future::BoxFuture;
+use futures_util::FutureExt;
+use nom_sql::Dialect;
+use serde::Serialize;
+
+use crate::artifact::Artifact;
+use crate::validate::{compare_results, install_artifact, ValidationFailure};
+use crate::QuerySeed;
+
+/// A phase of running a single query
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Phase {
+    /// Creating the query's tables, loading its data, and caching the query
+    Install,
+    /// Running the query and comparing its results
+    Execute,
+}
+
+impl Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Phase::Install => f.write_str("install"),
+            Phase::Execute => f.write_str("execute"),
+        }
+    }
+}
+
+/// The outcome of running a single query
+#[derive(Debug, Clone, PartialEq)]
+pub enum QueryOutcome {
+    /// Both phases of running the query finished, with the validation failure for the query if
+    /// its results differed between the reference database and ReadySet
+    Completed(Option<ValidationFailure>),
+    /// A phase of running the query didn't finish within its timeout
+    TimedOut { phase: Phase },
+    /// A phase of running the query panicked
+    Panicked { phase: Phase, message: String },
+    /// A phase of running the query returned an error (for example, because ReadySet doesn't
+    /// support the query), so the query was skipped
+    Skipped { phase: Phase, error: String },
+}
+
+impl QueryOutcome {
+    /// Returns the name of the kind of this outcome, used as the key for its count in a
+    /// [`RunSummary`]
+    pub fn kind(&self) -> &'static str {
+        match self {
+            QueryOutcome::Completed(_) => "completed",
+            QueryOutcome::TimedOut { .. } => "timed_out",
+            QueryOutcome::Panicked { .. } => "panicked",
+            QueryOutcome::Skipped { .. } => "skipped",
+        }
+    }
+
+    /// Returns true if the backend may have been left in a bad state by this outcome, and should
+    /// be restarted before running any more queries
+    pub fn poisoned_backend(&self) -> bool {
+        matches!(
+            self,
+            QueryOutcome::TimedOut { .. } | QueryOutcome::Panicked { .. }
+        )
+    }
+}
+
+impl Display for QueryOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QueryOutcome::Completed(None) => f.write_str("completed"),
+            QueryOutcome::Completed(Some(_)) => f.write_str("completed (failed validation)"),
+            QueryOutcome::TimedOut { phase } => write!(f, "timed out during {phase}"),
+            QueryOutcome::Panicked { phase, message } => {
+                write!(f, "panicked during {phase}: {message}")
+            }
+            QueryOutcome::Skipped { phase, error } => {
+                write!(f, "skipped after error during {phase}: {error}")
+            }
+        }
+    }
+}
+
+/// A backend which queries can be installed in and executed against by [`run_queries`]
+pub trait QueryDriver {
+    /// Install the query in the given artifact, along with its tables and data
+    fn install<'a>(&'a mut self, artifact: &'a Artifact) -> BoxFuture<'a, anyhow::Result<()>>;
+
+    /// Execute the query in the given artifact, which has already been installed, returning a
+    /// validation failure if its results were incorrect
+    fn execute<'a>(
+        &'a mut self,
+        artifact: &'a Artifact,
+    ) -> BoxFuture<'a, anyhow::Result<Option<ValidationFailure>>>;
+
+    /// Tear down and rebuild the backend, after a query left it in a bad state
+    fn restart(&mut self) -> BoxFuture<'_, anyhow::Result<()>>;
+}
+
+/// Options controlling how [`run_queries`] isolates queries from each other
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RuntimeOptions {
+    /// Maximum amount of time to spend installing each query
+    pub install_timeout: Duration,
+    /// Maximum amount of time to spend executing each query
+    pub query_timeout: Duration,
+    /// Whether to [restart](QueryDriver::restart) the driver after a query times out or panics
+    pub restart_on_failure: bool,
+}
+
+impl Default for RuntimeOptions {
+    fn default() -> Self {
+        Self {
+            install_timeout: Duration::from_secs(60),
+            query_timeout: Duration::from_secs(30),
+            restart_on_failure: false,
+        }
+    }
+}
+
+/// The result of running a single query with [`run_queries`]
+#[derive(Debug, Clone)]
+pub struct QueryResult {
+    /// The seed the query was generated from
+    pub seed: QuerySeed,
+    /// What happened when the query was run
+    pub outcome: QueryOutcome,
+    /// The query, along with the data and keys it was run with, if the query didn't complete.
+    ///
+    /// Artifacts for queries which completed are dropped once they've been run, to keep long runs
+    /// from holding on to the data for every query. Queries which completed but failed validation
+    /// keep their artifact in the [`ValidationFailure`].
+    pub artifact: Option<Artifact>,
+}
+
+/// The results of running a batch of queries with [`run_queries`]
+#[derive(Debug, Clone, Default)]
+pub struct RunSummary {
+    /// The result of each query, in the order the queries were run
+    pub results: Vec<QueryResult>,
+}
+
+impl RunSummary {
+    /// Returns the number of queries with each [kind](QueryOutcome::kind) of outcome, including
+    /// kinds which no query had
+    pub fn outcome_counts(&self) -> Vec<(&'static str, usize)> {
+        ["completed", "timed_out", "panicked", "skipped"]
+            .into_iter()
+            .map(|kind| {
+                let count = self
+                    .results
+                    .iter()
+                    .filter(|res| res.outcome.kind() == kind)
+                    .count();
+                (kind, count)
+            })
+            .collect()
+    }
+
+    /// Returns an iterator over the results of all queries whose outcome wasn't
+    /// [`QueryOutcome::Completed`]
+    pub fn incomplete(&self) -> impl Iterator<Item = &QueryResult> {
+        self.results
+            .iter()
+            .filter(|res| !matches!(res.outcome, QueryOutcome::Completed(_)))
+    }
+
+    /// Returns an iterator over all validation failures for queries which completed
+    pub fn validation_failures(&self) -> impl Iterator<Item = &ValidationFailure> {
+        self.results.iter().filter_map(|res| match &res.outcome {
+            QueryOutcome::Completed(failure) => failure.as_ref(),
+            _ => None,
+        })
+    }
+
+    /// Returns a JSON representation of this summary, containing the count of each kind of
+    /// outcome, along with the seed of, and what happened to, every query which didn't complete
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "outcomes": self
+                .outcome_counts()
+                .into_iter()
+                .map(|(kind, count)| (kind.to_owned(), count.into()))
+                .collect::<serde_json::Map<_, _>>(),
+            "validation_failures": self.validation_failures().count(),
+            "incomplete": self
+                .incomplete()
+                .map(|res| {
+                    let (phase, detail) = match &res.outcome {
+                        QueryOutcome::TimedOut { phase } => (phase, None),
+                        QueryOutcome::Panicked { phase, message } => (phase, Some(message)),
+                        QueryOutcome::Skipped { phase, error } => (phase, Some(error)),
+                        QueryOutcome::Completed(_) => unreachable!(),
+                    };
+                    serde_json::json!({
+                        "outcome": res.outcome.kind(),
+                        "phase": phase,
+                        "detail": detail,
+                        "seed": res.seed,
+                    })
+                })
+                .collect::<Vec<_>>(),
+        })
+    }
+}
+
+impl Display for RunSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Ran {} queries:", self.results.len())?;
+        for (kind, count) in self.outcome_counts() {
+            writeln!(f, "  {kind}: {count}")?;
+        }
+        for res in self.incomplete() {
+            writeln!(f, "{:?} {}", res.seed, res.outcome)?;
+        }
+        Ok(())
+    }
+}
+
+/// Extract the message from the payload of a panic, if it has one
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    match payload.downcast::<String>() {
+        Ok(msg) => *msg,
+        Err(payload) => payload
+            .downcast_ref::<&str>()
+            .map(|msg| msg.to_string())
+            .unwrap_or_else(|| "<non-string panic payload>".to_owned()),
+    }
+}
+
+/// Run a single phase of a query, returning `Err` with the outcome of the query if the phase
+/// didn't succeed
+async fn run_phase<T>(
+    phase: Phase,
+    timeout: Duration,
+    fut: BoxFuture<'_, anyhow::Result<T>>,
+) -> Result<T, QueryOutcome> {
+    match tokio::time::timeout(timeout, AssertUnwindSafe(fut).catch_unwind()).await {
+        Ok(Ok(Ok(res))) => Ok(res),
+        Ok(Ok(Err(error))) => Err(QueryOutcome::Skipped {
+            phase,
+            error: format!("{error:#}"),
+        }),
+        Ok(Err(payload)) => Err(QueryOutcome::Panicked {
+            phase,
+            message: panic_message(payload),
+        }),
+        Err(_) => Err(QueryOutcome::TimedOut { phase }),
+    }
+}
+
+/// Run a single query through both phases, returning its outcome
+async fn run_query<D>(driver: &mut D, artifact: &Artifact, options: RuntimeOptions) -> QueryOutcome
+where
+    D: QueryDriver,
+{
+    let res = async {
+        run_phase(
+            Phase::Install,
+            options.install_timeout,
+            driver.install(artifact),
+        )
+        .await?;
+        run_phase(
+            Phase::Execute,
+            options.query_timeout,
+            driver.execute(artifact),
+        )
+        .await
+    }
+    .await;
+
+    match res {
+        Ok(failure) => QueryOutcome::Completed(failure),
+        Err(outcome) => outcome,
+    }
+}
+
+/// Run each of the given queries against `driver` in turn, recording the outcome of each query.
+///
+/// Queries are only taken from `queries` as they're run, so it can generate them lazily.
+///
+/// Each phase of each query is bounded by the timeouts in `options`, and panics within either
+/// phase are caught rather than aborting the run. If a query times out or panics and
+/// [`RuntimeOptions::restart_on_failure`] is set, the driver is restarted before running the next
+/// query. Returns an error only if restarting the driver fails.
+pub async fn run_queries<D, I>(
+    driver: &mut D,
+    queries: I,
+    options: RuntimeOptions,
+) -> anyhow::Result<RunSummary>
+where
+    D: QueryDriver,
+    I: IntoIterator<Item = (QuerySeed, Artifact)>,
+{
+    let mut summary = RunSummary::default();
+    let mut queries = queries.into_iter().peekable();
+    while let Some((seed, artifact)) = queries.next() {
+        let outcome = run_query(driver, &artifact, options).await;
+        if outcome.poisoned_backend() && options.restart_on_failure && queries.peek().is_some() {
+            driver.restart().await?;
+        }
+        let artifact = (!matches!(outcome, QueryOutcome::Completed(_))).then_some(artifact);
+        summary.results.push(QueryResult {
+            seed,
+            outcome,
+            artifact,
+        });
+    }
+    Ok(summary)
+}
+
+/// A [`QueryDriver`] which validates queries by comparing the results of running them against a
+/// reference database with the results of running them against ReadySet.
+///
+/// Restarting the driver drops both connections and reconnects to the configured URLs.
+pub struct DatabaseDriver {
+    reference_url: DatabaseURL,
+    readyset_url: DatabaseURL,
+    reference: DatabaseConnection,
+    readyset: DatabaseConnection,
+    dialect: Dialect,
+}
+
+impl DatabaseDriver {
+    /// Connect to the reference database and ReadySet instance at the given URLs
+    pub async fn connect(
+        reference_url: DatabaseURL,
+        readyset_url: DatabaseURL,
+    ) -> anyhow::Result<Self> {
+        let reference = reference_url.connect(None).await?;
+        let readyset = readyset_url.connect(None).await?;
+        Ok(Self {
+            dialect: reference_url.dialect(),
+            reference_url,
+            readyset_url,
+            reference,
+            readyset,
+        })
+    }
+}
+
+impl QueryDriver for DatabaseDriver {
+    fn install<'a>(&'a mut self, artifact: &'a Artifact) -> BoxFuture<'a, anyhow::Result<()>> {
+        install_artifact(
+            artifact,
+            &mut self.reference,
+            &mut self.readyset,
+            self.dialect,
+        )
+        .boxed()
+    }
+
+    fn execute<'a>(
+        &'a mut self,
+        artifact: &'a Artifact,
+    ) -> BoxFuture<'a, anyhow::Result<Option<ValidationFailure>>> {
+        compare_results(
+            artifact,
+            &mut self.reference,
+            &mut self.readyset,
+            self.dialect,
+        )
+        .boxed()
+    }
+
+    fn restart(&mut self) -> BoxFuture<'_, anyhow::Result<()>> {
+        async move {
+            self.reference = self.reference_url.connect(None).await?;
+            self.readyset = self.readyset_url.connect(None).await?;
+            Ok(())
+        }
+        .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use nom_sql::{OrderType, SqlType};
+
+    use super::*;
+    use crate::{AggregateType, GeneratorState, QueryOperation};
+
+    /// What the [`MockDriver`] does when it runs the marker query
+    #[derive(Clone, Copy)]
+    enum Misbehavior {
+        Hang,
+        Panic,
+    }
+
+    /// A [`QueryDriver`] which misbehaves while running the marker query during the given phase,
+    /// and succeeds for every other query
+    struct MockDriver {
+        marker: Artifact,
+        misbehavior: Misbehavior,
+        phase: Phase,
+        restarts: usize,
+    }
+
+    impl MockDriver {
+        async fn run(&self, artifact: &Artifact, phase: Phase) {
+            if *artifact == self.marker && phase == self.phase {
+                match self.misbehavior {
+                    Misbehavior::Hang => futures_util::future::pending::<()>().await,
+                    Misbehavior::Panic => panic!("marker query exploded"),
+                }
+            }
+        }
+    }
+
+    impl QueryDriver for MockDriver {
+        fn install<'a>(&'a mut self, artifact: &'a Artifact) -> BoxFuture<'a, anyhow::Result<()>> {
+            async move {
+                self.run(artifact, Phase::Install).await;
+                Ok(())
+            }
+            .boxed()
+        }
+
+        fn execute<'a>(
+            &'a mut self,
+            artifact: &'a Artifact,
+        ) -> BoxFuture<'a, anyhow::Result<Option<ValidationFailure>>> {
+            async move {
+                self.run(artifact, Phase::Execute).await;
+                Ok(None)
+            }
+            .boxed()
+        }
+
+        fn restart(&mut self) -> BoxFuture<'_, anyhow::Result<()>> {
+            self.restarts += 1;
+            async { Ok(()) }.boxed()
+        }
+    }
+
+    fn queries() -> Vec<(QuerySeed, Artifact)> {
+        let seeds = vec![
+            QuerySeed::new(vec![QueryOperation::SingleParameter], vec![]),
+            QuerySeed::new(
+                vec![QueryOperation::TopK {
+                    order_type: OrderType::OrderAscending,
+                    limit: 3,
+                }],
+                vec![],
+            ),
+            QuerySeed::new(
+                vec![QueryOperation::ColumnAggregate(AggregateType::Count {
+                    column_type: SqlType::Int(None),
+                    distinct: false,
+                })],
+                vec![],
+            ),
+            QuerySeed::new(vec![QueryOperation::Distinct], vec![]),
+        ];
+        let mut gen = GeneratorState::default();
+        seeds
+            .into_iter()
+            .map(|seed| {
+                let query = gen.generate_query(seed.clone());
+                let artifact = Artifact::new(&query, &HashMap::new(), &[]);
+                (seed, artifact)
+            })
+            .collect()
+    }
+
+    fn options(restart_on_failure: bool) -> RuntimeOptions {
+        RuntimeOptions {
+            install_timeout: Duration::from_millis(100),
+            query_timeout: Duration::from_millis(100),
+            restart_on_failure,
+        }
+    }
+
+    async fn run_with_marker(
+        misbehavior: Misbehavior,
+        phase: Phase,
+        restart_on_failure: bool,
+    ) -> (RunSummary, MockDriver) {
+        let queries = queries();
+        let mut driver = MockDriver {
+            marker: queries[1].1.clone(),
+            misbehavior,
+            phase,
+            restarts: 0,
+        };
+        let summary = run_queries(&mut driver, queries, options(restart_on_failure))
+            .await
+            .unwrap();
+        (summary, driver)
+    }
+
+    #[tokio::test]
+    async fn hang_during_install_times_out() {
+        let (summary, driver) = run_with_marker(Misbehavior::Hang, Phase::Install, true).await;
+        let outcomes = summary
+            .results
+            .iter()
+            .map(|res| res.outcome.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            outcomes,
+            vec![
+                QueryOutcome::Completed(None),
+                QueryOutcome::TimedOut {
+                    phase: Phase::Install
+                },
+                QueryOutcome::Completed(None),
+                QueryOutcome::Completed(None),
+            ]
+        );
+        assert_eq!(driver.restarts, 1);
+        assert_eq!(summary.results[1].artifact.as_ref(), Some(&driver.marker));
+        assert!(summary.results[0].artifact.is_none());
+    }
+
+    #[tokio::test]
+    async fn hang_during_execute_times_out() {
+        let (summary, driver) = run_with_marker(Misbehavior::Hang, Phase::Execute, false).await;
+        assert_eq!(
+            summary.results[1].outcome,
+            QueryOutcome::TimedOut {
+                phase: Phase::Execute
+            }
+        );
+        assert!(summary.results[2..]
+            .iter()
+            .all(|res| res.outcome == QueryOutcome::Completed(None)));
+        assert_eq!(driver.restarts, 0);
+    }
+
+    #[tokio::test]
+    async fn summary_includes_incomplete_seeds() {
+        let (summary, _) = run_with_marker(Misbehavior::Hang, Phase::Install, true).await;
+        assert_eq!(
+            summary.outcome_counts(),
+            vec![
+                ("completed", 3),
+                ("timed_out", 1),
+                ("panicked", 0),
+                ("skipped", 0)
+            ]
+        );
+
+        let json = summary.to_json();
+        assert_eq!(json["outcomes"]["completed"], 3);
+        assert_eq!(json["outcomes"]["timed_out"], 1);
+        let incomplete = json["incomplete"].as_array().unwrap();
+        assert_eq!(incomplete.len(), 1);
+        assert_eq!(incomplete[0]["phase"], "install");
+        assert_eq!(
+            serde_json::from_value::<QuerySeed>(incomplete[0]["seed"].clone()).unwrap(),
+            summary.results[1].seed
+        );
+    }
+
+    #[tokio::test]
+    async fn panics_are_isolated() {
+        let (summary, driver) = run_with_marker(Misbehavior::Panic, Phase::Install, true).await;
+        assert_eq!(
+            summary.results[1].outcome,
+            QueryOutcome::Panicked {
+                phase: Phase::Install,
+                message: "marker query exploded".to_owned()
+            }
+        );
+        assert!(summary.results[2..]
+            .iter()
+            .all(|res| res.outcome == QueryOutcome::Completed(None)));
+        assert_eq!(driver.restarts, 1);
+    }
+}
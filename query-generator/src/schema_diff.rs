@@ -0,0 +1,535 @@
+//! Computing the difference between the schemas of two [`GeneratorState`]s, as the DDL statements
+//! which transform one into the other.
+//!
+//! Fuzzing sessions which alter the schema of their tables (with [`TableSpec::drop_column`],
+//! [`TableSpec::rename_column`], and so on) can use [`GeneratorState::schema_diff`] to reproduce
+//! the final schema from the initial one directly, rather than replaying every change. Applying
+//! the statements from [`SchemaDiff::to_statements`] to the initial state with
+//! [`GeneratorState::apply_ddl`] always results in the same `CREATE TABLE` statements as the final
+//! state: tables which can't be altered into their final form (for example, because their keys
+//! changed) are dropped and recreated instead.
+
+use std::collections::BTreeSet;
+
+use anyhow::{anyhow, bail};
+use nom_sql::{
+    AlterTableDefinition, AlterTableStatement, ColumnSpecification, CreateTableStatement,
+    DropTableStatement, Relation, SqlQuery, SqlType,
+};
+
+use crate::{ColumnName, GeneratorState, TableName, TableSpec};
+
+/// The difference between a table which exists in both of two schemas
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableDiff {
+    pub table: TableName,
+    /// Columns in the new table which aren't in the old table (and weren't renamed from one of its
+    /// columns), along with their types, in the order they appear in the new table
+    pub added_columns: Vec<(ColumnName, SqlType)>,
+    /// Columns in the old table which aren't in the new table (and weren't renamed to one of its
+    /// columns), in the order they appear in the old table
+    pub dropped_columns: Vec<ColumnName>,
+    /// Pairs of the old and new names of columns which were renamed
+    pub renamed_columns: Vec<(ColumnName, ColumnName)>,
+    /// Columns whose type changed, along with their new types, in the order they appear in the new
+    /// table
+    pub changed_types: Vec<(ColumnName, SqlType)>,
+    /// Whether the primary key, secondary indexes, or foreign keys of the table changed, other
+    /// than by renaming their columns
+    pub keys_changed: bool,
+    /// Whether the table has to be dropped and recreated, because altering its columns can't
+    /// reproduce the new table - for example because its keys changed, or because its columns
+    /// were reordered
+    pub recreate: bool,
+    /// The `CREATE TABLE` statement for the new table
+    pub new_table: CreateTableStatement,
+}
+
+/// Rename detection: a column dropped from a table and a column added to it are considered to be
+/// the same column if they have the same type and data generator
+fn same_column(
+    old: &TableSpec,
+    old_col: &ColumnName,
+    new: &TableSpec,
+    new_col: &ColumnName,
+) -> bool {
+    let (old_spec, new_spec) = (&old.columns[old_col], &new.columns[new_col]);
+    // The two columns may share a generator (if one table is a clone of the other), so only lock
+    // one of them at a time
+    let old_generator = old_spec.gen_spec.lock().generator.clone();
+    old_spec.sql_type == new_spec.sql_type && old_generator == new_spec.gen_spec.lock().generator
+}
+
+impl TableDiff {
+    /// Compute the difference between the `old` and `new` versions of a table, or return `None` if
+    /// they have the same schema
+    fn new(old: &TableSpec, new: &TableSpec) -> Option<Self> {
+        let dropped = old
+            .column_order
+            .iter()
+            .filter(|col| !new.columns.contains_key(*col))
+            .collect::<Vec<_>>();
+        let added = new
+            .column_order
+            .iter()
+            .filter(|col| !old.columns.contains_key(*col))
+            .collect::<Vec<_>>();
+
+        // Columns are only detected as renamed if every dropped column pairs up, in order, with
+        // an added column
+        let is_rename = dropped.len() == added.len()
+            && dropped
+                .iter()
+                .zip(&added)
+                .all(|(old_col, new_col)| same_column(old, old_col, new, new_col));
+        let (renamed_columns, dropped_columns, added_columns) = if is_rename {
+            let renamed = dropped
+                .into_iter()
+                .zip(added)
+                .map(|(old_col, new_col)| (old_col.clone(), new_col.clone()))
+                .collect();
+            (renamed, vec![], vec![])
+        } else {
+            (
+                vec![],
+                dropped.into_iter().cloned().collect(),
+                added
+                    .into_iter()
+                    .map(|col| (col.clone(), new.columns[col].sql_type.clone()))
+                    .collect(),
+            )
+        };
+
+        let changed_types = new
+            .column_order
+            .iter()
+            .filter_map(|col| {
+                let old_spec = old.columns.get(col)?;
+                let new_type = &new.columns[col].sql_type;
+                (old_spec.sql_type != *new_type).then(|| (col.clone(), new_type.clone()))
+            })
+            .collect();
+
+        let mut diff = Self {
+            table: new.name.clone(),
+            added_columns,
+            dropped_columns,
+            renamed_columns,
+            changed_types,
+            keys_changed: false,
+            recreate: false,
+            new_table: new.clone().into(),
+        };
+
+        // Check that altering the old table reproduces the new one exactly, and recreate it if not
+        let mut altered = old.clone();
+        let altered_table = diff
+            .alter_definitions()
+            .iter()
+            .try_for_each(|def| apply_alter_definition(&mut altered, def))
+            .ok()
+            .map(|()| CreateTableStatement::from(altered));
+        let keys = |table: &CreateTableStatement| {
+            table.body.as_ref().ok().and_then(|body| body.keys.clone())
+        };
+        diff.keys_changed = altered_table.as_ref().map(keys) != Some(keys(&diff.new_table));
+        diff.recreate = altered_table.as_ref() != Some(&diff.new_table);
+
+        if diff.alter_definitions().is_empty() && !diff.recreate {
+            None
+        } else {
+            Some(diff)
+        }
+    }
+
+    /// The definitions of an `ALTER TABLE` statement which alters the columns of the old table to
+    /// match the new table: renames first, then drops, type changes, and finally additions.
+    fn alter_definitions(&self) -> Vec<AlterTableDefinition> {
+        let column_spec = |name: &ColumnName, sql_type: &SqlType| ColumnSpecification {
+            column: name.clone().into(),
+            sql_type: sql_type.clone(),
+            constraints: vec![],
+            comment: None,
+        };
+
+        self.renamed_columns
+            .iter()
+            .map(|(old, new)| AlterTableDefinition::RenameColumn {
+                name: old.clone().into(),
+                new_name: new.clone().into(),
+            })
+            .chain(
+                self.dropped_columns
+                    .iter()
+                    .map(|col| AlterTableDefinition::DropColumn {
+                        name: col.clone().into(),
+                        behavior: None,
+                    }),
+            )
+            .chain(self.changed_types.iter().map(|(col, sql_type)| {
+                AlterTableDefinition::ChangeColumn {
+                    name: col.clone().into(),
+                    spec: column_spec(col, sql_type),
+                }
+            }))
+            .chain(
+                self.added_columns.iter().map(|(col, sql_type)| {
+                    AlterTableDefinition::AddColumn(column_spec(col, sql_type))
+                }),
+            )
+            .collect()
+    }
+}
+
+/// The difference between the schemas of two [`GeneratorState`]s. See [the module
+/// documentation](self).
+///
+/// All the lists in a [`SchemaDiff`] are sorted by table name.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SchemaDiff {
+    /// `CREATE TABLE` statements for the tables which only exist in the new schema
+    pub added_tables: Vec<CreateTableStatement>,
+    /// The names of the tables which only exist in the old schema
+    pub dropped_tables: Vec<TableName>,
+    /// The differences between the tables which exist in both schemas, but differ between them
+    pub altered_tables: Vec<TableDiff>,
+}
+
+impl SchemaDiff {
+    /// Returns true if both schemas are the same
+    pub fn is_empty(&self) -> bool {
+        self.added_tables.is_empty()
+            && self.dropped_tables.is_empty()
+            && self.altered_tables.is_empty()
+    }
+
+    /// Render this diff as a list of DDL statements which transform the old schema into the new
+    /// schema: a `DROP TABLE` for all the dropped tables, followed by an `ALTER TABLE` (or a `DROP
+    /// TABLE` and a `CREATE TABLE`) for each altered table, followed by a `CREATE TABLE` for each
+    /// added table.
+    pub fn to_statements(&self) -> Vec<SqlQuery> {
+        let drop_tables = |tables: Vec<Relation>| {
+            SqlQuery::DropTable(DropTableStatement {
+                tables,
+                if_exists: false,
+            })
+        };
+
+        let mut res = vec![];
+        if !self.dropped_tables.is_empty() {
+            res.push(drop_tables(
+                self.dropped_tables
+                    .iter()
+                    .map(|table| table.clone().into())
+                    .collect(),
+            ));
+        }
+
+        for diff in &self.altered_tables {
+            if diff.recreate {
+                res.push(drop_tables(vec![diff.table.clone().into()]));
+                res.push(SqlQuery::CreateTable(diff.new_table.clone()));
+            } else {
+                res.push(SqlQuery::AlterTable(AlterTableStatement {
+                    table: diff.table.clone().into(),
+                    definitions: Ok(diff.alter_definitions()),
+                    only: false,
+                }));
+            }
+        }
+
+        res.extend(self.added_tables.iter().cloned().map(SqlQuery::CreateTable));
+        res
+    }
+}
+
+/// Apply a single definition of an `ALTER TABLE` statement generated by
+/// [`SchemaDiff::to_statements`] to `table`
+fn apply_alter_definition(table: &mut TableSpec, def: &AlterTableDefinition) -> anyhow::Result<()> {
+    match def {
+        AlterTableDefinition::AddColumn(spec) => {
+            let column_name = ColumnName::from(spec.column.name.clone());
+            if table.columns.contains_key(&column_name) {
+                bail!(
+                    "Column {} already exists in table {}",
+                    column_name,
+                    table.name
+                );
+            }
+            table.add_column(column_name, spec.sql_type.clone());
+        }
+        AlterTableDefinition::DropColumn { name, .. } => {
+            table.drop_column(&name.clone().into())?;
+        }
+        AlterTableDefinition::RenameColumn { name, new_name } => {
+            table.rename_column(&name.clone().into(), new_name.clone().into())?;
+        }
+        AlterTableDefinition::ChangeColumn { name, spec } => {
+            let old_name = ColumnName::from(name.clone());
+            let new_name = ColumnName::from(spec.column.name.clone());
+            if new_name != old_name {
+                table.rename_column(&old_name, new_name.clone())?;
+            }
+            table.change_column_type(&new_name, spec.sql_type.clone())?;
+        }
+        _ => bail!("Unsupported ALTER TABLE definition: {:?}", def),
+    }
+    Ok(())
+}
+
+impl GeneratorState {
+    /// Compute the difference between the schema of this generator state and the schema of
+    /// `other`. See [the module documentation](crate::schema_diff).
+    pub fn schema_diff(&self, other: &GeneratorState) -> SchemaDiff {
+        let mut diff = SchemaDiff::default();
+        let table_names = self
+            .tables
+            .keys()
+            .chain(other.tables.keys())
+            .collect::<BTreeSet<_>>();
+        for table_name in table_names {
+            match (self.tables.get(table_name), other.tables.get(table_name)) {
+                (Some(_), None) => diff.dropped_tables.push(table_name.clone()),
+                (None, Some(new)) => diff.added_tables.push(new.clone().into()),
+                (Some(old), Some(new)) => diff.altered_tables.extend(TableDiff::new(old, new)),
+                (None, None) => {}
+            }
+        }
+        diff
+    }
+
+    /// Apply the given `CREATE TABLE`, `DROP TABLE`, or `ALTER TABLE` statement to the schema of
+    /// this generator state. Only the `ALTER TABLE` definitions generated by
+    /// [`SchemaDiff::to_statements`] are supported.
+    ///
+    /// Tables created by this method generate data the same way as tables read from a schema file.
+    pub fn apply_ddl(&mut self, statement: &SqlQuery) -> anyhow::Result<()> {
+        match statement {
+            SqlQuery::CreateTable(create) => {
+                let table_name = TableName::from(create.table.name.clone());
+                if self.tables.contains_key(&table_name) {
+                    bail!("Table {} already exists", table_name);
+                }
+                self.tables.insert(table_name, create.clone().into());
+            }
+            SqlQuery::DropTable(drop) => {
+                for table in &drop.tables {
+                    let table_name = TableName::from(table.name.clone());
+                    if self.tables.remove(&table_name).is_none() && !drop.if_exists {
+                        bail!("Table {} not found", table_name);
+                    }
+                }
+            }
+            SqlQuery::AlterTable(alter) => {
+                let table_name = TableName::from(alter.table.name.clone());
+                let table = self
+                    .tables
+                    .get_mut(&table_name)
+                    .ok_or_else(|| anyhow!("Table {} not found", table_name))?;
+                let definitions = alter
+                    .definitions
+                    .as_ref()
+                    .map_err(|rest| anyhow!("Failed to parse ALTER TABLE definitions: {rest}"))?;
+                for def in definitions {
+                    apply_alter_definition(table, def)?;
+                }
+            }
+            _ => bail!("Unsupported DDL statement: {:?}", statement),
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use nom_sql::{parse_create_table, Dialect};
+
+    use super::*;
+
+    fn state(ddl: &[&str]) -> GeneratorState {
+        GeneratorState::from(
+            ddl.iter()
+                .map(|stmt| parse_create_table(Dialect::MySQL, stmt).unwrap())
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    fn sorted_ddl(state: &GeneratorState) -> BTreeMap<TableName, CreateTableStatement> {
+        state
+            .ddl()
+            .map(|stmt| (stmt.table.name.clone().into(), stmt))
+            .collect()
+    }
+
+    /// Assert that applying the diff between `old` and `new` to `old` results in `new`, and return
+    /// the diff
+    fn assert_round_trip(old: &GeneratorState, new: &GeneratorState) -> SchemaDiff {
+        let diff = old.schema_diff(new);
+        let mut applied = old.clone();
+        for statement in diff.to_statements() {
+            applied.apply_ddl(&statement).unwrap();
+        }
+        assert_eq!(sorted_ddl(&applied), sorted_ddl(new));
+        assert!(applied.schema_diff(new).is_empty());
+        diff
+    }
+
+    #[test]
+    fn identical_schemas() {
+        let gen = state(&["CREATE TABLE t (a INT, b TEXT, PRIMARY KEY (a))"]);
+        let diff = gen.schema_diff(&gen.clone());
+        assert!(diff.is_empty());
+        assert!(diff.to_statements().is_empty());
+    }
+
+    #[test]
+    fn added_and_dropped_tables() {
+        let old = state(&[
+            "CREATE TABLE t1 (a INT)",
+            "CREATE TABLE t2 (a INT)",
+            "CREATE TABLE t4 (a INT)",
+        ]);
+        let new = state(&[
+            "CREATE TABLE t2 (a INT)",
+            "CREATE TABLE t5 (b TEXT)",
+            "CREATE TABLE t3 (a INT)",
+        ]);
+        let diff = assert_round_trip(&old, &new);
+        assert_eq!(
+            diff.dropped_tables,
+            vec![TableName::from("t1"), TableName::from("t4")]
+        );
+        assert_eq!(
+            diff.added_tables
+                .iter()
+                .map(|t| t.table.name.to_string())
+                .collect::<Vec<_>>(),
+            vec!["t3", "t5"]
+        );
+        assert!(diff.altered_tables.is_empty());
+        assert!(matches!(
+            diff.to_statements().as_slice(),
+            [
+                SqlQuery::DropTable(_),
+                SqlQuery::CreateTable(_),
+                SqlQuery::CreateTable(_)
+            ]
+        ));
+    }
+
+    #[test]
+    fn renamed_columns() {
+        let old = state(&["CREATE TABLE t (a INT, b TEXT, c INT, PRIMARY KEY (a), KEY (c))"]);
+        let mut new = old.clone();
+        let table = new.table_mut("t").unwrap();
+        table.rename_column(&"b".into(), "x".into()).unwrap();
+        table.rename_column(&"c".into(), "y".into()).unwrap();
+
+        let diff = assert_round_trip(&old, &new);
+        assert_eq!(diff.altered_tables.len(), 1);
+        let table_diff = &diff.altered_tables[0];
+        assert_eq!(
+            table_diff.renamed_columns,
+            vec![("b".into(), "x".into()), ("c".into(), "y".into())]
+        );
+        assert!(table_diff.added_columns.is_empty());
+        assert!(table_diff.dropped_columns.is_empty());
+        assert!(!table_diff.keys_changed);
+        assert!(!table_diff.recreate);
+    }
+
+    #[test]
+    fn dropped_and_added_columns() {
+        let old = state(&["CREATE TABLE t (a INT, b TEXT, c INT)"]);
+        // `b` and `d` have different types, so aren't detected as a rename
+        let new = state(&["CREATE TABLE t (a INT, c INT, d INT)"]);
+
+        let diff = assert_round_trip(&old, &new);
+        let table_diff = &diff.altered_tables[0];
+        assert!(table_diff.renamed_columns.is_empty());
+        assert_eq!(table_diff.dropped_columns, vec![ColumnName::from("b")]);
+        assert_eq!(
+            table_diff.added_columns,
+            vec![("d".into(), SqlType::Int(None))]
+        );
+        assert!(!table_diff.recreate);
+    }
+
+    #[test]
+    fn changed_column_types() {
+        let old = state(&["CREATE TABLE t (a INT, b TEXT)"]);
+        let new = state(&["CREATE TABLE t (a BIGINT, b TEXT)"]);
+
+        let diff = assert_round_trip(&old, &new);
+        let table_diff = &diff.altered_tables[0];
+        assert_eq!(
+            table_diff.changed_types,
+            vec![("a".into(), SqlType::BigInt(None))]
+        );
+        assert!(!table_diff.recreate);
+        assert!(matches!(
+            diff.to_statements().as_slice(),
+            [SqlQuery::AlterTable(_)]
+        ));
+
+        // Changing the type of a column in a clone doesn't affect the original
+        assert_eq!(
+            old.tables()[&TableName::from("t")].columns[&ColumnName::from("a")].sql_type,
+            SqlType::Int(None)
+        );
+    }
+
+    #[test]
+    fn changed_keys() {
+        let old = state(&["CREATE TABLE t (a INT, b INT, PRIMARY KEY (a))"]);
+        let new = state(&["CREATE TABLE t (a INT, b INT, PRIMARY KEY (a), UNIQUE KEY (b))"]);
+
+        let diff = assert_round_trip(&old, &new);
+        let table_diff = &diff.altered_tables[0];
+        assert!(table_diff.keys_changed);
+        assert!(table_diff.recreate);
+        assert!(matches!(
+            diff.to_statements().as_slice(),
+            [SqlQuery::DropTable(_), SqlQuery::CreateTable(_)]
+        ));
+    }
+
+    #[test]
+    fn reordered_columns() {
+        let old = state(&["CREATE TABLE t (a INT, b INT)"]);
+        let new = state(&["CREATE TABLE t (b INT, a INT)"]);
+
+        let diff = assert_round_trip(&old, &new);
+        let table_diff = &diff.altered_tables[0];
+        assert!(!table_diff.keys_changed);
+        assert!(table_diff.recreate);
+    }
+
+    #[test]
+    fn diff_is_deterministic() {
+        let old = state(&[
+            "CREATE TABLE t1 (a INT, b TEXT)",
+            "CREATE TABLE t2 (a INT)",
+            "CREATE TABLE t3 (a INT)",
+        ]);
+        let new = state(&[
+            "CREATE TABLE t1 (a INT, c TEXT)",
+            "CREATE TABLE t2 (a INT, b INT)",
+            "CREATE TABLE t3 (a TEXT)",
+            "CREATE TABLE t4 (a INT)",
+        ]);
+        let diff = assert_round_trip(&old, &new);
+        assert_eq!(
+            diff.altered_tables
+                .iter()
+                .map(|t| t.table.clone())
+                .collect::<Vec<_>>(),
+            vec!["t1".into(), "t2".into(), "t3".into()]
+        );
+        for _ in 0..10 {
+            assert_eq!(old.clone().schema_diff(&new.clone()), diff);
+        }
+    }
+}
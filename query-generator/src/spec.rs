@@ -0,0 +1,833 @@
+//! Parsing of the operation specifications accepted by [`Operations::from_str`] and
+//! [`OperationList::from_str`].
+//!
+//! Each specification is an operation name, optionally followed by a parenthesized,
+//! comma-separated list of arguments which override fields of the operations that name expands
+//! to, eg `topk(limit=100,desc)`. An argument is either a bare flag (`desc`) or a `name=value`
+//! pair (`limit=100`). The arguments accepted by each kind of operation are:
+//!
+//! | Operation                | Arguments                                                    |
+//! |--------------------------|--------------------------------------------------------------|
+//! | count, sum, avg          | `distinct`, `type=<sql type>`                                |
+//! | max, min, bit_*          | `type=<sql type>`                                            |
+//! | comparison filters       | `op=<operator>`, `type=<sql type>`                           |
+//! | between, is_null filters | `type=<sql type>`                                            |
+//! | joins                    | `key_type=<sql type>`, `key_columns=<1-3>`                   |
+//! | anti_join                | `overlap_percent=<1-99>`                                     |
+//! | in_parameter             | `num_values=<1-255>`                                         |
+//! | topk                     | `limit=<0-100>`, `asc`, `desc`                               |
+//! | param_topk               | `limit=<1-100>`, `asc`, `desc`                               |
+//! | paginate                 | `limit=<0-100>`, `page_number=<0-100>`, `asc`, `desc`        |
+//! | rollup                   | `num_group_cols=<n>`                                         |
+//!
+//! where `<operator>` is one of `equal`, `not_equal`, `greater`, `greater_or_equal`, `less`,
+//! `less_or_equal`, `like`, `not_like`, `ilike` or `not_ilike`.
+//!
+//! When a name expands to more than one operation (such as `aggregates` or `filters`), operations
+//! which don't accept all of the given arguments are dropped, so eg `filters(op=greater)` only
+//! includes comparison filters. Operations which end up identical after applying the arguments
+//! are only included once.
+//!
+//! [`Operations::from_str`]: std::str::FromStr::from_str
+//! [`OperationList::from_str`]: std::str::FromStr::from_str
+
+use std::str::FromStr;
+
+use anyhow::{anyhow, bail, Context};
+use nom_sql::{BinaryOperator, OrderType, SqlType};
+
+use crate::{
+    AggregateType, FilterOp, Operations, QueryOperation, MAX_JOIN_KEY_COLUMNS, MAX_LIMIT,
+    MAX_PAGE_NUMBER,
+};
+
+/// Split a comma-separated list of operation specifications, ignoring commas within the
+/// parenthesized arguments to an operation.
+pub(crate) fn split_operation_list(s: &str) -> anyhow::Result<Vec<&str>> {
+    let mut res = vec![];
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (i, c) in s.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => {
+                depth = depth
+                    .checked_sub(1)
+                    .ok_or_else(|| anyhow!("unmatched `)` in operation list `{s}`"))?;
+            }
+            ',' if depth == 0 => {
+                res.push(&s[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    if depth != 0 {
+        bail!("unmatched `(` in operation list `{s}`");
+    }
+    res.push(&s[start..]);
+    Ok(res)
+}
+
+fn is_identifier(s: &str) -> bool {
+    !s.is_empty() && s.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// A single argument to an operation specification
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Argument<'a> {
+    name: &'a str,
+    /// The value of the argument, or `None` for bare flags
+    value: Option<&'a str>,
+}
+
+impl<'a> Argument<'a> {
+    fn parse(s: &'a str) -> anyhow::Result<Self> {
+        let (name, value) = match s.split_once('=') {
+            Some((name, value)) => {
+                let value = value.trim();
+                if value.is_empty() {
+                    bail!("missing value for argument `{}`", name.trim());
+                }
+                (name.trim(), Some(value))
+            }
+            None => (s.trim(), None),
+        };
+        if !is_identifier(name) {
+            bail!("invalid argument name `{name}`");
+        }
+        Ok(Self { name, value })
+    }
+}
+
+/// An operation name as given by the user, along with the arguments given to it
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct OperationSpec<'a> {
+    pub(crate) name: &'a str,
+    args: Vec<Argument<'a>>,
+}
+
+impl<'a> OperationSpec<'a> {
+    pub(crate) fn parse(s: &'a str) -> anyhow::Result<Self> {
+        let s = s.trim();
+        let (name, args) = match s.split_once('(') {
+            Some((name, rest)) => {
+                let args = rest
+                    .strip_suffix(')')
+                    .ok_or_else(|| anyhow!("expected `)` at the end of `{s}`"))?;
+                if args.contains(['(', ')']) {
+                    bail!("unexpected parenthesis in the arguments of `{s}`");
+                }
+                (name.trim_end(), args)
+            }
+            None => (s, ""),
+        };
+        if !is_identifier(name) {
+            bail!("invalid operation name `{name}`");
+        }
+
+        let args = if args.trim().is_empty() {
+            vec![]
+        } else {
+            args.split(',')
+                .map(Argument::parse)
+                .collect::<anyhow::Result<Vec<_>>>()
+                .with_context(|| format!("invalid arguments to `{name}`"))?
+        };
+        for (i, arg) in args.iter().enumerate() {
+            if args[..i].iter().any(|other| other.name == arg.name) {
+                bail!("argument `{}` given more than once to `{name}`", arg.name);
+            }
+        }
+        if ["asc", "desc"]
+            .iter()
+            .all(|flag| args.iter().any(|arg| arg.name == *flag))
+        {
+            bail!("only one of `asc` and `desc` may be given to `{name}`");
+        }
+
+        Ok(Self { name, args })
+    }
+
+    /// Override the fields of each of `ops` (which should be the operations named by this
+    /// specification) with the arguments of this specification.
+    pub(crate) fn apply(&self, ops: Operations) -> anyhow::Result<Operations> {
+        if self.args.is_empty() {
+            return Ok(ops);
+        }
+
+        for arg in &self.args {
+            if !ops
+                .0
+                .iter()
+                .any(|op| accepted_arguments(op).contains(&arg.name))
+            {
+                let mut accepted = ops
+                    .0
+                    .iter()
+                    .flat_map(accepted_arguments)
+                    .copied()
+                    .collect::<Vec<_>>();
+                accepted.sort_unstable();
+                accepted.dedup();
+                if accepted.is_empty() {
+                    bail!("`{}` does not accept any arguments", self.name);
+                }
+                bail!(
+                    "unknown argument `{}` for `{}`; expected one of: {}",
+                    arg.name,
+                    self.name,
+                    accepted.join(", ")
+                );
+            }
+        }
+
+        let mut res = vec![];
+        for mut op in ops {
+            if !self
+                .args
+                .iter()
+                .all(|arg| accepted_arguments(&op).contains(&arg.name))
+            {
+                continue;
+            }
+            for arg in &self.args {
+                self.apply_argument(&mut op, arg)?;
+            }
+            if !res.contains(&op) {
+                res.push(op);
+            }
+        }
+
+        if res.is_empty() {
+            bail!(
+                "no operation named by `{}` accepts all of the given arguments",
+                self.name
+            );
+        }
+        Ok(Operations(res))
+    }
+
+    fn flag(&self, arg: &Argument<'_>) -> anyhow::Result<()> {
+        match arg.value {
+            None => Ok(()),
+            Some(_) => bail!(
+                "argument `{}` of `{}` is a flag, and does not take a value",
+                arg.name,
+                self.name
+            ),
+        }
+    }
+
+    fn value<T>(&self, arg: &Argument<'_>) -> anyhow::Result<T>
+    where
+        T: FromStr,
+        T::Err: std::fmt::Display,
+    {
+        let value = arg.value.ok_or_else(|| {
+            anyhow!(
+                "argument `{}` of `{}` requires a value",
+                arg.name,
+                self.name
+            )
+        })?;
+        value.parse().map_err(|e| {
+            anyhow!(
+                "invalid value `{value}` for argument `{}` of `{}`: {e}",
+                arg.name,
+                self.name
+            )
+        })
+    }
+
+    fn value_in_range<T>(
+        &self,
+        arg: &Argument<'_>,
+        range: std::ops::RangeInclusive<T>,
+    ) -> anyhow::Result<T>
+    where
+        T: FromStr + PartialOrd + std::fmt::Display,
+        T::Err: std::fmt::Display,
+    {
+        let value = self.value(arg)?;
+        if !range.contains(&value) {
+            bail!(
+                "invalid value `{value}` for argument `{}` of `{}`: must be between {} and {}",
+                arg.name,
+                self.name,
+                range.start(),
+                range.end()
+            );
+        }
+        Ok(value)
+    }
+
+    fn sql_type(&self, arg: &Argument<'_>) -> anyhow::Result<SqlType> {
+        self.value(arg)
+    }
+
+    fn operator(&self, arg: &Argument<'_>) -> anyhow::Result<BinaryOperator> {
+        let value = self.value::<String>(arg)?;
+        Ok(match value.as_str() {
+            "equal" => BinaryOperator::Equal,
+            "not_equal" => BinaryOperator::NotEqual,
+            "greater" => BinaryOperator::Greater,
+            "greater_or_equal" => BinaryOperator::GreaterOrEqual,
+            "less" => BinaryOperator::Less,
+            "less_or_equal" => BinaryOperator::LessOrEqual,
+            "like" => BinaryOperator::Like,
+            "not_like" => BinaryOperator::NotLike,
+            "ilike" => BinaryOperator::ILike,
+            "not_ilike" => BinaryOperator::NotILike,
+            _ => bail!(
+                "invalid value `{value}` for argument `{}` of `{}`: unknown operator",
+                arg.name,
+                self.name
+            ),
+        })
+    }
+
+    fn order_type(&self, arg: &Argument<'_>) -> anyhow::Result<OrderType> {
+        self.flag(arg)?;
+        Ok(if arg.name == "asc" {
+            OrderType::OrderAscending
+        } else {
+            OrderType::OrderDescending
+        })
+    }
+
+    /// Override the field of `op` named by `arg`, which must be one of the
+    /// [`accepted_arguments`] of `op`
+    fn apply_argument(&self, op: &mut QueryOperation, arg: &Argument<'_>) -> anyhow::Result<()> {
+        match (arg.name, op) {
+            (
+                "distinct",
+                QueryOperation::ColumnAggregate(
+                    AggregateType::Count { distinct, .. }
+                    | AggregateType::Sum { distinct, .. }
+                    | AggregateType::Avg { distinct, .. },
+                ),
+            ) => {
+                self.flag(arg)?;
+                *distinct = true;
+            }
+            (
+                "type",
+                QueryOperation::ColumnAggregate(
+                    AggregateType::Count { column_type, .. }
+                    | AggregateType::Sum { column_type, .. }
+                    | AggregateType::Avg { column_type, .. }
+                    | AggregateType::Max { column_type }
+                    | AggregateType::Min { column_type }
+                    | AggregateType::BitAnd { column_type }
+                    | AggregateType::BitOr { column_type }
+                    | AggregateType::BitXor { column_type },
+                ),
+            ) => *column_type = self.sql_type(arg)?,
+            ("type", QueryOperation::Filter(filter)) => filter.column_type = self.sql_type(arg)?,
+            (
+                "op",
+                QueryOperation::Filter(crate::Filter {
+                    operation: FilterOp::Comparison { op, .. },
+                    ..
+                }),
+            ) => *op = self.operator(arg)?,
+            ("key_type", QueryOperation::Join { key_type, .. }) => {
+                *key_type = self.sql_type(arg)?
+            }
+            ("key_columns", QueryOperation::Join { key_columns, .. }) => {
+                *key_columns = self.value_in_range(arg, 1..=MAX_JOIN_KEY_COLUMNS)?
+            }
+            ("overlap_percent", QueryOperation::AntiJoin { overlap_percent }) => {
+                *overlap_percent = self.value_in_range(arg, 1..=99)?
+            }
+            ("num_values", QueryOperation::InParameter { num_values }) => {
+                *num_values = self.value_in_range(arg, 1..=u8::MAX)?
+            }
+            (
+                "limit",
+                QueryOperation::TopK { limit, .. } | QueryOperation::Paginate { limit, .. },
+            ) => *limit = self.value_in_range(arg, 0..=MAX_LIMIT)?,
+            ("limit", QueryOperation::ParamTopK { limit, .. }) => {
+                *limit = self.value_in_range(arg, 1..=MAX_LIMIT)?
+            }
+            ("page_number", QueryOperation::Paginate { page_number, .. }) => {
+                *page_number = self.value_in_range(arg, 0..=MAX_PAGE_NUMBER)?
+            }
+            (
+                "asc" | "desc",
+                QueryOperation::TopK { order_type, .. }
+                | QueryOperation::ParamTopK { order_type, .. }
+                | QueryOperation::Paginate { order_type, .. },
+            ) => *order_type = self.order_type(arg)?,
+            ("num_group_cols", QueryOperation::Rollup { num_group_cols }) => {
+                *num_group_cols = self.value_in_range(arg, 1..=usize::MAX)?
+            }
+            (name, op) => bail!("argument `{name}` can't be applied to {op:?}"),
+        }
+        Ok(())
+    }
+}
+
+/// Returns the names of the arguments which can be used to override the fields of `op`
+fn accepted_arguments(op: &QueryOperation) -> &'static [&'static str] {
+    match op {
+        QueryOperation::ColumnAggregate(
+            AggregateType::Count { .. } | AggregateType::Sum { .. } | AggregateType::Avg { .. },
+        ) => &["distinct", "type"],
+        QueryOperation::ColumnAggregate(AggregateType::GroupConcat) => &[],
+        QueryOperation::ColumnAggregate(_) => &["type"],
+        QueryOperation::Filter(filter) => match filter.operation {
+            FilterOp::Comparison { .. } => &["op", "type"],
+            FilterOp::Between { .. } | FilterOp::IsNull { .. } => &["type"],
+            // The column type of a coerced comparison is tied to the type of its literal
+            FilterOp::CoercedComparison { .. } => &[],
+        },
+        QueryOperation::Join { .. } => &["key_type", "key_columns"],
+        QueryOperation::AntiJoin { .. } => &["overlap_percent"],
+        QueryOperation::InParameter { .. } => &["num_values"],
+        QueryOperation::TopK { .. } | QueryOperation::ParamTopK { .. } => &["limit", "asc", "desc"],
+        QueryOperation::Paginate { .. } => &["limit", "page_number", "asc", "desc"],
+        QueryOperation::Rollup { .. } => &["num_group_cols"],
+        _ => &[],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nom_sql::JoinOperator;
+
+    use super::*;
+    use crate::{
+        Filter, FilterRHS, FilterTarget, JoinConstraintType, LogicalOp, OperationList,
+        DEFAULT_LIMIT,
+    };
+
+    fn parse(s: &str) -> Vec<QueryOperation> {
+        Operations::from_str(s).unwrap().0
+    }
+
+    fn parse_err(s: &str) -> String {
+        format!("{:#}", Operations::from_str(s).unwrap_err())
+    }
+
+    #[test]
+    fn split_list_ignores_commas_in_arguments() {
+        assert_eq!(
+            split_operation_list("topk(limit=100,desc),count(distinct),joins").unwrap(),
+            vec!["topk(limit=100,desc)", "count(distinct)", "joins"]
+        );
+        assert_eq!(split_operation_list("count").unwrap(), vec!["count"]);
+    }
+
+    #[test]
+    fn split_list_unbalanced() {
+        assert_eq!(
+            split_operation_list("topk(limit=1,count")
+                .unwrap_err()
+                .to_string(),
+            "unmatched `(` in operation list `topk(limit=1,count`"
+        );
+        assert_eq!(
+            split_operation_list("topk),count").unwrap_err().to_string(),
+            "unmatched `)` in operation list `topk),count`"
+        );
+    }
+
+    #[test]
+    fn parse_spec() {
+        assert_eq!(
+            OperationSpec::parse(" topk ( limit = 100 , desc ) ").unwrap(),
+            OperationSpec {
+                name: "topk",
+                args: vec![
+                    Argument {
+                        name: "limit",
+                        value: Some("100"),
+                    },
+                    Argument {
+                        name: "desc",
+                        value: None,
+                    },
+                ],
+            }
+        );
+        assert_eq!(
+            OperationSpec::parse("topk()").unwrap(),
+            OperationSpec {
+                name: "topk",
+                args: vec![],
+            }
+        );
+    }
+
+    #[test]
+    fn bare_names_are_unchanged() {
+        assert_eq!(parse("topk()"), parse("topk"));
+        assert_eq!(
+            parse("count"),
+            vec![QueryOperation::ColumnAggregate(AggregateType::Count {
+                column_type: SqlType::Int(None),
+                distinct: false,
+            })]
+        );
+    }
+
+    #[test]
+    fn topk_arguments() {
+        assert_eq!(
+            parse("topk(limit=100,desc)"),
+            vec![QueryOperation::TopK {
+                order_type: OrderType::OrderDescending,
+                limit: 100,
+            }]
+        );
+        assert_eq!(
+            parse("topk(asc)"),
+            vec![QueryOperation::TopK {
+                order_type: OrderType::OrderAscending,
+                limit: DEFAULT_LIMIT,
+            }]
+        );
+        assert_eq!(
+            parse("topk(limit=7)"),
+            vec![
+                QueryOperation::TopK {
+                    order_type: OrderType::OrderAscending,
+                    limit: 7,
+                },
+                QueryOperation::TopK {
+                    order_type: OrderType::OrderDescending,
+                    limit: 7,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn param_topk_arguments() {
+        assert_eq!(
+            parse("param_topk(limit=20,asc)"),
+            vec![QueryOperation::ParamTopK {
+                order_type: OrderType::OrderAscending,
+                limit: 20,
+            }]
+        );
+        assert_eq!(
+            parse_err("param_topk(limit=0)"),
+            "invalid value `0` for argument `limit` of `param_topk`: must be between 1 and 100"
+        );
+        assert_eq!(
+            parse_err("param_topk(limit=18446744073709551615)"),
+            "invalid value `18446744073709551615` for argument `limit` of `param_topk`: must be \
+             between 1 and 100"
+        );
+    }
+
+    #[test]
+    fn paginate_arguments() {
+        assert_eq!(
+            parse("paginate(limit=5,page_number=2,desc)"),
+            vec![QueryOperation::Paginate {
+                order_type: OrderType::OrderDescending,
+                limit: 5,
+                page_number: 2,
+            }]
+        );
+        assert_eq!(
+            parse_err("paginate(limit=101)"),
+            "invalid value `101` for argument `limit` of `paginate`: must be between 0 and 100"
+        );
+        assert_eq!(
+            parse_err("paginate(page_number=1000)"),
+            "invalid value `1000` for argument `page_number` of `paginate`: must be between 0 and \
+             100"
+        );
+    }
+
+    #[test]
+    fn aggregate_arguments() {
+        assert_eq!(
+            parse("count(distinct)"),
+            vec![QueryOperation::ColumnAggregate(AggregateType::Count {
+                column_type: SqlType::Int(None),
+                distinct: true,
+            })]
+        );
+        assert_eq!(
+            parse("sum(type=bigint)"),
+            vec![QueryOperation::ColumnAggregate(AggregateType::Sum {
+                column_type: SqlType::BigInt(None),
+                distinct: false,
+            })]
+        );
+        assert_eq!(
+            parse("avg(type=double,distinct)"),
+            vec![QueryOperation::ColumnAggregate(AggregateType::Avg {
+                column_type: SqlType::Double,
+                distinct: true,
+            })]
+        );
+        assert_eq!(
+            parse("max(type=text)"),
+            vec![QueryOperation::ColumnAggregate(AggregateType::Max {
+                column_type: SqlType::Text,
+            })]
+        );
+        assert_eq!(
+            parse("bit_or(type=bigint)"),
+            vec![QueryOperation::ColumnAggregate(AggregateType::BitOr {
+                column_type: SqlType::BigInt(None),
+            })]
+        );
+    }
+
+    #[test]
+    fn aggregates_distinct_drops_aggregates_without_distinct() {
+        let ops = parse("aggregates(distinct)");
+        assert_eq!(ops.len(), 3);
+        assert!(ops.iter().all(|op| matches!(
+            op,
+            QueryOperation::ColumnAggregate(
+                AggregateType::Count { distinct: true, .. }
+                    | AggregateType::Sum { distinct: true, .. }
+                    | AggregateType::Avg { distinct: true, .. }
+            )
+        )));
+    }
+
+    #[test]
+    fn filter_arguments() {
+        let ops = parse("filters(op=greater)");
+        assert!(!ops.is_empty());
+        assert!(ops.iter().all(|op| matches!(
+            op,
+            QueryOperation::Filter(Filter {
+                operation: FilterOp::Comparison {
+                    op: BinaryOperator::Greater,
+                    ..
+                },
+                ..
+            })
+        )));
+        assert_eq!(
+            Operations::from_str("filters(op=greater)").unwrap(),
+            Operations::from_str("greater_filters").unwrap()
+        );
+
+        assert_eq!(
+            parse("is_null_filters(type=text)"),
+            parse("is_null_filters")
+                .into_iter()
+                .map(|op| match op {
+                    QueryOperation::Filter(filter) => QueryOperation::Filter(Filter {
+                        column_type: SqlType::Text,
+                        ..filter
+                    }),
+                    _ => unreachable!(),
+                })
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn join_arguments() {
+        assert_eq!(
+            parse("inner_join(key_type=text)"),
+            vec![QueryOperation::Join {
+                operator: JoinOperator::InnerJoin,
+                constraint: JoinConstraintType::On,
+                key_type: SqlType::Text,
+                key_columns: 1,
+            }]
+        );
+        assert!(parse("joins(key_type=bigint)").iter().all(|op| matches!(
+            op,
+            QueryOperation::Join {
+                key_type: SqlType::BigInt(None),
+                ..
+            }
+        )));
+        assert_eq!(
+            parse("inner_join_using(key_columns=3)"),
+            vec![QueryOperation::Join {
+                operator: JoinOperator::InnerJoin,
+                constraint: JoinConstraintType::Using,
+                key_type: SqlType::Int(None),
+                key_columns: 3,
+            }]
+        );
+        assert_eq!(parse("composite_join"), parse("inner_join(key_columns=2)"));
+    }
+
+    #[test]
+    fn numeric_arguments() {
+        assert_eq!(
+            parse("anti_join(overlap_percent=10)"),
+            vec![QueryOperation::AntiJoin {
+                overlap_percent: 10
+            }]
+        );
+        assert_eq!(
+            parse("in_parameter(num_values=7)"),
+            vec![QueryOperation::InParameter { num_values: 7 }]
+        );
+        assert_eq!(
+            parse("rollup(num_group_cols=3)"),
+            vec![QueryOperation::Rollup { num_group_cols: 3 }]
+        );
+    }
+
+    #[test]
+    fn unknown_argument() {
+        assert_eq!(
+            parse_err("topk(limt=10)"),
+            "unknown argument `limt` for `topk`; expected one of: asc, desc, limit"
+        );
+        assert_eq!(
+            parse_err("count(distnct)"),
+            "unknown argument `distnct` for `count`; expected one of: distinct, type"
+        );
+        assert_eq!(
+            parse_err("distinct(limit=1)"),
+            "`distinct` does not accept any arguments"
+        );
+    }
+
+    #[test]
+    fn argument_not_accepted_by_any_operation() {
+        assert_eq!(
+            parse_err("max(distinct)"),
+            "unknown argument `distinct` for `max`; expected one of: type"
+        );
+        assert_eq!(
+            parse_err("is_null_filters(op=greater)"),
+            "unknown argument `op` for `is_null_filters`; expected one of: type"
+        );
+    }
+
+    #[test]
+    fn invalid_values() {
+        assert_eq!(
+            parse_err("topk(limit=ten)"),
+            "invalid value `ten` for argument `limit` of `topk`: invalid digit found in string"
+        );
+        assert_eq!(
+            parse_err("sum(type=bigitn)"),
+            "invalid value `bigitn` for argument `type` of `sum`: failed to parse"
+        );
+        assert_eq!(
+            parse_err("filters(op=greatr)"),
+            "invalid value `greatr` for argument `op` of `filters`: unknown operator"
+        );
+        assert_eq!(
+            parse_err("anti_join(overlap_percent=100)"),
+            "invalid value `100` for argument `overlap_percent` of `anti_join`: must be between 1 \
+             and 99"
+        );
+        assert_eq!(
+            parse_err("topk(limit)"),
+            "argument `limit` of `topk` requires a value"
+        );
+        assert_eq!(
+            parse_err("count(distinct=true)"),
+            "argument `distinct` of `count` is a flag, and does not take a value"
+        );
+    }
+
+    #[test]
+    fn malformed_specs() {
+        assert_eq!(
+            parse_err("topk(limit=1"),
+            "expected `)` at the end of `topk(limit=1`"
+        );
+        assert_eq!(
+            parse_err("topk(limit=(1))"),
+            "unexpected parenthesis in the arguments of `topk(limit=(1))`"
+        );
+        assert_eq!(
+            parse_err("topk(limit=1,,desc)"),
+            "invalid arguments to `topk`: invalid argument name ``"
+        );
+        assert_eq!(
+            parse_err("topk(limit=)"),
+            "invalid arguments to `topk`: missing value for argument `limit`"
+        );
+        assert_eq!(
+            parse_err("topk(limit=1,limit=2)"),
+            "argument `limit` given more than once to `topk`"
+        );
+        assert_eq!(
+            parse_err("topk(asc,desc)"),
+            "only one of `asc` and `desc` may be given to `topk`"
+        );
+        assert_eq!(parse_err("top k"), "invalid operation name `top k`");
+        assert_eq!(parse_err("tpok(desc)"), "unknown query operation: tpok");
+    }
+
+    #[test]
+    fn operation_list_with_arguments() {
+        let OperationList(res) =
+            OperationList::from_str("topk(limit=100,desc),count(distinct),inner_join").unwrap();
+        assert_eq!(
+            format!("{res:?}"),
+            "[Operations([TopK { order_type: OrderDescending, limit: 100 }]), \
+             Operations([ColumnAggregate(Count { column_type: Int(None), distinct: true })]), \
+             Operations([Join { operator: InnerJoin, constraint: On, key_type: Int(None), key_columns: 1 \
+             }])]"
+        );
+    }
+
+    #[test]
+    fn debug_round_trip() {
+        for (spec, expected) in [
+            (
+                "topk(limit=100,desc)",
+                "Operations([TopK { order_type: OrderDescending, limit: 100 }])",
+            ),
+            (
+                "count(distinct)",
+                "Operations([ColumnAggregate(Count { column_type: Int(None), distinct: true })])",
+            ),
+            (
+                "sum(type=bigint)",
+                "Operations([ColumnAggregate(Sum { column_type: BigInt(None), distinct: false \
+                 })])",
+            ),
+            (
+                "inner_join(key_type=text)",
+                "Operations([Join { operator: InnerJoin, constraint: On, key_type: Text, \
+                 key_columns: 1 }])",
+            ),
+            (
+                "equal_filters(op=greater)",
+                "Operations([Filter(Filter { extend_where_with: And, operation: Comparison { op: \
+                 Greater, rhs: Column }, column_type: Int(None), filter_target: Base }), \
+                 Filter(Filter { extend_where_with: Or, operation: Comparison { op: Greater, \
+                 rhs: Column }, column_type: Int(None), filter_target: Base }), Filter(Filter { \
+                 extend_where_with: And, operation: Comparison { op: Greater, rhs: \
+                 Constant(Integer(1)) }, column_type: Int(None), filter_target: Base }), \
+                 Filter(Filter { extend_where_with: Or, operation: Comparison { op: Greater, \
+                 rhs: Constant(Integer(1)) }, column_type: Int(None), filter_target: Base })])",
+            ),
+        ] {
+            assert_eq!(
+                format!("{:?}", Operations::from_str(spec).unwrap()),
+                expected
+            );
+        }
+        // Make sure the filters in the expected output above are in the order they're generated
+        assert_eq!(
+            parse("equal_filters(op=greater)")[0],
+            QueryOperation::Filter(Filter {
+                extend_where_with: LogicalOp::And,
+                operation: FilterOp::Comparison {
+                    op: BinaryOperator::Greater,
+                    rhs: FilterRHS::Column,
+                },
+                column_type: SqlType::Int(None),
+                filter_target: FilterTarget::Base,
+            })
+        );
+    }
+}
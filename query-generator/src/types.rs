@@ -21,6 +21,24 @@ pub fn arbitrary_numeric_type(dialect: Option<Dialect>) -> impl Strategy<Value =
     proptest::sample::select(variants)
 }
 
+/// Returns a proptest strategy to generate *integer* [`SqlType`]s, optionally filtering to only
+/// those which are valid for the given SQL dialect
+pub fn arbitrary_integer_type(dialect: Option<Dialect>) -> impl Strategy<Value = SqlType> {
+    let mut variants = vec![SmallInt(None), Int(None), BigInt(None)];
+
+    if dialect.is_none() || dialect == Some(Dialect::MySQL) {
+        variants.extend([
+            TinyInt(None),
+            UnsignedInt(None),
+            UnsignedBigInt(None),
+            UnsignedTinyInt(None),
+            UnsignedSmallInt(None),
+        ])
+    }
+
+    proptest::sample::select(variants)
+}
+
 /// Returns a proptest strategy to generate types which are valid as the argument to the `min` and
 /// `max` aggregates in PostgreSQL.
 ///
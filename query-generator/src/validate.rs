@@ -0,0 +1,287 @@
+//! Checking the correctness of generated queries by comparing the results of running them against
+//! ReadySet with the results of running them against a reference database.
+//!
+//! Each query is validated by writing an [`Artifact`] for it, replaying that artifact against
+//! both databases, and comparing the rows returned for each key as multisets. Since the artifact
+//! contains everything needed to reproduce the query, failed validations can be saved and later
+//! replayed with the `replay` subcommand of the query generator CLI.
+
+use std::collections::HashMap;
+use std::fmt::{self, Display};
+
+use anyhow::{bail, Context};
+use database_utils::{DatabaseConnection, QueryableConnection};
+use itertools::Itertools;
+use nom_sql::{Dialect, DialectDisplay};
+use readyset_data::DfValue;
+use readyset_util::hash::stable_hash;
+
+use crate::artifact::Artifact;
+use crate::Query;
+
+/// A difference between the rows returned by ReadySet and the rows returned by the reference
+/// database for a single key
+#[derive(Debug, Clone, PartialEq)]
+pub struct RowDiff {
+    /// The key the query was executed with
+    pub key: Vec<DfValue>,
+    /// Rows returned by the reference database but not by ReadySet
+    pub missing: Vec<Vec<DfValue>>,
+    /// Rows returned by ReadySet but not by the reference database
+    pub extra: Vec<Vec<DfValue>>,
+}
+
+/// The result of a failed validation of a generated query
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidationFailure {
+    /// The query that was validated, along with the data and keys it was validated with
+    pub artifact: Artifact,
+    /// The differences between the results from ReadySet and the reference database, for each
+    /// key that had any
+    pub diffs: Vec<RowDiff>,
+}
+
+impl ValidationFailure {
+    /// Returns a stable identifier for the query that failed validation, suitable for use in a
+    /// file name.
+    ///
+    /// See [`stable_hash`] for the hash function used.
+    pub fn query_hash(&self) -> String {
+        let query = self.artifact.query.display(Dialect::MySQL).to_string();
+        format!("{:016x}", stable_hash(&query))
+    }
+}
+
+impl Display for ValidationFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Results differ for query: {}",
+            self.artifact.query.display(Dialect::MySQL)
+        )?;
+        for diff in &self.diffs {
+            writeln!(f, "  Key: {:?}", diff.key)?;
+            for row in &diff.missing {
+                writeln!(f, "  - {}", row.iter().join(" | "))?;
+            }
+            for row in &diff.extra {
+                writeln!(f, "  + {}", row.iter().join(" | "))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Compare the rows returned by the reference database with the rows returned by ReadySet for a
+/// single key, ignoring order, returning `None` if they contain the same rows
+pub fn diff_rows(
+    key: Vec<DfValue>,
+    reference: Vec<Vec<DfValue>>,
+    readyset: Vec<Vec<DfValue>>,
+) -> Option<RowDiff> {
+    let mut counts: HashMap<Vec<DfValue>, isize> = HashMap::new();
+    for row in reference {
+        *counts.entry(row).or_default() += 1;
+    }
+    for row in readyset {
+        *counts.entry(row).or_default() -= 1;
+    }
+
+    let mut missing = vec![];
+    let mut extra = vec![];
+    for (row, count) in counts.into_iter().sorted() {
+        match count {
+            n if n > 0 => missing.extend(itertools::repeat_n(row, n as usize)),
+            n if n < 0 => extra.extend(itertools::repeat_n(row, n.unsigned_abs())),
+            _ => {}
+        }
+    }
+
+    if missing.is_empty() && extra.is_empty() {
+        None
+    } else {
+        Some(RowDiff {
+            key,
+            missing,
+            extra,
+        })
+    }
+}
+
+/// Drop every table in the given artifact from the database at the given connection, if it
+/// exists, so that the artifact can be loaded into a database which already has tables from a
+/// previous validation.
+async fn drop_tables(
+    artifact: &Artifact,
+    conn: &mut DatabaseConnection,
+    dialect: Dialect,
+) -> anyhow::Result<()> {
+    for create_table in &artifact.create_tables {
+        let drop_table = format!(
+            "DROP TABLE IF EXISTS {}",
+            create_table.table.display(dialect)
+        );
+        conn.query_drop(drop_table.as_str())
+            .await
+            .with_context(|| format!("Running {drop_table}"))?;
+    }
+    Ok(())
+}
+
+/// Validate the given artifact, by loading it into both the reference database and ReadySet,
+/// caching its query in ReadySet, and comparing the results of running its query against both.
+///
+/// Any existing tables with the same names as the tables in the artifact are dropped from both
+/// databases first. ReadySet must not be replicating from the reference database, as otherwise
+/// the data in the artifact would be inserted twice.
+pub async fn validate_artifact(
+    artifact: Artifact,
+    reference: &mut DatabaseConnection,
+    readyset: &mut DatabaseConnection,
+    dialect: Dialect,
+) -> anyhow::Result<Option<ValidationFailure>> {
+    install_artifact(&artifact, reference, readyset, dialect).await?;
+    compare_results(&artifact, reference, readyset, dialect).await
+}
+
+/// Load the given artifact into both the reference database and ReadySet, and cache its query in
+/// ReadySet, dropping any existing tables with the same names as the tables in the artifact from
+/// both databases first.
+///
+/// This is the first half of [`validate_artifact`], followed by [`compare_results`].
+pub async fn install_artifact(
+    artifact: &Artifact,
+    reference: &mut DatabaseConnection,
+    readyset: &mut DatabaseConnection,
+    dialect: Dialect,
+) -> anyhow::Result<()> {
+    drop_tables(artifact, reference, dialect).await?;
+    drop_tables(artifact, readyset, dialect).await?;
+
+    artifact
+        .load(reference, dialect)
+        .await
+        .context("Loading data into reference database")?;
+    artifact
+        .load(readyset, dialect)
+        .await
+        .context("Loading data into ReadySet")?;
+
+    let create_cache = format!("CREATE CACHE FROM {}", artifact.query.display(dialect));
+    readyset
+        .query_drop(create_cache.as_str())
+        .await
+        .with_context(|| format!("Running {create_cache}"))?;
+
+    Ok(())
+}
+
+/// Compare the results of running the query in the given artifact, which must already have been
+/// installed with [`install_artifact`], against the reference database and ReadySet.
+///
+/// Returns `None` if the results are the same for every key. Returns an error if the artifact
+/// [expects empty results](Artifact::expects_empty_results) but the reference database returned
+/// rows, since the query was generated incorrectly.
+pub async fn compare_results(
+    artifact: &Artifact,
+    reference: &mut DatabaseConnection,
+    readyset: &mut DatabaseConnection,
+    dialect: Dialect,
+) -> anyhow::Result<Option<ValidationFailure>> {
+    let reference_results = artifact
+        .run_query(reference, dialect)
+        .await
+        .context("Running query against reference database")?;
+    let readyset_results = artifact
+        .run_query(readyset, dialect)
+        .await
+        .context("Running query against ReadySet")?;
+
+    if artifact.expects_empty_results {
+        if let Some(rows) = reference_results.iter().find(|rows| !rows.is_empty()) {
+            bail!(
+                "Query expected to return no rows returned {} rows from the reference database",
+                rows.len()
+            );
+        }
+    }
+
+    let keys = if artifact.keys.is_empty() {
+        vec![vec![]]
+    } else {
+        artifact.keys.clone()
+    };
+    let diffs = keys
+        .into_iter()
+        .zip(reference_results.into_iter().zip(readyset_results))
+        .filter_map(|(key, (reference, readyset))| diff_rows(key, reference, readyset))
+        .collect::<Vec<_>>();
+
+    Ok(if diffs.is_empty() {
+        None
+    } else {
+        Some(ValidationFailure {
+            artifact: artifact.clone(),
+            diffs,
+        })
+    })
+}
+
+impl<'gen> Query<'gen> {
+    /// Validate that this query returns the same results in ReadySet as it does in the given
+    /// reference database, generating `rows_per_table` rows of data for each table in the query
+    /// and looking up the query's key.
+    ///
+    /// Returns `None` if the results are the same. See [`validate_artifact`] for more
+    /// information.
+    pub async fn validate_against_database(
+        &mut self,
+        rows_per_table: usize,
+        reference: &mut DatabaseConnection,
+        readyset: &mut DatabaseConnection,
+        dialect: Dialect,
+    ) -> anyhow::Result<Option<ValidationFailure>> {
+        let artifact = self.to_artifact(rows_per_table);
+        validate_artifact(artifact, reference, readyset, dialect).await
+    }
+
+    /// Generate `rows_per_table` rows of data for each table in this query, along with a key to
+    /// look up the query with if it has any parameters, and return an [`Artifact`] containing
+    /// the query along with that data and key
+    pub fn to_artifact(&mut self, rows_per_table: usize) -> Artifact {
+        let key = self.state.key();
+        let keys = if key.is_empty() { vec![] } else { vec![key] };
+        let data = self.state.generate_data(rows_per_table, false, false);
+        Artifact::new(self, &data, &keys)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diff_rows_ignores_order() {
+        assert_eq!(
+            diff_rows(
+                vec![],
+                vec![vec![1.into()], vec![2.into()]],
+                vec![vec![2.into()], vec![1.into()]]
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn diff_rows_reports_missing_and_extra() {
+        let diff = diff_rows(
+            vec![1.into()],
+            vec![vec![1.into()], vec![1.into()], vec![2.into()]],
+            vec![vec![1.into()], vec![3.into()]],
+        )
+        .unwrap();
+        assert_eq!(diff.key, vec![DfValue::from(1)]);
+        assert_eq!(diff.missing, vec![vec![DfValue::from(1)], vec![2.into()]]);
+        assert_eq!(diff.extra, vec![vec![DfValue::from(3)]]);
+    }
+}
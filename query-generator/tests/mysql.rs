@@ -1,9 +1,17 @@
+use std::collections::HashMap;
 use std::env;
 
+use itertools::Itertools;
 use mysql_async::prelude::Queryable;
-use mysql_async::{OptsBuilder, Params};
-use nom_sql::{CreateTableStatement, Dialect, DialectDisplay};
-use query_generator::{GeneratorState, QuerySeed};
+use mysql_async::{OptsBuilder, Params, Row, Value};
+use nom_sql::{
+    BinaryOperator, Column, CreateTableStatement, Dialect, DialectDisplay, Expr, JoinConstraint,
+    JoinRightSide,
+};
+use query_generator::{
+    ColumnName, GeneratorState, Operations, QueryOperation, QuerySeed, RankFunction, TableName,
+};
+use readyset_data::DfValue;
 use serial_test::serial;
 use test_strategy::proptest;
 
@@ -42,6 +50,39 @@ async fn mysql_connection() -> mysql_async::Conn {
     conn
 }
 
+/// Create all the tables in `gen`, and insert the rows in `data` into them
+async fn load_generated_data(
+    conn: &mut mysql_async::Conn,
+    gen: &GeneratorState,
+    data: HashMap<TableName, Vec<HashMap<ColumnName, DfValue>>>,
+) {
+    for table in gen.tables().values() {
+        let create_table = CreateTableStatement::from(table.clone())
+            .display(Dialect::MySQL)
+            .to_string();
+        conn.query_drop(create_table).await.unwrap();
+    }
+
+    for (table_name, rows) in data {
+        for row in rows {
+            let (columns, values): (Vec<_>, Vec<Value>) = row
+                .into_iter()
+                .map(|(col, val)| (format!("`{col}`"), val.try_into().unwrap()))
+                .unzip();
+            conn.exec_drop(
+                format!(
+                    "INSERT INTO `{table_name}` ({}) VALUES ({})",
+                    columns.join(", "),
+                    vec!["?"; values.len()].join(", ")
+                ),
+                Params::Positional(values),
+            )
+            .await
+            .unwrap();
+        }
+    }
+}
+
 #[proptest]
 #[serial]
 #[ignore = "Currently failing"]
@@ -88,3 +129,355 @@ fn queries_work_in_mysql(seed: QuerySeed) {
         };
     });
 }
+
+#[tokio::test]
+#[serial]
+#[ignore = "Requires a running MySQL instance"]
+async fn rollup_query_returns_subtotals() {
+    let mut conn = mysql_connection().await;
+    let mut gen = GeneratorState::default();
+    let Operations(operations) = "rollup".parse().unwrap();
+    let mut query = gen.generate_query(QuerySeed::new(operations, vec![]));
+    let statement = query.statement.display(Dialect::MySQL).to_string();
+    let data = query.state.generate_data(10, false, false);
+
+    load_generated_data(&mut conn, &gen, data).await;
+
+    eprintln!("Query: {statement}");
+    let rows: Vec<Row> = conn.query(statement).await.unwrap();
+
+    // The grand total row has NULL in every grouped column
+    assert!(
+        rows.iter()
+            .any(|row| matches!(row.as_ref(0), Some(Value::NULL))),
+        "Expected a subtotal row in {rows:?}"
+    );
+}
+
+#[tokio::test]
+#[serial]
+#[ignore = "Requires a running MySQL instance"]
+async fn project_literal_queries_work_in_mysql() {
+    let mut conn = mysql_connection().await;
+    let Operations(operations) = "project_literal".parse().unwrap();
+    for operation in operations {
+        let mut gen = GeneratorState::default();
+        let query = gen.generate_query(QuerySeed::new(vec![operation], vec![]));
+        let statement = query.statement.display(Dialect::MySQL).to_string();
+
+        for table in gen.tables().values() {
+            let create_table = CreateTableStatement::from(table.clone())
+                .display(Dialect::MySQL)
+                .to_string();
+            conn.query_drop(format!("DROP TABLE IF EXISTS `{}`", table.name))
+                .await
+                .unwrap();
+            conn.query_drop(create_table).await.unwrap();
+        }
+
+        eprintln!("Query: {statement}");
+        let _: Vec<Row> = conn.query(statement).await.unwrap();
+    }
+}
+
+#[tokio::test]
+#[serial]
+#[ignore = "Requires a running MySQL instance"]
+async fn using_join_matches_on_join() {
+    let mut conn = mysql_connection().await;
+    let mut gen = GeneratorState::default();
+    let Operations(operations) = "inner_join_using".parse().unwrap();
+    let mut query = gen.generate_query(QuerySeed::new(operations, vec![]));
+    let data = query.state.generate_data(10, false, false);
+
+    // Build the equivalent query joining with `ON` on the shared column
+    let using_statement = query.statement.clone();
+    let mut on_statement = using_statement.clone();
+    for join in &mut on_statement.join {
+        let (JoinConstraint::Using(cols), JoinRightSide::Table(right)) =
+            (&join.constraint, &join.right)
+        else {
+            panic!("Expected a USING join, got {:?}", join.constraint);
+        };
+        let left = on_statement
+            .tables
+            .first()
+            .unwrap()
+            .inner
+            .as_table()
+            .unwrap();
+        let right = right.inner.as_table().unwrap();
+        let col = cols.first().unwrap();
+        join.constraint = JoinConstraint::On(Expr::BinaryOp {
+            lhs: Box::new(Expr::Column(Column {
+                table: Some(left.clone()),
+                ..col.clone()
+            })),
+            op: BinaryOperator::Equal,
+            rhs: Box::new(Expr::Column(Column {
+                table: Some(right.clone()),
+                ..col.clone()
+            })),
+        });
+    }
+
+    load_generated_data(&mut conn, &gen, data).await;
+
+    let using_query = using_statement.display(Dialect::MySQL).to_string();
+    let on_query = on_statement.display(Dialect::MySQL).to_string();
+    eprintln!("USING query: {using_query}\nON query: {on_query}");
+
+    let mut using_rows: Vec<Vec<Value>> = conn
+        .query::<Row, _>(using_query)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(Row::unwrap)
+        .collect();
+    let mut on_rows: Vec<Vec<Value>> = conn
+        .query::<Row, _>(on_query)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(Row::unwrap)
+        .collect();
+    using_rows.sort_by_key(|r| format!("{r:?}"));
+    on_rows.sort_by_key(|r| format!("{r:?}"));
+
+    assert!(!using_rows.is_empty());
+    assert_eq!(using_rows, on_rows);
+}
+
+/// Compute the expected result of the given ranking window function for each row in `rows`, given
+/// as `(partition, order)` pairs
+fn expected_ranks(func: RankFunction, rows: &[(Option<i64>, Option<i64>)]) -> Vec<i64> {
+    rows.iter()
+        .map(|(partition, order)| {
+            let peers = rows.iter().filter(|(p, _)| p == partition);
+            match func {
+                RankFunction::Rank => 1 + peers.filter(|(_, o)| o < order).count() as i64,
+                RankFunction::DenseRank => {
+                    1 + peers
+                        .filter(|(_, o)| o < order)
+                        .map(|(_, o)| o)
+                        .unique()
+                        .count() as i64
+                }
+                RankFunction::RowNumber | RankFunction::Ntile(_) => {
+                    unreachable!("{func:?} depends on the order of ties")
+                }
+            }
+        })
+        .collect()
+}
+
+#[tokio::test]
+#[serial]
+#[ignore = "Requires a running MySQL instance"]
+async fn window_rank_functions_match_expected_ranks() {
+    let Operations(operations) = "window_rank".parse().unwrap();
+    for operation in operations {
+        let QueryOperation::WindowRankFunction { func, .. } = operation else {
+            panic!("Expected a window rank function, got {operation:?}");
+        };
+
+        let mut conn = mysql_connection().await;
+        let mut gen = GeneratorState::default();
+        let mut query = gen.generate_query(QuerySeed::new(vec![operation], vec![]));
+        let statement = query.statement.display(Dialect::MySQL).to_string();
+        let data = query.state.generate_data(20, false, false);
+
+        load_generated_data(&mut conn, &gen, data).await;
+
+        eprintln!("Query: {statement}");
+        let results: Vec<(Option<i64>, Option<i64>, i64)> = conn.query(statement).await.unwrap();
+        assert!(!results.is_empty());
+        let keys = results.iter().map(|(p, o, _)| (*p, *o)).collect::<Vec<_>>();
+        let ranks = results.iter().map(|(_, _, r)| *r).collect::<Vec<_>>();
+
+        match func {
+            RankFunction::Rank | RankFunction::DenseRank => {
+                assert_eq!(ranks, expected_ranks(func, &keys));
+            }
+            RankFunction::RowNumber | RankFunction::Ntile(_) => {
+                // Ties may be broken either way, so check each partition's results in order
+                for partition in keys.iter().map(|(p, _)| p).unique() {
+                    let mut rows = results
+                        .iter()
+                        .filter(|(p, _, _)| p == partition)
+                        .map(|(_, o, r)| (*o, *r))
+                        .collect::<Vec<_>>();
+                    rows.sort();
+                    let n = rows.len() as i64;
+                    let expected = (0..n).map(|i| match func {
+                        RankFunction::Ntile(buckets) => {
+                            let buckets = i64::from(buckets).min(n);
+                            // The first `n % buckets` buckets get one extra row
+                            let (size, extra) = (n / buckets, n % buckets);
+                            if i < extra * (size + 1) {
+                                i / (size + 1) + 1
+                            } else {
+                                extra + (i - extra * (size + 1)) / size + 1
+                            }
+                        }
+                        _ => i + 1,
+                    });
+                    assert!(
+                        rows.iter().map(|(_, r)| *r).eq(expected),
+                        "Unexpected {func:?} results for partition {partition:?}: {rows:?}"
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[tokio::test]
+#[serial]
+#[ignore = "Requires a running MySQL instance"]
+async fn json_path_filters_return_matching_rows() {
+    let Operations(operations) = "json_path".parse().unwrap();
+    for operation in operations {
+        let QueryOperation::JsonPath { operator } = operation else {
+            panic!("Expected a JSON path filter, got {operation:?}");
+        };
+
+        let mut conn = mysql_connection().await;
+        let mut gen = GeneratorState::default();
+        let mut query = gen.generate_query(QuerySeed::new(vec![operation], vec![]));
+        let statement = query.statement.display(Dialect::MySQL).to_string();
+        let data = query.state.generate_data(20, false, false);
+
+        let mut expected = vec![];
+        for row in data.values().flatten() {
+            if let Some(json) = row.values().find_map(|val| {
+                serde_json::from_str::<serde_json::Value>(&val.to_string())
+                    .ok()
+                    .filter(serde_json::Value::is_object)
+            }) {
+                // Rows without the path extract NULL, which never matches
+                let Some(value) = json.get("value").and_then(serde_json::Value::as_i64) else {
+                    continue;
+                };
+                if match operator {
+                    BinaryOperator::Equal => value == 42,
+                    BinaryOperator::NotEqual => value != 42,
+                    BinaryOperator::Greater => value > 42,
+                    BinaryOperator::GreaterOrEqual => value >= 42,
+                    BinaryOperator::Less => value < 42,
+                    BinaryOperator::LessOrEqual => value <= 42,
+                    _ => unreachable!("Unexpected operator {operator}"),
+                } {
+                    expected.push(json.to_string());
+                }
+            }
+        }
+        load_generated_data(&mut conn, &gen, data).await;
+
+        eprintln!("Query: {statement}");
+        let mut results = conn
+            .query::<String, _>(statement)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|json| {
+                serde_json::from_str::<serde_json::Value>(&json)
+                    .unwrap()
+                    .to_string()
+            })
+            .collect::<Vec<_>>();
+        results.sort();
+        expected.sort();
+
+        assert!(!results.is_empty());
+        assert_eq!(results, expected);
+    }
+}
+
+#[tokio::test]
+#[serial]
+#[ignore = "Requires a running MySQL instance"]
+async fn param_plus_literal_results_match_expectation() {
+    let Operations(satisfiable) = "param_plus_literal".parse().unwrap();
+    let Operations(contradictory) = "param_contradiction".parse().unwrap();
+    for operation in satisfiable.into_iter().chain(contradictory) {
+        let mut conn = mysql_connection().await;
+        let mut gen = GeneratorState::default();
+        let mut query = gen.generate_query(QuerySeed::new(vec![operation.clone()], vec![]));
+        let statement = query.statement.display(Dialect::MySQL).to_string();
+        let data = query.state.generate_data(20, false, false);
+
+        load_generated_data(&mut conn, &gen, data).await;
+
+        eprintln!("Query: {statement}");
+        let key = query
+            .state
+            .key()
+            .into_iter()
+            .map(|val| val.try_into().unwrap())
+            .collect::<Vec<Value>>();
+        let results = conn
+            .exec::<Row, _, _>(statement, Params::Positional(key))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            results.is_empty(),
+            query.state.expects_empty_results(),
+            "Unexpected results for {operation:?}: {results:?}"
+        );
+    }
+}
+
+#[tokio::test]
+#[serial]
+#[ignore = "Requires a running MySQL instance"]
+async fn upsert_updates_duplicate_keys() {
+    let mut conn = mysql_connection().await;
+    let mut gen = GeneratorState::default();
+    let table_name = gen.fresh_table_mut().name.clone();
+    let upsert = gen.generate_upsert(&table_name);
+    let table = gen.table(&table_name).unwrap();
+    // The primary key, followed by the column updated on conflict
+    assert_eq!(table.column_order.len(), 2);
+
+    let create_table = CreateTableStatement::from(table.clone())
+        .display(Dialect::MySQL)
+        .to_string();
+    conn.query_drop(create_table).await.unwrap();
+
+    eprintln!("Upsert: {upsert}");
+    for value in [1, 2, 3] {
+        conn.exec_drop(upsert.as_str(), (1, value)).await.unwrap();
+    }
+    conn.exec_drop(upsert.as_str(), (2, 4)).await.unwrap();
+
+    let rows: Vec<(i32, i32)> = conn
+        .query(format!("SELECT * FROM `{table_name}` ORDER BY 1"))
+        .await
+        .unwrap();
+    assert_eq!(rows, vec![(1, 3), (2, 4)]);
+}
+
+#[tokio::test]
+#[serial]
+#[ignore = "Requires a running MySQL instance"]
+async fn bit_or_matches_bitwise_or_of_values() {
+    let mut conn = mysql_connection().await;
+    let mut gen = GeneratorState::default();
+    let Operations(operations) = "bit_or".parse().unwrap();
+    let mut query = gen.generate_query(QuerySeed::new(operations, vec![]));
+    let statement = query.statement.display(Dialect::MySQL).to_string();
+    let data = query.state.generate_data(20, false, false);
+
+    let expected = data
+        .values()
+        .flatten()
+        .flat_map(|row| row.values())
+        .fold(0, |acc, val| acc | i64::try_from(val).unwrap());
+    load_generated_data(&mut conn, &gen, data).await;
+
+    eprintln!("Query: {statement}");
+    let result: Option<u64> = conn.query_first(statement).await.unwrap();
+    assert_eq!(result, Some(expected as u64));
+}
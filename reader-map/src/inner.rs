@@ -110,6 +110,20 @@ impl<K, V, S> Data<K, V, S> {
         }
     }
 
+    pub(crate) fn iter_after<Q>(
+        &self,
+        after: Option<&Q>,
+    ) -> Option<partial_map::Range<'_, K, Values<V>>>
+    where
+        K: Borrow<Q> + Ord,
+        Q: Ord + ?Sized,
+    {
+        match self {
+            Self::BTreeMap(map) => Some(map.iter_after(after)),
+            Self::HashMap(..) => None,
+        }
+    }
+
     pub(crate) fn clear(&mut self) {
         match self {
             Self::BTreeMap(map) => {
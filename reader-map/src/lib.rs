@@ -261,7 +261,7 @@ pub mod refs {
     pub use left_right::ReadGuard;
 
     pub use super::values::{Values, ValuesIter};
-    pub use crate::read::{MapReadRef, Miss, ReadGuardIter};
+    pub use crate::read::{KeysAfterIter, MapReadRef, Miss, ReadGuardIter};
 }
 
 /// Options for how to initialize the map.
@@ -12,7 +12,7 @@ use crate::values::Values;
 use crate::{Error, Result};
 
 mod read_ref;
-pub use read_ref::{MapReadRef, ReadGuardIter};
+pub use read_ref::{KeysAfterIter, MapReadRef, ReadGuardIter};
 
 pub use crate::inner::Miss;
 
@@ -95,6 +95,27 @@ where
         }
     }
 
+    /// Iterate, in ascending order, over all keys in the map which are greater than `after` (or
+    /// over all keys in the map, if `after` is `None`), or return `None` if the underlying map
+    /// is not a [`BTreeMap`](readyset_client::internal::IndexType::BTreeMap) and so isn't
+    /// ordered.
+    ///
+    /// Unlike [`range`](Self::range), this never misses - only the keys which are actually present
+    /// in the map are returned.
+    ///
+    /// Be careful with this function! While the iteration is ongoing, any writer that tries to
+    /// publish changes will block waiting on this reader to finish.
+    pub fn keys_after<Q>(&self, after: Option<&Q>) -> Option<KeysAfterIter<'_, K, V>>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.guard
+            .data
+            .iter_after(after)
+            .map(|iter| KeysAfterIter { iter })
+    }
+
     /// Iterate over all value sets in the map.
     ///
     /// Be careful with this function! While the iteration is ongoing, any writer that tries to
@@ -278,6 +299,27 @@ where
     }
 }
 
+/// An [`Iterator`] over the keys in a map which are greater than some key, in ascending order, as
+/// returned by [`MapReadRef::keys_after`].
+pub struct KeysAfterIter<'rg, K, V>
+where
+    K: Ord + Clone,
+    V: Eq + Hash,
+{
+    iter: btree_map::Range<'rg, K, Values<V>>,
+}
+
+impl<'rg, K, V> Iterator for KeysAfterIter<'rg, K, V>
+where
+    K: Ord + Clone,
+    V: Eq + Hash,
+{
+    type Item = &'rg K;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|(k, _)| k)
+    }
+}
+
 /// An [`Iterator`] over a range of keys.
 pub struct RangeIter<'rg, K, V>
 where
@@ -2062,6 +2062,7 @@ where
                     table: None,
                 },
                 column_type: DfType::UnsignedInt,
+                nullable: true,
                 base: None,
             };
             select_schema.schema.to_mut().push(count_schema);
@@ -3138,6 +3139,7 @@ where
                     table: None,
                 },
                 column_type: DfType::DEFAULT_TEXT,
+                nullable: true,
                 base: None,
             }]),
             columns: Cow::Owned(vec!["remote_addr".into()]),
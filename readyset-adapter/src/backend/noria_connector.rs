@@ -444,6 +444,7 @@ impl NoriaConnector {
                         table: None,
                     },
                     column_type: DfType::DEFAULT_TEXT,
+                    nullable: true,
                     base: None,
                 },
                 ColumnSchema {
@@ -452,6 +453,7 @@ impl NoriaConnector {
                         table: None,
                     },
                     column_type: DfType::DEFAULT_TEXT,
+                    nullable: true,
                     base: None,
                 },
             ]),
@@ -509,6 +511,7 @@ impl NoriaConnector {
                         table: None,
                     },
                     column_type: column_type.clone(),
+                    nullable: true,
                     base: None,
                 })
                 .collect(),
@@ -893,6 +896,7 @@ impl NoriaConnector {
                             table: None,
                         },
                         column_type: DfType::DEFAULT_TEXT,
+                        nullable: true,
                         base: None,
                     })
                     .collect(),
@@ -1410,6 +1414,7 @@ impl NoriaConnector {
             .map(|column| ColumnSchema {
                 column,
                 column_type: DfType::UnsignedBigInt,
+                nullable: true,
                 base: None,
             })
             .collect();
@@ -97,6 +97,20 @@ pub struct WorkerSchedulingConfig {
     /// Configuration for how domains containing or not containing reader nodes may be scheduled
     /// onto this worker
     pub reader_nodes: NodeTypeSchedulingRestriction,
+    /// The maximum number of domain shard replicas that may be scheduled onto this worker, or
+    /// `None` for no limit
+    ///
+    /// There's deliberately no equivalent limit on memory: a domain's memory usage isn't known
+    /// when it's scheduled, since its partial state only fills in as it serves upqueries. Memory
+    /// usage on a worker is instead bounded by evicting state once it exceeds the worker's memory
+    /// limit.
+    #[serde(default)]
+    pub max_domains: Option<usize>,
+    /// The NUMA node this worker runs on, if known. Used to co-locate domains which communicate
+    /// with each other on workers on the same NUMA node, if the controller is configured to use
+    /// the NUMA-aware placement strategy
+    #[serde(default)]
+    pub numa_node: Option<usize>,
 }
 
 /// Initial registration request body, sent from workers to controllers.
@@ -25,14 +25,16 @@ use tracing::{debug, trace};
 use url::Url;
 
 use crate::consensus::{Authority, AuthorityControl};
-use crate::debug::info::{GraphInfo, MaterializationInfo, NodeSize};
+use crate::debug::info::{BaseColumnEvent, GraphInfo, MaterializationInfo, NodeSize};
 use crate::debug::stats;
+use crate::health::{ComponentHealth, HealthReport};
 use crate::internal::{DomainIndex, ReplicaAddress};
 use crate::metrics::MetricsDump;
 use crate::recipe::changelist::ChangeList;
 use crate::recipe::{CacheExpr, ExtendRecipeResult, ExtendRecipeSpec, MigrationStatus};
 use crate::status::ReadySetControllerStatus;
 use crate::table::{PersistencePoint, Table, TableBuilder, TableRpc};
+use crate::tools::{write_view_export, ExportDest, ExportSummary};
 use crate::view::{View, ViewBuilder, ViewRpc};
 use crate::{
     ReplicationOffset, SingleKeyEviction, TableStatus, ViewCreateRequest, ViewFilter, ViewRequest,
@@ -42,6 +44,8 @@ mod rpc;
 
 const EXTEND_RECIPE_POLL_INTERVAL: Duration = Duration::from_secs(1);
 const WAIT_FOR_ALL_TABLES_TO_COMPACT_POLL_INTERVAL: Duration = Duration::from_secs(5);
+/// Extra time, beyond the requested timeout, to allow for a `wait_for_stabilization` RPC to return
+const WAIT_FOR_STABILIZATION_RPC_MARGIN: Duration = Duration::from_secs(5);
 
 /// Describes a running controller instance.
 ///
@@ -521,6 +525,28 @@ impl ReadySetHandle {
         self.request_view(request)
     }
 
+    /// Export the current materialized state of the given external view to `dest`, for offline
+    /// analysis with [`read_view_export`](crate::tools::read_view_export).
+    ///
+    /// If the view is sharded, each shard is exported separately - see [`ExportDest`] for how the
+    /// shards are written.
+    ///
+    /// `Self::poll_ready` must have returned `Async::Ready` before you call this method.
+    pub async fn export_view_state<I: Into<Relation>>(
+        &mut self,
+        name: I,
+        dest: ExportDest,
+    ) -> ReadySetResult<ExportSummary> {
+        let name = name.into();
+        let mut reader = self
+            .view(name.clone())
+            .await?
+            .into_reader_handle()
+            .ok_or_else(|| internal_err!("Cannot export the state of a reused view"))?;
+        let shards = reader.export_state().await?;
+        write_view_export(&name.display_unquoted().to_string(), shards, dest).await
+    }
+
     /// Obtain a `View` from the given pool of workers, that allows you to query the given external
     /// view.
     ///
@@ -663,6 +689,46 @@ impl ReadySetHandle {
         }
     }
 
+    simple_request!(
+        /// Get the current version of the schema, which starts at 0 and is advanced once by each
+        /// migration that's committed.
+        ///
+        /// `Self::poll_ready` must have returned `Async::Ready` before you call this method.
+        schema_version() -> usize
+    );
+
+    simple_request!(
+        /// Get every column added to or dropped from the base table with the given name since it
+        /// was created, in order, for debugging changes to the table's columns.
+        ///
+        /// `Self::poll_ready` must have returned `Async::Ready` before you call this method.
+        base_column_history(name: &Relation) -> Vec<BaseColumnEvent>
+    );
+
+    simple_request!(
+        /// Get the index of the base table node with the given name, if it exists. The index can
+        /// be passed to [`Self::table_by_index`].
+        ///
+        /// `Self::poll_ready` must have returned `Async::Ready` before you call this method.
+        get_base_node_by_name(name: &Relation) -> Option<NodeIndex>
+    );
+
+    simple_request!(
+        /// Get the index of the reader node with the given name, if it exists.
+        ///
+        /// `Self::poll_ready` must have returned `Async::Ready` before you call this method.
+        get_view_by_name(name: &Relation) -> Option<NodeIndex>
+    );
+
+    simple_request!(
+        /// Analyze the lookups performed against the view maintained on the given node,
+        /// recommending how (if at all) the key of the view should be changed. This never changes
+        /// the view itself.
+        ///
+        /// `Self::poll_ready` must have returned `Async::Ready` before you call this method.
+        analyze_view(leaf: NodeIndex) -> stats::ViewAnalysis
+    );
+
     simple_request!(
         /// Get statistics about the time spent processing different parts of the graph.
         ///
@@ -670,6 +736,39 @@ impl ReadySetHandle {
         statistics() -> stats::GraphStats
     );
 
+    simple_request!(
+        /// Get the most recent packets which took longer than the configured slow node threshold
+        /// to process at a single node, from every running domain replica, with node names
+        /// resolved from the dataflow graph.
+        ///
+        /// Each domain replica retains a bounded number of these, oldest first.
+        recent_slow_operations() -> Vec<stats::SlowOperation>
+    );
+
+    simple_request!(
+        /// Tag the given fraction (between 0 and 1) of the writes to every base table with a trace,
+        /// so that the time taken for them to propagate to each reader is recorded and reported as
+        /// the [`propagation_latency`](stats::NodeStats::propagation_latency) of the reader in
+        /// [`Self::statistics`]. A sample rate of 0 disables tracing, which is the default.
+        ///
+        /// Applies to both existing domains and domains created by later migrations.
+        set_write_tracing(sample_rate: f64) -> ()
+    );
+
+    /// For each base table which has been written to, report how many records have been emitted
+    /// by each node downstream of it as the result of those writes. See
+    /// [`stats::GraphStats::write_amplification`].
+    ///
+    /// Records are only counted by servers started with `--count-emitted-records`, so this is
+    /// empty otherwise.
+    ///
+    /// `Self::poll_ready` must have returned `Async::Ready` before you call this method.
+    pub async fn write_amplification(
+        &mut self,
+    ) -> ReadySetResult<HashMap<NodeIndex, stats::AmplificationReport>> {
+        Ok(self.statistics().await?.write_amplification())
+    }
+
     simple_request!(
         /// Flush all partial state, evicting all rows present.
         ///
@@ -910,6 +1009,101 @@ impl ReadySetHandle {
         Ok(())
     }
 
+    /// Wait for all writes that have been sent into the dataflow graph so far to finish
+    /// propagating through every domain in the graph.
+    ///
+    /// This works by passing a barrier from each domain to its children in topological order, and
+    /// returns once every domain has processed all the updates sent to it before the barrier. It
+    /// doesn't wait for replays, so upqueries which were triggered by the writes (or anything else)
+    /// may still be in progress when this returns. Each call takes at least one round trip to each
+    /// domain in the graph.
+    ///
+    /// Returns [`ReadySetError::StabilizationTimeout`] if the graph does not stabilize within
+    /// `timeout`.
+    pub async fn wait_for_stabilization(&mut self, timeout: Duration) -> ReadySetResult<()> {
+        self.rpc(
+            "wait_for_stabilization",
+            timeout,
+            Some(timeout + WAIT_FOR_STABILIZATION_RPC_MARGIN),
+        )
+        .await
+    }
+
+    /// Check the health of the ReadySet deployment, distinguishing failures of the control plane
+    /// from failures of the data plane. See [`HealthReport`] for more information.
+    ///
+    /// This pings every domain replica and every worker known to the controller, and performs a
+    /// synthetic non-blocking read with no keys against one of the views in the graph (if there
+    /// are any) to check the read path. The returned future completes within `deadline` even if
+    /// some components hang, in which case those components are reported as timed out.
+    pub async fn health(&mut self, deadline: Duration) -> HealthReport {
+        let start = Instant::now();
+        // Give the controller half the deadline to check domains and workers, leaving the rest for
+        // its response and for checking the read path
+        let report: HealthReport = match tokio::time::timeout(
+            deadline,
+            self.rpc("health_report", deadline / 2, Some(deadline)),
+        )
+        .await
+        {
+            Ok(Ok(report)) => report,
+            Ok(Err(error)) => {
+                debug!(%error, "Controller failed to check health");
+                return HealthReport::controller_unreachable();
+            }
+            Err(_) => return HealthReport::controller_unreachable(),
+        };
+
+        let read_path = tokio::time::timeout(
+            deadline.saturating_sub(start.elapsed()),
+            self.check_read_path(),
+        )
+        .await
+        .unwrap_or(Some(ComponentHealth::TimedOut));
+
+        HealthReport::new(report.domains, report.workers, read_path)
+    }
+
+    /// Perform a non-blocking read with no keys against some view in the graph, or return `None`
+    /// if there are no views
+    async fn check_read_path(&mut self) -> Option<ComponentHealth> {
+        let res: ReadySetResult<bool> = async {
+            let Some(name) = self.views().await?.into_keys().next() else {
+                return Ok(false);
+            };
+            let Some(mut reader) = self.view(name).await?.into_reader_handle() else {
+                return Ok(false);
+            };
+            reader.multi_lookup(vec![], false).await?;
+            Ok(true)
+        }
+        .await;
+
+        match res {
+            Ok(true) => Some(ComponentHealth::Healthy),
+            Ok(false) => None,
+            Err(error) => Some(ComponentHealth::Failed(error.to_string())),
+        }
+    }
+
+    simple_request!(
+        /// Return the indices of all the nodes in the dataflow graph that the given node
+        /// (transitively) depends on, not including dropped nodes.
+        ///
+        /// Returns [`ReadySetError::NodeNotFound`] if the node does not exist.
+        node_ancestors(node: NodeIndex) -> Vec<NodeIndex>
+    );
+
+    simple_request!(
+        /// Return the indices of all the nodes in the dataflow graph that (transitively) depend on
+        /// the given node, not including dropped nodes.
+        ///
+        /// This can be used to compute eg the set of queries which depend on a base table.
+        ///
+        /// Returns [`ReadySetError::NodeNotFound`] if the node does not exist.
+        node_descendants(node: NodeIndex) -> Vec<NodeIndex>
+    );
+
     simple_request!(
         /// Return a map of node indices to key counts.
         node_sizes() -> HashMap<NodeIndex, NodeSize>
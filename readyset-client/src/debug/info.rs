@@ -4,6 +4,7 @@ use std::ops::{AddAssign, Deref};
 
 use nom_sql::Relation;
 use petgraph::graph::NodeIndex;
+use readyset_data::DfValue;
 use serde::{Deserialize, Serialize};
 use url::Url;
 
@@ -27,6 +28,20 @@ impl Deref for GraphInfo {
     }
 }
 
+/// A change made by a migration to the columns of a base table, as returned by
+/// [`ReadySetHandle::base_column_history`](crate::ReadySetHandle::base_column_history).
+///
+/// Columns of a base table are identified by their index, which is assigned when the column is
+/// added and never reused - dropped columns keep their index (and are filled in with their default
+/// value by writes), and added columns are always given a fresh index at the end of the table.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BaseColumnEvent {
+    /// A column was added at the given index, with the given default value
+    Added { column: usize, default: DfValue },
+    /// The column at the given index was dropped
+    Dropped { column: usize },
+}
+
 /// Used to wrap key counts since we use row count estimates as a rough correlate of the key count
 /// in the case of RocksDB nodes, and we want to keep track of when we do that so as to avoid any
 /// confusion in other parts of the code.
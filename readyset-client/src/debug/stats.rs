@@ -1,5 +1,7 @@
 use std::collections::HashMap;
+use std::time::Duration;
 
+use nom_sql::Relation;
 use petgraph::graph::NodeIndex;
 use serde::{Deserialize, Serialize};
 
@@ -23,6 +25,11 @@ pub struct DomainStats {
     pub total_forward_time: u64,
     /// Total wall-clock time spent waiting for work in this domain.
     pub wait_time: u64,
+    /// Total number of low-priority full replay batches deferred behind foreground work in this
+    /// domain.
+    pub deferred_replay_batches: u64,
+    /// Total number of deferred low-priority full replay batches processed by this domain.
+    pub processed_deferred_replay_batches: u64,
 }
 
 /// Statistics about a node.
@@ -42,6 +49,209 @@ pub struct NodeStats {
     pub materialized: MaterializationStatus,
     /// The value returned from Ingredient::probe.
     pub probe_result: HashMap<String, String>,
+    /// For reader nodes, the time taken for traced writes to propagate from the base tables they
+    /// were written to until they were visible in this reader, if any traced writes have reached
+    /// it. See [`ReadySetHandle::set_write_tracing`](crate::ReadySetHandle::set_write_tracing).
+    #[serde(default)]
+    pub propagation_latency: Option<PropagationLatency>,
+    /// For base tables, internal nodes, and readers, the number of records this node has emitted
+    /// as the result of writes to base tables, indexed by the base table the write was made to.
+    /// Records emitted during replays aren't counted. See [`GraphStats::write_amplification`].
+    #[serde(default)]
+    pub emitted_records: HashMap<NodeIndex, u64>,
+}
+
+/// The records derived from the writes to a single base table, as reported by
+/// [`GraphStats::write_amplification`]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AmplificationReport {
+    /// The number of records emitted by the base table itself
+    pub base_records: u64,
+    /// The number of records emitted by each node downstream of the base table as the result of
+    /// writes to it, not including the base table itself
+    pub derived_records: HashMap<NodeIndex, u64>,
+}
+
+impl AmplificationReport {
+    /// Returns the total number of records emitted downstream of the base table
+    pub fn total_derived_records(&self) -> u64 {
+        self.derived_records.values().sum()
+    }
+
+    /// Returns the average number of records emitted downstream of the base table for each record
+    /// written to it, or 0 if no records have been written to it
+    pub fn factor(&self) -> f64 {
+        if self.base_records == 0 {
+            0.0
+        } else {
+            self.total_derived_records() as f64 / self.base_records as f64
+        }
+    }
+
+    /// Returns the average number of records emitted by `node` for each record written to the
+    /// base table, or 0 if no records have been written to it
+    pub fn node_factor(&self, node: NodeIndex) -> f64 {
+        if self.base_records == 0 {
+            0.0
+        } else {
+            self.derived_records.get(&node).copied().unwrap_or(0) as f64 / self.base_records as f64
+        }
+    }
+}
+
+/// Percentiles of the time taken for traced writes to propagate from the base table they were
+/// written to until they were visible in a reader.
+///
+/// Percentiles are computed from a bounded, uniformly sampled reservoir of the latencies recorded
+/// at the reader, so they're approximate once more than about a thousand traced writes have reached
+/// it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PropagationLatency {
+    /// The total number of traced writes which have reached the reader
+    pub samples: u64,
+    /// The median propagation latency
+    pub p50: Duration,
+    /// The 90th percentile propagation latency
+    pub p90: Duration,
+    /// The 99th percentile propagation latency
+    pub p99: Duration,
+    /// The maximum propagation latency recorded
+    pub max: Duration,
+}
+
+/// A single packet which took longer than the configured slow node threshold to be processed at a
+/// node in a domain.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SlowOperation {
+    /// The replica of the domain the node is in
+    pub domain: ReplicaAddress,
+    /// The global index of the node that processed the packet
+    pub node: NodeIndex,
+    /// The name of the node, as resolved from the dataflow graph by the controller.
+    ///
+    /// `None` if the node has since been removed from the graph.
+    pub node_name: Option<Relation>,
+    /// The kind of packet that was processed, eg `Input` or `Message`
+    pub packet_kind: String,
+    /// How long it took to process the packet
+    pub duration: Duration,
+    /// The number of records or table operations in the packet
+    pub batch_size: usize,
+}
+
+/// Counts of the lookups performed against a reader since it was created.
+///
+/// Lookups are only counted once they either hit or miss, so lookups against a reader which isn't
+/// ready yet are not included.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReaderLookupStats {
+    /// The number of lookups performed against the reader
+    pub lookups: u64,
+    /// The number of lookups which missed on at least one of their keys
+    pub misses: u64,
+    /// The number of lookups whose post-lookup filter compared each column of the reader for
+    /// equality with a value, as part of the top-level conjunction of the filter, indexed by
+    /// column
+    pub filter_columns: Vec<u64>,
+}
+
+impl ReaderLookupStats {
+    /// Returns the fraction of lookups which missed on at least one of their keys, or 0 if there
+    /// haven't been any lookups
+    pub fn miss_rate(&self) -> f64 {
+        if self.lookups == 0 {
+            0.0
+        } else {
+            self.misses as f64 / self.lookups as f64
+        }
+    }
+
+    /// Add the counts in `other` to the counts in `self`, for example to combine the counts from
+    /// all the shards of a reader
+    pub fn merge(&mut self, other: &ReaderLookupStats) {
+        self.lookups += other.lookups;
+        self.misses += other.misses;
+        if self.filter_columns.len() < other.filter_columns.len() {
+            self.filter_columns.resize(other.filter_columns.len(), 0);
+        }
+        for (count, other_count) in self.filter_columns.iter_mut().zip(&other.filter_columns) {
+            *count += other_count;
+        }
+    }
+}
+
+/// The minimum fraction of lookups against a view whose post-lookup filters compare a column for
+/// equality for a [`ViewAnalysis`] to recommend adding that column to the key of the view
+pub const REKEY_VIEW_THRESHOLD: f64 = 0.9;
+
+/// The minimum fraction of lookups against a view whose post-lookup filters compare a column for
+/// equality for a [`ViewAnalysis`] to recommend adding a secondary reader keyed on that column
+/// along with the current key
+pub const SECONDARY_READER_THRESHOLD: f64 = 0.5;
+
+/// A recommended change to the key of a view, as part of a [`ViewAnalysis`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ViewRecommendation {
+    /// The view's current key is fine
+    Keep,
+    /// Almost all lookups filter on a column for equality after looking up the current key, so
+    /// the view should be keyed on the given columns (the current key followed by that column)
+    /// instead
+    Rekey { columns: Vec<usize> },
+    /// Many lookups filter on a column for equality after looking up the current key, so a second
+    /// reader keyed on the given columns (the current key followed by that column) should be added
+    /// alongside the current one
+    AddSecondaryReader { columns: Vec<usize> },
+}
+
+/// An analysis of the lookups performed against a view, along with a recommendation for how (if
+/// at all) the key of the view should be changed. Computing an analysis never changes the view.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ViewAnalysis {
+    /// The columns the reader for the view is currently keyed on
+    pub key: Vec<usize>,
+    /// The lookups performed against the view, summed across all shards and replicas of its
+    /// reader
+    pub lookups: ReaderLookupStats,
+    /// How the key of the view should be changed
+    pub recommendation: ViewRecommendation,
+}
+
+impl ViewAnalysis {
+    /// Analyze the given lookups against a view keyed on `key`
+    pub fn new(key: Vec<usize>, lookups: ReaderLookupStats) -> Self {
+        // The column not already in the key which is compared for equality by the most post-lookup
+        // filters, preferring the lowest column index in case of a tie
+        let hottest = lookups
+            .filter_columns
+            .iter()
+            .enumerate()
+            .filter(|(column, count)| **count > 0 && !key.contains(column))
+            .min_by_key(|(column, count)| (std::cmp::Reverse(**count), *column));
+
+        let recommendation = match hottest {
+            Some((column, count)) => {
+                // Lookups still need to provide the current key, so the filtered column can only
+                // be added to it, not replace it
+                let columns = key.iter().copied().chain(std::iter::once(column)).collect();
+                let fraction = *count as f64 / lookups.lookups as f64;
+                if fraction >= REKEY_VIEW_THRESHOLD {
+                    ViewRecommendation::Rekey { columns }
+                } else if fraction >= SECONDARY_READER_THRESHOLD {
+                    ViewRecommendation::AddSecondaryReader { columns }
+                } else {
+                    ViewRecommendation::Keep
+                }
+            }
+            None => ViewRecommendation::Keep,
+        };
+
+        Self {
+            key,
+            lookups,
+            recommendation,
+        }
+    }
 }
 
 /// Status that we persist in the Authority to make it available across restarts.
@@ -65,6 +275,48 @@ pub struct GraphStats {
     pub domains: DomainMap,
 }
 
+impl GraphStats {
+    /// Returns the [`PropagationLatency`] of each reader replica which has received any traced
+    /// writes, along with the domain replica it's in and the [`NodeStats::desc`] of the reader
+    pub fn propagation_latencies(
+        &self,
+    ) -> impl Iterator<Item = (ReplicaAddress, NodeIndex, &str, &PropagationLatency)> + '_ {
+        self.domains
+            .iter()
+            .filter_map(|(addr, stats)| Some((*addr, stats.as_ref()?)))
+            .flat_map(|(addr, (_, nodes))| {
+                nodes.iter().filter_map(move |(node, stats)| {
+                    Some((
+                        addr,
+                        *node,
+                        stats.desc.as_str(),
+                        stats.propagation_latency.as_ref()?,
+                    ))
+                })
+            })
+    }
+
+    /// Returns the records derived from the writes to each base table which has been written to,
+    /// summed across all shards and replicas of each node, indexed by the global index of the base
+    /// table
+    pub fn write_amplification(&self) -> HashMap<NodeIndex, AmplificationReport> {
+        let mut reports: HashMap<NodeIndex, AmplificationReport> = HashMap::new();
+        for (_, nodes) in self.domains.values().flatten() {
+            for (node, stats) in nodes {
+                for (origin, count) in &stats.emitted_records {
+                    let report = reports.entry(*origin).or_default();
+                    if node == origin {
+                        report.base_records += count;
+                    } else {
+                        *report.derived_records.entry(*node).or_default() += count;
+                    }
+                }
+            }
+        }
+        reports
+    }
+}
+
 use std::ops::Deref;
 impl Deref for GraphStats {
     type Target = DomainMap;
@@ -72,3 +324,151 @@ impl Deref for GraphStats {
         &self.domains
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node_stats(emitted_records: Vec<(usize, u64)>) -> NodeStats {
+        NodeStats {
+            desc: String::new(),
+            process_time: 0,
+            process_ptime: 0,
+            mem_size: 0,
+            materialized: MaterializationStatus::Not,
+            probe_result: HashMap::new(),
+            propagation_latency: None,
+            emitted_records: emitted_records
+                .into_iter()
+                .map(|(origin, count)| (NodeIndex::new(origin), count))
+                .collect(),
+        }
+    }
+
+    fn domain_stats() -> DomainStats {
+        DomainStats {
+            total_time: 0,
+            total_ptime: 0,
+            total_replay_time: 0,
+            total_forward_time: 0,
+            wait_time: 0,
+            deferred_replay_batches: 0,
+            processed_deferred_replay_batches: 0,
+        }
+    }
+
+    #[test]
+    fn write_amplification() {
+        // Base 1 feeds an aggregate (2) in the same domain, which feeds a reader (3) with two
+        // replicas in another domain. Base 4 feeds the aggregate too, but hasn't been written to.
+        let replica = |domain, replica| ReplicaAddress {
+            domain_index: DomainIndex::from(domain),
+            shard: 0,
+            replica,
+        };
+        let stats = GraphStats {
+            domains: HashMap::from([
+                (
+                    replica(0, 0),
+                    Some((
+                        domain_stats(),
+                        HashMap::from([
+                            (NodeIndex::new(1), node_stats(vec![(1, 10)])),
+                            (NodeIndex::new(2), node_stats(vec![(1, 19)])),
+                            (NodeIndex::new(4), node_stats(vec![])),
+                        ]),
+                    )),
+                ),
+                (
+                    replica(1, 0),
+                    Some((
+                        domain_stats(),
+                        HashMap::from([(NodeIndex::new(3), node_stats(vec![(1, 19)]))]),
+                    )),
+                ),
+                (
+                    replica(1, 1),
+                    Some((
+                        domain_stats(),
+                        HashMap::from([(NodeIndex::new(3), node_stats(vec![(1, 19)]))]),
+                    )),
+                ),
+                (replica(2, 0), None),
+            ]),
+        };
+
+        let reports = stats.write_amplification();
+        assert_eq!(reports.len(), 1);
+        let report = &reports[&NodeIndex::new(1)];
+        assert_eq!(report.base_records, 10);
+        assert_eq!(
+            report.derived_records,
+            HashMap::from([(NodeIndex::new(2), 19), (NodeIndex::new(3), 38)])
+        );
+        assert_eq!(report.total_derived_records(), 57);
+        assert_eq!(report.factor(), 5.7);
+        assert_eq!(report.node_factor(NodeIndex::new(3)), 3.8);
+        assert_eq!(report.node_factor(NodeIndex::new(4)), 0.0);
+    }
+
+    fn analyze(key: Vec<usize>, filter_columns: Vec<u64>) -> ViewRecommendation {
+        ViewAnalysis::new(
+            key,
+            ReaderLookupStats {
+                lookups: 100,
+                misses: 0,
+                filter_columns,
+            },
+        )
+        .recommendation
+    }
+
+    #[test]
+    fn view_analysis_recommendations() {
+        assert_eq!(analyze(vec![0], vec![]), ViewRecommendation::Keep);
+        assert_eq!(analyze(vec![0], vec![0, 10, 20]), ViewRecommendation::Keep);
+        assert_eq!(
+            analyze(vec![0], vec![0, 60, 95]),
+            ViewRecommendation::Rekey {
+                columns: vec![0, 2]
+            }
+        );
+        assert_eq!(
+            analyze(vec![0], vec![0, 60, 60]),
+            ViewRecommendation::AddSecondaryReader {
+                columns: vec![0, 1]
+            }
+        );
+        assert_eq!(
+            analyze(vec![1, 0], vec![0, 0, 95]),
+            ViewRecommendation::Rekey {
+                columns: vec![1, 0, 2]
+            }
+        );
+        // Filters on the key columns themselves don't count
+        assert_eq!(analyze(vec![0], vec![100, 0, 0]), ViewRecommendation::Keep);
+    }
+
+    #[test]
+    fn merge_lookup_stats() {
+        let mut stats = ReaderLookupStats {
+            lookups: 2,
+            misses: 1,
+            filter_columns: vec![1],
+        };
+        stats.merge(&ReaderLookupStats {
+            lookups: 3,
+            misses: 1,
+            filter_columns: vec![0, 2],
+        });
+        assert_eq!(
+            stats,
+            ReaderLookupStats {
+                lookups: 5,
+                misses: 2,
+                filter_columns: vec![1, 2],
+            }
+        );
+        assert_eq!(stats.miss_rate(), 0.4);
+    }
+}
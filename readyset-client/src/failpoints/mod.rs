@@ -33,5 +33,14 @@ pub const POSTGRES_NEXT_WAL_EVENT: &str = "postgres-next-wal-event";
 pub const START_INNER_POSTGRES: &str = "start-inner-postgres";
 /// Imitate a backwards incompatible deserialization from controller state
 pub const LOAD_CONTROLLER_STATE: &str = "load-controller-state";
+/// Imitates a domain failing to respond to a health check ping
+pub const DOMAIN_PING: &str = "domain-ping";
+/// Injected while a domain is processing a packet at a node, and counted towards the time taken to
+/// process the packet. Configure with `sleep(<ms>)` to simulate a slow operator
+pub const DOMAIN_PROCESS_PACKET: &str = "domain-process-packet";
 /// Injects a failpoint at the beginning of DfState::extend_recipe
 pub const EXTEND_RECIPE: &str = "extend-recipe";
+/// Injected before each domain request is sent while applying a migration plan. Configure with
+/// `pause` to hold a migration in the middle of being committed, or with `return` to fail it after
+/// its new domains have been booted
+pub const MIGRATION_APPLY_DOMAIN_REQUEST: &str = "migration-apply-domain-request";
@@ -0,0 +1,211 @@
+//! Health checks for a running ReadySet deployment.
+//!
+//! A [`HealthReport`] is returned by [`ReadySetHandle::health`](crate::ReadySetHandle::health),
+//! and distinguishes failures of the control plane (the workers the controller talks to) from
+//! failures of the data plane (domains and the read path), so that it can be used to drive eg load
+//! balancer health checks.
+use std::fmt::{self, Display};
+
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::internal::ReplicaAddress;
+
+/// The overall health of a ReadySet deployment, as reported in a [`HealthReport`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HealthStatus {
+    /// Every component that was checked is healthy
+    Ready,
+    /// Some, but not all, components are unhealthy
+    Degraded,
+    /// The controller or every worker is unreachable
+    Unhealthy,
+}
+
+impl Display for HealthStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            HealthStatus::Ready => "Ready",
+            HealthStatus::Degraded => "Degraded",
+            HealthStatus::Unhealthy => "Unhealthy",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// The result of health checking a single component
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ComponentHealth {
+    /// The component responded successfully
+    Healthy,
+    /// The component responded with an error
+    Failed(String),
+    /// The component did not respond before the deadline for the health check
+    TimedOut,
+    /// The component is a domain replica which has not been placed onto a worker
+    Unplaced,
+}
+
+impl ComponentHealth {
+    /// Returns `true` if this is [`ComponentHealth::Healthy`]
+    pub fn is_healthy(&self) -> bool {
+        matches!(self, ComponentHealth::Healthy)
+    }
+}
+
+/// The health of a single replica of a shard of a domain
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DomainHealth {
+    pub replica_address: ReplicaAddress,
+    pub health: ComponentHealth,
+}
+
+/// The health of a single worker
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WorkerHealth {
+    pub uri: Url,
+    pub health: ComponentHealth,
+}
+
+/// The result of health checking every domain replica and worker known to the controller, plus the
+/// read path
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HealthReport {
+    /// The overall health, derived from the health of each component
+    pub status: HealthStatus,
+    /// The health of each domain replica, as determined by sending a ping to it
+    pub domains: Vec<DomainHealth>,
+    /// The health of each worker registered with the controller
+    pub workers: Vec<WorkerHealth>,
+    /// The health of the read path, as determined by performing a read against a view, or `None`
+    /// if there were no views to read from
+    pub read_path: Option<ComponentHealth>,
+}
+
+impl HealthReport {
+    /// Construct a new [`HealthReport`] from the health of each component, computing the overall
+    /// [`HealthStatus`] from them.
+    pub fn new(
+        domains: Vec<DomainHealth>,
+        workers: Vec<WorkerHealth>,
+        read_path: Option<ComponentHealth>,
+    ) -> Self {
+        let status = if !workers.is_empty() && workers.iter().all(|w| !w.health.is_healthy()) {
+            HealthStatus::Unhealthy
+        } else if domains
+            .iter()
+            .map(|d| &d.health)
+            .chain(workers.iter().map(|w| &w.health))
+            .chain(&read_path)
+            .all(ComponentHealth::is_healthy)
+        {
+            HealthStatus::Ready
+        } else {
+            HealthStatus::Degraded
+        };
+
+        Self {
+            status,
+            domains,
+            workers,
+            read_path,
+        }
+    }
+
+    /// Construct a [`HealthReport`] for when the controller itself could not be reached, and so no
+    /// components could be checked.
+    pub fn controller_unreachable() -> Self {
+        Self {
+            status: HealthStatus::Unhealthy,
+            domains: vec![],
+            workers: vec![],
+            read_path: None,
+        }
+    }
+
+    /// Returns an iterator over the addresses of all the domain replicas which are not healthy
+    pub fn unhealthy_domains(&self) -> impl Iterator<Item = &ReplicaAddress> + '_ {
+        self.domains
+            .iter()
+            .filter(|d| !d.health.is_healthy())
+            .map(|d| &d.replica_address)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::internal::DomainIndex;
+
+    fn domain(domain_index: usize, health: ComponentHealth) -> DomainHealth {
+        DomainHealth {
+            replica_address: ReplicaAddress {
+                domain_index: DomainIndex::from(domain_index),
+                shard: 0,
+                replica: 0,
+            },
+            health,
+        }
+    }
+
+    fn worker(health: ComponentHealth) -> WorkerHealth {
+        WorkerHealth {
+            uri: "http://127.0.0.1:6033".parse().unwrap(),
+            health,
+        }
+    }
+
+    #[test]
+    fn all_healthy_is_ready() {
+        let report = HealthReport::new(
+            vec![
+                domain(0, ComponentHealth::Healthy),
+                domain(1, ComponentHealth::Healthy),
+            ],
+            vec![worker(ComponentHealth::Healthy)],
+            Some(ComponentHealth::Healthy),
+        );
+        assert_eq!(report.status, HealthStatus::Ready);
+        assert_eq!(report.unhealthy_domains().count(), 0);
+    }
+
+    #[test]
+    fn unhealthy_domain_is_degraded() {
+        let report = HealthReport::new(
+            vec![
+                domain(0, ComponentHealth::Healthy),
+                domain(1, ComponentHealth::TimedOut),
+            ],
+            vec![worker(ComponentHealth::Healthy)],
+            None,
+        );
+        assert_eq!(report.status, HealthStatus::Degraded);
+        assert_eq!(
+            report
+                .unhealthy_domains()
+                .map(|addr| addr.domain_index)
+                .collect::<Vec<_>>(),
+            vec![DomainIndex::from(1)]
+        );
+    }
+
+    #[test]
+    fn failed_read_path_is_degraded() {
+        let report = HealthReport::new(
+            vec![domain(0, ComponentHealth::Healthy)],
+            vec![worker(ComponentHealth::Healthy)],
+            Some(ComponentHealth::Failed("connection refused".into())),
+        );
+        assert_eq!(report.status, HealthStatus::Degraded);
+    }
+
+    #[test]
+    fn all_workers_unreachable_is_unhealthy() {
+        let report = HealthReport::new(
+            vec![domain(0, ComponentHealth::TimedOut)],
+            vec![worker(ComponentHealth::TimedOut)],
+            None,
+        );
+        assert_eq!(report.status, HealthStatus::Unhealthy);
+    }
+}
@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 
 /// Describe the materialization state of an operator.
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum MaterializationStatus {
     /// Operator's state is not materialized.
     Not,
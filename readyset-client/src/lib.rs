@@ -256,10 +256,12 @@ pub mod failpoints;
 
 pub mod consistency;
 mod controller;
+pub mod health;
 pub mod metrics;
 pub mod query;
 pub mod status;
 mod table;
+pub mod tools;
 pub mod utils;
 mod view;
 use std::convert::TryFrom;
@@ -366,8 +368,9 @@ pub use crate::table::{
     TableOperation, TableReplicationStatus, TableRequest, TableStatus,
 };
 pub use crate::view::{
-    KeyComparison, LookupResult, ReadQuery, ReadReply, ReadReplyBatch, ReadReplyStats, SchemaType,
-    View, ViewCreateRequest, ViewQuery,
+    KeyComparison, LookupResult, RangeLookupResult, RangeScanBatch, ReadError, ReadQuery,
+    ReadReply, ReadReplyBatch, ReadReplyStats, ScanBatch, ScanCursor, ScanSummary, SchemaType,
+    View, ViewCreateRequest, ViewQuery, ViewScan, ViewShardState,
 };
 
 pub mod builders {
@@ -194,6 +194,9 @@ pub mod recorded {
     /// Counter: The number of controller HTTP requests received by the readyset-server.
     pub const SERVER_CONTROLLER_REQUESTS: &str = "readyset_server.controller_requests";
 
+    /// Gauge: The number of domain replicas currently running on this worker.
+    pub const SERVER_WORKER_RUNNING_DOMAINS: &str = "readyset_server.worker_running_domains";
+
     /// Counter: The number of lookup requests to a base table nodes state.
     ///
     /// | Tag | Description |
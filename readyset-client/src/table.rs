@@ -241,6 +241,9 @@ pub struct PacketTrace {
     /// be adjusted, and system time values across machines is subject to
     /// synchronization issues.
     pub start: SystemTime,
+    /// Sequence number of the trace, which increases with each trace generated by the same
+    /// writer - either a [`Table`] handle, or the domain that received the write.
+    pub sequence: u64,
 }
 
 /// Wrapper of packet payloads with their destination node.
@@ -308,6 +311,10 @@ pub struct TableBuilder {
     pub ni: NodeIndex,
     pub addr: LocalNodeIndex,
     pub key_is_primary: bool,
+    /// The primary key of the table, or the column it is sharded by if it has no primary key.
+    ///
+    /// Empty for unsharded tables with neither, which only support inserting and deleting whole
+    /// rows.
     pub key: Vec<usize>,
     pub dropped: VecMap<DfValue>,
 
@@ -371,6 +378,7 @@ impl TableBuilder {
             shard_addrs: addrs,
             shards: conns,
             last_trace_sample: Instant::now(),
+            next_trace_sequence: 0,
             request_timeout: self.table_request_timeout,
         }
     }
@@ -396,6 +404,7 @@ pub struct Table {
     shards: Vec<TableRpc>,
     shard_addrs: Vec<SocketAddr>,
     last_trace_sample: Instant,
+    next_trace_sequence: u64,
     request_timeout: Duration,
 }
 
@@ -820,8 +829,10 @@ impl Table {
         }
 
         self.last_trace_sample = now;
+        self.next_trace_sequence += 1;
         Some(PacketTrace {
             start: SystemTime::now(),
+            sequence: self.next_trace_sequence,
         })
     }
 
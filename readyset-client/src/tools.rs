@@ -0,0 +1,314 @@
+//! Tools for working with the state of ReadySet offline, outside of a running deployment.
+//!
+//! Currently this consists of the on-disk format written by
+//! [`ReadySetHandle::export_view_state`](crate::ReadySetHandle::export_view_state), which
+//! captures the materialized state of a view for debugging, and [`read_view_export`] for reading
+//! it back.
+//!
+//! An export of a single shard consists of a bincode-serialized [`ShardExportHeader`], followed by
+//! one bincode-serialized `(key, rows)` entry for each key present in the reader. An export of a
+//! sharded view to a path is a directory containing one such file per shard, along with a JSON
+//! [`ExportManifest`] named [`MANIFEST_FILE_NAME`].
+
+use std::fs::File;
+use std::io::{self, BufReader, Read};
+use std::path::{Path, PathBuf};
+
+use readyset_data::DfValue;
+use readyset_errors::{ReadySetError, ReadySetResult};
+use serde::{Deserialize, Serialize};
+
+use crate::ViewShardState;
+
+/// The version of the export format written by this version of ReadySet
+pub const EXPORT_FORMAT_VERSION: u32 = 1;
+
+/// The name of the manifest file written to the directory of an export of a sharded view
+pub const MANIFEST_FILE_NAME: &str = "manifest.json";
+
+/// A single key of an exported view, along with all the rows for that key
+pub type ExportEntry = (Vec<DfValue>, Vec<Vec<DfValue>>);
+
+/// Where to write an export of the state of a view
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExportDest {
+    /// Write the export to a file at the given path, or if the view is sharded, to a directory at
+    /// the given path containing one file per shard and a manifest
+    Path(PathBuf),
+    /// Return the export of each shard of the view in [`ExportSummary::bytes`]
+    Bytes,
+}
+
+/// A summary of an export of the state of a view
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExportSummary {
+    /// The total number of keys exported, across all shards
+    pub keys: usize,
+    /// The total number of rows exported, across all shards
+    pub rows: usize,
+    /// The total number of bytes of the export, across all shards (not including the manifest)
+    pub bytes_written: usize,
+    /// Whether the view is partially materialized, in which case only the keys which were filled
+    /// at the time of the export are included
+    pub partial: bool,
+    /// If exported to [`ExportDest::Bytes`], the export of each shard of the view, in shard order
+    pub bytes: Vec<Vec<u8>>,
+}
+
+/// The header at the start of the export of a single shard of a view
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ShardExportHeader {
+    /// The version of the export format, currently always [`EXPORT_FORMAT_VERSION`]
+    pub version: u32,
+    /// The name of the exported view
+    pub view: String,
+    /// The index of the exported shard
+    pub shard: usize,
+    /// Whether the reader is partially materialized
+    pub partial: bool,
+    /// The number of entries following this header
+    pub num_keys: u64,
+}
+
+/// Description of a single shard file in an [`ExportManifest`]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ShardManifest {
+    /// The name of the shard's file, relative to the directory containing the manifest
+    pub file: String,
+    /// The number of keys exported from the shard
+    pub keys: usize,
+    /// The number of rows exported from the shard
+    pub rows: usize,
+}
+
+/// The manifest written alongside the per-shard files of an export of a sharded view
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ExportManifest {
+    /// The version of the export format, currently always [`EXPORT_FORMAT_VERSION`]
+    pub version: u32,
+    /// The name of the exported view
+    pub view: String,
+    /// Whether the view is partially materialized
+    pub partial: bool,
+    /// Each shard of the view, in shard order
+    pub shards: Vec<ShardManifest>,
+}
+
+/// Serialize the export of the given shard of the view named `view`
+fn serialize_shard(view: &str, shard: usize, state: &ViewShardState) -> ReadySetResult<Vec<u8>> {
+    let header = ShardExportHeader {
+        version: EXPORT_FORMAT_VERSION,
+        view: view.to_owned(),
+        shard,
+        partial: state.partial,
+        num_keys: state.entries.len() as u64,
+    };
+    let mut buf = bincode::serialize(&header)?;
+    for entry in &state.entries {
+        bincode::serialize_into(&mut buf, entry)?;
+    }
+    Ok(buf)
+}
+
+/// Write the exported state of each shard of the view named `view` to `dest`, returning a summary
+/// of the export
+pub(crate) async fn write_view_export(
+    view: &str,
+    shards: Vec<ViewShardState>,
+    dest: ExportDest,
+) -> ReadySetResult<ExportSummary> {
+    let mut summary = ExportSummary {
+        partial: shards.iter().any(|s| s.partial),
+        ..Default::default()
+    };
+    let mut manifest = ExportManifest {
+        version: EXPORT_FORMAT_VERSION,
+        view: view.to_owned(),
+        partial: summary.partial,
+        shards: Vec::with_capacity(shards.len()),
+    };
+
+    let sharded = shards.len() > 1;
+    if let (ExportDest::Path(dir), true) = (&dest, sharded) {
+        tokio::fs::create_dir_all(dir).await?;
+    }
+
+    for (shard, state) in shards.iter().enumerate() {
+        let keys = state.entries.len();
+        let rows = state.entries.iter().map(|(_, rows)| rows.len()).sum();
+        let buf = serialize_shard(view, shard, state)?;
+        summary.keys += keys;
+        summary.rows += rows;
+        summary.bytes_written += buf.len();
+
+        match &dest {
+            ExportDest::Bytes => summary.bytes.push(buf),
+            ExportDest::Path(path) if !sharded => tokio::fs::write(path, buf).await?,
+            ExportDest::Path(dir) => {
+                let file = format!("shard_{shard}");
+                tokio::fs::write(dir.join(&file), buf).await?;
+                manifest.shards.push(ShardManifest { file, keys, rows });
+            }
+        }
+    }
+
+    if let (ExportDest::Path(dir), true) = (&dest, sharded) {
+        tokio::fs::write(
+            dir.join(MANIFEST_FILE_NAME),
+            serde_json::to_vec_pretty(&manifest)?,
+        )
+        .await?;
+    }
+
+    Ok(summary)
+}
+
+/// An iterator over the entries of the export of a single shard of a view, returned by
+/// [`ShardExportReader::new`]
+pub struct ShardExportReader<R> {
+    reader: R,
+    header: ShardExportHeader,
+    remaining: u64,
+}
+
+impl<R: Read> ShardExportReader<R> {
+    /// Read the header of the export of a single shard of a view from `reader`, returning an
+    /// iterator over its entries
+    pub fn new(mut reader: R) -> ReadySetResult<Self> {
+        let header: ShardExportHeader = bincode::deserialize_from(&mut reader)?;
+        if header.version != EXPORT_FORMAT_VERSION {
+            return Err(ReadySetError::SerializationFailed(format!(
+                "Unsupported view export format version {} (expected {})",
+                header.version, EXPORT_FORMAT_VERSION
+            )));
+        }
+        Ok(Self {
+            reader,
+            remaining: header.num_keys,
+            header,
+        })
+    }
+
+    /// Returns the header of the export
+    pub fn header(&self) -> &ShardExportHeader {
+        &self.header
+    }
+}
+
+impl<R: Read> Iterator for ShardExportReader<R> {
+    type Item = ReadySetResult<ExportEntry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        Some(bincode::deserialize_from(&mut self.reader).map_err(|e| {
+            // Don't keep reading past a corrupt entry
+            self.remaining = 0;
+            e.into()
+        }))
+    }
+}
+
+fn open_shard(path: &Path) -> ReadySetResult<ShardExportReader<BufReader<File>>> {
+    ShardExportReader::new(BufReader::new(File::open(path)?))
+}
+
+/// Read an export of the state of a view written by
+/// [`ReadySetHandle::export_view_state`](crate::ReadySetHandle::export_view_state), returning an
+/// iterator over each `(key, rows)` entry in the export.
+///
+/// `path` can either be the file written for an unsharded view, or the directory written for a
+/// sharded view, in which case the entries of every shard listed in the manifest are returned in
+/// shard order.
+pub fn read_view_export<P: AsRef<Path>>(
+    path: P,
+) -> ReadySetResult<Box<dyn Iterator<Item = ReadySetResult<ExportEntry>>>> {
+    let path = path.as_ref();
+    if !path.is_dir() {
+        return Ok(Box::new(open_shard(path)?));
+    }
+
+    let manifest: ExportManifest =
+        serde_json::from_reader(BufReader::new(File::open(path.join(MANIFEST_FILE_NAME))?))?;
+    let shards = manifest
+        .shards
+        .iter()
+        .map(|shard| open_shard(&path.join(&shard.file)))
+        .collect::<ReadySetResult<Vec<_>>>()?;
+    Ok(Box::new(shards.into_iter().flatten()))
+}
+
+/// Read the export of a single shard of a view from an in-memory buffer, as returned in
+/// [`ExportSummary::bytes`]
+pub fn read_shard_export(
+    bytes: &[u8],
+) -> ReadySetResult<impl Iterator<Item = ReadySetResult<ExportEntry>> + '_> {
+    ShardExportReader::new(io::Cursor::new(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shard(partial: bool, keys: std::ops::Range<i32>) -> ViewShardState {
+        ViewShardState {
+            partial,
+            entries: keys
+                .map(|k| (vec![k.into()], vec![vec![k.into(), (k * 2).into()]]))
+                .collect(),
+        }
+    }
+
+    fn read_all(it: impl Iterator<Item = ReadySetResult<ExportEntry>>) -> Vec<ExportEntry> {
+        it.collect::<ReadySetResult<_>>().unwrap()
+    }
+
+    #[tokio::test]
+    async fn round_trip_bytes() {
+        let state = shard(true, 0..10);
+        let summary = write_view_export("v", vec![state.clone()], ExportDest::Bytes)
+            .await
+            .unwrap();
+        assert_eq!(summary.keys, 10);
+        assert_eq!(summary.rows, 10);
+        assert!(summary.partial);
+        assert_eq!(summary.bytes.len(), 1);
+        assert_eq!(summary.bytes_written, summary.bytes[0].len());
+
+        let reader = ShardExportReader::new(summary.bytes[0].as_slice()).unwrap();
+        assert!(reader.header().partial);
+        assert_eq!(read_all(reader), state.entries);
+    }
+
+    #[tokio::test]
+    async fn round_trip_sharded_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("export");
+        let shards = vec![shard(false, 0..5), shard(false, 5..8)];
+        let summary = write_view_export("v", shards.clone(), ExportDest::Path(path.clone()))
+            .await
+            .unwrap();
+        assert_eq!(summary.keys, 8);
+        assert!(!summary.partial);
+        assert!(path.join(MANIFEST_FILE_NAME).exists());
+
+        assert_eq!(
+            read_all(read_view_export(&path).unwrap()),
+            shards
+                .into_iter()
+                .flat_map(|s| s.entries)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn truncated_export() {
+        let mut buf = serialize_shard("v", 0, &shard(false, 0..3)).unwrap();
+        buf.truncate(buf.len() - 1);
+        let entries = read_shard_export(&buf).unwrap().collect::<Vec<_>>();
+        assert_eq!(entries.len(), 3);
+        assert!(entries[2].is_err());
+    }
+}
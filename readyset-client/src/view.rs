@@ -16,7 +16,7 @@ use async_bincode::{AsyncBincodeStream, AsyncDestination};
 use dataflow_expression::{BinaryOperator as DfBinaryOperator, Dialect, Expr as DfExpr};
 use futures_util::future::TryFutureExt;
 use futures_util::stream::futures_unordered::FuturesUnordered;
-use futures_util::stream::{StreamExt, TryStreamExt};
+use futures_util::stream::{FuturesOrdered, StreamExt, TryStreamExt};
 use futures_util::{future, ready};
 use nom_sql::{
     BinaryOperator, Column, ColumnConstraint, ColumnSpecification, ItemPlaceholder, Literal,
@@ -28,7 +28,8 @@ use rand::prelude::IteratorRandom;
 use rand::thread_rng;
 use readyset_data::{Bound, BoundedRange, DfType, DfValue, IntoBoundedRange, RangeBounds};
 use readyset_errors::{
-    internal, internal_err, rpc_err, unsupported, view_err, ReadySetError, ReadySetResult,
+    internal, internal_err, rpc_err, shard_unreachable_err, unsupported, view_err, ReadySetError,
+    ReadySetResult,
 };
 use readyset_sql_passes::anonymize::{Anonymize, Anonymizer};
 use readyset_tracing::child_span;
@@ -48,8 +49,10 @@ use tracing::{debug_span, error, instrument, trace};
 use tracing_futures::Instrument;
 use vec1::{vec1, Vec1};
 
+mod read_error;
 pub(crate) mod results;
 
+pub use self::read_error::ReadError;
 use self::results::{ResultIterator, Results};
 use crate::consistency::Timestamp;
 use crate::{ReaderAddress, Tagged, Tagger};
@@ -162,6 +165,8 @@ pub struct ColumnSchema {
     pub column: Column,
     /// The column's type
     pub column_type: DfType,
+    /// Whether the column may contain NULL values
+    pub nullable: bool,
     /// If the column is an alias, this field represents its base column
     pub base: Option<ColumnBase>,
 }
@@ -174,7 +179,12 @@ impl ColumnSchema {
         table: Relation,
         dialect: Dialect,
     ) -> ReadySetResult<Self> {
+        let nullable = !spec
+            .constraints
+            .iter()
+            .any(|c| matches!(c, ColumnConstraint::NotNull | ColumnConstraint::PrimaryKey));
         Ok(Self {
+            nullable,
             base: Some(ColumnBase {
                 column: spec.column.name.clone(),
                 table,
@@ -803,6 +813,30 @@ pub enum ReadQuery {
         /// Where to read from
         target: ReaderAddress,
     },
+    /// Scan the rows of a leaf view whose key falls within a range, in ascending key order
+    RangeScan {
+        /// Where to read from
+        target: ReaderAddress,
+        /// The lower and upper bounds on the (single-column) key of the view
+        bounds: BoundedRange<DfValue>,
+        /// The maximum number of rows to return
+        limit: Option<usize>,
+    },
+    /// Read the full materialized state of a leaf view, for offline analysis
+    Export {
+        /// Where to read from
+        target: ReaderAddress,
+    },
+    /// Read the next batch of keys (along with their rows) in a full scan of a leaf view
+    Scan {
+        /// Where to read from
+        target: ReaderAddress,
+        /// The last key returned by the previous batch of the scan, or `None` to start from the
+        /// beginning
+        cursor: Option<Vec<DfValue>>,
+        /// The maximum number of keys to return
+        batch_size: usize,
+    },
 }
 
 /// The result of a lookup to a view.
@@ -862,6 +896,204 @@ pub enum ReadReply<D = ReadReplyBatch> {
     Size(usize),
     // Read keys of view
     Keys(Vec<Vec<DfValue>>),
+    /// A reply to a range scan request
+    RangeScan(ReadySetResult<RangeScanBatch>),
+    /// A reply to an export request
+    Export(ReadySetResult<ViewShardState>),
+    /// A reply to a scan request
+    Scan(ReadySetResult<ScanBatch>),
+}
+
+/// The materialized state of a single shard of a view, as returned by
+/// [`ReaderHandle::export_state`].
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq, Eq, Clone)]
+pub struct ViewShardState {
+    /// Whether the reader is partially materialized, in which case `entries` only includes the
+    /// keys which were filled at the time of the export
+    pub partial: bool,
+    /// Each key present in the reader, along with the rows for that key
+    pub entries: Vec<(Vec<DfValue>, Vec<Vec<DfValue>>)>,
+}
+
+/// A single page of the results of a range scan against one shard of a view.
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq, Eq, Clone)]
+pub struct RangeScanBatch {
+    /// `(key, row)` pairs within the requested bounds, in ascending key order
+    pub rows: Vec<(DfValue, Vec<DfValue>)>,
+    /// If the scan stopped early because it reached its limit, the last key that was returned.
+    ///
+    /// All rows for that key are included in `rows`, so the scan can be resumed by passing this
+    /// key as an excluded lower bound.
+    pub continuation: Option<DfValue>,
+}
+
+impl RangeScanBatch {
+    /// Merge the batches returned by each shard of a view into a single batch, keeping at most
+    /// `limit` rows.
+    ///
+    /// Rows are only ever cut at key boundaries, so that the resulting continuation token never
+    /// skips over rows for a key that has only been partially returned.
+    pub fn merge<I>(batches: I, limit: Option<usize>) -> Self
+    where
+        I: IntoIterator<Item = RangeScanBatch>,
+    {
+        let mut rows = vec![];
+        // The smallest continuation key of any shard that stopped early - no rows past this key
+        // can be returned, since that shard may have more rows before it
+        let mut continuation: Option<DfValue> = None;
+        for batch in batches {
+            rows.extend(batch.rows);
+            if let Some(key) = batch.continuation {
+                if continuation.iter().all(|c| key < *c) {
+                    continuation = Some(key);
+                }
+            }
+        }
+
+        // Stable sort, so rows for the same key stay in the order their shard returned them
+        rows.sort_by(|(k1, _), (k2, _)| k1.cmp(k2));
+        if let Some(cont) = &continuation {
+            rows.retain(|(k, _)| k <= cont);
+        }
+
+        if let Some(limit) = limit {
+            if rows.len() > limit {
+                // Don't split the rows for a single key, but always make progress
+                let last_key = rows[limit.max(1) - 1].0.clone();
+                let end = rows.partition_point(|(k, _)| *k <= last_key);
+                rows.truncate(end);
+                continuation = Some(last_key);
+            }
+        }
+
+        Self { rows, continuation }
+    }
+}
+
+/// The results of [`ReaderHandle::range_lookup`].
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct RangeLookupResult {
+    /// The rows within the requested bounds, in ascending key order
+    pub rows: Vec<Vec<DfValue>>,
+    /// If more rows are available, the key to pass as an excluded lower bound to fetch the next
+    /// page of results
+    pub continuation: Option<DfValue>,
+}
+
+/// A single batch of a full scan of one shard of a view, as returned by the reader for a
+/// [`ReadQuery::Scan`].
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq, Eq, Clone)]
+pub struct ScanBatch {
+    /// Each key in the batch, along with the rows for that key, in ascending key order
+    pub entries: Vec<(Vec<DfValue>, Vec<Vec<DfValue>>)>,
+    /// Whether the shard has any more keys after the last key in `entries`
+    pub more: bool,
+    /// Whether the reader is partially materialized, in which case `entries` only includes keys
+    /// which were filled at the time of the scan
+    pub partial: bool,
+}
+
+/// A serializable position within a scan of a view, which can be used to resume the scan with
+/// [`ReaderHandle::scan_from`].
+///
+/// The cursor records the last key returned rather than a position within the reader's state, so
+/// it remains valid regardless of what is written to the view in between batches.
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq, Eq, Clone)]
+pub struct ScanCursor {
+    /// The index of the shard currently being scanned
+    shard: usize,
+    /// The last key returned from that shard, or `None` if no keys have been returned from it
+    /// yet
+    last_key: Option<Vec<DfValue>>,
+}
+
+/// A summary of the keys and rows returned so far by a [`ViewScan`]
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub struct ScanSummary {
+    /// The number of keys returned
+    pub keys: usize,
+    /// The number of rows returned
+    pub rows: usize,
+    /// Whether any of the shards scanned were partially materialized, in which case only the keys
+    /// which were filled at the time of the scan were returned
+    pub partial: bool,
+}
+
+/// A pull-based scan over every key in a view, along with its rows, created with
+/// [`ReaderHandle::scan`].
+///
+/// Each call to [`next_batch`](Self::next_batch) reads the next batch of keys from a single shard,
+/// and shards are scanned one after another, in order. Keys within each shard are returned in
+/// ascending order.
+///
+/// The scan is not a consistent snapshot of the view. Each key which is present in the view for
+/// the whole duration of the scan is returned exactly once, but a key which is written to while
+/// the scan is in progress may be returned with rows from before or after the write, and a key
+/// which is inserted behind the scan's current position isn't returned at all. If the view is
+/// partially materialized, keys which are evicted or filled concurrently may similarly be returned
+/// zero times, and are never filled by the scan itself. Concurrent writes never invalidate the
+/// scan's [cursor](Self::cursor).
+#[derive(Debug)]
+pub struct ViewScan {
+    handle: ReaderHandle,
+    batch_size: usize,
+    cursor: ScanCursor,
+    summary: ScanSummary,
+}
+
+impl ViewScan {
+    /// Read the next batch of (at most `batch_size`) keys in the scan, along with their rows, or
+    /// return `None` if the scan is complete.
+    pub async fn next_batch(
+        &mut self,
+    ) -> ReadySetResult<Option<Vec<(Vec<DfValue>, Vec<Vec<DfValue>>)>>> {
+        while self.cursor.shard < self.handle.num_shards() {
+            let batch = self
+                .handle
+                .scan_shard(
+                    self.cursor.shard,
+                    self.cursor.last_key.clone(),
+                    self.batch_size,
+                )
+                .await?;
+
+            self.summary.partial |= batch.partial;
+            self.summary.keys += batch.entries.len();
+            self.summary.rows += batch
+                .entries
+                .iter()
+                .map(|(_, rows)| rows.len())
+                .sum::<usize>();
+
+            if batch.more {
+                self.cursor.last_key = batch.entries.last().map(|(key, _)| key.clone());
+            } else {
+                self.cursor.shard += 1;
+                self.cursor.last_key = None;
+            }
+
+            if !batch.entries.is_empty() {
+                return Ok(Some(batch.entries));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Returns the current position of this scan, which can be passed to
+    /// [`ReaderHandle::scan_from`] to resume the scan from after the last batch returned by
+    /// [`next_batch`](Self::next_batch)
+    pub fn cursor(&self) -> &ScanCursor {
+        &self.cursor
+    }
+
+    /// Returns a summary of the keys and rows returned by this scan so far.
+    ///
+    /// If the scan was resumed from a cursor, only the batches returned since it was resumed are
+    /// included.
+    pub fn summary(&self) -> ScanSummary {
+        self.summary
+    }
 }
 
 impl<D> ReadReply<D> {
@@ -1174,10 +1406,11 @@ impl Service<ViewQuery> for ReaderHandle {
     type Future = impl Future<Output = Result<Self::Response, Self::Error>> + Send;
 
     fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
-        for s in &mut self.shards {
+        for (shard, s) in self.shards.iter_mut().enumerate() {
             let ni = self.node;
             ready!(s.poll_ready(cx))
                 .map_err(rpc_err!("<View as Service<ViewQuery>>::poll_ready"))
+                .map_err(|e| shard_unreachable_err(shard, e))
                 .map_err(|e| view_err(ni, e))?
         }
         Poll::Ready(Ok(()))
@@ -1215,6 +1448,7 @@ impl Service<ViewQuery> for ReaderHandle {
                         multiplex::MultiplexTransport<Transport, Tagger>,
                         Instrumented<Tagged<ReadQuery>>,
                     ))
+                    .map_err(|e| shard_unreachable_err(0, e))
                     .and_then(move |reply| {
                         let future = async move {
                             reply
@@ -1295,6 +1529,7 @@ impl Service<ViewQuery> for ReaderHandle {
                     shard
                         .call(request)
                         .map_err(rpc_err!("<View as Service<ViewQuery>>::call"))
+                        .map_err(move |e| shard_unreachable_err(shardi, e))
                         .and_then(|reply| async move {
                             reply.v.into_normal().ok_or_else(|| {
                                 internal_err!("Unexpected response type from reader service")
@@ -1455,6 +1690,181 @@ impl ReaderHandle {
         Ok(vec)
     }
 
+    /// Retrieve the full materialized state of each shard of this view, in shard order.
+    ///
+    /// Each reader exports its state in chunks, so that the export doesn't block concurrent reads
+    /// for its full duration. As a result, the export is not a consistent snapshot of the view if
+    /// it's being written to concurrently.
+    #[instrument(level = "info", skip(self))]
+    pub async fn export_state(&mut self) -> ReadySetResult<Vec<ViewShardState>> {
+        future::poll_fn(|cx| self.poll_ready(cx)).await?;
+
+        let node = self.node;
+        let name = self.name.clone();
+        let rsps = self
+            .shards
+            .iter_mut()
+            .enumerate()
+            .map(|(shardi, shard)| {
+                shard.call(Instrumented::from(Tagged::from(ReadQuery::Export {
+                    target: ReaderAddress {
+                        node,
+                        name: name.clone(),
+                        shard: shardi,
+                    },
+                })))
+            })
+            .collect::<FuturesOrdered<_>>();
+
+        rsps.map(
+            |reply| match reply.map_err(rpc_err!("View::export_state"))?.v {
+                ReadReply::Export(state) => state,
+                _ => unreachable!(),
+            },
+        )
+        .try_collect()
+        .await
+    }
+
+    /// Start a scan over every key in this view, along with its rows, reading at most
+    /// `batch_size` keys at a time.
+    ///
+    /// See the documentation for [`ViewScan`] for the consistency guarantees of the scan.
+    pub fn scan(&self, batch_size: usize) -> ViewScan {
+        self.scan_from(ScanCursor::default(), batch_size)
+    }
+
+    /// Resume a scan over this view from a cursor returned by [`ViewScan::cursor`], reading at
+    /// most `batch_size` keys at a time.
+    pub fn scan_from(&self, cursor: ScanCursor, batch_size: usize) -> ViewScan {
+        ViewScan {
+            handle: self.clone(),
+            batch_size: batch_size.max(1),
+            cursor,
+            summary: ScanSummary::default(),
+        }
+    }
+
+    /// Read a single batch of a scan from the given shard of this view
+    #[instrument(level = "debug", skip(self))]
+    async fn scan_shard(
+        &mut self,
+        shard: usize,
+        cursor: Option<Vec<DfValue>>,
+        batch_size: usize,
+    ) -> ReadySetResult<ScanBatch> {
+        future::poll_fn(|cx| self.poll_ready(cx)).await?;
+
+        let target = ReaderAddress {
+            node: self.node,
+            name: self.name.clone(),
+            shard,
+        };
+        let reply = self
+            .shards
+            .get_mut(shard)
+            .ok_or_else(|| internal_err!("Scan cursor refers to nonexistent shard {shard}"))?
+            .call(Instrumented::from(Tagged::from(ReadQuery::Scan {
+                target,
+                cursor,
+                batch_size,
+            })))
+            .await
+            .map_err(rpc_err!("View::scan"))?;
+
+        match reply.v {
+            ReadReply::Scan(batch) => batch,
+            _ => unreachable!(),
+        }
+    }
+
+    /// Retrieve the rows of this view whose key falls between `lower` and `upper`, in ascending
+    /// key order, returning at most `limit` rows.
+    ///
+    /// The view must be keyed on a single column with an ordered (btree) index, otherwise
+    /// [`ReadySetError::RangeUnsupported`] is returned. If the scan misses and `block` is `true`,
+    /// the holes in the requested range are filled and the scan is retried; otherwise a miss
+    /// returns [`ReadySetError::ReaderMissingKey`].
+    ///
+    /// The reader's post-lookup operations are applied to the rows for each key, so each key's
+    /// rows are the same as a lookup of just that key would return.
+    ///
+    /// If more rows are available than were returned, the result includes a continuation key,
+    /// which can be passed as [`Bound::Excluded`] to a subsequent call to fetch the next page.
+    #[instrument(level = "info", skip(self))]
+    pub async fn range_lookup(
+        &mut self,
+        lower: Bound<DfValue>,
+        upper: Bound<DfValue>,
+        limit: Option<usize>,
+        block: bool,
+    ) -> ReadySetResult<RangeLookupResult> {
+        // Scan before filling any holes, so that readers which can't be scanned return
+        // `RangeUnsupported` rather than failing the lookup
+        let batch = match self.range_scan_shards(&lower, &upper, limit).await {
+            Err(ReadySetError::ReaderMissingKey) if block => {
+                let key = KeyComparison::Range((
+                    lower.clone().map(|v| vec1![v]),
+                    upper.clone().map(|v| vec1![v]),
+                ));
+                self.raw_lookup((vec![key], true).into()).await?;
+                self.range_scan_shards(&lower, &upper, limit).await?
+            }
+            res => res?,
+        };
+
+        Ok(RangeLookupResult {
+            rows: batch.rows.into_iter().map(|(_, row)| row).collect(),
+            continuation: batch.continuation,
+        })
+    }
+
+    /// Scan the rows between `lower` and `upper` in every shard of this view, merging the results
+    /// into a single batch of at most `limit` rows
+    async fn range_scan_shards(
+        &mut self,
+        lower: &Bound<DfValue>,
+        upper: &Bound<DfValue>,
+        limit: Option<usize>,
+    ) -> ReadySetResult<RangeScanBatch> {
+        future::poll_fn(|cx| self.poll_ready(cx)).await?;
+
+        let node = self.node;
+        let name = self.name.clone();
+        let mut rsps = self
+            .shards
+            .iter_mut()
+            .enumerate()
+            .map(|(shardi, shard)| {
+                shard.call(Instrumented::from(Tagged::from(ReadQuery::RangeScan {
+                    target: ReaderAddress {
+                        node,
+                        name: name.clone(),
+                        shard: shardi,
+                    },
+                    bounds: (lower.clone(), upper.clone()),
+                    limit,
+                })))
+            })
+            .collect::<FuturesUnordered<_>>();
+
+        let mut batches = vec![];
+        while let Some(reply) = rsps
+            .next()
+            .await
+            .transpose()
+            .map_err(rpc_err!("View::range_lookup"))?
+        {
+            if let ReadReply::RangeScan(batch) = reply.v {
+                batches.push(batch?);
+            } else {
+                unreachable!();
+            }
+        }
+
+        Ok(RangeScanBatch::merge(batches, limit))
+    }
+
     // TODO(andrew): consolidate RYW and normal reads into cohesive API once API design is settled.
     // RYW functionality currently added as duplicate methods so as not to disrupt current
     // reader usage until RYW is fully adopted
@@ -1479,6 +1889,23 @@ impl ReaderHandle {
         self.lookup_ryw(key, block, None).await
     }
 
+    /// Retrieve the rows for the given parameter value, classifying any failure as a
+    /// [`ReadError`].
+    ///
+    /// Unlike [`lookup`](Self::lookup), a blocking lookup which finds no rows for the key returns
+    /// [`ReadError::KeyNotFound`] rather than an empty set of rows.
+    pub async fn lookup_classified(
+        &mut self,
+        key: &[DfValue],
+        block: bool,
+    ) -> Result<Vec<Vec<DfValue>>, ReadError> {
+        let rows = self.lookup(key, block).await?.into_vec();
+        if block && rows.is_empty() {
+            return Err(ReadError::KeyNotFound);
+        }
+        Ok(rows)
+    }
+
     /// Retrieve the query results for the given parameter values.
     ///
     /// The method will block if the results are not yet available only when `block` is `true`.
@@ -2109,6 +2536,7 @@ mod tests {
                             table: Some("t".into()),
                         },
                         column_type: DfType::Int,
+                        nullable: true,
                         base: Some(ColumnBase {
                             table: "t".into(),
                             column: "x".into(),
@@ -2123,6 +2551,7 @@ mod tests {
                             table: Some("t".into()),
                         },
                         column_type: DfType::DEFAULT_TEXT,
+                        nullable: true,
                         base: Some(ColumnBase {
                             table: "t".into(),
                             column: "y".into(),
@@ -2139,6 +2568,7 @@ mod tests {
                             table: Some("t".into()),
                         },
                         column_type: DfType::Int,
+                        nullable: true,
                         base: Some(ColumnBase {
                             table: "t".into(),
                             column: "x".into(),
@@ -2153,6 +2583,7 @@ mod tests {
                             table: Some("t".into()),
                         },
                         column_type: DfType::DEFAULT_TEXT,
+                        nullable: true,
                         base: Some(ColumnBase {
                             table: "t".into(),
                             column: "y".into(),
@@ -2458,4 +2889,61 @@ mod tests {
             );
         }
     }
+
+    mod range_scan_batch {
+        use super::*;
+
+        fn batch(keys: &[i32], continuation: Option<i32>) -> RangeScanBatch {
+            RangeScanBatch {
+                rows: keys
+                    .iter()
+                    .map(|k| (DfValue::from(*k), vec![DfValue::from(*k)]))
+                    .collect(),
+                continuation: continuation.map(DfValue::from),
+            }
+        }
+
+        fn values(keys: &[i32]) -> Vec<DfValue> {
+            keys.iter().copied().map(DfValue::from).collect()
+        }
+
+        fn keys(batch: &RangeScanBatch) -> Vec<DfValue> {
+            batch.rows.iter().map(|(k, _)| k.clone()).collect()
+        }
+
+        #[test]
+        fn merge_no_limit() {
+            let merged =
+                RangeScanBatch::merge(vec![batch(&[1, 3], None), batch(&[2, 4], None)], None);
+            assert_eq!(keys(&merged), values(&[1, 2, 3, 4]));
+            assert_eq!(merged.continuation, None);
+        }
+
+        #[test]
+        fn merge_with_limit() {
+            let merged = RangeScanBatch::merge(
+                vec![batch(&[1, 3, 5], Some(5)), batch(&[2, 4, 6], Some(6))],
+                Some(3),
+            );
+            assert_eq!(keys(&merged), values(&[1, 2, 3]));
+            assert_eq!(merged.continuation, Some(DfValue::from(3)));
+        }
+
+        #[test]
+        fn merge_respects_shard_continuation() {
+            let merged = RangeScanBatch::merge(
+                vec![batch(&[1, 3], Some(3)), batch(&[2, 4, 6], None)],
+                Some(10),
+            );
+            assert_eq!(keys(&merged), values(&[1, 2, 3]));
+            assert_eq!(merged.continuation, Some(DfValue::from(3)));
+        }
+
+        #[test]
+        fn merge_keeps_whole_keys() {
+            let merged = RangeScanBatch::merge(vec![batch(&[1, 2, 2, 2, 3], None)], Some(2));
+            assert_eq!(keys(&merged), values(&[1, 2, 2, 2]));
+            assert_eq!(merged.continuation, Some(DfValue::from(2)));
+        }
+    }
 }
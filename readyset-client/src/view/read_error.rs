@@ -0,0 +1,139 @@
+use readyset_errors::ReadySetError;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// A classification of the ways a read from a view can fail, for use by callers which need to
+/// decide how to handle a failed read - eg whether to retry the read, fall back to the upstream
+/// database, or surface the error.
+///
+/// Obtained from a [`ReadySetError`] returned by a read via [`ReadError::classify`], or returned
+/// directly by [`ReaderHandle::lookup_classified`](crate::ReaderHandle::lookup_classified).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Error)]
+pub enum ReadError {
+    /// The key was not present in a partially materialized view, and the read was non-blocking.
+    /// A replay to fill the key was triggered by the read.
+    #[error("Key not cached")]
+    MissKeyNotCached {
+        /// Whether a blocking retry of the read is expected to succeed, once the replay that was
+        /// triggered by the read completes
+        retry_will_succeed: bool,
+    },
+
+    /// The key was looked up in fully materialized state, and no rows were found for it.
+    #[error("Key not found")]
+    KeyNotFound,
+
+    /// The reader for the view no longer exists, eg because the query was removed
+    #[error("View was dropped")]
+    ViewDropped,
+
+    /// The connection to one of the shards of the view failed
+    #[error("Shard {shard} of view unreachable")]
+    ShardUnreachable {
+        /// The index of the shard which could not be reached
+        shard: usize,
+    },
+
+    /// The read timed out waiting for a replay to fill the key
+    #[error("Timed out waiting for key to be filled")]
+    Timeout,
+
+    /// Any other error
+    #[error(transparent)]
+    Other(ReadySetError),
+}
+
+impl ReadError {
+    /// Classify an error returned by a read from a view
+    pub fn classify(error: ReadySetError) -> Self {
+        let classified = error.find_map_cause(|e| match e {
+            ReadySetError::ReaderMissingKey => Some(ReadError::MissKeyNotCached {
+                // Every miss at the reader triggers a replay for the missed keys
+                retry_will_succeed: true,
+            }),
+            ReadySetError::ViewDestroyed | ReadySetError::ReaderNotFound => {
+                Some(ReadError::ViewDropped)
+            }
+            ReadySetError::ReaderShardUnreachable { shard, .. } => {
+                Some(ReadError::ShardUnreachable { shard: *shard })
+            }
+            ReadySetError::UpqueryTimeout => Some(ReadError::Timeout),
+            _ => None,
+        });
+        match classified {
+            Some(read_error) => read_error,
+            None => ReadError::Other(error),
+        }
+    }
+}
+
+impl From<ReadySetError> for ReadError {
+    fn from(error: ReadySetError) -> Self {
+        Self::classify(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use petgraph::graph::NodeIndex;
+    use readyset_errors::rpc_err_no_downcast;
+
+    use super::*;
+
+    fn view_err(err: ReadySetError) -> ReadySetError {
+        readyset_errors::view_err(NodeIndex::new(1), err)
+    }
+
+    #[test]
+    fn classify_miss() {
+        assert_eq!(
+            ReadError::classify(ReadySetError::ReaderMissingKey),
+            ReadError::MissKeyNotCached {
+                retry_will_succeed: true
+            }
+        );
+    }
+
+    #[test]
+    fn classify_view_dropped() {
+        assert_eq!(
+            ReadError::classify(view_err(ReadySetError::ReaderNotFound)),
+            ReadError::ViewDropped
+        );
+        assert_eq!(
+            ReadError::classify(view_err(ReadySetError::ViewDestroyed)),
+            ReadError::ViewDropped
+        );
+    }
+
+    #[test]
+    fn classify_shard_unreachable() {
+        let err = view_err(ReadySetError::ReaderShardUnreachable {
+            shard: 3,
+            source: Box::new(rpc_err_no_downcast(
+                "read",
+                ReadySetError::TransportRecvFailed,
+            )),
+        });
+        assert_eq!(
+            ReadError::classify(err),
+            ReadError::ShardUnreachable { shard: 3 }
+        );
+    }
+
+    #[test]
+    fn classify_timeout() {
+        assert_eq!(
+            ReadError::classify(view_err(ReadySetError::UpqueryTimeout)),
+            ReadError::Timeout
+        );
+    }
+
+    #[test]
+    fn classify_other() {
+        assert_eq!(
+            ReadError::classify(ReadySetError::EmptyKey),
+            ReadError::Other(ReadySetError::EmptyKey)
+        );
+    }
+}
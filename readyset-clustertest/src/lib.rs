@@ -331,6 +331,12 @@ pub struct ServerParams {
     /// Only allow domains containing readers to run on this server. Corresponds to the
     /// `--reader-only` flag to the readyset server binary
     reader_only: bool,
+    /// The maximum number of domain shards to schedule onto this server. Corresponds to the
+    /// `--max-domains` flag to the readyset server binary
+    max_domains: Option<usize>,
+    /// The NUMA node this server runs on. Corresponds to the `--numa-node` flag to the readyset
+    /// server binary
+    numa_node: Option<usize>,
 }
 
 impl ServerParams {
@@ -351,6 +357,18 @@ impl ServerParams {
         self.reader_only = true;
         self
     }
+
+    /// Configure the maximum number of domain shards to schedule onto this server
+    pub fn max_domains(mut self, max_domains: usize) -> Self {
+        self.max_domains = Some(max_domains);
+        self
+    }
+
+    /// Configure the NUMA node this server runs on
+    pub fn numa_node(mut self, numa_node: usize) -> Self {
+        self.numa_node = Some(numa_node);
+        self
+    }
 }
 
 #[must_use]
@@ -379,6 +397,8 @@ pub struct ServerStartParams {
     wait_for_failpoint: bool,
     /// Whether to allow full materialization nodes or not
     allow_full_materialization: bool,
+    /// The strategy used to schedule domains onto servers, passed via `--placement-strategy`
+    placement_strategy: Option<String>,
 }
 
 /// Set of parameters defining an entire cluster's topology.
@@ -455,6 +475,8 @@ pub struct DeploymentBuilder {
     prometheus_metrics: bool,
     /// How to execute the adapter and server processes.
     deployment_mode: DeploymentMode,
+    /// The strategy used to schedule domains onto servers, if not the default
+    placement_strategy: Option<String>,
 }
 
 pub enum FailpointDestination {
@@ -529,6 +551,7 @@ impl DeploymentBuilder {
             enable_experimental_placeholder_inlining: false,
             allow_full_materialization: false,
             prometheus_metrics: true,
+            placement_strategy: None,
         }
     }
     /// The number of shards in the graph, `shards` <= 1 disables sharding.
@@ -685,6 +708,13 @@ impl DeploymentBuilder {
         self
     }
 
+    /// Sets the strategy used to schedule domains onto servers, as accepted by the
+    /// `--placement-strategy` flag to the readyset server binary (eg `numa-aware`)
+    pub fn placement_strategy(mut self, placement_strategy: &str) -> Self {
+        self.placement_strategy = Some(placement_strategy.to_owned());
+        self
+    }
+
     /// Sets whether or not to automatically create inlined caches for queries with unsupported
     /// placeholders
     pub fn enable_experimental_placeholder_inlining(mut self) -> Self {
@@ -750,6 +780,7 @@ impl DeploymentBuilder {
             auto_restart: self.auto_restart,
             wait_for_failpoint,
             allow_full_materialization: self.allow_full_materialization,
+            placement_strategy: self.placement_strategy.clone(),
         }
     }
 
@@ -1577,6 +1608,15 @@ async fn start_server(
     if server_params.no_readers {
         builder = builder.no_readers();
     }
+    if let Some(max_domains) = server_params.max_domains {
+        builder = builder.max_domains(max_domains);
+    }
+    if let Some(numa_node) = server_params.numa_node {
+        builder = builder.numa_node(numa_node);
+    }
+    if let Some(placement_strategy) = &server_start_params.placement_strategy {
+        builder = builder.placement_strategy(placement_strategy);
+    }
     if let Some(upstream_addr) = upstream_addr {
         builder = builder.upstream_addr(upstream_addr);
     }
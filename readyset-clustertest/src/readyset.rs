@@ -1,8 +1,11 @@
+use std::collections::HashMap;
+
 use ::readyset_client::metrics::{recorded, DumpedMetricValue};
 use ::readyset_client::recipe::changelist::ChangeList;
 use ::readyset_client::{failpoints, get_metric};
 use database_utils::QueryableConnection;
 use readyset_data::{DfValue, Dialect};
+use readyset_errors::ReadySetError;
 use readyset_util::eventually;
 use rust_decimal::prelude::FromPrimitive;
 use rust_decimal::Decimal;
@@ -143,6 +146,199 @@ async fn get_metric(
     get_metric!(metrics, name)
 }
 
+// Validate that once every worker is running its maximum number of domains, migrations which need
+// to place a new domain fail rather than leaving the domain unscheduled
+#[clustertest]
+async fn worker_max_domains() {
+    let mut deployment = DeploymentBuilder::new(DatabaseType::MySQL, "ct_worker_max_domains")
+        .with_servers(2, ServerParams::default().max_domains(2))
+        .start()
+        .await
+        .unwrap();
+
+    // Each base table runs in its own domain, so the first 4 tables fill up both workers
+    for i in 1..=4 {
+        deployment
+            .leader_handle()
+            .extend_recipe(
+                ChangeList::from_str(
+                    format!("CREATE TABLE t{i} (id INT PRIMARY KEY);"),
+                    Dialect::DEFAULT_MYSQL,
+                )
+                .unwrap(),
+            )
+            .await
+            .unwrap();
+    }
+
+    let info = deployment.leader_handle().get_info().await.unwrap();
+    assert_eq!(info.len(), 2);
+    for domains in info.values() {
+        assert_eq!(domains.len(), 2);
+    }
+
+    let err = deployment
+        .leader_handle()
+        .extend_recipe(
+            ChangeList::from_str(
+                "CREATE TABLE t5 (id INT PRIMARY KEY);",
+                Dialect::DEFAULT_MYSQL,
+            )
+            .unwrap(),
+        )
+        .await
+        .unwrap_err();
+    assert!(
+        err.any_cause(|e| matches!(e, ReadySetError::WorkerCapacityExceeded { .. })),
+        "{err}"
+    );
+
+    deployment.teardown().await.unwrap();
+}
+
+// Validate that room on a reader-only worker doesn't count as room for a base table domain, which
+// can't run there
+#[clustertest]
+async fn worker_max_domains_ignores_reader_only_workers() {
+    let mut deployment = DeploymentBuilder::new(
+        DatabaseType::MySQL,
+        "ct_worker_max_domains_ignores_reader_only_workers",
+    )
+    .with_servers(1, ServerParams::default().max_domains(1))
+    .with_servers(1, ServerParams::default().reader_only())
+    .start()
+    .await
+    .unwrap();
+
+    deployment
+        .leader_handle()
+        .extend_recipe(
+            ChangeList::from_str(
+                "CREATE TABLE t1 (id INT PRIMARY KEY);",
+                Dialect::DEFAULT_MYSQL,
+            )
+            .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    let err = deployment
+        .leader_handle()
+        .extend_recipe(
+            ChangeList::from_str(
+                "CREATE TABLE t2 (id INT PRIMARY KEY);",
+                Dialect::DEFAULT_MYSQL,
+            )
+            .unwrap(),
+        )
+        .await
+        .unwrap_err();
+    assert!(
+        err.any_cause(|e| matches!(e, ReadySetError::WorkerCapacityExceeded { .. })),
+        "{err}"
+    );
+
+    deployment.teardown().await.unwrap();
+}
+
+// Validate that with the NUMA-aware placement strategy, base tables feeding the same join are
+// scheduled onto workers on the same NUMA node
+#[clustertest]
+async fn numa_aware_placement() {
+    let mut deployment = DeploymentBuilder::new(DatabaseType::MySQL, "ct_numa_aware_placement")
+        .with_servers(2, ServerParams::default().numa_node(0))
+        .with_servers(2, ServerParams::default().numa_node(1))
+        .placement_strategy("numa-aware")
+        .start()
+        .await
+        .unwrap();
+
+    deployment
+        .leader_handle()
+        .extend_recipe(
+            ChangeList::from_str(
+                "
+        CREATE TABLE t1 (id INT PRIMARY KEY, val INT);
+        CREATE TABLE t2 (id INT PRIMARY KEY, val INT);
+        CREATE CACHE q FROM SELECT t1.val, t2.val FROM t1 JOIN t2 ON t1.id = t2.id;",
+                Dialect::DEFAULT_MYSQL,
+            )
+            .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    let tables = deployment.leader_handle().tables().await.unwrap();
+    let info = deployment.leader_handle().get_info().await.unwrap();
+    let mut numa_node_of_table = |table: &str| {
+        let node = tables
+            .iter()
+            .find(|(name, _)| name.name == table)
+            .map(|(_, node)| *node)
+            .unwrap();
+        let worker = info
+            .iter()
+            .find(|(_, domains)| domains.values().any(|nodes| nodes.contains(&node)))
+            .map(|(worker, _)| worker.clone())
+            .unwrap();
+        deployment.server_handles()[&worker].params.numa_node
+    };
+
+    // Both tables feed the same join, so they should be scheduled onto the same NUMA node
+    let t1_numa_node = numa_node_of_table("t1");
+    assert!(t1_numa_node.is_some());
+    assert_eq!(t1_numa_node, numa_node_of_table("t2"));
+
+    deployment.teardown().await.unwrap();
+}
+
+// Validate that with the NUMA-aware placement strategy, a connected dataflow graph is still spread
+// across the workers on every NUMA node, rather than all being scheduled onto one NUMA node
+#[clustertest]
+async fn numa_aware_placement_connected_graph() {
+    let mut deployment = DeploymentBuilder::new(
+        DatabaseType::MySQL,
+        "ct_numa_aware_placement_connected_graph",
+    )
+    .with_servers(2, ServerParams::default().numa_node(0))
+    .with_servers(2, ServerParams::default().numa_node(1))
+    .placement_strategy("numa-aware")
+    .start()
+    .await
+    .unwrap();
+
+    // Every table is joined with `hub`, so the whole graph is connected
+    let mut recipe = "CREATE TABLE hub (id INT PRIMARY KEY, val INT);".to_owned();
+    for i in 0..8 {
+        recipe.push_str(&format!(
+            "CREATE TABLE t{i} (id INT PRIMARY KEY, val INT);
+             CREATE CACHE q{i} FROM
+                 SELECT t{i}.val, hub.val FROM t{i} JOIN hub ON t{i}.id = hub.id;"
+        ));
+    }
+    deployment
+        .leader_handle()
+        .extend_recipe(ChangeList::from_str(recipe, Dialect::DEFAULT_MYSQL).unwrap())
+        .await
+        .unwrap();
+
+    let info = deployment.leader_handle().get_info().await.unwrap();
+    let domains_per_numa_node = info
+        .iter()
+        .fold(HashMap::new(), |mut acc, (worker, domains)| {
+            let numa_node = deployment.server_handles()[worker].params.numa_node;
+            *acc.entry(numa_node).or_insert(0) += domains.len();
+            acc
+        });
+    assert!(
+        domains_per_numa_node.get(&Some(0)).copied().unwrap_or(0) > 0
+            && domains_per_numa_node.get(&Some(1)).copied().unwrap_or(0) > 0,
+        "{domains_per_numa_node:?}"
+    );
+
+    deployment.teardown().await.unwrap();
+}
+
 // Validate that, on promotion of a follower to leader, its
 // `CONTROLLER_IS_LEADER` metric changes from 0 (not leader) to 1 (leader)
 #[clustertest]
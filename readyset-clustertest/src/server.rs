@@ -146,6 +146,18 @@ impl ReadysetServerBuilder {
         self.push_arg("--no-readers")
     }
 
+    pub fn max_domains(self, max_domains: usize) -> Self {
+        self.push_arg_kv("--max-domains", &max_domains.to_string())
+    }
+
+    pub fn numa_node(self, numa_node: usize) -> Self {
+        self.push_arg_kv("--numa-node", &numa_node.to_string())
+    }
+
+    pub fn placement_strategy(self, placement_strategy: &str) -> Self {
+        self.push_arg_kv("--placement-strategy", placement_strategy)
+    }
+
     pub fn authority_addr(self, authority_addr: &str) -> Self {
         self.push_arg_kv("--authority-address", authority_addr)
     }
@@ -1,16 +1,18 @@
 use std::borrow::Cow;
 use std::cmp::Ordering;
+use std::sync::atomic::{AtomicU64, Ordering as AtomicOrdering};
 use std::sync::Arc;
 
 use ahash::RandomState;
 use common::SizeOf;
-use dataflow_expression::{PostLookup, ReaderProcessing};
+use dataflow_expression::{BinaryOperator, Expr as DfExpr, PostLookup, ReaderProcessing};
 use nom_sql::Relation;
 use reader_map::{EvictionQuantity, EvictionStrategy};
 use readyset_client::consistency::Timestamp;
+use readyset_client::debug::stats::ReaderLookupStats;
 use readyset_client::results::SharedResults;
-use readyset_client::KeyComparison;
-use readyset_data::Bound;
+use readyset_client::{KeyComparison, RangeScanBatch, ScanBatch};
+use readyset_data::{Bound, BoundedRange};
 use vec1::Vec1;
 
 pub use self::multir::LookupError;
@@ -138,6 +140,7 @@ fn new_inner(
     };
 
     let (notifier, receiver) = tokio::sync::broadcast::channel(1);
+    let lookup_stats = Arc::new(LookupStats::new(cols));
     let partial = trigger.is_some();
     let w = WriteHandle {
         partial,
@@ -149,6 +152,7 @@ fn new_inner(
         mem_size: 0,
         notifier,
         eviction_epoch: 0,
+        lookup_stats: lookup_stats.clone(),
     };
 
     let r = SingleReadHandle {
@@ -158,6 +162,7 @@ fn new_inner(
         post_lookup: post_processing,
         receiver,
         eviction_epoch: 0,
+        lookup_stats,
     };
 
     (r, w)
@@ -166,6 +171,88 @@ fn new_inner(
 mod multir;
 mod multiw;
 
+/// Counts of the lookups performed against a reader, shared between all the [`SingleReadHandle`]s
+/// for the reader and its [`WriteHandle`]
+#[derive(Debug)]
+struct LookupStats {
+    lookups: AtomicU64,
+    misses: AtomicU64,
+    /// The number of lookups whose post-lookup filter compared each column for equality, indexed
+    /// by column
+    filter_columns: Box<[AtomicU64]>,
+}
+
+impl LookupStats {
+    fn new(cols: usize) -> Self {
+        Self {
+            lookups: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            filter_columns: (0..cols).map(|_| AtomicU64::new(0)).collect(),
+        }
+    }
+
+    fn record(&self, filter: Option<&DfExpr>, miss: bool) {
+        self.lookups.fetch_add(1, AtomicOrdering::Relaxed);
+        if miss {
+            self.misses.fetch_add(1, AtomicOrdering::Relaxed);
+        }
+        if let Some(filter) = filter {
+            let mut columns = vec![];
+            equality_filter_columns(filter, &mut columns);
+            columns.sort_unstable();
+            columns.dedup();
+            for column in columns {
+                if let Some(count) = self.filter_columns.get(column) {
+                    count.fetch_add(1, AtomicOrdering::Relaxed);
+                }
+            }
+        }
+    }
+
+    fn snapshot(&self) -> ReaderLookupStats {
+        ReaderLookupStats {
+            lookups: self.lookups.load(AtomicOrdering::Relaxed),
+            misses: self.misses.load(AtomicOrdering::Relaxed),
+            filter_columns: self
+                .filter_columns
+                .iter()
+                .map(|count| count.load(AtomicOrdering::Relaxed))
+                .collect(),
+        }
+    }
+}
+
+/// Push the index of every column that `filter` compares for equality with a value not depending
+/// on any column to `columns`, only looking at the top-level conjunction of `filter`. Only these
+/// columns could be added to the key of the reader to replace the filter with a lookup.
+fn equality_filter_columns(filter: &DfExpr, columns: &mut Vec<usize>) {
+    match filter {
+        DfExpr::Op {
+            op: BinaryOperator::And,
+            left,
+            right,
+            ..
+        } => {
+            equality_filter_columns(left, columns);
+            equality_filter_columns(right, columns);
+        }
+        DfExpr::Op {
+            op: BinaryOperator::Equal,
+            left,
+            right,
+            ..
+        } => match (&**left, &**right) {
+            (DfExpr::Column { index, .. }, value) | (value, DfExpr::Column { index, .. })
+                if value.referred_columns().is_empty() =>
+            {
+                columns.push(*index)
+            }
+            _ => {}
+        },
+        _ => {}
+    }
+}
+
 fn key_to_single(k: Key) -> Cow<DfValue> {
     assert_eq!(k.len(), 1);
     match k {
@@ -187,6 +274,8 @@ pub(crate) struct WriteHandle {
     notifier: ReaderUpdatedSender,
     /// How many eviction rounds this handle had
     eviction_epoch: usize,
+    /// Counts of the lookups performed against this reader, recorded by its read handles
+    lookup_stats: Arc<LookupStats>,
 }
 
 type Key<'a> = Cow<'a, [DfValue]>;
@@ -296,6 +385,11 @@ impl WriteHandle {
         self.handle.read().len()
     }
 
+    /// Returns the counts of the lookups performed against this reader so far
+    pub(crate) fn lookup_stats(&self) -> ReaderLookupStats {
+        self.lookup_stats.snapshot()
+    }
+
     /// Add a new set of records to the backlog.
     ///
     /// These will be made visible to readers after the next call to `swap()`.
@@ -470,6 +564,8 @@ pub struct SingleReadHandle {
     receiver: ReaderUpdatedNotifier,
     /// Caches the eviction epoch of the associated [`WriteHandle`]
     eviction_epoch: usize,
+    /// Counts of the lookups performed against this reader, shared with the [`WriteHandle`]
+    lookup_stats: Arc<LookupStats>,
 }
 
 impl Clone for SingleReadHandle {
@@ -481,6 +577,7 @@ impl Clone for SingleReadHandle {
             post_lookup: self.post_lookup.clone(),
             receiver: self.receiver.resubscribe(),
             eviction_epoch: self.eviction_epoch,
+            lookup_stats: self.lookup_stats.clone(),
         }
     }
 }
@@ -550,6 +647,89 @@ impl SingleReadHandle {
         }
     }
 
+    /// Scan the rows whose key falls within `bounds`, in ascending key order, returning at most
+    /// `limit` rows (rounded up to include all the rows for the last key). The rows for each key
+    /// have this reader's post-lookup operations applied, as if that key was looked up on its own.
+    ///
+    /// Returns `Ok(None)` if this reader's state cannot be iterated in key order, either because
+    /// it is not backed by a [`BTreeMap`](IndexType::BTreeMap) or because it is keyed on more than
+    /// one column.
+    pub fn range_scan(
+        &self,
+        bounds: &BoundedRange<DfValue>,
+        limit: Option<usize>,
+    ) -> Result<Option<RangeScanBatch>, LookupError<'static>> {
+        if self.index.index_type != IndexType::BTreeMap {
+            return Ok(None);
+        }
+
+        match self.handle.range_scan(bounds, limit, &self.post_lookup) {
+            Err(e) if e.is_miss() && self.trigger.is_none() => Ok(Some(RangeScanBatch::default())),
+            r => r,
+        }
+    }
+
+    /// Returns the rows for each of `keys` which is currently present in this reader, along with
+    /// its key, all under the same reader guard. Keys which aren't present are skipped, so that
+    /// keys listed by [`Self::keys`] and evicted before they could be read are omitted.
+    pub fn export_chunk(
+        &self,
+        keys: &[Vec<DfValue>],
+    ) -> ReadySetResult<Vec<(Vec<DfValue>, Vec<Vec<DfValue>>)>> {
+        let mut entries = self.handle.get_present(keys).map_err(|e| match e {
+            reader_map::Error::NotPublished => ReadySetError::ViewNotYetAvailable,
+            reader_map::Error::Destroyed => ReadySetError::ViewDestroyed,
+        })?;
+
+        if let Some(cols) = &self.post_lookup.returned_cols {
+            for row in entries.iter_mut().flat_map(|(_, rows)| rows) {
+                row.truncate(cols.len());
+            }
+        }
+
+        Ok(entries)
+    }
+
+    /// Returns the next batch of at most `batch_size` keys in this reader after `cursor` (the last
+    /// key returned by a previous batch), along with their rows, in ascending key order.
+    ///
+    /// Only keys which are currently materialized are returned, so if this reader is partial the
+    /// scan doesn't trigger any upqueries.
+    pub fn scan(&self, cursor: Option<&[DfValue]>, batch_size: usize) -> ReadySetResult<ScanBatch> {
+        let (mut entries, more) =
+            self.handle
+                .scan(cursor, batch_size.max(1))
+                .map_err(|e| match e {
+                    reader_map::Error::NotPublished => ReadySetError::ViewNotYetAvailable,
+                    reader_map::Error::Destroyed => ReadySetError::ViewDestroyed,
+                })?;
+
+        if let Some(cols) = &self.post_lookup.returned_cols {
+            for row in entries.iter_mut().flat_map(|(_, rows)| rows) {
+                row.truncate(cols.len());
+            }
+        }
+
+        Ok(ScanBatch {
+            entries,
+            more,
+            partial: self.is_partial(),
+        })
+    }
+
+    /// Record a lookup against this reader which either hit or (if `miss` is true) missed, with
+    /// the given post-lookup filter, so that it can be reported by
+    /// [`DomainRequest::RequestReaderLookupStats`](crate::DomainRequest::RequestReaderLookupStats)
+    pub fn record_lookup(&self, filter: Option<&DfExpr>, miss: bool) {
+        self.lookup_stats.record(filter, miss)
+    }
+
+    /// Returns true if this reader is partially materialized, in which case it only contains the
+    /// keys which have been filled by upqueries and not since evicted
+    pub fn is_partial(&self) -> bool {
+        self.trigger.is_some()
+    }
+
     pub fn len(&self) -> usize {
         self.handle.len()
     }
@@ -585,8 +765,9 @@ impl SingleReadHandle {
 #[cfg(test)]
 #[allow(clippy::panic)]
 mod tests {
+    use nom_sql::OrderType;
     use readyset_client::results::SharedRows;
-    use readyset_data::Bound;
+    use readyset_data::{Bound, DfType};
 
     use super::*;
 
@@ -867,4 +1048,95 @@ mod tests {
             assert!(r.get_multi(range_key).err().unwrap().is_miss());
         }
     }
+
+    #[test]
+    fn range_scan_applies_post_lookup() {
+        let (r, mut w) = new(
+            3,
+            Index::btree_map(vec![0]),
+            ReaderProcessing::new(
+                Some(vec![(1, OrderType::OrderDescending)]),
+                Some(1),
+                Some(vec![0, 1]),
+                None,
+                None,
+            )
+            .unwrap(),
+        );
+        w.add(vec![
+            Record::Positive(vec![1.into(), 1.into(), "a".into()]),
+            Record::Positive(vec![1.into(), 2.into(), "b".into()]),
+            Record::Positive(vec![2.into(), 3.into(), "c".into()]),
+        ]);
+        w.swap();
+
+        let batch = r
+            .range_scan(&(Bound::Unbounded, Bound::Unbounded), None)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            batch.rows,
+            vec![
+                (1.into(), vec![1.into(), 2.into()]),
+                (2.into(), vec![2.into(), 3.into()]),
+            ]
+        );
+    }
+
+    #[test]
+    fn lookup_stats_only_count_equality_conjuncts() {
+        let column = |index| {
+            Box::new(DfExpr::Column {
+                index,
+                ty: DfType::Int,
+            })
+        };
+        let literal = || {
+            Box::new(DfExpr::Literal {
+                val: 1.into(),
+                ty: DfType::Int,
+            })
+        };
+        let op = |left, op, right| {
+            Box::new(DfExpr::Op {
+                left,
+                op,
+                right,
+                ty: DfType::Bool,
+            })
+        };
+
+        let (r, w) = new(4, Index::hash_map(vec![0]), ReaderProcessing::default());
+        // c1 = 1 AND (c2 = 1 OR c3 = 1)
+        r.record_lookup(
+            Some(&op(
+                op(column(1), BinaryOperator::Equal, literal()),
+                BinaryOperator::And,
+                op(
+                    op(column(2), BinaryOperator::Equal, literal()),
+                    BinaryOperator::Or,
+                    op(column(3), BinaryOperator::Equal, literal()),
+                ),
+            )),
+            false,
+        );
+        // 1 = c1 AND c2 > 1 AND c3 = c2
+        r.record_lookup(
+            Some(&op(
+                op(
+                    op(literal(), BinaryOperator::Equal, column(1)),
+                    BinaryOperator::And,
+                    op(column(2), BinaryOperator::Greater, literal()),
+                ),
+                BinaryOperator::And,
+                op(column(3), BinaryOperator::Equal, column(2)),
+            )),
+            true,
+        );
+
+        let stats = w.lookup_stats();
+        assert_eq!(stats.lookups, 2);
+        assert_eq!(stats.misses, 1);
+        assert_eq!(stats.filter_columns, vec![0, 2, 0, 0]);
+    }
 }
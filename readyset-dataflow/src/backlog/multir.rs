@@ -1,16 +1,18 @@
-use std::borrow::Cow;
-use std::collections::HashSet;
+use std::borrow::{Borrow, Cow};
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashSet};
 use std::convert::TryInto;
 
 use ahash::RandomState;
 use common::DfValue;
-use dataflow_expression::PreInsertion;
+use dataflow_expression::{PostLookup, PreInsertion};
 use reader_map::refs::Miss;
 use readyset_client::consistency::Timestamp;
-use readyset_client::results::{SharedResults, SharedRows};
-use readyset_client::KeyComparison;
+use readyset_client::results::{ResultIterator, SharedResults, SharedRows};
+use readyset_client::{KeyComparison, RangeScanBatch};
 use readyset_errors::ReadySetError;
-use readyset_util::ranges::RangeBounds;
+use readyset_util::intervals::cmp_start_end;
+use readyset_util::ranges::{BoundedRange, RangeBounds};
 use serde::{Deserialize, Serialize};
 use tracing::warn;
 use vec1::{vec1, Vec1};
@@ -109,6 +111,96 @@ impl Handle {
         }
     }
 
+    /// Returns the rows for each of `keys` which are present in the map, along with its key, all
+    /// under the same read guard. Keys which aren't present (for example because they were evicted
+    /// after being listed) are skipped.
+    pub(super) fn get_present(
+        &self,
+        keys: &[Vec<DfValue>],
+    ) -> reader_map::Result<Vec<(Vec<DfValue>, Vec<Vec<DfValue>>)>> {
+        let to_vecs = |rows: &SharedRows| -> Vec<Vec<DfValue>> {
+            rows.iter().map(|row| row.to_vec()).collect()
+        };
+        match self {
+            Handle::Single(h) => {
+                let map = h.enter()?;
+                Ok(keys
+                    .iter()
+                    .filter_map(|key| {
+                        map.get(&key[0])
+                            .map(|rows| (key.clone(), to_vecs(rows.as_ref())))
+                    })
+                    .collect())
+            }
+            Handle::Many(h) => {
+                let map = h.enter()?;
+                Ok(keys
+                    .iter()
+                    .filter_map(|key| {
+                        map.get(key.as_slice())
+                            .map(|rows| (key.clone(), to_vecs(rows.as_ref())))
+                    })
+                    .collect())
+            }
+        }
+    }
+
+    /// Returns the (at most) `batch_size` smallest keys in the map which are greater than `after`,
+    /// along with their rows, all under the same read guard. Also returns whether there are any
+    /// more keys greater than the last key returned.
+    ///
+    /// Keys are compared by value rather than by their position in the map, so that scanning a map
+    /// by repeatedly passing the last key returned as `after` sees every key which is present for
+    /// the whole scan exactly once, regardless of what else is written to the map in between.
+    ///
+    /// Maps backed by a BTreeMap are already ordered, so each batch only visits the keys it
+    /// returns. Maps backed by a HashMap have no order, so every batch has to visit every key
+    /// in the map to find the smallest ones.
+    pub(super) fn scan(
+        &self,
+        after: Option<&[DfValue]>,
+        batch_size: usize,
+    ) -> reader_map::Result<(Vec<(Vec<DfValue>, Vec<Vec<DfValue>>)>, bool)> {
+        let to_vecs = |rows: &SharedRows| -> Vec<Vec<DfValue>> {
+            rows.iter().map(|row| row.to_vec()).collect()
+        };
+        match self {
+            Handle::Single(h) => {
+                let map = h.enter()?;
+                let after = after.and_then(|key| key.first());
+                let (keys, more) = match map.keys_after(after) {
+                    Some(keys) => first_keys(keys, batch_size),
+                    None => smallest_keys_after(map.keys(), after, batch_size),
+                };
+                Ok((
+                    keys.into_iter()
+                        .filter_map(|key| {
+                            map.get(key)
+                                .map(|rows| (vec![key.clone()], to_vecs(rows.as_ref())))
+                        })
+                        .collect(),
+                    more,
+                ))
+            }
+            Handle::Many(h) => {
+                let map = h.enter()?;
+                let (keys, more) = match map.keys_after(after) {
+                    Some(keys) => first_keys(keys, batch_size),
+                    None => smallest_keys_after(map.keys(), after, batch_size),
+                };
+                Ok((
+                    keys.into_iter()
+                        .filter_map(|key| {
+                            map.get(key.as_slice())
+                                .map(|rows| (key.clone(), to_vecs(rows.as_ref())))
+                        })
+                        .collect(),
+                    more,
+                ))
+            }
+        }
+    }
+
     fn get_multi_single_handle<'a, T, F: Fn() -> T>(
         handle: &HandleSingle,
         keys: &'a [KeyComparison],
@@ -270,6 +362,70 @@ impl Handle {
         }
     }
 
+    /// Scan the rows whose key falls within `bounds`, in ascending key order, stopping after the
+    /// first key at which at least `limit` rows have been collected.
+    ///
+    /// The rows for each key are processed with `post_lookup`, so that they're the same as the
+    /// rows a lookup of just that key would return.
+    ///
+    /// Returns `Ok(None)` if this handle is keyed on more than one column, since only
+    /// single-column keys can be bounded by a single value.
+    pub(super) fn range_scan(
+        &self,
+        bounds: &BoundedRange<DfValue>,
+        limit: Option<usize>,
+        post_lookup: &PostLookup,
+    ) -> Result<Option<RangeScanBatch>, LookupError<'static>> {
+        let h = match self {
+            Handle::Single(h) => h,
+            Handle::Many(_) => return Ok(None),
+        };
+
+        if cmp_start_end(bounds.0.as_ref().into(), bounds.1.as_ref().into()) == Ordering::Greater {
+            warn!("Reader received range scan with start bound above end bound; returning empty result set");
+            return Ok(Some(RangeScanBatch::default()));
+        }
+
+        let map = h.enter()?;
+        let range = map
+            .range(&(bounds.0.as_ref(), bounds.1.as_ref()))
+            .map_err(|Miss(miss)| {
+                LookupError::Miss((
+                    miss.into_iter()
+                        .map(|(start, end)| {
+                            Cow::Owned(KeyComparison::Range((
+                                start.map(|s| vec1![s]),
+                                end.map(|e| vec1![e]),
+                            )))
+                        })
+                        .collect(),
+                    (),
+                ))
+            })?;
+
+        let mut batch = RangeScanBatch::default();
+        for (key, rows) in range {
+            if limit.map_or(false, |limit| {
+                !batch.rows.is_empty() && batch.rows.len() >= limit
+            }) {
+                batch.continuation = batch.rows.last().map(|(k, _)| k.clone());
+                break;
+            }
+            let rows = ResultIterator::new(
+                std::iter::once(rows.as_ref().clone()).collect(),
+                post_lookup,
+                None,
+                None,
+                None,
+            );
+            batch
+                .rows
+                .extend(rows.into_vec().into_iter().map(|row| (key.clone(), row)));
+        }
+
+        Ok(Some(batch))
+    }
+
     /// Returns Ok(true) if this handle contains the given key, Ok(false) if it doesn't, or an error
     /// if the underlying reader map is not able to accept reads
     ///
@@ -351,10 +507,50 @@ impl Handle {
     }
 }
 
+/// Returns the first (at most) `n` of `keys`, which must already be in ascending order, along with
+/// whether there were any more keys.
+fn first_keys<'a, K, I>(keys: I, n: usize) -> (Vec<&'a K>, bool)
+where
+    K: 'a,
+    I: Iterator<Item = &'a K>,
+{
+    let mut keys = keys.take(n + 1).collect::<Vec<_>>();
+    let more = keys.len() > n;
+    keys.truncate(n);
+    (keys, more)
+}
+
+/// Returns the (at most) `n` smallest of `keys` which are greater than `after`, in ascending
+/// order, along with whether any other keys greater than `after` were skipped.
+///
+/// This only ever holds `n + 1` keys at a time, so that batches of a scan over a large map can be
+/// selected without sorting the whole map.
+fn smallest_keys_after<'a, K, Q, I>(keys: I, after: Option<&Q>, n: usize) -> (Vec<&'a K>, bool)
+where
+    K: Borrow<Q> + Ord + 'a,
+    Q: Ord + ?Sized,
+    I: Iterator<Item = &'a K>,
+{
+    let mut heap = BinaryHeap::with_capacity(n + 1);
+    let mut more = false;
+    for key in keys {
+        if after.map_or(false, |after| Borrow::<Q>::borrow(key) <= after) {
+            continue;
+        }
+        heap.push(key);
+        if heap.len() > n {
+            heap.pop();
+            more = true;
+        }
+    }
+    (heap.into_sorted_vec(), more)
+}
+
 #[cfg(test)]
 mod tests {
     use proptest::prelude::*;
     use reader_map::handles::WriteHandle;
+    use readyset_client::internal::IndexType;
     use readyset_data::Bound;
 
     use super::*;
@@ -387,6 +583,92 @@ mod tests {
         (w, Handle::Many(r))
     }
 
+    #[test]
+    fn scan_single() {
+        let (mut w, handle) = make_single();
+        for n in (0i32..25).rev() {
+            w.insert(n.into(), vec![n.into()].into_boxed_slice());
+        }
+        w.publish();
+
+        let mut keys = vec![];
+        let mut after = None;
+        loop {
+            let (batch, more) = handle.scan(after.as_deref(), 10).unwrap();
+            assert!(batch.len() <= 10);
+            for (key, rows) in &batch {
+                assert_eq!(rows, &vec![key.clone()]);
+            }
+            keys.extend(batch.into_iter().map(|(key, _)| key));
+            if !more {
+                break;
+            }
+            after = keys.last().cloned();
+
+            // Keys written behind the cursor mid-scan are skipped, without disturbing the scan
+            w.insert((-1i32).into(), vec![(-1i32).into()].into_boxed_slice());
+            w.publish();
+        }
+
+        assert_eq!(keys, (0i32..25).map(|n| vec![n.into()]).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn scan_single_hash_map() {
+        let (mut w, r) = reader_map::Options::default()
+            .with_meta(-1)
+            .with_timestamp(Timestamp::default())
+            .with_hasher(RandomState::default())
+            .with_insertion_order(None)
+            .with_index_type(IndexType::HashMap)
+            .construct();
+        let handle = Handle::Single(r);
+        for n in (0i32..25).rev() {
+            w.insert(n.into(), vec![n.into()].into_boxed_slice());
+        }
+        w.publish();
+
+        let mut keys = vec![];
+        let mut after = None;
+        loop {
+            let (batch, more) = handle.scan(after.as_deref(), 10).unwrap();
+            keys.extend(batch.into_iter().map(|(key, _)| key));
+            if !more {
+                break;
+            }
+            after = keys.last().cloned();
+        }
+
+        assert_eq!(keys, (0i32..25).map(|n| vec![n.into()]).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn scan_many() {
+        let (mut w, handle) = make_many();
+        for n in 0i32..5 {
+            w.insert(vec![n.into(), n.into()], vec![n.into()].into_boxed_slice());
+        }
+        w.publish();
+
+        let (batch, more) = handle
+            .scan(Some(&[DfValue::from(1i32), DfValue::from(1i32)]), 2)
+            .unwrap();
+        assert!(more);
+        assert_eq!(
+            batch,
+            vec![
+                (vec![2i32.into(), 2i32.into()], vec![vec![2i32.into()]]),
+                (vec![3i32.into(), 3i32.into()], vec![vec![3i32.into()]]),
+            ]
+        );
+
+        let (batch, more) = handle
+            .scan(Some(&[DfValue::from(3i32), DfValue::from(3i32)]), 2)
+            .unwrap();
+        assert!(!more);
+        assert_eq!(batch.len(), 1);
+    }
+
     proptest! {
         #[test]
         fn get_double(key: [DfValue; 2], val: Box<[DfValue]>) {
@@ -1,6 +1,9 @@
 pub(crate) mod channel;
 mod domain_metrics;
 mod replay_paths;
+mod replay_scheduler;
+pub(crate) mod tuning;
+pub(crate) mod write_tracing;
 
 use std::borrow::Cow;
 use std::collections::{HashMap, HashSet, VecDeque};
@@ -18,7 +21,7 @@ use dataflow_state::{
     BaseTableState, EvictBytesResult, EvictKeysResult, EvictRandomResult, MaterializedNodeState,
     PointKey, RangeKey, RangeLookupResult,
 };
-use failpoint_macros::failpoint;
+use failpoint_macros::{failpoint, set_failpoint};
 use futures_util::future::FutureExt;
 use futures_util::stream::StreamExt;
 use futures_util::TryFutureExt;
@@ -29,6 +32,9 @@ use nom_sql::Relation;
 use petgraph::graph::NodeIndex;
 use readyset_alloc::StdThreadBuildWrapper;
 use readyset_client::debug::info::KeyCount;
+use readyset_client::debug::stats::SlowOperation;
+#[cfg(feature = "failure_injection")]
+use readyset_client::failpoints;
 use readyset_client::internal::{self, Index};
 use readyset_client::metrics::recorded;
 use readyset_client::{KeyComparison, PersistencePoint, ReaderAddress};
@@ -48,12 +54,15 @@ use vec1::Vec1;
 
 pub(crate) use self::replay_paths::ReplayPath;
 use self::replay_paths::{Destination, ReplayPathSpec, ReplayPaths, Target};
+use self::replay_scheduler::ReplayScheduler;
+use self::write_tracing::WriteTracer;
 use crate::domain::channel::{ChannelCoordinator, DomainReceiver, DomainSender};
+use crate::domain::tuning::DomainTuning;
 use crate::node::special::EgressTx;
 use crate::node::{NodeProcessingResult, ProcessEnv};
 use crate::payload::{
-    EvictRequest, MaterializedState, PacketDiscriminants, PrepareStateKind, PrettyReplayPath,
-    ReplayPieceContext, SourceSelection,
+    BarrierState, EvictRequest, MaterializedState, PacketDiscriminants, PrepareStateKind,
+    PrettyReplayPath, ReplayPieceContext, SourceSelection,
 };
 use crate::prelude::*;
 use crate::processing::ColumnMiss;
@@ -82,9 +91,32 @@ pub struct Config {
     /// Whether to emit verbose metrics for the domain.
     #[serde(default)]
     pub verbose_metrics: bool,
+
+    /// If processing a single packet at a single node takes longer than this, the domain records
+    /// it as a [`SlowOperation`] and logs a warning.
+    #[serde(default = "default_slow_node_threshold")]
+    pub slow_node_threshold: time::Duration,
+
+    /// Tunable parameters for how the domain processes replays and records slow operations
+    #[serde(default)]
+    pub tuning: DomainTuning,
+
+    /// The number of writes out of every million received by base tables which are tagged with a
+    /// trace, so that the latency of their propagation to readers is recorded. 0 disables write
+    /// tracing.
+    #[serde(default)]
+    pub write_tracing_samples_per_million: u32,
+
+    /// Whether to count the records emitted by each node as the result of writes to each base
+    /// table, to report write amplification in the graph statistics. This has a cost for every
+    /// node a write passes through, so it's disabled by default.
+    #[serde(default)]
+    pub count_emitted_records: bool,
 }
 
-const BATCH_SIZE: usize = 256;
+fn default_slow_node_threshold() -> time::Duration {
+    time::Duration::from_secs(1)
+}
 
 #[derive(Debug)]
 enum DomainMode {
@@ -480,6 +512,8 @@ impl DomainBuilder {
 
             delayed_for_self: Default::default(),
 
+            barriers: Default::default(),
+
             state_size,
             total_time: Timer::new(),
             total_ptime: Timer::new(),
@@ -493,6 +527,12 @@ impl DomainBuilder {
             aggressively_update_state_sizes: self.config.aggressively_update_state_sizes,
 
             metrics: domain_metrics::DomainMetrics::new(self.config.verbose_metrics),
+            slow_node_threshold: self.config.slow_node_threshold,
+            slow_operations: VecDeque::with_capacity(self.config.tuning.max_slow_operations()),
+            replay_scheduler: ReplayScheduler::new(self.config.tuning.migration_replay_min_share()),
+            tuning: self.config.tuning,
+            write_tracer: WriteTracer::new(self.config.write_tracing_samples_per_million),
+            emitted_records: self.config.count_emitted_records.then(Default::default),
 
             eviction_kind: self.config.eviction_kind,
             remapped_keys: Default::default(),
@@ -688,6 +728,10 @@ pub struct Domain {
 
     delayed_for_self: VecDeque<Packet>,
 
+    /// State of all the barriers this domain has seen, keyed by barrier id. See
+    /// [`DomainRequest::Barrier`]
+    barriers: HashMap<u64, BarrierState>,
+
     state_size: Arc<AtomicUsize>,
     total_time: Timer<SimpleTracker, RealTime>,
     total_ptime: Timer<SimpleTracker, ThreadTime>,
@@ -709,6 +753,27 @@ pub struct Domain {
     metrics: domain_metrics::DomainMetrics,
     eviction_kind: crate::EvictionKind,
 
+    /// See [`Config::slow_node_threshold`]
+    slow_node_threshold: time::Duration,
+    /// The most recent packets which took longer than `slow_node_threshold` to process at a single
+    /// node, oldest first. Bounded to [`DomainTuning::max_slow_operations`].
+    slow_operations: VecDeque<SlowOperation>,
+
+    /// See [`Config::tuning`]
+    tuning: DomainTuning,
+
+    /// Low-priority full replay batches deferred behind foreground work
+    replay_scheduler: ReplayScheduler,
+
+    /// Samples writes received by base tables to trace their propagation to readers. See
+    /// [`Config::write_tracing_samples_per_million`]
+    write_tracer: WriteTracer,
+
+    /// The number of records emitted by each base table, internal node, and reader in this domain
+    /// as the result of regular updates, by the global index of the base table whose write they
+    /// were derived from, if [`Config::count_emitted_records`] is set
+    emitted_records: Option<NodeMap<HashMap<NodeIndex, u64>>>,
+
     /// This channel is used to notify the replica that a base node has its persistent state
     /// initialized.
     /// This allow us to asynchronously run that process, and avoid any bottlenecks on the
@@ -1220,8 +1285,12 @@ impl Domain {
         let (mut m, evictions) = {
             #[allow(clippy::indexing_slicing)] // we checked the node exists already
             let mut n = self.nodes[me].borrow_mut();
+            let packet_kind: &'static str = PacketDiscriminants::from(&m).into();
+            let batch_size = m.batch_size();
+            let start = time::Instant::now();
             self.process_times.start(me);
             self.process_ptimes.start(me);
+            set_failpoint!(failpoints::DOMAIN_PROCESS_PACKET);
             let mut m = Some(m);
             let NodeProcessingResult {
                 misses, captured, ..
@@ -1244,6 +1313,47 @@ impl Domain {
             self.process_ptimes.stop();
             self.process_times.stop();
 
+            if let Some(emitted_records) = &mut self.emitted_records {
+                if n.is_base() || n.is_internal() || n.is_reader() {
+                    if let Some(Packet::Message {
+                        origin: Some(origin),
+                        data,
+                        ..
+                    }) = &m
+                    {
+                        if !data.is_empty() {
+                            *emitted_records
+                                .entry(me)
+                                .or_default()
+                                .entry(*origin)
+                                .or_default() += data.len() as u64;
+                        }
+                    }
+                }
+            }
+
+            let duration = start.elapsed();
+            if duration > self.slow_node_threshold {
+                warn!(
+                    node = %n.global_addr().index(),
+                    packet_kind,
+                    ?duration,
+                    batch_size,
+                    "Slow operation processing packet at node"
+                );
+                if self.slow_operations.len() >= self.tuning.max_slow_operations() {
+                    self.slow_operations.pop_front();
+                }
+                self.slow_operations.push_back(SlowOperation {
+                    domain: self.address(),
+                    node: n.global_addr(),
+                    node_name: None,
+                    packet_kind: packet_kind.to_owned(),
+                    duration,
+                    batch_size,
+                });
+            }
+
             if m.is_none() {
                 // no need to deal with our children if we're not sending them anything
                 return Ok(());
@@ -1440,6 +1550,43 @@ impl Domain {
         Ok(())
     }
 
+    /// Send a [`Packet::Barrier`] with the given id to every domain replica that this domain sends
+    /// updates to, and record the barriers we sent in the state for that barrier. Does nothing if
+    /// we've already sent the barrier with this id.
+    ///
+    /// Since we've already handled every packet we received before this point, and packets to each
+    /// domain are sent in order, the barriers will only be received after all the updates we've
+    /// sent so far.
+    fn send_barrier(&mut self, id: u64, executor: &mut dyn Executor) {
+        if self.barriers.get(&id).map_or(false, |state| state.started) {
+            return;
+        }
+
+        let destinations = self
+            .nodes
+            .values()
+            .flat_map(|n| {
+                let n = n.borrow();
+                if n.is_dropped() {
+                    vec![]
+                } else if let Some(egress) = n.as_egress() {
+                    egress.destinations(self.replica)
+                } else if let Some(sharder) = n.as_sharder() {
+                    sharder.destinations(self.replica)
+                } else {
+                    vec![]
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let state = self.barriers.entry(id).or_default();
+        state.started = true;
+        for dest in destinations {
+            *state.sent.entry(dest).or_default() += 1;
+            executor.send(dest, Packet::Barrier { id });
+        }
+    }
+
     pub fn domain_request(
         &mut self,
         req: DomainRequest,
@@ -1478,6 +1625,9 @@ impl Domain {
                     };
                     self.auxiliary_node_states.remove(node);
                     self.reader_write_handles.remove(node);
+                    if let Some(emitted_records) = &mut self.emitted_records {
+                        emitted_records.remove(node);
+                    }
                     trace!(local = node.id(), "node removed");
                 }
 
@@ -2025,6 +2175,7 @@ impl Domain {
                         // it's backed by an *estimate* of the number of keys in the state
                         last: is_empty,
                         replicas: replicas.clone(),
+                        low_priority: false,
                     },
                     data: Vec::<Record>::new().into(),
                     cache_name: MIGRATION_CACHE_NAME_STUB.into(),
@@ -2059,6 +2210,7 @@ impl Domain {
                 let replay_tx_desc = self.channel_coordinator.builder_for(&self.address())?;
 
                 let address = self.address();
+                let replay_batch_size = self.tuning.replay_batch_size();
                 thread::Builder::new()
                     .name(format!("replay{}.{}", self.index(), link.src))
                     .spawn_wrapper(move || {
@@ -2079,7 +2231,7 @@ impl Domain {
                         debug!(node = %link.dst, "starting state chunker");
 
                         let mut guard = all_records.read();
-                        let iter = guard.iter().chunks(BATCH_SIZE);
+                        let iter = guard.iter().chunks(replay_batch_size);
                         let mut iter = iter
                             .into_iter()
                             .map(|chunk| Records::from_iter(chunk.map(&fix)))
@@ -2099,6 +2251,7 @@ impl Domain {
                                 context: ReplayPieceContext::Full {
                                     last,
                                     replicas: replicas.clone(),
+                                    low_priority: true,
                                 },
                                 data: chunk,
                                 cache_name: MIGRATION_CACHE_NAME_STUB.into(),
@@ -2124,6 +2277,7 @@ impl Domain {
                                 context: ReplayPieceContext::Full {
                                     last: true,
                                     replicas: replicas.clone(),
+                                    low_priority: true,
                                 },
                                 data: Default::default(),
                                 cache_name: MIGRATION_CACHE_NAME_STUB.into(),
@@ -2250,12 +2404,16 @@ impl Domain {
                 Ok(Some(bincode::serialize(&is_ready)?))
             }
             DomainRequest::GetStatistics => {
+                let (deferred_replay_batches, processed_deferred_replay_batches) =
+                    self.replay_scheduler.counts();
                 let domain_stats = readyset_client::debug::stats::DomainStats {
                     total_time: self.total_time.num_nanoseconds(),
                     total_ptime: self.total_ptime.num_nanoseconds(),
                     total_replay_time: self.total_replay_time.num_nanoseconds(),
                     total_forward_time: self.total_forward_time.num_nanoseconds(),
                     wait_time: self.wait_time.num_nanoseconds(),
+                    deferred_replay_batches,
+                    processed_deferred_replay_batches,
                 };
 
                 let node_stats: HashMap<
@@ -2323,6 +2481,15 @@ impl Domain {
                                     mem_size,
                                     materialized: mat_state,
                                     probe_result,
+                                    propagation_latency: n
+                                        .as_reader()
+                                        .and_then(|r| r.propagation_latency()),
+                                    emitted_records: self
+                                        .emitted_records
+                                        .as_ref()
+                                        .and_then(|records| records.get(local_index))
+                                        .cloned()
+                                        .unwrap_or_default(),
                                 },
                             ))
                         } else {
@@ -2411,6 +2578,40 @@ impl Domain {
                 let key = self.handle_eviction(req, executor)?;
                 Ok(Some(bincode::serialize(&key)?))
             }
+            DomainRequest::Barrier { id } => {
+                self.send_barrier(id, executor);
+                Ok(Some(bincode::serialize(
+                    &self.barriers.get(&id).cloned().unwrap_or_default(),
+                )?))
+            }
+            DomainRequest::QueryBarrier { id } => Ok(Some(bincode::serialize(
+                &self.barriers.get(&id).cloned().unwrap_or_default(),
+            )?)),
+            DomainRequest::ClearBarrier { id } => {
+                self.barriers.remove(&id);
+                Ok(None)
+            }
+            DomainRequest::Ping => {
+                set_failpoint!(failpoints::DOMAIN_PING, |_| Err(internal_err!(
+                    "Injected failure responding to ping"
+                )));
+                Ok(None)
+            }
+            DomainRequest::GetSlowOperations => Ok(Some(bincode::serialize(
+                &self.slow_operations.iter().collect::<Vec<_>>(),
+            )?)),
+            DomainRequest::SetWriteTracing {
+                samples_per_million,
+            } => {
+                self.write_tracer.set_sample_rate(samples_per_million);
+                Ok(None)
+            }
+            DomainRequest::RequestReaderLookupStats { node } => Ok(Some(bincode::serialize(
+                &self
+                    .reader_write_handles
+                    .get(node)
+                    .map(|wh| wh.lookup_stats()),
+            )?)),
         };
 
         // What we just did might have done things like insert into `self.delayed_for_self`, so
@@ -2423,7 +2624,7 @@ impl Domain {
     }
 
     #[allow(clippy::cognitive_complexity)]
-    fn handle(&mut self, m: Packet, executor: &mut dyn Executor) -> Result<(), ReadySetError> {
+    fn handle(&mut self, mut m: Packet, executor: &mut dyn Executor) -> Result<(), ReadySetError> {
         // TODO(eta): better error handling here.
         // In particular one dodgy packet can kill the whole domain, which is probably not what we
         // want.
@@ -2432,6 +2633,12 @@ impl Domain {
 
         match m {
             Packet::Message { .. } | Packet::Input { .. } => {
+                if let Packet::Input { ref mut inner, .. } = m {
+                    if inner.trace.is_none() {
+                        inner.trace = self.write_tracer.sample();
+                    }
+                }
+
                 // WO for https://github.com/rust-lang/rfcs/issues/1403
                 let start = time::Instant::now();
                 let d: PacketDiscriminants = (&m).into();
@@ -2565,6 +2772,9 @@ impl Domain {
             Packet::Spin => {
                 // spinning as instructed
             }
+            Packet::Barrier { id } => {
+                self.barriers.entry(id).or_default().received += 1;
+            }
         }
 
         Ok(())
@@ -4526,17 +4736,13 @@ impl Domain {
         })
     }
 
-    /// Handle a single message for this domain
-    #[failpoint("handle-packet")]
-    pub fn handle_packet(
+    /// Handle a single external packet, along with any packets the domain sends to itself as a
+    /// result
+    fn handle_external(
         &mut self,
         packet: Packet,
         executor: &mut dyn Executor,
     ) -> ReadySetResult<()> {
-        if self.wait_time.is_running() {
-            self.wait_time.stop();
-        }
-
         self.handle(packet, executor)?;
         // After we handle an external packet, the domain may have accumulated a bunch of packets to
         // itself we need to process them all next;
@@ -4544,6 +4750,62 @@ impl Domain {
             trace!("handling local transmission");
             self.handle(message, executor)?;
         }
+        Ok(())
+    }
+
+    /// Handle a single message for this domain.
+    ///
+    /// Low-priority batches of full replays are deferred, to be processed either once there's no
+    /// other work to do (see [`Self::handle_deferred_replay`]) or often enough to make up
+    /// [`DomainTuning::migration_replay_min_share`] of the packets processed by the domain.
+    #[failpoint("handle-packet")]
+    pub fn handle_packet(
+        &mut self,
+        packet: Packet,
+        executor: &mut dyn Executor,
+    ) -> ReadySetResult<()> {
+        let Some(packet) = self.replay_scheduler.schedule(packet) else {
+            return Ok(());
+        };
+
+        if self.wait_time.is_running() {
+            self.wait_time.stop();
+        }
+
+        self.handle_external(packet, executor)?;
+        if let Some(deferred) = self.replay_scheduler.foreground_processed() {
+            self.handle_external(deferred, executor)?;
+        }
+
+        if self.aggressively_update_state_sizes {
+            self.update_state_sizes();
+        }
+
+        if !self.wait_time.is_running() {
+            self.wait_time.start();
+        }
+
+        Ok(())
+    }
+
+    /// Returns true if this domain has deferred any low-priority replay batches which it has yet
+    /// to process
+    pub fn has_deferred_replays(&self) -> bool {
+        self.replay_scheduler.has_deferred()
+    }
+
+    /// Handle the next deferred low-priority replay batch, if any. Should be called when there are
+    /// no other packets waiting to be handled by the domain.
+    pub fn handle_deferred_replay(&mut self, executor: &mut dyn Executor) -> ReadySetResult<()> {
+        let Some(packet) = self.replay_scheduler.next_idle() else {
+            return Ok(());
+        };
+
+        if self.wait_time.is_running() {
+            self.wait_time.stop();
+        }
+
+        self.handle_external(packet, executor)?;
 
         if self.aggressively_update_state_sizes {
             self.update_state_sizes();
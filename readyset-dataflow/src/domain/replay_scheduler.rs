@@ -0,0 +1,153 @@
+//! Prioritization of foreground work over the full replays run during migrations.
+//!
+//! Full replays of large base tables can otherwise saturate a domain, starving the writes and
+//! partial replays which unrelated reads are waiting on. Low-priority replay batches (see
+//! [`Packet::is_low_priority`]) are instead queued in a [`ReplayScheduler`], and processed either
+//! when the domain has nothing else to do, or often enough to make up a minimum share of the
+//! packets the domain processes so that the migration still makes forward progress.
+
+use std::collections::VecDeque;
+
+use crate::Packet;
+
+/// A queue of deferred low-priority replay batches, along with the bookkeeping needed to guarantee
+/// them a minimum share of the packets processed by a domain.
+pub(super) struct ReplayScheduler {
+    /// Deferred batches, in the order they were received
+    deferred: VecDeque<Packet>,
+    /// See [`crate::DomainTuning::migration_replay_min_share`]
+    min_share: u8,
+    /// Credit accumulated by processing foreground packets while batches are deferred. Each
+    /// foreground packet adds `min_share`, and each deferred batch processed to maintain the
+    /// minimum share costs `100 - min_share`.
+    credit: u32,
+    /// Total number of batches deferred
+    num_deferred: u64,
+    /// Total number of deferred batches processed
+    num_processed: u64,
+}
+
+impl ReplayScheduler {
+    pub(super) fn new(min_share: u8) -> Self {
+        Self {
+            deferred: VecDeque::new(),
+            min_share,
+            credit: 0,
+            num_deferred: 0,
+            num_processed: 0,
+        }
+    }
+
+    /// If `packet` should be deferred behind foreground work, queue it and return `None`,
+    /// otherwise return it to be processed immediately
+    pub(super) fn schedule(&mut self, packet: Packet) -> Option<Packet> {
+        if !packet.is_low_priority() || self.min_share >= 100 {
+            return Some(packet);
+        }
+        self.num_deferred += 1;
+        self.deferred.push_back(packet);
+        None
+    }
+
+    /// Record that a foreground packet was processed, returning a deferred batch to process next
+    /// if one is due in order to maintain the minimum share
+    pub(super) fn foreground_processed(&mut self) -> Option<Packet> {
+        if self.deferred.is_empty() {
+            // Don't let credit build up while nothing is waiting, so that it can't be spent
+            // starving foreground work once a replay starts
+            self.credit = 0;
+            return None;
+        }
+
+        self.credit += u32::from(self.min_share);
+        let cost = u32::from(100 - self.min_share);
+        if self.credit < cost {
+            return None;
+        }
+        self.credit -= cost;
+        self.pop()
+    }
+
+    /// Return the next deferred batch to process while there's no foreground work to do
+    pub(super) fn next_idle(&mut self) -> Option<Packet> {
+        self.pop()
+    }
+
+    fn pop(&mut self) -> Option<Packet> {
+        let packet = self.deferred.pop_front()?;
+        self.num_processed += 1;
+        Some(packet)
+    }
+
+    /// Returns true if there are any deferred batches waiting to be processed
+    pub(super) fn has_deferred(&self) -> bool {
+        !self.deferred.is_empty()
+    }
+
+    /// Returns the total number of batches deferred, and the total number of deferred batches
+    /// processed
+    pub(super) fn counts(&self) -> (u64, u64) {
+        (self.num_deferred, self.num_processed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::payload::ReplayPieceContext;
+    use crate::prelude::*;
+
+    fn replay_piece(low_priority: bool) -> Packet {
+        Packet::ReplayPiece {
+            link: Link::new(LocalNodeIndex::make(0), LocalNodeIndex::make(1)),
+            tag: Tag::new(1),
+            data: Default::default(),
+            context: ReplayPieceContext::Full {
+                last: false,
+                replicas: None,
+                low_priority,
+            },
+            cache_name: "migration".into(),
+        }
+    }
+
+    #[test]
+    fn foreground_packets_not_deferred() {
+        let mut scheduler = ReplayScheduler::new(10);
+        assert!(scheduler.schedule(replay_piece(false)).is_some());
+        assert!(!scheduler.has_deferred());
+    }
+
+    #[test]
+    fn min_share_maintained() {
+        let mut scheduler = ReplayScheduler::new(10);
+        for _ in 0..100 {
+            assert!(scheduler.schedule(replay_piece(true)).is_none());
+        }
+
+        let processed = (0..900)
+            .filter(|_| scheduler.foreground_processed().is_some())
+            .count();
+        assert_eq!(processed, 100);
+        assert_eq!(scheduler.counts(), (100, 100));
+        assert!(!scheduler.has_deferred());
+    }
+
+    #[test]
+    fn idle_drains_deferred() {
+        let mut scheduler = ReplayScheduler::new(10);
+        scheduler.schedule(replay_piece(true));
+        scheduler.schedule(replay_piece(true));
+        assert!(scheduler.foreground_processed().is_none());
+        assert!(scheduler.next_idle().is_some());
+        assert!(scheduler.next_idle().is_some());
+        assert!(scheduler.next_idle().is_none());
+    }
+
+    #[test]
+    fn full_share_disables_prioritization() {
+        let mut scheduler = ReplayScheduler::new(100);
+        assert!(scheduler.schedule(replay_piece(true)).is_some());
+        assert_eq!(scheduler.counts(), (0, 0));
+    }
+}
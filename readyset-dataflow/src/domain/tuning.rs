@@ -0,0 +1,207 @@
+//! Tunable parameters for domains, which are validated when they're constructed with
+//! [`DomainTuning::builder`].
+
+use readyset_errors::{ReadySetError, ReadySetResult};
+use serde::{Deserialize, Serialize};
+
+/// The default value for [`DomainTuning::replay_batch_size`]
+const DEFAULT_REPLAY_BATCH_SIZE: usize = 256;
+
+/// The default value for [`DomainTuning::max_slow_operations`]
+const DEFAULT_MAX_SLOW_OPERATIONS: usize = 64;
+
+/// The default value for [`DomainTuning::migration_replay_min_share`]
+const DEFAULT_MIGRATION_REPLAY_MIN_SHARE: u8 = 10;
+
+/// Tunable parameters controlling how domains process replays and record slow operations.
+///
+/// Construct with [`DomainTuning::builder`], which validates all the parameters. Deserializing
+/// goes through the same validation.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(try_from = "DomainTuningFields")]
+pub struct DomainTuning {
+    replay_batch_size: usize,
+    max_slow_operations: usize,
+    migration_replay_min_share: u8,
+}
+
+/// The unvalidated fields of a [`DomainTuning`], which it's deserialized from
+#[derive(Deserialize)]
+struct DomainTuningFields {
+    replay_batch_size: usize,
+    max_slow_operations: usize,
+    migration_replay_min_share: u8,
+}
+
+impl TryFrom<DomainTuningFields> for DomainTuning {
+    type Error = ReadySetError;
+
+    fn try_from(fields: DomainTuningFields) -> ReadySetResult<Self> {
+        Self::builder()
+            .replay_batch_size(fields.replay_batch_size)
+            .max_slow_operations(fields.max_slow_operations)
+            .migration_replay_min_share(fields.migration_replay_min_share)
+            .build()
+    }
+}
+
+impl Default for DomainTuning {
+    fn default() -> Self {
+        Self {
+            replay_batch_size: DEFAULT_REPLAY_BATCH_SIZE,
+            max_slow_operations: DEFAULT_MAX_SLOW_OPERATIONS,
+            migration_replay_min_share: DEFAULT_MIGRATION_REPLAY_MIN_SHARE,
+        }
+    }
+}
+
+impl DomainTuning {
+    /// Returns a builder for a [`DomainTuning`], starting from the default value of each parameter
+    pub fn builder() -> DomainTuningBuilder {
+        DomainTuningBuilder {
+            tuning: Self::default(),
+        }
+    }
+
+    /// The number of records sent in each piece of a full replay
+    pub fn replay_batch_size(&self) -> usize {
+        self.replay_batch_size
+    }
+
+    /// The maximum number of slow operations retained by each domain. Once this is reached, the
+    /// oldest operation is discarded for each new one recorded.
+    pub fn max_slow_operations(&self) -> usize {
+        self.max_slow_operations
+    }
+
+    /// The minimum percentage of the packets processed by a domain which go to low-priority batches
+    /// of full replays run during migrations, while any are waiting behind foreground work such as
+    /// writes and partial replays for reads. A value of 100 disables prioritization entirely.
+    pub fn migration_replay_min_share(&self) -> u8 {
+        self.migration_replay_min_share
+    }
+}
+
+/// Builder for a [`DomainTuning`], returned by [`DomainTuning::builder`]
+#[derive(Clone, Debug)]
+pub struct DomainTuningBuilder {
+    tuning: DomainTuning,
+}
+
+impl DomainTuningBuilder {
+    /// Set the number of records sent in each piece of a full replay. Defaults to 256
+    pub fn replay_batch_size(mut self, replay_batch_size: usize) -> Self {
+        self.tuning.replay_batch_size = replay_batch_size;
+        self
+    }
+
+    /// Set the maximum number of slow operations retained by each domain. Defaults to 64
+    pub fn max_slow_operations(mut self, max_slow_operations: usize) -> Self {
+        self.tuning.max_slow_operations = max_slow_operations;
+        self
+    }
+
+    /// Set the minimum percentage of packets processed by each domain which go to low-priority
+    /// full replay batches. Defaults to 10
+    pub fn migration_replay_min_share(mut self, migration_replay_min_share: u8) -> Self {
+        self.tuning.migration_replay_min_share = migration_replay_min_share;
+        self
+    }
+
+    /// Validate the configured parameters, returning a [`DomainTuning`] if they're all valid or
+    /// [`ReadySetError::InvalidDomainTuning`] naming the first invalid parameter otherwise
+    pub fn build(self) -> ReadySetResult<DomainTuning> {
+        let invalid = |parameter: &str, reason: &str| ReadySetError::InvalidDomainTuning {
+            parameter: parameter.to_owned(),
+            reason: reason.to_owned(),
+        };
+
+        if self.tuning.replay_batch_size == 0 {
+            return Err(invalid(
+                "replay_batch_size",
+                "full replays must send at least one record per piece",
+            ));
+        }
+        if self.tuning.max_slow_operations == 0 {
+            return Err(invalid(
+                "max_slow_operations",
+                "at least one slow operation must be retained",
+            ));
+        }
+        if !(1..=100).contains(&self.tuning.migration_replay_min_share) {
+            return Err(invalid(
+                "migration_replay_min_share",
+                "must be a percentage between 1 and 100, so that migrations make progress",
+            ));
+        }
+
+        Ok(self.tuning)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_is_valid() {
+        assert_eq!(
+            DomainTuning::builder().build().unwrap(),
+            DomainTuning::default()
+        );
+    }
+
+    #[test]
+    fn zero_replay_batch_size_rejected() {
+        let err = DomainTuning::builder()
+            .replay_batch_size(0)
+            .build()
+            .unwrap_err();
+        assert!(
+            matches!(
+                &err,
+                ReadySetError::InvalidDomainTuning { parameter, .. }
+                    if parameter == "replay_batch_size"
+            ),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn zero_max_slow_operations_rejected() {
+        DomainTuning::builder()
+            .max_slow_operations(0)
+            .build()
+            .unwrap_err();
+    }
+
+    #[test]
+    fn out_of_range_migration_replay_min_share_rejected() {
+        for share in [0, 101] {
+            DomainTuning::builder()
+                .migration_replay_min_share(share)
+                .build()
+                .unwrap_err();
+        }
+    }
+
+    #[test]
+    fn deserialize_validates() {
+        let tuning = DomainTuning::builder()
+            .replay_batch_size(16)
+            .build()
+            .unwrap();
+        let serialized = serde_json::to_string(&tuning).unwrap();
+        assert_eq!(
+            serde_json::from_str::<DomainTuning>(&serialized).unwrap(),
+            tuning
+        );
+
+        for invalid in [
+            r#"{"replay_batch_size":0,"max_slow_operations":64,"migration_replay_min_share":10}"#,
+            r#"{"replay_batch_size":256,"max_slow_operations":64,"migration_replay_min_share":0}"#,
+        ] {
+            serde_json::from_str::<DomainTuning>(invalid).unwrap_err();
+        }
+    }
+}
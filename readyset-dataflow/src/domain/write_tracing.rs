@@ -0,0 +1,173 @@
+//! Tracing the propagation of writes from base tables to readers.
+//!
+//! A [`WriteTracer`] tags a sampled fraction of the writes received by the base tables in a domain
+//! with a [`PacketTrace`], which is carried along with the updates produced by the write through
+//! every operator and domain they pass through. When a traced update reaches a reader, the reader
+//! records the time since the write was tagged into a [`LatencyReservoir`], which is reported in
+//! the statistics for the reader.
+
+use std::time::{Duration, SystemTime};
+
+use rand::Rng;
+use readyset_client::debug::stats::PropagationLatency;
+use readyset_client::PacketTrace;
+
+/// The denominator of [`WriteTracer`] sample rates
+pub(crate) const SAMPLES_PER_MILLION: u32 = 1_000_000;
+
+/// The maximum number of latencies retained by a [`LatencyReservoir`]
+const RESERVOIR_CAPACITY: usize = 1024;
+
+/// Tags a sampled fraction of the writes received by a domain with a [`PacketTrace`].
+///
+/// Sampling is deterministic: with a sample rate of `n` per million, exactly `n` out of every
+/// million writes are traced, evenly spaced.
+#[derive(Debug, Default)]
+pub(crate) struct WriteTracer {
+    /// The number of writes out of every [`SAMPLES_PER_MILLION`] to trace
+    samples_per_million: u32,
+    /// Accumulates `samples_per_million` for each write, tracing a write whenever it reaches
+    /// [`SAMPLES_PER_MILLION`]
+    credit: u32,
+    /// The sequence number of the last trace generated
+    sequence: u64,
+}
+
+impl WriteTracer {
+    pub(crate) fn new(samples_per_million: u32) -> Self {
+        Self {
+            samples_per_million: samples_per_million.min(SAMPLES_PER_MILLION),
+            ..Default::default()
+        }
+    }
+
+    /// Set the number of writes out of every million to trace. 0 disables tracing.
+    pub(crate) fn set_sample_rate(&mut self, samples_per_million: u32) {
+        self.samples_per_million = samples_per_million.min(SAMPLES_PER_MILLION);
+        self.credit = 0;
+    }
+
+    /// Returns a trace to tag the next write with, if it's sampled
+    pub(crate) fn sample(&mut self) -> Option<PacketTrace> {
+        if self.samples_per_million == 0 {
+            return None;
+        }
+
+        self.credit += self.samples_per_million;
+        if self.credit < SAMPLES_PER_MILLION {
+            return None;
+        }
+        self.credit -= SAMPLES_PER_MILLION;
+        self.sequence += 1;
+        Some(PacketTrace {
+            start: SystemTime::now(),
+            sequence: self.sequence,
+        })
+    }
+}
+
+/// A bounded, uniformly sampled set of the propagation latencies recorded at a reader
+#[derive(Debug, Default)]
+pub struct LatencyReservoir {
+    /// A uniform sample of at most [`RESERVOIR_CAPACITY`] of the latencies recorded
+    samples: Vec<Duration>,
+    /// The total number of latencies recorded
+    count: u64,
+    max: Duration,
+}
+
+impl LatencyReservoir {
+    /// Record a single propagation latency
+    pub fn record(&mut self, latency: Duration) {
+        self.count += 1;
+        self.max = self.max.max(latency);
+        if self.samples.len() < RESERVOIR_CAPACITY {
+            self.samples.push(latency);
+        } else {
+            // Algorithm R: replace a random sample with probability capacity / count
+            let idx = rand::thread_rng().gen_range(0, self.count);
+            if let Some(sample) = self.samples.get_mut(idx as usize) {
+                *sample = latency;
+            }
+        }
+    }
+
+    /// Returns percentiles of the latencies recorded so far, or `None` if none have been recorded
+    pub fn percentiles(&self) -> Option<PropagationLatency> {
+        if self.samples.is_empty() {
+            return None;
+        }
+
+        let mut sorted = self.samples.clone();
+        sorted.sort_unstable();
+        // Nearest-rank percentiles
+        let percentile = |p: usize| sorted[((sorted.len() * p).div_ceil(100)).max(1) - 1];
+        Some(PropagationLatency {
+            samples: self.count,
+            p50: percentile(50),
+            p90: percentile(90),
+            p99: percentile(99),
+            max: self.max,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn disabled_tracer_never_samples() {
+        let mut tracer = WriteTracer::default();
+        assert!((0..1000).all(|_| tracer.sample().is_none()));
+    }
+
+    #[test]
+    fn samples_fraction_of_writes() {
+        let mut tracer = WriteTracer::new(SAMPLES_PER_MILLION / 4);
+        let traces = (0..100).filter_map(|_| tracer.sample()).collect::<Vec<_>>();
+        assert_eq!(traces.len(), 25);
+        assert!(traces
+            .iter()
+            .zip(1..)
+            .all(|(trace, sequence)| trace.sequence == sequence));
+
+        tracer.set_sample_rate(SAMPLES_PER_MILLION);
+        assert!((0..100).all(|_| tracer.sample().is_some()));
+    }
+
+    #[test]
+    fn reservoir_percentiles() {
+        let mut reservoir = LatencyReservoir::default();
+        assert_eq!(reservoir.percentiles(), None);
+
+        for ms in (1..=100).rev() {
+            reservoir.record(Duration::from_millis(ms));
+        }
+        let latency = reservoir.percentiles().unwrap();
+        assert_eq!(latency.samples, 100);
+        assert_eq!(latency.p50, Duration::from_millis(50));
+        assert_eq!(latency.p90, Duration::from_millis(90));
+        assert_eq!(latency.p99, Duration::from_millis(99));
+        assert_eq!(latency.max, Duration::from_millis(100));
+    }
+
+    #[test]
+    fn reservoir_is_bounded() {
+        let mut reservoir = LatencyReservoir::default();
+        for ms in 0..(RESERVOIR_CAPACITY as u64 * 4) {
+            reservoir.record(Duration::from_millis(ms));
+        }
+        assert_eq!(reservoir.samples.len(), RESERVOIR_CAPACITY);
+
+        let latency = reservoir.percentiles().unwrap();
+        assert_eq!(latency.samples, RESERVOIR_CAPACITY as u64 * 4);
+        assert_eq!(
+            latency.max,
+            Duration::from_millis(RESERVOIR_CAPACITY as u64 * 4 - 1)
+        );
+        assert!(latency.p50 <= latency.p90);
+        assert!(latency.p90 <= latency.p99);
+        assert!(latency.p99 <= latency.max);
+    }
+}
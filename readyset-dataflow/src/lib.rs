@@ -63,9 +63,10 @@ pub use dataflow_state::{
 };
 
 pub use crate::domain::channel::{ChannelCoordinator, DomainReceiver, DomainSender, DualTcpStream};
+pub use crate::domain::tuning::{DomainTuning, DomainTuningBuilder};
 pub use crate::domain::{Domain, DomainBuilder, DomainIndex};
 pub use crate::node_map::NodeMap;
-pub use crate::payload::{DomainRequest, Packet, PacketDiscriminants};
+pub use crate::payload::{BarrierState, DomainRequest, Packet, PacketDiscriminants};
 pub use crate::processing::LookupIndex;
 
 #[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
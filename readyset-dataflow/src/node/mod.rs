@@ -659,6 +659,21 @@ impl Node {
 
 // is this or that?
 impl Node {
+    /// Returns the type of this node, for use in errors
+    pub fn error_node_type(&self) -> readyset_errors::NodeType {
+        use readyset_errors::NodeType as ErrorNodeType;
+        match self.inner {
+            NodeType::Ingress => ErrorNodeType::Ingress,
+            NodeType::Base(..) => ErrorNodeType::Base,
+            NodeType::Internal(..) => ErrorNodeType::Internal,
+            NodeType::Egress(..) => ErrorNodeType::Egress,
+            NodeType::Sharder(..) => ErrorNodeType::Sharder,
+            NodeType::Reader(..) => ErrorNodeType::Reader,
+            NodeType::Source => ErrorNodeType::Source,
+            NodeType::Dropped => ErrorNodeType::Dropped,
+        }
+    }
+
     pub fn is_dropped(&self) -> bool {
         matches!(self.inner, NodeType::Dropped)
     }
@@ -200,6 +200,7 @@ impl Node {
                             link: Link::new(dst, dst),
                             data: rs,
                             trace,
+                            origin: Some(gaddr),
                         });
                     }
                     Some(ref p) => {
@@ -286,7 +287,10 @@ impl Node {
                         }
                         Packet::ReplayPiece {
                             ref mut data,
-                            context: payload::ReplayPieceContext::Full { last, ref replicas },
+                            context:
+                                payload::ReplayPieceContext::Full {
+                                    last, ref replicas, ..
+                                },
                             tag,
                             ..
                         } => {
@@ -6,6 +6,7 @@ use std::convert::TryFrom;
 use dataflow_state::{MaterializedNodeState, PointKey, SnapshotMode};
 use itertools::Itertools;
 use nom_sql::Relation;
+use readyset_client::debug::info::BaseColumnEvent;
 use readyset_client::{Modification, Operation, TableOperation};
 use readyset_data::{DfValue, DfValueKind};
 use readyset_errors::ReadySetResult;
@@ -68,6 +69,8 @@ pub struct Base {
 
     defaults: Vec<DfValue>,
     dropped: Vec<usize>,
+    /// Every column added or dropped after this base was created, in order
+    column_history: Vec<BaseColumnEvent>,
     unmodified: bool,
     permissive_writes: bool,
 }
@@ -118,16 +121,23 @@ impl Base {
             .collect()
     }
 
-    /// Add a new column to this base node.
+    /// Add a new column to this base node, returning its index.
+    ///
+    /// The new column is always given a fresh index at the end of the table, even if columns have
+    /// been dropped, so that nodes built against a dropped column never read the new column's
+    /// values in its place.
     pub fn add_column(&mut self, default: DfValue) -> ReadySetResult<usize> {
         invariant!(
             !self.defaults.is_empty(),
             "cannot add columns to base nodes without\
              setting default values for initial columns"
         );
-        self.defaults.push(default);
+        self.defaults.push(default.clone());
         self.unmodified = false;
-        Ok(self.defaults.len() - 1)
+        let column = self.defaults.len() - 1;
+        self.column_history
+            .push(BaseColumnEvent::Added { column, default });
+        Ok(column)
     }
 
     /// Drop a column from this base node.
@@ -138,15 +148,28 @@ impl Base {
              setting default values for initial columns"
         );
         invariant!(column < self.defaults.len());
+        invariant!(
+            !self.dropped.contains(&column),
+            "column {} has already been dropped",
+            column
+        );
         self.unmodified = false;
 
         // note that we don't need to *do* anything for dropped columns when we receive records.
         // the only thing that matters is that new Mutators remember to inject default values for
         // dropped columns.
         self.dropped.push(column);
+        self.column_history
+            .push(BaseColumnEvent::Dropped { column });
         Ok(())
     }
 
+    /// Returns every column added to or dropped from this base node since it was created, in
+    /// order
+    pub fn column_history(&self) -> &[BaseColumnEvent] {
+        &self.column_history
+    }
+
     pub fn get_dropped(&self) -> VecMap<DfValue> {
         self.dropped
             .iter()
@@ -460,6 +483,7 @@ impl Default for Base {
             unique_keys: Vec::new(),
             defaults: Vec::new(),
             dropped: Vec::new(),
+            column_history: Vec::new(),
             unmodified: true,
             permissive_writes: false,
         }
@@ -698,6 +722,32 @@ mod tests {
         assert!(b.unmodified);
     }
 
+    #[test]
+    fn column_history() {
+        let mut b = Base::new().with_default_values(vec![1.into(), 2.into()]);
+        b.drop_column(1).unwrap();
+        assert_eq!(b.add_column(3.into()).unwrap(), 2);
+        assert_eq!(
+            b.column_history(),
+            [
+                BaseColumnEvent::Dropped { column: 1 },
+                BaseColumnEvent::Added {
+                    column: 2,
+                    default: 3.into()
+                },
+            ]
+        );
+        assert_eq!(b.get_dropped().len(), 1);
+    }
+
+    #[test]
+    fn drop_column_twice() {
+        let mut b = Base::new().with_default_values(vec![1.into(), 2.into()]);
+        b.drop_column(1).unwrap();
+        b.drop_column(1).unwrap_err();
+        assert_eq!(b.column_history().len(), 1);
+    }
+
     mod process {
         use dataflow_state::MaterializedNodeState;
         use readyset_data::DfType;
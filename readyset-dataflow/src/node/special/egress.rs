@@ -51,6 +51,18 @@ impl EgressTx {
         }
     }
 
+    /// Returns the addresses of the domain replicas this tx sends regular updates to, when the
+    /// egress node is running in the given replica
+    fn destinations(&self, replica: usize) -> impl Iterator<Item = ReplicaAddress> + '_ {
+        self.replication
+            .target_replicas(replica)
+            .map(move |replica| ReplicaAddress {
+                domain_index: self.domain_index,
+                shard: self.shard,
+                replica,
+            })
+    }
+
     fn inc_sent(&mut self) {
         metrics::counter!(recorded::EGRESS_NODE_SENT_PACKETS, 1)
     }
@@ -94,6 +106,15 @@ impl Egress {
         self.tags.insert(tag, dst);
     }
 
+    /// Returns the addresses of all the domain replicas that this egress node sends regular
+    /// (non-replay) updates to, when running in the given replica
+    pub fn destinations(&self, replica: usize) -> Vec<ReplicaAddress> {
+        self.txs
+            .values()
+            .flat_map(|tx| tx.destinations(replica))
+            .collect()
+    }
+
     pub fn process(
         &mut self,
         message: &mut Option<Packet>,
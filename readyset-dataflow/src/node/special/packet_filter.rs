@@ -498,6 +498,7 @@ mod test {
                 link: create_link(),
                 data: records.into(),
                 trace: None,
+                origin: None,
             }
         }
     }
@@ -601,6 +602,7 @@ mod test {
                 ReplayPieceContext::Full {
                     last: false,
                     replicas: None,
+                    low_priority: false,
                 }
             };
             Packet::ReplayPiece {
@@ -3,12 +3,14 @@ use std::time::SystemTime;
 use dataflow_expression::ReaderProcessing;
 use failpoint_macros::failpoint;
 use metrics::histogram;
+use readyset_client::debug::stats::PropagationLatency;
 use readyset_client::metrics::recorded;
 use readyset_client::{KeyColumnIdx, ViewPlaceholder};
 use serde::{Deserialize, Serialize};
 use tracing::{trace, warn};
 
 use crate::backlog;
+use crate::domain::write_tracing::LatencyReservoir;
 use crate::prelude::*;
 
 #[derive(Serialize, Deserialize)]
@@ -25,6 +27,10 @@ pub struct Reader {
     ///
     /// The data is stored in this manner instead of in a Hashmap to support ordered iteration.
     placeholder_map: Vec<(ViewPlaceholder, KeyColumnIdx)>,
+
+    /// Latencies of the propagation of traced writes to this reader, since it was created
+    #[serde(skip)]
+    propagation_latencies: LatencyReservoir,
 }
 
 impl Clone for Reader {
@@ -34,6 +40,7 @@ impl Clone for Reader {
             reader_processing: self.reader_processing.clone(),
             index: self.index.clone(),
             placeholder_map: self.placeholder_map.clone(),
+            propagation_latencies: Default::default(),
         }
     }
 }
@@ -45,6 +52,7 @@ impl Reader {
             reader_processing,
             index: None,
             placeholder_map: Default::default(),
+            propagation_latencies: Default::default(),
         }
     }
 
@@ -60,6 +68,7 @@ impl Reader {
             reader_processing: self.reader_processing.clone(),
             index: self.index.clone(),
             placeholder_map: self.placeholder_map.clone(),
+            propagation_latencies: std::mem::take(&mut self.propagation_latencies),
         }
     }
 
@@ -115,6 +124,12 @@ impl Reader {
         self.placeholder_map.as_ref()
     }
 
+    /// Returns percentiles of the propagation latencies of the traced writes received by this
+    /// reader, or `None` if it hasn't received any
+    pub fn propagation_latency(&self) -> Option<PropagationLatency> {
+        self.propagation_latencies.percentiles()
+    }
+
     #[allow(clippy::unreachable)]
     #[failpoint("reader-handle-packet")]
     pub(in crate::node) fn process(
@@ -124,6 +139,7 @@ impl Reader {
         state: &mut backlog::WriteHandle,
     ) {
         let m = m.as_mut().unwrap();
+        let propagation_latencies = &mut self.propagation_latencies;
         m.handle_trace(
             |trace| match SystemTime::now().duration_since(trace.start) {
                 Ok(d) => {
@@ -131,6 +147,7 @@ impl Reader {
                         recorded::PACKET_WRITE_PROPAGATION_TIME,
                         d.as_micros() as f64
                     );
+                    propagation_latencies.record(d);
                 }
                 Err(e) => {
                     warn!(error = %e, "Write latency trace failed");
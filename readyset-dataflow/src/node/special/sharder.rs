@@ -154,6 +154,23 @@ impl Sharder {
         }))
     }
 
+    /// Returns the addresses of all the domain replicas that this sharder sends regular
+    /// (non-replay) updates to, when running in the given replica
+    pub fn destinations(&self, replica: usize) -> Vec<ReplicaAddress> {
+        self.txs
+            .values()
+            .flat_map(|tx| {
+                tx.replication
+                    .target_replicas(replica)
+                    .map(move |replica| ReplicaAddress {
+                        domain_index: tx.domain_index,
+                        shard: tx.shard,
+                        replica,
+                    })
+            })
+            .collect()
+    }
+
     pub fn sharded_by(&self) -> usize {
         self.shard_by
     }
@@ -280,6 +280,11 @@ impl GroupedOperation for Aggregator {
         self.out_ty.clone()
     }
 
+    fn output_col_nullable(&self) -> bool {
+        // COUNT always returns a number, even if all its inputs are NULL
+        !matches!(self.op, Aggregation::Count)
+    }
+
     fn empty_value(&self) -> Option<DfValue> {
         match self.op {
             Aggregation::Count { .. } => Some(0.into()),
@@ -86,6 +86,14 @@ pub trait GroupedOperation: fmt::Debug + Clone {
     /// Other operators like Count (int) and Concat (text) always have the same column type.
     fn output_col_type(&self) -> DfType;
 
+    /// Returns whether the output column of this operation may contain NULL values.
+    ///
+    /// Defaults to `true`, since most aggregates (e.g. SUM and MAX) return NULL if all their
+    /// inputs are NULL.
+    fn output_col_nullable(&self) -> bool {
+        true
+    }
+
     /// Returns the empty value for this aggregate, if any. Groups that have the empty value in
     /// their output column will be omitted from results
     fn empty_value(&self) -> Option<DfValue> {
@@ -134,6 +142,10 @@ impl<T: GroupedOperation> GroupedOperator<T> {
     pub fn output_col_type(&self) -> DfType {
         self.inner.output_col_type()
     }
+
+    pub fn output_col_nullable(&self) -> bool {
+        self.inner.output_col_nullable()
+    }
 }
 
 /// Extract a copy of all values in the record being targeted by the group
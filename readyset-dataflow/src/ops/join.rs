@@ -115,6 +115,16 @@ impl Join {
         }
     }
 
+    /// Returns whether the given output column may be NULL even when the parent column it's taken
+    /// from isn't, because it's taken from the right-hand side of a left join
+    pub fn is_nullable_column(&self, col: usize) -> bool {
+        self.kind == JoinType::Left
+            && self
+                .emit
+                .get(col)
+                .map_or(false, |(side, _)| *side == Side::Right)
+    }
+
     fn on_left(&self) -> Vec<usize> {
         self.on.iter().map(|(l, _)| *l).collect()
     }
@@ -31,6 +31,18 @@ impl Project {
             us: None,
         }
     }
+
+    /// Returns whether the given output column, which must not be a column taken directly from
+    /// the parent, may contain NULL values.
+    ///
+    /// Only non-NULL literals are known to never be NULL; any other expression is assumed to be
+    /// nullable.
+    pub fn is_nullable_column(&self, col: usize) -> bool {
+        !matches!(
+            self.emit.get(col),
+            Some(Expr::Literal { val, .. }) if !val.is_none()
+        )
+    }
 }
 
 impl Ingredient for Project {
@@ -1,10 +1,11 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::fmt::{self, Display};
+use std::ops::Range;
 
 use dataflow_state::MaterializedNodeState;
 use itertools::Itertools;
 use nom_sql::Relation;
-use readyset_client::{self, KeyComparison, PacketData, PacketTrace};
+use readyset_client::{self, KeyComparison, PacketData, PacketPayload, PacketTrace};
 use readyset_data::DfType;
 use serde::{Deserialize, Serialize};
 use strum_macros::{EnumCount, EnumDiscriminants, EnumIter, IntoStaticStr};
@@ -187,6 +188,12 @@ pub enum ReplayPieceContext {
         ///
         /// [`Fanout`]: SenderReplication::Fanout
         replicas: Option<Vec<usize>>,
+        /// Can this batch be deferred by domains in favor of foreground work?
+        ///
+        /// Set for all the batches of a full replay sent by the state chunker, but never for the
+        /// first batch, which must stay ordered with respect to the updates which follow it since
+        /// it's what tells the target domain to start buffering them.
+        low_priority: bool,
     },
 }
 
@@ -222,6 +229,17 @@ pub enum SenderReplication {
     Fanout { num_replicas: usize },
 }
 
+impl SenderReplication {
+    /// Returns the indices of the replicas that a sender running in the given replica sends
+    /// regular (non-replay) messages to
+    pub(crate) fn target_replicas(&self, replica: usize) -> Range<usize> {
+        match self {
+            SenderReplication::Same => replica..(replica + 1),
+            SenderReplication::Fanout { num_replicas } => 0..*num_replicas,
+        }
+    }
+}
+
 #[derive(Clone, Serialize, Deserialize, PartialEq, Eq, EnumDiscriminants, Debug)]
 /// A request to evict state.
 pub enum EvictRequest {
@@ -431,6 +449,67 @@ pub enum DomainRequest {
 
     /// Requests an eviction from state within this Domain.
     Evict(EvictRequest),
+
+    /// Start a barrier with the given id, by sending a [`Packet::Barrier`] to every child domain.
+    ///
+    /// Each domain only sends the barrier with a given id once; repeated requests to start the
+    /// same barrier are ignored. Returns the [`BarrierState`] of the domain for the barrier.
+    Barrier {
+        id: u64,
+    },
+
+    /// Request the [`BarrierState`] of this domain for the barrier with the given id
+    QueryBarrier {
+        id: u64,
+    },
+
+    /// Discard all state for the barrier with the given id
+    ClearBarrier {
+        id: u64,
+    },
+
+    /// Do nothing, and reply with an empty response. Used to check that the domain is running and
+    /// processing requests.
+    Ping,
+
+    /// Request the most recent packets which took longer than
+    /// [`Config::slow_node_threshold`](crate::DomainConfig::slow_node_threshold) to process at a
+    /// single node. Replies with a `Vec<SlowOperation>`, oldest first
+    GetSlowOperations,
+
+    /// Set the number of writes out of every million received by base tables in the domain which
+    /// are tagged with a trace to record their propagation latency at readers. See
+    /// [`DomainConfig::write_tracing_samples_per_million`].
+    ///
+    /// [`DomainConfig::write_tracing_samples_per_million`]:
+    /// crate::DomainConfig::write_tracing_samples_per_million
+    SetWriteTracing {
+        samples_per_million: u32,
+    },
+
+    /// Request the counts of the lookups performed against the given reader node. Replies with an
+    /// `Option<ReaderLookupStats>`, which is `None` if the node isn't a reader in this domain
+    RequestReaderLookupStats {
+        node: LocalNodeIndex,
+    },
+}
+
+/// The state of a single barrier within a domain, as returned by [`DomainRequest::QueryBarrier`].
+///
+/// Once a domain has received every barrier packet sent to it by the domains upstream of it, it
+/// has processed every update those domains sent before starting the barrier. Starting the
+/// barrier in each domain only once that's the case, in topological order, means that once every
+/// domain has received all the barrier packets sent to it, every update that was sent before the
+/// first barrier was started has been processed by the entire graph.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BarrierState {
+    /// Whether this domain has started the barrier, and sent a [`Packet::Barrier`] to each of its
+    /// children
+    pub started: bool,
+    /// The number of [`Packet::Barrier`]s received by this domain
+    pub received: usize,
+    /// The number of [`Packet::Barrier`]s sent by this domain to each other domain
+    pub sent: HashMap<ReplicaAddress, usize>,
 }
 
 /// The primary unit of communication between nodes in the dataflow graph.
@@ -455,6 +534,9 @@ pub enum Packet {
         link: Link,
         data: Records,
         trace: Option<PacketTrace>,
+        /// The global index of the base table whose write this update was derived from, used to
+        /// count the records derived from the writes to each base table
+        origin: Option<NodeIndex>,
     },
 
     /// Update that is part of a tagged data-flow replay path.
@@ -508,6 +590,17 @@ pub enum Packet {
         src: SourceChannelIdentifier,
         timestamp: PacketData,
     },
+
+    /// Marker sent along every edge between domains to determine when all updates sent before it
+    /// have been processed. See [`DomainRequest::Barrier`] and [`BarrierState`].
+    ///
+    /// Receiving a barrier only records its arrival; the domain doesn't send the barrier on to its
+    /// own children until it's asked to start the barrier itself. Since packets sent between a
+    /// pair of domains are always delivered in order, a barrier is only received after all the
+    /// updates sent before it.
+    Barrier {
+        id: u64,
+    },
 }
 
 // Getting rid of the various unreachables on the accessor functions in this impl requires
@@ -573,6 +666,19 @@ impl Packet {
         }
     }
 
+    /// Returns the number of records or table operations in this packet, or 0 for packets which
+    /// don't carry any data
+    pub(crate) fn batch_size(&self) -> usize {
+        match self {
+            Packet::Input { inner, .. } => match &inner.data {
+                PacketPayload::Input(ops) => ops.len(),
+                PacketPayload::Timestamp(_) => 0,
+            },
+            Packet::Message { data, .. } | Packet::ReplayPiece { data, .. } => data.len(),
+            _ => 0,
+        }
+    }
+
     pub(crate) fn mut_data(&mut self) -> &mut Records {
         match *self {
             Packet::Message { ref mut data, .. } | Packet::ReplayPiece { ref mut data, .. } => data,
@@ -596,6 +702,21 @@ impl Packet {
         }
     }
 
+    /// Returns true if this packet is a low-priority batch of a full replay, which domains can
+    /// defer in favor of foreground work. See [`ReplayPieceContext::Full::low_priority`].
+    pub(crate) fn is_low_priority(&self) -> bool {
+        matches!(
+            self,
+            Packet::ReplayPiece {
+                context: ReplayPieceContext::Full {
+                    low_priority: true,
+                    ..
+                },
+                ..
+            }
+        )
+    }
+
     pub(crate) fn is_regular(&self) -> bool {
         matches!(*self, Packet::Message { .. })
     }
@@ -625,10 +746,12 @@ impl Packet {
                 link,
                 ref data,
                 ref trace,
+                origin,
             } => Packet::Message {
                 link,
                 data: data.clone(),
                 trace: trace.clone(),
+                origin,
             },
             Packet::ReplayPiece {
                 link,
@@ -676,6 +799,7 @@ impl ToString for Packet {
             Packet::Finish { .. } => "Finish",
             Packet::Spin { .. } => "Spin",
             Packet::Evict { .. } => "Evict",
+            Packet::Barrier { .. } => "Barrier",
         }
         .to_string()
     }
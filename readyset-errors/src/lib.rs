@@ -2,6 +2,7 @@
 #![feature(never_type, exhaustive_patterns)]
 
 pub mod rpc;
+mod unsupported;
 
 use std::error::Error;
 use std::io;
@@ -15,6 +16,8 @@ use thiserror::Error;
 use url::Url;
 use vec1::{Size0Error, Vec1};
 
+pub use crate::unsupported::{ConversionStats, UnsupportedFeature, UnsupportedFeatureCount};
+
 /// Wraps a boxed `std::error::Error` to make it implement, um, `std::error::Error`.
 /// Yes, I'm as disappointed as you are.
 #[repr(transparent)]
@@ -57,6 +60,45 @@ pub enum NodeType {
     Reader,
     /// Sharder nodes
     Sharder,
+    /// Ingress nodes
+    Ingress,
+    /// Internal (operator) nodes
+    Internal,
+    /// The source node at the root of the graph
+    Source,
+    /// Nodes which have been removed from the graph
+    Dropped,
+}
+
+/// The reasons a table for writing to a base table node could not be built, for use in
+/// [`ReadySetError::MutatorBuildFailed`]
+#[derive(Eq, PartialEq, Serialize, Deserialize, Error, Debug, Clone)]
+pub enum MutatorBuildError {
+    /// The node is not a base table node
+    #[error("Node is of type {actual_kind}, not a base table")]
+    NotABaseNode {
+        /// The type of the node
+        actual_kind: NodeType,
+    },
+
+    /// The domain the base table node belongs to is not running
+    #[error("Domain {domain} is not running")]
+    DomainNotRunning {
+        /// The index of the domain
+        domain: usize,
+    },
+
+    /// There is no channel to one of the shards of the base table node's domain
+    #[error("No channel found for shard {shard}")]
+    NoChannelForShard {
+        /// The shard with no channel
+        shard: usize,
+    },
+
+    /// The base table node is sharded, but has neither a primary key nor a sharding column to
+    /// route writes to shards by
+    #[error("Sharded base table has no key to route writes by")]
+    NoKeyAvailable,
 }
 
 /// General error type to be used across all of the ReadySet codebase.
@@ -163,6 +205,10 @@ pub enum ReadySetError {
         source: Box<ReadySetError>,
     },
 
+    /// A migration was aborted before it was committed.
+    #[error("Migration was aborted")]
+    MigrationAborted,
+
     /// Failures during recipe creation which may indicate ReadySet is in an invalid state.
     #[error("Unable to create recipe from received DDL: {}", Sensitive(.0))]
     RecipeInvariantViolated(String),
@@ -291,6 +337,10 @@ pub enum ReadySetError {
     #[error("Could not find view {0}")]
     ViewNotFound(String),
 
+    /// A view was requested while the migration adding it was still being committed.
+    #[error("View {0} is not ready yet")]
+    ViewNotReady(String),
+
     /// A view couldn't be found for the given query.
     #[error("Could not find view for query {}", Sensitive(&statement.display(nom_sql::Dialect::MySQL)))]
     ViewNotFoundForQuery { statement: SelectStatement },
@@ -319,6 +369,17 @@ pub enum ReadySetError {
     #[error("Reader not found")]
     ReaderNotFound,
 
+    /// A request to one shard of a reader failed at the connection level, so the shard could not
+    /// be reached.
+    #[error("Could not reach shard {shard} of reader: {source}")]
+    ReaderShardUnreachable {
+        /// The index of the shard that could not be reached
+        shard: usize,
+        /// The error returned by the failed request
+        #[source]
+        source: Box<ReadySetError>,
+    },
+
     /// The request cannot be serviced because the server is shutting down.
     #[error("Server is shutting down")]
     ServerShuttingDown,
@@ -327,6 +388,10 @@ pub enum ReadySetError {
     #[error("Upquery timeout")]
     UpqueryTimeout,
 
+    /// Timed out waiting for all pending updates to be processed by the dataflow graph.
+    #[error("Timed out waiting for the dataflow graph to stabilize")]
+    StabilizationTimeout,
+
     /// The query specified an empty lookup key.
     #[error("the query specified an empty lookup key")]
     EmptyKey,
@@ -335,6 +400,13 @@ pub enum ReadySetError {
     #[error("the queries lookup key is not found at the reader")]
     ReaderMissingKey,
 
+    /// A range scan was issued against a reader whose state cannot be iterated in key order.
+    #[error("Reader '{reader}' does not support range scans")]
+    RangeUnsupported {
+        /// The name of the reader the range scan was issued against
+        reader: String,
+    },
+
     /// A prepared statement is missing.
     #[error("Prepared statement with ID {statement_id} not found")]
     PreparedStatementMissing {
@@ -359,12 +431,44 @@ pub enum ReadySetError {
     #[error("Bad request: {0}")]
     BadRequest(String),
 
+    /// A custom pipeline of query rewrite passes violated one of the ordering constraints
+    /// between those passes
+    #[error("Invalid rewrite pipeline: {pass} {constraint} {dependency}")]
+    InvalidRewritePipeline {
+        /// The name of the pass whose constraint was violated
+        pass: String,
+        /// The kind of constraint that was violated (eg "requires" or "must follow")
+        constraint: String,
+        /// The name of the pass that `pass` depends on
+        dependency: String,
+    },
+
+    /// A parameter passed to a builder for tunable domain parameters was invalid
+    #[error("Invalid domain tuning parameter {parameter}: {reason}")]
+    InvalidDomainTuning {
+        /// The name of the invalid parameter
+        parameter: String,
+        /// Why the value of the parameter is invalid
+        reason: String,
+    },
+
     /// An operation isn't supported by ReadySet yet, but might be in the future.
     ///
     /// This is produced by the [`unsupported!`] macro.
     #[error("Operation unsupported: {0}")]
     Unsupported(String),
 
+    /// A query uses a feature which isn't supported by ReadySet yet, but might be in the future.
+    ///
+    /// This is produced by the [`unsupported_feature!`] macro.
+    #[error("Operation unsupported: {message}")]
+    UnsupportedFeature {
+        /// The unsupported feature
+        feature: UnsupportedFeature,
+        /// A description of how the query uses the feature
+        message: String,
+    },
+
     /// The query provided by the user could not be parsed by `nom-sql`.
     ///
     /// TODO(eta): extend nom-sql to be able to provide more granular parse failure information.
@@ -489,6 +593,17 @@ pub enum ReadySetError {
         unknown_uri: Url,
     },
 
+    /// A domain could not be scheduled because every worker it could run on is already running
+    /// its maximum number of domain shards
+    #[error(
+        "Could not schedule domain {domain_index}: all workers have reached their maximum number \
+         of domains"
+    )]
+    WorkerCapacityExceeded {
+        /// The index of the domain that could not be scheduled
+        domain_index: usize,
+    },
+
     /// An RPC request was attempted against a worker that has failed.
     #[error("Worker at {uri} failed")]
     WorkerFailed {
@@ -557,6 +672,15 @@ pub enum ReadySetError {
         domain_index: usize,
     },
 
+    /// A table for writing to the given node could not be built
+    #[error("Could not build table for node {node_index}: {reason}")]
+    MutatorBuildFailed {
+        /// The index of the node
+        node_index: usize,
+        /// Why the table could not be built
+        reason: MutatorBuildError,
+    },
+
     /// An unknown domain was requested
     #[error("Unknown domain {domain_index}")]
     UnknownDomain {
@@ -619,12 +743,17 @@ pub enum ReadySetError {
     RangeAlreadyFilled,
 
     /// Tried to look up non-existent column.
-    #[error("Could not look up non-existent column {column} in {node}")]
+    #[error(
+        "Could not look up non-existent column {column} in {node} (available columns: {})",
+        .available.join(", ")
+    )]
     NonExistentColumn {
         /// The column that was attempted to be looked up.
         column: String,
         /// The node the column was looked up in.
         node: String,
+        /// The columns that do exist in the node.
+        available: Vec<String>,
     },
 
     /// Error when calling a [`jemalloc_ctl`] API
@@ -739,7 +868,9 @@ impl ReadySetError {
                 .any(move |e| e.any_cause(f.clone()))
     }
 
-    fn find_map_cause<'a, F, R: 'a>(&'a self, f: F) -> Option<R>
+    /// Returns the result of the first call to `f` which returns `Some`, calling it with this error
+    /// and then each of its causes in turn
+    pub fn find_map_cause<'a, F, R: 'a>(&'a self, f: F) -> Option<R>
     where
         F: Fn(&'a Self) -> Option<R>,
     {
@@ -761,13 +892,16 @@ impl ReadySetError {
         self.any_cause(|e| e.is_unparseable_query())
     }
 
-    /// Returns `true` if the error is [`Unsupported`].
+    /// Returns `true` if the error is [`Unsupported`] or [`UnsupportedFeature`].
     pub fn is_unsupported(&self) -> bool {
-        matches!(self, Self::Unsupported(..))
+        matches!(
+            self,
+            Self::Unsupported(..) | Self::UnsupportedFeature { .. }
+        )
     }
 
-    /// Returns true if the error either *is* [`Unsupported`], or was *caused by*
-    /// [`Unsupported`]
+    /// Returns true if the error either *is* [`Unsupported`] or [`UnsupportedFeature`], or was
+    /// *caused by* [`Unsupported`] or [`UnsupportedFeature`]
     pub fn caused_by_unsupported(&self) -> bool {
         self.any_cause(|e| e.is_unsupported())
     }
@@ -805,6 +939,15 @@ impl ReadySetError {
         })
     }
 
+    /// If `self` either *is* [`MutatorBuildFailed`] or was *caused by* [`MutatorBuildFailed`],
+    /// returns the reason the table could not be built. Otherwise, returns `None`
+    pub fn mutator_build_cause(&self) -> Option<&MutatorBuildError> {
+        self.find_map_cause(|e| match e {
+            Self::MutatorBuildFailed { reason, .. } => Some(reason),
+            _ => None,
+        })
+    }
+
     /// Returns `true` if self is [`TableNotReplicated`].
     pub fn is_table_not_replicated(&self) -> bool {
         matches!(self, Self::TableNotReplicated { .. })
@@ -871,6 +1014,15 @@ impl ReadySetError {
             _ => None,
         })
     }
+
+    /// If `self` either *is* [`UnsupportedFeature`] or was *caused by* [`UnsupportedFeature`],
+    /// returns the feature that was not supported. Otherwise, returns `None`
+    pub fn unsupported_feature_cause(&self) -> Option<&UnsupportedFeature> {
+        self.find_map_cause(|e| match e {
+            Self::UnsupportedFeature { feature, .. } => Some(feature),
+            _ => None,
+        })
+    }
 }
 
 /// Make a new [`ReadySetError::Internal`] with the provided format arguments.
@@ -1023,6 +1175,36 @@ macro_rules! invalid_query {
     };
 }
 
+/// Make a new [`ReadySetError::UnsupportedFeature`] for the given [`UnsupportedFeature`], with the
+/// provided format arguments as its message.
+///
+/// When building in debug mode, the returned error also captures file, line, and column information
+/// for further debugging purposes.
+#[macro_export]
+macro_rules! unsupported_feature_err {
+    ($feature:expr, $($format_args:tt)*) => {
+        $crate::ReadySetError::UnsupportedFeature {
+            feature: $feature,
+            message: format!(
+                "{}{}",
+                $crate::__location_info!("in {}: "),
+                format_args!($($format_args)*)
+            ),
+        }
+    }
+}
+
+/// Return a [`ReadySetError::UnsupportedFeature`] from the current function.
+///
+/// Usage is like [`unsupported!`], except that the first argument is the [`UnsupportedFeature`]
+/// that isn't supported.
+#[macro_export]
+macro_rules! unsupported_feature {
+    ($($args:tt)*) => {
+        return Err($crate::unsupported_feature_err!($($args)*).into())
+    };
+}
+
 /// Return a [`ReadySetError::Unsupported`] from the current function.
 ///
 /// Usage is like [`panic!`], in that you can pass a format string and arguments.
@@ -1138,6 +1320,14 @@ pub fn view_err<A: Into<NodeIndex>, B: Into<ReadySetError>>(idx: A, err: B) -> R
     }
 }
 
+/// Make a new [`ReadySetError::ReaderShardUnreachable`] with the provided `shard` and `err` values.
+pub fn shard_unreachable_err(shard: usize, err: ReadySetError) -> ReadySetError {
+    ReadySetError::ReaderShardUnreachable {
+        shard,
+        source: Box::new(err),
+    }
+}
+
 /// Make a new `ReadySetError::TableError` with the provided `name` and `err` values.
 pub fn table_err<E: Into<ReadySetError>>(table: Relation, err: E) -> ReadySetError {
     ReadySetError::TableError {
@@ -0,0 +1,187 @@
+//! Structured classification of the features which cause queries to be unsupported by ReadySet.
+
+use std::collections::HashMap;
+
+use derive_more::Display;
+use serde::{Deserialize, Serialize};
+
+use crate::ReadySetError;
+
+/// A feature of SQL queries which ReadySet does not (yet) support, for use in
+/// [`ReadySetError::UnsupportedFeature`]
+#[derive(Eq, PartialEq, Hash, Serialize, Deserialize, Debug, Display, Clone)]
+pub enum UnsupportedFeature {
+    /// A call to a function which isn't implemented
+    #[display(fmt = "function {}", name)]
+    Function {
+        /// The name of the function
+        name: String,
+    },
+    /// A binary operator which can't be used in a filter condition
+    #[display(fmt = "binary operator {}", operator)]
+    BinaryOperator {
+        /// The operator, as it appears in SQL
+        operator: String,
+    },
+    /// A correlated subquery which couldn't be rewritten into a join
+    #[display(fmt = "correlated subquery")]
+    CorrelatedSubquery,
+    /// A subquery in a filter condition which couldn't be rewritten into a join
+    #[display(fmt = "subquery in filter")]
+    NestedSelectInFilter,
+    /// A join whose condition references more than two tables, or which uses an unsupported join
+    /// operator or right-hand side
+    #[display(fmt = "join shape")]
+    JoinShape,
+    /// A condition in the `ON` clause of a `LEFT JOIN` which references a parameter, or no columns
+    /// from the joined tables
+    #[display(fmt = "left join condition")]
+    LeftJoinCondition,
+    /// A combination of `LIMIT` and `OFFSET` which can't be cached
+    #[display(fmt = "pagination")]
+    Pagination,
+    /// An operation which would have to be performed on the results of a lookup into a reader,
+    /// where that isn't supported or isn't enabled
+    #[display(fmt = "post-lookup operation")]
+    PostLookup,
+    /// `GROUP BY ... WITH ROLLUP`
+    #[display(fmt = "GROUP BY ... WITH ROLLUP")]
+    Rollup,
+    /// A non-default `NULLS FIRST` or `NULLS LAST` in an `ORDER BY` clause
+    #[display(fmt = "NULLS FIRST/LAST")]
+    NullsOrder,
+    /// `TABLESAMPLE`
+    #[display(fmt = "TABLESAMPLE")]
+    TableSample,
+    /// A `SELECT` statement with no tables
+    #[display(fmt = "SELECT without tables")]
+    NoTables,
+}
+
+/// The number of queries which failed because of a single [`UnsupportedFeature`], in a
+/// [`ConversionStats`] report
+#[derive(Eq, PartialEq, Serialize, Deserialize, Debug, Clone)]
+pub struct UnsupportedFeatureCount {
+    /// The unsupported feature
+    pub feature: UnsupportedFeature,
+    /// The number of queries which used the feature
+    pub count: usize,
+    /// The first query recorded as using the feature
+    pub example_query: String,
+}
+
+/// Accumulates the results of converting many queries (eg from an application's query log), to
+/// report which unsupported features caused the most queries to fail
+#[derive(Default, Debug, Clone)]
+pub struct ConversionStats {
+    /// The number of queries which were converted successfully
+    pub succeeded: usize,
+    /// The number of queries which failed with an error that wasn't caused by an
+    /// [`UnsupportedFeature`], including errors for unsupported operations that haven't been
+    /// classified yet
+    pub other_errors: usize,
+    features: HashMap<UnsupportedFeature, (usize, String)>,
+}
+
+impl ConversionStats {
+    /// Create a new, empty, [`ConversionStats`]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the result of converting `query`
+    pub fn record<T>(&mut self, query: &str, result: &Result<T, ReadySetError>) {
+        match result {
+            Ok(_) => self.succeeded += 1,
+            Err(e) => match e.unsupported_feature_cause() {
+                Some(feature) => {
+                    self.features
+                        .entry(feature.clone())
+                        .or_insert_with(|| (0, query.to_owned()))
+                        .0 += 1
+                }
+                None => self.other_errors += 1,
+            },
+        }
+    }
+
+    /// Returns the total number of queries recorded
+    pub fn total(&self) -> usize {
+        self.succeeded + self.other_errors + self.features.values().map(|(n, _)| n).sum::<usize>()
+    }
+
+    /// Returns the unsupported features which caused queries to fail, most frequent first, along
+    /// with the number of queries that used each feature and an example of such a query
+    pub fn report(&self) -> Vec<UnsupportedFeatureCount> {
+        let mut report = self
+            .features
+            .iter()
+            .map(
+                |(feature, (count, example_query))| UnsupportedFeatureCount {
+                    feature: feature.clone(),
+                    count: *count,
+                    example_query: example_query.clone(),
+                },
+            )
+            .collect::<Vec<_>>();
+        // Break ties by name, so that the report is deterministic
+        report.sort_by(|a, b| {
+            b.count
+                .cmp(&a.count)
+                .then_with(|| a.feature.to_string().cmp(&b.feature.to_string()))
+        });
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{internal_err, unsupported_err, unsupported_feature_err};
+
+    #[test]
+    fn report_ranks_features_by_frequency() {
+        let mut stats = ConversionStats::new();
+        stats.record("SELECT 1 FROM t", &Ok(()));
+        stats.record::<()>(
+            "SELECT * FROM t GROUP BY x WITH ROLLUP",
+            &Err(unsupported_feature_err!(
+                UnsupportedFeature::Rollup,
+                "rollup"
+            )),
+        );
+        for q in ["SELECT f(x) FROM t", "SELECT f(y) FROM t"] {
+            stats.record::<()>(
+                q,
+                &Err(ReadySetError::SelectQueryCreationFailed {
+                    qname: "q".into(),
+                    source: Box::new(unsupported_feature_err!(
+                        UnsupportedFeature::Function { name: "f".into() },
+                        "Function f does not exist"
+                    )),
+                }),
+            );
+        }
+        stats.record::<()>("SELECT a", &Err(unsupported_err!("unclassified")));
+        stats.record::<()>("SELECT b", &Err(internal_err!("oops")));
+
+        assert_eq!(stats.total(), 6);
+        assert_eq!(stats.succeeded, 1);
+        assert_eq!(stats.other_errors, 2);
+        assert_eq!(
+            stats.report(),
+            vec![
+                UnsupportedFeatureCount {
+                    feature: UnsupportedFeature::Function { name: "f".into() },
+                    count: 2,
+                    example_query: "SELECT f(x) FROM t".into(),
+                },
+                UnsupportedFeatureCount {
+                    feature: UnsupportedFeature::Rollup,
+                    count: 1,
+                    example_query: "SELECT * FROM t GROUP BY x WITH ROLLUP".into(),
+                },
+            ]
+        );
+    }
+}
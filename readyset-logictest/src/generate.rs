@@ -131,6 +131,20 @@ impl Seed {
     pub fn from_seeds<I>(seeds: I, dialect: nom_sql::Dialect) -> anyhow::Result<Self>
     where
         I: IntoIterator<Item = QuerySeed>,
+    {
+        Self::from_seeds_filtered(seeds, dialect, |_| true)
+    }
+
+    /// Like [`Self::from_seeds`], but only includes the generated queries for which `keep` returns
+    /// true
+    fn from_seeds_filtered<I, F>(
+        seeds: I,
+        dialect: nom_sql::Dialect,
+        mut keep: F,
+    ) -> anyhow::Result<Self>
+    where
+        I: IntoIterator<Item = QuerySeed>,
+        F: FnMut(&query_generator::Query<'_>) -> bool,
     {
         let mut generator = query_generator::GeneratorState::with_parameter_mode(match dialect {
             Dialect::MySQL => ParameterMode::Positional,
@@ -138,11 +152,14 @@ impl Seed {
         });
         let queries = seeds
             .into_iter()
-            .map(|seed| -> anyhow::Result<Query> {
+            .map(|seed| -> anyhow::Result<Option<Query>> {
                 let query = generator.generate_query(seed);
+                if !keep(&query) {
+                    return Ok(None);
+                }
                 let query_string = query.statement.display(dialect).to_string();
 
-                Ok(Query {
+                Ok(Some(Query {
                     label: None,
                     column_types: None,
                     sort_mode: if query.statement.order.is_some() {
@@ -161,9 +178,12 @@ impl Seed {
                             .map(|dt| dt.try_into())
                             .collect::<Result<Vec<_>, _>>()?,
                     ),
-                })
+                }))
             })
-            .collect::<Result<Vec<_>, _>>()?;
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>();
 
         let mut tables = vec![];
         let mut records = vec![];
@@ -214,8 +234,11 @@ impl Seed {
     pub fn from_generate_opts(
         opts: query_generator::GenerateOpts,
         dialect: nom_sql::Dialect,
+        rows_per_table: usize,
     ) -> anyhow::Result<Self> {
-        Self::from_seeds(opts.into_query_seeds(), dialect)
+        Self::from_seeds_filtered(opts.clone().into_query_seeds(), dialect, |query| {
+            opts.admits(&query.estimate_result_size(rows_per_table))
+        })
     }
 
     pub async fn run(
@@ -485,7 +508,11 @@ impl Generate {
         let dialect = self.script_options.dialect();
         let mut seed = match self.from.take() {
             Some(path) => Seed::try_from(path)?,
-            None => Seed::from_generate_opts(self.query_options.clone(), dialect)?,
+            None => Seed::from_generate_opts(
+                self.query_options.clone(),
+                dialect,
+                self.script_options.rows_per_table,
+            )?,
         };
 
         let script = seed.run(self.script_options, dialect).await?;
@@ -207,6 +207,8 @@ impl Permute {
                         )),
                         subquery_depth: self.subquery_depth,
                         num_operations: None,
+                        include_cte_reuse: false,
+                        result_size_range: None,
                     },
                     script_options: self.script_options.clone(),
                     output: Some(output.clone()),
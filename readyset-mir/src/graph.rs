@@ -355,10 +355,7 @@ impl MirGraph {
 
     /// Returns the index of the column in the column list for the given node.
     pub fn column_id_for_column(&self, node: NodeIndex, c: &MirColumn) -> ReadySetResult<usize> {
-        let err = Err(ReadySetError::NonExistentColumn {
-            column: c.to_string(),
-            node: self.graph[node].name().display_unquoted().to_string(),
-        });
+        let err = Err(self.non_existent_column_err(node, c));
         #[allow(clippy::cmp_owned)]
         match self.graph[node].inner {
             // if we're a base, translate to absolute column ID (taking into account deleted
@@ -415,6 +412,24 @@ impl MirGraph {
         }
     }
 
+    /// Returns the index of the column in the given node that is the source of the given child
+    /// column, as determined by [`Self::find_source_for_child_column`].
+    ///
+    /// Returns [`ReadySetError::NonExistentColumn`], listing all the columns that do exist in the
+    /// node, if the column can't be found.
+    pub fn column_position(&self, node: NodeIndex, child: &MirColumn) -> ReadySetResult<usize> {
+        self.find_source_for_child_column(node, child)
+            .ok_or_else(|| self.non_existent_column_err(node, child))
+    }
+
+    fn non_existent_column_err(&self, node: NodeIndex, c: &MirColumn) -> ReadySetError {
+        ReadySetError::NonExistentColumn {
+            column: c.to_string(),
+            node: self.graph[node].name().display_unquoted().to_string(),
+            available: self.columns(node).iter().map(|c| c.to_string()).collect(),
+        }
+    }
+
     /// Add a new column to the set of emitted columns for this node
     pub fn add_column(&mut self, node: NodeIndex, c: MirColumn) -> ReadySetResult<()> {
         if !self.graph[node].inner.add_column(c.clone())? {
@@ -493,6 +508,46 @@ impl IndexMut<NodeIndex> for MirGraph {
 mod tests {
     use super::*;
 
+    #[test]
+    fn column_position_lists_available_columns() {
+        let mut graph = MirGraph::new();
+        let t = graph.add_node(MirNode::new(
+            "t".into(),
+            MirNodeInner::Base {
+                column_specs: vec![
+                    nom_sql::ColumnSpecification::new(
+                        nom_sql::Column::from("t.a"),
+                        nom_sql::SqlType::Int(None),
+                    ),
+                    nom_sql::ColumnSpecification::new(
+                        nom_sql::Column::from("t.b"),
+                        nom_sql::SqlType::Int(None),
+                    ),
+                ],
+                primary_key: None,
+                unique_keys: Default::default(),
+            },
+        ));
+
+        assert_eq!(
+            graph
+                .column_position(t, &MirColumn::new(Some("t"), "b"))
+                .unwrap(),
+            1
+        );
+        let err = graph
+            .column_position(t, &MirColumn::new(Some("t"), "c"))
+            .unwrap_err();
+        assert_eq!(
+            err,
+            ReadySetError::NonExistentColumn {
+                column: "t.c".into(),
+                node: "t".into(),
+                available: vec!["t.a".into(), "t.b".into()],
+            }
+        );
+    }
+
     #[test]
     fn swap_with_child_preserves_edge_weights() {
         let mut graph = MirGraph::new();
@@ -403,6 +403,30 @@ impl MirNodeInner {
         matches!(self, Self::ViewKey { .. })
     }
 
+    /// Returns the name of the variant of this node, eg `"Aggregation"` or `"Leaf"`
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            MirNodeInner::Aggregation { .. } => "Aggregation",
+            MirNodeInner::Base { .. } => "Base",
+            MirNodeInner::Extremum { .. } => "Extremum",
+            MirNodeInner::Filter { .. } => "Filter",
+            MirNodeInner::Identity => "Identity",
+            MirNodeInner::Join { .. } => "Join",
+            MirNodeInner::JoinAggregates => "JoinAggregates",
+            MirNodeInner::LeftJoin { .. } => "LeftJoin",
+            MirNodeInner::DependentJoin { .. } => "DependentJoin",
+            MirNodeInner::DependentLeftJoin { .. } => "DependentLeftJoin",
+            MirNodeInner::ViewKey { .. } => "ViewKey",
+            MirNodeInner::Project { .. } => "Project",
+            MirNodeInner::Union { .. } => "Union",
+            MirNodeInner::Paginate { .. } => "Paginate",
+            MirNodeInner::TopK { .. } => "TopK",
+            MirNodeInner::Distinct { .. } => "Distinct",
+            MirNodeInner::AliasTable { .. } => "AliasTable",
+            MirNodeInner::Leaf { .. } => "Leaf",
+        }
+    }
+
     pub(crate) fn description(&self) -> String {
         match self {
             MirNodeInner::Aggregation {
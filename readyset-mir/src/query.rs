@@ -2,7 +2,7 @@ use std::marker::PhantomData;
 
 use itertools::Itertools;
 use nom_sql::{Relation, SqlIdentifier};
-use petgraph::visit::{Bfs, EdgeRef, IntoNodeReferences, Reversed};
+use petgraph::visit::{Bfs, EdgeRef, IntoNodeReferences, Reversed, VisitMap};
 use petgraph::Direction;
 use readyset_errors::{internal_err, ReadySetError, ReadySetResult};
 
@@ -106,6 +106,23 @@ impl<'a> MirQuery<'a> {
         nodes
     }
 
+    /// Returns the index of the first node belonging to this query, in topological order, whose
+    /// name is the given name.
+    pub fn node_by_name(&self, name: &str) -> Option<NodeIndex> {
+        self.topo_nodes()
+            .into_iter()
+            .find(|&n| self.graph[n].name().name == name)
+    }
+
+    /// Returns the indices of all the nodes belonging to this query, in topological order, whose
+    /// [`MirNodeInner`] variant has the given name (as returned by [`MirNodeInner::type_name`]).
+    pub fn nodes_by_type(&self, type_name: &str) -> Vec<NodeIndex> {
+        self.topo_nodes()
+            .into_iter()
+            .filter(|&n| self.graph[n].inner.type_name() == type_name)
+            .collect()
+    }
+
     /// Returns the list of immediate ancestors for the given node.
     /// If the node does not belong to the query (or doesn't exist), an empty
     /// vector is returned.
@@ -277,6 +294,35 @@ impl<'a> MirQuery<'a> {
         self.graph.insert_below(parent, node)
     }
 
+    /// Prunes all the nodes belonging to this query which the leaf node isn't reachable from, such
+    /// as nodes left disconnected by rewrite passes, so that they aren't lowered to dataflow.
+    ///
+    /// Nodes which also belong to other queries (including nodes reused from other queries) only
+    /// stop belonging to this query. Other unreachable nodes are removed from the graph entirely,
+    /// unless they're base tables or have already been lowered to dataflow.
+    ///
+    /// Returns the number of nodes pruned from this query.
+    pub fn prune_unreachable_nodes(&mut self) -> usize {
+        let graph = Reversed(&**self.graph);
+        let mut bfs = Bfs::new(graph, self.leaf);
+        while bfs.next(graph).is_some() {}
+
+        let unreachable = self
+            .node_references()
+            .map(|(n, _)| n)
+            .filter(|n| !bfs.discovered.is_visited(n))
+            .collect::<Vec<_>>();
+        for &node_idx in &unreachable {
+            let node = &mut self.graph[node_idx];
+            node.remove_owner(&self.name);
+            if node.owners().is_empty() && !node.is_base() && node.df_node_index().is_none() {
+                self.graph.remove_node(node_idx);
+            }
+        }
+
+        unreachable.len()
+    }
+
     /// Runs the given function on the [`MirNodeInner`] belonging to the given node,
     /// and returns the result of said function.
     /// Returns [`None`] if the node does not belong to the query or doesn't exist.
@@ -389,3 +435,123 @@ impl<'a> Iterator for Topo<'a, Descendants> {
         self.visitor.next(&**self.graph)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use common::IndexType;
+
+    use super::*;
+
+    fn make_query(query_name: &Relation, graph: &mut MirGraph) -> NodeIndex {
+        let base = graph.add_node(MirNode::new(
+            "base".into(),
+            MirNodeInner::Base {
+                column_specs: vec![],
+                primary_key: None,
+                unique_keys: Default::default(),
+            },
+        ));
+        graph[base].add_owner(query_name.clone());
+
+        let project_1 = graph.add_node(MirNode::new(
+            "q_n1".into(),
+            MirNodeInner::Project { emit: vec![] },
+        ));
+        graph[project_1].add_owner(query_name.clone());
+        graph.add_edge(base, project_1, 0);
+
+        let project_2 = graph.add_node(MirNode::new(
+            "q_n2".into(),
+            MirNodeInner::Project { emit: vec![] },
+        ));
+        graph[project_2].add_owner(query_name.clone());
+        graph.add_edge(project_1, project_2, 0);
+
+        let leaf = graph.add_node(MirNode::new(
+            "q".into(),
+            MirNodeInner::leaf(vec![], IndexType::HashMap),
+        ));
+        graph[leaf].add_owner(query_name.clone());
+        graph.add_edge(project_2, leaf, 0);
+
+        leaf
+    }
+
+    #[test]
+    fn node_by_name() {
+        let query_name = Relation::from("q");
+        let mut graph = MirGraph::new();
+        let leaf = make_query(&query_name, &mut graph);
+        let query = MirQuery::new(query_name, leaf, &mut graph);
+
+        let node = query.node_by_name("q_n2").unwrap();
+        assert_eq!(query.graph[node].name(), &Relation::from("q_n2"));
+        assert_eq!(query.descendants(node).unwrap(), vec![leaf]);
+        assert_eq!(query.node_by_name("q"), Some(leaf));
+        assert_eq!(query.node_by_name("q_n3"), None);
+    }
+
+    #[test]
+    fn nodes_by_type() {
+        let query_name = Relation::from("q");
+        let mut graph = MirGraph::new();
+        let leaf = make_query(&query_name, &mut graph);
+        let query = MirQuery::new(query_name, leaf, &mut graph);
+
+        let projects = query.nodes_by_type("Project");
+        assert_eq!(
+            projects
+                .iter()
+                .map(|&n| query.graph[n].name().name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["q_n1", "q_n2"]
+        );
+        assert_eq!(query.nodes_by_type("Leaf"), vec![leaf]);
+        assert!(query.nodes_by_type("Aggregation").is_empty());
+    }
+
+    #[test]
+    fn prune_unreachable_nodes() {
+        let query_name = Relation::from("q");
+        let other_name = Relation::from("other");
+        let mut graph = MirGraph::new();
+        let leaf = make_query(&query_name, &mut graph);
+        let base = graph.node_indices().find(|&n| graph[n].is_base()).unwrap();
+
+        // A dead branch off the base table, belonging only to this query
+        let dead = graph.add_node(MirNode::new(
+            "q_dead".into(),
+            MirNodeInner::Project { emit: vec![] },
+        ));
+        graph[dead].add_owner(query_name.clone());
+        graph.add_edge(base, dead, 0);
+        let dead_child = graph.add_node(MirNode::new(
+            "q_dead_child".into(),
+            MirNodeInner::Project { emit: vec![] },
+        ));
+        graph[dead_child].add_owner(query_name.clone());
+        graph.add_edge(dead, dead_child, 0);
+
+        // A node reused from another query, which isn't reachable from this query's leaf
+        let shared = graph.add_node(MirNode::new(
+            "other_n1".into(),
+            MirNodeInner::Project { emit: vec![] },
+        ));
+        graph[shared].add_owner(query_name.clone());
+        graph[shared].add_owner(other_name.clone());
+        graph.add_edge(base, shared, 0);
+
+        let mut query = MirQuery::new(query_name.clone(), leaf, &mut graph);
+        let num_nodes = query.topo_nodes().len();
+        assert_eq!(query.prune_unreachable_nodes(), 3);
+        assert_eq!(query.topo_nodes().len(), num_nodes - 3);
+        assert_eq!(query.prune_unreachable_nodes(), 0);
+
+        assert!(!graph.contains_node(dead));
+        assert!(!graph.contains_node(dead_child));
+        assert!(graph.contains_node(base));
+        assert!(graph[base].is_owned_by(&query_name));
+        assert!(!graph[shared].is_owned_by(&query_name));
+        assert!(graph[shared].is_owned_by(&other_name));
+    }
+}
@@ -3,7 +3,9 @@ use std::iter;
 use itertools::{Either, Itertools};
 use nom_sql::analysis::ReferredColumns;
 use nom_sql::{BinaryOperator, Expr};
-use readyset_errors::{internal, invariant, unsupported, ReadySetResult};
+use readyset_errors::{
+    internal, invariant, unsupported_feature, ReadySetResult, UnsupportedFeature,
+};
 use tracing::{instrument, trace};
 
 use crate::node::MirNodeInner;
@@ -32,7 +34,7 @@ use crate::{Column, NodeIndex};
 /// [`Join`]: MirNodeInner::LeftJoin
 /// [`Aggregation`]: MirNodeInner::Aggregation
 /// [`Extremum`]: MirNodeInner::Extremum
-/// [unsupported error]: noria_errors::ReadySetError::Unsupported
+/// [unsupported error]: readyset_errors::ReadySetError::UnsupportedFeature
 /// [`pull_all_required_columns`]: noria_mir::rewrite::pull_columns::pull_all_required_columns
 fn push_dependent_filter(
     query: &mut MirQuery<'_>,
@@ -44,7 +46,10 @@ fn push_dependent_filter(
     if children.len() != 1 {
         // TODO: this probably happens for unions; we should try to deal with that at some point
         // (see what the HyPer and SQL Server papers say about disjunctive predicates)
-        unsupported!();
+        unsupported_feature!(
+            UnsupportedFeature::CorrelatedSubquery,
+            "Can't decorrelate a subquery whose dependent filter has multiple children"
+        );
     }
 
     let child_idx = *children.first().unwrap();
@@ -111,7 +116,8 @@ fn push_dependent_filter(
             }
             true
         }
-        inner => unsupported!(
+        inner => unsupported_feature!(
+            UnsupportedFeature::CorrelatedSubquery,
             "Don't know how to push filter below {} to decorrelate",
             inner.description()
         ),
@@ -22,6 +22,7 @@ impl<'a> MirQuery<'a> {
         pull_columns::pull_all_required_columns(&mut self)?;
         fuse::fuse_project_nodes(&mut self)?;
         fuse::fuse_filter_nodes(&mut self)?;
+        self.prune_unreachable_nodes();
         Ok(self)
     }
 }
@@ -856,6 +856,7 @@ impl QueryHandler for MySqlQueryHandler {
                                 table: None,
                             },
                             column_type: DfType::UnsignedInt,
+                            nullable: true,
                             base: None,
                         }]),
                         columns: Cow::Owned(vec![field_name]),
@@ -82,14 +82,15 @@ pub(crate) fn convert_column(col: &ColumnSchema) -> ReadySetResult<mysql_srv::Co
         DfType::Array(_) => unsupported!("MySQL does not support arrays"),
     };
 
+    if !col.nullable {
+        colflags |= mysql_srv::ColumnFlags::NOT_NULL_FLAG;
+    }
+
     for c in col.base.iter().flat_map(|b| &b.constraints) {
         match *c {
             ColumnConstraint::AutoIncrement => {
                 colflags |= mysql_srv::ColumnFlags::AUTO_INCREMENT_FLAG;
             }
-            ColumnConstraint::NotNull => {
-                colflags |= mysql_srv::ColumnFlags::NOT_NULL_FLAG;
-            }
             ColumnConstraint::PrimaryKey => {
                 colflags |= mysql_srv::ColumnFlags::PRI_KEY_FLAG;
             }
@@ -118,6 +118,51 @@ async fn two_columns_with_same_name() {
     shutdown_tx.shutdown().await;
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn ambiguous_unqualified_column() {
+    let (opts, _handle, shutdown_tx) = setup().await;
+    let mut conn = mysql_async::Conn::new(opts).await.unwrap();
+
+    conn.query_drop("CREATE TABLE t1 (x int, y int);")
+        .await
+        .unwrap();
+    conn.query_drop("INSERT INTO t1 (x, y) VALUES (1, 2);")
+        .await
+        .unwrap();
+    conn.query_drop("CREATE TABLE t2 (x int, z int);")
+        .await
+        .unwrap();
+    conn.query_drop("INSERT INTO t2 (x, z) VALUES (1, 3);")
+        .await
+        .unwrap();
+    sleep().await;
+
+    // `x` exists in both tables
+    let err = conn
+        .query_first::<i32, _>("SELECT x FROM t1 JOIN t2 ON t1.x = t2.x")
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("ambiguous"), "{err}");
+    let err = conn
+        .exec_first::<i32, _, _>(
+            "SELECT t1.y FROM t1 JOIN t2 ON t1.x = t2.x WHERE x = ?",
+            (1,),
+        )
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("ambiguous"), "{err}");
+
+    // `y` and `z` only exist in one table each, so they don't need to be qualified
+    let res = conn
+        .query_first::<(i32, i32), _>("SELECT y, z FROM t1 JOIN t2 ON t1.x = t2.x")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(res, (2, 3));
+
+    shutdown_tx.shutdown().await;
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn delete_basic() {
     let (opts, _handle, shutdown_tx) = setup().await;
@@ -26,7 +26,10 @@ impl From<Error> for ps::Error {
             ReadySet(ReadySetError::PreparedStatementMissing { statement_id }) => {
                 ps::Error::MissingPreparedStatement(statement_id.to_string())
             }
-            ReadySet(ReadySetError::Unsupported(s)) => ps::Error::Unsupported(s),
+            ReadySet(ReadySetError::Unsupported(s))
+            | ReadySet(ReadySetError::UnsupportedFeature { message: s, .. }) => {
+                ps::Error::Unsupported(s)
+            }
             ReadySet(e) => ps::Error::Unknown(e.to_string()),
             PostgreSql(e) => e.into(),
         }
@@ -107,6 +107,7 @@ impl<'a> TryFrom<QueryResponse<'a>> for ps::QueryResponse<Resultset> {
                                     table: None,
                                 },
                                 column_type: DfType::DEFAULT_TEXT,
+                                nullable: true,
                                 base: None,
                             })
                             .collect(),
@@ -135,6 +136,7 @@ impl<'a> TryFrom<QueryResponse<'a>> for ps::QueryResponse<Resultset> {
                                 table: None,
                             },
                             column_type: DfType::DEFAULT_TEXT,
+                            nullable: true,
                             base: None,
                         },
                         ColumnSchema {
@@ -143,6 +145,7 @@ impl<'a> TryFrom<QueryResponse<'a>> for ps::QueryResponse<Resultset> {
                                 table: None,
                             },
                             column_type: DfType::DEFAULT_TEXT,
+                            nullable: true,
                             base: None,
                         },
                     ]),
@@ -173,6 +176,7 @@ impl<'a> TryFrom<QueryResponse<'a>> for ps::QueryResponse<Resultset> {
                                 table: None,
                             },
                             column_type: DfType::DEFAULT_TEXT,
+                            nullable: true,
                             base: None,
                         },
                         ColumnSchema {
@@ -181,6 +185,7 @@ impl<'a> TryFrom<QueryResponse<'a>> for ps::QueryResponse<Resultset> {
                                 table: None,
                             },
                             column_type: DfType::DEFAULT_TEXT,
+                            nullable: true,
                             base: None,
                         },
                     ]),
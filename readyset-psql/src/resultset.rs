@@ -178,6 +178,7 @@ mod tests {
             schema: Cow::Owned(vec![ColumnSchema {
                 column: "tab1.col1".into(),
                 column_type: DfType::BigInt,
+                nullable: true,
                 base: None,
             }]),
             columns: Cow::Owned(vec!["col1".into()]),
@@ -197,6 +198,7 @@ mod tests {
             schema: Cow::Owned(vec![ColumnSchema {
                 column: "tab1.col1".into(),
                 column_type: DfType::BigInt,
+                nullable: true,
                 base: None,
             }]),
             columns: Cow::Owned(vec!["col1".into()]),
@@ -219,6 +221,7 @@ mod tests {
             schema: Cow::Owned(vec![ColumnSchema {
                 column: "tab1.col1".into(),
                 column_type: DfType::BigInt,
+                nullable: true,
                 base: None,
             }]),
             columns: Cow::Owned(vec!["col1".into()]),
@@ -63,6 +63,41 @@ async fn two_columns_with_same_name() {
     shutdown_tx.shutdown().await;
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn ambiguous_unqualified_column() {
+    let (opts, _handle, shutdown_tx) = setup().await;
+    let conn = connect(opts).await;
+
+    conn.simple_query("CREATE TABLE t1 (x int, y int);")
+        .await
+        .unwrap();
+    conn.simple_query("INSERT INTO t1 (x, y) VALUES (1, 2);")
+        .await
+        .unwrap();
+    conn.simple_query("CREATE TABLE t2 (x int, z int);")
+        .await
+        .unwrap();
+    conn.simple_query("INSERT INTO t2 (x, z) VALUES (1, 3);")
+        .await
+        .unwrap();
+    sleep().await;
+
+    // `x` exists in both tables
+    assert!(conn
+        .query("SELECT x FROM t1 JOIN t2 ON t1.x = t2.x", &[])
+        .await
+        .is_err());
+
+    // `y` and `z` only exist in one table each, so they don't need to be qualified
+    let res = conn
+        .query_one("SELECT y, z FROM t1 JOIN t2 ON t1.x = t2.x", &[])
+        .await
+        .unwrap();
+    assert_eq!((res.get::<_, i32>(0), res.get::<_, i32>(1)), (2, 3));
+
+    shutdown_tx.shutdown().await;
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn delete_basic() {
     let (opts, _handle, shutdown_tx) = setup().await;
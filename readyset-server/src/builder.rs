@@ -6,18 +6,19 @@ use std::sync::Arc;
 use std::time::{self, Duration};
 
 use database_utils::{ReplicationServerId, UpstreamConfig};
-use dataflow::PersistenceParameters;
+use dataflow::{DomainTuning, PersistenceParameters};
 use readyset_client::consensus::{
     Authority, LocalAuthority, LocalAuthorityStore, NodeTypeSchedulingRestriction,
     WorkerSchedulingConfig,
 };
+use readyset_sql_passes::SelfJoinPolicy;
 use readyset_telemetry_reporter::TelemetrySender;
 use readyset_util::shutdown::{self, ShutdownSender};
 use tracing::info;
 
 use crate::controller::replication::ReplicationStrategy;
 use crate::handle::Handle;
-use crate::{Config, FrontierStrategy, ReuseConfigType, VolumeId};
+use crate::{Config, FrontierStrategy, PlacementStrategy, ReuseConfigType, VolumeId};
 
 /// Used to construct a worker.
 #[derive(Clone)]
@@ -91,6 +92,7 @@ impl Builder {
         builder.set_allow_mixed_comparisons(opts.enable_experimental_mixed_comparisons);
         builder.set_allow_straddled_joins(opts.enable_experimental_straddled_joins);
         builder.set_allow_post_lookup(opts.enable_experimental_post_lookup);
+        builder.set_self_join_policy(opts.self_join_policy);
         builder.set_worker_timeout(Duration::from_secs(opts.worker_request_timeout_seconds));
         builder.set_background_recovery_interval(Duration::from_secs(
             opts.background_recovery_interval_seconds,
@@ -98,6 +100,7 @@ impl Builder {
 
         builder.set_replication_strategy(opts.domain_replication_options.into());
         builder.set_verbose_domain_metrics(opts.verbose_domain_metrics);
+        builder.set_count_emitted_records(opts.count_emitted_records);
 
         if let Some(volume_id) = opts.volume_id {
             builder.set_volume_id(volume_id);
@@ -225,6 +228,12 @@ impl Builder {
         self.config.reuse = reuse_type;
     }
 
+    /// Set which self-joins to allow in queries for all subsequent migrations. See
+    /// [`SelfJoinPolicy`].
+    pub fn set_self_join_policy(&mut self, self_join_policy: SelfJoinPolicy) {
+        self.config.self_join_policy = self_join_policy;
+    }
+
     /// Set the value of [`controller::sql::Config::allow_topk`]
     pub fn set_allow_topk(&mut self, allow_topk: bool) {
         self.config.mir_config.allow_topk = allow_topk;
@@ -293,6 +302,11 @@ impl Builder {
         self.config.replication_strategy = replication_strategy
     }
 
+    /// Sets the strategy to use to decide which workers to schedule domains onto
+    pub fn set_placement_strategy(&mut self, placement_strategy: PlacementStrategy) {
+        self.config.placement_strategy = placement_strategy
+    }
+
     /// Configures this ReadySet server to accept only domains that contain reader nodes.
     ///
     /// Overwrites any previous call to [`no_readers`]
@@ -319,6 +333,19 @@ impl Builder {
         self.domain_scheduling_config.volume_id = Some(volume_id);
     }
 
+    /// Configures the maximum number of domain shard replicas that may be scheduled onto this
+    /// server. Migrations which would need to schedule more domains when every server is at its
+    /// maximum fail with a `WorkerCapacityExceeded` error.
+    pub fn set_max_domains(&mut self, max_domains: usize) {
+        self.domain_scheduling_config.max_domains = Some(max_domains);
+    }
+
+    /// Configures the NUMA node this server runs on, which is used to co-locate domains that
+    /// communicate with each other if the [`PlacementStrategy::NumaAware`] strategy is used.
+    pub fn set_numa_node(&mut self, numa_node: usize) {
+        self.domain_scheduling_config.numa_node = Some(numa_node);
+    }
+
     /// Set the value of [`Config::abort_on_task_failure`]. See the documentation of that field for
     /// more information.
     pub fn set_abort_on_task_failure(&mut self, abort_on_task_failure: bool) {
@@ -343,6 +370,18 @@ impl Builder {
         self.config.domain_config.verbose_metrics = value;
     }
 
+    /// Sets the value of [`Config::domain_config::count_emitted_records`]. See documentation of
+    /// that field for more information.
+    pub fn set_count_emitted_records(&mut self, value: bool) {
+        self.config.domain_config.count_emitted_records = value;
+    }
+
+    /// Sets the value of [`Config::domain_config::slow_node_threshold`]. See documentation of
+    /// that field for more information.
+    pub fn set_slow_node_threshold(&mut self, value: std::time::Duration) {
+        self.config.domain_config.slow_node_threshold = value;
+    }
+
     /// Sets the value of [`Config::domain_config::table_request_timeout`]. See documentation of
     /// that field for more information.
     pub fn set_table_request_timeout(&mut self, value: std::time::Duration) {
@@ -361,6 +400,14 @@ impl Builder {
         self.config.domain_config.eviction_kind = value;
     }
 
+    /// Sets the tunable parameters used by all domains, unless overridden for the domains created
+    /// by a particular migration with [`Migration::with_domain_tuning`].
+    ///
+    /// [`Migration::with_domain_tuning`]: crate::manual::Migration::with_domain_tuning
+    pub fn configure_domains(&mut self, tuning: DomainTuning) {
+        self.config.domain_config.tuning = tuning;
+    }
+
     /// Assigns a telemetry reporter to this ReadySet server
     pub fn set_telemetry_sender(&mut self, value: TelemetrySender) {
         self.telemetry = value;
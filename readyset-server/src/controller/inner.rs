@@ -26,7 +26,9 @@ use readyset_client::internal::ReplicaAddress;
 use readyset_client::metrics::recorded;
 use readyset_client::recipe::{ExtendRecipeResult, ExtendRecipeSpec, MigrationStatus};
 use readyset_client::status::{ReadySetControllerStatus, SnapshotStatus};
-use readyset_client::{GraphvizOptions, SingleKeyEviction, ViewCreateRequest, WorkerDescriptor};
+use readyset_client::{
+    GraphvizOptions, SingleKeyEviction, ViewCreateRequest, ViewRequest, WorkerDescriptor,
+};
 use readyset_errors::{internal_err, ReadySetError, ReadySetResult};
 use readyset_telemetry_reporter::TelemetrySender;
 use readyset_util::futures::abort_on_panic;
@@ -92,6 +94,10 @@ pub struct Leader {
     /// `/migration_status`.
     running_migrations: Mutex<SlotMap<DefaultKey, RunningMigration>>,
 
+    /// The names of all the views added by migrations which are being committed in the
+    /// background, which aren't ready to be read from yet.
+    pub(super) pending_views: Arc<parking_lot::Mutex<HashSet<Relation>>>,
+
     /// A channel that will be notified if a background task for the controller fails
     pub(super) background_task_failed: mpsc::Sender<ReadySetError>,
 
@@ -293,6 +299,10 @@ impl Leader {
                 let ds = self.dataflow_state_handle.read().await;
                 return_serialized!(ds.get_statistics().await);
             }
+            (&Method::GET | &Method::POST, "/recent_slow_operations") => {
+                let ds = self.dataflow_state_handle.read().await;
+                return_serialized!(ds.recent_slow_operations().await?);
+            }
             (&Method::GET | &Method::POST, "/instances") => {
                 let ds = self.dataflow_state_handle.read().await;
                 return_serialized!(ds.get_instances());
@@ -354,6 +364,10 @@ impl Leader {
                 let ds = self.dataflow_state_handle.read().await;
                 return_serialized!(ds.tables())
             }
+            (&Method::GET | &Method::POST, "/schema_version") => {
+                let ds = self.dataflow_state_handle.read().await;
+                return_serialized!(ds.current_schema_version())
+            }
             (&Method::POST, "/table_statuses") => {
                 let res = {
                     let ds = self.dataflow_state_handle.read().await;
@@ -429,6 +443,26 @@ impl Leader {
                 let ret = ds.table_builder(&body);
                 return_serialized!(ret);
             }
+            (&Method::POST, "/base_column_history") => {
+                let name = bincode::deserialize(&body)?;
+                let ds = self.dataflow_state_handle.read().await;
+                return_serialized!(ds.base_column_history(&name)?);
+            }
+            (&Method::POST, "/get_base_node_by_name") => {
+                let name = bincode::deserialize(&body)?;
+                let ds = self.dataflow_state_handle.read().await;
+                return_serialized!(ds.get_base_node_by_name(&name));
+            }
+            (&Method::POST, "/get_view_by_name") => {
+                let name = bincode::deserialize(&body)?;
+                let ds = self.dataflow_state_handle.read().await;
+                return_serialized!(ds.get_view_by_name(&name));
+            }
+            (&Method::POST, "/analyze_view") => {
+                let leaf = bincode::deserialize(&body)?;
+                let ds = self.dataflow_state_handle.read().await;
+                return_serialized!(ds.analyze_view(leaf).await?);
+            }
             (&Method::POST, "/table_builder_by_index") => {
                 // NOTE(eta): there is DELIBERATELY no `?` after the `table_builder` call,
                 // because the receiving end expects a `ReadySetResult` to be serialized.
@@ -440,9 +474,15 @@ impl Leader {
             (&Method::POST, "/view_builder") => {
                 // NOTE(eta): same as above applies
                 require_leader_ready()?;
-                let body = bincode::deserialize(&body)?;
+                let body: ViewRequest = bincode::deserialize(&body)?;
+                let name = body.name.clone();
                 let ds = self.dataflow_state_handle.read().await;
-                let ret = ds.view_builder(body);
+                let ret = match ds.view_builder(body) {
+                    Ok(None) if self.pending_views.lock().contains(&name) => Err(
+                        ReadySetError::ViewNotReady(name.display_unquoted().to_string()),
+                    ),
+                    ret => ret,
+                };
                 return_serialized!(ret);
             }
             (&Method::POST, "/get_info") => {
@@ -477,6 +517,32 @@ impl Leader {
                 }?;
                 return_serialized!(res);
             }
+            (&Method::POST, "/wait_for_stabilization") => {
+                let timeout = bincode::deserialize(&body)?;
+                let res = self
+                    .dataflow_state_handle
+                    .wait_for_stabilization(timeout)
+                    .await?;
+                return_serialized!(res);
+            }
+            (&Method::POST, "/node_ancestors") => {
+                let node = bincode::deserialize(&body)?;
+                let ds = self.dataflow_state_handle.read().await;
+                return_serialized!(ds.node_ancestors(node)?);
+            }
+            (&Method::POST, "/node_descendants") => {
+                let node = bincode::deserialize(&body)?;
+                let ds = self.dataflow_state_handle.read().await;
+                return_serialized!(ds.node_descendants(node)?);
+            }
+            (&Method::POST, "/health_report") => {
+                let deadline = bincode::deserialize(&body)?;
+                let res = {
+                    let ds = self.dataflow_state_handle.read().await;
+                    ds.health_report(deadline).await
+                };
+                return_serialized!(res);
+            }
             (&Method::POST, "/node_sizes") => {
                 let res = {
                     let ds = self.dataflow_state_handle.read().await;
@@ -537,6 +603,13 @@ impl Leader {
                 return_serialized!(key);
             }
 
+            (&Method::POST, "/set_write_tracing") => {
+                let sample_rate: f64 = bincode::deserialize(&body)?;
+                let mut writer = self.dataflow_state_handle.write().await;
+                writer.as_mut().set_write_tracing(sample_rate).await?;
+                self.dataflow_state_handle.commit(writer, authority).await?;
+                return_serialized!(());
+            }
             (&Method::GET, "/flush_partial") => {
                 let ret = {
                     let mut writer = self.dataflow_state_handle.write().await;
@@ -971,6 +1044,7 @@ impl Leader {
             background_recovery_interval,
             background_recovery_running: Arc::new(AtomicBool::new(false)),
             running_migrations: Default::default(),
+            pending_views: Default::default(),
             background_task_failed,
             running_recovery: None,
         }
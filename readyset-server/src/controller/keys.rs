@@ -1,9 +1,12 @@
 use std::collections::{HashMap, HashSet};
 use std::convert::TryFrom;
 use std::mem;
+use std::sync::Arc;
 
 pub use common::IndexRef;
 use dataflow::prelude::*;
+use parking_lot::Mutex;
+use petgraph::visit::Dfs;
 use readyset_errors::ReadySetError;
 use vec1::{vec1, Vec1};
 
@@ -21,6 +24,117 @@ pub fn provenance_of(
     trace(graph, path)
 }
 
+/// The paths traced by [`provenance_of`] for a single column, in *trace order*
+type ColumnTrace = Vec<Vec<(NodeIndex, Vec<Option<usize>>)>>;
+
+/// A single path from a column of a node in the graph back to the base table node that column
+/// originates from, as returned by [`ProvenanceCache::column_provenance`]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ProvenancePath {
+    /// The base table node at the end of the path
+    pub base: NodeIndex,
+    /// The index of the column in [`Self::base`] that the column is a copy of, or `None` if the
+    /// column is not a copy of any column in the base table (for example, if it's the output of an
+    /// aggregate)
+    pub column: Option<usize>,
+    /// All the nodes in the path, in *trace order*, with the node the column was traced from first
+    /// and the base table node last
+    pub nodes: Vec<NodeIndex>,
+}
+
+/// A cache of the result of tracing the provenance of individual columns of nodes in the graph,
+/// keyed by node and column index.
+///
+/// Tracing provenance requires walking the graph all the way up to base tables, so this avoids
+/// repeating that work every time (for example) the schema of a view is requested. Since the
+/// provenance of a node's columns can only change if that node gains new ancestors, the cache must
+/// be [invalidated](Self::invalidate_descendants) for all the descendants of the nodes added or
+/// removed by each migration.
+#[derive(Debug, Default)]
+pub(super) struct ProvenanceCache {
+    traces: Mutex<HashMap<(NodeIndex, usize), Arc<ColumnTrace>>>,
+}
+
+impl Clone for ProvenanceCache {
+    fn clone(&self) -> Self {
+        Self {
+            traces: Mutex::new(self.traces.lock().clone()),
+        }
+    }
+}
+
+impl ProvenanceCache {
+    /// Trace the provenance of the given column of the given node, as in [`provenance_of`],
+    /// reusing a previously cached trace if there is one.
+    pub(super) fn provenance_of(
+        &self,
+        graph: &Graph,
+        node: NodeIndex,
+        column: usize,
+    ) -> ReadySetResult<Arc<ColumnTrace>> {
+        if let Some(trace) = self.traces.lock().get(&(node, column)) {
+            return Ok(Arc::clone(trace));
+        }
+
+        let trace = Arc::new(provenance_of(graph, node, &[column])?);
+        self.traces
+            .lock()
+            .insert((node, column), Arc::clone(&trace));
+        Ok(trace)
+    }
+
+    /// Returns a [`ProvenancePath`] for each path from the given column of the given node back to
+    /// a base table node.
+    pub(super) fn column_provenance(
+        &self,
+        graph: &Graph,
+        node: NodeIndex,
+        column: usize,
+    ) -> ReadySetResult<Vec<ProvenancePath>> {
+        self.provenance_of(graph, node, column)?
+            .iter()
+            .map(|path| {
+                let (base, columns) = path
+                    .last()
+                    .ok_or_else(|| internal_err!("Traced an empty provenance path"))?;
+                Ok(ProvenancePath {
+                    base: *base,
+                    column: columns.first().copied().flatten(),
+                    nodes: path.iter().map(|(ni, _)| *ni).collect(),
+                })
+            })
+            .collect()
+    }
+
+    /// Remove all cached traces for the given nodes, and all of their descendants in the graph.
+    pub(super) fn invalidate_descendants<I>(&self, graph: &Graph, nodes: I)
+    where
+        I: IntoIterator<Item = NodeIndex>,
+    {
+        let mut traces = self.traces.lock();
+        if traces.is_empty() {
+            return;
+        }
+
+        let mut invalidated = HashSet::new();
+        for node in nodes {
+            if invalidated.contains(&node) {
+                continue;
+            }
+            let mut dfs = Dfs::new(graph, node);
+            while let Some(descendant) = dfs.next(graph) {
+                invalidated.insert(descendant);
+            }
+        }
+        traces.retain(|(node, _), _| !invalidated.contains(node));
+    }
+
+    #[cfg(test)]
+    pub(super) fn is_cached(&self, node: NodeIndex, column: usize) -> bool {
+        self.traces.lock().contains_key(&(node, column))
+    }
+}
+
 /// The return value of `deduce_column_source`.
 #[derive(Debug)]
 struct DeducedColumnSource {
@@ -592,6 +706,7 @@ fn trace(
 
 #[cfg(test)]
 mod tests {
+    use dataflow::ops::grouped::aggregate::Aggregation;
     use dataflow::utils::make_columns;
     use dataflow::{node, ops, Expr};
     use nom_sql::OrderType;
@@ -1221,4 +1336,113 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn provenance_cache() {
+        let (mut g, a, b) = bases();
+
+        let x = g.add_node(node::Node::new(
+            "x",
+            make_columns(&["a1", "a2b1", "b2"]),
+            ops::NodeOperator::Join(ops::join::Join::new(
+                a,
+                b,
+                ops::join::JoinType::Inner,
+                vec![(1, 0)],
+                vec![
+                    (ops::Side::Left, 0),
+                    (ops::Side::Left, 1),
+                    (ops::Side::Right, 1),
+                ],
+            )),
+        ));
+        g.add_edge(a, x, ());
+        g.add_edge(b, x, ());
+
+        let mut agg_node = node::Node::new(
+            "agg",
+            make_columns(&["b2", "count"]),
+            ops::NodeOperator::Aggregation(
+                Aggregation::Count
+                    .over(x, 0, &[2], &DfType::Unknown)
+                    .unwrap(),
+            ),
+        );
+        agg_node.on_connected(&g);
+        let agg = g.add_node(agg_node);
+        g.add_edge(x, agg, ());
+
+        let reader = g.add_node(node::Node::new(
+            "reader",
+            make_columns(&["b2", "count"]),
+            node::special::Reader::new(agg, Default::default()),
+        ));
+        g.add_edge(agg, reader, ());
+
+        let cache = ProvenanceCache::default();
+
+        // The group-by column of the aggregate is a copy of a column from the right side of the
+        // join
+        assert_eq!(
+            cache.column_provenance(&g, agg, 0).unwrap(),
+            vec![ProvenancePath {
+                base: b,
+                column: Some(1),
+                nodes: vec![agg, x, b],
+            }]
+        );
+        // The output of the aggregate isn't a copy of any base column, and is replayed through the
+        // left side of the join
+        assert_eq!(
+            cache.column_provenance(&g, agg, 1).unwrap(),
+            vec![ProvenancePath {
+                base: a,
+                column: None,
+                nodes: vec![agg, x, a],
+            }]
+        );
+        // A column passed through the join from the left
+        assert_eq!(
+            cache.column_provenance(&g, x, 0).unwrap(),
+            vec![ProvenancePath {
+                base: a,
+                column: Some(0),
+                nodes: vec![x, a],
+            }]
+        );
+        assert_eq!(
+            cache.column_provenance(&g, reader, 0).unwrap(),
+            vec![ProvenancePath {
+                base: b,
+                column: Some(1),
+                nodes: vec![reader, agg, x, b],
+            }]
+        );
+        assert!(cache.is_cached(reader, 0));
+        assert!(cache.is_cached(agg, 0));
+
+        // Route the reader through a new ingress node, giving it a new ancestor
+        let ingress = g.add_node(node::Node::new(
+            "ingress",
+            make_columns(&["b2", "count"]),
+            node::special::Ingress,
+        ));
+        let old_edge = g.find_edge(agg, reader).unwrap();
+        g.remove_edge(old_edge);
+        g.add_edge(agg, ingress, ());
+        g.add_edge(ingress, reader, ());
+        cache.invalidate_descendants(&g, [ingress]);
+
+        assert!(!cache.is_cached(reader, 0));
+        assert!(cache.is_cached(agg, 0));
+        assert!(cache.is_cached(x, 0));
+        assert_eq!(
+            cache.column_provenance(&g, reader, 0).unwrap(),
+            vec![ProvenancePath {
+                base: b,
+                column: Some(1),
+                nodes: vec![reader, ingress, agg, x, b],
+            }]
+        );
+    }
 }
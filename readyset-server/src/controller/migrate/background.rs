@@ -0,0 +1,133 @@
+//! Handles to [`Migration`]s which are committed in the background, as started by
+//! [`Handle::migrate_async`].
+//!
+//! The changes made by a background migration are made to a copy of the dataflow state, which only
+//! replaces the state used to look up views once the migration has been committed in full. This
+//! means that reads from existing views keep working while the migration is being committed, and
+//! aborting a migration before then leaves the dataflow state unchanged. Any domains booted by the
+//! migration before it was aborted are killed, but just like a migration which fails to apply, no
+//! attempt is made to undo any changes which were already sent to existing domains (such as adding
+//! nodes to them).
+//!
+//! [`Migration`]: crate::controller::migrate::Migration
+//! [`Handle::migrate_async`]: crate::Handle::migrate_async
+
+use std::time::Duration;
+
+use readyset_errors::ReadySetError;
+use tokio::sync::{oneshot, watch};
+
+use crate::controller::migrate::progress::MigrationPhase;
+
+/// The status of a migration which is being committed in the background, as returned by
+/// [`MigrationHandle::status`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BackgroundMigrationStatus {
+    /// The migration is still being committed
+    Committing {
+        /// The most recent phase of the commit to complete, if any
+        last_phase: Option<MigrationPhase>,
+        /// The number of phases of the commit which have completed so far
+        phases_completed: usize,
+    },
+    /// The migration has been committed, and all the views it added can be read from
+    Done,
+    /// The migration failed to commit
+    Failed(ReadySetError),
+    /// The migration was aborted via [`MigrationHandle::abort`] before it was committed
+    Aborted,
+}
+
+impl Default for BackgroundMigrationStatus {
+    fn default() -> Self {
+        Self::Committing {
+            last_phase: None,
+            phases_completed: 0,
+        }
+    }
+}
+
+impl BackgroundMigrationStatus {
+    /// Returns true if the migration is no longer being committed, either because it finished or
+    /// because it failed or was aborted
+    pub fn is_finished(&self) -> bool {
+        !matches!(self, Self::Committing { .. })
+    }
+
+    /// Record that the given phase of committing the migration has completed
+    pub(in crate::controller) fn phase_completed(&mut self, phase: MigrationPhase) {
+        if let Self::Committing {
+            last_phase,
+            phases_completed,
+        } = self
+        {
+            *last_phase = Some(phase);
+            *phases_completed += 1;
+        }
+    }
+}
+
+/// A handle to a migration which is being committed in the background, returned by
+/// [`Handle::migrate_async`].
+///
+/// Dropping the handle does *not* abort the migration.
+///
+/// [`Handle::migrate_async`]: crate::Handle::migrate_async
+pub struct MigrationHandle<T> {
+    result: T,
+    status: watch::Receiver<BackgroundMigrationStatus>,
+    abort_tx: Option<oneshot::Sender<()>>,
+}
+
+impl<T> MigrationHandle<T> {
+    pub(crate) fn new(
+        result: T,
+        status: watch::Receiver<BackgroundMigrationStatus>,
+        abort_tx: oneshot::Sender<()>,
+    ) -> Self {
+        Self {
+            result,
+            status,
+            abort_tx: Some(abort_tx),
+        }
+    }
+
+    /// Returns a reference to the value returned by the function that made the changes for this
+    /// migration
+    pub fn result(&self) -> &T {
+        &self.result
+    }
+
+    /// Consume this handle, returning the value returned by the function that made the changes for
+    /// this migration
+    pub fn into_result(self) -> T {
+        self.result
+    }
+
+    /// Returns the current status of the migration
+    pub fn status(&self) -> BackgroundMigrationStatus {
+        self.status.borrow().clone()
+    }
+
+    /// Wait up to `timeout` for the migration to finish, returning its status once it does or once
+    /// the timeout elapses, whichever comes first.
+    pub async fn wait(&mut self, timeout: Duration) -> BackgroundMigrationStatus {
+        // An error here means the background task was dropped, in which case the final status was
+        // already sent
+        let _ = tokio::time::timeout(timeout, self.status.wait_for(|s| s.is_finished())).await;
+        self.status()
+    }
+
+    /// Abort the migration, if it hasn't been committed yet.
+    ///
+    /// The migration is aborted asynchronously - use [`MigrationHandle::wait`] to wait for it to
+    /// stop. If the migration finishes committing before it sees the request to abort, its status
+    /// will still be [`BackgroundMigrationStatus::Done`].
+    pub fn abort(&mut self) {
+        if let Some(abort_tx) = self.abort_tx.take() {
+            // The receiver is only dropped once the migration has finished, in which case there's
+            // nothing to abort
+            let _ = abort_tx.send(());
+        }
+    }
+}
@@ -0,0 +1,152 @@
+//! Migrations which install a group of related queries all at once, as started by
+//! [`Handle::migrate_group`].
+//!
+//! Every query in a group is added to the graph by the same [`Migration`], so either all of them
+//! are installed, or none are: if adding any one of the queries or committing the migration fails,
+//! even if the error from adding the query is ignored, the copy of the dataflow state the migration
+//! ran against is discarded, and any domains it booted are killed, just like for any other failed
+//! migration (see [`DfState::try_migrate`]). The views added by the group are marked as pending
+//! until the migration has been committed, so none of them can be looked up before all of them can.
+//!
+//! [`Handle::migrate_group`]: crate::Handle::migrate_group
+//! [`DfState::try_migrate`]: crate::controller::state::DfState::try_migrate
+
+use std::collections::HashMap;
+use std::ops::{Deref, DerefMut};
+
+use dataflow::prelude::*;
+use nom_sql::Relation;
+
+use crate::controller::migrate::Migration;
+
+/// A [`Migration`] which records the boundaries of each of the logical queries it adds to the
+/// graph, passed to the function given to [`Handle::migrate_group`].
+///
+/// Derefs to the underlying [`Migration`], so nodes shared between the queries of the group can be
+/// added directly.
+///
+/// [`Handle::migrate_group`]: crate::Handle::migrate_group
+pub struct GroupMigration<'a, 'df> {
+    migration: &'a mut Migration<'df>,
+    /// The name and leaf node of each query added so far, in the order they were added
+    queries: Vec<(String, NodeIndex)>,
+    /// The error returned by the first query which failed to be added, if any. The group fails
+    /// with this error even if the function adding the queries ignores it, since the failed
+    /// query may have left some of its nodes in the graph.
+    failure: Option<ReadySetError>,
+}
+
+impl<'a, 'df> Deref for GroupMigration<'a, 'df> {
+    type Target = Migration<'df>;
+
+    fn deref(&self) -> &Self::Target {
+        self.migration
+    }
+}
+
+impl<'a, 'df> DerefMut for GroupMigration<'a, 'df> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.migration
+    }
+}
+
+impl<'a, 'df> GroupMigration<'a, 'df> {
+    pub(in crate::controller) fn new(migration: &'a mut Migration<'df>) -> Self {
+        Self {
+            migration,
+            queries: vec![],
+            failure: None,
+        }
+    }
+
+    /// Add the query named `name` to the group, by calling `f` to add its nodes to the migration.
+    /// `f` returns the leaf node of the query, which is returned from this method.
+    ///
+    /// If `f` returns an error, the error is returned wrapped in
+    /// [`ReadySetError::SelectQueryCreationFailed`] along with the name of the query. The whole
+    /// group is then rolled back with the first such error, whether or not it's propagated out of
+    /// the group.
+    pub fn query<F>(&mut self, name: &str, f: F) -> ReadySetResult<NodeIndex>
+    where
+        F: FnOnce(&mut Migration<'df>) -> ReadySetResult<NodeIndex>,
+    {
+        let res = if self.queries.iter().any(|(n, _)| n == name) {
+            Err(ReadySetError::ViewAlreadyExists(name.to_owned()))
+        } else {
+            f(&mut *self.migration).map_err(|e| ReadySetError::SelectQueryCreationFailed {
+                qname: name.to_owned(),
+                source: Box::new(e),
+            })
+        };
+
+        match res {
+            Ok(leaf) => {
+                self.queries.push((name.to_owned(), leaf));
+                Ok(leaf)
+            }
+            Err(e) => {
+                self.failure.get_or_insert_with(|| e.clone());
+                Err(e)
+            }
+        }
+    }
+
+    /// Returns the error from the first query which failed to be added to the group, if any
+    pub(in crate::controller) fn check(&self) -> ReadySetResult<()> {
+        match &self.failure {
+            Some(e) => Err(e.clone()),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns the [`GroupOutcome`] for the queries added to the group so far
+    pub(in crate::controller) fn outcome(&self) -> GroupOutcome {
+        let queries = self
+            .queries
+            .iter()
+            .map(|(name, leaf)| {
+                let reader = self.migration.readers.get(leaf).copied();
+                let view_name = reader
+                    .and_then(|ri| self.migration.dataflow_state.ingredients.node_weight(ri))
+                    .map(|n| n.name().clone());
+                (
+                    name.clone(),
+                    GroupQuery {
+                        leaf: *leaf,
+                        reader,
+                        view_name,
+                    },
+                )
+            })
+            .collect();
+        GroupOutcome { queries }
+    }
+}
+
+/// The nodes added for a single query in a group migration
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GroupQuery {
+    /// The leaf node of the query
+    pub leaf: NodeIndex,
+    /// The reader maintaining the query's leaf node, if the query was given one
+    pub reader: Option<NodeIndex>,
+    /// The name the query's view can be looked up by, if the query was given a reader
+    pub view_name: Option<Relation>,
+}
+
+/// The result of successfully committing a group migration with [`Handle::migrate_group`]
+///
+/// [`Handle::migrate_group`]: crate::Handle::migrate_group
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GroupOutcome {
+    /// The nodes added for each query in the group, by the name given to
+    /// [`GroupMigration::query`]
+    pub queries: HashMap<String, GroupQuery>,
+}
+
+impl GroupOutcome {
+    /// Returns the nodes added for the query with the given name, if it was part of the group
+    pub fn get(&self, name: &str) -> Option<&GroupQuery> {
+        self.queries.get(name)
+    }
+}
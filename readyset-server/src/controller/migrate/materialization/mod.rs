@@ -16,6 +16,7 @@ use serde::{Deserialize, Serialize};
 use tracing::{debug, error, info_span, trace};
 
 use crate::controller::keys::{self, RawReplayPath};
+use crate::controller::migrate::progress::{MigrationPhase, ProgressReporter};
 use crate::controller::migrate::DomainMigrationPlan;
 use crate::controller::state::Graphviz;
 
@@ -1062,6 +1063,7 @@ impl Materializations {
         graph: &mut Graph,
         new: &HashSet<NodeIndex>,
         dmp: &mut DomainMigrationPlan,
+        progress: &mut ProgressReporter,
     ) -> Result<(), ReadySetError> {
         let mut reindex = Vec::with_capacity(new.len());
         let mut make = Vec::with_capacity(new.len());
@@ -1090,6 +1092,7 @@ impl Materializations {
 
         // first, we add any new indices to existing nodes
         for node in reindex {
+            progress.start_phase();
             let mut index_on = self.added.remove(&node).unwrap();
 
             // are they trying to make a non-materialized node materialized?
@@ -1180,10 +1183,15 @@ impl Materializations {
                 self.setup(node, &mut index_on, &mut non_ready_nodes, graph, dmp)?;
             }
             index_on.clear();
+            progress.finish_phase(MigrationPhase::Materialization {
+                node,
+                mode: self.get_status(node, &graph[node]),
+            });
         }
 
         // then, we start prepping new nodes
         for ni in &make {
+            progress.start_phase();
             let n = &graph[*ni];
             let mut index_on = self
                 .added
@@ -1222,6 +1230,11 @@ impl Materializations {
                     "reconstruction completed"
                 );
             }
+
+            let mode = self.get_status(*ni, n);
+            if mode != MaterializationStatus::Not {
+                progress.finish_phase(MigrationPhase::Materialization { node: *ni, mode });
+            }
         }
 
         // Wait for each of the nodes to be ready which we didn't already (eg because we wanted to
@@ -37,28 +37,39 @@ use std::time::{Duration, Instant};
 use array2::Array2;
 use dataflow::node::Column;
 use dataflow::prelude::*;
-use dataflow::{node, DomainRequest, ReaderProcessing};
+use dataflow::{node, DomainRequest, DomainTuning, ReaderProcessing};
+#[cfg(feature = "failure_injection")]
+use failpoint_macros::set_failpoint;
 use metrics::{counter, histogram};
 use nom_sql::Relation;
+#[cfg(feature = "failure_injection")]
+use readyset_client::failpoints;
 use readyset_client::metrics::recorded;
 use readyset_client::{KeyColumnIdx, ViewPlaceholder};
 use readyset_data::{DfType, Dialect};
+use readyset_errors::set_failpoint_return_err;
+use tokio::sync::oneshot;
 use tokio::time::sleep;
 use tokio_retry::strategy::ExponentialBackoff;
 use tracing::{debug, debug_span, error, info, info_span, instrument, trace};
 
+use crate::controller::keys::ProvenancePath;
 use crate::controller::migrate::materialization::InvalidEdge;
 use crate::controller::migrate::node_changes::{MigrationNodeChanges, NodeChanges};
+use crate::controller::migrate::progress::{MigrationEvent, MigrationPhase, ProgressReporter};
 use crate::controller::migrate::scheduling::Scheduler;
 use crate::controller::state::DfState;
 use crate::controller::WorkerIdentifier;
 
 pub(crate) mod assignment;
 mod augmentation;
+pub(crate) mod background;
+pub(crate) mod group;
 pub(crate) mod materialization;
 pub(in crate::controller) mod node_changes;
+pub(crate) mod progress;
 pub(in crate::controller) mod routing;
-pub(in crate::controller) mod scheduling;
+pub(crate) mod scheduling;
 mod sharding;
 
 /// The base delay used when sending follow up requests to a domain, for the exponential backoff
@@ -334,12 +345,16 @@ pub struct DomainMigrationPlan {
     failed_placement: Vec<ReplicaAddress>,
     /// A map of valid domain indices to the settings for that domain.
     domains: HashMap<DomainIndex, DomainSettings>,
+    /// If set, applying the plan stops with [`ReadySetError::MigrationAborted`] once this receives
+    /// a value.
+    abort: Option<oneshot::Receiver<()>>,
 }
 
 /// A set of stored data sufficient to apply a migration.
 pub struct MigrationPlan<'df> {
     dataflow_state: &'df mut DfState,
     dmp: DomainMigrationPlan,
+    progress: ProgressReporter,
 }
 
 impl<'df> MigrationPlan<'df> {
@@ -353,6 +368,7 @@ impl<'df> MigrationPlan<'df> {
         let MigrationPlan {
             dataflow_state,
             dmp,
+            mut progress,
         } = self;
 
         debug!(
@@ -362,10 +378,12 @@ impl<'df> MigrationPlan<'df> {
         );
 
         let start = Instant::now();
+        progress.start_phase();
 
         match dmp.apply(dataflow_state).await {
             Ok(_) => {
                 debug!(ms = %start.elapsed().as_millis(), "migration plan applied");
+                progress.finish_phase(MigrationPhase::Finalize);
                 Ok(())
             }
             Err(e) => {
@@ -387,6 +405,16 @@ impl DomainMigrationPlan {
             mode,
             domains,
             failed_placement: vec![],
+            abort: None,
+        }
+    }
+
+    /// Returns [`ReadySetError::MigrationAborted`] if the plan has been asked to abort via the
+    /// receiver passed to [`Migration::abort_on`]
+    fn check_aborted(&mut self) -> ReadySetResult<()> {
+        match self.abort.as_mut().map(|abort| abort.try_recv()) {
+            Some(Ok(())) => Err(ReadySetError::MigrationAborted),
+            _ => Ok(()),
         }
     }
 
@@ -442,7 +470,10 @@ impl DomainMigrationPlan {
 
     /// Apply all stored changes using the given controller object, placing new domains and sending
     /// messages added since the last time this method was called.
-    pub async fn apply(self, mainline: &mut DfState) -> ReadySetResult<()> {
+    ///
+    /// If the plan is aborted, the domains placed before the abort are left running in `mainline`,
+    /// so that the caller can kill them.
+    pub async fn apply(mut self, mainline: &mut DfState) -> ReadySetResult<()> {
         // First, tell all the workers to run the domains
         //
         // While we're doing this, we also maintain a map of all the domains' shard replicas which
@@ -452,7 +483,8 @@ impl DomainMigrationPlan {
             .iter()
             .map(|(di, dh)| (*di, dh.placed_shard_replicas()))
             .collect::<HashMap<_, _>>();
-        for place in self.place {
+        for place in std::mem::take(&mut self.place) {
+            self.check_aborted()?;
             match just_placed_shard_replicas.entry(place.idx) {
                 hash_map::Entry::Occupied(mut e) => {
                     for (pos, addr) in place.shard_replica_workers.entries() {
@@ -483,7 +515,7 @@ impl DomainMigrationPlan {
         // nodes that might not be ready yet.
         // TODO(fran): This feels yucky, we should revisit this in the future and think of a better
         //  abstraction.
-        let mut stored = self.stored;
+        let mut stored = std::mem::take(&mut self.stored);
         let create_exponential_backoff = || {
             ExponentialBackoff::from_millis(DOMAIN_REQUEST_DELAY_BASE_BACKOFF_MS)
                 .factor(DOMAIN_REQUEST_DELAY_BACKOFF_FACTOR)
@@ -491,6 +523,8 @@ impl DomainMigrationPlan {
         };
         let mut retry_strategy = create_exponential_backoff();
         while let Some(req) = stored.pop_front() {
+            set_failpoint_return_err!(failpoints::MIGRATION_APPLY_DOMAIN_REQUEST);
+            self.check_aborted()?;
             if let Some(req) = req.apply(mainline, &just_placed_shard_replicas).await? {
                 // Initializing base table nodes might take a lot of time, so we try to wait using
                 // an exponential backoff strategy.
@@ -675,12 +709,21 @@ pub struct Migration<'df> {
     pub(super) readers: HashMap<NodeIndex, NodeIndex>,
     pub(super) worker: Option<WorkerIdentifier>,
     pub(super) dialect: Dialect,
+    pub(super) progress: ProgressReporter,
+    /// The index of the first node added to the graph by this migration
+    pub(super) first_new_node: usize,
+    /// Tunable parameters for the domains created by this migration, if overridden with
+    /// [`Migration::with_domain_tuning`]
+    pub(super) domain_tuning: Option<DomainTuning>,
+    /// Aborts committing this migration when sent to, if set with [`Migration::abort_on`]
+    pub(super) abort: Option<oneshot::Receiver<()>>,
 
     pub(super) start: Instant,
 }
 
 impl<'df> Migration<'df> {
     pub(super) fn new(dataflow_state: &'df mut DfState, dialect: Dialect) -> Self {
+        let first_new_node = dataflow_state.ingredients.node_count();
         Self {
             dataflow_state,
             changes: Default::default(),
@@ -688,10 +731,92 @@ impl<'df> Migration<'df> {
             readers: Default::default(),
             worker: None,
             dialect,
+            progress: Default::default(),
+            first_new_node,
+            domain_tuning: None,
+            abort: None,
             start: Instant::now(),
         }
     }
 
+    /// Register a callback to be called with a [`MigrationEvent`] as each phase of committing this
+    /// migration completes.
+    ///
+    /// The callback is run on a separate task, so it may be called after the migration has
+    /// finished committing. See the [`progress`] module for more information.
+    pub fn on_progress<F>(&mut self, f: F)
+    where
+        F: FnMut(MigrationEvent) + Send + 'static,
+    {
+        self.progress = ProgressReporter::new(f);
+    }
+
+    /// Use the given tunable parameters for all the domains created by this migration, instead of
+    /// the ones the server was configured with.
+    ///
+    /// Domains which already exist keep their current parameters, even if this migration adds
+    /// nodes to them.
+    pub fn with_domain_tuning(&mut self, tuning: DomainTuning) {
+        self.domain_tuning = Some(tuning);
+    }
+
+    /// Stop committing this migration with [`ReadySetError::MigrationAborted`] once `abort`
+    /// receives a value.
+    ///
+    /// The abort is only checked for between booting each new domain and sending each message to
+    /// the domains, so the migration should be committed with [`DfState::try_migrate`] to kill any
+    /// domains it booted before it was aborted.
+    pub(in crate::controller) fn abort_on(&mut self, abort: oneshot::Receiver<()>) {
+        self.abort = Some(abort);
+    }
+
+    /// Returns a [`ProvenancePath`] for each path from the given column of the given node back to
+    /// a base table node.
+    ///
+    /// Results are cached across migrations until the node gains new ancestors.
+    pub fn column_provenance(
+        &self,
+        node: NodeIndex,
+        column: usize,
+    ) -> ReadySetResult<Vec<ProvenancePath>> {
+        self.dataflow_state.column_provenance(node, column)
+    }
+
+    /// Returns true if the provenance of the given column of the given node is currently cached
+    #[cfg(test)]
+    pub(crate) fn is_provenance_cached(&self, node: NodeIndex, column: usize) -> bool {
+        self.dataflow_state.provenance.is_cached(node, column)
+    }
+
+    /// Returns the index of the column named `name` in the given node.
+    ///
+    /// Returns [`ReadySetError::NoSuchColumn`] if the node has no column with that name.
+    pub fn column_index(&self, node: NodeIndex, name: &str) -> ReadySetResult<usize> {
+        self.dataflow_state
+            .ingredients
+            .node_weight(node)
+            .ok_or_else(|| ReadySetError::NoSuchNode(node.index()))?
+            .columns()
+            .iter()
+            .position(|col| col.name() == name)
+            .ok_or_else(|| ReadySetError::NoSuchColumn(name.to_owned()))
+    }
+
+    /// Returns the version of the schema as of the start of this migration. Committing the
+    /// migration advances the version by one.
+    pub fn current_schema_version(&self) -> usize {
+        self.dataflow_state.current_schema_version()
+    }
+
+    /// Returns the names of all the views added so far in this migration
+    pub(in crate::controller) fn added_view_names(&self) -> Vec<Relation> {
+        self.readers
+            .values()
+            .filter_map(|ri| self.dataflow_state.ingredients.node_weight(*ri))
+            .map(|n| n.name().clone())
+            .collect()
+    }
+
     /// Add the given `Ingredient` to the dataflow graph.
     ///
     /// The returned identifier can later be used to refer to the added ingredient.
@@ -961,9 +1086,57 @@ impl<'df> Migration<'df> {
         r.set_mapping(placeholder_map);
     }
 
+    /// Re-key the view maintained on the given node on the given columns, for example as
+    /// recommended by [`DfState::analyze_view`], returning the index of the new reader for the
+    /// view.
+    ///
+    /// A new reader keyed on `key` is created with the same name, index type, and reader
+    /// processing as the current reader for the view, and the current reader is removed, both when
+    /// this migration is committed. The new reader has no placeholder mapping, so it can be looked
+    /// up by key directly, but not by executing the query it was originally created for.
+    ///
+    /// If a reader for the view was already added earlier in this migration (including by a
+    /// previous call to this method), that reader is the one that gets replaced.
+    pub fn rekey_view(&mut self, leaf: NodeIndex, key: Vec<usize>) -> ReadySetResult<NodeIndex> {
+        // Forget any reader added for the view in this migration, so that `ensure_reader_for`
+        // creates a new one rather than handing us back the reader we're about to drop
+        let old_reader_node = match self.readers.remove(&leaf) {
+            Some(reader) => reader,
+            None => self
+                .dataflow_state
+                .any_reader_for(leaf)
+                .ok_or(ReadySetError::ReaderNotFound)?,
+        };
+        #[allow(clippy::indexing_slicing)] // `any_reader_for` returns valid indices
+        let node = &self.dataflow_state.ingredients[old_reader_node];
+        let name = node.name().clone();
+        let old_reader = node.as_reader().ok_or(ReadySetError::ReaderNotFound)?;
+        let index = Index::new(old_reader.index_type().unwrap_or(IndexType::HashMap), key);
+        let reader_processing = old_reader.reader_processing().clone();
+
+        let new_reader_node = self.ensure_reader_for(leaf, Some(name), reader_processing);
+        invariant_ne!(
+            new_reader_node,
+            old_reader_node,
+            "rekeying a view must create a new reader"
+        );
+        // we know it's a reader - we just made it!
+        #[allow(clippy::indexing_slicing, clippy::unwrap_used)]
+        self.dataflow_state.ingredients[new_reader_node]
+            .as_mut_reader()
+            .unwrap()
+            .set_index(&index);
+
+        self.changes.drop_node(old_reader_node);
+        Ok(new_reader_node)
+    }
+
     /// Build a `MigrationPlan` for this migration, and apply it if the planning stage succeeds.
+    ///
+    /// Advances the version of the schema exactly once, before planning the migration.
     pub(super) async fn commit(self, dry_run: bool) -> ReadySetResult<()> {
         let start = self.start;
+        let schema_version = self.dataflow_state.advance_schema_version();
 
         let plan = self
             .plan()
@@ -978,6 +1151,7 @@ impl<'df> Migration<'df> {
 
         debug!(
             ms = ?start.elapsed().as_millis(),
+            schema_version,
             "migration planning completed"
         );
 
@@ -1006,29 +1180,58 @@ impl<'df> Migration<'df> {
         );
 
         let mut added = 0;
-        let mut dropped = 0;
+        let mut dropped_nodes = vec![];
         let columns = self.columns;
+        let first_new_node = self.first_new_node;
         let worker = self.worker;
+        let mut progress = self.progress;
+        let domain_tuning = self.domain_tuning;
+        dmp.abort = self.abort;
         for change in self.changes.into_iter() {
             match change {
                 NodeChanges::Add(new_nodes) => {
                     added += new_nodes.len();
-                    dmp.extend(plan_add_nodes(dataflow_state, new_nodes, &worker)?)
+                    dmp.extend(plan_add_nodes(
+                        dataflow_state,
+                        new_nodes,
+                        &worker,
+                        &mut progress,
+                    )?)
                 }
                 NodeChanges::Drop(drop_nodes) => {
-                    dropped += drop_nodes.len();
+                    dropped_nodes.extend(drop_nodes.iter().copied());
                     dmp.extend(plan_drop_nodes(dataflow_state, drop_nodes)?)
                 }
             }
         }
 
+        if let Some(tuning) = domain_tuning {
+            for place in &dmp.place {
+                dataflow_state
+                    .domain_tuning_overrides
+                    .insert(place.idx, tuning.clone());
+            }
+        }
+
+        // The only nodes whose ancestors can have changed are the descendants of the nodes added
+        // (including while planning, eg for routing) or dropped by this migration
+        let changed_nodes = (first_new_node..dataflow_state.ingredients.node_count())
+            .map(NodeIndex::new)
+            .chain(dropped_nodes.iter().copied())
+            .collect::<Vec<_>>();
+        dataflow_state
+            .provenance
+            .invalidate_descendants(&dataflow_state.ingredients, changed_nodes);
+
         // We have successfully made a valid graph! Now we can inform the dmp of all the
         // changes
+        progress.start_phase();
         inform_col_changes(&mut dmp, &columns, &dataflow_state.ingredients)?;
+        progress.finish_phase(MigrationPhase::ColumnChanges);
 
         debug!(
             added_nodes = added,
-            dropped_nodes = dropped,
+            dropped_nodes = dropped_nodes.len(),
             "finalizing migration"
         );
 
@@ -1040,6 +1243,7 @@ impl<'df> Migration<'df> {
         Ok(MigrationPlan {
             dataflow_state,
             dmp,
+            progress,
         })
     }
 }
@@ -1048,7 +1252,9 @@ fn plan_add_nodes(
     dataflow_state: &mut DfState,
     mut new_nodes: HashSet<NodeIndex>,
     worker: &Option<WorkerIdentifier>,
+    progress: &mut ProgressReporter,
 ) -> ReadySetResult<DomainMigrationPlan> {
+    progress.start_phase();
     let mut topo = topo_order(dataflow_state, &new_nodes);
 
     // Tracks partially materialized nodes that were duplicated as fully materialized in this
@@ -1069,9 +1275,11 @@ fn plan_add_nodes(
     } else {
         HashMap::default()
     };
+    progress.finish_phase(MigrationPhase::Sharding);
 
     // Assign domains
     assignment::assign(dataflow_state, &topo)?;
+    progress.finish_phase(MigrationPhase::DomainAssignment);
 
     // Set up ingress and egress nodes
     let swapped1 = routing::add(dataflow_state, &mut new_nodes, &topo)?;
@@ -1118,6 +1326,7 @@ fn plan_add_nodes(
         }
     }
     let mut swapped = swapped0;
+    progress.finish_phase(MigrationPhase::Routing);
     loop {
         let mut sorted_new = new_nodes.iter().collect::<Vec<_>>();
         sorted_new.sort();
@@ -1294,6 +1503,8 @@ fn plan_add_nodes(
             dataflow_state.domain_settings(),
         );
         let mut scheduler = Scheduler::new(dataflow_state, worker)?;
+        progress.start_phase();
+        let mut new_domains = 0;
 
         for domain in changed_domains {
             if dataflow_state.domains.contains_key(&domain) {
@@ -1306,6 +1517,16 @@ fn plan_add_nodes(
             let nodes = uninformed_domain_nodes.remove(&domain).unwrap();
             let worker_shards = scheduler.schedule_domain(domain, &nodes)?;
 
+            // Replicas which can't be placed are normally left for recovery to place once more
+            // workers are available, but workers that are full won't become available on their own
+            if worker_shards.entries().any(|(_, worker)| worker.is_none())
+                && scheduler.all_workers_at_capacity(&nodes)
+            {
+                return Err(ReadySetError::WorkerCapacityExceeded {
+                    domain_index: domain.index(),
+                });
+            }
+
             for ((shard, replica), worker) in worker_shards.entries() {
                 if worker.is_none() {
                     dmp.replica_failed_placement(ReplicaAddress {
@@ -1326,7 +1547,9 @@ fn plan_add_nodes(
                     num_replicas,
                 },
             );
+            new_domains += 1;
         }
+        progress.finish_phase(MigrationPhase::BootingDomains { count: new_domains });
 
         // And now, the last piece of the puzzle -- set up materializations
         debug!("initializing new materializations");
@@ -1402,21 +1625,25 @@ fn plan_add_nodes(
 
             // Add any new nodes to existing domains (they'll also ignore all updates for now)
             debug!("mutating existing domains");
+            progress.start_phase();
             augmentation::inform(
                 dataflow_state,
                 &mut dmp,
                 uninformed_domain_nodes,
                 &new_nodes,
             )?;
+            progress.finish_phase(MigrationPhase::Augmentation);
 
             // Set up inter-domain connections
             debug!("bringing up inter-domain connections");
             routing::connect(&dataflow_state.ingredients, &mut dmp, &new_nodes)?;
+            progress.finish_phase(MigrationPhase::InterDomainConnections);
 
             dataflow_state.materializations.commit(
                 &mut dataflow_state.ingredients,
                 &new_nodes,
                 &mut dmp,
+                progress,
             )?;
 
             dataflow_state
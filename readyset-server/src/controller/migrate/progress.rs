@@ -0,0 +1,114 @@
+//! Structured progress reporting for [`Migration`]s.
+//!
+//! Committing a large migration can take a long time, so callers can register a callback with
+//! [`Migration::on_progress`] to be notified as each phase of the migration completes. Every event
+//! is also logged at the `debug` level.
+//!
+//! [`Migration`]: crate::controller::migrate::Migration
+//! [`Migration::on_progress`]: crate::controller::migrate::Migration::on_progress
+
+use std::time::{Duration, Instant};
+
+use petgraph::graph::NodeIndex;
+use readyset_client::internal::MaterializationStatus;
+use tokio::sync::mpsc::{self, UnboundedSender};
+use tracing::debug;
+
+/// A phase of committing a migration, as reported in a [`MigrationEvent`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MigrationPhase {
+    /// Sharding the new nodes in the graph
+    Sharding,
+    /// Assigning new nodes to domains
+    DomainAssignment,
+    /// Adding ingress and egress nodes between domains
+    Routing,
+    /// Scheduling new domains onto workers
+    BootingDomains {
+        /// The number of new domains
+        count: usize,
+    },
+    /// Informing existing domains of their new nodes
+    Augmentation,
+    /// Informing base tables of added, dropped, and retyped columns
+    ColumnChanges,
+    /// Setting up connections between new and existing domains
+    InterDomainConnections,
+    /// Planning the replays needed to materialize a single new node
+    Materialization {
+        /// The node being materialized
+        node: NodeIndex,
+        /// How the node is materialized
+        mode: MaterializationStatus,
+    },
+    /// Applying the migration plan to the running domains, including running all the replays
+    /// planned for new materializations
+    Finalize,
+}
+
+/// An event emitted when a [`MigrationPhase`] completes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MigrationEvent {
+    /// The phase which completed
+    pub phase: MigrationPhase,
+    /// The amount of time spent in the phase
+    pub elapsed: Duration,
+}
+
+/// Tracks the current phase of a migration, and sends a [`MigrationEvent`] to the callback
+/// registered for the migration (if any) when each phase completes.
+pub(in crate::controller) struct ProgressReporter {
+    tx: Option<UnboundedSender<MigrationEvent>>,
+    phase_start: Instant,
+}
+
+impl Default for ProgressReporter {
+    fn default() -> Self {
+        Self {
+            tx: None,
+            phase_start: Instant::now(),
+        }
+    }
+}
+
+impl ProgressReporter {
+    /// Construct a new [`ProgressReporter`] which calls `f` with each event.
+    ///
+    /// `f` is run on its own task rather than by the migration itself, so that it can never block
+    /// the migration (or deadlock it by eg making a request to the controller while the migration
+    /// holds the lock on the dataflow state). As such, events may be delivered after the migration
+    /// has finished committing.
+    pub(in crate::controller) fn new<F>(mut f: F) -> Self
+    where
+        F: FnMut(MigrationEvent) + Send + 'static,
+    {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            while let Some(event) = rx.recv().await {
+                f(event);
+            }
+        });
+
+        Self {
+            tx: Some(tx),
+            phase_start: Instant::now(),
+        }
+    }
+
+    /// Start timing a new phase
+    pub(in crate::controller) fn start_phase(&mut self) {
+        self.phase_start = Instant::now();
+    }
+
+    /// Report that the given phase has completed, and start timing the next phase
+    pub(in crate::controller) fn finish_phase(&mut self, phase: MigrationPhase) {
+        let elapsed = self.phase_start.elapsed();
+        debug!(?phase, ms = %elapsed.as_millis(), "migration phase completed");
+        if let Some(tx) = &self.tx {
+            // The receiver is only dropped if the callback panicked, in which case there's nobody
+            // left to report progress to
+            let _ = tx.send(MigrationEvent { phase, elapsed });
+        }
+        self.start_phase();
+    }
+}
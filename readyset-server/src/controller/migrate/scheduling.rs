@@ -9,27 +9,63 @@
 //!    all shards of all domains within the migration will be scheduled to that worker, *if* it's
 //!    valid
 //! 3. Otherwise, for each replica of each shard in the domain, we first filter the set of workers
-//!    down to only workers that aren't running a different replica of the same domain shard, then
-//!    either: a. Run the domain shard on the worker matching its [placement restrictions][], if it
-//!    has any, or b. If the domain contains base tables, run it on the worker running the smallest
-//!    number of other base tables, or otherwise c. Run it on the worker that has the smallest
-//!    number of domain shards scheduled onto it
+//!    down to only workers that aren't running a different replica of the same domain shard, and
+//!    that haven't reached their [maximum number of domains][max_domains], then either: a. Run the
+//!    domain shard on the worker matching its [placement restrictions][], if it has any, or b. If
+//!    the domain contains base tables, run it on the worker running the smallest number of other
+//!    base tables, or otherwise c. Run it on the worker that has the smallest number of domain
+//!    shards scheduled onto it
+//! 4. With the [NUMA-aware placement strategy][numa], before applying the load-balancing criteria
+//!    in 3b and 3c, we prefer workers on the [NUMA node][numa_node] which is running the most
+//!    replicas of the domains that the domain communicates with - but only among the workers whose
+//!    load is within [`NUMA_AFFINITY_MAX_IMBALANCE`] of the least-loaded worker
 //!
 //! [reader_only]: Worker::reader_only
 //! [worker]: Migration::worker
+//! [max_domains]: readyset_client::consensus::WorkerSchedulingConfig::max_domains
 //! [placement restrictions]: DomainPlacementRestriction
+//! [numa]: PlacementStrategy::NumaAware
+//! [numa_node]: readyset_client::consensus::WorkerSchedulingConfig::numa_node
 
-use std::collections::{HashMap, HashSet};
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet, VecDeque};
 
 use array2::Array2;
 use dataflow::prelude::*;
 use readyset_client::consensus::NodeTypeSchedulingRestriction;
 use readyset_client::internal::DomainIndex;
+use serde::{Deserialize, Serialize};
 use tracing::{instrument, trace};
 
 use crate::controller::state::DfState;
 use crate::controller::{DomainPlacementRestriction, NodeRestrictionKey, Worker, WorkerIdentifier};
 
+/// The strategy used to pick which of the workers that a domain shard replica could run on it
+/// should be scheduled onto
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+pub enum PlacementStrategy {
+    /// Balance the number of domain shards (or base table domain shards) run by each worker
+    #[default]
+    LoadBalanced,
+    /// Prefer to run domains on workers on the same NUMA node as the domains they communicate
+    /// with, to avoid the cost of cross-socket memory access, and balance load within that NUMA
+    /// node.
+    ///
+    /// Affinity only takes precedence over load among workers whose load is within
+    /// [`NUMA_AFFINITY_MAX_IMBALANCE`] of the least-loaded worker, so a connected dataflow graph
+    /// (eg every query reading from the same set of tables) still spreads across every NUMA node,
+    /// rather than piling onto the first one it was scheduled on.
+    NumaAware,
+}
+
+/// The maximum number of domain shard replicas (or base table domain shard replicas, for domains
+/// with base tables) more than the least-loaded worker that a worker may be running for the
+/// [NUMA-aware placement strategy][PlacementStrategy::NumaAware] to prefer it for its NUMA node.
+///
+/// Workers any more heavily loaded than this are only picked once every worker within the margin is
+/// unavailable, which bounds how unbalanced the load can get in exchange for NUMA affinity.
+pub const NUMA_AFFINITY_MAX_IMBALANCE: usize = 2;
+
 /// Verifies that the worker `worker` meets the domain placement restrictions of all dataflow nodes
 /// that will be placed in a new domain on the worker.  If the set of restrictions in this domain
 /// are too stringent, no worker may be able to satisfy the domain placement.
@@ -42,6 +78,16 @@ fn worker_meets_restrictions(
         .all(|r| r.worker_volume == worker.domain_scheduling_config.volume_id)
 }
 
+/// Returns true if the worker `worker` is allowed to run a domain, given whether or not that domain
+/// contains a reader node
+fn worker_allows_domain(worker: &Worker, is_reader_domain: bool) -> bool {
+    match worker.domain_scheduling_config.reader_nodes {
+        NodeTypeSchedulingRestriction::None => true,
+        NodeTypeSchedulingRestriction::OnlyWithNodeType => is_reader_domain,
+        NodeTypeSchedulingRestriction::NeverWithNodeType => !is_reader_domain,
+    }
+}
+
 /// Statistics about the domains scheduled onto a worker
 #[derive(Default, Clone, Copy)]
 struct WorkerStats {
@@ -61,6 +107,8 @@ pub(crate) struct Scheduler<'state> {
     valid_workers: Vec<(&'state WorkerIdentifier, &'state Worker)>,
     worker_stats: HashMap<&'state WorkerIdentifier, WorkerStats>,
     scheduled_shards: HashMap<&'state WorkerIdentifier, HashSet<(DomainIndex, usize)>>,
+    /// The workers running replicas of each domain, including those scheduled by this scheduler
+    domain_workers: HashMap<DomainIndex, Vec<&'state WorkerIdentifier>>,
     dataflow_state: &'state DfState,
 }
 
@@ -80,6 +128,7 @@ impl<'state> Scheduler<'state> {
         let mut worker_stats: HashMap<&WorkerIdentifier, WorkerStats> = HashMap::new();
         let mut scheduled_shards: HashMap<&WorkerIdentifier, HashSet<(DomainIndex, usize)>> =
             HashMap::new();
+        let mut domain_workers: HashMap<DomainIndex, Vec<&WorkerIdentifier>> = HashMap::new();
 
         for (di, dh) in &dataflow_state.domains {
             let is_base_table_domain = dataflow_state.domain_nodes[di]
@@ -94,6 +143,7 @@ impl<'state> Scheduler<'state> {
                     }
 
                     scheduled_shards.entry(wi).or_default().insert((*di, shard));
+                    domain_workers.entry(*di).or_default().push(wi);
                 }
             }
         }
@@ -102,10 +152,101 @@ impl<'state> Scheduler<'state> {
             valid_workers,
             worker_stats,
             scheduled_shards,
+            domain_workers,
             dataflow_state,
         })
     }
 
+    /// Returns the NUMA node of the given worker, if it has one
+    fn numa_node(&self, wi: &WorkerIdentifier) -> Option<usize> {
+        self.dataflow_state
+            .workers
+            .get(wi)
+            .and_then(|worker| worker.domain_scheduling_config.numa_node)
+    }
+
+    /// Returns the number of replicas of the domains which the domain containing `nodes`
+    /// communicates with that are running on each NUMA node.
+    ///
+    /// A domain communicates with the scheduled domains containing nodes adjacent to its own, and
+    /// also (transitively) with the scheduled domains adjacent to any domain it's adjacent to that
+    /// hasn't been scheduled yet. This way, when a migration adds a join and the base tables it
+    /// reads from, the domains of the base tables are related to each other via the (not yet
+    /// scheduled) domain of the join.
+    fn related_numa_nodes(
+        &self,
+        domain_index: DomainIndex,
+        nodes: &[NodeIndex],
+    ) -> HashMap<usize, usize> {
+        let ingredients = &self.dataflow_state.ingredients;
+        let mut res = HashMap::new();
+        let mut visited = nodes.iter().copied().collect::<HashSet<_>>();
+        let mut related_domains = HashSet::new();
+        let mut queue = nodes.iter().copied().collect::<VecDeque<_>>();
+        while let Some(ni) = queue.pop_front() {
+            for neighbor in ingredients.neighbors_undirected(ni) {
+                if neighbor == self.dataflow_state.source || !visited.insert(neighbor) {
+                    continue;
+                }
+                let Some(node) = ingredients.node_weight(neighbor) else {
+                    continue;
+                };
+                if node.is_dropped() || !node.has_domain() {
+                    continue;
+                }
+
+                let di = node.domain();
+                match self.domain_workers.get(&di) {
+                    Some(workers) if di != domain_index => {
+                        if related_domains.insert(di) {
+                            for numa_node in workers.iter().filter_map(|wi| self.numa_node(wi)) {
+                                *res.entry(numa_node).or_default() += 1;
+                            }
+                        }
+                    }
+                    _ => queue.push_back(neighbor),
+                }
+            }
+        }
+        res
+    }
+
+    /// Returns true if the given worker is already running its maximum number of domain shard
+    /// replicas
+    fn at_capacity(&self, wi: &WorkerIdentifier, worker: &Worker) -> bool {
+        worker
+            .domain_scheduling_config
+            .max_domains
+            .map_or(false, |max_domains| {
+                self.worker_stats
+                    .get(wi)
+                    .map_or(0, |stats| stats.num_domain_shard_replicas)
+                    >= max_domains
+            })
+    }
+
+    /// Returns true if there are workers which the domain containing `nodes` could be scheduled
+    /// onto, but all of them are already running their maximum number of domain shard replicas
+    ///
+    /// Workers which aren't allowed to run the domain at all (eg reader-only workers, for a domain
+    /// without a reader) are not considered, since having room on them doesn't help the domain.
+    ///
+    /// # Invariants
+    ///
+    /// * All the nodes in `nodes` must exist in `self.dataflow_state.ingredients`
+    #[allow(clippy::indexing_slicing)] // documented invariant
+    pub(crate) fn all_workers_at_capacity(&self, nodes: &[NodeIndex]) -> bool {
+        let is_reader_domain = nodes
+            .iter()
+            .any(|n| self.dataflow_state.ingredients[*n].is_reader());
+        let mut workers = self
+            .valid_workers
+            .iter()
+            .filter(|(_, worker)| worker_allows_domain(worker, is_reader_domain))
+            .peekable();
+        workers.peek().is_some() && workers.all(|(wi, worker)| self.at_capacity(wi, worker))
+    }
+
     /// Decide which workers the shards of the given `domain` (with the given list of `nodes`)
     /// should run on
     ///
@@ -145,13 +286,16 @@ impl<'state> Scheduler<'state> {
             invariant_eq!(num_replicas, 1);
         }
 
-        let workers = self.valid_workers.iter().filter(|(_, worker)| {
-            match worker.domain_scheduling_config.reader_nodes {
-                NodeTypeSchedulingRestriction::None => true,
-                NodeTypeSchedulingRestriction::OnlyWithNodeType => is_reader_domain,
-                NodeTypeSchedulingRestriction::NeverWithNodeType => !is_reader_domain,
-            }
-        });
+        let workers = self
+            .valid_workers
+            .iter()
+            .filter(|(_, worker)| worker_allows_domain(worker, is_reader_domain));
+
+        let related_numa_nodes = match self.dataflow_state.placement_strategy {
+            PlacementStrategy::LoadBalanced => HashMap::new(),
+            PlacementStrategy::NumaAware => self.related_numa_nodes(domain_index, nodes),
+        };
+        trace!(?related_numa_nodes);
 
         let mut res = Vec::with_capacity(num_shards);
         for shard in 0..num_shards {
@@ -171,13 +315,15 @@ impl<'state> Scheduler<'state> {
                 }
 
                 // Filter out any workers that have a different replica of the same domain shard, to
-                // avoid scheduling two replicas of the same shard onto the same worker
+                // avoid scheduling two replicas of the same shard onto the same worker, and any
+                // workers that are already running as many domain shards as they're allowed to
                 let available_workers = workers
                     .clone()
-                    .filter(|(wi, _)| {
+                    .filter(|(wi, worker)| {
                         self.scheduled_shards
                             .get(wi)
                             .map_or(true, |shards| !shards.contains(&(domain_index, shard)))
+                            && !self.at_capacity(wi, worker)
                     })
                     .collect::<Vec<_>>();
 
@@ -199,9 +345,8 @@ impl<'state> Scheduler<'state> {
                 let worker_id = if dataflow_node_restrictions.is_empty() {
                     // If there are no placement restrictions, pick the node based on load-balancing
                     // heuristics
-                    available_workers.iter().min_by_key(|(wi, _)| {
+                    let worker_load = |wi: &WorkerIdentifier| {
                         let stats = self.worker_stats.get(wi).copied().unwrap_or_default();
-
                         if is_base_table_domain {
                             // If there are base tables in the domain, find the worker running the
                             // smallest number of base table domain shards
@@ -211,6 +356,29 @@ impl<'state> Scheduler<'state> {
                             // shards overall
                             stats.num_domain_shard_replicas
                         }
+                    };
+                    let min_load = available_workers
+                        .iter()
+                        .map(|(wi, _)| worker_load(wi))
+                        .min()
+                        .unwrap_or(0);
+
+                    available_workers.iter().min_by_key(|(wi, worker)| {
+                        let load = worker_load(wi);
+                        let within_margin = load - min_load <= NUMA_AFFINITY_MAX_IMBALANCE;
+
+                        // Among the workers whose load is close enough to the least-loaded worker,
+                        // prefer the NUMA node running the most replicas of related domains. This
+                        // is always 0 unless we're using the NUMA-aware placement strategy.
+                        let numa_affinity = worker
+                            .domain_scheduling_config
+                            .numa_node
+                            .filter(|_| within_margin)
+                            .and_then(|numa_node| related_numa_nodes.get(&numa_node))
+                            .copied()
+                            .unwrap_or(0);
+
+                        (!within_margin, Reverse(numa_affinity), load)
                     })
                 } else {
                     // Otherwise, if there are placement restrictions, we select the first worker
@@ -234,6 +402,10 @@ impl<'state> Scheduler<'state> {
                         .entry(worker_id)
                         .or_default()
                         .insert((domain_index, shard));
+                    self.domain_workers
+                        .entry(domain_index)
+                        .or_default()
+                        .push(worker_id);
 
                     let stats = self.worker_stats.entry(worker_id).or_default();
                     stats.num_domain_shard_replicas += 1;
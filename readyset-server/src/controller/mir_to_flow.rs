@@ -910,11 +910,7 @@ fn make_project_node(
     for expr in emit {
         let (name, ty, source, expr) = match expr {
             ProjectExpr::Column(c) => {
-                let index = graph
-                    .find_source_for_child_column(parent, c)
-                    .ok_or_else(|| {
-                        internal_err!("could not find source for child column: {:?}", c)
-                    })?;
+                let index = graph.column_position(parent, c)?;
                 let parent = &parent_cols[index];
 
                 (
@@ -968,12 +964,8 @@ fn make_distinct_node(
 
     let grp_by_column_ids = group_by
         .iter()
-        .map(|c| {
-            graph
-                .find_source_for_child_column(parent, c)
-                .ok_or_else(|| internal_err!("could not find source for child column: {:?}", c))
-        })
-        .collect::<Result<Vec<_>, _>>()?;
+        .map(|c| graph.column_position(parent, c))
+        .collect::<ReadySetResult<Vec<_>>>()?;
 
     let mut cols = grp_by_column_ids
         .iter()
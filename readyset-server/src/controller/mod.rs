@@ -39,6 +39,8 @@ use tracing_futures::Instrument;
 use url::Url;
 
 use crate::controller::inner::Leader;
+use crate::controller::migrate::background::BackgroundMigrationStatus;
+use crate::controller::migrate::group::{GroupMigration, GroupOutcome};
 use crate::controller::migrate::Migration;
 use crate::controller::sql::Recipe;
 use crate::controller::state::DfState;
@@ -48,7 +50,7 @@ use crate::{Config, VolumeId};
 
 mod domain_handle;
 mod inner;
-mod keys;
+pub(crate) mod keys;
 pub(crate) mod migrate; // crate viz for tests
 mod mir_to_flow;
 pub(crate) mod replication;
@@ -134,7 +136,7 @@ impl ControllerState {
         let recipe = Recipe::with_config(
             crate::sql::Config {
                 reuse_type: config.reuse,
-                ..Default::default()
+                self_join_policy: config.self_join_policy,
             },
             config.mir_config.clone(),
             permissive_writes,
@@ -153,6 +155,7 @@ impl ControllerState {
             HashMap::new(),
             cc,
             config.replication_strategy,
+            config.placement_strategy,
         );
 
         Self {
@@ -327,6 +330,34 @@ pub enum HandleRequest {
         /// The result of the migration gets sent down here.
         done_tx: tokio::sync::oneshot::Sender<ReadySetResult<()>>,
     },
+    /// Performs a manual migration of a group of queries, restoring the state as it was before
+    /// the migration if any part of it fails.
+    PerformGroupMigration {
+        /// The migration function to perform.
+        func: Box<dyn FnOnce(&mut GroupMigration<'_, '_>) -> ReadySetResult<()> + Send + 'static>,
+        /// The SQL dialect to use for all migrated queries and types
+        dialect: Dialect,
+        /// The result of the migration gets sent down here.
+        done_tx: tokio::sync::oneshot::Sender<ReadySetResult<GroupOutcome>>,
+    },
+    /// Performs a manual migration, committing it in the background.
+    PerformBackgroundMigration {
+        /// The migration function to perform.
+        func: Box<
+            dyn FnOnce(&mut crate::controller::migrate::Migration<'_>) -> ReadySetResult<()>
+                + Send
+                + 'static,
+        >,
+        /// The SQL dialect to use for all migrated queries and types
+        dialect: Dialect,
+        /// The status of the migration gets sent down here as it's committed.
+        status_tx: tokio::sync::watch::Sender<BackgroundMigrationStatus>,
+        /// Aborts the migration if it hasn't been committed yet when sent to.
+        abort_rx: tokio::sync::oneshot::Receiver<()>,
+        /// Sent to once the changes have been made to the graph, and the views added by the
+        /// migration have been marked as pending.
+        ready_tx: tokio::sync::oneshot::Sender<()>,
+    },
     /// Set a failpoint
     #[cfg(feature = "failure_injection")]
     Failpoint {
@@ -515,6 +546,128 @@ impl Controller {
                     return Err(ReadySetError::NotLeader);
                 }
             }
+            HandleRequest::PerformGroupMigration {
+                func,
+                dialect,
+                done_tx,
+            } => {
+                let mut guard = self.inner.write().await;
+                let Some(ref mut inner) = *guard else {
+                    return Err(ReadySetError::NotLeader);
+                };
+                let pending_views = Arc::clone(&inner.pending_views);
+                let mut added_views = vec![];
+                let mut writer = inner.dataflow_state_handle.write().await;
+                let existing_domains = writer.as_ref().domain_indices();
+                // The views added by the group can't be looked up until the whole group has been
+                // committed
+                let res = writer
+                    .as_mut()
+                    .try_migrate(false, dialect, |m| {
+                        let mut group = GroupMigration::new(m);
+                        func(&mut group)?;
+                        group.check()?;
+                        let outcome = group.outcome();
+                        added_views = m.added_view_names();
+                        pending_views.lock().extend(added_views.iter().cloned());
+                        Ok(outcome)
+                    })
+                    .await;
+                // Dropping the writer without committing it discards all the changes made to the
+                // dataflow state by the migration
+                let res = match res {
+                    Ok(outcome) => inner
+                        .dataflow_state_handle
+                        .commit_migration(writer, &existing_domains, &self.authority)
+                        .await
+                        .map(|()| outcome),
+                    Err(e) => Err(e),
+                };
+
+                {
+                    let mut pending_views = pending_views.lock();
+                    for view in &added_views {
+                        pending_views.remove(view);
+                    }
+                }
+
+                if done_tx.send(res).is_err() {
+                    warn!("handle-based group migration sender hung up!");
+                }
+            }
+            HandleRequest::PerformBackgroundMigration {
+                func,
+                dialect,
+                status_tx,
+                abort_rx,
+                ready_tx,
+            } => {
+                let guard = self.inner.read().await;
+                let Some(ref inner) = *guard else {
+                    return Err(ReadySetError::NotLeader);
+                };
+                let dataflow_state_handle = Arc::clone(&inner.dataflow_state_handle);
+                let pending_views = Arc::clone(&inner.pending_views);
+                let authority = Arc::clone(&self.authority);
+                let status_tx = Arc::new(status_tx);
+
+                // Only the graph changes are made before we handle the next request - the rest of
+                // the migration is committed in the background, so that it doesn't block the
+                // controller
+                tokio::spawn(async move {
+                    let mut added_views = vec![];
+                    let mut writer = dataflow_state_handle.write().await;
+                    let existing_domains = writer.as_ref().domain_indices();
+                    let progress_tx = Arc::clone(&status_tx);
+                    // An abort is seen by the migration itself while it's being committed, rather
+                    // than by dropping it part-way through, so that `try_migrate` can kill any
+                    // domains it booted before then
+                    let res = writer
+                        .as_mut()
+                        .try_migrate(false, dialect, |m| {
+                            m.on_progress(move |event| {
+                                progress_tx.send_modify(|s| s.phase_completed(event.phase))
+                            });
+                            m.abort_on(abort_rx);
+                            func(m)?;
+                            added_views = m.added_view_names();
+                            pending_views.lock().extend(added_views.iter().cloned());
+                            // The receiver is dropped if the handle stopped waiting, in which case
+                            // there's no one to tell
+                            let _ = ready_tx.send(());
+                            Ok(())
+                        })
+                        .await;
+                    // Dropping the writer without committing it discards all the changes made to
+                    // the dataflow state by the migration
+                    let res = match res {
+                        Ok(()) => {
+                            dataflow_state_handle
+                                .commit_migration(writer, &existing_domains, &authority)
+                                .await
+                        }
+                        Err(e) => Err(e),
+                    };
+
+                    {
+                        let mut pending_views = pending_views.lock();
+                        for view in &added_views {
+                            pending_views.remove(view);
+                        }
+                    }
+
+                    status_tx.send_replace(match res {
+                        Ok(()) => BackgroundMigrationStatus::Done,
+                        Err(e) if e.any_cause(|e| matches!(e, ReadySetError::MigrationAborted)) => {
+                            BackgroundMigrationStatus::Aborted
+                        }
+                        Err(e) => {
+                            warn!(error = %e, "background migration failed");
+                            BackgroundMigrationStatus::Failed(e)
+                        }
+                    });
+                });
+            }
             #[cfg(feature = "failure_injection")]
             HandleRequest::Failpoint {
                 name,
@@ -1041,6 +1194,7 @@ impl AuthorityLeaderElectionState {
                                 }
                                 state.dataflow_state.domain_config = self.config.domain_config.clone();
                                 state.dataflow_state.replication_strategy = self.config.replication_strategy;
+                                state.dataflow_state.placement_strategy = self.config.placement_strategy;
                                 state.config = self.config.clone();
                                 Ok(state)
                             }
@@ -1383,7 +1537,7 @@ async fn handle_controller_request(
 #[cfg(test)]
 mod tests {
 
-    use std::collections::BTreeMap;
+    use std::collections::{BTreeMap, HashSet};
 
     use dataflow::DomainIndex;
     use nom_sql::{
@@ -1391,6 +1545,8 @@ mod tests {
         NotReplicatedReason, Relation,
     };
     use readyset_client::debug::info::KeyCount;
+    #[cfg(feature = "failure_injection")]
+    use readyset_client::failpoints;
     use readyset_client::recipe::changelist::{Change, ChangeList};
     use readyset_client::{
         PersistencePoint, TableOperation, TableReplicationStatus, TableStatus, ViewCreateRequest,
@@ -1924,4 +2080,96 @@ mod tests {
 
         shutdown_tx.shutdown().await;
     }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn failed_migration_restores_state() {
+        let (mut noria, shutdown_tx) = start_simple("failed_migration_restores_state").await;
+        noria
+            .extend_recipe(
+                ChangeList::from_str(
+                    "CREATE TABLE t1 (id INT PRIMARY KEY, val INT);
+                     CREATE CACHE q1 FROM SELECT * FROM t1 WHERE id = ?;",
+                    DataDialect::DEFAULT_MYSQL,
+                )
+                .unwrap(),
+            )
+            .await
+            .unwrap();
+        let tables = noria.tables().await.unwrap();
+        let views = noria.views().await.unwrap();
+        let domains = noria.domains().await.unwrap();
+
+        // The table is valid, but the cache references a table that doesn't exist, so the
+        // migration as a whole should fail
+        noria
+            .extend_recipe(
+                ChangeList::from_str(
+                    "CREATE TABLE t2 (id INT PRIMARY KEY);
+                     CREATE CACHE q2 FROM SELECT * FROM nonexistent WHERE id = ?;",
+                    DataDialect::DEFAULT_MYSQL,
+                )
+                .unwrap(),
+            )
+            .await
+            .unwrap_err();
+
+        assert_eq!(noria.tables().await.unwrap(), tables);
+        assert_eq!(noria.views().await.unwrap(), views);
+        assert_eq!(
+            noria
+                .domains()
+                .await
+                .unwrap()
+                .into_keys()
+                .collect::<HashSet<_>>(),
+            domains.keys().copied().collect::<HashSet<_>>()
+        );
+
+        // Fail a migration after its new domains have been booted, while it's being committed
+        #[cfg(feature = "failure_injection")]
+        {
+            fail::cfg(failpoints::MIGRATION_APPLY_DOMAIN_REQUEST, "return").unwrap();
+            noria
+                .extend_recipe(
+                    ChangeList::from_str(
+                        "CREATE TABLE t3 (id INT PRIMARY KEY);
+                         CREATE CACHE q3 FROM SELECT * FROM t3 WHERE id = ?;",
+                        DataDialect::DEFAULT_MYSQL,
+                    )
+                    .unwrap(),
+                )
+                .await
+                .unwrap_err();
+            fail::remove(failpoints::MIGRATION_APPLY_DOMAIN_REQUEST);
+
+            assert_eq!(noria.tables().await.unwrap(), tables);
+            assert_eq!(noria.views().await.unwrap(), views);
+            assert_eq!(
+                noria
+                    .domains()
+                    .await
+                    .unwrap()
+                    .into_keys()
+                    .collect::<HashSet<_>>(),
+                domains.keys().copied().collect::<HashSet<_>>()
+            );
+        }
+
+        // Migrations after the failed one should still work
+        noria
+            .extend_recipe(
+                ChangeList::from_str(
+                    "CREATE TABLE t2 (id INT PRIMARY KEY);
+                     CREATE CACHE q2 FROM SELECT * FROM t2 WHERE id = ?;",
+                    DataDialect::DEFAULT_MYSQL,
+                )
+                .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert!(noria.tables().await.unwrap().contains_key(&"t2".into()));
+        assert!(noria.views().await.unwrap().contains_key(&"q2".into()));
+
+        shutdown_tx.shutdown().await;
+    }
 }
@@ -1,10 +1,11 @@
+use dataflow::ops::NodeOperator;
 use dataflow::prelude::*;
-use nom_sql::Column;
+use nom_sql::{Column, ColumnConstraint};
 use readyset_client::{ColumnBase, ColumnSchema};
 use readyset_data::DfType;
 use tracing::trace;
 
-use super::keys::provenance_of;
+use super::keys::ProvenanceCache;
 use super::sql::{BaseSchema, Recipe, Schema};
 
 type Path<'a> = &'a [(
@@ -85,8 +86,46 @@ fn get_base_for_column(
     Ok(None)
 }
 
+/// Returns whether the column at the end of the given path may contain NULL values, either because
+/// it originates in a nullable column or expression, or because it passes through the right-hand
+/// side of a left join. `base` is the base table column the column originates in, if any.
+fn column_nullable_on_path(path: Path, graph: &Graph, base: Option<&ColumnBase>) -> bool {
+    for (ni, cols) in path {
+        if let (Some(NodeOperator::Join(join)), Some(Some(col))) =
+            (graph[*ni].as_internal(), cols.first())
+        {
+            if join.is_nullable_column(*col) {
+                return true;
+            }
+        }
+    }
+
+    // column originates at last element of the path whose second element is not None
+    let Some((ni, Some(col))) = path
+        .iter()
+        .rfind(|e| e.1.iter().any(Option::is_some))
+        .map(|(ni, cols)| (ni, cols.first().copied().flatten()))
+    else {
+        return true;
+    };
+
+    match graph[*ni].as_internal() {
+        Some(NodeOperator::Aggregation(agg)) => agg.output_col_nullable(),
+        Some(NodeOperator::Extremum(agg)) => agg.output_col_nullable(),
+        Some(NodeOperator::Concat(agg)) => agg.output_col_nullable(),
+        Some(NodeOperator::Project(project)) => project.is_nullable_column(col),
+        Some(_) => true,
+        None => !base.map_or(false, |base| {
+            base.constraints
+                .iter()
+                .any(|c| matches!(c, ColumnConstraint::NotNull | ColumnConstraint::PrimaryKey))
+        }),
+    }
+}
+
 pub(super) fn column_schema(
     graph: &Graph,
+    provenance: &ProvenanceCache,
     view: NodeIndex,
     recipe: &Recipe,
     column_index: usize,
@@ -96,18 +135,24 @@ pub(super) fn column_schema(
         column_index,
         view.index()
     );
-    let paths = provenance_of(graph, view, &[column_index])?;
+    let paths = provenance.provenance_of(graph, view, column_index)?;
     let vn = &graph[view];
 
     let mut col_type = &DfType::Unknown;
     let mut col_base = None;
-    for ref p in paths {
+    // A column is nullable if it might be NULL along any of its paths, or if we don't know where
+    // it comes from at all
+    let mut nullable = paths.is_empty();
+    for p in paths.iter() {
         trace!("considering path {:?}", p);
 
+        let path_base = get_base_for_column(p, graph, recipe)?;
+        nullable |= column_nullable_on_path(p, graph, path_base.as_ref());
+
         let ty = trace_column_type_on_path(p, graph)?;
         if !ty.is_unknown() {
             col_type = ty;
-            col_base = get_base_for_column(p, graph, recipe)?;
+            col_base = path_base;
         }
     }
 
@@ -118,5 +163,6 @@ pub(super) fn column_schema(
             table: Some(vn.name().clone()),
         },
         column_type: col_type.clone(),
+        nullable,
     }))
 }
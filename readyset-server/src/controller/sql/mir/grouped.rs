@@ -7,7 +7,7 @@ use mir::{Column, NodeIndex};
 use nom_sql::analysis::ReferredColumns;
 use nom_sql::FunctionExpr::*;
 use nom_sql::{self, DialectDisplay, Expr, FieldDefinitionExpr, Relation, SqlIdentifier};
-use readyset_errors::{unsupported, ReadySetError, ReadySetResult};
+use readyset_errors::{unsupported_feature, ReadySetError, ReadySetResult, UnsupportedFeature};
 use readyset_sql_passes::is_aggregate;
 
 use crate::controller::sql::mir::join::make_joins_for_aggregates;
@@ -308,15 +308,24 @@ pub(super) fn post_lookup_aggregates(
             column: Column::named(alias.clone()).aliased_as_table(query_name.clone()),
             function: match function {
                 Avg { .. } => {
-                    unsupported!("Average is not supported as a post-lookup aggregate")
+                    unsupported_feature!(
+                        UnsupportedFeature::PostLookup,
+                        "Average is not supported as a post-lookup aggregate"
+                    )
                 }
                 Count { distinct, .. } if *distinct => {
                     // TODO(REA-4289)
-                    unsupported!("COUNT(DISTINCT ...) is not supported as a post-lookup aggregate")
+                    unsupported_feature!(
+                        UnsupportedFeature::PostLookup,
+                        "COUNT(DISTINCT ...) is not supported as a post-lookup aggregate"
+                    )
                 }
                 Sum { distinct, .. } if *distinct => {
                     // TODO(REA-4289)
-                    unsupported!("SUM(DISTINCT ...) is not supported as a post-lookup aggregate")
+                    unsupported_feature!(
+                        UnsupportedFeature::PostLookup,
+                        "SUM(DISTINCT ...) is not supported as a post-lookup aggregate"
+                    )
                 }
                 // Count and sum are handled the same way, as re-aggregating counts is
                 // done by just summing the numbers together
@@ -326,7 +335,7 @@ pub(super) fn post_lookup_aggregates(
                 GroupConcat { separator, .. } => PostLookupAggregateFunction::GroupConcat {
                     separator: separator.clone().unwrap_or_else(|| ",".to_owned()),
                 },
-                Call { .. } | Substring { .. } => continue,
+                Call { .. } | Substring { .. } | Window { .. } => continue,
             },
         });
     }
@@ -2,7 +2,9 @@ use std::collections::{HashMap, HashSet};
 
 use mir::NodeIndex;
 use nom_sql::Relation;
-use readyset_errors::{internal_err, invariant, unsupported, ReadySetResult};
+use readyset_errors::{
+    internal_err, invariant, unsupported_feature, ReadySetResult, UnsupportedFeature,
+};
 
 use super::JoinKind;
 use crate::controller::sql::mir::SqlToMirConverter;
@@ -56,10 +58,16 @@ pub(super) fn make_joins(
                     params,
                 } => {
                     if !global_preds.is_empty() {
-                        unsupported!("Global predicates not yet supported in left joins");
+                        unsupported_feature!(
+                            UnsupportedFeature::LeftJoinCondition,
+                            "Global predicates not yet supported in left joins"
+                        );
                     }
                     if !params.is_empty() {
-                        unsupported!("Parameters not yet supported in left joins");
+                        unsupported_feature!(
+                            UnsupportedFeature::LeftJoinCondition,
+                            "Parameters not yet supported in left joins"
+                        );
                     }
                     (
                         JoinKind::Left,
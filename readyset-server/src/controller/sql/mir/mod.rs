@@ -22,12 +22,12 @@ use nom_sql::{
     Literal, NonReplicatedRelation, OrderBy, OrderClause, OrderType, Relation, SelectStatement,
     SqlIdentifier, TableKey, UnaryOperator,
 };
-use petgraph::visit::Reversed;
+use petgraph::visit::{Dfs, Reversed, VisitMap};
 use petgraph::Direction;
 use readyset_client::ViewPlaceholder;
 use readyset_errors::{
-    internal, internal_err, invalid_query, invalid_query_err, invariant, invariant_eq, unsupported,
-    ReadySetError, ReadySetResult,
+    internal, internal_err, invalid_query, invalid_query_err, invariant, invariant_eq,
+    unsupported_feature, ReadySetError, ReadySetResult, UnsupportedFeature,
 };
 use readyset_sql_passes::is_correlated;
 use readyset_util::redacted::Sensitive;
@@ -97,6 +97,15 @@ pub struct MirRemovalResult {
     pub relations_removed: HashSet<Relation>,
 }
 
+/// The result of [`SqlToMirConverter::collect_garbage`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct MirGarbageCollectionResult {
+    /// The number of MIR nodes remaining in the graph after garbage collection
+    pub(crate) retained: usize,
+    /// The number of MIR nodes that were removed from the graph
+    pub(crate) dropped: usize,
+}
+
 /// Kinds of joins in MIR
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum JoinKind {
@@ -169,6 +178,12 @@ pub(crate) struct Config {
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub(super) struct SqlToMirConverter {
     pub(in crate::controller::sql) config: Config,
+    /// Every version of the schema of each base table, as a list of (version, columns) pairs.
+    ///
+    /// A new version is recorded each time a base table is created with a different schema than
+    /// its current version, and versions are retained across the table being dropped and
+    /// recreated (eg as part of an `ALTER TABLE`), until they're removed by
+    /// [`SqlToMirConverter::collect_garbage`].
     pub(in crate::controller::sql) base_schemas:
         HashMap<Relation, Vec<(usize, Vec<ColumnSpecification>)>>,
     /// The graph containing all of the MIR base tables, views and cached queries.
@@ -201,6 +216,54 @@ impl SqlToMirConverter {
         self.relations.get(relation).copied()
     }
 
+    /// Returns the current version of the schema of the given base table, or `None` if no schema
+    /// has ever been recorded for the table.
+    pub(super) fn schema_version_for(&self, table_name: &Relation) -> Option<usize> {
+        self.base_schemas
+            .get(table_name)?
+            .iter()
+            .map(|(version, _)| *version)
+            .max()
+    }
+
+    /// Returns all the versions of the schema of the given base table which are still retained,
+    /// in ascending order, including the current version.
+    pub(super) fn all_schema_versions(&self, table_name: &Relation) -> Vec<usize> {
+        let mut versions = self
+            .base_schemas
+            .get(table_name)
+            .map(|versions| versions.iter().map(|(version, _)| *version).collect())
+            .unwrap_or_else(Vec::new);
+        versions.sort_unstable();
+        versions
+    }
+
+    /// Record `cols` as a new version of the schema of the given base table, unless it's the same
+    /// as the current version
+    fn record_base_schema(&mut self, table_name: &Relation, cols: &[ColumnSpecification]) {
+        let current = self.base_schemas.get(table_name).and_then(|versions| {
+            versions
+                .iter()
+                .max_by_key(|(version, _)| *version)
+                .map(|(_, cols)| cols.as_slice())
+        });
+        if current == Some(cols) {
+            return;
+        }
+
+        let version = self.schema_version_for(table_name).map_or(0, |v| v + 1);
+        debug!(
+            table_name = %table_name.display_unquoted(),
+            version,
+            previous_versions = ?self.all_schema_versions(table_name),
+            "Recording new base table schema version"
+        );
+        self.base_schemas
+            .entry(table_name.clone())
+            .or_default()
+            .push((version, cols.to_vec()));
+    }
+
     /// Generates a label based on the number of nodes in the MIR graph.
     /// Useful to generate label for new nodes.
     ///
@@ -301,9 +364,11 @@ impl SqlToMirConverter {
                                             order_type.unwrap_or(OrderType::OrderAscending);
                                         if let Some(null_order) = null_order {
                                             if !null_order.is_default_for(order_type) {
-                                                unsupported!(
-                                                 "Non-default NULLS FIRST/LAST is not yet supported"
-                                             );
+                                                unsupported_feature!(
+                                                    UnsupportedFeature::NullsOrder,
+                                                    "Non-default NULLS FIRST/LAST is not yet \
+                                                     supported"
+                                                );
                                             }
                                         }
                                         Ok((
@@ -459,6 +524,67 @@ impl SqlToMirConverter {
         self.remove_dependent_nodes(*root)
     }
 
+    /// Removes all MIR nodes which are not reachable (as an ancestor) from any of the relations
+    /// known to the converter, and discards all but the newest `keep_versions` schema versions of
+    /// each base table. Schema versions which are still the schema of a base table node in the
+    /// graph are never discarded.
+    ///
+    /// Nodes can be left unreachable if compiling a query to MIR fails partway through, after some
+    /// of its nodes have already been added to the graph. Unreachable nodes which have already been
+    /// lowered to dataflow are retained, since those must be removed from the dataflow graph as
+    /// well.
+    pub(super) fn collect_garbage(&mut self, keep_versions: usize) -> MirGarbageCollectionResult {
+        let graph = Reversed(&*self.mir_graph);
+        let mut dfs = Dfs::empty(graph);
+        for &node in self.relations.values() {
+            dfs.move_to(node);
+            while dfs.next(graph).is_some() {}
+        }
+
+        let unreachable = self
+            .mir_graph
+            .node_indices()
+            .filter(|n| {
+                !dfs.discovered.is_visited(n) && self.mir_graph[*n].df_node_index().is_none()
+            })
+            .collect::<Vec<_>>();
+        let dropped = unreachable.len();
+        for node in unreachable {
+            self.mir_graph.remove_node(node);
+        }
+
+        let mut referenced_schemas: HashMap<&Relation, Vec<&[ColumnSpecification]>> =
+            HashMap::new();
+        for node in self.mir_graph.node_weights() {
+            if let MirNodeInner::Base { column_specs, .. } = &node.inner {
+                referenced_schemas
+                    .entry(node.name())
+                    .or_default()
+                    .push(column_specs);
+            }
+        }
+
+        for (table, versions) in self.base_schemas.iter_mut() {
+            if versions.len() > keep_versions {
+                versions.sort_unstable_by_key(|(version, _)| *version);
+                let first_kept = versions.len() - keep_versions;
+                let referenced = referenced_schemas.get(table);
+                let mut i = 0;
+                versions.retain(|(_, cols)| {
+                    let keep = i >= first_kept
+                        || referenced.map_or(false, |schemas| schemas.contains(&cols.as_slice()));
+                    i += 1;
+                    keep
+                });
+            }
+        }
+
+        MirGarbageCollectionResult {
+            retained: self.mir_graph.node_count(),
+            dropped,
+        }
+    }
+
     pub(super) fn make_mir_query(
         &mut self,
         query_name: Relation,
@@ -618,6 +744,7 @@ impl SqlToMirConverter {
         };
 
         // remember the schema for this version
+        self.record_base_schema(table_name, cols);
         let node = MirNode::new(
             table_name.clone(),
             MirNodeInner::Base {
@@ -1286,9 +1413,9 @@ impl SqlToMirConverter {
         is_topk: bool,
     ) -> ReadySetResult<Vec<NodeIndex>> {
         if !self.config.allow_topk && is_topk {
-            unsupported!("TopK is not supported");
+            unsupported_feature!(UnsupportedFeature::Pagination, "TopK is not supported");
         } else if !self.config.allow_paginate && !is_topk {
-            unsupported!("Paginate is not supported");
+            unsupported_feature!(UnsupportedFeature::Pagination, "Paginate is not supported");
         }
 
         // Gather a list of expressions we need to evaluate before the paginate node
@@ -1671,7 +1798,10 @@ impl SqlToMirConverter {
                     )?
                 }
             }
-            Expr::NestedSelect(_) => unsupported!("Nested selects not supported in filters"),
+            Expr::NestedSelect(_) => unsupported_feature!(
+                UnsupportedFeature::NestedSelectInFilter,
+                "Nested selects not supported in filters"
+            ),
             _ => self.make_filter_node(
                 query_name,
                 format!(
@@ -2254,7 +2384,10 @@ impl SqlToMirConverter {
                 if !self.config.allow_post_lookup
                     && (aggregates.is_some() || order_by.is_some() || limit.is_some())
                 {
-                    unsupported!("Queries which perform operations post-lookup are not supported");
+                    unsupported_feature!(
+                        UnsupportedFeature::PostLookup,
+                        "Queries which perform operations post-lookup are not supported"
+                    );
                 }
 
                 self.add_query_node(
@@ -2325,3 +2458,238 @@ impl SqlToMirConverter {
         Ok(leaf)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use nom_sql::{parse_query, Dialect, SqlQuery, SqlType};
+    use readyset_errors::{ConversionStats, UnsupportedFeatureCount};
+    use readyset_sql_passes::NormalizeTopKWithAggregate;
+
+    use super::*;
+
+    fn converter_with_base() -> (SqlToMirConverter, NodeIndex) {
+        let mut converter = SqlToMirConverter::default();
+        let table = Relation::from("t");
+        let base = converter
+            .make_base_node(
+                &table,
+                &[ColumnSpecification::new(
+                    nom_sql::Column {
+                        name: "a".into(),
+                        table: Some(table.clone()),
+                    },
+                    SqlType::Int(None),
+                )],
+                None,
+            )
+            .unwrap();
+        (converter, base)
+    }
+
+    #[test]
+    fn collect_garbage_drops_unreachable_nodes() {
+        let (mut converter, base) = converter_with_base();
+        let query_name = Relation::from("q");
+
+        // Install the same query three times, where every attempt but the last fails after adding
+        // a node to the graph
+        let mut attempts = (0..3)
+            .map(|attempt| {
+                converter.add_query_node(
+                    query_name.clone(),
+                    MirNode::new(format!("q_{attempt}").into(), MirNodeInner::Identity),
+                    &[base],
+                )
+            })
+            .collect::<Vec<_>>();
+        let leaf = attempts.pop().unwrap();
+        converter.relations.insert(query_name.clone(), leaf);
+
+        let res = converter.collect_garbage(1);
+        assert_eq!(
+            res,
+            MirGarbageCollectionResult {
+                retained: 2,
+                dropped: 2
+            }
+        );
+        for node in attempts {
+            assert!(converter.get_node(node).is_none());
+        }
+        assert!(converter.get_node(base).is_some());
+        assert_eq!(converter.get_relation(&query_name), Some(leaf));
+        assert_eq!(
+            converter
+                .mir_graph
+                .neighbors_directed(leaf, Direction::Incoming)
+                .collect::<Vec<_>>(),
+            vec![base]
+        );
+    }
+
+    #[test]
+    fn all_schema_versions_after_upgrades() {
+        let (mut converter, _) = converter_with_base();
+        let table = Relation::from("t");
+        assert_eq!(converter.schema_version_for(&table), Some(0));
+
+        // Upgrade the schema twice, by dropping the table and recreating it with an extra column
+        let mut cols = converter.base_schemas[&table][0].1.clone();
+        for name in ["b", "c"] {
+            let base = converter.relations.remove(&table).unwrap();
+            converter.mir_graph.remove_node(base);
+
+            cols.push(ColumnSpecification::new(
+                nom_sql::Column {
+                    name: name.into(),
+                    table: Some(table.clone()),
+                },
+                SqlType::Int(None),
+            ));
+            converter.make_base_node(&table, &cols, None).unwrap();
+        }
+
+        assert_eq!(converter.schema_version_for(&table), Some(2));
+        assert_eq!(converter.all_schema_versions(&table), vec![0, 1, 2]);
+        assert_eq!(
+            converter.schema_version_for(&Relation::from("nonexistent")),
+            None
+        );
+        assert!(converter
+            .all_schema_versions(&Relation::from("nonexistent"))
+            .is_empty());
+    }
+
+    #[test]
+    fn recreating_base_with_same_schema_keeps_version() {
+        let (mut converter, base) = converter_with_base();
+        let table = Relation::from("t");
+        let cols = converter.base_schemas[&table][0].1.clone();
+        converter.relations.remove(&table);
+        converter.mir_graph.remove_node(base);
+        converter.make_base_node(&table, &cols, None).unwrap();
+
+        assert_eq!(converter.all_schema_versions(&table), vec![0]);
+    }
+
+    #[test]
+    fn collect_garbage_keeps_newest_base_schema_versions() {
+        let (mut converter, _) = converter_with_base();
+        let table = Relation::from("t");
+        converter.base_schemas.insert(
+            table.clone(),
+            (0..3).map(|version| (version, vec![])).collect(),
+        );
+
+        let res = converter.collect_garbage(1);
+        assert_eq!(res.dropped, 0);
+        assert_eq!(converter.base_schemas[&table], vec![(2, vec![])]);
+    }
+
+    #[test]
+    fn collect_garbage_keeps_referenced_base_schema_versions() {
+        let (mut converter, _) = converter_with_base();
+        let table = Relation::from("t");
+        let cols = converter.base_schemas[&table][0].1.clone();
+        converter
+            .base_schemas
+            .get_mut(&table)
+            .unwrap()
+            .extend([(1, vec![]), (2, vec![])]);
+
+        converter.collect_garbage(1);
+        assert_eq!(converter.all_schema_versions(&table), vec![0, 2]);
+        assert_eq!(converter.base_schemas[&table][0].1, cols);
+    }
+
+    #[test]
+    fn row_number_filter_makes_topk() {
+        let (mut converter, _) = converter_with_base();
+        converter.set_config(Config {
+            allow_topk: true,
+            ..Default::default()
+        });
+
+        let stmt = match parse_query(
+            Dialect::MySQL,
+            "SELECT sub.a FROM (SELECT t.a, row_number() AS rn FROM t ORDER BY t.a) AS sub \
+             WHERE sub.rn <= 3",
+        )
+        .unwrap()
+        {
+            SqlQuery::Select(stmt) => stmt.normalize_topk_with_aggregate().unwrap(),
+            _ => panic!(),
+        };
+        let query_graph = to_query_graph(stmt).unwrap();
+        converter
+            .named_query_to_mir(
+                &"q".into(),
+                &query_graph,
+                &HashMap::new(),
+                LeafBehavior::Leaf,
+            )
+            .unwrap();
+
+        let limits = converter
+            .mir_graph
+            .node_weights()
+            .filter_map(|node| match &node.inner {
+                MirNodeInner::TopK { limit, .. } => Some(*limit),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(limits, vec![3]);
+    }
+
+    #[test]
+    fn conversion_stats_classify_unsupported_features() {
+        let (mut converter, _) = converter_with_base();
+        let queries = [
+            "SELECT t.a FROM t WHERE t.a = 1",
+            "SELECT t.a FROM t GROUP BY t.a WITH ROLLUP",
+            "SELECT t.a FROM t ORDER BY t.a LIMIT 3 OFFSET 5",
+            "SELECT t.a FROM t ORDER BY t.a LIMIT 10 OFFSET 20",
+            "SELECT 1",
+        ];
+
+        let mut stats = ConversionStats::new();
+        for (i, query) in queries.into_iter().enumerate() {
+            let res = match parse_query(Dialect::MySQL, query).unwrap() {
+                SqlQuery::Select(stmt) => to_query_graph(stmt).and_then(|query_graph| {
+                    converter.named_query_to_mir(
+                        &format!("q{i}").into(),
+                        &query_graph,
+                        &HashMap::new(),
+                        LeafBehavior::Leaf,
+                    )
+                }),
+                _ => panic!(),
+            };
+            stats.record(query, &res);
+        }
+
+        assert_eq!(stats.total(), 5);
+        assert_eq!(stats.succeeded, 1);
+        assert_eq!(stats.other_errors, 0);
+        assert_eq!(
+            stats.report(),
+            vec![
+                UnsupportedFeatureCount {
+                    feature: UnsupportedFeature::Pagination,
+                    count: 2,
+                    example_query: queries[2].into(),
+                },
+                UnsupportedFeatureCount {
+                    feature: UnsupportedFeature::Rollup,
+                    count: 1,
+                    example_query: queries[1].into(),
+                },
+                UnsupportedFeatureCount {
+                    feature: UnsupportedFeature::NoTables,
+                    count: 1,
+                    example_query: queries[4].into(),
+                },
+            ]
+        );
+    }
+}
@@ -20,7 +20,9 @@ use readyset_errors::{
     ReadySetResult,
 };
 use readyset_sql_passes::alias_removal::TableAliasRewrite;
-use readyset_sql_passes::{AliasRemoval, DetectUnsupportedPlaceholders, Rewrite, RewriteContext};
+use readyset_sql_passes::{
+    AliasRemoval, DetectUnsupportedPlaceholders, Rewrite, RewriteContext, SelfJoinPolicy,
+};
 use readyset_util::redacted::Sensitive;
 use tracing::{debug, error, info, trace, warn};
 use vec1::Vec1;
@@ -41,17 +43,26 @@ mod query_signature;
 mod recipe;
 mod registry;
 
+/// Number of schema versions of each base table that are retained in MIR when collecting garbage,
+/// in addition to any versions still used by base table nodes in the MIR graph
+const MIR_BASE_SCHEMA_VERSIONS_TO_KEEP: usize = 1;
+
 /// Configuration for converting SQL to dataflow
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[non_exhaustive]
 pub(crate) struct Config {
     pub(crate) reuse_type: Option<ReuseConfigType>,
+    /// Which self-joins to allow when rewriting queries, out of the ones which could return
+    /// incorrect results
+    #[serde(default)]
+    pub(crate) self_join_policy: SelfJoinPolicy,
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
             reuse_type: Some(ReuseConfigType::Finkelstein),
+            self_join_policy: SelfJoinPolicy::default(),
         }
     }
 }
@@ -204,6 +215,7 @@ impl SqlIncorporator {
             search_path,
             dialect,
             invalidating_tables,
+            self_join_policy: self.config.self_join_policy,
         })
     }
 
@@ -539,6 +551,11 @@ impl SqlIncorporator {
             }
         }
 
+        // Compiling a query may have failed after some of its MIR nodes were added, without
+        // failing the whole migration (eg if an existing cache could be reused instead). If the
+        // migration does fail, all of our state is discarded anyway.
+        self.collect_mir_garbage();
+
         Ok(())
     }
 
@@ -1210,6 +1227,21 @@ impl SqlIncorporator {
         Ok(df_leaf.address())
     }
 
+    /// Remove any MIR nodes which are no longer reachable from any relation, such as those left
+    /// behind by queries which failed to compile. See [`SqlToMirConverter::collect_garbage`].
+    fn collect_mir_garbage(&mut self) {
+        let res = self
+            .mir_converter
+            .collect_garbage(MIR_BASE_SCHEMA_VERSIONS_TO_KEEP);
+        if res.dropped > 0 {
+            debug!(
+                retained = res.retained,
+                dropped = res.dropped,
+                "Removed unreachable MIR nodes"
+            );
+        }
+    }
+
     pub(super) fn remove_query(
         &mut self,
         query_name: &Relation,
@@ -18,7 +18,8 @@ use nom_sql::{
 use readyset_client::{PlaceholderIdx, ViewPlaceholder};
 use readyset_errors::{
     internal, invalid_query, invalid_query_err, invariant, invariant_eq, no_table_for_col,
-    unsupported, unsupported_err, ReadySetResult,
+    unsupported, unsupported_err, unsupported_feature, unsupported_feature_err, ReadySetResult,
+    UnsupportedFeature,
 };
 use readyset_sql_passes::{is_aggregate, is_correlated, is_predicate, map_aggregates, LogicalOp};
 use serde::{Deserialize, Serialize};
@@ -350,7 +351,13 @@ impl QueryGraph {
                 config: &mir::Config,
             ) -> ReadySetResult<Option<IndexType>> {
                 let new_index_type = Some(IndexType::for_operator(operator).ok_or_else(|| {
-                    unsupported_err!("Unsupported binary operator `{}`", operator)
+                    unsupported_feature_err!(
+                        UnsupportedFeature::BinaryOperator {
+                            operator: operator.to_string()
+                        },
+                        "Unsupported binary operator `{}`",
+                        operator
+                    )
                 })?);
                 if !config.allow_mixed_comparisons
                     && current_index_type.is_some()
@@ -436,7 +443,10 @@ impl QueryGraph {
 
             if let Some(offset) = offset {
                 if index_type == Some(IndexType::BTreeMap) {
-                    unsupported!("ReadySet does not support Pagination and range queries")
+                    unsupported_feature!(
+                        UnsupportedFeature::Pagination,
+                        "ReadySet does not support Pagination and range queries"
+                    )
                 } else {
                     columns.push((mir::Column::named(PAGE_NUMBER_COL.clone()), offset));
                 }
@@ -795,7 +805,10 @@ pub(crate) fn extract_limit_offset(
     limit_clause: &LimitClause,
 ) -> ReadySetResult<Option<(usize, Option<ViewPlaceholder>)>> {
     if limit_clause.limit().is_none() && limit_clause.offset().is_some() {
-        unsupported!("ReadySet does not support OFFSET without LIMIT");
+        unsupported_feature!(
+            UnsupportedFeature::Pagination,
+            "ReadySet does not support OFFSET without LIMIT"
+        );
     }
 
     let limit = if let Some(limit) = limit_clause.limit() {
@@ -809,7 +822,10 @@ pub(crate) fn extract_limit_offset(
         Literal::Integer(val) => u64::try_from(*val)
             .map_err(|_| unsupported_err!("LIMIT field cannot have a negative value"))?,
         Literal::Placeholder(_) => {
-            unsupported!("ReadySet does not support parameterized LIMIT fields")
+            unsupported_feature!(
+                UnsupportedFeature::Pagination,
+                "ReadySet does not support parameterized LIMIT fields"
+            )
         }
         _ => unsupported!("Invalid LIMIT statement"),
     };
@@ -827,7 +843,10 @@ pub(crate) fn extract_limit_offset(
                         limit,
                     })
                 }
-                _ => unsupported!("Numeric OFFSETs must be parameterized"),
+                _ => unsupported_feature!(
+                    UnsupportedFeature::Pagination,
+                    "Numeric OFFSETs must be parameterized"
+                ),
             }
         })
         .transpose()?;
@@ -869,7 +888,9 @@ fn default_row_for_select(st: &SelectStatement) -> Option<Vec<DfValue>> {
                     FunctionExpr::Max(..) => DfValue::None,
                     FunctionExpr::Min(..) => DfValue::None,
                     FunctionExpr::GroupConcat { .. } => DfValue::None,
-                    FunctionExpr::Call { .. } | FunctionExpr::Substring { .. } => DfValue::None,
+                    FunctionExpr::Call { .. }
+                    | FunctionExpr::Substring { .. }
+                    | FunctionExpr::Window { .. } => DfValue::None,
                 },
                 _ => DfValue::None,
             })
@@ -940,6 +961,16 @@ pub fn to_query_graph(stmt: SelectStatement) -> ReadySetResult<QueryGraph> {
 
     let mut relations = HashMap::new();
     let mut add_table_expr = |table_expr: &TableExpr| -> ReadySetResult<Relation> {
+        if table_expr.sample.is_some() {
+            // TODO: lower to a MIR sample node wrapping the base node once the query generator can
+            // exercise it. Sampled results are non-deterministic, so such a query could never be
+            // materialized or kept up to date, and would have to be marked non-materializable.
+            unsupported_feature!(
+                UnsupportedFeature::TableSample,
+                "ReadySet does not support TABLESAMPLE"
+            );
+        }
+
         match &table_expr.inner {
             TableExprInner::Table(t) => {
                 if relations
@@ -990,7 +1021,10 @@ pub fn to_query_graph(stmt: SelectStatement) -> ReadySetResult<QueryGraph> {
                     inner_join_rels.insert(rel);
                 }
             }
-            JoinRightSide::Tables(_) => unsupported!("JoinRightSide::Tables not yet implemented"),
+            JoinRightSide::Tables(_) => unsupported_feature!(
+                UnsupportedFeature::JoinShape,
+                "JoinRightSide::Tables not yet implemented"
+            ),
         };
     }
 
@@ -1006,15 +1040,20 @@ pub fn to_query_graph(stmt: SelectStatement) -> ReadySetResult<QueryGraph> {
     let mut query_parameters = Vec::new();
 
     // The table specified in the query is available for USING joins.
-    let prev_table =
-        table_expr_name(stmt.tables.last().ok_or_else(|| {
-            unsupported_err!("SELECT statements with no tables are unsupported")
-        })?)?;
+    let prev_table = table_expr_name(stmt.tables.last().ok_or_else(|| {
+        unsupported_feature_err!(
+            UnsupportedFeature::NoTables,
+            "SELECT statements with no tables are unsupported"
+        )
+    })?)?;
 
     for jc in stmt.join {
         let rhs_relation = match jc.right {
             JoinRightSide::Table(te) => table_expr_name(&te)?,
-            JoinRightSide::Tables(_) => unsupported!("JoinRightSide::Tables not yet implemented"),
+            JoinRightSide::Tables(_) => unsupported_feature!(
+                UnsupportedFeature::JoinShape,
+                "JoinRightSide::Tables not yet implemented"
+            ),
         };
         // will be defined by join constraint
         let left_table;
@@ -1050,7 +1089,10 @@ pub fn to_query_graph(stmt: SelectStatement) -> ReadySetResult<QueryGraph> {
                     left_table = tables_mentioned.remove(0);
                     right_table = left_table.clone();
                 } else {
-                    unsupported!("more than 2 tables mentioned in join condition!");
+                    unsupported_feature!(
+                        UnsupportedFeature::JoinShape,
+                        "more than 2 tables mentioned in join condition!"
+                    );
                 };
 
                 for pred in join_preds.iter_mut() {
@@ -1146,7 +1188,10 @@ pub fn to_query_graph(stmt: SelectStatement) -> ReadySetResult<QueryGraph> {
                     }
                     QueryGraphEdge::Join { on }
                 }
-                _ => unsupported!("join operator not supported"),
+                _ => unsupported_feature!(
+                    UnsupportedFeature::JoinShape,
+                    "join operator not supported"
+                ),
             });
         }
     }
@@ -1270,6 +1315,13 @@ pub fn to_query_graph(stmt: SelectStatement) -> ReadySetResult<QueryGraph> {
     }
 
     let group_by = if let Some(group_by_clause) = &stmt.group_by {
+        if group_by_clause.with_rollup {
+            unsupported_feature!(
+                UnsupportedFeature::Rollup,
+                "ReadySet does not support GROUP BY ... WITH ROLLUP"
+            );
+        }
+
         group_by_clause
             .fields
             .iter()
@@ -1351,7 +1403,10 @@ pub fn to_query_graph(stmt: SelectStatement) -> ReadySetResult<QueryGraph> {
                         let order_type = order_type.unwrap_or(OrderType::OrderAscending);
                         if let Some(null_order) = null_order {
                             if !null_order.is_default_for(order_type) {
-                                unsupported!("Non-default NULLS FIRST/LAST is not yet supported");
+                                unsupported_feature!(
+                                    UnsupportedFeature::NullsOrder,
+                                    "Non-default NULLS FIRST/LAST is not yet supported"
+                                );
                             }
                         }
 
@@ -1625,6 +1680,17 @@ mod tests {
         to_query_graph(query).unwrap_err();
     }
 
+    #[test]
+    fn table_sample_unsupported() {
+        let query = parse_select_statement(
+            Dialect::PostgreSQL,
+            "SELECT * FROM t TABLESAMPLE BERNOULLI (10)",
+        )
+        .unwrap();
+        let err = to_query_graph(query).unwrap_err();
+        assert!(err.is_unsupported(), "{err}");
+    }
+
     #[test]
     fn order_by_aggregate() {
         let qg = make_query_graph(
@@ -13,21 +13,22 @@
 //! to manipulate it in a thread-safe way.
 
 use std::cell;
-use std::collections::{BTreeMap, HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use std::net::SocketAddr;
 use std::ops::Deref;
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use array2::Array2;
 use common::{IndexPair, Tag};
 use dataflow::payload::EvictRequest;
 use dataflow::prelude::{ChannelCoordinator, DomainIndex, DomainNodes, Graph, NodeIndex};
 use dataflow::{
-    BaseTableState, DomainBuilder, DomainConfig, DomainRequest, NodeMap, Packet,
-    PersistenceParameters, Sharding,
+    BarrierState, BaseTableState, DomainBuilder, DomainConfig, DomainRequest, DomainTuning,
+    NodeMap, Packet, PersistenceParameters, Sharding,
 };
 use failpoint_macros::set_failpoint;
+use futures::future::join_all;
 use futures::stream::{self, FuturesUnordered, StreamExt, TryStreamExt};
 use futures::{FutureExt, TryFutureExt, TryStream};
 use metrics::{gauge, histogram};
@@ -39,10 +40,13 @@ use readyset_client::builders::{
     ReaderHandleBuilder, ReusedReaderHandleBuilder, TableBuilder, ViewBuilder,
 };
 use readyset_client::consensus::{Authority, AuthorityControl};
-use readyset_client::debug::info::{GraphInfo, MaterializationInfo, NodeSize};
-use readyset_client::debug::stats::{DomainStats, GraphStats, NodeStats};
+use readyset_client::debug::info::{BaseColumnEvent, GraphInfo, MaterializationInfo, NodeSize};
+use readyset_client::debug::stats::{
+    DomainStats, GraphStats, NodeStats, ReaderLookupStats, SlowOperation, ViewAnalysis,
+};
 #[cfg(feature = "failure_injection")]
 use readyset_client::failpoints;
+use readyset_client::health::{ComponentHealth, DomainHealth, HealthReport, WorkerHealth};
 use readyset_client::internal::{MaterializationStatus, ReplicaAddress};
 use readyset_client::metrics::recorded;
 use readyset_client::recipe::changelist::{Change, ChangeList};
@@ -53,7 +57,8 @@ use readyset_client::{
 };
 use readyset_data::{DfValue, Dialect};
 use readyset_errors::{
-    internal, internal_err, invariant_eq, NodeType, ReadySetError, ReadySetResult,
+    internal, internal_err, invariant_eq, MutatorBuildError, NodeType, ReadySetError,
+    ReadySetResult,
 };
 use replication_offset::{ReplicationOffset, ReplicationOffsets};
 use serde::de::DeserializeOwned;
@@ -66,8 +71,10 @@ use super::migrate::DomainSettings;
 use super::replication::ReplicationStrategy;
 use super::sql::Recipe;
 use crate::controller::domain_handle::DomainHandle;
+use crate::controller::keys::{ProvenanceCache, ProvenancePath};
 use crate::controller::migrate::materialization::Materializations;
-use crate::controller::migrate::scheduling::Scheduler;
+use crate::controller::migrate::progress::ProgressReporter;
+use crate::controller::migrate::scheduling::{PlacementStrategy, Scheduler};
 use crate::controller::migrate::{routing, DomainMigrationMode, DomainMigrationPlan, Migration};
 use crate::controller::sql::{RecipeExpr, Schema};
 use crate::controller::{
@@ -86,6 +93,10 @@ pub(in crate::controller) use self::graphviz::Graphviz;
 /// for replication offsets)
 const CONCURRENT_REQUESTS: usize = 16;
 
+/// How often to poll domains for the state of a barrier in
+/// [`DfStateHandle::wait_for_stabilization`]
+const WAIT_FOR_STABILIZATION_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
 /// This structure holds all the dataflow state.
 /// It's meant to be handled exclusively by the [`DfStateHandle`], which is the structure
 /// that guarantees thread-safe access to it.
@@ -99,8 +110,17 @@ pub struct DfState {
     pub(super) sharding: Option<usize>,
 
     pub(super) domain_config: DomainConfig,
+    /// Tunable parameters for domains created by migrations which overrode the ones in
+    /// [`Self::domain_config`], via [`Migration::with_domain_tuning`].
+    ///
+    /// [`Migration::with_domain_tuning`]: crate::controller::migrate::Migration::with_domain_tuning
+    #[serde(default, with = "serde_with::rust::hashmap_as_tuple_list")]
+    pub(super) domain_tuning_overrides: HashMap<DomainIndex, DomainTuning>,
 
     pub(super) replication_strategy: ReplicationStrategy,
+    /// The strategy used to decide which workers to schedule domains onto
+    #[serde(default)]
+    pub(super) placement_strategy: PlacementStrategy,
 
     /// Controls the persistence mode, and parameters related to persistence.
     ///
@@ -140,6 +160,14 @@ pub struct DfState {
     pub(super) read_addrs: HashMap<WorkerIdentifier, SocketAddr>,
     #[serde(skip)]
     pub(super) workers: HashMap<WorkerIdentifier, Worker>,
+
+    /// Cache of the provenance of columns of nodes in [`Self::ingredients`]
+    #[serde(skip)]
+    pub(super) provenance: ProvenanceCache,
+
+    /// The current version of the schema, advanced once by each migration that's committed
+    #[serde(default)]
+    schema_version: usize,
 }
 
 impl DfState {
@@ -157,6 +185,7 @@ impl DfState {
         node_restrictions: HashMap<NodeRestrictionKey, DomainPlacementRestriction>,
         channel_coordinator: Arc<ChannelCoordinator>,
         replication_strategy: ReplicationStrategy,
+        placement_strategy: PlacementStrategy,
     ) -> Self {
         Self {
             ingredients,
@@ -164,6 +193,7 @@ impl DfState {
             ndomains,
             sharding,
             domain_config,
+            domain_tuning_overrides: Default::default(),
             persistence,
             materializations,
             recipe,
@@ -176,9 +206,38 @@ impl DfState {
             workers: Default::default(),
             domain_node_index_pairs: Default::default(),
             replication_strategy,
+            placement_strategy,
+            provenance: Default::default(),
+            schema_version: 0,
         }
     }
 
+    /// Returns the current version of the schema, which starts at 0 and is advanced once by each
+    /// migration that's committed
+    pub(super) fn current_schema_version(&self) -> usize {
+        self.schema_version
+    }
+
+    /// Advance the version of the schema, returning the new version.
+    ///
+    /// Since this takes `&mut self`, it can't race with another migration (which would also need
+    /// exclusive access to the dataflow state), so no two migrations are given the same version.
+    pub(super) fn advance_schema_version(&mut self) -> usize {
+        self.schema_version += 1;
+        self.schema_version
+    }
+
+    /// Returns a [`ProvenancePath`] for each path from the given column of the given node back to
+    /// a base table node, reusing the result of any previous call for the same node and column
+    pub(super) fn column_provenance(
+        &self,
+        node: NodeIndex,
+        column: usize,
+    ) -> ReadySetResult<Vec<ProvenancePath>> {
+        self.provenance
+            .column_provenance(&self.ingredients, node, column)
+    }
+
     pub(super) fn schema_replication_offset(&self) -> &Option<ReplicationOffset> {
         &self.schema_replication_offset
     }
@@ -235,6 +294,35 @@ impl DfState {
             .collect()
     }
 
+    /// Returns the index of the base table node called `name`, if one exists and hasn't been
+    /// dropped.
+    ///
+    /// Unlike [`Self::table_builder`], this searches the graph directly rather than resolving the
+    /// name via the recipe, so it also finds base nodes added directly by a migration.
+    pub(super) fn get_base_node_by_name(&self, name: &Relation) -> Option<NodeIndex> {
+        self.ingredients
+            .neighbors_directed(self.source, petgraph::EdgeDirection::Outgoing)
+            .find(|&n| {
+                #[allow(clippy::indexing_slicing)] // just came from self.ingredients
+                let node = &self.ingredients[n];
+                node.is_base() && !node.is_dropped() && node.name() == name
+            })
+    }
+
+    /// Returns the index of the reader node called `name`, if one exists.
+    ///
+    /// Note that this is the index of the reader node itself, rather than the node it reads from
+    /// as returned by [`Self::views`].
+    pub(super) fn get_view_by_name(&self, name: &Relation) -> Option<NodeIndex> {
+        self.ingredients
+            .externals(petgraph::EdgeDirection::Outgoing)
+            .find(|&n| {
+                #[allow(clippy::indexing_slicing)] // just came from self.ingredients
+                let node = &self.ingredients[n];
+                node.is_reader() && node.name() == name
+            })
+    }
+
     /// Return a list of all relations (tables or views) which are known to exist in the upstream
     /// database that we are replicating from, but are not being replicated to ReadySet (which are
     /// recorded via [`Change::AddNonReplicatedRelation`]).
@@ -355,6 +443,19 @@ impl DfState {
         None
     }
 
+    /// Find *some* reader for the given node, regardless of its name. See [`Self::find_reader_for`]
+    /// for why this isn't just a search of the node's immediate children.
+    pub(super) fn any_reader_for(&self, node: NodeIndex) -> Option<NodeIndex> {
+        let mut bfs = Bfs::new(&self.ingredients, node);
+        while let Some(child) = bfs.next(&self.ingredients) {
+            #[allow(clippy::indexing_slicing)] // just came from self.ingredients
+            if self.ingredients[child].is_reader_for(node) {
+                return Some(child);
+            }
+        }
+        None
+    }
+
     /// Create a ViewBuilder given the node, the ViewRequest, and an optional name if this view
     /// request is for a reused cache.
     fn view_builder_inner(
@@ -538,14 +639,30 @@ impl DfState {
             .unwrap_or_else(|| (0..n.columns().len()).collect());
 
         let projected_schema = (0..n.columns().len())
-            .map(|i| schema::column_schema(&self.ingredients, view_ni, &self.recipe, i))
+            .map(|i| {
+                schema::column_schema(
+                    &self.ingredients,
+                    &self.provenance,
+                    view_ni,
+                    &self.recipe,
+                    i,
+                )
+            })
             .collect::<Result<Vec<_>, ReadySetError>>()?
             .into_iter()
             .collect::<Option<Vec<_>>>();
 
         let returned_schema = returned_cols
             .iter()
-            .map(|idx| schema::column_schema(&self.ingredients, view_ni, &self.recipe, *idx))
+            .map(|idx| {
+                schema::column_schema(
+                    &self.ingredients,
+                    &self.provenance,
+                    view_ni,
+                    &self.recipe,
+                    *idx,
+                )
+            })
             .collect::<Result<Vec<_>, ReadySetError>>()?
             .into_iter()
             .collect::<Option<Vec<_>>>();
@@ -570,6 +687,36 @@ impl DfState {
         self.table_builder_by_index(ni)
     }
 
+    /// Returns every column added to or dropped from the base table with the given name since it
+    /// was created, in order
+    pub(super) fn base_column_history(
+        &self,
+        name: &Relation,
+    ) -> ReadySetResult<Vec<BaseColumnEvent>> {
+        let ni = self
+            .recipe
+            .node_addr_for(name)
+            .map_err(|_| ReadySetError::TableNotFound {
+                name: name.name.clone().into(),
+                schema: name.schema.clone().map(Into::into),
+            })?;
+        let node = self
+            .ingredients
+            .node_weight(ni)
+            .ok_or_else(|| ReadySetError::NodeNotFound { index: ni.index() })?;
+        let base = node
+            .get_base()
+            .ok_or_else(|| internal_err!("table {} is not a base node", name.display_unquoted()))?;
+        Ok(base.column_history().to_vec())
+    }
+
+    /// Obtain a TableBuilder for the base node with the given index.
+    ///
+    /// Returns [`ReadySetError::MutatorBuildFailed`] if `ni` is not a base node (including if it
+    /// has been dropped), or if the base node's domain isn't running. Base tables with neither a
+    /// primary key nor a sharding column are built with an empty key, which only supports inserts
+    /// and deletes of whole rows, unless they're sharded, in which case there's no way to route
+    /// writes to shards and [`MutatorBuildError::NoKeyAvailable`] is returned.
     pub(super) fn table_builder_by_index(
         &self,
         ni: NodeIndex,
@@ -579,15 +726,20 @@ impl DfState {
             .node_weight(ni)
             .ok_or_else(|| ReadySetError::NodeNotFound { index: ni.index() })?;
         let base = node.name();
+        let build_failed = |reason| ReadySetError::MutatorBuildFailed {
+            node_index: ni.index(),
+            reason,
+        };
 
         trace!(base = %base.display_unquoted(), "creating table");
 
-        let mut key = node
-            .get_base()
-            .ok_or_else(|| ReadySetError::InvalidNodeType {
-                node_index: node.local_addr().id(),
-                expected_type: NodeType::Base,
-            })?
+        let base_operator = node.get_base().ok_or_else(|| {
+            build_failed(MutatorBuildError::NotABaseNode {
+                actual_kind: node.error_node_type(),
+            })
+        })?;
+
+        let mut key = base_operator
             .primary_key()
             .map(|k| k.to_owned())
             .unwrap_or_default();
@@ -601,12 +753,11 @@ impl DfState {
             is_primary = true;
         }
 
-        let domain =
-            self.domains
-                .get(&node.domain())
-                .ok_or_else(|| ReadySetError::UnknownDomain {
-                    domain_index: node.domain().index(),
-                })?;
+        let domain = self.domains.get(&node.domain()).ok_or_else(|| {
+            build_failed(MutatorBuildError::DomainNotRunning {
+                domain: node.domain().index(),
+            })
+        })?;
 
         invariant_eq!(
             domain.num_replicas(),
@@ -614,6 +765,10 @@ impl DfState {
             "Base table domains can't be replicated"
         );
 
+        if key.is_empty() && domain.num_shards() > 1 {
+            return Err(build_failed(MutatorBuildError::NoKeyAvailable));
+        }
+
         let txs = (0..domain.num_shards())
             .map(|shard| {
                 let replica_addr = ReplicaAddress {
@@ -623,15 +778,9 @@ impl DfState {
                 };
                 self.channel_coordinator
                     .get_addr(&replica_addr)
-                    .ok_or_else(|| {
-                        internal_err!("failed to get channel coordinator for {}", replica_addr)
-                    })
+                    .ok_or_else(|| build_failed(MutatorBuildError::NoChannelForShard { shard }))
             })
             .collect::<ReadySetResult<Vec<_>>>()?;
-
-        let base_operator = node
-            .get_base()
-            .ok_or_else(|| internal_err!("asked to get table for non-base node"))?;
         let columns: Vec<SqlIdentifier> = node
             .columns()
             .iter()
@@ -704,6 +853,102 @@ impl DfState {
         Ok(GraphStats { domains })
     }
 
+    /// Get the most recent packets which took longer than the configured slow node threshold to
+    /// process at a single node, from every running domain replica, with node names resolved from
+    /// the graph
+    pub(super) async fn recent_slow_operations(&self) -> ReadySetResult<Vec<SlowOperation>> {
+        let mut slow_operations = vec![];
+        for dh in self.domains.values() {
+            slow_operations.extend(
+                dh.send_to_healthy::<Vec<SlowOperation>>(
+                    DomainRequest::GetSlowOperations,
+                    &self.workers,
+                )
+                .await?
+                .into_cells()
+                .into_iter()
+                .flatten(/* Discard results from non-running domains */)
+                .flatten(),
+            );
+        }
+
+        for slow_operation in &mut slow_operations {
+            slow_operation.node_name = self
+                .ingredients
+                .node_weight(slow_operation.node)
+                .map(|n| n.name().clone());
+        }
+
+        Ok(slow_operations)
+    }
+
+    /// Tag the given fraction (between 0 and 1) of the writes received by base tables with a
+    /// trace, so that the latency of their propagation to each reader is recorded in the
+    /// statistics for the reader. Applies to all running domains, as well as any domains created
+    /// later.
+    pub(super) async fn set_write_tracing(&mut self, sample_rate: f64) -> ReadySetResult<()> {
+        if !(0.0..=1.0).contains(&sample_rate) {
+            return Err(ReadySetError::BadRequest(format!(
+                "Write tracing sample rate must be between 0 and 1, got {sample_rate}"
+            )));
+        }
+
+        let samples_per_million = (sample_rate * 1_000_000.0).round() as u32;
+        self.domain_config.write_tracing_samples_per_million = samples_per_million;
+        for dh in self.domains.values() {
+            dh.send_to_healthy::<()>(
+                DomainRequest::SetWriteTracing {
+                    samples_per_million,
+                },
+                &self.workers,
+            )
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Analyze the lookups performed against the reader for the view maintained on the given
+    /// node, recommending how (if at all) the key of the view should be changed, without changing
+    /// anything.
+    ///
+    /// The recommendation can be applied with [`Migration::rekey_view`].
+    pub(super) async fn analyze_view(&self, leaf: NodeIndex) -> ReadySetResult<ViewAnalysis> {
+        let reader_node = self
+            .any_reader_for(leaf)
+            .ok_or(ReadySetError::ReaderNotFound)?;
+        #[allow(clippy::indexing_slicing)] // `any_reader_for` returns valid indices
+        let node = &self.ingredients[reader_node];
+        let key = node
+            .as_reader()
+            .and_then(|r| r.key())
+            .ok_or(ReadySetError::ReaderNotFound)?
+            .to_vec();
+        let domain = node.domain();
+        let request = DomainRequest::RequestReaderLookupStats {
+            node: node.local_addr(),
+        };
+
+        let mut lookups = ReaderLookupStats::default();
+        for shard_stats in self
+            .domains
+            .get(&domain)
+            .ok_or_else(|| ReadySetError::UnknownDomain {
+                domain_index: domain.index(),
+            })?
+            .send_to_healthy::<Option<ReaderLookupStats>>(request, &self.workers)
+            .await?
+            .into_cells()
+            .into_iter()
+            .flatten(/* Discard results from non-running domains */)
+            .flatten()
+        {
+            lookups.merge(&shard_stats);
+        }
+
+        Ok(ViewAnalysis::new(key, lookups))
+    }
+
     pub(super) fn get_instances(&self) -> Vec<(WorkerIdentifier, bool)> {
         self.workers
             .iter()
@@ -754,6 +999,53 @@ impl DfState {
         .to_string())
     }
 
+    /// Returns the indices of all the non-dropped nodes in the graph which the given node
+    /// (transitively) receives updates from, in breadth-first order, not including the node
+    /// itself or the source node.
+    pub(super) fn node_ancestors(&self, node: NodeIndex) -> ReadySetResult<Vec<NodeIndex>> {
+        self.nodes_reachable_from(node, Direction::Incoming)
+    }
+
+    /// Returns the indices of all the non-dropped nodes in the graph which (transitively) receive
+    /// updates from the given node, in breadth-first order, not including the node itself.
+    pub(super) fn node_descendants(&self, node: NodeIndex) -> ReadySetResult<Vec<NodeIndex>> {
+        self.nodes_reachable_from(node, Direction::Outgoing)
+    }
+
+    fn nodes_reachable_from(
+        &self,
+        node: NodeIndex,
+        direction: Direction,
+    ) -> ReadySetResult<Vec<NodeIndex>> {
+        if self
+            .ingredients
+            .node_weight(node)
+            .map_or(true, |n| n.is_dropped())
+        {
+            return Err(ReadySetError::NodeNotFound {
+                index: node.index(),
+            });
+        }
+
+        let mut res = vec![];
+        let mut seen = HashSet::from([node]);
+        let mut queue = VecDeque::from([node]);
+        while let Some(ni) = queue.pop_front() {
+            for next in self.ingredients.neighbors_directed(ni, direction) {
+                if next == self.source || !seen.insert(next) {
+                    continue;
+                }
+                #[allow(clippy::indexing_slicing)] // neighbors must be in self.ingredients
+                if self.ingredients[next].is_dropped() {
+                    continue;
+                }
+                res.push(next);
+                queue.push_back(next);
+            }
+        }
+        Ok(res)
+    }
+
     /// List data-flow nodes, on a specific worker if `worker` specified.
     pub(super) fn nodes_on_worker(
         &self,
@@ -1115,6 +1407,160 @@ impl DfState {
         Ok(true)
     }
 
+    /// Check the health of every domain replica and worker known to the controller, by
+    /// concurrently sending a [`DomainRequest::Ping`] to every domain replica and a
+    /// [`WorkerRequestKind::Ping`] to every worker.
+    ///
+    /// Any check which does not complete within `deadline` is reported as
+    /// [`ComponentHealth::TimedOut`]. The returned report does not include the health of the read
+    /// path, which is checked by the client.
+    pub(super) async fn health_report(&self, deadline: Duration) -> HealthReport {
+        fn component_health<T>(
+            res: Result<ReadySetResult<T>, tokio::time::error::Elapsed>,
+        ) -> ComponentHealth {
+            match res {
+                Ok(Ok(_)) => ComponentHealth::Healthy,
+                Ok(Err(e)) => ComponentHealth::Failed(e.to_string()),
+                Err(_) => ComponentHealth::TimedOut,
+            }
+        }
+
+        let domains = join_all(self.domains.values().flat_map(|dh| {
+            (0..dh.num_shards()).flat_map(move |shard| {
+                (0..dh.num_replicas()).map(move |replica| async move {
+                    let replica_address = ReplicaAddress {
+                        domain_index: dh.index(),
+                        shard,
+                        replica,
+                    };
+                    let health = if dh.assignment(shard, replica).is_none() {
+                        ComponentHealth::Unplaced
+                    } else {
+                        component_health(
+                            tokio::time::timeout(
+                                deadline,
+                                dh.send_to_healthy_shard_replica::<()>(
+                                    shard,
+                                    replica,
+                                    DomainRequest::Ping,
+                                    &self.workers,
+                                ),
+                            )
+                            .await,
+                        )
+                    };
+                    DomainHealth {
+                        replica_address,
+                        health,
+                    }
+                })
+            })
+        }));
+
+        let workers = join_all(self.workers.iter().map(|(uri, worker)| async move {
+            WorkerHealth {
+                uri: uri.clone(),
+                health: component_health(
+                    tokio::time::timeout(deadline, worker.rpc::<()>(WorkerRequestKind::Ping)).await,
+                ),
+            }
+        }));
+
+        let (domains, workers) = futures::join!(domains, workers);
+        HealthReport::new(domains, workers, None)
+    }
+
+    /// Returns the indices of all the domains in the graph, ordered such that each domain comes
+    /// after all of the domains that send updates to it.
+    fn domains_in_topological_order(&self) -> Vec<DomainIndex> {
+        let mut domains = Vec::with_capacity(self.domains.len());
+        let mut seen = HashSet::new();
+        let mut topo = petgraph::visit::Topo::new(&self.ingredients);
+        while let Some(node) = topo.next(&self.ingredients) {
+            if node == self.source {
+                continue;
+            }
+            #[allow(clippy::indexing_slicing)] // node must be in self.ingredients
+            let node = &self.ingredients[node];
+            if node.is_dropped() {
+                continue;
+            }
+            let domain = node.domain();
+            if self.domains.contains_key(&domain) && seen.insert(domain) {
+                domains.push(domain);
+            }
+        }
+        domains
+    }
+
+    /// Returns whether every healthy replica of `domain` has received all of the packets for the
+    /// barrier with the given `id` that were counted in `sent`. Replicas which aren't healthy are
+    /// treated as not caught up, and a domain which no longer exists is treated as caught up.
+    ///
+    /// See [`DfStateHandle::wait_for_stabilization`].
+    async fn barrier_caught_up(
+        &self,
+        domain: DomainIndex,
+        id: u64,
+        sent: &HashMap<ReplicaAddress, usize>,
+    ) -> ReadySetResult<bool> {
+        let Some(dh) = self.domains.get(&domain) else {
+            return Ok(true);
+        };
+        let states = dh
+            .send_to_healthy::<BarrierState>(DomainRequest::QueryBarrier { id }, &self.workers)
+            .await?;
+        Ok(states.into_entries().all(|((shard, replica), state)| {
+            let Some(state) = state else {
+                return false;
+            };
+            let addr = ReplicaAddress {
+                domain_index: domain,
+                shard,
+                replica,
+            };
+            state.received >= sent.get(&addr).copied().unwrap_or_default()
+        }))
+    }
+
+    /// Start the barrier with the given `id` in `domain`, adding the number of barrier packets it
+    /// sent to each of its children to `sent`.
+    ///
+    /// See [`DfStateHandle::wait_for_stabilization`].
+    async fn start_barrier(
+        &self,
+        domain: DomainIndex,
+        id: u64,
+        sent: &mut HashMap<ReplicaAddress, usize>,
+    ) -> ReadySetResult<()> {
+        let Some(dh) = self.domains.get(&domain) else {
+            return Ok(());
+        };
+        let states = dh
+            .send_to_healthy::<BarrierState>(DomainRequest::Barrier { id }, &self.workers)
+            .await?;
+        for state in states.into_cells().into_iter().flatten() {
+            for (dest, count) in state.sent {
+                *sent.entry(dest).or_default() += count;
+            }
+        }
+        Ok(())
+    }
+
+    /// Clear the state of the barrier with the given `id` in `domain`, logging (rather than
+    /// returning) any errors.
+    async fn clear_barrier(&self, domain: DomainIndex, id: u64) {
+        let Some(dh) = self.domains.get(&domain) else {
+            return;
+        };
+        if let Err(error) = dh
+            .send_to_healthy::<()>(DomainRequest::ClearBarrier { id }, &self.workers)
+            .await
+        {
+            warn!(%error, %domain, "Failed to clear barrier state");
+        }
+    }
+
     /// Return a map of node indices to key counts.
     pub(super) async fn node_sizes(&self) -> ReadySetResult<HashMap<NodeIndex, NodeSize>> {
         // Copying the keys into a vec here is a workaround for a higher order
@@ -1182,6 +1628,52 @@ impl DfState {
         Ok(r)
     }
 
+    /// Perform a new query schema migration like [`Self::migrate`], but if either `f` or committing
+    /// the migration returns an error, kill any domains the migration booted before it failed.
+    ///
+    /// The rest of the changes the failed migration made to `self` are left in place - callers
+    /// always run migrations against a copy of the state (usually through a [`DfStateWriter`]),
+    /// which they discard rather than commit if the migration fails.
+    pub(crate) async fn try_migrate<F, T>(
+        &mut self,
+        dry_run: bool,
+        dialect: Dialect,
+        f: F,
+    ) -> ReadySetResult<T>
+    where
+        F: FnOnce(&mut Migration<'_>) -> ReadySetResult<T>,
+    {
+        let existing_domains = self.domain_indices();
+        match self.migrate(dry_run, dialect, f).await {
+            Ok(r) => Ok(r),
+            Err(error) => {
+                warn!(%error, "Migration failed, killing any domains it booted");
+                self.kill_new_domains(&existing_domains).await;
+                gauge!(recorded::CONTROLLER_MIGRATION_IN_PROGRESS, 0.0);
+                Err(error)
+            }
+        }
+    }
+
+    /// Returns the indices of all the domains in the graph
+    pub(super) fn domain_indices(&self) -> HashSet<DomainIndex> {
+        self.domains.keys().copied().collect()
+    }
+
+    /// Kill all the domains which aren't in `existing_domains`, logging (rather than returning)
+    /// any error, since this is only done to clean up after another error
+    async fn kill_new_domains(&mut self, existing_domains: &HashSet<DomainIndex>) {
+        let new_domains = self
+            .domains
+            .keys()
+            .filter(|di| !existing_domains.contains(di))
+            .copied()
+            .collect::<Vec<_>>();
+        if let Err(error) = self.kill_domains(new_domains).await {
+            warn!(%error, "Failed to kill domains booted by failed migration");
+        }
+    }
+
     /// Controls the persistence mode, and parameters related to persistence.
     ///
     /// Three modes are available:
@@ -1258,7 +1750,13 @@ impl DfState {
                     shard: if num_shards > 1 { Some(shard) } else { None },
                     replica,
                     nshards: num_shards,
-                    config: self.domain_config.clone(),
+                    config: match self.domain_tuning_overrides.get(&idx) {
+                        Some(tuning) => DomainConfig {
+                            tuning: tuning.clone(),
+                            ..self.domain_config.clone()
+                        },
+                        None => self.domain_config.clone(),
+                    },
                     nodes: domain_nodes.clone(),
                     persistence_parameters: self.persistence.clone(),
                 };
@@ -1357,6 +1855,9 @@ impl DfState {
         &mut self,
         removals: &[NodeIndex],
     ) -> Result<(), ReadySetError> {
+        self.provenance
+            .invalidate_descendants(&self.ingredients, removals.iter().copied());
+
         // Remove node from controller local state
         let mut domain_removals: HashMap<DomainIndex, Vec<LocalNodeIndex>> = HashMap::default();
         for ni in removals {
@@ -1550,7 +2051,7 @@ impl DfState {
         let mut new = self.recipe.clone();
 
         let r = self
-            .migrate(dry_run, changelist.dialect, |mig| {
+            .try_migrate(dry_run, changelist.dialect, |mig| {
                 new.activate(mig, changelist)
             })
             .await;
@@ -1797,8 +2298,12 @@ impl DfState {
         self.materializations
             .extend(&mut self.ingredients, &new, &dmp)?;
 
-        self.materializations
-            .commit(&mut self.ingredients, &new, &mut dmp)?;
+        self.materializations.commit(
+            &mut self.ingredients,
+            &new,
+            &mut dmp,
+            &mut ProgressReporter::default(),
+        )?;
 
         Ok(dmp)
     }
@@ -1944,7 +2449,85 @@ impl DfStateHandle {
         writer: DfStateWriter<'_>,
         authority: &Arc<Authority>,
     ) -> ReadySetResult<()> {
-        let new_state = &writer.state;
+        self.commit_state(&writer.state, authority).await
+    }
+
+    /// Commits the changes made to the dataflow state by a migration run with
+    /// [`DfState::try_migrate`], like [`Self::commit`], but if committing fails, kills any domains
+    /// which aren't in `existing_domains` (the [`DfState::domain_indices`] from before the
+    /// migration), since the migration booted them but they'll never be part of the committed
+    /// state.
+    pub(super) async fn commit_migration(
+        &self,
+        mut writer: DfStateWriter<'_>,
+        existing_domains: &HashSet<DomainIndex>,
+        authority: &Arc<Authority>,
+    ) -> ReadySetResult<()> {
+        let res = self.commit_state(&writer.state, authority).await;
+        if let Err(error) = &res {
+            warn!(%error, "Committing migration failed, killing any domains it booted");
+            writer.state.kill_new_domains(existing_domains).await;
+        }
+        res
+    }
+
+    /// Wait for all updates which were sent into the dataflow graph before this method was called
+    /// to be processed by every domain in the graph, returning an error if that takes longer than
+    /// `timeout`.
+    ///
+    /// This works by starting a barrier (see [`DomainRequest::Barrier`]) in each domain in
+    /// topological order, but only once every healthy replica of that domain has received every
+    /// barrier packet sent to it by the domains upstream of it. Each domain sends the barrier to
+    /// each of its children once, so this takes one round of polling per domain, and sends one
+    /// barrier packet per edge between domain replicas.
+    ///
+    /// The read lock over the dataflow state is only held for each request to the domains, not
+    /// between polls, so that waiting doesn't block migrations from committing.
+    pub(super) async fn wait_for_stabilization(&self, timeout: Duration) -> ReadySetResult<()> {
+        let id = rand::thread_rng().gen::<u64>();
+        let domains = self.read().await.domains_in_topological_order();
+
+        let res: Result<ReadySetResult<()>, _> = tokio::time::timeout(timeout, async {
+            // The total number of barrier packets sent to each domain replica by the domains we've
+            // started the barrier in so far. Since we go in topological order, by the time we get
+            // to a domain this includes every barrier packet that will ever be sent to it.
+            let mut sent: HashMap<ReplicaAddress, usize> = HashMap::new();
+            for domain in &domains {
+                while !self
+                    .read()
+                    .await
+                    .barrier_caught_up(*domain, id, &sent)
+                    .await?
+                {
+                    trace!(%id, %domain, "Waiting for barrier to propagate");
+                    tokio::time::sleep(WAIT_FOR_STABILIZATION_POLL_INTERVAL).await;
+                }
+
+                self.read()
+                    .await
+                    .start_barrier(*domain, id, &mut sent)
+                    .await?;
+            }
+
+            Ok(())
+        })
+        .await;
+
+        {
+            let ds = self.read().await;
+            for domain in &domains {
+                ds.clear_barrier(*domain, id).await;
+            }
+        }
+
+        res.map_err(|_| ReadySetError::StabilizationTimeout)?
+    }
+
+    async fn commit_state(
+        &self,
+        new_state: &DfState,
+        authority: &Arc<Authority>,
+    ) -> ReadySetResult<()> {
         if let Some(local) = authority.as_local() {
             local.update_controller_in_place(|state: Option<&mut ControllerState>| match state {
                 None => {
@@ -8,6 +8,8 @@ use readyset_data::Dialect;
 use reqwest::Url;
 use tokio::sync::mpsc::Sender;
 
+use crate::controller::migrate::background::MigrationHandle;
+use crate::controller::migrate::group::{GroupMigration, GroupOutcome};
 use crate::controller::migrate::Migration;
 use crate::controller::HandleRequest;
 use crate::ControllerDescriptor;
@@ -108,6 +110,104 @@ impl Handle {
         ret_rx.await.unwrap()
     }
 
+    /// Perform a single migration which makes the changes of each of the given functions, in order,
+    /// returning their results.
+    ///
+    /// This is equivalent to calling [`Handle::migrate`] once for each function, except that all
+    /// the changes are committed together, so the (fixed) overhead of committing a migration is
+    /// only paid once for the whole batch.
+    pub async fn migrate_batch<F, T>(&mut self, fs: Vec<F>) -> Vec<T>
+    where
+        F: FnOnce(&mut Migration<'_>) -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        self.migrate(move |mig| fs.into_iter().map(|f| f(mig)).collect())
+            .await
+    }
+
+    /// Perform a single migration which installs a group of related queries all at once, with
+    /// all-or-nothing semantics.
+    ///
+    /// `f` adds each query of the group with [`GroupMigration::query`]. If `f` returns an error,
+    /// any of the queries fails to be added (even if `f` ignores the error), or committing the
+    /// migration fails, none of the queries are installed and the graph is left as
+    /// it was before the migration began. Otherwise, the returned [`GroupOutcome`] records the leaf
+    /// node and reader of each query. None of the views added by the group can be looked up until
+    /// the whole group has been committed.
+    pub async fn migrate_group<F>(&mut self, f: F) -> ReadySetResult<GroupOutcome>
+    where
+        F: FnOnce(&mut GroupMigration<'_, '_>) -> ReadySetResult<()> + Send + 'static,
+    {
+        let (done_tx, done_rx) = tokio::sync::oneshot::channel();
+        self.event_tx
+            .as_mut()
+            .unwrap()
+            .send(HandleRequest::PerformGroupMigration {
+                func: Box::new(f),
+                // This function is only used in tests at the moment, so we just hardcode the MySQL
+                // dialect
+                dialect: Dialect::DEFAULT_MYSQL,
+                done_tx,
+            })
+            .await
+            .expect("Controller dropped, failed, or panicked");
+
+        done_rx.await.unwrap()
+    }
+
+    /// Perform a migration like [`Handle::migrate`], but commit it in the background.
+    ///
+    /// `f` is run to make its changes to the graph before this method returns, but the long-running
+    /// part of committing the migration (booting new domains, sending new nodes to domains, and
+    /// running the replays needed to materialize them) happens in the background, where it can be
+    /// observed and aborted via the returned [`MigrationHandle`]. Migrations started after this one
+    /// wait for it to finish before running.
+    ///
+    /// Existing views can be read from as normal while the migration is being committed, but
+    /// requesting any of the views added by the migration returns [`ReadySetError::ViewNotReady`]
+    /// until it's done.
+    pub async fn migrate_async<F, T>(&mut self, f: F) -> MigrationHandle<T>
+    where
+        F: FnOnce(&mut Migration<'_>) -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        let (ret_tx, ret_rx) = tokio::sync::oneshot::channel();
+        let (status_tx, status_rx) = tokio::sync::watch::channel(Default::default());
+        let (abort_tx, abort_rx) = tokio::sync::oneshot::channel();
+        let (ready_tx, ready_rx) = tokio::sync::oneshot::channel();
+        let b = Box::new(move |m: &mut Migration<'_>| {
+            if ret_tx.send(f(m)).is_err() {
+                internal!("could not return migration result")
+            }
+            Ok(())
+        });
+
+        self.event_tx
+            .as_mut()
+            .unwrap()
+            .send(HandleRequest::PerformBackgroundMigration {
+                func: b,
+                // This function is only used in tests at the moment, so we just hardcode the MySQL
+                // dialect
+                dialect: Dialect::DEFAULT_MYSQL,
+                status_tx,
+                abort_rx,
+                ready_tx,
+            })
+            .await
+            .expect("Controller dropped, failed, or panicked");
+
+        let ret = ret_rx
+            .await
+            .expect("Migration failed before making any changes");
+        // Don't return until the views added by the migration are known to be pending, so that
+        // requesting them can't race with the migration being committed
+        ready_rx
+            .await
+            .expect("Migration failed before making any changes");
+        MigrationHandle::new(ret, status_rx, abort_tx)
+    }
+
     #[cfg(feature = "failure_injection")]
     /// Injects a failpoint with the provided name/action
     pub async fn set_failpoint<S: std::fmt::Display>(&mut self, name: S, action: S) {
@@ -22,7 +22,8 @@ use dataflow::ops::union::{self, Union};
 use dataflow::ops::Side;
 use dataflow::utils::{dataflow_column, make_columns};
 use dataflow::{
-    BinaryOperator, DurabilityMode, Expr as DfExpr, PersistenceParameters, ReaderProcessing,
+    BinaryOperator, DomainTuning, DurabilityMode, Expr as DfExpr, PersistenceParameters,
+    ReaderProcessing,
 };
 use futures::{join, StreamExt};
 use itertools::Itertools;
@@ -30,13 +31,22 @@ use nom_sql::{
     parse_create_table, parse_create_view, parse_query, parse_select_statement, OrderType,
     Relation, SqlQuery,
 };
+use petgraph::graph::NodeIndex;
 use readyset_client::consensus::{Authority, LocalAuthority, LocalAuthorityStore};
 use readyset_client::consistency::Timestamp;
+use readyset_client::debug::info::BaseColumnEvent;
+use readyset_client::debug::stats::ViewRecommendation;
+use readyset_client::health::{ComponentHealth, HealthStatus};
 use readyset_client::internal::LocalNodeIndex;
 use readyset_client::recipe::changelist::{Change, ChangeList, CreateCache};
-use readyset_client::{KeyComparison, Modification, SchemaType, ViewPlaceholder, ViewQuery};
+use readyset_client::tools::{read_view_export, ExportDest};
+use readyset_client::{
+    KeyComparison, Modification, ReadError, ReaderHandle, ScanSummary, SchemaType, ViewPlaceholder,
+    ViewQuery,
+};
 use readyset_data::{Bound, DfType, DfValue, Dialect, IntoBoundedRange};
 use readyset_errors::ReadySetError::{self, RpcFailed, SelectQueryCreationFailed};
+use readyset_errors::{MutatorBuildError, NodeType, ReadySetResult, UnsupportedFeature};
 use readyset_util::eventually;
 use readyset_util::shutdown::ShutdownSender;
 use rust_decimal::prelude::ToPrimitive;
@@ -48,9 +58,13 @@ use tokio::sync::mpsc;
 use tokio_stream::wrappers::ReceiverStream;
 use vec1::vec1;
 
+use crate::controller::migrate::background::{BackgroundMigrationStatus, MigrationHandle};
+use crate::controller::migrate::group::GroupMigration;
+use crate::controller::migrate::progress::MigrationPhase;
+use crate::controller::migrate::Migration;
 use crate::controller::sql::SqlIncorporator;
 use crate::integration_utils::*;
-use crate::{get_col, Builder};
+use crate::{get_col, Builder, Handle};
 
 #[tokio::test(flavor = "multi_thread")]
 async fn it_completes() {
@@ -94,18 +108,24 @@ async fn it_completes() {
     assert_eq!(muta.columns(), &["a", "b"]);
 
     muta.insert(vec![id.clone(), 2.into()]).await.unwrap();
-    sleep().await;
+    g.wait_for_stabilization(Duration::from_secs(5))
+        .await
+        .unwrap();
     assert_eq!(
         cq.lookup(&[id.clone()], true).await.unwrap().into_vec(),
         vec![vec![1.into(), 2.into()]]
     );
     mutb.insert(vec![id.clone(), 4.into()]).await.unwrap();
-    sleep().await;
+    g.wait_for_stabilization(Duration::from_secs(5))
+        .await
+        .unwrap();
     let res = cq.lookup(&[id.clone()], true).await.unwrap().into_vec();
     assert!(res.iter().any(|r| *r == vec![id.clone(), 2.into()]));
     assert!(res.iter().any(|r| *r == vec![id.clone(), 4.into()]));
     muta.delete(vec![id.clone()]).await.unwrap();
-    sleep().await;
+    g.wait_for_stabilization(Duration::from_secs(5))
+        .await
+        .unwrap();
     assert_eq!(
         cq.lookup(&[id.clone()], true).await.unwrap().into_vec(),
         vec![vec![1.into(), 4.into()]]
@@ -321,7 +341,9 @@ async fn sharded_shuffle() {
         .await
         .unwrap();
 
-    sleep().await;
+    g.wait_for_stabilization(Duration::from_secs(5))
+        .await
+        .unwrap();
 
     // moment of truth
     let rows = view
@@ -2471,6 +2493,78 @@ async fn migrate_drop_columns() {
     shutdown_tx.shutdown().await;
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn drop_then_add_column_keeps_old_view_semantics() {
+    let id: DfValue = "x".into();
+
+    let (mut g, shutdown_tx) =
+        start_simple_unsharded("drop_then_add_column_keeps_old_view_semantics").await;
+    let a = g
+        .migrate(|mig| {
+            let a = mig.add_base(
+                "a",
+                make_columns(&["a", "b"]),
+                Base::new().with_default_values(vec!["a".into(), "b".into()]),
+            );
+            // a view built against column b
+            let v = mig.add_ingredient(
+                "v",
+                make_columns(&["a", "b"]),
+                Project::new(
+                    a,
+                    vec![
+                        DfExpr::Column {
+                            index: 0,
+                            ty: DfType::Unknown,
+                        },
+                        DfExpr::Column {
+                            index: 1,
+                            ty: DfType::Unknown,
+                        },
+                    ],
+                ),
+            );
+            mig.maintain_anonymous(v, &Index::hash_map(vec![0]));
+            a
+        })
+        .await;
+    let mut vq = g.view("v").await.unwrap().into_reader_handle().unwrap();
+
+    // drop b, then add c, which must not take over b's place in the old view
+    g.migrate(move |mig| {
+        mig.drop_column(a, 1).unwrap();
+    })
+    .await;
+    g.migrate(move |mig| {
+        assert_eq!(
+            mig.add_column(a, dataflow_column("c"), "c".into()).unwrap(),
+            2
+        );
+    })
+    .await;
+
+    let mut muta = g.table("a").await.unwrap();
+    assert_eq!(muta.columns().len(), 2);
+    muta.insert(vec![id.clone(), "cy".into()]).await.unwrap();
+    sleep().await;
+
+    let res = vq.lookup(&[id.clone()], true).await.unwrap().into_vec();
+    assert_eq!(res, vec![vec![id.clone(), "b".into()]]);
+
+    assert_eq!(
+        g.base_column_history(&"a".into()).await.unwrap(),
+        vec![
+            BaseColumnEvent::Dropped { column: 1 },
+            BaseColumnEvent::Added {
+                column: 2,
+                default: "c".into()
+            },
+        ]
+    );
+
+    shutdown_tx.shutdown().await;
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn key_on_added() {
     // set up graph
@@ -2523,6 +2617,385 @@ async fn key_on_added() {
     shutdown_tx.shutdown().await;
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn get_nodes_by_name() {
+    let (mut g, shutdown_tx) = start_simple_unsharded("get_nodes_by_name").await;
+    let (a, x) = g
+        .migrate(|mig| {
+            let a = mig.add_base(
+                "a",
+                make_columns(&["a", "b"]),
+                Base::new().with_primary_key([0]),
+            );
+            let x = mig.add_ingredient(
+                "x",
+                make_columns(&["b"]),
+                Project::new(
+                    a,
+                    vec![DfExpr::Column {
+                        index: 1,
+                        ty: DfType::Unknown,
+                    }],
+                ),
+            );
+            mig.maintain_anonymous(x, &Index::hash_map(vec![0]));
+            (a, x)
+        })
+        .await;
+
+    assert_eq!(g.get_base_node_by_name(&"a".into()).await.unwrap(), Some(a));
+    assert_eq!(g.get_base_node_by_name(&"x".into()).await.unwrap(), None);
+    assert_eq!(g.get_base_node_by_name(&"b".into()).await.unwrap(), None);
+
+    let mut a_table = g.table_by_index(a).await.unwrap();
+    assert_eq!(*a_table.table_name(), "a".into());
+    a_table.insert(vec![1.into(), 2.into()]).await.unwrap();
+
+    let reader = g.get_view_by_name(&"x".into()).await.unwrap().unwrap();
+    assert_ne!(reader, x);
+    assert_eq!(g.get_view_by_name(&"a".into()).await.unwrap(), None);
+
+    shutdown_tx.shutdown().await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn analyze_and_rekey_view() {
+    let (mut g, shutdown_tx) = start_simple_unsharded("analyze_and_rekey_view").await;
+    let a = g
+        .migrate(|mig| {
+            let a = mig.add_base("a", make_columns(&["a", "b", "c"]), Base::default());
+            mig.maintain_anonymous(a, &Index::hash_map(vec![0]));
+            a
+        })
+        .await;
+
+    let mut table = g.table_by_index(a).await.unwrap();
+    table
+        .insert_many((0..10i32).map(|i| {
+            vec![
+                DfValue::from(i % 2),
+                DfValue::from(i),
+                DfValue::from(i * 10),
+            ]
+        }))
+        .await
+        .unwrap();
+    sleep().await;
+
+    // Every lookup filters on column 2 after looking up column 0
+    let mut reader = g.view("a").await.unwrap().into_reader_handle().unwrap();
+    for _ in 0..20 {
+        let res = reader
+            .raw_lookup(ViewQuery {
+                key_comparisons: vec![KeyComparison::Equal(vec1![0.into()])],
+                block: true,
+                filter: Some(DfExpr::Op {
+                    left: Box::new(DfExpr::Column {
+                        index: 2,
+                        ty: DfType::Int,
+                    }),
+                    op: BinaryOperator::Equal,
+                    right: Box::new(DfExpr::Literal {
+                        val: 20.into(),
+                        ty: DfType::Int,
+                    }),
+                    ty: DfType::Bool,
+                }),
+                timestamp: None,
+                limit: None,
+                offset: None,
+            })
+            .await
+            .unwrap()
+            .into_vec();
+        assert_eq!(
+            res,
+            vec![vec![DfValue::from(0), DfValue::from(2), DfValue::from(20)]]
+        );
+    }
+
+    let analysis = g.analyze_view(a).await.unwrap();
+    assert_eq!(analysis.key, vec![0]);
+    assert_eq!(analysis.lookups.lookups, 20);
+    assert_eq!(analysis.lookups.misses, 0);
+    assert_eq!(analysis.lookups.filter_columns, vec![0, 0, 20]);
+    assert_eq!(
+        analysis.recommendation,
+        ViewRecommendation::Rekey {
+            columns: vec![0, 2]
+        }
+    );
+
+    // Rekeying the same view twice in a migration replaces the reader from the first rekey
+    g.migrate(move |mig| {
+        let first = mig.rekey_view(a, vec![2]).unwrap();
+        let second = mig.rekey_view(a, vec![0, 2]).unwrap();
+        assert_ne!(first, second);
+    })
+    .await;
+
+    let mut reader = g.view("a").await.unwrap().into_reader_handle().unwrap();
+    let res = reader
+        .lookup(&[1.into(), 30.into()], true)
+        .await
+        .unwrap()
+        .into_vec();
+    assert_eq!(
+        res,
+        vec![vec![DfValue::from(1), DfValue::from(3), DfValue::from(30)]]
+    );
+
+    // The new reader starts with a clean slate
+    let analysis = g.analyze_view(a).await.unwrap();
+    assert_eq!(analysis.key, vec![0, 2]);
+    assert_eq!(analysis.lookups.lookups, 1);
+    assert_eq!(analysis.recommendation, ViewRecommendation::Keep);
+
+    shutdown_tx.shutdown().await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn full_replay_with_domain_tuning() {
+    let mut g = Builder::for_tests();
+    g.disable_partial();
+    g.set_persistence(get_persistence_params("full_replay_with_domain_tuning"));
+    g.configure_domains(
+        DomainTuning::builder()
+            .replay_batch_size(2)
+            .build()
+            .unwrap(),
+    );
+    let (mut g, shutdown_tx) = g.start_local().await.unwrap();
+    let base = g
+        .migrate(|mig| {
+            mig.add_base(
+                "base",
+                make_columns(&["id", "value"]),
+                Base::new().with_primary_key([0]),
+            )
+        })
+        .await;
+
+    let mut table = g.table_by_index(base).await.unwrap();
+    for i in 0..10 {
+        table
+            .insert(vec![DfValue::from(i), DfValue::from(i % 3)])
+            .await
+            .unwrap();
+    }
+    sleep().await;
+
+    // The existing rows are replayed to the new view from the base table's domain in pieces of two
+    // records, and then processed by the view's new domains with the overridden tuning
+    g.migrate(move |mig| {
+        mig.with_domain_tuning(
+            DomainTuning::builder()
+                .replay_batch_size(1)
+                .build()
+                .unwrap(),
+        );
+        let ident =
+            mig.add_ingredient("ident", make_columns(&["id", "value"]), Identity::new(base));
+        mig.maintain_anonymous(ident, &Index::hash_map(vec![1]));
+    })
+    .await;
+
+    let mut view = g.view("ident").await.unwrap().into_reader_handle().unwrap();
+    let mut ids = view
+        .lookup(&[0.into()], true)
+        .await
+        .unwrap()
+        .into_vec()
+        .into_iter()
+        .map(|row| row[0].clone())
+        .collect::<Vec<_>>();
+    ids.sort();
+    assert_eq!(
+        ids,
+        vec![
+            DfValue::from(0),
+            DfValue::from(3),
+            DfValue::from(6),
+            DfValue::from(9)
+        ]
+    );
+
+    shutdown_tx.shutdown().await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn column_provenance_invalidated_by_later_migrations() {
+    let (mut g, shutdown_tx) =
+        start_simple_unsharded("column_provenance_invalidated_by_later_migrations").await;
+    let base = g
+        .migrate(|mig| mig.add_base("a", make_columns(&["id", "value"]), Base::default()))
+        .await;
+
+    // Tracing the provenance of a node added by the migration caches it, but committing the
+    // migration can give the node new ancestors (eg for routing), so the trace must be dropped
+    let (ident, before) = g
+        .migrate(move |mig| {
+            let ident =
+                mig.add_ingredient("ident", make_columns(&["id", "value"]), Identity::new(base));
+            mig.maintain_anonymous(ident, &Index::hash_map(vec![0]));
+            let before = mig.column_provenance(ident, 1).unwrap();
+            assert!(mig.is_provenance_cached(ident, 1));
+            (ident, before)
+        })
+        .await;
+    assert_eq!(before.len(), 1);
+    assert_eq!(before[0].base, base);
+
+    let child = g
+        .migrate(move |mig| {
+            assert!(!mig.is_provenance_cached(ident, 1));
+            let paths = mig.column_provenance(ident, 1).unwrap();
+            assert_eq!(paths.len(), 1);
+            assert_eq!(paths[0].base, base);
+            assert_eq!(paths[0].column, Some(1));
+            assert_eq!(paths[0].nodes.first(), Some(&ident));
+            assert_eq!(paths[0].nodes.last(), Some(&base));
+            assert!(mig.is_provenance_cached(ident, 1));
+
+            let child = mig.add_ingredient(
+                "child",
+                make_columns(&["id", "value"]),
+                Identity::new(ident),
+            );
+            mig.maintain_anonymous(child, &Index::hash_map(vec![0]));
+            mig.column_provenance(child, 1).unwrap();
+            child
+        })
+        .await;
+
+    // Only the nodes whose ancestors were changed by the last migration lose their cached traces
+    g.migrate(move |mig| {
+        assert!(mig.is_provenance_cached(ident, 1));
+        assert!(!mig.is_provenance_cached(child, 1));
+        let paths = mig.column_provenance(child, 1).unwrap();
+        assert_eq!(paths.len(), 1);
+        assert_eq!(paths[0].base, base);
+        assert!(paths[0].nodes.contains(&ident));
+    })
+    .await;
+
+    shutdown_tx.shutdown().await;
+}
+
+/// Write rows to a base table while a migration runs a full replay from it, returning the total
+/// time taken for the writes to become visible in a view of the base table
+async fn write_latency_during_migration_replay(
+    name: &str,
+    migration_replay_min_share: u8,
+) -> Duration {
+    const NUM_ROWS: i32 = 2000;
+    const NUM_WRITES: i32 = 20;
+
+    let mut g = Builder::for_tests();
+    g.disable_partial();
+    g.set_persistence(get_persistence_params(name));
+    // Replay one record per piece, so that the full replay below is made up of many low-priority
+    // batches competing with the writes to the same base table
+    g.configure_domains(
+        DomainTuning::builder()
+            .replay_batch_size(1)
+            .migration_replay_min_share(migration_replay_min_share)
+            .build()
+            .unwrap(),
+    );
+    let (mut g, shutdown_tx) = g.start_local().await.unwrap();
+    let base = g
+        .migrate(|mig| {
+            let base = mig.add_base(
+                "base",
+                make_columns(&["id", "value"]),
+                Base::new().with_primary_key([0]),
+            );
+            let before = mig.add_ingredient(
+                "before",
+                make_columns(&["id", "value"]),
+                Identity::new(base),
+            );
+            mig.maintain_anonymous(before, &Index::hash_map(vec![0]));
+            base
+        })
+        .await;
+
+    let mut table = g.table_by_index(base).await.unwrap();
+    table
+        .insert_many((0..NUM_ROWS).map(|i| vec![DfValue::from(i), DfValue::from(0)]))
+        .await
+        .unwrap();
+    sleep().await;
+
+    let mut before = g
+        .view("before")
+        .await
+        .unwrap()
+        .into_reader_handle()
+        .unwrap();
+    let writes = async {
+        let mut total = Duration::ZERO;
+        for i in NUM_ROWS..(NUM_ROWS + NUM_WRITES) {
+            let start = std::time::Instant::now();
+            table
+                .insert(vec![DfValue::from(i), DfValue::from(0)])
+                .await
+                .unwrap();
+            eventually!(attempts: 5000, sleep: Duration::from_millis(1), run_test: {
+                before.lookup(&[i.into()], true).await.unwrap().into_vec()
+            }, then_assert: |rows| assert_eq!(rows.len(), 1));
+            total += start.elapsed();
+        }
+        total
+    };
+    let migration = g.migrate(move |mig| {
+        let after =
+            mig.add_ingredient("after", make_columns(&["id", "value"]), Identity::new(base));
+        mig.maintain_anonymous(after, &Index::hash_map(vec![1]));
+    });
+    let (latency, ()) = tokio::join!(writes, migration);
+
+    // The replay still ran to completion, and every row (including those written while the
+    // replay was running) made it to the new view
+    let mut after = g.view("after").await.unwrap().into_reader_handle().unwrap();
+    eventually!(run_test: {
+        after.lookup(&[0.into()], true).await.unwrap().into_vec()
+    }, then_assert: |rows| assert_eq!(rows.len(), (NUM_ROWS + NUM_WRITES) as usize));
+
+    // Replay batches are only ever deferred if prioritization is enabled
+    let stats = g.statistics().await.unwrap();
+    assert_eq!(
+        stats
+            .values()
+            .flatten()
+            .any(|(domain, _)| domain.deferred_replay_batches > 0),
+        migration_replay_min_share < 100
+    );
+    assert!(stats.values().flatten().all(|(domain, _)| {
+        domain.processed_deferred_replay_batches == domain.deferred_replay_batches
+    }));
+
+    shutdown_tx.shutdown().await;
+    latency
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn writes_prioritized_over_migration_replay() {
+    let prioritized =
+        write_latency_during_migration_replay("writes_prioritized_over_migration_replay", 10).await;
+    let unprioritized = write_latency_during_migration_replay(
+        "writes_prioritized_over_migration_replay_disabled",
+        100,
+    )
+    .await;
+    assert!(
+        prioritized < unprioritized,
+        "writes took {prioritized:?} to become visible during replay with prioritization, and \
+         {unprioritized:?} without"
+    );
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn replay_during_replay() {
     // what we're trying to set up here is a case where a join receives a record with a value for
@@ -3822,21 +4295,766 @@ async fn remove_query() {
     shutdown_tx.shutdown().await;
 }
 
-macro_rules! get {
-    ($private:ident, $public:ident, $uid:expr, $aid:expr) => {{
-        // combine private and public results
-        // also, there's currently a bug where MIR doesn't guarantee the order of parameters, so we
-        // try both O:)
-        let mut v = $private
-            .lookup(&[$uid.into(), $aid.into()], true)
-            .await
-            .unwrap()
-            .into_vec();
-        v.append(
-            &mut $private
-                .lookup(&[$aid.into(), $uid.into()], true)
-                .await
-                .unwrap()
+#[tokio::test(flavor = "multi_thread")]
+async fn read_errors_are_classified() {
+    readyset_tracing::init_test_logging();
+    let (mut g, shutdown_tx) = start_simple_unsharded("read_errors_are_classified").await;
+    g.extend_recipe(
+        ChangeList::from_str(
+            "CREATE TABLE t (a int, b int);
+             CREATE CACHE q FROM SELECT a, b FROM t WHERE a = ?;",
+            Dialect::DEFAULT_MYSQL,
+        )
+        .unwrap(),
+    )
+    .await
+    .unwrap();
+
+    let mut t = g.table("t").await.unwrap();
+    let mut q = g.view("q").await.unwrap().into_reader_handle().unwrap();
+    t.insert(vec![1.into(), 2.into()]).await.unwrap();
+    g.wait_for_stabilization(Duration::from_secs(5))
+        .await
+        .unwrap();
+
+    assert_eq!(
+        q.lookup_classified(&[1.into()], false).await.unwrap_err(),
+        ReadError::MissKeyNotCached {
+            retry_will_succeed: true
+        }
+    );
+    assert_eq!(
+        q.lookup_classified(&[1.into()], true).await.unwrap(),
+        vec![vec![DfValue::from(1), DfValue::from(2)]]
+    );
+    assert_eq!(
+        q.lookup_classified(&[2.into()], true).await.unwrap_err(),
+        ReadError::KeyNotFound
+    );
+
+    // Drop the view out from under the reader handle
+    g.extend_recipe(ChangeList::from_str("DROP CACHE q;", Dialect::DEFAULT_MYSQL).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(
+        q.lookup_classified(&[1.into()], true).await.unwrap_err(),
+        ReadError::ViewDropped
+    );
+
+    shutdown_tx.shutdown().await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn read_from_unreachable_shard() {
+    readyset_tracing::init_test_logging();
+    let (mut g, shutdown_tx) = start_simple_unsharded("read_from_unreachable_shard").await;
+    g.extend_recipe(
+        ChangeList::from_str(
+            "CREATE TABLE t (a int, b int);
+             CREATE CACHE q FROM SELECT a, b FROM t WHERE a = ?;",
+            Dialect::DEFAULT_MYSQL,
+        )
+        .unwrap(),
+    )
+    .await
+    .unwrap();
+    let mut q = g.view("q").await.unwrap().into_reader_handle().unwrap();
+    q.lookup_classified(&[1.into()], true).await.unwrap_err();
+
+    // Shutting down the server kills the connection to the reader
+    shutdown_tx.shutdown().await;
+
+    assert_eq!(
+        tokio::time::timeout(
+            Duration::from_secs(10),
+            q.lookup_classified(&[1.into()], true)
+        )
+        .await
+        .unwrap()
+        .unwrap_err(),
+        ReadError::ShardUnreachable { shard: 0 }
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn node_ancestors_and_descendants() {
+    readyset_tracing::init_test_logging();
+    let (mut g, shutdown_tx) = start_simple_unsharded("node_ancestors_and_descendants").await;
+    let (x, y, join, reader) = g
+        .migrate(|mig| {
+            let x = mig.add_base("x", make_columns(&["a", "b"]), Base::default());
+            let y = mig.add_base("y", make_columns(&["b", "c"]), Base::default());
+            let join = mig.add_ingredient(
+                "join",
+                make_columns(&["a", "b", "c"]),
+                Join::new(
+                    x,
+                    y,
+                    JoinType::Inner,
+                    vec![(1, 0)],
+                    vec![(Side::Left, 0), (Side::Left, 1), (Side::Right, 1)],
+                ),
+            );
+            let reader = mig.maintain_anonymous(join, &Index::hash_map(vec![0]));
+            (x, y, join, reader)
+        })
+        .await;
+
+    let ancestors = g.node_ancestors(reader).await.unwrap();
+    assert!(ancestors.contains(&join));
+    assert!(ancestors.contains(&x));
+    assert!(ancestors.contains(&y));
+    assert!(!ancestors.contains(&reader));
+
+    let descendants = g.node_descendants(x).await.unwrap();
+    assert!(descendants.contains(&join));
+    assert!(descendants.contains(&reader));
+    assert!(!descendants.contains(&y));
+
+    assert!(g.node_ancestors(x).await.unwrap().is_empty());
+    assert!(g.node_descendants(reader).await.unwrap().is_empty());
+
+    shutdown_tx.shutdown().await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn recent_slow_operations() {
+    readyset_tracing::init_test_logging();
+    let mut builder = Builder::for_tests();
+    builder.set_sharding(None);
+    builder.set_persistence(get_persistence_params("recent_slow_operations"));
+    // Record every operation, so that the test doesn't depend on anything actually being slow
+    builder.set_slow_node_threshold(Duration::ZERO);
+    let (mut g, shutdown_tx) = builder.start_local().await.unwrap();
+    g.extend_recipe(
+        ChangeList::from_str("CREATE TABLE t (a int, b int);", Dialect::DEFAULT_MYSQL).unwrap(),
+    )
+    .await
+    .unwrap();
+    let t_node = g.tables().await.unwrap()[&Relation::from("t")];
+
+    let mut t = g.table("t").await.unwrap();
+    t.insert_many(vec![
+        vec![DfValue::from(1), DfValue::from(2)],
+        vec![DfValue::from(3), DfValue::from(4)],
+    ])
+    .await
+    .unwrap();
+    sleep().await;
+
+    let slow_operations = g.recent_slow_operations().await.unwrap();
+    let insert = slow_operations
+        .iter()
+        .find(|op| op.node == t_node && op.packet_kind == "Input")
+        .unwrap_or_else(|| panic!("No slow operation recorded for insert: {slow_operations:?}"));
+    assert_eq!(insert.node_name, Some(Relation::from("t")));
+    assert_eq!(insert.batch_size, 2);
+    assert!(insert.duration < Duration::from_secs(10), "{insert:?}");
+
+    // Make every operation slow
+    #[cfg(feature = "failure_injection")]
+    {
+        fail::cfg(
+            readyset_client::failpoints::DOMAIN_PROCESS_PACKET,
+            "sleep(200)",
+        )
+        .unwrap();
+        t.insert(vec![DfValue::from(5), DfValue::from(6)])
+            .await
+            .unwrap();
+        sleep().await;
+        fail::remove(readyset_client::failpoints::DOMAIN_PROCESS_PACKET);
+
+        let slow_operations = g.recent_slow_operations().await.unwrap();
+        let insert = slow_operations
+            .iter()
+            .rev()
+            .find(|op| op.node == t_node && op.packet_kind == "Input")
+            .unwrap();
+        assert_eq!(insert.batch_size, 1);
+        assert!(insert.duration >= Duration::from_millis(200), "{insert:?}");
+    }
+
+    shutdown_tx.shutdown().await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn write_propagation_latencies() {
+    let (mut g, shutdown_tx) = start_simple_unsharded("write_propagation_latencies").await;
+    assert!(g.set_write_tracing(1.5).await.is_err());
+    g.set_write_tracing(1.0).await.unwrap();
+
+    let vote = g
+        .migrate(|mig| {
+            let vote = mig.add_base("vote", make_columns(&["user", "id"]), Base::default());
+            mig.maintain_anonymous(vote, &Index::hash_map(vec![0]));
+            vote
+        })
+        .await;
+
+    let mut t = g.table_by_index(vote).await.unwrap();
+    for i in 0..10 {
+        t.insert(vec![i.into(), 1.into()]).await.unwrap();
+    }
+    sleep().await;
+
+    let stats = g.statistics().await.unwrap();
+    let latencies = stats.propagation_latencies().collect::<Vec<_>>();
+    assert_eq!(latencies.len(), 1, "{latencies:?}");
+    let (_, _, _, latency) = latencies[0];
+    assert_eq!(latency.samples, 10);
+    assert!(latency.p50 <= latency.p90, "{latency:?}");
+    assert!(latency.p90 <= latency.p99, "{latency:?}");
+    assert!(latency.p99 <= latency.max, "{latency:?}");
+    assert!(latency.max < Duration::from_secs(10), "{latency:?}");
+
+    shutdown_tx.shutdown().await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn write_amplification() {
+    let mut builder = Builder::for_tests();
+    builder.set_sharding(None);
+    builder.set_persistence(get_persistence_params("write_amplification"));
+    builder.set_count_emitted_records(true);
+    let (mut g, shutdown_tx) = builder.start_local().await.unwrap();
+    let (vote, vc) = g
+        .migrate(|mig| {
+            let vote = mig.add_base("vote", make_columns(&["user", "id"]), Base::default());
+            let vc = mig.add_ingredient(
+                "votecount",
+                make_columns(&["id", "votes"]),
+                Aggregation::Count
+                    .over(vote, 0, &[1], &DfType::Unknown)
+                    .unwrap(),
+            );
+            mig.maintain_anonymous(vc, &Index::hash_map(vec![0]));
+            (vote, vc)
+        })
+        .await;
+
+    let mut t = g.table_by_index(vote).await.unwrap();
+    let mut vc_state = g
+        .view("votecount")
+        .await
+        .unwrap()
+        .into_reader_handle()
+        .unwrap();
+
+    // The first vote creates the count, and makes sure the key is present in the reader if it's
+    // partial, so that every subsequent vote just replaces the count
+    t.insert(vec![0.into(), 1.into()]).await.unwrap();
+    sleep().await;
+    assert_eq!(
+        vc_state.lookup(&[1.into()], true).await.unwrap().into_vec(),
+        vec![vec![1.into(), 1.into()]]
+    );
+    let before = g
+        .write_amplification()
+        .await
+        .unwrap()
+        .remove(&vote)
+        .unwrap();
+
+    let votes = 10u64;
+    for user in 1..=votes {
+        t.insert(vec![user.into(), 1.into()]).await.unwrap();
+    }
+    sleep().await;
+    let after = g
+        .write_amplification()
+        .await
+        .unwrap()
+        .remove(&vote)
+        .unwrap();
+
+    // Each vote emits a negative record for the old count and a positive record for the new count
+    // from both the aggregate and the reader
+    assert_eq!(after.base_records - before.base_records, votes);
+    assert_eq!(
+        after.derived_records[&vc] - before.derived_records[&vc],
+        2 * votes
+    );
+    assert_eq!(after.derived_records.len(), 2, "{after:?}");
+    assert_eq!(
+        after.total_derived_records() - before.total_derived_records(),
+        4 * votes
+    );
+
+    shutdown_tx.shutdown().await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn migration_progress_events() {
+    let (mut g, shutdown_tx) = start_simple_unsharded("migration_progress_events").await;
+    let vote = g
+        .migrate(|mig| mig.add_base("vote", make_columns(&["user", "id"]), Base::default()))
+        .await;
+    g.table_by_index(vote)
+        .await
+        .unwrap()
+        .insert(vec![0.into(), 1.into()])
+        .await
+        .unwrap();
+
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let (vc, reader) = g
+        .migrate(move |mig| {
+            mig.on_progress(move |event| tx.send(event).unwrap());
+            let vc = mig.add_ingredient(
+                "votecount",
+                make_columns(&["id", "votes"]),
+                Aggregation::Count
+                    .over(vote, 0, &[1], &DfType::Unknown)
+                    .unwrap(),
+            );
+            let reader = mig.maintain_anonymous(vc, &Index::hash_map(vec![0]));
+            (vc, reader)
+        })
+        .await;
+
+    // The sender is dropped along with the callback once the migration has finished, which ends
+    // the stream of events
+    let mut events = vec![];
+    while let Some(event) = rx.recv().await {
+        events.push(event);
+    }
+
+    let phases = events
+        .iter()
+        .map(|event| match event.phase {
+            MigrationPhase::Materialization { .. } => "Materialization",
+            MigrationPhase::Sharding => "Sharding",
+            MigrationPhase::DomainAssignment => "DomainAssignment",
+            MigrationPhase::Routing => "Routing",
+            MigrationPhase::BootingDomains { .. } => "BootingDomains",
+            MigrationPhase::Augmentation => "Augmentation",
+            MigrationPhase::ColumnChanges => "ColumnChanges",
+            MigrationPhase::InterDomainConnections => "InterDomainConnections",
+            MigrationPhase::Finalize => "Finalize",
+        })
+        .dedup()
+        .collect::<Vec<_>>();
+    assert_eq!(
+        phases,
+        vec![
+            "Sharding",
+            "DomainAssignment",
+            "Routing",
+            "BootingDomains",
+            "Augmentation",
+            "InterDomainConnections",
+            "Materialization",
+            "ColumnChanges",
+            "Finalize",
+        ]
+    );
+
+    let materialized = events
+        .iter()
+        .filter_map(|event| match &event.phase {
+            MigrationPhase::Materialization { node, .. } => Some(*node),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert!(materialized.contains(&vc));
+    assert!(materialized.contains(&reader));
+    assert!(!materialized.contains(&vote));
+
+    shutdown_tx.shutdown().await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn batch_migration() {
+    let (mut g, shutdown_tx) = start_simple_unsharded("batch_migration").await;
+    let bases = g
+        .migrate_batch(
+            (0..5)
+                .map(|i| {
+                    move |mig: &mut Migration<'_>| {
+                        let base = mig.add_base(
+                            format!("base_{i}"),
+                            make_columns(&["a", "b"]),
+                            Base::default(),
+                        );
+                        mig.maintain_anonymous(base, &Index::hash_map(vec![0]));
+                        let by_a = mig.add_ingredient(
+                            format!("count_by_a_{i}"),
+                            make_columns(&["a", "count"]),
+                            Aggregation::Count
+                                .over(base, 1, &[0], &DfType::Unknown)
+                                .unwrap(),
+                        );
+                        mig.maintain_anonymous(by_a, &Index::hash_map(vec![0]));
+                        let by_b = mig.add_ingredient(
+                            format!("count_by_b_{i}"),
+                            make_columns(&["b", "count"]),
+                            Aggregation::Count
+                                .over(base, 0, &[1], &DfType::Unknown)
+                                .unwrap(),
+                        );
+                        mig.maintain_anonymous(by_b, &Index::hash_map(vec![0]));
+                        base
+                    }
+                })
+                .collect(),
+        )
+        .await;
+    assert_eq!(bases.len(), 5);
+
+    for (i, base) in bases.into_iter().enumerate() {
+        g.table_by_index(base)
+            .await
+            .unwrap()
+            .insert(vec![DfValue::from(i), DfValue::from(i * 10)])
+            .await
+            .unwrap();
+    }
+    sleep().await;
+
+    for i in 0..5usize {
+        let mut by_a = g
+            .view(format!("count_by_a_{i}"))
+            .await
+            .unwrap()
+            .into_reader_handle()
+            .unwrap();
+        assert_eq!(
+            by_a.lookup(&[DfValue::from(i)], true)
+                .await
+                .unwrap()
+                .into_vec(),
+            vec![vec![DfValue::from(i), DfValue::from(1)]]
+        );
+        let mut by_b = g
+            .view(format!("count_by_b_{i}"))
+            .await
+            .unwrap()
+            .into_reader_handle()
+            .unwrap();
+        assert_eq!(
+            by_b.lookup(&[DfValue::from(i * 10)], true)
+                .await
+                .unwrap()
+                .into_vec(),
+            vec![vec![DfValue::from(i * 10), DfValue::from(1)]]
+        );
+    }
+
+    shutdown_tx.shutdown().await;
+}
+
+/// Start a graph with a `vote` base table holding 10,000 votes, returning the index of the table
+async fn start_vote_graph(name: &str) -> (Handle, ShutdownSender, NodeIndex) {
+    let (mut g, shutdown_tx) = start_simple_unsharded(name).await;
+    let vote = g
+        .migrate(|mig| {
+            let vote = mig.add_base("vote", make_columns(&["user", "id"]), Base::default());
+            mig.maintain_anonymous(vote, &Index::hash_map(vec![0]));
+            vote
+        })
+        .await;
+    g.table_by_index(vote)
+        .await
+        .unwrap()
+        .perform_all((0..10_000).map(|i| vec![DfValue::from(i), DfValue::from(i % 10)]))
+        .await
+        .unwrap();
+    g.wait_for_stabilization(Duration::from_secs(10))
+        .await
+        .unwrap();
+    (g, shutdown_tx, vote)
+}
+
+/// Commit a migration adding a `votecount` view over the `vote` table in the background
+async fn migrate_votecount(g: &mut Handle, vote: NodeIndex) -> MigrationHandle<NodeIndex> {
+    g.migrate_async(move |mig| {
+        let vc = mig.add_ingredient(
+            "votecount",
+            make_columns(&["id", "votes"]),
+            Aggregation::Count
+                .over(vote, 0, &[1], &DfType::Unknown)
+                .unwrap(),
+        );
+        mig.maintain_anonymous(vc, &Index::hash_map(vec![0]));
+        vc
+    })
+    .await
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn background_migration() {
+    let (mut g, shutdown_tx, vote) = start_vote_graph("background_migration").await;
+
+    // Hold the migration part-way through being committed, so we can observe it
+    #[cfg(feature = "failure_injection")]
+    fail::cfg(
+        readyset_client::failpoints::MIGRATION_APPLY_DOMAIN_REQUEST,
+        "pause",
+    )
+    .unwrap();
+
+    let mut handle = migrate_votecount(&mut g, vote).await;
+
+    #[cfg(feature = "failure_injection")]
+    {
+        assert!(matches!(
+            handle.status(),
+            BackgroundMigrationStatus::Committing { .. }
+        ));
+
+        // The new view isn't ready until the migration has been committed...
+        let err = g.view("votecount").await.unwrap_err();
+        assert!(
+            err.any_cause(|e| matches!(e, ReadySetError::ViewNotReady(_))),
+            "{err}"
+        );
+
+        // ...but existing views can still be read from in the meantime
+        let mut votes = g.view("vote").await.unwrap().into_reader_handle().unwrap();
+        assert_eq!(
+            votes.lookup(&[1.into()], true).await.unwrap().into_vec(),
+            vec![vec![1.into(), 1.into()]]
+        );
+
+        fail::remove(readyset_client::failpoints::MIGRATION_APPLY_DOMAIN_REQUEST);
+    }
+
+    assert_eq!(
+        handle.wait(Duration::from_secs(30)).await,
+        BackgroundMigrationStatus::Done
+    );
+    let mut votecount = g
+        .view("votecount")
+        .await
+        .unwrap()
+        .into_reader_handle()
+        .unwrap();
+    assert_eq!(
+        votecount
+            .lookup(&[3.into()], true)
+            .await
+            .unwrap()
+            .into_vec(),
+        vec![vec![3.into(), 1000.into()]]
+    );
+
+    shutdown_tx.shutdown().await;
+}
+
+#[cfg(feature = "failure_injection")]
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn background_migration_abort() {
+    let (mut g, shutdown_tx, vote) = start_vote_graph("background_migration_abort").await;
+    let domains_before = g.statistics().await.unwrap().domains.len();
+
+    // Hold the migration after it's booted its new domains, so that the abort is seen part-way
+    // through the commit
+    fail::cfg(
+        readyset_client::failpoints::MIGRATION_APPLY_DOMAIN_REQUEST,
+        "pause",
+    )
+    .unwrap();
+    let mut handle = migrate_votecount(&mut g, vote).await;
+    handle.abort();
+    fail::remove(readyset_client::failpoints::MIGRATION_APPLY_DOMAIN_REQUEST);
+
+    assert_eq!(
+        handle.wait(Duration::from_secs(30)).await,
+        BackgroundMigrationStatus::Aborted
+    );
+    // The view was never added, and the domains booted for it were killed
+    let err = g.view("votecount").await.unwrap_err();
+    assert!(
+        !err.any_cause(|e| matches!(e, ReadySetError::ViewNotReady(_))),
+        "{err}"
+    );
+    assert_eq!(g.statistics().await.unwrap().domains.len(), domains_before);
+
+    // Existing views are unaffected
+    let mut votes = g.view("vote").await.unwrap().into_reader_handle().unwrap();
+    assert_eq!(
+        votes.lookup(&[1.into()], true).await.unwrap().into_vec(),
+        vec![vec![1.into(), 1.into()]]
+    );
+
+    shutdown_tx.shutdown().await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn group_migration_is_all_or_nothing() {
+    let (mut g, shutdown_tx) = start_simple_unsharded("group_migration_is_all_or_nothing").await;
+    let vote = g
+        .migrate(|mig| {
+            let vote = mig.add_base("vote", make_columns(&["user", "id"]), Base::default());
+            mig.maintain_anonymous(vote, &Index::hash_map(vec![0]));
+            vote
+        })
+        .await;
+    g.table_by_index(vote)
+        .await
+        .unwrap()
+        .perform_all((0..100).map(|i| vec![DfValue::from(i), DfValue::from(i % 10)]))
+        .await
+        .unwrap();
+    g.wait_for_stabilization(Duration::from_secs(10))
+        .await
+        .unwrap();
+
+    // A count of votes, and a view of the votes keyed by the column named `id_column`
+    let vote_views = move |id_column: &'static str| {
+        move |group: &mut GroupMigration<'_, '_>| -> ReadySetResult<()> {
+            group.query("votecount", |mig| {
+                let vc = mig.add_ingredient(
+                    "votecount",
+                    make_columns(&["id", "votes"]),
+                    Aggregation::Count.over(vote, 0, &[1], &DfType::Unknown)?,
+                );
+                mig.maintain_anonymous(vc, &Index::hash_map(vec![0]));
+                Ok(vc)
+            })?;
+            group.query("votes_by_id", |mig| {
+                let id = mig.column_index(vote, id_column)?;
+                let votes = mig.add_ingredient(
+                    "votes_by_id",
+                    make_columns(&["user", "id"]),
+                    Identity::new(vote),
+                );
+                mig.maintain_anonymous(votes, &Index::hash_map(vec![id]));
+                Ok(votes)
+            })?;
+            Ok(())
+        }
+    };
+
+    let err = g.migrate_group(vote_views("idd")).await.unwrap_err();
+    assert!(
+        err.any_cause(|e| matches!(e, ReadySetError::NoSuchColumn(c) if c == "idd")),
+        "{err}"
+    );
+    // The first query of the group was added successfully, but must not have been installed
+    // without the second
+    let err = g.view("votecount").await.unwrap_err();
+    assert!(
+        !err.any_cause(|e| matches!(e, ReadySetError::ViewNotReady(_))),
+        "{err}"
+    );
+    assert!(g.view("votes_by_id").await.is_err());
+
+    // Ignoring the error from a failed query still fails the whole group
+    let err = g
+        .migrate_group(move |group| {
+            vote_views("id")(group)?;
+            let _ = group.query("bad_votes", |mig| {
+                mig.column_index(vote, "idd").map(|_| vote)
+            });
+            Ok(())
+        })
+        .await
+        .unwrap_err();
+    assert!(
+        err.any_cause(|e| matches!(e, ReadySetError::NoSuchColumn(c) if c == "idd")),
+        "{err}"
+    );
+    assert!(g.view("votecount").await.is_err());
+
+    let outcome = g.migrate_group(vote_views("id")).await.unwrap();
+    assert_eq!(outcome.queries.len(), 2);
+    let votecount_query = outcome.get("votecount").unwrap();
+    assert!(votecount_query.reader.is_some());
+    assert_eq!(votecount_query.view_name, Some("votecount".into()));
+
+    let mut votecount = g
+        .view("votecount")
+        .await
+        .unwrap()
+        .into_reader_handle()
+        .unwrap();
+    assert_eq!(
+        votecount
+            .lookup(&[3.into()], true)
+            .await
+            .unwrap()
+            .into_vec(),
+        vec![vec![3.into(), 10.into()]]
+    );
+    let mut votes_by_id = g
+        .view("votes_by_id")
+        .await
+        .unwrap()
+        .into_reader_handle()
+        .unwrap();
+    assert_eq!(
+        votes_by_id
+            .lookup(&[3.into()], true)
+            .await
+            .unwrap()
+            .into_vec()
+            .len(),
+        10
+    );
+
+    shutdown_tx.shutdown().await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn health_report() {
+    readyset_tracing::init_test_logging();
+    let (mut g, shutdown_tx) = start_simple_unsharded("health_report").await;
+    g.extend_recipe(
+        ChangeList::from_str(
+            "CREATE TABLE t (a int, b int);
+             CREATE CACHE q FROM SELECT a, b FROM t WHERE a = ?;",
+            Dialect::DEFAULT_MYSQL,
+        )
+        .unwrap(),
+    )
+    .await
+    .unwrap();
+
+    let report = g.health(Duration::from_secs(10)).await;
+    assert_eq!(report.status, HealthStatus::Ready, "{report:?}");
+    assert!(!report.domains.is_empty());
+    assert_eq!(report.workers.len(), 1);
+    assert_eq!(report.read_path, Some(ComponentHealth::Healthy));
+
+    // Make every domain fail to respond to pings
+    #[cfg(feature = "failure_injection")]
+    {
+        fail::cfg(readyset_client::failpoints::DOMAIN_PING, "return").unwrap();
+        let report = g.health(Duration::from_secs(10)).await;
+        fail::remove(readyset_client::failpoints::DOMAIN_PING);
+
+        assert_eq!(report.status, HealthStatus::Degraded, "{report:?}");
+        assert_eq!(
+            report.unhealthy_domains().collect::<Vec<_>>(),
+            report
+                .domains
+                .iter()
+                .map(|d| &d.replica_address)
+                .collect::<Vec<_>>()
+        );
+        assert!(report.workers.iter().all(|w| w.health.is_healthy()));
+    }
+
+    shutdown_tx.shutdown().await;
+}
+
+macro_rules! get {
+    ($private:ident, $public:ident, $uid:expr, $aid:expr) => {{
+        // combine private and public results
+        // also, there's currently a bug where MIR doesn't guarantee the order of parameters, so we
+        // try both O:)
+        let mut v = $private
+            .lookup(&[$uid.into(), $aid.into()], true)
+            .await
+            .unwrap()
+            .into_vec();
+        v.append(
+            &mut $private
+                .lookup(&[$aid.into(), $uid.into()], true)
+                .await
+                .unwrap()
                 .into_vec(),
         );
         v.append(
@@ -4331,22 +5549,99 @@ async fn correct_nested_view_schema() {
         .await
         .unwrap();
 
-    let q = g.view("swvc").await.unwrap().into_reader_handle().unwrap();
+    let q = g.view("swvc").await.unwrap().into_reader_handle().unwrap();
+
+    let expected_schema = vec![
+        ("swvc.id".into(), DfType::Int),
+        ("swvc.content".into(), DfType::DEFAULT_TEXT),
+        ("swvc.vc".into(), DfType::BigInt),
+    ];
+    assert_eq!(
+        q.schema()
+            .unwrap()
+            .schema(SchemaType::ProjectedSchema)
+            .iter()
+            .map(|cs| (cs.column.clone(), cs.column_type.clone()))
+            .collect::<Vec<_>>(),
+        expected_schema
+    );
+
+    shutdown_tx.shutdown().await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn view_schema_nullability() {
+    let (mut g, shutdown_tx) = start_simple_unsharded("view_schema_nullability").await;
+    g.extend_recipe(
+        ChangeList::from_str(
+            "CREATE TABLE stories (id int PRIMARY KEY, title text NOT NULL, url text);
+             CREATE TABLE votes (story_id int NOT NULL, user_id int NOT NULL);
+             CREATE CACHE story_votes FROM
+             SELECT stories.id, stories.title, stories.url, votes.user_id, 1 AS one
+             FROM stories LEFT JOIN votes ON (stories.id = votes.story_id);
+             CREATE CACHE vote_counts FROM
+             SELECT votes.story_id, COUNT(votes.user_id) AS vc, SUM(votes.user_id) AS total
+             FROM votes GROUP BY votes.story_id;",
+            Dialect::DEFAULT_MYSQL,
+        )
+        .unwrap(),
+    )
+    .await
+    .unwrap();
+
+    let schema = |view: ReaderHandle| {
+        view.schema()
+            .unwrap()
+            .schema(SchemaType::ReturnedSchema)
+            .iter()
+            .map(|cs| {
+                (
+                    cs.column.name.to_string(),
+                    cs.column_type.clone(),
+                    cs.nullable,
+                )
+            })
+            .collect::<Vec<_>>()
+    };
 
-    let expected_schema = vec![
-        ("swvc.id".into(), DfType::Int),
-        ("swvc.content".into(), DfType::DEFAULT_TEXT),
-        ("swvc.vc".into(), DfType::BigInt),
-    ];
+    let story_votes = schema(
+        g.view("story_votes")
+            .await
+            .unwrap()
+            .into_reader_handle()
+            .unwrap(),
+    );
     assert_eq!(
-        q.schema()
+        story_votes[..4],
+        [
+            ("id".to_owned(), DfType::Int, false),
+            ("title".to_owned(), DfType::DEFAULT_TEXT, false),
+            ("url".to_owned(), DfType::DEFAULT_TEXT, true),
+            // Columns from the right-hand side of a left join are nullable, even if the base
+            // table column isn't
+            ("user_id".to_owned(), DfType::Int, true),
+        ]
+    );
+    let (name, ty, nullable) = &story_votes[4];
+    assert_eq!(name, "one");
+    assert!(!ty.is_unknown());
+    assert!(!nullable);
+
+    let vote_counts = schema(
+        g.view("vote_counts")
+            .await
             .unwrap()
-            .schema(SchemaType::ProjectedSchema)
+            .into_reader_handle()
+            .unwrap(),
+    );
+    assert_eq!(
+        vote_counts
             .iter()
-            .map(|cs| (cs.column.clone(), cs.column_type.clone()))
+            .map(|(name, _, nullable)| (name.as_str(), *nullable))
             .collect::<Vec<_>>(),
-        expected_schema
+        vec![("story_id", false), ("vc", false), ("total", true)]
     );
+    assert_eq!(vote_counts[1].1, DfType::BigInt);
 
     shutdown_tx.shutdown().await;
 }
@@ -4733,109 +6028,362 @@ async fn self_join_basic() {
     let results: Vec<(i32, i32)> = query
         .lookup(&[0i32.into()], true)
         .await
-        .unwrap()
-        .into_vec()
-        .iter()
-        .map(|r| {
-            (
-                get_col!(query, r, "user", i32),
-                get_col!(query, r, "agreer", i32),
-            )
-        })
-        .sorted()
-        .collect();
-    assert_eq!(results, expected);
+        .unwrap()
+        .into_vec()
+        .iter()
+        .map(|r| {
+            (
+                get_col!(query, r, "user", i32),
+                get_col!(query, r, "agreer", i32),
+            )
+        })
+        .sorted()
+        .collect();
+    assert_eq!(results, expected);
+
+    shutdown_tx.shutdown().await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn self_join_param() {
+    let (mut g, shutdown_tx) = start_simple_unsharded("self_join_param").await;
+    g.extend_recipe(
+        ChangeList::from_str(
+            "CREATE TABLE users (id int, friend int);
+         CREATE CACHE fof FROM SELECT u1.id AS user, u2.friend AS fof \
+             FROM users u1 \
+             JOIN users u2 ON (u1.friend = u2.id) WHERE u1.id = ?;
+         CREATE VIEW fof2 AS SELECT u1.id AS user, u2.friend AS fof \
+             FROM users u1 \
+             JOIN users u2 ON (u1.friend = u2.id);
+         CREATE CACHE follow_on FROM SELECT * FROM fof2 WHERE user = ?;",
+            Dialect::DEFAULT_MYSQL,
+        )
+        .unwrap(),
+    )
+    .await
+    .unwrap();
+
+    let mut votes = g.table("users").await.unwrap();
+    votes.insert(vec![1i32.into(), 2i32.into()]).await.unwrap();
+    votes.insert(vec![1i32.into(), 3i32.into()]).await.unwrap();
+    votes.insert(vec![2i32.into(), 5i32.into()]).await.unwrap();
+    votes.insert(vec![3i32.into(), 1i32.into()]).await.unwrap();
+    votes.insert(vec![5i32.into(), 6i32.into()]).await.unwrap();
+    votes.insert(vec![6i32.into(), 2i32.into()]).await.unwrap();
+
+    // Check fof
+
+    let mut query = g.view("fof").await.unwrap().into_reader_handle().unwrap();
+    assert_eq!(query.columns(), vec!["user", "fof"]);
+    let results: Vec<(i32, i32)> = query
+        .lookup(&[1i32.into()], true)
+        .await
+        .unwrap()
+        .into_vec()
+        .iter()
+        .map(|r| {
+            (
+                get_col!(query, r, "user", i32),
+                get_col!(query, r, "fof", i32),
+            )
+        })
+        .sorted()
+        .collect();
+    let expected = vec![(1, 1), (1, 5)];
+    assert_eq!(results, expected);
+
+    // Check follow_on
+
+    let mut query = g
+        .view("follow_on")
+        .await
+        .unwrap()
+        .into_reader_handle()
+        .unwrap();
+
+    // Disabled because a "bogokey" column is present as well.
+    // assert_eq!(query.columns(), vec!["user", "fof"]);
+
+    let results: Vec<(i32, i32)> = query
+        .lookup(&[1i32.into()], true)
+        .await
+        .unwrap()
+        .into_vec()
+        .iter()
+        .map(|r| {
+            (
+                get_col!(query, r, "user", i32),
+                get_col!(query, r, "fof", i32),
+            )
+        })
+        .sorted()
+        .collect();
+    assert_eq!(results, expected);
+
+    shutdown_tx.shutdown().await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn non_sql_materialized_range_query() {
+    let (mut g, shutdown_tx) = {
+        let mut builder = Builder::for_tests();
+        builder.disable_partial();
+        builder.set_sharding(None);
+        builder.set_persistence(get_persistence_params("non_sql_materialized_range_query"));
+        builder.start_local()
+    }
+    .await
+    .unwrap();
+
+    let a = g
+        .migrate(|mig| {
+            let a = mig.add_base(
+                "a",
+                make_columns(&["a", "b"]),
+                Base::new().with_primary_key([0]),
+            );
+            mig.maintain_anonymous(a, &Index::btree_map(vec![0]));
+            a
+        })
+        .await;
+
+    let mut a = g.table_by_index(a).await.unwrap();
+    let mut reader = g.view("a").await.unwrap().into_reader_handle().unwrap();
+    a.insert_many((0i32..10).map(|n| vec![DfValue::from(n), DfValue::from(n)]))
+        .await
+        .unwrap();
+
+    sleep().await;
+
+    let res = reader
+        .multi_lookup(
+            vec![(vec1![DfValue::from(2)]..vec1![DfValue::from(5)]).into()],
+            false,
+        )
+        .await
+        .unwrap()
+        .into_vec();
+
+    assert_eq!(
+        res,
+        (2..5).map(|n| vec![n.into(), n.into()]).collect::<Vec<_>>()
+    );
+
+    shutdown_tx.shutdown().await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn non_sql_range_upquery() {
+    let (mut g, shutdown_tx) = {
+        let mut builder = Builder::for_tests();
+        builder.set_sharding(None);
+        builder.set_persistence(get_persistence_params("non_sql_range_upquery"));
+        builder.start_local()
+    }
+    .await
+    .unwrap();
+
+    let a = g
+        .migrate(|mig| {
+            let a = mig.add_base(
+                "a",
+                make_columns(&["a", "b"]),
+                Base::new().with_primary_key([0]),
+            );
+            mig.maintain_anonymous(a, &Index::btree_map(vec![0]));
+            a
+        })
+        .await;
+
+    let mut a = g.table_by_index(a).await.unwrap();
+    let mut reader = g.view("a").await.unwrap().into_reader_handle().unwrap();
+    a.insert_many((0i32..10).map(|n| vec![DfValue::from(n), DfValue::from(n)]))
+        .await
+        .unwrap();
+
+    sleep().await;
+
+    let res = reader
+        .multi_lookup(
+            vec![(vec1![DfValue::from(2)]..vec1![DfValue::from(5)]).into()],
+            true,
+        )
+        .await
+        .unwrap()
+        .into_vec();
+
+    assert_eq!(
+        res,
+        (2..5).map(|n| vec![n.into(), n.into()]).collect::<Vec<_>>()
+    );
+
+    shutdown_tx.shutdown().await;
+}
+
+async fn range_scan_impl(sharding: Option<usize>, persistence_name: &str) {
+    let (mut g, shutdown_tx) = {
+        let mut builder = Builder::for_tests();
+        builder.disable_partial();
+        builder.set_sharding(sharding);
+        builder.set_persistence(get_persistence_params(persistence_name));
+        builder.start_local()
+    }
+    .await
+    .unwrap();
+
+    let a = g
+        .migrate(|mig| {
+            let a = mig.add_base(
+                "a",
+                make_columns(&["a", "b"]),
+                Base::new().with_primary_key([0]),
+            );
+            mig.maintain_anonymous(a, &Index::btree_map(vec![0]));
+            a
+        })
+        .await;
+
+    let mut a = g.table_by_index(a).await.unwrap();
+    let mut reader = g.view("a").await.unwrap().into_reader_handle().unwrap();
+    a.insert_many((0i32..100).map(|n| vec![DfValue::from(n), DfValue::from(n * 2)]))
+        .await
+        .unwrap();
+
+    sleep().await;
+
+    let res = reader
+        .range_lookup(
+            Bound::Included(DfValue::from(10)),
+            Bound::Excluded(DfValue::from(20)),
+            None,
+            true,
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        res.rows,
+        (10..20)
+            .map(|n| vec![n.into(), (n * 2).into()])
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(res.continuation, None);
+
+    // Page through the same range, a few rows at a time
+    let mut rows = vec![];
+    let mut lower = Bound::Included(DfValue::from(10));
+    loop {
+        let page = reader
+            .range_lookup(lower, Bound::Excluded(DfValue::from(20)), Some(3), true)
+            .await
+            .unwrap();
+        assert!(page.rows.len() <= 3);
+        rows.extend(page.rows);
+        match page.continuation {
+            Some(key) => lower = Bound::Excluded(key),
+            None => break,
+        }
+    }
+    assert_eq!(
+        rows,
+        (10..20)
+            .map(|n| vec![n.into(), (n * 2).into()])
+            .collect::<Vec<_>>()
+    );
 
     shutdown_tx.shutdown().await;
 }
 
 #[tokio::test(flavor = "multi_thread")]
-async fn self_join_param() {
-    let (mut g, shutdown_tx) = start_simple_unsharded("self_join_param").await;
-    g.extend_recipe(
-        ChangeList::from_str(
-            "CREATE TABLE users (id int, friend int);
-         CREATE CACHE fof FROM SELECT u1.id AS user, u2.friend AS fof \
-             FROM users u1 \
-             JOIN users u2 ON (u1.friend = u2.id) WHERE u1.id = ?;
-         CREATE VIEW fof2 AS SELECT u1.id AS user, u2.friend AS fof \
-             FROM users u1 \
-             JOIN users u2 ON (u1.friend = u2.id);
-         CREATE CACHE follow_on FROM SELECT * FROM fof2 WHERE user = ?;",
-            Dialect::DEFAULT_MYSQL,
-        )
-        .unwrap(),
-    )
-    .await
-    .unwrap();
+async fn range_scan() {
+    range_scan_impl(None, "range_scan").await
+}
 
-    let mut votes = g.table("users").await.unwrap();
-    votes.insert(vec![1i32.into(), 2i32.into()]).await.unwrap();
-    votes.insert(vec![1i32.into(), 3i32.into()]).await.unwrap();
-    votes.insert(vec![2i32.into(), 5i32.into()]).await.unwrap();
-    votes.insert(vec![3i32.into(), 1i32.into()]).await.unwrap();
-    votes.insert(vec![5i32.into(), 6i32.into()]).await.unwrap();
-    votes.insert(vec![6i32.into(), 2i32.into()]).await.unwrap();
+#[tokio::test(flavor = "multi_thread")]
+async fn range_scan_sharded() {
+    range_scan_impl(Some(DEFAULT_SHARDING), "range_scan_sharded").await
+}
 
-    // Check fof
+async fn export_view_state_impl(sharding: Option<usize>, persistence_name: &str) {
+    let (mut g, shutdown_tx) = {
+        let mut builder = Builder::for_tests();
+        builder.disable_partial();
+        builder.set_sharding(sharding);
+        builder.set_persistence(get_persistence_params(persistence_name));
+        builder.start_local()
+    }
+    .await
+    .unwrap();
 
-    let mut query = g.view("fof").await.unwrap().into_reader_handle().unwrap();
-    assert_eq!(query.columns(), vec!["user", "fof"]);
-    let results: Vec<(i32, i32)> = query
-        .lookup(&[1i32.into()], true)
-        .await
-        .unwrap()
-        .into_vec()
-        .iter()
-        .map(|r| {
-            (
-                get_col!(query, r, "user", i32),
-                get_col!(query, r, "fof", i32),
-            )
+    let a = g
+        .migrate(|mig| {
+            let a = mig.add_base(
+                "a",
+                make_columns(&["a", "b"]),
+                Base::new().with_primary_key([0]),
+            );
+            mig.maintain_anonymous(a, &Index::hash_map(vec![1]));
+            a
         })
-        .sorted()
-        .collect();
-    let expected = vec![(1, 1), (1, 5)];
-    assert_eq!(results, expected);
-
-    // Check follow_on
+        .await;
 
-    let mut query = g
-        .view("follow_on")
+    let mut a = g.table_by_index(a).await.unwrap();
+    let mut reader = g.view("a").await.unwrap().into_reader_handle().unwrap();
+    a.insert_many((0i32..100).map(|n| vec![DfValue::from(n), DfValue::from(n % 10)]))
         .await
-        .unwrap()
-        .into_reader_handle()
         .unwrap();
 
-    // Disabled because a "bogokey" column is present as well.
-    // assert_eq!(query.columns(), vec!["user", "fof"]);
+    sleep().await;
 
-    let results: Vec<(i32, i32)> = query
-        .lookup(&[1i32.into()], true)
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("a");
+    let summary = g
+        .export_view_state("a", ExportDest::Path(path.clone()))
         .await
+        .unwrap();
+    assert_eq!(summary.keys, 10);
+    assert_eq!(summary.rows, 100);
+    assert!(summary.bytes_written > 0);
+    assert!(!summary.partial);
+
+    let mut exported = read_view_export(&path)
         .unwrap()
-        .into_vec()
-        .iter()
-        .map(|r| {
-            (
-                get_col!(query, r, "user", i32),
-                get_col!(query, r, "fof", i32),
-            )
-        })
-        .sorted()
-        .collect();
-    assert_eq!(results, expected);
+        .collect::<ReadySetResult<Vec<_>>>()
+        .unwrap();
+    exported.sort();
+
+    // Compare against a direct lookup of every key in the view
+    let mut expected = vec![];
+    for key in (0i32..10).map(|n| vec![DfValue::from(n)]) {
+        let mut rows = reader.lookup(&key, true).await.unwrap().into_vec();
+        rows.sort();
+        expected.push((key, rows));
+    }
+    for (_, rows) in &mut exported {
+        rows.sort();
+    }
+    assert_eq!(exported, expected);
 
     shutdown_tx.shutdown().await;
 }
 
 #[tokio::test(flavor = "multi_thread")]
-async fn non_sql_materialized_range_query() {
+async fn export_view_state() {
+    export_view_state_impl(None, "export_view_state").await
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn export_view_state_sharded() {
+    export_view_state_impl(Some(DEFAULT_SHARDING), "export_view_state_sharded").await
+}
+
+async fn scan_view_impl(sharding: Option<usize>, persistence_name: &str) {
     let (mut g, shutdown_tx) = {
         let mut builder = Builder::for_tests();
         builder.disable_partial();
-        builder.set_sharding(None);
-        builder.set_persistence(get_persistence_params("non_sql_materialized_range_query"));
+        builder.set_sharding(sharding);
+        builder.set_persistence(get_persistence_params(persistence_name));
         builder.start_local()
     }
     .await
@@ -4848,42 +6396,67 @@ async fn non_sql_materialized_range_query() {
                 make_columns(&["a", "b"]),
                 Base::new().with_primary_key([0]),
             );
-            mig.maintain_anonymous(a, &Index::btree_map(vec![0]));
+            mig.maintain_anonymous(a, &Index::hash_map(vec![0]));
             a
         })
         .await;
 
     let mut a = g.table_by_index(a).await.unwrap();
-    let mut reader = g.view("a").await.unwrap().into_reader_handle().unwrap();
-    a.insert_many((0i32..10).map(|n| vec![DfValue::from(n), DfValue::from(n)]))
+    let reader = g.view("a").await.unwrap().into_reader_handle().unwrap();
+    a.insert_many((0i32..10_000).map(|n| vec![DfValue::from(n), DfValue::from(n * 2)]))
         .await
         .unwrap();
 
     sleep().await;
 
-    let res = reader
-        .multi_lookup(
-            vec![(vec1![DfValue::from(2)]..vec1![DfValue::from(5)]).into()],
-            false,
-        )
-        .await
-        .unwrap()
-        .into_vec();
+    let mut entries = vec![];
+    let mut scan = reader.scan(100);
+    for _ in 0..20 {
+        let batch = scan.next_batch().await.unwrap().unwrap();
+        assert!(batch.len() <= 100);
+        entries.extend(batch);
+    }
+    let summary = scan.summary();
+    assert_eq!(summary.keys, entries.len());
+    assert!(!summary.partial);
+
+    // Drop the scan part-way through, and resume it from its cursor
+    let cursor = scan.cursor().clone();
+    drop(scan);
+    let mut scan = reader.scan_from(cursor, 100);
+    while let Some(batch) = scan.next_batch().await.unwrap() {
+        assert!(batch.len() <= 100);
+        entries.extend(batch);
+    }
+    assert!(scan.next_batch().await.unwrap().is_none());
 
+    entries.sort();
     assert_eq!(
-        res,
-        (2..5).map(|n| vec![n.into(), n.into()]).collect::<Vec<_>>()
+        entries,
+        (0i32..10_000)
+            .map(|n| (vec![n.into()], vec![vec![n.into(), (n * 2).into()]]))
+            .collect::<Vec<_>>()
     );
 
     shutdown_tx.shutdown().await;
 }
 
 #[tokio::test(flavor = "multi_thread")]
-async fn non_sql_range_upquery() {
+async fn scan_view() {
+    scan_view_impl(None, "scan_view").await
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn scan_view_sharded() {
+    scan_view_impl(Some(DEFAULT_SHARDING), "scan_view_sharded").await
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn scan_partial_view() {
     let (mut g, shutdown_tx) = {
         let mut builder = Builder::for_tests();
         builder.set_sharding(None);
-        builder.set_persistence(get_persistence_params("non_sql_range_upquery"));
+        builder.set_persistence(get_persistence_params("scan_partial_view"));
         builder.start_local()
     }
     .await
@@ -4896,36 +6469,87 @@ async fn non_sql_range_upquery() {
                 make_columns(&["a", "b"]),
                 Base::new().with_primary_key([0]),
             );
-            mig.maintain_anonymous(a, &Index::btree_map(vec![0]));
+            mig.maintain_anonymous(a, &Index::hash_map(vec![0]));
             a
         })
         .await;
 
     let mut a = g.table_by_index(a).await.unwrap();
     let mut reader = g.view("a").await.unwrap().into_reader_handle().unwrap();
-    a.insert_many((0i32..10).map(|n| vec![DfValue::from(n), DfValue::from(n)]))
+    a.insert_many((0i32..100).map(|n| vec![DfValue::from(n), DfValue::from(n)]))
         .await
         .unwrap();
 
     sleep().await;
 
-    let res = reader
-        .multi_lookup(
-            vec![(vec1![DfValue::from(2)]..vec1![DfValue::from(5)]).into()],
-            true,
-        )
-        .await
-        .unwrap()
-        .into_vec();
+    // Fill a few keys in the reader
+    for n in [3i32, 14, 15] {
+        reader.lookup(&[n.into()], true).await.unwrap();
+    }
 
+    let mut scan = reader.scan(2);
+    let mut keys = vec![];
+    while let Some(batch) = scan.next_batch().await.unwrap() {
+        keys.extend(batch.into_iter().map(|(key, _)| key));
+    }
     assert_eq!(
-        res,
-        (2..5).map(|n| vec![n.into(), n.into()]).collect::<Vec<_>>()
+        keys,
+        vec![vec![3i32.into()], vec![14i32.into()], vec![15i32.into()]]
+    );
+    assert_eq!(
+        scan.summary(),
+        ScanSummary {
+            keys: 3,
+            rows: 3,
+            partial: true,
+        }
     );
 
     shutdown_tx.shutdown().await;
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn range_scan_hash_reader() {
+    let (mut g, shutdown_tx) = {
+        let mut builder = Builder::for_tests();
+        builder.disable_partial();
+        builder.set_sharding(None);
+        builder.set_persistence(get_persistence_params("range_scan_hash_reader"));
+        builder.start_local()
+    }
+    .await
+    .unwrap();
+
+    g.migrate(|mig| {
+        let a = mig.add_base(
+            "a",
+            make_columns(&["a", "b"]),
+            Base::new().with_primary_key([0]),
+        );
+        mig.maintain_anonymous(a, &Index::hash_map(vec![0]));
+    })
+    .await;
+
+    let mut reader = g.view("a").await.unwrap().into_reader_handle().unwrap();
+    for block in [false, true] {
+        let res = reader
+            .range_lookup(
+                Bound::Included(DfValue::from(10)),
+                Bound::Excluded(DfValue::from(20)),
+                None,
+                block,
+            )
+            .await;
+
+        assert!(
+            matches!(res, Err(ReadySetError::RangeUnsupported { .. })),
+            "{res:?}"
+        );
+    }
+
+    shutdown_tx.shutdown().await;
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn range_upquery_after_point_queries() {
     let (mut g, shutdown_tx) = {
@@ -8529,6 +10153,101 @@ async fn simple_drop_tables() {
     shutdown_tx.shutdown().await;
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn schema_version_increases_monotonically() {
+    let (mut g, shutdown_tx) =
+        start_simple_unsharded("schema_version_increases_monotonically").await;
+    assert_eq!(g.schema_version().await.unwrap(), 0);
+
+    let version = g
+        .migrate(|mig| {
+            mig.add_base(
+                "a",
+                make_columns(&["a", "b"]),
+                Base::new().with_primary_key([0]),
+            );
+            mig.current_schema_version()
+        })
+        .await;
+    assert_eq!(version, 0);
+    assert_eq!(g.schema_version().await.unwrap(), 1);
+
+    let version = g
+        .migrate(|mig| {
+            mig.add_base(
+                "b",
+                make_columns(&["a", "b"]),
+                Base::new().with_primary_key([0]),
+            );
+            mig.current_schema_version()
+        })
+        .await;
+    assert_eq!(version, 1);
+    assert_eq!(g.schema_version().await.unwrap(), 2);
+
+    shutdown_tx.shutdown().await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn table_for_non_base_node() {
+    let (mut g, shutdown_tx) = start_simple_unsharded("table_for_non_base_node").await;
+
+    let (base, reader) = g
+        .migrate(|mig| {
+            let a = mig.add_base(
+                "a",
+                make_columns(&["a", "b"]),
+                Base::new().with_primary_key([0]),
+            );
+            let reader = mig.maintain_anonymous(a, &Index::hash_map(vec![0]));
+            (a, reader)
+        })
+        .await;
+    g.table_by_index(base).await.unwrap();
+
+    let err = g.table_by_index(reader).await.unwrap_err();
+    assert_eq!(
+        err.mutator_build_cause(),
+        Some(&MutatorBuildError::NotABaseNode {
+            actual_kind: NodeType::Reader
+        }),
+        "{err}"
+    );
+
+    g.extend_recipe(
+        ChangeList::from_str(
+            "CREATE TABLE table_1 (column_1 INT);",
+            Dialect::DEFAULT_MYSQL,
+        )
+        .unwrap(),
+    )
+    .await
+    .unwrap();
+    let (_, dropped) = g
+        .tables()
+        .await
+        .unwrap()
+        .into_iter()
+        .find(|(table, _)| table.name == "table_1")
+        .unwrap();
+    g.table_by_index(dropped).await.unwrap();
+
+    g.extend_recipe(ChangeList::from_str("DROP TABLE table_1;", Dialect::DEFAULT_MYSQL).unwrap())
+        .await
+        .unwrap();
+
+    let err = g.table_by_index(dropped).await.unwrap_err();
+    assert_eq!(
+        err.mutator_build_cause(),
+        Some(&MutatorBuildError::NotABaseNode {
+            actual_kind: NodeType::Dropped
+        }),
+        "{err}"
+    );
+
+    shutdown_tx.shutdown().await;
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn join_drop_tables() {
     let (mut g, shutdown_tx) = start_simple_unsharded("simple_drop_tables").await;
@@ -8794,7 +10513,11 @@ async fn simple_dry_run_unsupported() {
         Err(RpcFailed {
             source:
                 box SelectQueryCreationFailed {
-                    source: box ReadySetError::Unsupported(_),
+                    source:
+                        box ReadySetError::UnsupportedFeature {
+                            feature: UnsupportedFeature::NoTables,
+                            ..
+                        },
                     ..
                 },
             ..
@@ -48,6 +48,16 @@ rusty_fork_test! {
             .unwrap();
         rt.block_on(test_metrics_client_impl());
     }
+
+    #[test]
+    #[cfg(feature = "failure_injection")]
+    fn failed_migration_kills_new_domains() {
+        let rt = tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        rt.block_on(failed_migration_kills_new_domains_impl());
+    }
 }
 
 async fn it_works_basic_impl() {
@@ -342,3 +352,52 @@ async fn test_metrics_client_impl() {
 
     shutdown_tx.shutdown().await;
 }
+
+#[cfg(feature = "failure_injection")]
+async fn failed_migration_kills_new_domains_impl() {
+    register_metric_recorder();
+    let (mut g, shutdown_tx) = start_simple_unsharded("failed_migration_kills_new_domains").await;
+    let mut client = initialize_metrics(&mut g).await;
+
+    g.extend_recipe(
+        ChangeList::from_str(
+            "CREATE TABLE t1 (id INT PRIMARY KEY, val INT);
+             CREATE CACHE q1 FROM SELECT * FROM t1 WHERE id = ?;",
+            Dialect::DEFAULT_MYSQL,
+        )
+        .unwrap(),
+    )
+    .await
+    .unwrap();
+    let metrics = client.get_metrics().await.unwrap();
+    let running_domains = get_metric!(&metrics[0].metrics, recorded::SERVER_WORKER_RUNNING_DOMAINS);
+    assert!(matches!(running_domains, Some(DumpedMetricValue::Gauge(n)) if n > 0.0));
+
+    // Fail the migration once its new domains have been booted, but before it's been committed
+    fail::cfg(
+        readyset_client::failpoints::MIGRATION_APPLY_DOMAIN_REQUEST,
+        "return",
+    )
+    .unwrap();
+    g.extend_recipe(
+        ChangeList::from_str(
+            "CREATE TABLE t2 (id INT PRIMARY KEY, val INT);
+             CREATE CACHE q2 FROM SELECT * FROM t2 WHERE id = ?;",
+            Dialect::DEFAULT_MYSQL,
+        )
+        .unwrap(),
+    )
+    .await
+    .unwrap_err();
+    fail::remove(readyset_client::failpoints::MIGRATION_APPLY_DOMAIN_REQUEST);
+
+    // The domains booted by the failed migration should have been killed on the worker
+    let metrics = client.get_metrics().await.unwrap();
+    assert_eq!(
+        get_metric!(&metrics[0].metrics, recorded::SERVER_WORKER_RUNNING_DOMAINS),
+        running_domains
+    );
+    assert!(!g.tables().await.unwrap().contains_key(&"t2".into()));
+
+    shutdown_tx.shutdown().await;
+}
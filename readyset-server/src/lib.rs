@@ -430,6 +430,7 @@ pub enum ReuseConfigType {
 
 use controller::migrate::materialization;
 pub use controller::migrate::materialization::FrontierStrategy;
+pub use controller::migrate::scheduling::PlacementStrategy;
 pub use controller::replication::{ReplicationOptions, ReplicationStrategy};
 use controller::sql;
 use database_utils::UpstreamConfig;
@@ -446,6 +447,10 @@ pub mod manual {
     pub use dataflow::node::special::Base;
     pub use dataflow::ops;
 
+    pub use crate::controller::keys::ProvenancePath;
+    pub use crate::controller::migrate::background::{BackgroundMigrationStatus, MigrationHandle};
+    pub use crate::controller::migrate::group::{GroupMigration, GroupOutcome, GroupQuery};
+    pub use crate::controller::migrate::progress::{MigrationEvent, MigrationPhase};
     pub use crate::controller::migrate::Migration;
 }
 
@@ -456,6 +461,7 @@ use std::time::Duration;
 use anyhow::anyhow;
 use clap::Args;
 use dataflow::DomainConfig;
+use readyset_sql_passes::SelfJoinPolicy;
 use serde::{Deserialize, Serialize};
 use tracing::warn;
 
@@ -478,6 +484,9 @@ pub struct Config {
     #[serde(alias = "quorum")]
     pub(crate) min_workers: usize,
     pub(crate) reuse: Option<ReuseConfigType>,
+    /// Which self-joins to allow in queries, out of the ones which could return incorrect results
+    #[serde(default)]
+    pub(crate) self_join_policy: SelfJoinPolicy,
     /// If set to true (the default), failing tokio tasks will cause a full-process abort.
     pub(crate) abort_on_task_failure: bool,
     /// Configuration for converting SQL to MIR
@@ -488,6 +497,9 @@ pub struct Config {
     pub(crate) replicator_statement_logging: bool,
     #[serde(default)]
     pub(crate) replication_strategy: ReplicationStrategy,
+    /// The strategy used to decide which workers to schedule domains onto
+    #[serde(default)]
+    pub(crate) placement_strategy: PlacementStrategy,
     /// The duration to wait before canceling the task waiting on an upquery.
     pub(crate) upquery_timeout: Duration,
     /// The duration to wait before canceling a task waiting on a worker request. Worker requests
@@ -519,15 +531,21 @@ impl Default for Config {
                 table_request_timeout: Duration::from_millis(1800000),
                 eviction_kind: dataflow::EvictionKind::Random,
                 verbose_metrics: false,
+                slow_node_threshold: Duration::from_secs(1),
+                tuning: Default::default(),
+                write_tracing_samples_per_million: 0,
+                count_emitted_records: false,
             },
             persistence: Default::default(),
             min_workers: 1,
             reuse: None,
+            self_join_policy: Default::default(),
             abort_on_task_failure: true,
             mir_config: Default::default(),
             replicator_statement_logging: false,
             replicator_config: Default::default(),
             replication_strategy: Default::default(),
+            placement_strategy: Default::default(),
             upquery_timeout: Duration::from_millis(5000),
             worker_request_timeout: Duration::from_millis(1800000),
             background_recovery_interval: default_background_recovery_interval(),
@@ -635,6 +653,18 @@ pub struct WorkerOptions {
     #[arg(long, env = "EXPERIMENTAL_POST_LOOKUP_SUPPORT", hide = true)]
     pub enable_experimental_post_lookup: bool,
 
+    /// Which self-joins to allow in queries, out of the ones which could return incorrect results
+    /// since both sides of the join key depend on the same column. `allow-on-primary-key` allows
+    /// the ones joining on the table's primary key, and `allow-all` allows all of them.
+    #[arg(
+        long,
+        env = "SELF_JOIN_POLICY",
+        value_enum,
+        default_value_t = SelfJoinPolicy::Reject,
+        hide = true
+    )]
+    pub self_join_policy: SelfJoinPolicy,
+
     /// Directory in which to store replicated table data. If not specified, defaults to the
     /// current working directory.
     #[arg(long, env = "STORAGE_DIR", conflicts_with = "db_dir")]
@@ -682,6 +712,16 @@ pub struct WorkerOptions {
         hide = true
     )]
     pub verbose_domain_metrics: bool,
+
+    /// Whether domains should count the records emitted by each node as the result of writes to
+    /// each base table, to report write amplification in the graph statistics
+    #[arg(
+        long,
+        env = "COUNT_EMITTED_RECORDS",
+        default_value = "false",
+        hide = true
+    )]
+    pub count_emitted_records: bool,
 }
 
 impl WorkerOptions {
@@ -743,4 +783,17 @@ mod tests {
         let worker_opts = Wrapper::parse_from(["test", "--db-dir", db_dir]).worker_opts;
         assert_eq!(Some(PathBuf::from(db_dir)), worker_opts.storage_dir());
     }
+
+    #[test]
+    fn self_join_policy() {
+        let worker_opts = Wrapper::parse_from(["test"]).worker_opts;
+        assert_eq!(worker_opts.self_join_policy, SelfJoinPolicy::Reject);
+
+        let worker_opts =
+            Wrapper::parse_from(["test", "--self-join-policy", "allow-on-primary-key"]).worker_opts;
+        assert_eq!(
+            worker_opts.self_join_policy,
+            SelfJoinPolicy::AllowOnPrimaryKey
+        );
+    }
 }
@@ -15,7 +15,9 @@ use readyset_server::consensus::AuthorityType;
 use readyset_server::metrics::{
     install_global_recorder, CompositeMetricsRecorder, MetricsRecorder,
 };
-use readyset_server::{resolve_addr, Builder, NoriaMetricsRecorder, WorkerOptions};
+use readyset_server::{
+    resolve_addr, Builder, NoriaMetricsRecorder, PlacementStrategy, WorkerOptions,
+};
 use readyset_telemetry_reporter::{TelemetryEvent, TelemetryInitializer};
 use readyset_version::*;
 use tracing::{error, info};
@@ -121,6 +123,25 @@ struct Options {
     #[arg(long, conflicts_with = "reader_only", env = "NO_READERS")]
     no_readers: bool,
 
+    /// Maximum number of domain shards that may be scheduled onto this server. If unset, there is
+    /// no limit
+    #[arg(long, env = "MAX_DOMAINS")]
+    max_domains: Option<usize>,
+
+    /// The NUMA node this server runs on. With `--placement-strategy numa-aware`, domains which
+    /// communicate with each other are preferentially scheduled onto servers on the same NUMA node
+    #[arg(long, env = "NUMA_NODE")]
+    numa_node: Option<usize>,
+
+    /// The strategy to use to decide which servers to schedule domains onto
+    #[arg(
+        long,
+        env = "PLACEMENT_STRATEGY",
+        value_enum,
+        default_value_t = PlacementStrategy::LoadBalanced
+    )]
+    placement_strategy: PlacementStrategy,
+
     /// Prevent this instance from ever being elected as the leader
     #[arg(long)]
     cannot_become_leader: bool,
@@ -250,6 +271,13 @@ fn main() -> anyhow::Result<()> {
     if opts.no_readers {
         builder.no_readers()
     }
+    if let Some(max_domains) = opts.max_domains {
+        builder.set_max_domains(max_domains)
+    }
+    if let Some(numa_node) = opts.numa_node {
+        builder.set_numa_node(numa_node)
+    }
+    builder.set_placement_strategy(opts.placement_strategy);
 
     let deployment = opts.deployment;
     let external_port = opts.external_port;
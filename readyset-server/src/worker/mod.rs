@@ -293,6 +293,7 @@ impl Worker {
                 info!("controller requested that this worker clears its existing domains");
                 self.coord.clear();
                 self.domains.clear();
+                gauge!(recorded::SERVER_WORKER_RUNNING_DOMAINS, 0.0);
                 while let Some((result, domain)) = self.domain_wait_queue.next().await {
                     log_domain_result(domain, &result)
                 }
@@ -387,6 +388,10 @@ impl Worker {
 
                 self.domains
                     .insert(replica_addr, DomainHandle { req_tx, abort });
+                gauge!(
+                    recorded::SERVER_WORKER_RUNNING_DOMAINS,
+                    self.domains.len() as f64
+                );
 
                 self.domain_wait_queue
                     .push(FinishedDomain(jh, replica_addr));
@@ -407,6 +412,10 @@ impl Worker {
                         None => warn!(domain = %addr, "Asked to kill domain that is not running"),
                     }
                 }
+                gauge!(
+                    recorded::SERVER_WORKER_RUNNING_DOMAINS,
+                    self.domains.len() as f64
+                );
                 Ok(None)
             }
             WorkerRequestKind::GossipDomainInformation(domains) => {
@@ -24,8 +24,9 @@ use readyset_client::metrics::recorded;
 use readyset_client::results::ResultIterator;
 use readyset_client::{
     KeyComparison, LookupResult, ReadQuery, ReadReply, ReadReplyStats, ReaderAddress, Tagged,
-    ViewQuery,
+    ViewQuery, ViewShardState,
 };
+use readyset_data::BoundedRange;
 use readyset_errors::internal_err;
 use readyset_util::shutdown::ShutdownReceiver;
 use serde::ser::Serializer;
@@ -44,6 +45,10 @@ const RETRY_TIMEOUT: Duration = Duration::from_micros(100);
 
 const WAIT_BEFORE_WARNING: Duration = Duration::from_secs(7);
 
+/// The number of keys read from a reader at a time when exporting its state, between each of which
+/// the export yields so that it doesn't hold up other reads
+const EXPORT_CHUNK_SIZE: usize = 1024;
+
 /// A batch of records either intended for local consumption only via the
 /// [`ServerReadReplyBatch::Unserialized`] variant, that avoids cloning entirely or for remote
 /// serialization using the [`ServerReadReplyBatch: :Serialized`] variant.
@@ -221,6 +226,7 @@ impl ReadRequestHandler {
                 // We hit on all keys, and there is no consistency miss, can return results
                 // immediately
                 self.hit_ctr.increment(1);
+                reader.record_lookup(filter.as_ref(), false);
 
                 let results = ResultIterator::new(hit, &reader.post_lookup, limit, offset, filter);
 
@@ -238,6 +244,7 @@ impl ReadRequestHandler {
         };
 
         self.miss_ctr.increment(1);
+        reader.record_lookup(filter.as_ref(), true);
 
         // Trigger backfills for all the keys we missed on, regardless of a consistency hit/miss
         if !keys_to_replay.is_empty() {
@@ -298,6 +305,102 @@ impl ReadRequestHandler {
             v: ReadReply::Keys(reader.keys()),
         })
     }
+
+    fn handle_range_scan_query(
+        &mut self,
+        tag: u32,
+        target: &ReaderAddress,
+        bounds: BoundedRange<DfValue>,
+        limit: Option<usize>,
+    ) -> Reply {
+        let reader = get_reader_from_cache(target, &mut self.readers_cache, &self.global_readers)?;
+
+        let res = match reader.range_scan(&bounds, limit) {
+            Ok(Some(batch)) => Ok(batch),
+            Ok(None) => Err(ReadySetError::RangeUnsupported {
+                reader: target.name.display_unquoted().to_string(),
+            }),
+            Err(LookupError::NotReady) => Err(ReadySetError::ViewNotYetAvailable),
+            Err(LookupError::Destroyed) => Err(ReadySetError::ViewDestroyed),
+            Err(LookupError::Error(e)) => Err(e),
+            Err(LookupError::Miss((misses, _))) => {
+                // Range scans never block at the reader, but trigger backfills for the missing
+                // ranges so that a retry can succeed
+                self.miss_ctr.increment(1);
+                reader.trigger(
+                    misses.into_iter().map(|k| k.into_owned()),
+                    target.name.clone(),
+                );
+                Err(ReadySetError::ReaderMissingKey)
+            }
+        };
+
+        Ok(Tagged {
+            tag,
+            v: ReadReply::RangeScan(res),
+        })
+    }
+
+    fn handle_scan_query(
+        &mut self,
+        tag: u32,
+        target: &ReaderAddress,
+        cursor: Option<Vec<DfValue>>,
+        batch_size: usize,
+    ) -> Reply {
+        let reader = get_reader_from_cache(target, &mut self.readers_cache, &self.global_readers)?;
+
+        Ok(Tagged {
+            tag,
+            v: ReadReply::Scan(reader.scan(cursor.as_deref(), batch_size)),
+        })
+    }
+
+    /// Export the full state of the reader at `target` on a separate task, so that exporting a
+    /// large reader doesn't hold up other reads handled by this handler. The reply is sent on the
+    /// returned channel once the export completes.
+    fn handle_export_query(
+        &mut self,
+        tag: u32,
+        target: &ReaderAddress,
+    ) -> ReadySetResult<oneshot::Receiver<Reply>> {
+        let reader =
+            get_reader_from_cache(target, &mut self.readers_cache, &self.global_readers)?.clone();
+
+        let (tx, rx) = oneshot::channel();
+        tokio::spawn(async move {
+            let res = export_reader(&reader).await;
+            let _ = tx.send(Ok(Tagged {
+                tag,
+                v: ReadReply::Export(res),
+            }));
+        });
+
+        Ok(rx)
+    }
+}
+
+/// Read the full state of `reader`, [`EXPORT_CHUNK_SIZE`] keys at a time, yielding to the runtime
+/// between each chunk
+async fn export_reader(reader: &SingleReadHandle) -> ReadySetResult<ViewShardState> {
+    let mut state = ViewShardState {
+        partial: reader.is_partial(),
+        entries: Vec::with_capacity(reader.len()),
+    };
+
+    for keys in reader.keys().chunks(EXPORT_CHUNK_SIZE) {
+        state.entries.extend(reader.export_chunk(keys)?);
+        tokio::task::yield_now().await;
+    }
+
+    Ok(state)
+}
+
+/// The response to a single call to [`ReadRequestHandler`], either produced by the handler itself
+/// or by a task spawned by the handler
+enum Response<F: Future<Output = Reply>> {
+    Call(CallResult<F>),
+    Spawned(oneshot::Receiver<Reply>),
 }
 
 impl Service<Tagged<ReadQuery>> for ReadRequestHandler {
@@ -317,24 +420,55 @@ impl Service<Tagged<ReadQuery>> for ReadRequestHandler {
             ReadQuery::Normal { target, query } => {
                 let span = readyset_tracing::child_span!(INFO, "normal_read_query");
                 let _g = span.enter();
-                self.handle_normal_read_query(tag, target, query, false)
+                Response::Call(self.handle_normal_read_query(tag, target, query, false))
             }
             ReadQuery::Size { ref target } => {
                 let span = readyset_tracing::child_span!(INFO, "size_query");
                 let _g = span.enter();
-                CallResult::Immediate(self.handle_size_query(tag, target))
+                Response::Call(CallResult::Immediate(self.handle_size_query(tag, target)))
             }
             ReadQuery::Keys { ref target } => {
                 let span = readyset_tracing::child_span!(INFO, "keys_query");
                 let _g = span.enter();
-                CallResult::Immediate(self.handle_keys_query(tag, target))
+                Response::Call(CallResult::Immediate(self.handle_keys_query(tag, target)))
+            }
+            ReadQuery::RangeScan {
+                ref target,
+                bounds,
+                limit,
+            } => {
+                let span = readyset_tracing::child_span!(INFO, "range_scan_query");
+                let _g = span.enter();
+                Response::Call(CallResult::Immediate(
+                    self.handle_range_scan_query(tag, target, bounds, limit),
+                ))
+            }
+            ReadQuery::Scan {
+                ref target,
+                cursor,
+                batch_size,
+            } => {
+                let span = readyset_tracing::child_span!(INFO, "scan_query");
+                let _g = span.enter();
+                Response::Call(CallResult::Immediate(
+                    self.handle_scan_query(tag, target, cursor, batch_size),
+                ))
+            }
+            ReadQuery::Export { ref target } => {
+                let span = readyset_tracing::child_span!(INFO, "export_query");
+                let _g = span.enter();
+                match self.handle_export_query(tag, target) {
+                    Ok(rx) => Response::Spawned(rx),
+                    Err(e) => Response::Call(CallResult::Immediate(Err(e))),
+                }
             }
         };
 
         async {
             match res {
-                CallResult::Immediate(immediate_response) => immediate_response,
-                CallResult::Async(async_response) => async_response.await,
+                Response::Call(CallResult::Immediate(immediate_response)) => immediate_response,
+                Response::Call(CallResult::Async(async_response)) => async_response.await,
+                Response::Spawned(rx) => rx.await.unwrap_or_else(|e| Err(internal_err!("{e}"))),
             }
         }
     }
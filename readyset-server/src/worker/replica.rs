@@ -463,6 +463,12 @@ impl Replica {
                 // Update domain sizes when `refresh_sizes` expires
                 Some(_) = refresh_sizes.next() => domain.update_state_sizes(),
 
+                // Process deferred low-priority replay batches once nothing else is ready. Yielding
+                // first gives every other branch a chance to be polled before this one completes.
+                _ = tokio::task::yield_now(), if domain.has_deferred_replays() => {
+                    span.in_scope(|| domain.handle_deferred_replay(out))?
+                },
+
                 // Wait for a possible sleep
                 _ = tokio::time::sleep(domain.next_poll_duration().unwrap_or_else(|| Duration::from_secs(3600))) => domain.handle_timeout()?,
             }
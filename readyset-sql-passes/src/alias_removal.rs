@@ -254,6 +254,7 @@ mod tests {
                 }),
                 alias: Some("t".into()),
                 index_hint: None,
+                sample: None,
             }],
             fields: vec![FieldDefinitionExpr::from(Column::from("t.id"))],
             where_clause: Some(Expr::BinaryOp {
@@ -293,6 +294,7 @@ mod tests {
                         }),
                         alias: None,
                         index_hint: None,
+                        sample: None,
                     }]
                 );
             }
@@ -329,6 +331,7 @@ mod tests {
                 }),
                 alias: Some("t".into()),
                 index_hint: None,
+                sample: None,
             }],
             fields: vec![FieldDefinitionExpr::from(col_small.clone())],
             where_clause: Some(Expr::BinaryOp {
@@ -365,6 +368,7 @@ mod tests {
                         }),
                         alias: None,
                         index_hint: None,
+                        sample: None,
                     }]
                 );
             }
@@ -407,6 +411,7 @@ mod tests {
                         }),
                         alias: None,
                         index_hint: None,
+                        sample: None,
                     }]
                 );
                 assert_eq!(
@@ -420,6 +425,7 @@ mod tests {
                             }),
                             alias: None,
                             index_hint: None,
+                            sample: None,
                         }),
                         constraint: JoinConstraint::On(Expr::BinaryOp {
                             op: BinaryOperator::Equal,
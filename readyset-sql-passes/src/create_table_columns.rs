@@ -59,4 +59,21 @@ mod tests {
         .unwrap();
         assert_eq!(orig.normalize_create_table_columns(), expected);
     }
+
+    #[test]
+    fn preserves_column_order() {
+        // Rows replicated from upstream (and the results of `SELECT *`) are laid out in declaration
+        // order, so columns must never be reordered - even to put the primary key first
+        let orig = parse_create_table(
+            Dialect::MySQL,
+            "CREATE TABLE t (b int, c int, a int primary key, unique (c))",
+        )
+        .unwrap();
+        let expected = parse_create_table(
+            Dialect::MySQL,
+            "CREATE TABLE t (t.b int, t.c int, t.a int primary key, unique (t.c))",
+        )
+        .unwrap();
+        assert_eq!(orig.normalize_create_table_columns(), expected);
+    }
 }
@@ -3,12 +3,28 @@ use std::iter;
 
 use itertools::{Either, Itertools};
 use nom_sql::{
-    BinaryOperator, Column, Expr, FieldDefinitionExpr, JoinConstraint, JoinRightSide, Relation,
-    SelectStatement, SqlIdentifier, SqlQuery, TableExpr, TableExprInner,
+    BinaryOperator, Column, ColumnConstraint, CreateTableBody, Expr, FieldDefinitionExpr,
+    JoinConstraint, JoinRightSide, Relation, SelectStatement, SqlIdentifier, SqlQuery, TableExpr,
+    TableExprInner, TableKey,
 };
 use readyset_errors::{
     internal_err, invalid_query_err, unsupported, unsupported_err, ReadySetResult,
 };
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+/// What to do with self-joins detected by [`DetectProblematicSelfJoins`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+pub enum SelfJoinPolicy {
+    /// Return an unsupported error for all problematic self-joins
+    #[default]
+    Reject,
+    /// Allow self-joins where the columns joined on cover the primary key of the table, and
+    /// return an unsupported error for all other problematic self-joins
+    AllowOnPrimaryKey,
+    /// Allow all self-joins, logging a warning for the ones which would otherwise be rejected
+    AllowAll,
+}
 
 pub trait DetectProblematicSelfJoins: Sized {
     /// Detect and return an unsupported error for any joins where both sides of the join key are
@@ -16,15 +32,95 @@ pub trait DetectProblematicSelfJoins: Sized {
     /// queries return incorrect results due to
     /// [ENG-411](https://readysettech.atlassian.net/browse/ENG-411)
     ///
+    /// Which of those joins are actually rejected is controlled by `policy` - see
+    /// [`SelfJoinPolicy`]. `base_schemas` is used to look up the primary keys of tables for
+    /// [`SelfJoinPolicy::AllowOnPrimaryKey`].
+    ///
     /// This must be run after the following rewrite passes:
     /// - [`expand_implied_tables`](super::ImpliedTableExpansion::expand_implied_tables)
     /// - [`expand_stars`](super::StarExpansion::expand_stars)
-    fn detect_problematic_self_joins(self) -> ReadySetResult<Self>;
+    fn detect_problematic_self_joins(
+        self,
+        policy: SelfJoinPolicy,
+        base_schemas: &HashMap<&Relation, &CreateTableBody>,
+    ) -> ReadySetResult<Self>;
+}
+
+/// Configuration for [`check_select_statement`], passed through to the checks for subqueries
+#[derive(Clone, Copy)]
+struct Context<'a> {
+    policy: SelfJoinPolicy,
+    base_schemas: &'a HashMap<&'a Relation, &'a CreateTableBody>,
+}
+
+/// Returns the names of the columns in the primary key of the given table, or an empty list if we
+/// don't know the schema of the table or the table has no primary key
+fn primary_key_columns<'a>(table: &Relation, ctx: Context<'a>) -> Vec<&'a SqlIdentifier> {
+    let Some(body) = ctx.base_schemas.get(table) else {
+        return vec![];
+    };
+
+    body.keys
+        .iter()
+        .flatten()
+        .find_map(|key| match key {
+            TableKey::PrimaryKey { columns, .. } => {
+                Some(columns.iter().map(|c| &c.name).collect::<Vec<_>>())
+            }
+            _ => None,
+        })
+        .unwrap_or_else(|| {
+            body.fields
+                .iter()
+                .filter(|f| f.constraints.contains(&ColumnConstraint::PrimaryKey))
+                .map(|f| &f.column.name)
+                .collect()
+        })
+}
+
+/// Decide, according to the [`SelfJoinPolicy`] in `ctx`, whether to allow a self-join whose
+/// condition makes both sides of the join depend on the given base table columns
+fn check_self_join(columns: &HashSet<(Relation, &str)>, ctx: Context) -> ReadySetResult<()> {
+    match ctx.policy {
+        SelfJoinPolicy::Reject => unsupported!("Self-joins using the same column are unsupported"),
+        SelfJoinPolicy::AllowOnPrimaryKey => {
+            let tables = columns
+                .iter()
+                .map(|(table, col)| (table, *col))
+                .into_group_map();
+            for (table, columns) in tables.into_iter().sorted() {
+                let primary_key = primary_key_columns(table, ctx);
+                if primary_key.is_empty()
+                    || !primary_key.iter().all(|pk| columns.contains(&pk.as_str()))
+                {
+                    unsupported!(
+                        "Self-joins using the same column are unsupported unless the join is on \
+                         the table's primary key: ({}) would need to be the primary key of {}",
+                        columns.iter().sorted().join(", "),
+                        table.display_unquoted()
+                    )
+                }
+            }
+            Ok(())
+        }
+        SelfJoinPolicy::AllowAll => {
+            warn!(
+                columns = %columns
+                    .iter()
+                    .map(|(table, col)| format!("{}.{col}", table.display_unquoted()))
+                    .sorted()
+                    .join(", "),
+                "Allowing self-join using the same column, which may return incorrect results"
+            );
+            Ok(())
+        }
+    }
 }
 
 fn check_select_statement<'a>(
     stmt: &'a SelectStatement,
     cte_ctx: &HashMap<&'a SqlIdentifier, &'a SelectStatement>,
+    ctx: Context<'a>,
 ) -> ReadySetResult<()> {
     // Iterate over all the *base table* columns in the query that the given *projected* column
     // depends on
@@ -32,6 +128,7 @@ fn check_select_statement<'a>(
         col: &'a Column,
         stmt: &'a SelectStatement,
         cte_ctx: &HashMap<&'a SqlIdentifier, &'a SelectStatement>,
+        ctx: Context<'a>,
     ) -> ReadySetResult<impl Iterator<Item = ReadySetResult<(Relation, &'a str)>> + 'a> {
         let table = col.table.as_ref().ok_or_else(|| {
             internal_err!("detect_problematic_self_joins must be run after expand_implied_tables")
@@ -69,9 +166,10 @@ fn check_select_statement<'a>(
                 table: Relation,
                 col_name: &'a str,
                 ctes: &HashMap<&'a SqlIdentifier, &'a SelectStatement>,
+                ctx: Context<'a>,
             ) -> ReadySetResult<impl Iterator<Item = ReadySetResult<(Relation, &'a str)>> + 'a>
             {
-                check_select_statement(stmt, ctes)?;
+                check_select_statement(stmt, ctes, ctx)?;
                 let expr = stmt
                     .fields
                     .iter()
@@ -98,10 +196,10 @@ fn check_select_statement<'a>(
                     .recursive_subexpressions()
                     .chain(iter::once(expr))
                     .map(move |expr| match expr {
-                        Expr::Column(col) => {
-                            Ok(Either::Left(Box::new(dependent_columns(col, stmt, &ctes)?)
-                                as Box<dyn Iterator<Item = Result<_, _>>>))
-                        }
+                        Expr::Column(col) => Ok(Either::Left(Box::new(dependent_columns(
+                            col, stmt, &ctes, ctx,
+                        )?)
+                            as Box<dyn Iterator<Item = Result<_, _>>>)),
                         _ => Ok(Either::Right(iter::empty())),
                     })
                     .flatten_ok()
@@ -124,6 +222,7 @@ fn check_select_statement<'a>(
                                 table.clone(),
                                 &col.name,
                                 &ctes,
+                                ctx,
                             )?));
                         }
                     }
@@ -146,12 +245,14 @@ fn check_select_statement<'a>(
                             inner: TableExprInner::Subquery(sq),
                             alias: Some(alias),
                             index_hint: None,
+                            ..
                         }) if table.schema.is_none() && *alias == table.name => {
                             res = Some(Either::Right(trace_subquery(
                                 sq,
                                 table.clone(),
                                 &col.name,
                                 &ctes,
+                                ctx,
                             )?));
                             break;
                         }
@@ -179,7 +280,7 @@ fn check_select_statement<'a>(
                     if tbl.schema.is_none()
                         && let Some(cte) = ctes.get(&tbl.name)
                     {
-                        Ok(Either::Right(trace_subquery(cte, tbl, cn, &ctes)?))
+                        Ok(Either::Right(trace_subquery(cte, tbl, cn, &ctes, ctx)?))
                     } else {
                         Ok(once_ok!((tbl, cn)))
                     }
@@ -190,43 +291,43 @@ fn check_select_statement<'a>(
         }
     }
 
-    fn expr_is_problematic<'a>(
+    /// Returns the set of base table columns which both sides of any equality comparison between
+    /// columns in `expr` depend on
+    fn problematic_columns<'a>(
         expr: &'a Expr,
         stmt: &'a SelectStatement,
         cte_ctx: &HashMap<&'a SqlIdentifier, &'a SelectStatement>,
-    ) -> ReadySetResult<bool> {
-        expr.recursive_subexpressions()
-            .chain(iter::once(expr))
-            .try_fold(false, |acc, expr| {
-                Ok(acc
-                    || match expr {
-                        Expr::BinaryOp {
-                            lhs: box Expr::Column(lhs_col),
-                            op: BinaryOperator::Equal,
-                            rhs: box Expr::Column(rhs_col),
-                        } => {
-                            let lhs_cols = dependent_columns(lhs_col, stmt, cte_ctx)?
-                                .collect::<Result<HashSet<_>, _>>()?;
-                            let mut problematic = false;
-                            for col in dependent_columns(rhs_col, stmt, cte_ctx)? {
-                                if lhs_cols.contains(&col?) {
-                                    problematic = true;
-                                    break;
-                                }
-                            }
-                            problematic
-                        }
-                        _ => false,
-                    })
-            })
+        ctx: Context<'a>,
+    ) -> ReadySetResult<HashSet<(Relation, &'a str)>> {
+        let mut res = HashSet::new();
+        for expr in expr.recursive_subexpressions().chain(iter::once(expr)) {
+            if let Expr::BinaryOp {
+                lhs: box Expr::Column(lhs_col),
+                op: BinaryOperator::Equal,
+                rhs: box Expr::Column(rhs_col),
+            } = expr
+            {
+                let lhs_cols =
+                    dependent_columns(lhs_col, stmt, cte_ctx, ctx)?
+                        .collect::<Result<HashSet<_>, _>>()?;
+                for col in dependent_columns(rhs_col, stmt, cte_ctx, ctx)? {
+                    let col = col?;
+                    if lhs_cols.contains(&col) {
+                        res.insert(col);
+                    }
+                }
+            }
+        }
+        Ok(res)
     }
 
     for join in &stmt.join {
         match &join.constraint {
             JoinConstraint::Using(_) => unsupported!("USING is unsupported"),
             JoinConstraint::On(expr) => {
-                if expr_is_problematic(expr, stmt, cte_ctx)? {
-                    unsupported!("Self-joins using the same column are unsupported")
+                let columns = problematic_columns(expr, stmt, cte_ctx, ctx)?;
+                if !columns.is_empty() {
+                    check_self_join(&columns, ctx)?;
                 }
             }
             JoinConstraint::Empty => {}
@@ -237,21 +338,37 @@ fn check_select_statement<'a>(
 }
 
 impl DetectProblematicSelfJoins for SelectStatement {
-    fn detect_problematic_self_joins(self) -> ReadySetResult<Self> {
-        check_select_statement(&self, &HashMap::new())?;
+    fn detect_problematic_self_joins(
+        self,
+        policy: SelfJoinPolicy,
+        base_schemas: &HashMap<&Relation, &CreateTableBody>,
+    ) -> ReadySetResult<Self> {
+        let ctx = Context {
+            policy,
+            base_schemas,
+        };
+        check_select_statement(&self, &HashMap::new(), ctx)?;
         Ok(self)
     }
 }
 
 impl DetectProblematicSelfJoins for SqlQuery {
-    fn detect_problematic_self_joins(self) -> ReadySetResult<Self> {
+    fn detect_problematic_self_joins(
+        self,
+        policy: SelfJoinPolicy,
+        base_schemas: &HashMap<&Relation, &CreateTableBody>,
+    ) -> ReadySetResult<Self> {
+        let ctx = Context {
+            policy,
+            base_schemas,
+        };
         match &self {
             SqlQuery::Select(stmt) => {
-                check_select_statement(stmt, &HashMap::new())?;
+                check_select_statement(stmt, &HashMap::new(), ctx)?;
             }
             SqlQuery::CompoundSelect(stmt) => {
                 for (_, stmt) in &stmt.selects {
-                    check_select_statement(stmt, &HashMap::new())?;
+                    check_select_statement(stmt, &HashMap::new(), ctx)?;
                 }
             }
             _ => {}
@@ -271,7 +388,7 @@ mod tests {
 
         fn is_unsupported(query_str: &str) {
             let query = parse_query(Dialect::MySQL, query_str).unwrap();
-            let res = query.detect_problematic_self_joins();
+            let res = query.detect_problematic_self_joins(SelfJoinPolicy::Reject, &HashMap::new());
             assert!(res.is_err());
             let err = res.err().unwrap();
             assert!(err.is_unsupported(), "res.err().unwrap() = {:?}", err);
@@ -371,7 +488,9 @@ mod tests {
 
         fn is_supported(query_str: &str) {
             let query = parse_query(Dialect::MySQL, query_str).unwrap();
-            let res = query.clone().detect_problematic_self_joins();
+            let res = query
+                .clone()
+                .detect_problematic_self_joins(SelfJoinPolicy::Reject, &HashMap::new());
             assert!(res.is_ok());
             assert_eq!(res.unwrap(), query);
         }
@@ -386,4 +505,133 @@ mod tests {
             is_supported("SELECT * FROM t JOIN t2 t2 ON t.x = t2.x");
         }
     }
+
+    mod policies {
+        use nom_sql::{parse_create_table, parse_query, Dialect};
+        use readyset_errors::ReadySetError;
+
+        use super::*;
+
+        fn check(query_str: &str, policy: SelfJoinPolicy) -> ReadySetResult<SqlQuery> {
+            let ddl = [
+                "CREATE TABLE t (id INT PRIMARY KEY, x INT, y INT)",
+                "CREATE TABLE u (a INT, b INT, c INT, PRIMARY KEY (a, b))",
+            ]
+            .map(|s| parse_create_table(Dialect::MySQL, s).unwrap());
+            let base_schemas = ddl
+                .iter()
+                .map(|stmt| (&stmt.table, stmt.body.as_ref().unwrap()))
+                .collect();
+
+            parse_query(Dialect::MySQL, query_str)
+                .unwrap()
+                .detect_problematic_self_joins(policy, &base_schemas)
+        }
+
+        fn unsupported_message(res: ReadySetResult<SqlQuery>) -> String {
+            match res {
+                Err(ReadySetError::Unsupported(msg)) => msg,
+                res => panic!("Expected unsupported error, got {res:?}"),
+            }
+        }
+
+        #[test]
+        fn reject_primary_key_join() {
+            unsupported_message(check(
+                "SELECT t.x FROM t JOIN t t2 ON t.id = t2.id",
+                SelfJoinPolicy::Reject,
+            ));
+        }
+
+        #[test]
+        fn allow_on_primary_key() {
+            check(
+                "SELECT t.x FROM t JOIN t t2 ON t.id = t2.id",
+                SelfJoinPolicy::AllowOnPrimaryKey,
+            )
+            .unwrap();
+        }
+
+        #[test]
+        fn allow_on_primary_key_rejects_non_key_join() {
+            let msg = unsupported_message(check(
+                "SELECT t.x FROM t JOIN t t2 ON t.x = t2.x AND t.y = t2.y",
+                SelfJoinPolicy::AllowOnPrimaryKey,
+            ));
+            assert!(
+                msg.contains("(x, y) would need to be the primary key of t"),
+                "msg = {msg}"
+            );
+        }
+
+        #[test]
+        fn allow_on_compound_primary_key() {
+            check(
+                "SELECT u.c FROM u JOIN u u2 ON u.a = u2.a AND u.b = u2.b",
+                SelfJoinPolicy::AllowOnPrimaryKey,
+            )
+            .unwrap();
+            let msg = unsupported_message(check(
+                "SELECT u.c FROM u JOIN u u2 ON u.a = u2.a",
+                SelfJoinPolicy::AllowOnPrimaryKey,
+            ));
+            assert!(
+                msg.contains("(a) would need to be the primary key of u"),
+                "msg = {msg}"
+            );
+        }
+
+        #[test]
+        fn allow_on_primary_key_unknown_table() {
+            unsupported_message(check(
+                "SELECT v.x FROM v JOIN v v2 ON v.id = v2.id",
+                SelfJoinPolicy::AllowOnPrimaryKey,
+            ));
+        }
+
+        #[test]
+        fn allow_on_primary_key_aliased() {
+            check(
+                "SELECT t1.x FROM t t1 JOIN t t2 ON t1.id = t2.id",
+                SelfJoinPolicy::AllowOnPrimaryKey,
+            )
+            .unwrap();
+            unsupported_message(check(
+                "SELECT t1.x FROM t t1 JOIN t t2 ON t1.x = t2.x",
+                SelfJoinPolicy::AllowOnPrimaryKey,
+            ));
+        }
+
+        #[test]
+        fn allow_on_primary_key_subquery() {
+            check(
+                "SELECT t.x FROM t JOIN (SELECT t.id AS id FROM t) sq ON t.id = sq.id",
+                SelfJoinPolicy::AllowOnPrimaryKey,
+            )
+            .unwrap();
+            unsupported_message(check(
+                "SELECT t.x FROM t JOIN (SELECT t.x + 1 - 1 AS x FROM t) sq ON t.x = sq.x",
+                SelfJoinPolicy::AllowOnPrimaryKey,
+            ));
+        }
+
+        #[test]
+        fn allow_all() {
+            for query in [
+                "SELECT t.x FROM t JOIN t t2 ON t.x = t2.x",
+                "SELECT t1.x FROM t t1 JOIN t t2 ON t1.x = t2.x",
+                "SELECT t.x FROM t JOIN (SELECT t.x AS x FROM t) sq ON t.x = sq.x",
+            ] {
+                check(query, SelfJoinPolicy::AllowAll).unwrap();
+            }
+        }
+
+        #[test]
+        fn allow_all_still_rejects_using() {
+            unsupported_message(check(
+                "SELECT t.x FROM t JOIN t t2 USING (x)",
+                SelfJoinPolicy::AllowAll,
+            ));
+        }
+    }
 }
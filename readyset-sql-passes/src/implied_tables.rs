@@ -3,13 +3,14 @@ use std::mem;
 
 use itertools::Itertools;
 use nom_sql::analysis::visit_mut::{
-    walk_group_by_clause, walk_order_clause, walk_select_statement, VisitorMut,
+    walk_group_by_clause, walk_join_constraint, walk_order_clause, walk_select_statement,
+    VisitorMut,
 };
 use nom_sql::{
-    Column, FieldDefinitionExpr, Relation, SelectStatement, SqlIdentifier, SqlQuery, TableExprInner,
+    Column, FieldDefinitionExpr, JoinConstraint, JoinOperator, Relation, SelectStatement,
+    SqlIdentifier, SqlQuery, TableExpr, TableExprInner,
 };
 use readyset_errors::{internal, invalid_query_err, ReadySetError, ReadySetResult};
-use tracing::warn;
 
 use crate::{outermost_table_exprs, util};
 
@@ -32,13 +33,80 @@ struct ExpandImpliedTablesVisitor<'schema> {
     tables: HashMap<Relation, Relation>,
     /// The set of aliases for projected fields that are currently in-scope
     aliases: HashSet<SqlIdentifier>,
+    /// The names of the columns merged by the `JOIN ... USING` clauses of the query currently in
+    /// scope, each mapped to the table (by alias) an unqualified reference to that column resolves
+    /// to
+    using_columns: HashMap<SqlIdentifier, Relation>,
     // Are we currently in a position in the query that can reference aliases in the projected
     // field list?
     can_reference_aliases: bool,
 }
 
 impl<'schema> ExpandImpliedTablesVisitor<'schema> {
-    fn find_table(&self, column_name: &str) -> Option<Relation> {
+    /// Returns true if the table in scope with the given alias (or name, if unaliased) has a column
+    /// with the given name
+    fn table_has_column(&self, alias: &Relation, column_name: &SqlIdentifier) -> bool {
+        self.tables
+            .iter()
+            .find(|(_, a)| *a == alias)
+            .and_then(|(t, _)| {
+                self.schema
+                    .get(t)
+                    .or_else(|| self.subquery_schemas.get(&t.name))
+            })
+            .map_or(false, |cols| cols.contains(column_name))
+    }
+
+    /// Resolve each of the columns merged by the `JOIN ... USING` clauses of the given query to
+    /// the table an unqualified reference to that column refers to.
+    ///
+    /// For a `RIGHT JOIN`, that's the table on the right side of the join, since the merged column
+    /// is `NULL` on the left side for rows which have no match there. For every other kind of
+    /// join, it's the (left-most) table on the left side of the join which has the column.
+    fn using_columns(&self, stmt: &SelectStatement) -> HashMap<SqlIdentifier, Relation> {
+        fn name(tbl: &TableExpr) -> Option<Relation> {
+            tbl.alias
+                .clone()
+                .map(Relation::from)
+                .or_else(|| tbl.inner.as_table().cloned())
+        }
+
+        let mut res: HashMap<SqlIdentifier, Relation> = HashMap::new();
+        let mut left_tables = stmt.tables.iter().filter_map(name).collect::<Vec<_>>();
+        for jc in &stmt.join {
+            let right_tables = jc.right.table_exprs().filter_map(name).collect::<Vec<_>>();
+            if let JoinConstraint::Using(cols) = &jc.constraint {
+                for col in cols {
+                    let find = |tables: &[Relation]| {
+                        tables
+                            .iter()
+                            .find(|t| self.table_has_column(t, &col.name))
+                            .cloned()
+                    };
+                    let left = res.get(&col.name).cloned().or_else(|| find(&left_tables));
+                    let right = find(&right_tables);
+                    let table = if jc.operator == JoinOperator::RightJoin {
+                        right.or(left)
+                    } else {
+                        left.or(right)
+                    };
+                    if let Some(table) = table {
+                        res.insert(col.name.clone(), table);
+                    }
+                }
+            }
+            left_tables.extend(right_tables);
+        }
+        res
+    }
+
+    /// Find the (unique) table in scope which has a column with the given name.
+    ///
+    /// Returns `Ok(None)` if no table in scope has such a column, and an error if more than one
+    /// does, since the column reference is then ambiguous - unless the column was merged by a
+    /// `JOIN ... USING` clause, in which case it resolves to the table given by
+    /// [`Self::using_columns`].
+    fn find_table(&self, column_name: &str) -> ReadySetResult<Option<Relation>> {
         let mut matches = self
             .schema
             .iter()
@@ -58,23 +126,23 @@ impl<'schema> ExpandImpliedTablesVisitor<'schema> {
                     None
                 }
             })
+            .unique()
             .collect::<Vec<_>>();
 
         if matches.len() > 1 {
-            warn!(
-                "Ambiguous column {} exists in tables: {} -- picking a random one",
+            if let Some(table) = self.using_columns.get(column_name) {
+                return Ok(Some(table.clone()));
+            }
+            Err(invalid_query_err!(
+                "Column {} is ambiguous; it exists in tables: {}",
                 column_name,
                 matches.iter().map(|t| t.display_unquoted()).join(", ")
-            );
-            Some(matches.pop().unwrap())
-        } else if matches.is_empty() {
-            // This might be an alias for a computed column, which has no
-            // implied table. So, we allow it to pass and our code should
-            // crash in the future if this is not the case.
-            None
+            ))
         } else {
-            // exactly one match
-            Some(matches.pop().unwrap())
+            // If there are no matches, this might be an alias for a computed column, which has no
+            // implied table. So, we allow it to pass and our code should crash in the future if
+            // this is not the case.
+            Ok(matches.pop())
         }
     }
 }
@@ -114,6 +182,8 @@ impl<'ast, 'schema> VisitorMut<'ast> for ExpandImpliedTablesVisitor<'schema> {
             .map(|(k, v)| (k.into(), v.into_iter().map(|s| s.into()).collect()))
             .collect(),
         );
+        let using_columns = self.using_columns(select_statement);
+        let orig_using_columns = mem::replace(&mut self.using_columns, using_columns);
         let orig_aliases = mem::replace(
             &mut self.aliases,
             select_statement
@@ -132,6 +202,7 @@ impl<'ast, 'schema> VisitorMut<'ast> for ExpandImpliedTablesVisitor<'schema> {
         self.tables = orig_tables;
         self.subquery_schemas = orig_subquery_schemas;
         self.aliases = orig_aliases;
+        self.using_columns = orig_using_columns;
 
         Ok(())
     }
@@ -156,6 +227,18 @@ impl<'ast, 'schema> VisitorMut<'ast> for ExpandImpliedTablesVisitor<'schema> {
         Ok(())
     }
 
+    fn visit_join_constraint(
+        &mut self,
+        join_constraint: &'ast mut JoinConstraint,
+    ) -> Result<(), Self::Error> {
+        // The columns in a `USING` clause name a column in both of the tables being joined, so
+        // they can't be qualified with either one
+        if matches!(join_constraint, JoinConstraint::Using(_)) {
+            return Ok(());
+        }
+        walk_join_constraint(self, join_constraint)
+    }
+
     fn visit_column(&mut self, column: &'ast mut Column) -> Result<(), Self::Error> {
         if self.can_reference_aliases && self.aliases.contains(&column.name) {
             return Ok(());
@@ -184,7 +267,7 @@ impl<'ast, 'schema> VisitorMut<'ast> for ExpandImpliedTablesVisitor<'schema> {
                 table.schema = t.schema.clone();
             }
         } else {
-            column.table = self.find_table(&column.name);
+            column.table = self.find_table(&column.name)?;
         }
 
         Ok(())
@@ -200,6 +283,7 @@ fn rewrite_select(
         subquery_schemas: Default::default(),
         tables: Default::default(),
         aliases: Default::default(),
+        using_columns: Default::default(),
         can_reference_aliases: false,
     };
 
@@ -354,6 +438,36 @@ mod tests {
         assert_eq!(res, expected);
     }
 
+    #[test]
+    fn in_values_row_constructors() {
+        // `(a, b) IN (VALUES (1, 2), (3, 4))` parses to the same AST as this query. Columns inside
+        // the row constructors can reference the tables in scope just like any other expression,
+        // so they're qualified too
+        let orig = parse_query(
+            Dialect::MySQL,
+            "SELECT id FROM t1 WHERE (a, b) IN (ROW(1, 2), ROW(3, c))",
+        )
+        .unwrap();
+        let expected = parse_query(
+            Dialect::MySQL,
+            "SELECT t1.id FROM t1 WHERE (t1.a, t1.b) IN (ROW(1, 2), ROW(3, t1.c))",
+        )
+        .unwrap();
+        let schema = HashMap::from([(
+            "t1".into(),
+            vec!["id".into(), "a".into(), "b".into(), "c".into()],
+        )]);
+
+        let res = orig.expand_implied_tables(&schema).unwrap();
+        assert_eq!(
+            res,
+            expected,
+            "\n left: {}\nright: {}",
+            res.display(nom_sql::Dialect::MySQL),
+            expected.display(nom_sql::Dialect::MySQL)
+        );
+    }
+
     #[test]
     fn case_when() {
         let orig = parse_query(
@@ -473,6 +587,76 @@ Dialect::MySQL,
         orig.expand_implied_tables(&schema).unwrap_err();
     }
 
+    #[test]
+    fn ambiguous_column() {
+        let orig = parse_query(
+            Dialect::MySQL,
+            "SELECT id FROM users JOIN articles ON users.id = articles.author",
+        )
+        .unwrap();
+        let schema = HashMap::from([
+            ("users".into(), vec!["id".into(), "name".into()]),
+            ("articles".into(), vec!["id".into(), "author".into()]),
+        ]);
+
+        let err = orig.expand_implied_tables(&schema).unwrap_err();
+        assert!(err.to_string().contains("ambiguous"), "{err}");
+    }
+
+    #[test]
+    fn column_merged_by_join_using() {
+        let orig = parse_query(
+            Dialect::MySQL,
+            "SELECT id, x, y FROM a LEFT JOIN b USING (id) WHERE id = ?",
+        )
+        .unwrap();
+        let expected = parse_query(
+            Dialect::MySQL,
+            "SELECT a.id, a.x, b.y FROM a LEFT JOIN b USING (id) WHERE a.id = ?",
+        )
+        .unwrap();
+        let schema = HashMap::from([
+            ("a".into(), vec!["id".into(), "x".into()]),
+            ("b".into(), vec!["id".into(), "y".into()]),
+        ]);
+
+        let res = orig.expand_implied_tables(&schema).unwrap();
+        assert_eq!(
+            res,
+            expected,
+            "\n left: {}\nright: {}",
+            res.display(nom_sql::Dialect::MySQL),
+            expected.display(nom_sql::Dialect::MySQL)
+        );
+    }
+
+    #[test]
+    fn column_merged_by_right_join_using() {
+        let orig = parse_query(
+            Dialect::MySQL,
+            "SELECT id, x, y FROM a RIGHT JOIN b USING (id) WHERE id = ?",
+        )
+        .unwrap();
+        let expected = parse_query(
+            Dialect::MySQL,
+            "SELECT b.id, a.x, b.y FROM a RIGHT JOIN b USING (id) WHERE b.id = ?",
+        )
+        .unwrap();
+        let schema = HashMap::from([
+            ("a".into(), vec!["id".into(), "x".into()]),
+            ("b".into(), vec!["id".into(), "y".into()]),
+        ]);
+
+        let res = orig.expand_implied_tables(&schema).unwrap();
+        assert_eq!(
+            res,
+            expected,
+            "\n left: {}\nright: {}",
+            res.display(nom_sql::Dialect::MySQL),
+            expected.display(nom_sql::Dialect::MySQL)
+        );
+    }
+
     #[test]
     fn votes() {
         let orig = parse_query(
@@ -20,6 +20,7 @@ mod inline_literals;
 mod key_def_coalescing;
 mod normalize_topk_with_aggregate;
 mod order_limit_removal;
+pub mod pipeline;
 mod remove_numeric_field_references;
 mod resolve_schemas;
 mod rewrite_between;
@@ -40,7 +41,7 @@ use readyset_errors::ReadySetResult;
 
 pub use crate::alias_removal::AliasRemoval;
 pub use crate::create_table_columns::CreateTableColumns;
-pub use crate::detect_problematic_self_joins::DetectProblematicSelfJoins;
+pub use crate::detect_problematic_self_joins::{DetectProblematicSelfJoins, SelfJoinPolicy};
 pub use crate::detect_unsupported_placeholders::DetectUnsupportedPlaceholders;
 pub use crate::expr::ScalarOptimizeExpressions;
 pub use crate::implied_tables::ImpliedTableExpansion;
@@ -48,6 +49,7 @@ pub use crate::inline_literals::InlineLiterals;
 pub use crate::key_def_coalescing::KeyDefinitionCoalescing;
 pub use crate::normalize_topk_with_aggregate::NormalizeTopKWithAggregate;
 pub use crate::order_limit_removal::OrderLimitRemoval;
+pub use crate::pipeline::{rewrite_with_pipeline, Pass, DEFAULT_SELECT_PIPELINE};
 pub use crate::remove_numeric_field_references::RemoveNumericFieldReferences;
 pub use crate::resolve_schemas::ResolveSchemas;
 pub use crate::rewrite_between::RewriteBetween;
@@ -95,6 +97,10 @@ pub struct RewriteContext<'a> {
     ///
     /// [resolve_schemas pass]: crate::resolve_schemas
     pub invalidating_tables: Option<&'a mut Vec<Relation>>,
+
+    /// Which self-joins to allow, out of the ones which could return incorrect results. See
+    /// [`DetectProblematicSelfJoins`].
+    pub self_join_policy: SelfJoinPolicy,
 }
 
 /// Can a particular relation (in the map passed to [`ResolveSchemas::resolve_schemas`]) be queried
@@ -159,21 +165,7 @@ impl Rewrite for CreateTableStatement {
 
 impl Rewrite for SelectStatement {
     fn rewrite(self, context: &mut RewriteContext) -> ReadySetResult<Self> {
-        self.rewrite_between()
-            .scalar_optimize_expressions(context.dialect)
-            .strip_post_filters()
-            .resolve_schemas(
-                context.tables(),
-                context.custom_types,
-                context.search_path,
-                context.invalidating_tables.as_deref_mut(),
-            )?
-            .expand_stars(context.view_schemas, context.non_replicated_relations)?
-            .expand_implied_tables(context.view_schemas)?
-            .normalize_topk_with_aggregate()?
-            .detect_problematic_self_joins()?
-            .remove_numeric_field_references()?
-            .order_limit_removal(&context.base_schemas)
+        pipeline::run_pipeline(self, context, DEFAULT_SELECT_PIPELINE)
     }
 }
 
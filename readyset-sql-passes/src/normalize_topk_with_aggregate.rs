@@ -1,9 +1,10 @@
-use nom_sql::analysis::contains_aggregate;
+use nom_sql::analysis::{contains_aggregate, ReferredColumns};
 use nom_sql::{
-    DialectDisplay, Expr, FieldDefinitionExpr, FieldReference, LimitClause, OrderBy,
-    SelectStatement, SqlQuery,
+    BinaryOperator, Column, DialectDisplay, Expr, FieldDefinitionExpr, FieldReference,
+    FunctionExpr, LimitClause, LimitValue, Literal, OrderBy, OrderClause, SelectStatement,
+    SqlIdentifier, SqlQuery, TableExpr, TableExprInner,
 };
-use readyset_errors::{ReadySetError, ReadySetResult};
+use readyset_errors::{unsupported, ReadySetError, ReadySetResult};
 
 pub trait NormalizeTopKWithAggregate: Sized {
     /// Remove any topk clause (order by, limit, offset) from a query with an aggregate without a
@@ -13,11 +14,272 @@ pub trait NormalizeTopKWithAggregate: Sized {
     /// If the query *has* a GROUP BY clause, this query checks that all the columns in the ORDER BY
     /// clause either appear in the GROUP BY clause, or reference the results of aggregates, and
     /// returns an error otherwise.
+    ///
+    /// Before doing either of those, queries which select from a derived table that projects
+    /// `ROW_NUMBER() OVER (ORDER BY ...)`, and filter on it with a `<=` or `<` comparison against
+    /// a literal, are rewritten to put the equivalent `ORDER BY ... LIMIT n` in the derived table,
+    /// so that they can be executed with a TopK operator.
     fn normalize_topk_with_aggregate(self) -> ReadySetResult<Self>;
 }
 
+/// If the given expression is a comparison of the `ROW_NUMBER()` column with the given alias,
+/// projected by the subquery with the given alias, against an integer literal which bounds it from
+/// above, returns the number of rows that comparison allows
+fn row_number_bound(expr: &Expr, subquery: &SqlIdentifier, alias: &SqlIdentifier) -> Option<i64> {
+    let Expr::BinaryOp { lhs, op, rhs } = expr else {
+        return None;
+    };
+    let is_row_number = |col: &Column| {
+        col.name == *alias && col.table.as_ref().map_or(true, |t| t.name == *subquery)
+    };
+    let (op, literal) = match (lhs.as_ref(), rhs.as_ref()) {
+        (Expr::Column(col), Expr::Literal(lit)) if is_row_number(col) => (*op, lit),
+        (Expr::Literal(lit), Expr::Column(col)) if is_row_number(col) => {
+            (op.flip_ordering_comparison().ok()?, lit)
+        }
+        _ => return None,
+    };
+    let n = match literal {
+        Literal::Integer(n) => *n,
+        Literal::UnsignedInteger(n) => i64::try_from(*n).ok()?,
+        _ => return None,
+    };
+    match op {
+        BinaryOperator::LessOrEqual => Some(n.max(0)),
+        BinaryOperator::Less => Some((n - 1).max(0)),
+        _ => None,
+    }
+}
+
+/// Split the given expression into its top-level conjuncts
+fn conjuncts(expr: Expr) -> Vec<Expr> {
+    match expr {
+        Expr::BinaryOp {
+            lhs,
+            op: BinaryOperator::And,
+            rhs,
+        } => {
+            let mut res = conjuncts(*lhs);
+            res.extend(conjuncts(*rhs));
+            res
+        }
+        expr => vec![expr],
+    }
+}
+
+/// A `ROW_NUMBER()` column projected by a query
+struct RowNumberField {
+    /// The index of the column in the query's field list
+    index: usize,
+    /// The alias of the column
+    alias: SqlIdentifier,
+    /// The `PARTITION BY` and `ORDER BY` of the `OVER` clause of the call, if it has one
+    window: Option<(Vec<Expr>, Option<OrderClause>)>,
+}
+
+/// Returns the first aliased `ROW_NUMBER()` column in the given field list, if any
+fn row_number_field(fields: &[FieldDefinitionExpr]) -> Option<RowNumberField> {
+    fields.iter().enumerate().find_map(|(index, f)| match f {
+        FieldDefinitionExpr::Expr {
+            expr: Expr::Call(FunctionExpr::Call { name, arguments }),
+            alias: Some(alias),
+        } if name.eq_ignore_ascii_case("row_number") && arguments.is_empty() => {
+            Some(RowNumberField {
+                index,
+                alias: alias.clone(),
+                window: None,
+            })
+        }
+        FieldDefinitionExpr::Expr {
+            expr:
+                Expr::Call(FunctionExpr::Window {
+                    name,
+                    arguments,
+                    partition_by,
+                    order_by,
+                }),
+            alias: Some(alias),
+        } if name.eq_ignore_ascii_case("row_number") && arguments.is_empty() => {
+            Some(RowNumberField {
+                index,
+                alias: alias.clone(),
+                window: Some((partition_by.clone(), order_by.clone())),
+            })
+        }
+        _ => None,
+    })
+}
+
+/// Rewrite a query which numbers the rows of a derived table with `ROW_NUMBER()` and filters on
+/// that number, such as:
+///
+/// ```sql
+/// SELECT sub.a FROM (
+///     SELECT t.a, row_number() OVER (ORDER BY t.a) AS rn FROM t WHERE t.b = 1
+/// ) AS sub WHERE sub.rn <= 3
+/// ```
+///
+/// to the equivalent `ORDER BY ... LIMIT` form:
+///
+/// ```sql
+/// SELECT sub.a FROM (SELECT t.a FROM t WHERE t.b = 1 ORDER BY t.a LIMIT 3) AS sub
+/// ```
+///
+/// The order the rows are numbered in is taken from the `ORDER BY` in the `OVER` clause, which
+/// must match the derived table's own `ORDER BY` clause if it has one. A bare `row_number()` call
+/// with no `OVER` clause numbers the rows in the order given by the derived table's `ORDER BY`
+/// clause. Since we have no way of computing the `ROW_NUMBER()` column itself, it's removed from
+/// the derived table, so the outer query must not project it - that way, the rewritten query
+/// returns the same columns as the original.
+///
+/// Queries which don't filter on a `ROW_NUMBER()` column are left unchanged. Queries which do, but
+/// which can't be rewritten to a TopK (because they filter on it in the same query that computes
+/// it, the rows have no order, the window is partitioned, the derived table already has a `LIMIT`
+/// or is `DISTINCT`, or the `ROW_NUMBER()` column is referred to anywhere else) return an
+/// unsupported error.
+fn normalize_row_number(stmt: &mut SelectStatement) -> ReadySetResult<()> {
+    let Some(where_clause) = &stmt.where_clause else {
+        return Ok(());
+    };
+    let mut preds = conjuncts(where_clause.clone());
+
+    // Window functions are evaluated after the `WHERE` clause, so a `WHERE` clause can't filter on
+    // a `ROW_NUMBER()` column computed by the same query
+    if let Some(field) = row_number_field(&stmt.fields) {
+        if preds.iter().any(|pred| {
+            pred.referred_columns()
+                .any(|col| col.table.is_none() && col.name == field.alias)
+        }) {
+            unsupported!("ROW_NUMBER() can only be filtered on from an enclosing query");
+        }
+    }
+
+    let [TableExpr {
+        inner: TableExprInner::Subquery(subquery),
+        alias: Some(subquery_alias),
+    }] = stmt.tables.as_mut_slice()
+    else {
+        return Ok(());
+    };
+    if !stmt.join.is_empty() {
+        return Ok(());
+    }
+    let Some(RowNumberField {
+        index,
+        alias,
+        window,
+    }) = row_number_field(&subquery.fields)
+    else {
+        return Ok(());
+    };
+    let Some((pred_idx, limit)) = preds
+        .iter()
+        .enumerate()
+        .find_map(|(i, pred)| Some((i, row_number_bound(pred, subquery_alias, &alias)?)))
+    else {
+        return Ok(());
+    };
+    preds.remove(pred_idx);
+
+    if let Some((partition_by, window_order)) = window {
+        if !partition_by.is_empty() {
+            unsupported!("ROW_NUMBER() with PARTITION BY is unsupported");
+        }
+        if let Some(window_order) = window_order {
+            match &subquery.order {
+                None => subquery.order = Some(window_order),
+                Some(order) if *order == window_order => {}
+                Some(_) => unsupported!(
+                    "ROW_NUMBER() ordered differently from the query's ORDER BY is unsupported"
+                ),
+            }
+        }
+    }
+
+    if subquery.order.is_none() {
+        unsupported!("ROW_NUMBER() without an ORDER BY clause is unsupported");
+    }
+    if !matches!(
+        subquery.limit_clause,
+        LimitClause::LimitOffset {
+            limit: None,
+            offset: None
+        }
+    ) {
+        unsupported!("ROW_NUMBER() combined with LIMIT or OFFSET is unsupported");
+    }
+    if subquery.distinct {
+        unsupported!("ROW_NUMBER() combined with DISTINCT is unsupported");
+    }
+
+    let refers_to_row_number = |expr: &Expr| {
+        expr.referred_columns().any(|col| {
+            col.name == alias
+                && col
+                    .table
+                    .as_ref()
+                    .map_or(true, |t| t.name == *subquery_alias)
+        })
+    };
+    let outer_references = preds.iter().any(refers_to_row_number)
+        || stmt.having.iter().any(refers_to_row_number)
+        || stmt.fields.iter().any(|field| match field {
+            FieldDefinitionExpr::Expr { expr, .. } => refers_to_row_number(expr),
+            _ => true,
+        })
+        || stmt
+            .order
+            .iter()
+            .flat_map(|o| o.order_by.iter().map(|o| &o.field))
+            .chain(stmt.group_by.iter().flat_map(|g| &g.fields))
+            .any(|field| match field {
+                FieldReference::Numeric(_) => false,
+                FieldReference::Expr(expr) => refers_to_row_number(expr),
+            });
+    let inner_references = subquery
+        .order
+        .iter()
+        .flat_map(|o| o.order_by.iter().map(|o| &o.field))
+        .chain(subquery.group_by.iter().flat_map(|g| &g.fields))
+        .any(|field| match field {
+            FieldReference::Numeric(n) => *n as usize == index + 1,
+            FieldReference::Expr(Expr::Column(col)) => col.table.is_none() && col.name == alias,
+            FieldReference::Expr(_) => false,
+        });
+    if outer_references || inner_references {
+        unsupported!("ROW_NUMBER() may only be referenced in a single upper bound");
+    }
+
+    subquery.fields.remove(index);
+    // Numeric references to fields after the `ROW_NUMBER()` column now refer to one field earlier
+    for field in subquery
+        .order
+        .iter_mut()
+        .flat_map(|o| o.order_by.iter_mut().map(|o| &mut o.field))
+        .chain(subquery.group_by.iter_mut().flat_map(|g| &mut g.fields))
+    {
+        if let FieldReference::Numeric(n) = field {
+            if *n as usize > index + 1 {
+                *n -= 1;
+            }
+        }
+    }
+    subquery.limit_clause = LimitClause::LimitOffset {
+        limit: Some(LimitValue::Literal(Literal::Integer(limit))),
+        offset: None,
+    };
+    stmt.where_clause = preds.into_iter().reduce(|lhs, rhs| Expr::BinaryOp {
+        lhs: Box::new(lhs),
+        op: BinaryOperator::And,
+        rhs: Box::new(rhs),
+    });
+    Ok(())
+}
+
 impl NormalizeTopKWithAggregate for SelectStatement {
     fn normalize_topk_with_aggregate(mut self) -> ReadySetResult<Self> {
+        normalize_row_number(&mut self)?;
+
         if let Some(order) = self.order.take() {
             let aggs = self
                 .fields
@@ -203,6 +465,110 @@ mod tests {
         }
     }
 
+    fn normalizes_to(input: &str, expected: &str) {
+        let input_query = parse_query(Dialect::MySQL, input).unwrap();
+        let expected_query = parse_query(Dialect::MySQL, expected).unwrap();
+        let actual = input_query.normalize_topk_with_aggregate().unwrap();
+        assert_eq!(
+            actual,
+            expected_query,
+            "\nExpected: {}\n  Actual: {}",
+            expected_query.display(Dialect::MySQL),
+            actual.display(Dialect::MySQL)
+        );
+    }
+
+    #[test]
+    fn row_number_less_or_equal() {
+        normalizes_to(
+            "SELECT sub.a FROM (SELECT t.a, row_number() AS rn FROM t WHERE t.b = ? ORDER BY t.a) \
+             AS sub WHERE sub.rn <= 3",
+            "SELECT sub.a FROM (SELECT t.a FROM t WHERE t.b = ? ORDER BY t.a LIMIT 3) AS sub",
+        )
+    }
+
+    #[test]
+    fn row_number_less_than() {
+        normalizes_to(
+            "SELECT sub.a FROM (SELECT t.a, row_number() AS rn FROM t ORDER BY t.a DESC) AS sub \
+             WHERE rn < 3",
+            "SELECT sub.a FROM (SELECT t.a FROM t ORDER BY t.a DESC LIMIT 2) AS sub",
+        )
+    }
+
+    #[test]
+    fn row_number_flipped_comparison() {
+        normalizes_to(
+            "SELECT sub.a FROM (SELECT row_number() AS rn, t.a FROM t WHERE t.b = 1 ORDER BY 2) \
+             AS sub WHERE 5 >= sub.rn AND sub.a > 0",
+            "SELECT sub.a FROM (SELECT t.a FROM t WHERE t.b = 1 ORDER BY 1 LIMIT 5) AS sub \
+             WHERE sub.a > 0",
+        )
+    }
+
+    #[test]
+    fn row_number_window() {
+        normalizes_to(
+            "SELECT sub.a FROM (SELECT t.a, row_number() OVER (ORDER BY t.a DESC) AS rn FROM t) \
+             AS sub WHERE sub.rn <= 3",
+            "SELECT sub.a FROM (SELECT t.a FROM t ORDER BY t.a DESC LIMIT 3) AS sub",
+        );
+        normalizes_to(
+            "SELECT sub.a FROM (SELECT t.a, row_number() OVER (ORDER BY t.a) AS rn FROM t \
+             ORDER BY t.a) AS sub WHERE sub.rn < 3",
+            "SELECT sub.a FROM (SELECT t.a FROM t ORDER BY t.a LIMIT 2) AS sub",
+        );
+    }
+
+    #[test]
+    fn row_number_without_predicate_is_unchanged() {
+        for query in [
+            "SELECT sub.a, sub.rn FROM (SELECT t.a, row_number() AS rn FROM t ORDER BY t.a) AS sub \
+             WHERE sub.a = ?",
+            "SELECT t.a, row_number() AS rn FROM t WHERE t.b = ? ORDER BY t.a",
+        ] {
+            let query = parse_query(Dialect::MySQL, query).unwrap();
+            let result = query.clone().normalize_topk_with_aggregate().unwrap();
+            assert_eq!(result, query);
+        }
+    }
+
+    #[test]
+    fn row_number_not_expressible_as_topk_is_unsupported() {
+        for query in [
+            // Filtered on in the same query that computes it
+            "SELECT t.a, row_number() AS rn FROM t WHERE rn <= 3 ORDER BY t.a",
+            // Unordered
+            "SELECT sub.a FROM (SELECT t.a, row_number() AS rn FROM t) AS sub WHERE sub.rn <= 3",
+            // Referenced outside of the upper bound
+            "SELECT sub.a FROM (SELECT t.a, row_number() AS rn FROM t ORDER BY t.a) AS sub \
+             WHERE sub.rn <= 3 AND sub.rn > 1",
+            "SELECT sub.a FROM (SELECT t.a, row_number() AS rn FROM t ORDER BY rn) AS sub \
+             WHERE sub.rn <= 3",
+            // Projected, so removing it would change the columns the query returns
+            "SELECT sub.a, sub.rn FROM (SELECT t.a, row_number() AS rn FROM t ORDER BY t.a) AS sub \
+             WHERE sub.rn <= 3",
+            // Already limited, deduplicated, partitioned, or ordered differently
+            "SELECT sub.a FROM (SELECT t.a, row_number() AS rn FROM t ORDER BY t.a LIMIT 2) \
+             AS sub WHERE sub.rn <= 3",
+            "SELECT sub.a FROM (SELECT DISTINCT t.a, row_number() AS rn FROM t ORDER BY t.a) \
+             AS sub WHERE sub.rn <= 3",
+            "SELECT sub.a FROM (SELECT t.a, row_number() OVER (PARTITION BY t.b ORDER BY t.a) \
+             AS rn FROM t) AS sub WHERE sub.rn <= 3",
+            "SELECT sub.a FROM (SELECT t.a, row_number() OVER (ORDER BY t.a) AS rn FROM t \
+             ORDER BY t.b) AS sub WHERE sub.rn <= 3",
+        ] {
+            let parsed = parse_query(Dialect::MySQL, query).unwrap();
+            assert!(
+                matches!(
+                    parsed.normalize_topk_with_aggregate(),
+                    Err(ReadySetError::Unsupported(_))
+                ),
+                "{query}"
+            );
+        }
+    }
+
     #[test]
     fn group_by_reference() {
         let query = parse_query(
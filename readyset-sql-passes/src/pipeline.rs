@@ -0,0 +1,378 @@
+//! Declarative description of the sequence of rewrite passes run on a [`SelectStatement`].
+//!
+//! The default pipeline, which is what [`Rewrite`] uses for [`SelectStatement`]s, is
+//! [`DEFAULT_SELECT_PIPELINE`]. Custom pipelines can be run with [`rewrite_with_pipeline`], which
+//! first validates that the ordering constraints between passes (see [`Pass::requires`] and
+//! [`Pass::must_follow`]) are satisfied.
+//!
+//! [`Rewrite`]: crate::Rewrite
+
+use nom_sql::SelectStatement;
+use readyset_errors::{ReadySetError, ReadySetResult};
+
+use crate::{
+    DetectProblematicSelfJoins, ImpliedTableExpansion, NormalizeTopKWithAggregate,
+    OrderLimitRemoval, RemoveNumericFieldReferences, ResolveSchemas, RewriteBetween,
+    RewriteContext, ScalarOptimizeExpressions, StarExpansion, StripPostFilters,
+};
+
+/// A single rewrite pass which can be run on a [`SelectStatement`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Pass {
+    /// See [`RewriteBetween`]
+    RewriteBetween,
+    /// See [`ScalarOptimizeExpressions`]
+    ScalarOptimizeExpressions,
+    /// See [`StripPostFilters`]
+    StripPostFilters,
+    /// See [`ResolveSchemas`]
+    ResolveSchemas,
+    /// See [`StarExpansion`]
+    ExpandStars,
+    /// See [`ImpliedTableExpansion`]
+    ExpandImpliedTables,
+    /// See [`NormalizeTopKWithAggregate`]
+    NormalizeTopKWithAggregate,
+    /// See [`DetectProblematicSelfJoins`]
+    DetectProblematicSelfJoins,
+    /// See [`RemoveNumericFieldReferences`]
+    RemoveNumericFieldReferences,
+    /// See [`OrderLimitRemoval`]
+    OrderLimitRemoval,
+}
+
+/// The sequence of passes run by [`Rewrite::rewrite`] for [`SelectStatement`]s
+///
+/// [`Rewrite::rewrite`]: crate::Rewrite::rewrite
+pub const DEFAULT_SELECT_PIPELINE: &[Pass] = &[
+    Pass::RewriteBetween,
+    Pass::ScalarOptimizeExpressions,
+    Pass::StripPostFilters,
+    Pass::ResolveSchemas,
+    Pass::ExpandStars,
+    Pass::ExpandImpliedTables,
+    Pass::NormalizeTopKWithAggregate,
+    Pass::DetectProblematicSelfJoins,
+    Pass::RemoveNumericFieldReferences,
+    Pass::OrderLimitRemoval,
+];
+
+impl Pass {
+    /// Returns the list of passes which must appear somewhere in any pipeline that contains this
+    /// pass
+    pub fn requires(self) -> &'static [Pass] {
+        match self {
+            // Star expansion looks up tables in the schema by their fully-qualified names
+            Pass::ExpandStars => &[Pass::ResolveSchemas],
+            // Implied table expansion can't expand columns that are hidden behind a `*`
+            Pass::ExpandImpliedTables => &[Pass::ResolveSchemas, Pass::ExpandStars],
+            // Both of these need every column to be qualified with the table it comes from
+            Pass::DetectProblematicSelfJoins | Pass::OrderLimitRemoval => {
+                &[Pass::ExpandImpliedTables]
+            }
+            // Numeric field references index into the (expanded) list of fields
+            Pass::RemoveNumericFieldReferences => &[Pass::ExpandStars],
+            Pass::RewriteBetween
+            | Pass::ScalarOptimizeExpressions
+            | Pass::StripPostFilters
+            | Pass::ResolveSchemas
+            | Pass::NormalizeTopKWithAggregate => &[],
+        }
+    }
+
+    /// Returns the list of passes which, if they appear in a pipeline that contains this pass,
+    /// must run before this pass
+    pub fn must_follow(self) -> &'static [Pass] {
+        match self {
+            Pass::ExpandStars => &[Pass::ResolveSchemas],
+            Pass::ExpandImpliedTables => &[Pass::ResolveSchemas, Pass::ExpandStars],
+            Pass::DetectProblematicSelfJoins | Pass::OrderLimitRemoval => {
+                &[Pass::ResolveSchemas, Pass::ExpandImpliedTables]
+            }
+            Pass::RemoveNumericFieldReferences => &[Pass::ExpandStars],
+            // `BETWEEN` is desugared into expressions which can then be optimized further
+            Pass::ScalarOptimizeExpressions => &[Pass::RewriteBetween],
+            Pass::RewriteBetween
+            | Pass::StripPostFilters
+            | Pass::ResolveSchemas
+            | Pass::NormalizeTopKWithAggregate => &[],
+        }
+    }
+
+    /// Run this pass on the given statement
+    pub fn run(
+        self,
+        stmt: SelectStatement,
+        context: &mut RewriteContext,
+    ) -> ReadySetResult<SelectStatement> {
+        match self {
+            Pass::RewriteBetween => Ok(stmt.rewrite_between()),
+            Pass::ScalarOptimizeExpressions => {
+                Ok(stmt.scalar_optimize_expressions(context.dialect))
+            }
+            Pass::StripPostFilters => Ok(stmt.strip_post_filters()),
+            Pass::ResolveSchemas => stmt.resolve_schemas(
+                context.tables(),
+                context.custom_types,
+                context.search_path,
+                context.invalidating_tables.as_deref_mut(),
+            ),
+            Pass::ExpandStars => {
+                stmt.expand_stars(context.view_schemas, context.non_replicated_relations)
+            }
+            Pass::ExpandImpliedTables => stmt.expand_implied_tables(context.view_schemas),
+            Pass::NormalizeTopKWithAggregate => stmt.normalize_topk_with_aggregate(),
+            Pass::DetectProblematicSelfJoins => {
+                stmt.detect_problematic_self_joins(context.self_join_policy, &context.base_schemas)
+            }
+            Pass::RemoveNumericFieldReferences => stmt.remove_numeric_field_references(),
+            Pass::OrderLimitRemoval => stmt.order_limit_removal(&context.base_schemas),
+        }
+    }
+}
+
+/// Check that all of the [`requires`] and [`must_follow`] constraints of the passes in the given
+/// pipeline are satisfied, returning [`ReadySetError::InvalidRewritePipeline`] naming the first
+/// violated constraint if not.
+///
+/// [`requires`]: Pass::requires
+/// [`must_follow`]: Pass::must_follow
+pub fn validate_pipeline(pipeline: &[Pass]) -> ReadySetResult<()> {
+    for (idx, pass) in pipeline.iter().enumerate() {
+        if let Some(dependency) = pass.requires().iter().find(|dep| !pipeline.contains(dep)) {
+            return Err(ReadySetError::InvalidRewritePipeline {
+                pass: format!("{pass:?}"),
+                constraint: "requires".into(),
+                dependency: format!("{dependency:?}"),
+            });
+        }
+
+        if let Some(dependency) = pass
+            .must_follow()
+            .iter()
+            .find(|dep| pipeline[idx..].contains(dep))
+        {
+            return Err(ReadySetError::InvalidRewritePipeline {
+                pass: format!("{pass:?}"),
+                constraint: "must follow".into(),
+                dependency: format!("{dependency:?}"),
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Validate the given pipeline with [`validate_pipeline`], then run each of its passes in order
+/// on the given statement
+pub fn rewrite_with_pipeline(
+    stmt: SelectStatement,
+    context: &mut RewriteContext,
+    pipeline: &[Pass],
+) -> ReadySetResult<SelectStatement> {
+    validate_pipeline(pipeline)?;
+    run_pipeline(stmt, context, pipeline)
+}
+
+/// Run each of the passes in the given pipeline in order, without validating it
+pub(crate) fn run_pipeline(
+    stmt: SelectStatement,
+    context: &mut RewriteContext,
+    pipeline: &[Pass],
+) -> ReadySetResult<SelectStatement> {
+    pipeline
+        .iter()
+        .try_fold(stmt, |stmt, pass| pass.run(stmt, context))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{HashMap, HashSet};
+
+    use dataflow_expression::Dialect;
+    use nom_sql::{parse_query, CreateTableStatement, Relation, SqlIdentifier, SqlQuery};
+
+    use super::*;
+    use crate::util::parse_select_statement;
+
+    fn parse_create_table(q: &str) -> CreateTableStatement {
+        match parse_query(nom_sql::Dialect::MySQL, q).unwrap() {
+            SqlQuery::CreateTable(stmt) => stmt,
+            _ => panic!(),
+        }
+    }
+
+    /// The sequence of rewrite passes that was previously hardcoded in `Rewrite for
+    /// SelectStatement`, kept here to check that [`DEFAULT_SELECT_PIPELINE`] is equivalent
+    fn rewrite_with_method_chain(
+        stmt: SelectStatement,
+        context: &mut RewriteContext,
+    ) -> ReadySetResult<SelectStatement> {
+        stmt.rewrite_between()
+            .scalar_optimize_expressions(context.dialect)
+            .strip_post_filters()
+            .resolve_schemas(
+                context.tables(),
+                context.custom_types,
+                context.search_path,
+                context.invalidating_tables.as_deref_mut(),
+            )?
+            .expand_stars(context.view_schemas, context.non_replicated_relations)?
+            .expand_implied_tables(context.view_schemas)?
+            .normalize_topk_with_aggregate()?
+            .detect_problematic_self_joins(context.self_join_policy, &context.base_schemas)?
+            .remove_numeric_field_references()?
+            .order_limit_removal(&context.base_schemas)
+    }
+
+    /// Rewrite the given query with both the default pipeline and the method chain that the
+    /// default pipeline replaced, and return both results
+    fn rewrite_both_ways(
+        query: &str,
+    ) -> (
+        ReadySetResult<SelectStatement>,
+        ReadySetResult<SelectStatement>,
+    ) {
+        let schema_name = SqlIdentifier::from("s");
+        let ddl = [
+            "CREATE TABLE t (id int primary key, a int, b text)",
+            "CREATE TABLE u (id int primary key, t_id int, c text)",
+        ]
+        .into_iter()
+        .map(|q| {
+            let mut stmt = parse_create_table(q);
+            stmt.table.schema = Some(schema_name.clone());
+            stmt
+        })
+        .collect::<Vec<_>>();
+        let view_schemas = ddl
+            .iter()
+            .map(|stmt| {
+                (
+                    stmt.table.clone(),
+                    stmt.body
+                        .as_ref()
+                        .unwrap()
+                        .fields
+                        .iter()
+                        .map(|f| f.column.name.clone())
+                        .collect(),
+                )
+            })
+            .collect::<HashMap<Relation, Vec<SqlIdentifier>>>();
+        let non_replicated_relations = HashSet::new();
+        let custom_types = HashMap::new();
+        let search_path = [schema_name.clone()];
+        let make_context = || RewriteContext {
+            view_schemas: &view_schemas,
+            base_schemas: ddl
+                .iter()
+                .map(|stmt| (&stmt.table, stmt.body.as_ref().unwrap()))
+                .collect(),
+            uncompiled_views: &[],
+            non_replicated_relations: &non_replicated_relations,
+            custom_types: &custom_types,
+            search_path: &search_path,
+            dialect: Dialect::DEFAULT_MYSQL,
+            invalidating_tables: None,
+            self_join_policy: Default::default(),
+        };
+
+        let stmt = parse_select_statement(query);
+
+        let pipeline_res =
+            rewrite_with_pipeline(stmt.clone(), &mut make_context(), DEFAULT_SELECT_PIPELINE);
+
+        let chain_res = rewrite_with_method_chain(stmt, &mut make_context());
+
+        (pipeline_res, chain_res)
+    }
+
+    #[test]
+    fn default_pipeline_is_valid() {
+        validate_pipeline(DEFAULT_SELECT_PIPELINE).unwrap();
+    }
+
+    #[test]
+    fn default_pipeline_matches_method_chain() {
+        for query in [
+            "SELECT * FROM t",
+            "SELECT a, b FROM t WHERE id = ?",
+            "SELECT t.a, c FROM t JOIN u ON t.id = u.t_id WHERE b = 'x'",
+            "SELECT a FROM t WHERE a BETWEEN 1 AND 2 + 3",
+            "SELECT a FROM t WHERE NOT (a = 1 OR b = 'x')",
+            "SELECT a, count(*) FROM t GROUP BY 1 ORDER BY 2 DESC LIMIT 3",
+            "SELECT a FROM t WHERE id = ? ORDER BY b LIMIT 1",
+            "SELECT max(a) FROM t ORDER BY b LIMIT 10",
+            "SELECT * FROM t WHERE a IN (SELECT t_id FROM u WHERE c = 'y')",
+            "SELECT a FROM nonexistent",
+        ] {
+            let (pipeline_res, chain_res) = rewrite_both_ways(query);
+            match (pipeline_res, chain_res) {
+                (Ok(pipeline), Ok(chain)) => assert_eq!(pipeline, chain, "query: {query}"),
+                (Err(pipeline), Err(chain)) => {
+                    assert_eq!(pipeline.to_string(), chain.to_string(), "query: {query}")
+                }
+                (pipeline, chain) => {
+                    panic!("query: {query}, pipeline: {pipeline:?}, chain: {chain:?}")
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn row_number_window_rewritten_to_topk() {
+        let (pipeline_res, chain_res) = rewrite_both_ways(
+            "SELECT a FROM (SELECT a, ROW_NUMBER() OVER (ORDER BY a) AS rn FROM t WHERE b = 'x') \
+             AS sub WHERE rn <= 3",
+        );
+        let (expected, _) = rewrite_both_ways(
+            "SELECT a FROM (SELECT a FROM t WHERE b = 'x' ORDER BY a LIMIT 3) AS sub",
+        );
+        let expected = expected.unwrap();
+        assert_eq!(pipeline_res.unwrap(), expected);
+        assert_eq!(chain_res.unwrap(), expected);
+    }
+
+    #[test]
+    fn missing_required_pass() {
+        let err =
+            validate_pipeline(&[Pass::ResolveSchemas, Pass::ExpandImpliedTables]).unwrap_err();
+        assert_eq!(
+            err,
+            ReadySetError::InvalidRewritePipeline {
+                pass: "ExpandImpliedTables".into(),
+                constraint: "requires".into(),
+                dependency: "ExpandStars".into(),
+            }
+        );
+    }
+
+    #[test]
+    fn pass_out_of_order() {
+        let mut pipeline = DEFAULT_SELECT_PIPELINE.to_vec();
+        let stars = pipeline
+            .iter()
+            .position(|p| *p == Pass::ExpandStars)
+            .unwrap();
+        let implied = pipeline
+            .iter()
+            .position(|p| *p == Pass::ExpandImpliedTables)
+            .unwrap();
+        pipeline.swap(stars, implied);
+
+        let err = validate_pipeline(&pipeline).unwrap_err();
+        assert_eq!(
+            err,
+            ReadySetError::InvalidRewritePipeline {
+                pass: "ExpandImpliedTables".into(),
+                constraint: "must follow".into(),
+                dependency: "ExpandStars".into(),
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "Invalid rewrite pipeline: ExpandImpliedTables must follow ExpandStars"
+        );
+    }
+}
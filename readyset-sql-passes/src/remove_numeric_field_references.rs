@@ -5,6 +5,11 @@ pub trait RemoveNumericFieldReferences: Sized {
     /// Rewrite any [numeric field references][0] in the `GROUP BY` and `ORDER BY` clauses of the
     /// query into [expressions][1] given by the field in the `SELECT` list they reference.
     ///
+    /// Integer literals in the `HAVING` clause are left as-is, even if they appear on their own
+    /// (eg `HAVING 2`) - unlike `GROUP BY` and `ORDER BY`, neither MySQL nor PostgreSQL treat
+    /// integers in `HAVING` as references to fields, so rewriting them would change the results of
+    /// the query.
+    ///
     /// [0]: FieldReference::Numeric
     /// [0]: FieldReference::Expr
     fn remove_numeric_field_references(self) -> ReadySetResult<Self>;
@@ -68,7 +73,7 @@ impl RemoveNumericFieldReferences for SqlQuery {
 
 #[cfg(test)]
 mod tests {
-    use nom_sql::{Expr, GroupByClause, OrderClause, OrderType};
+    use nom_sql::{BinaryOperator, Expr, GroupByClause, Literal, OrderClause, OrderType};
 
     use super::*;
     use crate::util::parse_select_statement;
@@ -80,7 +85,8 @@ mod tests {
         assert_eq!(
             result.group_by,
             Some(GroupByClause {
-                fields: vec![FieldReference::Expr(Expr::Column("id".into()))]
+                fields: vec![FieldReference::Expr(Expr::Column("id".into()))],
+                with_rollup: false,
             })
         )
     }
@@ -100,4 +106,25 @@ mod tests {
             })
         )
     }
+
+    #[test]
+    fn having_standalone_integer_is_not_a_field_reference() {
+        let query = parse_select_statement("select id, count(*) from t group by 1 having 2");
+        let result = query.remove_numeric_field_references().unwrap();
+        assert_eq!(result.having, Some(Expr::Literal(Literal::Integer(2))));
+    }
+
+    #[test]
+    fn having_integer_comparison_is_not_a_field_reference() {
+        let query = parse_select_statement("select id, count(*) from t group by 1 having 2 > 10");
+        let result = query.remove_numeric_field_references().unwrap();
+        assert_eq!(
+            result.having,
+            Some(Expr::BinaryOp {
+                lhs: Box::new(Expr::Literal(Literal::Integer(2))),
+                op: BinaryOperator::Greater,
+                rhs: Box::new(Expr::Literal(Literal::Integer(10))),
+            })
+        );
+    }
 }
@@ -0,0 +1,100 @@
+#![warn(clippy::panic)]
+//! Tool to report which unsupported features prevent a set of queries (eg from an application's
+//! query log) from being cached, by dry-running a migration for each query against a deployment
+//! which already has the tables the queries read from.
+
+use std::fs;
+use std::path::PathBuf;
+
+use clap::builder::NonEmptyStringValueParser;
+use clap::Parser;
+use readyset_client::consensus::AuthorityType;
+use readyset_client::recipe::changelist::{Change, ChangeList, CreateCache};
+use readyset_client::ReadySetHandle;
+use readyset_errors::{ConversionStats, ReadySetError};
+
+#[derive(Parser)]
+#[command(name = "analyze_queries")]
+struct AnalyzeQueries {
+    #[arg(short, long, env("AUTHORITY_ADDRESS"), default_value("127.0.0.1:8500"))]
+    authority_address: String,
+
+    #[arg(long, env("AUTHORITY"), default_value("consul"), value_parser = ["consul"])]
+    authority: AuthorityType,
+
+    #[arg(short, long, env("DEPLOYMENT"), value_parser = NonEmptyStringValueParser::new())]
+    deployment: String,
+
+    /// The SQL dialect the queries are written in
+    #[arg(long, value_enum, default_value = "mysql")]
+    dialect: nom_sql::Dialect,
+
+    /// Print the report as JSON, rather than as text
+    #[arg(long)]
+    json: bool,
+
+    /// Path to a file containing the queries to analyze, one per line
+    queries: PathBuf,
+}
+
+impl AnalyzeQueries {
+    pub async fn run(self) -> anyhow::Result<()> {
+        let queries = fs::read_to_string(&self.queries)?;
+
+        let authority = self
+            .authority
+            .to_authority(&self.authority_address, &self.deployment);
+        let mut handle: ReadySetHandle = ReadySetHandle::new(authority).await;
+        handle.ready().await.unwrap();
+
+        let mut stats = ConversionStats::new();
+        for query in queries
+            .lines()
+            .map(|q| q.trim().trim_end_matches(';'))
+            .filter(|q| !q.is_empty())
+        {
+            let res = match nom_sql::parse_select_statement(self.dialect, query) {
+                Ok(statement) => {
+                    let changes = ChangeList::from_change(
+                        Change::CreateCache(CreateCache {
+                            name: None,
+                            statement: Box::new(statement),
+                            always: false,
+                        }),
+                        self.dialect.into(),
+                    );
+                    handle.dry_run(changes).await.map(|_| ())
+                }
+                Err(_) => Err(ReadySetError::UnparseableQuery {
+                    query: query.to_owned(),
+                }),
+            };
+            stats.record(query, &res);
+        }
+
+        let report = stats.report();
+        if self.json {
+            println!("{}", serde_json::to_string_pretty(&report)?);
+            return Ok(());
+        }
+
+        println!(
+            "{} queries: {} supported, {} failed with an unclassified error",
+            stats.total(),
+            stats.succeeded,
+            stats.other_errors
+        );
+        for count in report {
+            println!("{:>6}  {}", count.count, count.feature);
+            println!("        e.g. {}", count.example_query);
+        }
+
+        Ok(())
+    }
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let analyze_queries = AnalyzeQueries::parse();
+    analyze_queries.run().await
+}
@@ -3,6 +3,8 @@
 use std::collections::hash_map::DefaultHasher;
 use std::hash::{Hash, Hasher};
 
+use siphasher::sip::SipHasher24;
+
 /// Calculate the hash of `x` using the [`DefaultHasher`]
 ///
 /// ```rust
@@ -16,3 +18,23 @@ pub fn hash<T: Hash>(x: &T) -> u64 {
     x.hash(&mut hasher);
     hasher.finish()
 }
+
+/// Calculate a hash of `x` which is stable across runs and builds, for identifiers which are
+/// persisted or compared between runs (such as file names, or metric labels).
+///
+/// Unlike [`hash`], which uses the [`DefaultHasher`] whose algorithm is unspecified and may change
+/// between Rust releases, this always uses SipHash-2-4 with both keys set to zero, as implemented
+/// by the `siphasher` crate. Note that the result still depends on the [`Hash`] implementation of
+/// `T`, so it only stays stable for as long as that does.
+///
+/// ```rust
+/// assert_eq!(
+///     readyset_util::hash::stable_hash("abc"),
+///     10181197101972437984
+/// );
+/// ```
+pub fn stable_hash<T: Hash + ?Sized>(x: &T) -> u64 {
+    let mut hasher = SipHasher24::new_with_keys(0, 0);
+    x.hash(&mut hasher);
+    hasher.finish()
+}